@@ -7,12 +7,29 @@
 //!     It can be used to construct an [ActorRef](struct.ActorRef.html) with help
 //!     from the [Environment](../api/struct.Environment.html).
 
-use crate::api::{ActlibError, Environment};
+use crate::api::{ActlibError, Environment, Request, SpawnOptions};
+use crate::environment::{
+    current_actor, record_local_send, DeathWatches, MessageLimits, PendingReplies, ReplySlot,
+};
 use crate::message::*;
+use crate::scheduler;
+use crate::timer::{Timer, TimerHandle};
+use crate::wire::WireFormat;
+use log::warn;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cmp::Ordering;
 use std::fmt::Debug;
-use std::net::IpAddr;
-use std::sync::mpsc::Sender;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Weak};
+use std::task::{self, Poll};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 /// Trait that enables types to become [Actors](trait.Actor.html) used in the *actlib* library.
 ///
@@ -20,31 +37,336 @@ use uuid::Uuid;
 ///
 /// Actors are created with the [spawn](../api/struct.Environment.html#method.spawn)-method from the then-associated [Environment](../api/struct.Environment.html).
 pub trait Actor: Debug + Send + MessageHandler {
+    /// Called with the arguments of [Environment::spawn_with_args](../api/struct.Environment.html#method.spawn_with_args)
+    /// after the Actor was built and before [on_start](#method.on_start), on the machine the Actor is spawned on.
+    ///
+    /// *args* are serialized with bincode, whatever the [WireFormat](../wire/enum.WireFormat.html) of the Environment.
+    /// If this function panics, e.g. because the arguments do not fit the Actor, the spawn fails.
+    /// It is not called for Actors spawned without arguments.
+    ///
+    /// **Note:** It is expected that this function terminates.
+    fn on_init(&mut self, _args: &[u8]) {}
+
     /// Called after a new instance has been created.
     ///
-    /// [on_start](#method.on_start) will be called inside [spawn](../api/struct.Environment.html#method.spawn) after the [actor](trait.Actor.html) has been successfully created and it's mailbox is initialized, but before the [ActorRef](struct.ActorRef.html) is returned to the caller of [spawn](../api/struct.Environment.html#method.spawn).
+    /// [on_start](#method.on_start) will be called on the Actor's own thread after the [actor](trait.Actor.html) has been successfully created and it's mailbox is initialized.
+    ///
+    /// It is guaranteed to complete before any message is handled, messages that arrive earlier wait in the mailbox,
+    /// or are dropped if the Environment was created with [PreStartMessages::Discard](../options/enum.PreStartMessages.html#variant.Discard).
     ///
     /// **Note:** It is expected that this function terminates.
     fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
 
     /// Called when this Actor stops being active.
     ///
+    /// Superseded by [on_stop_with_reason](#method.on_stop_with_reason), which is told why the Actor stops.
+    /// Existing implementations keep working, the default implementation of on_stop_with_reason calls this method.
+    ///
     /// **Note:** It is expected that this function terminates.
+    #[deprecated(note = "implement on_stop_with_reason(&mut self, reason: StopReason) instead")]
     fn on_stop(&mut self) {}
 
+    /// Called when this Actor stops being active.
+    ///
+    /// The [StopReason](enum.StopReason.html) tells why the Actor stops, e.g. to decide whether to persist state or to send final updates.
+    ///
+    /// The default implementation calls the deprecated [on_stop](#method.on_stop).
+    ///
+    /// **Note:** It is expected that this function terminates.
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        #[allow(deprecated)]
+        self.on_stop();
+    }
+
+    /// Called instead of [on_stop_with_reason](#method.on_stop_with_reason) if the [StopPolicy](../options/enum.StopPolicy.html) of the Actor's type is ```HandOver```.
+    ///
+    /// *pending* holds the Messages the Actor had stashed or not handled yet, oldest first; downcast them to the Message types the Actor handles.
+    /// Pending [queries](struct.ActorRef.html#method.query) are not included, their callers get disconnected.
+    ///
+    /// The default implementation drops them and calls [on_stop_with_reason](#method.on_stop_with_reason).
+    ///
+    /// **Note:** It is expected that this function terminates.
+    fn on_stop_with_pending(&mut self, reason: StopReason, _pending: Vec<Box<dyn Any + Send>>) {
+        self.on_stop_with_reason(reason);
+    }
+
+    /// The projection of this Actor's state gathered by [Environment::collect_states](../api/struct.Environment.html#method.collect_states).
+    ///
+    /// Called on the Actor's own thread between two handlers, like a [query](struct.ActorRef.html#method.query).
+    /// The default exports nothing, which leaves the Actor out of every collection.
+    fn export_state(&self) -> Option<ExportedState> {
+        None
+    }
+
+    /// Take over the state this Actor's type [exported](#method.export_state) into a [Snapshot](../api/struct.Snapshot.html),
+    /// called by [Environment::restore](../api/struct.Environment.html#method.restore) after the Actor was built and before [on_start](#method.on_start).
+    ///
+    /// The default ignores the state.
+    fn import_state(&mut self, _state: ExportedState) {}
+
     /// Implement this function to define how this actor is to be reset.
     /// This function can either be called manually inside a message handler or is called every time this actor receives the special ```Reset``` message by calling [on_reset](../api/struct.Environment.html#method.on_reset).
     /// **Note** the occurrence of this token in the program flow is left entirely to the implementation that uses `actlib` and as such is entirely optional.
     fn on_reset(&mut self) {}
 }
 
+/// A serialized projection of an Actor's state, see [Actor::export_state](trait.Actor.html#method.export_state).
+#[derive(Debug, Clone)]
+pub struct ExportedState(pub(crate) Vec<u8>);
+
+impl ExportedState {
+    /// Serialize *state*, the collecting machine deserializes it to the type it asks [collect_states](../api/struct.Environment.html#method.collect_states) for.
+    pub fn new<S: Serialize>(state: &S) -> Result<Self, ActlibError> {
+        bincode::serialize(state)
+            .map(ExportedState)
+            .map_err(|e| ActlibError::InvalidState(format!("{:?}", e)))
+    }
+
+    /// Deserialize the state, e.g. in [import_state](trait.Actor.html#method.import_state).
+    pub fn deserialize<S: DeserializeOwned>(&self) -> Result<S, ActlibError> {
+        bincode::deserialize(&self.0).map_err(|e| ActlibError::InvalidState(format!("{:?}", e)))
+    }
+}
+
+/// Trait that allows other threads to read an Actor's state via [ActorRef::query](struct.ActorRef.html#method.query), without sending a message.
+///
+/// This is the sanctioned alternative to sharing state behind an ```Arc<Mutex<_>>```:
+/// the query runs on the Actor's own thread between two handlers, so it always sees a consistent state.
+///
+/// The Actor's [MessageHandler](../message/trait.MessageHandler.html) has to be implemented by the [impl_message_handler!](../macro.impl_message_handler.html)-macro.
+pub trait QueryableActor: Actor + Sized + 'static {
+    /// Run *f* on this Actor's state.
+    ///
+    /// Called on the Actor's own thread. Override it to e.g. log or restrict queries.
+    ///
+    /// **Note:** It is expected that *f* terminates quickly, the Actor handles no messages meanwhile.
+    fn query<R, F: FnOnce(&Self) -> R>(&self, f: F) -> R {
+        f(self)
+    }
+}
+
+/// A preemption point for long-running handlers.
+///
+/// If the Environment was created with a [TimeBudget](../api/struct.TimeBudget.html) and the current handler used up its time slice,
+/// the Actor thread gives way to other Actors according to the budget's penalty, and a new slice starts.
+/// Returns ```true``` if the handler had to give way.
+///
+/// Outside of a handler, or without a TimeBudget, this is a cheap no-op.
+///
+/// ```rust,ignore
+/// fn simulate(&mut self, tick: &Tick) {
+///     for cell in self.cells.iter_mut() {
+///         cell.update(tick);
+///         checkpoint();
+///     }
+/// }
+/// ```
+pub fn checkpoint() -> bool {
+    crate::environment::check_time_slice()
+}
+
+/// Access to the Actor whose code runs on the current thread, so the Actor does not need to keep its Environment and ActorRef.
+///
+/// Available in [on_start](trait.Actor.html#method.on_start), every handler, [on_reset](trait.Actor.html#method.on_reset),
+/// [on_stop_with_reason](trait.Actor.html#method.on_stop_with_reason) and [queries](struct.ActorRef.html#method.query).
+/// Elsewhere, e.g. on the main thread or a thread spawned by a handler, the accessors panic and the ```try_``` variants return ```None```.
+///
+/// ```rust,ignore
+/// fn join(field: &mut Field, player: &Join) {
+///     let neighbour = Context::env().to_actor_ref(field.neighbour.clone()).unwrap();
+///     neighbour.send_message(Moved(player.id, Context::self_id())).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Context;
+
+impl Context {
+    /// The Environment of the current Actor.
+    ///
+    /// Panics outside of an Actor.
+    pub fn env() -> Environment {
+        match Context::try_env() {
+            Some(env) => env,
+            None => panic!("Context::env called outside of an Actor"),
+        }
+    }
+
+    /// The id of the current Actor.
+    ///
+    /// Panics outside of an Actor.
+    pub fn self_id() -> ActorId {
+        match Context::try_self_id() {
+            Some(actor_id) => actor_id,
+            None => panic!("Context::self_id called outside of an Actor"),
+        }
+    }
+
+    /// An ActorRef to the current Actor, e.g. to send a message to itself.
+    ///
+    /// Panics outside of an Actor.
+    pub fn self_ref() -> ActorRef {
+        match Context::try_self_ref() {
+            Some(actor_ref) => actor_ref,
+            None => panic!("Context::self_ref called outside of an Actor"),
+        }
+    }
+
+    /// The Environment of the current Actor, ```None``` outside of an Actor.
+    pub fn try_env() -> Option<Environment> {
+        crate::environment::current_env()
+    }
+
+    /// The id of the current Actor, ```None``` outside of an Actor.
+    pub fn try_self_id() -> Option<ActorId> {
+        current_actor()
+    }
+
+    /// An ActorRef to the current Actor, ```None``` outside of an Actor.
+    pub fn try_self_ref() -> Option<ActorRef> {
+        match (Context::try_env(), Context::try_self_id()) {
+            (Some(env), Some(actor_id)) => env.to_actor_ref(actor_id).ok(),
+            _ => None,
+        }
+    }
+
+    /// The id of the Actor that sent the message the current handler is handling.
+    ///
+    /// ```None``` outside of a message handler, and for messages sent from outside of an Actor, e.g. the main thread,
+    /// [broadcasts](../api/struct.Environment.html#method.broadcast) and timers.
+    /// Messages from remote Actors carry their sender along.
+    pub fn sender_id() -> Option<ActorId> {
+        crate::environment::current_sender()
+    }
+
+    /// An ActorRef to the Actor that sent the message the current handler is handling, e.g. to answer it.
+    ///
+    /// ```None``` whenever [sender_id](#method.sender_id) is.
+    ///
+    /// ```rust,ignore
+    /// fn ping(_: &mut Pinger, Ping(n): &Ping) {
+    ///     if let Some(sender) = Context::sender() {
+    ///         sender.send_message(Pong(*n)).unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn sender() -> Option<ActorRef> {
+        match (Context::try_env(), Context::sender_id()) {
+            (Some(env), Some(actor_id)) => env.to_actor_ref(actor_id).ok(),
+            _ => None,
+        }
+    }
+
+    /// Put the message the current handler is handling aside, until the Actor calls [unstash_all](#method.unstash_all).
+    ///
+    /// E.g. an Actor waiting for its initial state stashes every other message meanwhile.
+    /// Only messages handled by a handler of the [impl_message_handler!](../macro.impl_message_handler.html)-macro can be stashed,
+    /// calls outside of a handler are ignored. Stashed messages are discarded once the Actor stops.
+    ///
+    /// ```rust,ignore
+    /// fn player_enters(field: &mut Field, enters: &PlayerEnters) {
+    ///     if field.state.is_none() {
+    ///         return Context::stash();
+    ///     }
+    ///     ...
+    /// }
+    ///
+    /// fn restore(field: &mut Field, state: &FieldState) {
+    ///     field.state = Some(state.clone());
+    ///     Context::unstash_all();
+    /// }
+    /// ```
+    pub fn stash() {
+        crate::environment::request_stash(false)
+    }
+
+    /// Hand all stashed messages back to the Actor once the current handler returned, in the order they arrived.
+    ///
+    /// They are handled before every message still waiting in the mailbox.
+    pub fn unstash_all() {
+        crate::environment::request_stash(true)
+    }
+
+    /// Spawn an Actor like [spawn_with_options](../api/struct.Environment.html#method.spawn_with_options),
+    /// but hold its [on_start](trait.Actor.html#method.on_start) until the current handler returned.
+    ///
+    /// The returned ActorRef is usable right away, messages sent to it meanwhile wait in the new Actor's mailbox.
+    /// So the handler can finish setting up the state the child relies on, without racing its on_start.
+    /// The child starts even if the handler panicked.
+    ///
+    /// Fails with [InvalidState](../api/enum.ActlibError.html#variant.InvalidState) outside of an Actor,
+    /// and like [spawn](../api/struct.Environment.html#method.spawn) otherwise.
+    ///
+    /// ```rust,ignore
+    /// fn player_leaves(field: &mut Field, leaves: &PlayerLeaves) {
+    ///     let neighbour = Context::spawn_after_handle("Field", SpawnOptions::new()).unwrap();
+    ///     neighbour.send_message(PlayerEnters(leaves.player)).unwrap();
+    ///     field.neighbours.insert(leaves.direction, neighbour.clone_id());
+    /// }
+    /// ```
+    pub fn spawn_after_handle(
+        actor_type_id: &str,
+        options: SpawnOptions,
+    ) -> Result<ActorRef, ActlibError> {
+        crate::environment::spawn_after_handle(actor_type_id, options)
+    }
+}
+
+/// Why an [Actor](trait.Actor.html) stops, passed to [on_stop_with_reason](trait.Actor.html#method.on_stop_with_reason).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    /// The Actor was explicitly [removed](../api/struct.Environment.html#method.remove).
+    Removed,
+    /// The Environment [expired](../api/struct.Environment.html#method.set_expired).
+    Expired,
+    /// A supervisor decided to stop the Actor.
+    SupervisorDecision,
+    /// The Actor is replaced by an Actor on another machine or with another id.
+    Migration,
+    /// Another machine holds an Actor with the same specified id, see [ConflictPolicy](../api/enum.ConflictPolicy.html).
+    IdConflict,
+    /// The Actor panicked, see [FailurePolicy](../api/enum.FailurePolicy.html).
+    Failed,
+}
+
 /// Unique [Actor](trait.Actor.html) identifier.
 ///
 /// Constructed out of a locally unique ID and a machine-unique ID.
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Serialize, Deserialize, Hash)]
+///
+/// A user specified id also carries the [incarnation](#method.incarnation) of its Actor, which is ignored when comparing ActorIds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActorId {
     pub(crate) local_id: LocalId,
-    pub(crate) location: IpAddr,
+    pub(crate) location: MachineId,
+    /// Tells apart the Actors spawned with the same user specified id, 0 if unknown
+    #[serde(default)]
+    pub(crate) incarnation: u64,
+}
+
+impl PartialEq for ActorId {
+    fn eq(&self, other: &Self) -> bool {
+        self.local_id == other.local_id && self.location == other.location
+    }
+}
+
+impl Eq for ActorId {}
+
+impl Hash for ActorId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.local_id.hash(state);
+        self.location.hash(state);
+    }
+}
+
+impl PartialOrd for ActorId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ActorId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.local_id, &self.location).cmp(&(&other.local_id, &other.location))
+    }
 }
 
 impl ToString for ActorId {
@@ -62,6 +384,63 @@ impl ActorId {
             _ => None,
         }
     }
+
+    /// Return the machine the Actor lives on.
+    pub fn location(&self) -> MachineId {
+        self.location
+    }
+
+    /// Return the incarnation of an Actor with a user specified id, which changes whenever the id is spawned again.
+    ///
+    /// ```0``` for automatically created ids and ids whose incarnation is unknown, e.g. rebuilt from a key.
+    /// See [IncarnationPolicy](../api/enum.IncarnationPolicy.html) for Messages addressed to an earlier incarnation.
+    pub fn incarnation(&self) -> u64 {
+        self.incarnation
+    }
+}
+
+/// Stable identifier of a machine, independent of the address it is currently reachable at.
+///
+/// Machines exchange their ids when connecting, so a machine keeps its identity across DHCP leases and address families.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy, Serialize, Deserialize, Hash)]
+pub struct MachineId(Uuid);
+
+impl MachineId {
+    /// Create a new random MachineId.
+    pub fn random() -> Self {
+        MachineId(Uuid::new_v4())
+    }
+
+    /// Read the MachineId stored in the file at *path*, creating the file with a new random id if it doesn't exist.
+    ///
+    /// Keeps the identity of a machine across restarts, see [EnvironmentOptions::machine_id](../api/struct.EnvironmentOptions.html#method.machine_id).
+    pub fn load_or_create<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(contents) => match Uuid::parse_str(contents.trim()) {
+                Ok(uuid) => Ok(MachineId(uuid)),
+                Err(e) => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid machine id in {:?}: {:?}", path.as_ref(), e),
+                )),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let machine_id = MachineId::random();
+                std::fs::write(path, machine_id.to_string())?;
+                Ok(machine_id)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl std::fmt::Display for MachineId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.to_hyphenated())
+    }
 }
 
 /// The local_id can either be automatically created, or User specified.
@@ -85,6 +464,16 @@ impl ToString for LocalId {
 pub struct ActorRef {
     pub(crate) actor_id: ActorId,
     pub(crate) sender: ActorRefChannel,
+    /// Runs the delayed sends, gone with the Environment
+    pub(crate) timer: Weak<Timer>,
+    /// Receives the replies to asks, gone with the Environment
+    pub(crate) replies: Weak<PendingReplies>,
+    /// The watchers of the local Actors, gone with the Environment
+    pub(crate) death_watches: Weak<DeathWatches>,
+    /// The message sizes the remote machines accept, gone with the Environment
+    pub(crate) message_limits: Weak<MessageLimits>,
+    /// The format Messages to remote Actors are encoded in
+    pub(crate) wire_format: WireFormat,
 }
 
 /// Possible Channel-Types for an [ActorRef](struct.ActorRef.html).
@@ -94,15 +483,39 @@ pub struct ActorRef {
 #[derive(Debug, Clone)]
 pub(crate) enum ActorRefChannel {
     /// A channel to an actor on the same machine.
-    Local(Sender<EitherMessage>),
+    Local(MailboxSender),
     /// A channel to the local environment, which will relay it to an actor on a remote machine.
     Remote(Sender<(ActorId, SerNetMessageContent)>),
 }
 
 impl ActorRef {
     /// Create a new [ActorRef](struct.ActorRef.html) if you know the Sender-End from the associated channel.
-    pub(crate) fn new(actor_id: ActorId, sender: ActorRefChannel) -> ActorRef {
-        ActorRef { actor_id, sender }
+    pub(crate) fn new(
+        actor_id: ActorId,
+        sender: ActorRefChannel,
+        timer: Weak<Timer>,
+        replies: Weak<PendingReplies>,
+        death_watches: Weak<DeathWatches>,
+        message_limits: Weak<MessageLimits>,
+        wire_format: WireFormat,
+    ) -> ActorRef {
+        ActorRef {
+            actor_id,
+            sender,
+            timer,
+            replies,
+            death_watches,
+            message_limits,
+            wire_format,
+        }
+    }
+
+    /// Fail right away if the machine of the remote Actor does not accept a message of *size* bytes.
+    fn check_size(&self, size: usize) -> Result<(), ActlibError> {
+        match self.message_limits.upgrade() {
+            Some(limits) => limits.check(&self.actor_id.location, size),
+            None => Ok(()),
+        }
     }
 
     /// Tries to send a special reset message to the actor behind this [ActorRef](struct.ActorRef.html).
@@ -113,20 +526,12 @@ impl ActorRef {
     /// The receiving actor will call its [on_reset](trait.Actor.html#method.on_reset) implementation.
     pub fn send_reset_message(&self) -> Result<(), ActlibError> {
         match &self.sender {
-            ActorRefChannel::Local(s) => {
-                if let Err(e) = s.send(EitherMessage::Special(Token::Reset)) {
-                    Err(ActlibError::InvalidActorRef(
-                        "This ActorRef is no longer connected to an Actor".to_string(),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
+            ActorRefChannel::Local(s) => s.send(EitherMessage::Special(Token::Reset), 0),
             ActorRefChannel::Remote(s) => {
-                if let Ok(token_serialized) = bincode::serialize(&Token::Reset) {
+                if let Ok(token_serialized) = self.wire_format.serialize(&Token::Reset) {
                     match s.send((
                         self.clone_id(),
-                        SerNetMessageContent::Token(token_serialized),
+                        SerNetMessageContent::Token(token_serialized, current_actor()),
                     )) {
                         Ok(_) => Ok(()),
                         Err(e) => Err(ActlibError::NetworkError(format!(
@@ -147,23 +552,72 @@ impl ActorRef {
     ///
     /// The message is sent unblocking. There is no guarantee that the Actor handles the Message (it may be already [removed](../api/struct.Environment.html#method.remove)).
     ///
-    /// The method can fail with [InvalidActorRef](../api/enum.ActlibError.html#variant.InvalidActorRef) and [NetworkError](../api/enum.ActlibError.html#variant.NetworkError).
+    /// The method can fail with [InvalidActorRef](../api/enum.ActlibError.html#variant.InvalidActorRef), [MailboxFull](../api/enum.ActlibError.html#variant.MailboxFull) and [NetworkError](../api/enum.ActlibError.html#variant.NetworkError).
+    ///
+    /// For a local Actor, the result tells what happened to the message:
+    ///
+    /// - ```Ok(())```: the message was enqueued.
+    /// - ```MailboxFull```: the Actor is alive, but its [quota](../api/struct.MailboxQuota.html) rejects the message, try again later.
+    /// - ```ActorStopping```: the Actor was asked to stop and discards the message, it is about to be gone.
+    /// - ```InvalidActorRef```: the Actor is gone.
+    ///
+    /// Messages to remote Actors only fail if the connection is gone, their delivery is not confirmed
+    /// unless [reliable delivery](../api/struct.EnvironmentOptions.html#method.reliable_delivery) is enabled.
     pub fn send_message<'de, M: Message<'de> + 'static>(
         &self,
         message: M,
     ) -> Result<(), ActlibError> {
         match &self.sender {
-            ActorRefChannel::Local(s) => match s.send(EitherMessage::Regular(Box::new(message))) {
-                Ok(_) => Ok(()),
-                Err(_e) => Err(ActlibError::InvalidActorRef(
-                    "This ActorRef is no longer connected to an Actor".to_string(),
-                )),
-            },
+            ActorRefChannel::Local(s) => {
+                let (message, size) = s.wrap(message, self.wire_format)?;
+                s.send_from(message, size, current_actor())?;
+                record_local_send(&self.actor_id);
+                Ok(())
+            }
+            ActorRefChannel::Remote(s) => {
+                if let Ok(message_serialized) = SerializedMessage::of(&message, self.wire_format) {
+                    self.check_size(message_serialized.bytes.len())?;
+                    match s.send((
+                        self.clone_id(),
+                        SerNetMessageContent::Message(message_serialized, current_actor()),
+                    )) {
+                        Ok(_) => Ok(()),
+                        Err(e) => Err(ActlibError::InvalidActorRef(format!(
+                            "Can no longer send Messages to remote Actors: {:?}",
+                            e
+                        ))),
+                    }
+                } else {
+                    Err(ActlibError::NetworkError(
+                        "Unable to serialize message".to_string(),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Like [send_message](#method.send_message), but the message replaces a queued message with the same [key](../message/trait.Keyed.html).
+    ///
+    /// Messages are only replaced if the receiving Actor's type has a [coalescing mailbox](../api/struct.EnvironmentOptions.html#method.coalescing_mailbox),
+    /// the replacement keeps the place of the queued message. Otherwise this is the same as [send_message](#method.send_message).
+    pub fn send_keyed<'de, M: Message<'de> + Keyed + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), ActlibError> {
+        let key = coalescing_key(&message);
+        match &self.sender {
+            ActorRefChannel::Local(s) => {
+                let (message, size) = s.wrap(message, self.wire_format)?;
+                s.send_keyed(message, size, key, current_actor())?;
+                record_local_send(&self.actor_id);
+                Ok(())
+            }
             ActorRefChannel::Remote(s) => {
-                if let Ok(message_serialized) = bincode::serialize(&message) {
+                if let Ok(message_serialized) = SerializedMessage::of(&message, self.wire_format) {
+                    self.check_size(message_serialized.bytes.len())?;
                     match s.send((
                         self.clone_id(),
-                        SerNetMessageContent::Message(message_serialized),
+                        SerNetMessageContent::Keyed(key, message_serialized, current_actor()),
                     )) {
                         Ok(_) => Ok(()),
                         Err(e) => Err(ActlibError::InvalidActorRef(format!(
@@ -180,20 +634,287 @@ impl ActorRef {
         }
     }
 
+    /// Like [send_message](#method.send_message), but the message is already serialized with ```bincode```.
+    ///
+    /// The receiving Actor deserializes it like a message from a remote machine, messages it doesn't understand are ignored.
+    pub fn send_serialized(&self, message: Vec<u8>) -> Result<(), ActlibError> {
+        self.send_serialized_from(SerializedMessage::untagged(message), current_actor())
+    }
+
+    /// Like [send_serialized](#method.send_serialized), on behalf of the given *sender*.
+    pub(crate) fn send_serialized_from(
+        &self,
+        message: SerializedMessage,
+        sender: Option<ActorId>,
+    ) -> Result<(), ActlibError> {
+        match &self.sender {
+            ActorRefChannel::Local(s) => {
+                let size = message.bytes.len();
+                s.send_from(EitherMessage::Serialized(message), size, sender)?;
+                record_local_send(&self.actor_id);
+                Ok(())
+            }
+            ActorRefChannel::Remote(s) => {
+                self.check_size(message.bytes.len())?;
+                match s.send((
+                    self.clone_id(),
+                    SerNetMessageContent::Message(message, sender),
+                )) {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(ActlibError::InvalidActorRef(format!(
+                        "Can no longer send Messages to remote Actors: {:?}",
+                        e
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Like [send_serialized_from](#method.send_serialized_from), but the message keeps the coalescing *key* it was sent with.
+    pub(crate) fn send_serialized_keyed_from(
+        &self,
+        message: SerializedMessage,
+        key: u64,
+        sender: Option<ActorId>,
+    ) -> Result<(), ActlibError> {
+        match &self.sender {
+            ActorRefChannel::Local(s) => {
+                let size = message.bytes.len();
+                s.send_keyed(EitherMessage::Serialized(message), size, key, sender)?;
+                record_local_send(&self.actor_id);
+                Ok(())
+            }
+            ActorRefChannel::Remote(s) => {
+                self.check_size(message.bytes.len())?;
+                match s.send((
+                    self.clone_id(),
+                    SerNetMessageContent::Keyed(key, message, sender),
+                )) {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(ActlibError::InvalidActorRef(format!(
+                        "Can no longer send Messages to remote Actors: {:?}",
+                        e
+                    ))),
+                }
+            }
+        }
+    }
+
+    /// Read the state of the local Actor behind this [ActorRef](struct.ActorRef.html) and return the result to the calling thread.
+    ///
+    /// The query is answered before any message waiting in the Actor's mailbox, using [QueryableActor::query](trait.QueryableActor.html#method.query).
+    /// The calling thread blocks until the answer arrives or the *timeout* passes.
+    ///
+    /// The method can fail with [Timeout](../api/enum.ActlibError.html#variant.Timeout),
+    /// [InvalidActorRef](../api/enum.ActlibError.html#variant.InvalidActorRef) if the Actor is remote, not of type *A* or gone,
+    /// and [InvalidState](../api/enum.ActlibError.html#variant.InvalidState) if an Actor queries itself.
+    pub fn query<A, R, F>(&self, f: F, timeout: Duration) -> Result<R, ActlibError>
+    where
+        A: QueryableActor,
+        R: Send + 'static,
+        F: FnOnce(&A) -> R + Send + 'static,
+    {
+        let sender = match &self.sender {
+            ActorRefChannel::Local(s) => s,
+            ActorRefChannel::Remote(_) => {
+                return Err(ActlibError::InvalidActorRef(
+                    "Only local Actors can be queried".to_string(),
+                ))
+            }
+        };
+        // the Actor would wait for its own answer
+        if current_actor().as_ref() == Some(&self.actor_id) {
+            return Err(ActlibError::InvalidState(
+                "An Actor cannot query itself".to_string(),
+            ));
+        }
+        let (result_sender, result_receiver) = channel();
+        let query = Query(Box::new(move |actor: &dyn Actor| {
+            if let Some(actor) = actor.as_any().and_then(|actor| actor.downcast_ref::<A>()) {
+                // the caller may have given up already
+                let _ = result_sender.send(actor.query(f));
+            }
+        }));
+        sender.send(EitherMessage::Query(query), 0)?;
+        match scheduler::blocking(|| result_receiver.recv_timeout(timeout)) {
+            Ok(result) => Ok(result),
+            Err(RecvTimeoutError::Timeout) => Err(ActlibError::Timeout(format!(
+                "Actor {:?} did not answer the query within {:?}",
+                self.actor_id, timeout
+            ))),
+            Err(RecvTimeoutError::Disconnected) => Err(ActlibError::InvalidActorRef(format!(
+                "Actor {:?} stopped or is not of type {}",
+                self.actor_id,
+                std::any::type_name::<A>()
+            ))),
+        }
+    }
+
+    /// Send the message wrapped in a [Request](../api/struct.Request.html) and return a [ResponseFuture](struct.ResponseFuture.html) for the typed reply.
+    ///
+    /// The receiving Actor handles ```Request<M>``` and answers with [Environment::reply](../api/struct.Environment.html#method.reply),
+    /// the reply finds its way back to this machine, whether the Actor is local or remote.
+    ///
+    /// Fails like [send_message](#method.send_message), or with [InvalidActorRef](../api/enum.ActlibError.html#variant.InvalidActorRef) if the Environment is gone.
+    ///
+    /// **Note:** An Actor waiting for the reply to an ask sent to itself waits forever.
+    pub fn ask<'de, M, R>(&self, message: M) -> Result<ResponseFuture<R>, ActlibError>
+    where
+        M: Message<'de> + 'static,
+        R: DeserializeOwned,
+    {
+        self.send_request(message, |actor_ref, request| {
+            actor_ref.send_message(request)
+        })
+    }
+
+    /// Like [ask](#method.ask), but the Request is sent with [send_keyed](#method.send_keyed), keyed like the message,
+    /// e.g. so every ask with the same key reaches the same worker of a [consistent-hash pool](../api/enum.RouterKind.html#variant.ConsistentHash).
+    pub fn ask_keyed<'de, M, R>(&self, message: M) -> Result<ResponseFuture<R>, ActlibError>
+    where
+        M: Message<'de> + Keyed + 'static,
+        R: DeserializeOwned,
+    {
+        self.send_request(message, |actor_ref, request| actor_ref.send_keyed(request))
+    }
+
+    /// Wrap the message in a Request and *send* it, returning the ResponseFuture for the reply.
+    fn send_request<M, R, F>(&self, message: M, send: F) -> Result<ResponseFuture<R>, ActlibError>
+    where
+        F: FnOnce(&Self, Request<M>) -> Result<(), ActlibError>,
+    {
+        let replies = match self.replies.upgrade() {
+            Some(replies) => replies,
+            None => {
+                return Err(ActlibError::InvalidActorRef(format!(
+                    "The Environment of {:?} is gone",
+                    self.actor_id
+                )))
+            }
+        };
+        let (correlation_id, slot) = replies.register()?;
+        let request = Request {
+            message,
+            correlation_id,
+            reply_to: replies.machine_id,
+        };
+        if let Err(e) = send(self, request) {
+            replies.forget(correlation_id);
+            return Err(e);
+        }
+        Ok(ResponseFuture {
+            actor_id: self.actor_id.clone(),
+            correlation_id,
+            slot,
+            replies: self.replies.clone(),
+            wire_format: self.wire_format,
+            reply: PhantomData,
+        })
+    }
+
+    /// Let the Actor of *watcher* know once the Actor behind this ActorRef stopped, for whatever reason.
+    ///
+    /// The watcher receives a [Terminated](../api/struct.Terminated.html) Message, register it with the [impl_message_handler!](../macro.impl_message_handler.html) macro.
+    /// If the Actor is not alive anymore, or never was, the Terminated Message is sent right away.
+    /// Both Actors may live on any machine.
+    pub fn watch(&self, watcher: &ActorRef) -> Result<(), ActlibError> {
+        match &self.sender {
+            ActorRefChannel::Local(_) => match self.death_watches.upgrade() {
+                Some(death_watches) => {
+                    death_watches.watch(&self.actor_id, watcher.clone());
+                    Ok(())
+                }
+                None => Err(ActlibError::InvalidActorRef(format!(
+                    "The Environment of {:?} is gone",
+                    self.actor_id
+                ))),
+            },
+            ActorRefChannel::Remote(s) => {
+                match s.send((
+                    self.clone_id(),
+                    SerNetMessageContent::Watch(watcher.clone_id()),
+                )) {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(ActlibError::NetworkError(format!(
+                        "Failed to send watch request: {:?}",
+                        e
+                    ))),
+                }
+            }
+        }
+    }
+
     /// Send a Message after some time has passed.
     /// The current thread is not blocked.
+    ///
+    /// Delayed messages that are still pending when the Environment [expires](../api/struct.Environment.html#method.set_expired) are cancelled.
+    /// Cancel it earlier with the returned [TimerHandle](../api/struct.TimerHandle.html).
     pub fn send_delayed_message<'de, M: Message<'de> + 'static>(
         &self,
         message: M,
         delay: std::time::Duration,
-    ) {
+    ) -> TimerHandle {
+        let actor_ref_clone = self.clone();
+        match self.timer.upgrade() {
+            Some(timer) => timer.schedule_at(Instant::now() + delay, move || {
+                // there is no way to react to this error, except blocking the timer
+                // we don't want that
+                let _ = actor_ref_clone.send_message(message);
+            }),
+            None => {
+                warn!(
+                    "The Environment of {:?} is gone, delayed message dropped.",
+                    self.actor_id
+                );
+                TimerHandle::inactive()
+            }
+        }
+    }
+
+    /// Send a clone of the Message every *interval*, the first one after an *interval* has passed.
+    /// The current thread is not blocked.
+    ///
+    /// The messages stop once the returned [TimerHandle](../api/struct.TimerHandle.html) is cancelled, a message cannot be sent,
+    /// or the Environment [expires](../api/struct.Environment.html#method.set_expired).
+    pub fn send_periodic_message<'de, M: Message<'de> + Clone + 'static>(
+        &self,
+        message: M,
+        interval: std::time::Duration,
+    ) -> TimerHandle {
         let actor_ref_clone = self.clone();
-        std::thread::spawn(move || {
-            std::thread::sleep(delay);
-            // there is no way to react to this error, except blocking the calling thread
-            // we don't want that
-            let _ = actor_ref_clone.send_message(message);
-        });
+        match self.timer.upgrade() {
+            Some(timer) => {
+                timer.schedule_periodic(Instant::now() + interval, interval, move || {
+                    match actor_ref_clone.send_message(message.clone()) {
+                        Ok(()) => true,
+                        Err(e) => {
+                            warn!(
+                                "Stopped the periodic message to {:?}: {:?}",
+                                actor_ref_clone.actor_id, e
+                            );
+                            false
+                        }
+                    }
+                })
+            }
+            None => {
+                warn!(
+                    "The Environment of {:?} is gone, periodic message dropped.",
+                    self.actor_id
+                );
+                TimerHandle::inactive()
+            }
+        }
+    }
+
+    /// Treat the Actor behind this ActorRef as an *A*, so only the Messages *A* handles can be sent.
+    ///
+    /// Nothing is checked, prefer [Environment::spawn_typed](../api/struct.Environment.html#method.spawn_typed) where possible.
+    pub fn typed<A: Actor>(self) -> TypedActorRef<A> {
+        TypedActorRef {
+            actor_ref: self,
+            actor: PhantomData,
+        }
     }
 
     /// Clones only the associated [ActorId](struct.ActorId).
@@ -203,3 +924,182 @@ impl ActorRef {
         self.actor_id.clone()
     }
 }
+
+/// An [ActorRef](struct.ActorRef.html) that knows the type *A* of its Actor, so it only sends the Messages *A* [handles](../message/trait.Handles.html).
+///
+/// Sending any other Message is a compile error, instead of a Message silently ignored by the receiving Actor.
+/// Created by [Environment::spawn_typed](../api/struct.Environment.html#method.spawn_typed) or [ActorRef::typed](struct.ActorRef.html#method.typed).
+#[derive(Debug)]
+pub struct TypedActorRef<A> {
+    actor_ref: ActorRef,
+    actor: PhantomData<fn() -> A>,
+}
+
+impl<A> Clone for TypedActorRef<A> {
+    fn clone(&self) -> Self {
+        TypedActorRef {
+            actor_ref: self.actor_ref.clone(),
+            actor: PhantomData,
+        }
+    }
+}
+
+impl<A: Actor> TypedActorRef<A> {
+    /// Like [ActorRef::send_message](struct.ActorRef.html#method.send_message), for the Messages *A* handles.
+    pub fn send_message<'de, M: Message<'de> + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), ActlibError>
+    where
+        A: Handles<M>,
+    {
+        self.actor_ref.send_message(message)
+    }
+
+    /// Like [ActorRef::send_keyed](struct.ActorRef.html#method.send_keyed), for the Messages *A* handles.
+    pub fn send_keyed<'de, M: Message<'de> + Keyed + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), ActlibError>
+    where
+        A: Handles<M>,
+    {
+        self.actor_ref.send_keyed(message)
+    }
+
+    /// Like [ActorRef::send_delayed_message](struct.ActorRef.html#method.send_delayed_message), for the Messages *A* handles.
+    pub fn send_delayed_message<'de, M: Message<'de> + 'static>(
+        &self,
+        message: M,
+        delay: Duration,
+    ) -> TimerHandle
+    where
+        A: Handles<M>,
+    {
+        self.actor_ref.send_delayed_message(message, delay)
+    }
+
+    /// Like [ActorRef::send_periodic_message](struct.ActorRef.html#method.send_periodic_message), for the Messages *A* handles.
+    pub fn send_periodic_message<'de, M: Message<'de> + Clone + 'static>(
+        &self,
+        message: M,
+        interval: Duration,
+    ) -> TimerHandle
+    where
+        A: Handles<M>,
+    {
+        self.actor_ref.send_periodic_message(message, interval)
+    }
+
+    /// Like [ActorRef::ask](struct.ActorRef.html#method.ask), for the Requests *A* handles.
+    pub fn ask<'de, M, R>(&self, message: M) -> Result<ResponseFuture<R>, ActlibError>
+    where
+        M: Message<'de> + 'static,
+        R: DeserializeOwned,
+        A: Handles<Request<M>>,
+    {
+        self.actor_ref.ask(message)
+    }
+
+    /// Like [ActorRef::ask_keyed](struct.ActorRef.html#method.ask_keyed), for the Requests *A* handles.
+    pub fn ask_keyed<'de, M, R>(&self, message: M) -> Result<ResponseFuture<R>, ActlibError>
+    where
+        M: Message<'de> + Keyed + 'static,
+        R: DeserializeOwned,
+        A: Handles<Request<M>>,
+    {
+        self.actor_ref.ask_keyed(message)
+    }
+
+    /// Clones only the associated [ActorId](struct.ActorId).
+    pub fn clone_id(&self) -> ActorId {
+        self.actor_ref.clone_id()
+    }
+
+    /// Like [ActorRef::watch](struct.ActorRef.html#method.watch).
+    pub fn watch(&self, watcher: &ActorRef) -> Result<(), ActlibError> {
+        self.actor_ref.watch(watcher)
+    }
+
+    /// The untyped ActorRef, e.g. to [remove](../api/struct.Environment.html#method.remove) the Actor.
+    pub fn untyped(&self) -> &ActorRef {
+        &self.actor_ref
+    }
+}
+
+/// The reply to an [ask](struct.ActorRef.html#method.ask), deserialized to *R*.
+///
+/// Either [wait](#method.wait) for it on the current thread, or ```.await``` it, as it implements ```std::future::Future```.
+/// Dropping the ResponseFuture drops the reply once it arrives.
+#[derive(Debug)]
+pub struct ResponseFuture<R> {
+    actor_id: ActorId,
+    correlation_id: u64,
+    slot: Arc<ReplySlot>,
+    replies: Weak<PendingReplies>,
+    /// The format the reply is encoded in
+    wire_format: WireFormat,
+    reply: PhantomData<fn() -> R>,
+}
+
+impl<R: DeserializeOwned> ResponseFuture<R> {
+    /// Block the current thread until the reply arrives or the *timeout* passes.
+    ///
+    /// Fails with [Timeout](../api/enum.ActlibError.html#variant.Timeout) and [InvalidState](../api/enum.ActlibError.html#variant.InvalidState) if the reply is not an *R*.
+    pub fn wait(self, timeout: Duration) -> Result<R, ActlibError> {
+        let reply = match self.slot.state.lock() {
+            Ok(state) => match scheduler::blocking(|| {
+                self.slot
+                    .arrived
+                    .wait_timeout_while(state, timeout, |state| state.reply.is_none())
+            }) {
+                Ok((mut state, _)) => state.reply.take(),
+                Err(e) => return Err(ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => return Err(ActlibError::from_poison_error(&e)),
+        };
+        match reply {
+            Some(reply) => self.deserialize(&reply),
+            None => Err(ActlibError::Timeout(format!(
+                "Actor {:?} did not reply within {:?}",
+                self.actor_id, timeout
+            ))),
+        }
+    }
+
+    fn deserialize(&self, reply: &[u8]) -> Result<R, ActlibError> {
+        self.wire_format.deserialize(reply).map_err(|e| {
+            ActlibError::InvalidState(format!(
+                "The reply of {:?} is no {}: {:?}",
+                self.actor_id,
+                std::any::type_name::<R>(),
+                e
+            ))
+        })
+    }
+}
+
+impl<R: DeserializeOwned> Future for ResponseFuture<R> {
+    type Output = Result<R, ActlibError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        match self.slot.state.lock() {
+            Ok(mut state) => match state.reply.take() {
+                Some(reply) => Poll::Ready(self.deserialize(&reply)),
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            },
+            Err(e) => Poll::Ready(Err(ActlibError::from_poison_error(&e))),
+        }
+    }
+}
+
+impl<R> Drop for ResponseFuture<R> {
+    fn drop(&mut self) {
+        if let Some(replies) = self.replies.upgrade() {
+            replies.forget(self.correlation_id);
+        }
+    }
+}
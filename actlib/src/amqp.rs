@@ -0,0 +1,176 @@
+//! This module connects [Actors](../actor/trait.Actor.html) to an AMQP broker, e.g. RabbitMQ.
+//!
+//! Only available with the ```amqp``` feature.
+//!
+//! - An [AmqpIngress](struct.AmqpIngress.html) consumes a queue and forwards every decoded payload as a message to an Actor.
+//! - An [AmqpEgress](struct.AmqpEgress.html) publishes selected messages to an exchange, e.g. from within a message handler.
+//!
+//! ```rust,ignore
+//! AmqpIngress::new("amqp://broker.lab:5672/%2f", "sensor-events", decode_event).forward_to(field_ref);
+//! ```
+
+use crate::actor::ActorRef;
+use crate::api::ActlibError;
+use crate::message::Message;
+use futures::executor::block_on;
+use futures::StreamExt;
+use lapin::options::{BasicAckOptions, BasicConsumeOptions, BasicNackOptions, BasicPublishOptions};
+use lapin::types::FieldTable;
+use lapin::{BasicProperties, Channel, Connection, ConnectionProperties};
+use log::{error, info, warn};
+use std::thread;
+
+/// Consumes an AMQP queue and forwards every payload to an Actor, converted by a user-supplied decoder.
+#[derive(Debug, Clone)]
+pub struct AmqpIngress<M> {
+    uri: String,
+    queue: String,
+    decoder: fn(&[u8]) -> Option<M>,
+}
+
+impl<M: Message<'static> + 'static> AmqpIngress<M> {
+    /// Create an ingress for the given (already declared) queue.
+    ///
+    /// Payloads for which the *decoder* returns ```None``` are rejected without requeueing.
+    pub fn new(uri: &str, queue: &str, decoder: fn(&[u8]) -> Option<M>) -> Self {
+        AmqpIngress {
+            uri: uri.to_string(),
+            queue: queue.to_string(),
+            decoder,
+        }
+    }
+
+    /// Start forwarding the decoded payloads to the given Actor.
+    ///
+    /// The ingress runs on its own thread. A delivery is acknowledged once it was passed to the Actor's mailbox.
+    /// The ingress stops once the Actor is gone or the connection breaks.
+    pub fn forward_to(self, target: ActorRef) {
+        thread::spawn(move || {
+            if let Err(e) = block_on(self.consume(target)) {
+                error!("AMQP ingress for queue {} stopped: {:?}", self.queue, e);
+            }
+        });
+    }
+
+    async fn consume(&self, target: ActorRef) -> Result<(), ActlibError> {
+        let channel = open_channel(&self.uri).await?;
+        let mut consumer = channel
+            .basic_consume(
+                &self.queue,
+                "actlib",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await
+            .map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))?;
+        info!(
+            "Forwarding AMQP queue {} to {:?}",
+            self.queue,
+            target.clone_id()
+        );
+        while let Some(delivery) = consumer.next().await {
+            let delivery = delivery.map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))?;
+            let result = match (self.decoder)(&delivery.data) {
+                Some(message) => match target.send_message(message) {
+                    Ok(_) => delivery.acker.ack(BasicAckOptions::default()).await,
+                    Err(ActlibError::InvalidActorRef(_)) | Err(ActlibError::ActorStopping(_)) => {
+                        // nobody left to forward to, leave the message to other consumers
+                        let _ = delivery
+                            .acker
+                            .nack(BasicNackOptions {
+                                requeue: true,
+                                ..BasicNackOptions::default()
+                            })
+                            .await;
+                        return Ok(());
+                    }
+                    Err(e) => {
+                        warn!("Failed to forward AMQP delivery: {:?}", e);
+                        delivery
+                            .acker
+                            .nack(BasicNackOptions {
+                                requeue: true,
+                                ..BasicNackOptions::default()
+                            })
+                            .await
+                    }
+                },
+                None => {
+                    warn!(
+                        "Rejected undecodable payload from AMQP queue {}",
+                        self.queue
+                    );
+                    delivery.acker.nack(BasicNackOptions::default()).await
+                }
+            };
+            result.map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Publishes messages to an AMQP exchange, converted by a user-supplied encoder.
+///
+/// The egress can be cloned and moved into Actors, which then publish from their handlers.
+#[derive(Debug, Clone)]
+pub struct AmqpEgress<M> {
+    channel: Channel,
+    exchange: String,
+    routing_key: String,
+    encoder: fn(&M) -> Vec<u8>,
+}
+
+impl<M> AmqpEgress<M> {
+    /// Connect to the broker, publishing to the given exchange (```""``` is the default exchange) and routing key.
+    ///
+    /// Fails with [NetworkError](../api/enum.ActlibError.html#variant.NetworkError) if the broker is not reachable.
+    pub fn connect(
+        uri: &str,
+        exchange: &str,
+        routing_key: &str,
+        encoder: fn(&M) -> Vec<u8>,
+    ) -> Result<Self, ActlibError> {
+        Ok(AmqpEgress {
+            channel: block_on(open_channel(uri))?,
+            exchange: exchange.to_string(),
+            routing_key: routing_key.to_string(),
+            encoder,
+        })
+    }
+
+    /// Publish a message, blocking until the broker accepted it.
+    ///
+    /// Fails with [NetworkError](../api/enum.ActlibError.html#variant.NetworkError) if the connection is gone.
+    pub fn publish(&self, message: &M) -> Result<(), ActlibError> {
+        block_on(async {
+            self.channel
+                .basic_publish(
+                    &self.exchange,
+                    &self.routing_key,
+                    BasicPublishOptions::default(),
+                    &(self.encoder)(message),
+                    BasicProperties::default(),
+                )
+                .await?
+                .await
+        })
+        .map(|_confirmation| ())
+        .map_err(|e| {
+            ActlibError::NetworkError(format!(
+                "Failed to publish to AMQP exchange {:?}: {:?}",
+                self.exchange, e
+            ))
+        })
+    }
+}
+
+/// Connect to the broker and open a channel.
+async fn open_channel(uri: &str) -> Result<Channel, ActlibError> {
+    let connection = Connection::connect(uri, ConnectionProperties::default())
+        .await
+        .map_err(|e| ActlibError::NetworkError(format!("Failed to connect to {}: {:?}", uri, e)))?;
+    connection
+        .create_channel()
+        .await
+        .map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))
+}
@@ -11,82 +11,1749 @@
 //! * The first [Message](../message/trait.Message.html) send, either by the main thread or an Actor [on_spawn](../actor/trait.Actor.html#method.on_start), gets the ball rolling.
 
 pub use crate::actor::*;
+pub use crate::counters::{Counter, CounterSnapshot, Gauge};
+pub use crate::environment::ActorBuilder;
 use crate::environment::*;
 pub use crate::errors::ActlibError;
 use crate::log_err_as;
 pub use crate::message::*;
-pub use crate::{actor_builder, impl_message_handler};
+pub use crate::options::*;
+use crate::router::{ResizePool, Router, ROUTER_TYPE_ID};
+use crate::scheduler;
+pub use crate::timer::TimerHandle;
+pub use crate::wire::WireFormat;
+pub use crate::{actor_builder, handler_set, impl_message_handler};
 use log::*;
-use std::net::SocketAddr;
+pub use netchannel::Peer;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{SocketAddr, TcpStream};
+use std::pin::Pin;
 use std::sync::mpsc::channel;
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::RecvError;
+use std::sync::mpsc::TryRecvError;
+use std::sync::Arc;
+use std::task::{self, Poll};
+use std::time::{Duration, Instant, SystemTime};
 use uuid::Uuid;
 
 /// Struct that supports `wait_until_expiration()`, a blocking function that waits for a termination signal by the associated Environment.
 ///
-/// Use this struct to halt the main thread of the program until the actor system has finished work and the program can be finished regularly.
+/// Use this struct to halt the main thread of the program until the actor system has finished work and the program can be finished regularly,
+/// or [poll](struct.EnvironmentExpirationChecker.html#method.try_check) it from a main loop doing its own work.
 ///
 /// The expiration signal is created by calling [Environment::set_expired](struct.Environment#method.set_expired).
 pub struct EnvironmentExpirationChecker {
-    termination_receiver: Receiver<()>,
+    termination_receiver: Receiver<ExpirationResult>,
 }
 
 impl EnvironmentExpirationChecker {
     /// Blocks the current thread until the associated Environment's [set expired](struct.Environment#method.set_expired) method has been called by another thread or the underlying `channel` has been compromised which usually signals a fatal condition of the associated Environment.
-    pub fn wait_until_expiration(&self) -> Result<(), RecvError> {
+    ///
+    /// The returned [ExpirationResult](struct.ExpirationResult.html) tells whether the run ended cleanly.
+    pub fn wait_until_expiration(&self) -> Result<ExpirationResult, RecvError> {
         self.termination_receiver.recv()
     }
-}
 
-/// The Environment knows about all [Actors](../actor/trait.Actor.html) in the system.
-///
-/// It can [spawn](struct.Environment.html#method.spawn) new actors and construct an [ActorRef](../actor/struct.ActorRef.html) from an identifier using [to_actor_ref](struct.Environment.html#method.to_actor_ref) and [find_actor_ref](struct.Environment.html#method.find_actor_ref).
-///
-/// Using the associated [EnvironmentExpirationChecker](struct.EnvironmentExpirationChecker.html) you can block the main thread until the Environment is [set_expired](struct.Environment#method.set_expired).
-#[derive(Clone, Debug)]
-pub struct Environment {
-    pub(crate) env: ArcEnvironment,
-}
+    /// Check for the expiration without blocking, so a main loop can do its own work in between.
+    ///
+    /// Returns ```None``` while the Environment is running. If the Environment is gone without expiring,
+    /// the result has the reason [Fatal](enum.ExpirationReason.html#variant.Fatal).
+    pub fn try_check(&self) -> Option<ExpirationResult> {
+        match self.termination_receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(ExpirationResult {
+                reason: ExpirationReason::Fatal(
+                    "The Environment is gone without expiring".to_string(),
+                ),
+                ..ExpirationResult::default()
+            }),
+        }
+    }
+}
+
+/// Why an Environment expired, see [ExpirationResult](struct.ExpirationResult.html).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ExpirationReason {
+    /// The local machine called [set_expired](struct.Environment.html#method.set_expired) or [shutdown](struct.Environment.html#method.shutdown).
+    #[default]
+    LocalRequest,
+    /// The given remote machine expired the Environment.
+    RemoteRequest(MachineId),
+    /// The Environment ended without an expiration, e.g. because it panicked or an [internal thread](struct.ThreadHealth.html) was given up on.
+    Fatal(String),
+}
+
+/// How a single machine wound down its Actors during expiration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrainReport {
+    /// The machine this report originates from.
+    pub machine: MachineId,
+    /// Number of Actors that were sent a Stop request.
+    pub actors_signaled: usize,
+    /// Number of Actors that ran their [on_stop_with_reason](../actor/trait.Actor.html#method.on_stop_with_reason) method before the deadline.
+    pub actors_stopped: usize,
+    /// Signaled Actors that were still working off their mailbox or running on_stop when the deadline passed.
+    pub actors_not_stopped: Vec<ActorId>,
+    /// Number of Messages that were still queued in the mailboxes of stopped Actors and got discarded.
+    pub messages_discarded: usize,
+    /// Number of delayed or scheduled Messages that were still pending and got cancelled.
+    pub delayed_messages_cancelled: usize,
+    /// How the [on_stop_with_reason](../actor/trait.Actor.html#method.on_stop_with_reason) method of every signaled Actor ended.
+    pub stop_records: Vec<StopRecord>,
+}
+
+impl DrainReport {
+    /// Returns ```true``` if every signaled Actor stopped without panicking in on_stop and no Message was discarded.
+    pub fn is_clean(&self) -> bool {
+        self.actors_stopped == self.actors_signaled
+            && self.messages_discarded == 0
+            && self
+                .stop_records
+                .iter()
+                .all(|record| record.outcome == StopOutcome::Completed)
+    }
+}
+
+/// How the [on_stop_with_reason](../actor/trait.Actor.html#method.on_stop_with_reason) method of a single Actor ended during expiration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopRecord {
+    /// The stopped Actor.
+    pub actor: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// Whether on_stop ran to completion.
+    pub outcome: StopOutcome,
+}
+
+/// The end of the [on_stop_with_reason](../actor/trait.Actor.html#method.on_stop_with_reason) method of an Actor, see [StopRecord](struct.StopRecord.html).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopOutcome {
+    /// on_stop returned.
+    Completed,
+    /// on_stop panicked with the given message.
+    Panicked(String),
+    /// The Actor was still working off its mailbox or running on_stop when the deadline passed.
+    TimedOut,
+}
+
+/// The outcome of an expiration, aggregated over all machines of the Environment.
+///
+/// Returned by [wait_until_expiration](struct.EnvironmentExpirationChecker.html#method.wait_until_expiration).
+#[derive(Debug, Clone, Default)]
+pub struct ExpirationResult {
+    /// Why the Environment expired.
+    pub reason: ExpirationReason,
+    /// One report per machine that answered in time, the local machine first.
+    pub reports: Vec<DrainReport>,
+    /// Remote machines that did not send a report before the deadline.
+    pub unreported: Vec<MachineId>,
+}
+
+impl ExpirationResult {
+    /// Total number of Actors stopped on all machines.
+    pub fn actors_stopped(&self) -> usize {
+        self.reports
+            .iter()
+            .map(|report| report.actors_stopped)
+            .sum()
+    }
+
+    /// Total number of Messages discarded on all machines.
+    pub fn messages_discarded(&self) -> usize {
+        self.reports
+            .iter()
+            .map(|report| report.messages_discarded)
+            .sum()
+    }
+
+    /// The Actors on all machines that failed to stop before the deadline.
+    pub fn actors_not_stopped(&self) -> Vec<ActorId> {
+        self.reports
+            .iter()
+            .flat_map(|report| report.actors_not_stopped.iter().cloned())
+            .collect()
+    }
+
+    /// How the on_stop method of every Actor on all reporting machines ended.
+    pub fn stop_records(&self) -> Vec<StopRecord> {
+        self.reports
+            .iter()
+            .flat_map(|report| report.stop_records.iter().cloned())
+            .collect()
+    }
+
+    /// The Actors on all machines whose on_stop method panicked.
+    pub fn actors_panicked(&self) -> Vec<ActorId> {
+        self.reports
+            .iter()
+            .flat_map(|report| report.stop_records.iter())
+            .filter(|record| matches!(record.outcome, StopOutcome::Panicked(_)))
+            .map(|record| record.actor.clone())
+            .collect()
+    }
+
+    /// Returns ```true``` if every machine reported and every report [is clean](struct.DrainReport.html#method.is_clean).
+    pub fn is_clean(&self) -> bool {
+        self.unreported.is_empty() && self.reports.iter().all(DrainReport::is_clean)
+    }
+}
+
+/// Structured description of an [Environment](struct.Environment.html), returned by [info](struct.Environment.html#method.info).
+#[derive(Debug, Clone)]
+pub struct EnvironmentInfo {
+    /// The identity of the local machine.
+    pub machine_id: MachineId,
+    /// The address the local machine is reachable at.
+    pub local_addr: SocketAddr,
+    /// The address the server accepting remote machines actually listens on,
+    /// ```None``` if no remote machine connects to this one or the Environment expired.
+    pub listen_addr: Option<SocketAddr>,
+    /// The remote machines this Environment is connected to, as configured.
+    pub peers: Vec<(MachineId, Peer)>,
+    /// The Actor type ids known to the [actor_builder](../macro.actor_builder.html).
+    pub actor_types: Vec<String>,
+    /// The [fingerprint](struct.ActorBuilder.html#method.fingerprint) of the Actor and message types, equal on all machines.
+    pub fingerprint: u64,
+    /// The version of the *actlib* library.
+    pub version: String,
+}
+
+/// The connection state of a configured remote machine, returned by [peer_status](struct.Environment.html#method.peer_status).
+#[derive(Debug, Clone)]
+pub struct PeerStatus {
+    /// The remote machine as configured.
+    pub peer: Peer,
+    /// The identity of the remote machine, ```None``` until it introduced itself.
+    pub machine_id: Option<MachineId>,
+    /// Whether the remote machine is connected.
+    pub state: PeerState,
+}
+
+/// Whether a remote machine is connected, see [PeerStatus](struct.PeerStatus.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerState {
+    /// The remote machine did not answer yet, it is attached in the background once it does.
+    Connecting,
+    /// Messages and spawns reach the remote machine.
+    Connected,
+    /// The connection dropped, it may be [re-established](struct.EnvironmentOptions.html#method.reconnect).
+    Disconnected,
+    /// The remote machine answered, but cannot join this Environment, e.g. because it runs an incompatible build.
+    Failed(String),
+}
+
+/// A machine of the Environment, returned by [machines](struct.Environment.html#method.machines).
+///
+/// Pass it to [spawn_on](struct.Environment.html#method.spawn_on) or [exclude_machines](struct.SpawnOptions.html#method.exclude_machines)
+/// to place Actors with the topology in mind.
+#[derive(Debug, Clone)]
+pub struct MachineRef {
+    /// The identity of the machine.
+    pub id: MachineId,
+    /// The address of the machine, as configured for remote machines.
+    pub address: Peer,
+    /// Whether the machine is connected, the local machine always is.
+    pub status: PeerState,
+    /// Number of Actors living on the machine, ```None``` for remote machines
+    /// unless [load exchange](struct.EnvironmentOptions.html#method.load_exchange) is enabled.
+    pub actors: Option<usize>,
+}
+
+impl MachineRef {
+    /// Whether Actors spawned on the machine can be reached.
+    pub fn is_connected(&self) -> bool {
+        self.status == PeerState::Connected
+    }
+}
+
+impl From<MachineRef> for MachineId {
+    fn from(machine: MachineRef) -> Self {
+        machine.id
+    }
+}
+
+impl From<&MachineRef> for MachineId {
+    fn from(machine: &MachineRef) -> Self {
+        machine.id
+    }
+}
+
+/// Whether a remote machine answers, see [MachineStatus](struct.MachineStatus.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachineState {
+    /// The machine is connected and answered its latest pings, if it was pinged at all.
+    Up,
+    /// The machine is not connected, never came up, or did not answer its latest pings.
+    Down,
+}
+
+/// Whether a configured remote machine answers, returned by [cluster_status](struct.Environment.html#method.cluster_status).
+#[derive(Debug, Clone)]
+pub struct MachineStatus {
+    /// The remote machine as configured.
+    pub peer: Peer,
+    /// The identity of the remote machine, ```None``` until it introduced itself.
+    pub machine_id: Option<MachineId>,
+    /// Whether the machine is up.
+    pub state: MachineState,
+    /// The round trip time of the latest answered ping, ```None``` if the machine never answered one.
+    pub round_trip: Option<Duration>,
+    /// Time since the machine answered its latest ping, ```None``` if it never answered one.
+    pub since_answer: Option<Duration>,
+    /// Number of pings in a row the machine did not answer.
+    pub missed_pings: u32,
+}
+
+impl MachineStatus {
+    /// Whether the machine is up.
+    pub fn is_up(&self) -> bool {
+        self.state == MachineState::Up
+    }
+}
+
+/// Snapshot of the Actors living on the local machine, returned by [introspect](struct.Environment.html#method.introspect).
+///
+/// Also written periodically to disk if a [StateDump](struct.StateDump.html) is configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Introspection {
+    /// The address of the local machine.
+    pub machine: SocketAddr,
+    /// The identity of the local machine.
+    pub machine_id: MachineId,
+    /// When the snapshot was taken.
+    pub taken_at: SystemTime,
+    /// Every Actor alive on the local machine.
+    pub actors: Vec<ActorDescription>,
+    /// Which Actor spawned which, only filled if [lineage tracing](struct.EnvironmentOptions.html#method.trace_lineage) is enabled.
+    pub lineage: Vec<LineageRecord>,
+    /// Traffic exchanged with every remote machine.
+    pub peers: Vec<PeerStats>,
+    /// Handled messages per Actor type and message type, only filled if [handler statistics](struct.EnvironmentOptions.html#method.handler_stats) are enabled.
+    pub handlers: Vec<HandlerStats>,
+    /// Answered asks per requester type and responder type, only filled if [reply tracing](struct.EnvironmentOptions.html#method.trace_replies) is enabled.
+    pub replies: Vec<ReplyStats>,
+}
+
+/// Upper bounds of the buckets of the [HandlerStats](struct.HandlerStats.html) histogram, a last bucket holds the longer runs.
+pub const HANDLER_TIME_BUCKETS: [Duration; 6] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// The handler runs for a single message type of a single Actor type on the local machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandlerStats {
+    /// The type id the Actors were built from.
+    pub type_id: String,
+    /// The name of the message type, ```<unknown>``` if the [MessageHandler](../message/trait.MessageHandler.html) does not tell.
+    pub message_type: String,
+    /// Number of handled messages.
+    pub handled: u64,
+    /// Time spent in the handler.
+    pub total_time: Duration,
+    /// The longest handler run.
+    pub max_time: Duration,
+    /// Number of handler runs per bucket of [HANDLER_TIME_BUCKETS](constant.HANDLER_TIME_BUCKETS.html), the last entry counts the longer runs.
+    pub histogram: Vec<u64>,
+}
+
+impl HandlerStats {
+    pub(crate) fn new(type_id: &str, message_type: &str) -> Self {
+        HandlerStats {
+            type_id: type_id.to_string(),
+            message_type: message_type.to_string(),
+            handled: 0,
+            total_time: Duration::from_secs(0),
+            max_time: Duration::from_secs(0),
+            histogram: vec![0; HANDLER_TIME_BUCKETS.len() + 1],
+        }
+    }
+
+    /// Add a single handler run.
+    pub(crate) fn record(&mut self, time: Duration) {
+        self.handled += 1;
+        self.total_time += time;
+        self.max_time = self.max_time.max(time);
+        let bucket = HANDLER_TIME_BUCKETS
+            .iter()
+            .position(|bound| time <= *bound)
+            .unwrap_or(HANDLER_TIME_BUCKETS.len());
+        self.histogram[bucket] += 1;
+    }
+
+    /// The average handler run.
+    pub fn mean_time(&self) -> Duration {
+        match self.handled {
+            0 => Duration::from_secs(0),
+            handled => self.total_time / handled as u32,
+        }
+    }
+}
+
+/// The asks sent by the Actors of one type and answered by the Actors of another type, measured on the machine of the requester.
+///
+/// The type is ```<outside>``` for asks sent from outside of an Actor, and ```<unknown>``` if it could not be told,
+/// e.g. because the requester stopped before the reply arrived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyStats {
+    /// The type id of the asking Actors.
+    pub requester_type: String,
+    /// The type id of the answering Actors.
+    pub responder_type: String,
+    /// Number of replies that arrived.
+    pub replies: u64,
+    /// Time from the asks to their replies.
+    pub total_time: Duration,
+    /// The longest time until a reply arrived.
+    pub max_time: Duration,
+    /// Number of replies per bucket of [HANDLER_TIME_BUCKETS](constant.HANDLER_TIME_BUCKETS.html), the last entry counts the slower replies.
+    pub histogram: Vec<u64>,
+}
+
+impl ReplyStats {
+    pub(crate) fn new(requester_type: &str, responder_type: &str) -> Self {
+        ReplyStats {
+            requester_type: requester_type.to_string(),
+            responder_type: responder_type.to_string(),
+            replies: 0,
+            total_time: Duration::from_secs(0),
+            max_time: Duration::from_secs(0),
+            histogram: vec![0; HANDLER_TIME_BUCKETS.len() + 1],
+        }
+    }
+
+    /// Add a single reply.
+    pub(crate) fn record(&mut self, time: Duration) {
+        self.replies += 1;
+        self.total_time += time;
+        self.max_time = self.max_time.max(time);
+        let bucket = HANDLER_TIME_BUCKETS
+            .iter()
+            .position(|bound| time <= *bound)
+            .unwrap_or(HANDLER_TIME_BUCKETS.len());
+        self.histogram[bucket] += 1;
+    }
+
+    /// The average time from an ask to its reply.
+    pub fn mean_time(&self) -> Duration {
+        match self.replies {
+            0 => Duration::from_secs(0),
+            replies => self.total_time / replies as u32,
+        }
+    }
+}
+
+/// Traffic exchanged with a single remote machine since the Environment was created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerStats {
+    /// The remote machine.
+    pub peer: MachineId,
+    /// Number of network frames sent.
+    pub frames_sent: u64,
+    /// Number of bytes sent.
+    pub bytes_sent: u64,
+    /// Number of network frames received.
+    pub frames_received: u64,
+    /// Number of bytes received.
+    pub bytes_received: u64,
+}
+
+impl Introspection {
+    /// The lineage records of all Actors spawned by the given Actor.
+    pub fn children_of(&self, spawner: &ActorId) -> Vec<&LineageRecord> {
+        self.lineage
+            .iter()
+            .filter(|record| record.spawner.as_ref() == Some(spawner))
+            .collect()
+    }
+
+    /// The share of the handler time of every message type of the given Actor type, the most expensive first.
+    ///
+    /// Tells where optimizations pay off, e.g. that 90% of the time goes to a single message type.
+    pub fn handler_time_shares(&self, type_id: &str) -> Vec<(&str, f64)> {
+        let handlers: Vec<&HandlerStats> = self
+            .handlers
+            .iter()
+            .filter(|stats| stats.type_id == type_id)
+            .collect();
+        let total: f64 = handlers
+            .iter()
+            .map(|stats| stats.total_time.as_secs_f64())
+            .sum();
+        let mut shares: Vec<(&str, f64)> = handlers
+            .iter()
+            .map(|stats| {
+                let share = if total > 0.0 {
+                    stats.total_time.as_secs_f64() / total
+                } else {
+                    0.0
+                };
+                (stats.message_type.as_str(), share)
+            })
+            .collect();
+        shares.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        shares
+    }
+
+    /// The chain of spawners of the given Actor, starting with its direct spawner.
+    ///
+    /// The chain ends at an Actor spawned from outside an Actor, or at an Actor spawned on another machine.
+    pub fn ancestry(&self, actor: &ActorId) -> Vec<ActorId> {
+        let mut ancestry = Vec::new();
+        let mut current = actor;
+        while let Some(record) = self.lineage.iter().find(|record| &record.actor == current) {
+            match &record.spawner {
+                Some(spawner) if !ancestry.contains(spawner) => {
+                    ancestry.push(spawner.clone());
+                    current = spawner;
+                }
+                _ => break,
+            }
+        }
+        ancestry
+    }
+}
+
+/// Counters and rates of the local machine, returned by [metrics](struct.Environment.html#method.metrics).
+///
+/// The counters start when the Environment is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metrics {
+    /// The measured machine.
+    pub machine_id: MachineId,
+    /// When the metrics were taken.
+    pub taken_at: SystemTime,
+    /// Number of live Actors on the local machine, by type id.
+    pub live_actors: HashMap<String, usize>,
+    /// The mailbox of every live Actor on the local machine.
+    pub mailboxes: Vec<MailboxBacklog>,
+    /// Number of messages handled by the local Actors.
+    pub messages_handled: u64,
+    /// Number of messages sent to Actors on remote machines.
+    pub remote_messages_sent: u64,
+    /// Number of messages received for the local Actors from remote machines.
+    pub remote_messages_received: u64,
+    /// Number of Actors spawned on the local machine.
+    pub actors_spawned: u64,
+    /// Number of Actors removed from the local machine, including the failed ones.
+    pub actors_removed: u64,
+    /// Number of messages the local machine [dropped](struct.MessageDropped.html), for every cause.
+    pub messages_dropped: HashMap<DropCause, u64>,
+    /// The counters per second.
+    pub rates: MetricRates,
+    /// How long the internal locks waited, empty unless [lock contention](struct.EnvironmentOptions.html#method.lock_contention) is instrumented.
+    pub lock_waits: Vec<LockWaits>,
+}
+
+/// The wait-time histogram of an internal lock, part of the [Metrics](struct.Metrics.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockWaits {
+    /// Name of the lock: ```local_actor_channels```, ```net_senders``` or ```remote_queries```.
+    pub lock: String,
+    /// Upper bounds with the number of waits no longer than them, ascending.
+    pub buckets: Vec<(Duration, u64)>,
+    /// Number of waits, including the ones longer than the highest bound.
+    pub count: u64,
+    /// The summed up wait.
+    pub total_wait: Duration,
+}
+
+/// The messages queued for a single Actor, part of the [Metrics](struct.Metrics.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailboxBacklog {
+    /// The Actor's id.
+    pub actor: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// Number of messages in the Actor's mailbox.
+    pub queued_messages: usize,
+    /// Serialized size of the messages in the Actor's mailbox.
+    ///
+    /// Messages of local senders are only measured if the mailbox has a [quota](struct.MailboxQuota.html)
+    /// or [mailbox alerts](struct.EnvironmentOptions.html#method.mailbox_alerts) are configured.
+    pub queued_bytes: usize,
+}
+
+/// The counters of the [Metrics](struct.Metrics.html) per second.
+///
+/// Measured over the interval between the two latest calls of [metrics](struct.Environment.html#method.metrics) at least a second apart,
+/// the first interval starts when the Environment is created.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetricRates {
+    /// The interval the rates were measured over.
+    pub interval: Duration,
+    /// Messages handled per second.
+    pub messages_handled: f64,
+    /// Messages sent to remote machines per second.
+    pub remote_messages_sent: f64,
+    /// Messages received from remote machines per second.
+    pub remote_messages_received: f64,
+    /// Actors spawned per second.
+    pub actors_spawned: f64,
+    /// Actors removed per second.
+    pub actors_removed: f64,
+}
+
+impl Metrics {
+    /// Render the metrics in the Prometheus text format, as served by the [Prometheus exporter](struct.EnvironmentOptions.html#method.prometheus_exporter).
+    ///
+    /// The mailboxes are summed up per type id, to keep the number of time series independent of the number of Actors.
+    /// Prometheus derives the rates from the counters itself.
+    pub fn to_prometheus(&self) -> String {
+        let mut per_type: Vec<TypeBacklog> = self
+            .live_actors
+            .iter()
+            .map(|(type_id, actors)| {
+                self.mailboxes
+                    .iter()
+                    .filter(|mailbox| &mailbox.type_id == type_id)
+                    .fold(
+                        TypeBacklog {
+                            type_id,
+                            actors: *actors,
+                            ..TypeBacklog::default()
+                        },
+                        |mut backlog, mailbox| {
+                            backlog.queued_messages += mailbox.queued_messages;
+                            backlog.queued_bytes += mailbox.queued_bytes;
+                            backlog.max_queued_messages =
+                                backlog.max_queued_messages.max(mailbox.queued_messages);
+                            backlog
+                        },
+                    )
+            })
+            .collect();
+        per_type.sort_by_key(|backlog| backlog.type_id);
+        let gauges: [(&str, &str, BacklogValue); 4] = [
+            ("actlib_live_actors", "Number of live Actors.", |backlog| {
+                backlog.actors
+            }),
+            (
+                "actlib_queued_messages",
+                "Messages queued in the mailboxes of the live Actors.",
+                |backlog| backlog.queued_messages,
+            ),
+            (
+                "actlib_queued_bytes",
+                "Serialized size of the messages queued in the mailboxes of the live Actors.",
+                |backlog| backlog.queued_bytes,
+            ),
+            (
+                "actlib_max_queued_messages",
+                "Messages queued in the fullest mailbox.",
+                |backlog| backlog.max_queued_messages,
+            ),
+        ];
+        let counters = [
+            (
+                "actlib_messages_handled_total",
+                "Messages handled by the local Actors.",
+                self.messages_handled,
+            ),
+            (
+                "actlib_remote_messages_sent_total",
+                "Messages sent to Actors on remote machines.",
+                self.remote_messages_sent,
+            ),
+            (
+                "actlib_remote_messages_received_total",
+                "Messages received from remote machines.",
+                self.remote_messages_received,
+            ),
+            (
+                "actlib_actors_spawned_total",
+                "Actors spawned on the local machine.",
+                self.actors_spawned,
+            ),
+            (
+                "actlib_actors_removed_total",
+                "Actors removed from the local machine.",
+                self.actors_removed,
+            ),
+        ];
+        let mut text = String::new();
+        for (name, help, value) in gauges.iter() {
+            text.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} gauge\n",
+                name, help, name
+            ));
+            for backlog in per_type.iter() {
+                text.push_str(&format!(
+                    "{}{{machine=\"{}\",type_id=\"{}\"}} {}\n",
+                    name,
+                    self.machine_id,
+                    escape_label(backlog.type_id),
+                    value(backlog)
+                ));
+            }
+        }
+        for (name, help, value) in counters.iter() {
+            text.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} counter\n{}{{machine=\"{}\"}} {}\n",
+                name, help, name, name, self.machine_id, value
+            ));
+        }
+        text.push_str("# HELP actlib_messages_dropped_total Messages dropped by the local machine, by cause.\n# TYPE actlib_messages_dropped_total counter\n");
+        for cause in DropCause::ALL.iter() {
+            text.push_str(&format!(
+                "actlib_messages_dropped_total{{machine=\"{}\",cause=\"{}\"}} {}\n",
+                self.machine_id,
+                cause.label(),
+                self.messages_dropped.get(cause).copied().unwrap_or(0)
+            ));
+        }
+        if !self.lock_waits.is_empty() {
+            text.push_str("# HELP actlib_lock_wait_seconds Time waited for the internal locks.\n# TYPE actlib_lock_wait_seconds histogram\n");
+        }
+        for waits in self.lock_waits.iter() {
+            let labels = format!("machine=\"{}\",lock=\"{}\"", self.machine_id, waits.lock);
+            for (bound, count) in waits.buckets.iter() {
+                text.push_str(&format!(
+                    "actlib_lock_wait_seconds_bucket{{{},le=\"{}\"}} {}\n",
+                    labels,
+                    bound.as_secs_f64(),
+                    count
+                ));
+            }
+            text.push_str(&format!(
+                "actlib_lock_wait_seconds_bucket{{{},le=\"+Inf\"}} {}\nactlib_lock_wait_seconds_sum{{{}}} {}\nactlib_lock_wait_seconds_count{{{}}} {}\n",
+                labels,
+                waits.count,
+                labels,
+                waits.total_wait.as_secs_f64(),
+                labels,
+                waits.count
+            ));
+        }
+        text
+    }
+}
+
+/// Reads a single gauge of a TypeBacklog.
+type BacklogValue = fn(&TypeBacklog) -> usize;
+
+/// The mailboxes of all live Actors of a type, summed up for the Prometheus text format.
+#[derive(Default)]
+struct TypeBacklog<'a> {
+    type_id: &'a str,
+    actors: usize,
+    queued_messages: usize,
+    queued_bytes: usize,
+    max_queued_messages: usize,
+}
+
+/// Escape a label value for the Prometheus text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Description of a single Actor living on the local machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorDescription {
+    /// The Actor's id.
+    pub id: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// The Actor that spawned this Actor, ```None``` if it was spawned from outside an Actor.
+    pub spawner: Option<ActorId>,
+    /// Number of messages in the Actor's mailbox.
+    pub queued_messages: usize,
+    /// Serialized size of the messages in the Actor's mailbox.
+    ///
+    /// Messages of local senders are only measured if the mailbox has a [quota](struct.MailboxQuota.html)
+    /// or [mailbox alerts](struct.EnvironmentOptions.html#method.mailbox_alerts) are configured.
+    pub queued_bytes: usize,
+    /// How long the oldest message in the Actor's mailbox waits, only known if [mailbox alerts](struct.EnvironmentOptions.html#method.mailbox_alerts) are configured.
+    pub oldest_message_age: Option<Duration>,
+}
+
+/// The local Actors with their state, taken by [snapshot](struct.Environment.html#method.snapshot) and respawned by [restore](struct.Environment.html#method.restore).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The machine the Actors lived on.
+    pub machine_id: MachineId,
+    /// When the snapshot was taken.
+    pub taken_at: SystemTime,
+    /// Every local Actor, ordered by id.
+    pub actors: Vec<ActorSnapshot>,
+}
+
+/// A single Actor of a [Snapshot](struct.Snapshot.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorSnapshot {
+    /// The Actor's id when the snapshot was taken.
+    pub id: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// The user specified id, ```None``` if the id was created automatically.
+    pub specified_id: Option<Vec<u8>>,
+    /// The [exported state](../actor/trait.Actor.html#method.export_state), ```None``` if the Actor exported nothing in time.
+    pub state: Option<Vec<u8>>,
+}
+
+/// The ids of the Actors alive on the connected machines, gathered by [list_cluster_actors](struct.Environment.html#method.list_cluster_actors).
+///
+/// Either [wait](#method.wait) for it on the current thread, or ```.await``` it, as it implements ```std::future::Future```.
+/// The Actors of machines that did not answer in time are left out.
+#[derive(Debug)]
+pub struct ActorListing {
+    slot: Arc<ListingSlot>,
+}
+
+impl ActorListing {
+    /// Block the current thread until every machine answered or the timeout passed.
+    pub fn wait(self) -> Vec<ActorId> {
+        match self.slot.state.lock() {
+            Ok(state) => match scheduler::blocking(|| {
+                self.slot.finished.wait_while(state, |state| !state.done)
+            }) {
+                Ok(mut state) => std::mem::take(&mut state.actors),
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    Vec::new()
+                }
+            },
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl Future for ActorListing {
+    type Output = Vec<ActorId>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        match self.slot.state.lock() {
+            Ok(mut state) => {
+                if state.done {
+                    Poll::Ready(std::mem::take(&mut state.actors))
+                } else {
+                    state.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Poll::Ready(Vec::new())
+            }
+        }
+    }
+}
+
+/// The end of a [broadcast in slices](struct.Environment.html#method.broadcast_in_slices) on the local machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastCompleted {
+    /// Number of local Actors that got the Message.
+    pub actors: usize,
+    /// Number of slices the Actors were handed the Message in.
+    pub slices: usize,
+    /// Time from the broadcast to its last slice.
+    pub elapsed: Duration,
+}
+
+/// A connection accepted on an [external listener](struct.Environment.html#method.register_external_listener), sent to its handler.
+///
+/// The handler takes the stream with [take_stream](#method.take_stream). Streams not taken within a minute are closed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalConnection {
+    /// The port the connection was accepted on.
+    pub port: u16,
+    /// The address of the connecting client.
+    pub peer: SocketAddr,
+    pub(crate) connection_no: u64,
+}
+
+impl ExternalConnection {
+    /// Take the accepted stream, ```None``` if it was taken already or closed.
+    ///
+    /// Only works on the machine that accepted the connection, which is where its handler lives.
+    pub fn take_stream(&self) -> Option<TcpStream> {
+        take_external_connection(self.connection_no)
+    }
+}
+
+/// A local Actor whose mailbox exceeds the [MailboxAlerts](struct.MailboxAlerts.html) thresholds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MailboxAlert {
+    /// The slow Actor.
+    pub actor: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// Number of messages in the Actor's mailbox.
+    pub queued_messages: usize,
+    /// How long the oldest message in the Actor's mailbox waits.
+    pub oldest_message_age: Option<Duration>,
+}
+
+/// The load of a single machine, part of every [ClusterLoad](struct.ClusterLoad.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineLoad {
+    /// The measured machine.
+    pub machine: MachineId,
+    /// The 1 minute load average divided by the number of CPUs, ```None``` if the platform does not provide it.
+    pub cpu_load: Option<f64>,
+    /// Number of Actors living on the machine.
+    pub actors: usize,
+    /// Number of messages queued in the mailboxes of all its Actors.
+    pub queued_messages: usize,
+    /// Serialized size of the messages queued in the mailboxes of all its Actors, as far as they are measured, see [MailboxBacklog](struct.MailboxBacklog.html#structfield.queued_bytes).
+    pub queued_bytes: usize,
+    /// When the load was measured, by the clock of the measured machine.
+    pub measured_at: SystemTime,
+}
+
+/// A change in the lifecycle of an Actor, sent to the [subscribers](struct.Environment.html#method.subscribe_lifecycle) of its type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LifecycleEvent {
+    /// The Actor was registered on its machine, its [on_start](../actor/trait.Actor.html#method.on_start) method runs next.
+    Spawned(ActorId),
+    /// The Actor was removed from its machine.
+    Stopped(ActorId),
+}
+
+/// Sent to the [watchers](../actor/struct.ActorRef.html#method.watch) of an Actor once it stopped, with the id of the stopped Actor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Terminated(pub ActorId);
+
+/// The latest load of every machine, sent to the [subscribers](struct.Environment.html#method.subscribe_cluster_load) after every measurement.
+///
+/// Lets an application slow down, e.g. stop spawning new players, once the machines saturate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterLoad {
+    /// One entry per machine that reported recently, the local machine first.
+    pub machines: Vec<MachineLoad>,
+}
+
+impl ClusterLoad {
+    /// The messages queued on all machines.
+    pub fn queued_messages(&self) -> usize {
+        self.machines.iter().map(|load| load.queued_messages).sum()
+    }
+
+    /// The serialized size of the messages queued on all machines.
+    pub fn queued_bytes(&self) -> usize {
+        self.machines.iter().map(|load| load.queued_bytes).sum()
+    }
+
+    /// The highest CPU load of all machines, ```None``` if no machine provides it.
+    pub fn max_cpu_load(&self) -> Option<f64> {
+        self.machines.iter().filter_map(|load| load.cpu_load).fold(
+            None,
+            |max, cpu_load| match max {
+                Some(max) if max >= cpu_load => Some(max),
+                _ => Some(cpu_load),
+            },
+        )
+    }
+}
+
+/// A forwarding entry created by [alias](struct.Environment.html#method.alias), returned by [aliases](struct.Environment.html#method.aliases).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AliasStats {
+    /// The id of the replaced Actor.
+    pub from: ActorId,
+    /// The Actor its messages are forwarded to.
+    pub to: ActorId,
+    /// Number of messages forwarded so far.
+    pub forwarded: u64,
+    /// Time until the entry expires.
+    pub expires_in: Duration,
+}
+
+/// A local Actor panicked, reported to the [watchers](struct.Environment.html#method.watch_actor_failures).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorFailed {
+    /// The panicked Actor.
+    pub actor: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// The type of the message being handled, ```None``` if the Actor panicked in on_start, on_reset or a query, or the type is unknown.
+    pub message_type: Option<String>,
+    /// The panic message.
+    pub reason: String,
+    /// ```true``` if the Actor was stopped, following the [FailurePolicy](enum.FailurePolicy.html).
+    pub stopped: bool,
+}
+
+/// A Message dropped because the machine of its keyed Actor was disconnected, reported to the [watchers](struct.Environment.html#method.watch_dead_letters).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// The Actor the Message was sent to.
+    pub actor: ActorId,
+    /// The type id of the Actor.
+    pub type_id: String,
+    /// The serialized Message.
+    pub message: Vec<u8>,
+}
+
+/// Why the local machine dropped a Message, part of a [MessageDropped](struct.MessageDropped.html) event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DropCause {
+    /// The receiving Actor does not handle the Message's type.
+    UnhandledType,
+    /// The receiving Actor was removed, stopped or failed before handling the Message.
+    ActorRemoved,
+    /// The Message arrived before the Actor's on_start completed, following [PreStartMessages::Discard](enum.PreStartMessages.html#variant.Discard).
+    BeforeStart,
+    /// The Message outlived the [retention](struct.OutboxRetention.html) of the durable outbox.
+    TtlExpired,
+    /// The mailbox of the receiving Actor exceeded its [quota](struct.MailboxQuota.html).
+    MailboxFull,
+    /// The Message could not be serialized, or exceeds the size the receiving machine accepts.
+    SerializationFailed,
+    /// The machine of the receiving Actor is disconnected or unreachable.
+    PeerDead,
+}
+
+impl DropCause {
+    /// Every cause, in the order the [Metrics](struct.Metrics.html) export them.
+    pub const ALL: [DropCause; 7] = [
+        DropCause::UnhandledType,
+        DropCause::ActorRemoved,
+        DropCause::BeforeStart,
+        DropCause::TtlExpired,
+        DropCause::MailboxFull,
+        DropCause::SerializationFailed,
+        DropCause::PeerDead,
+    ];
+
+    /// The name of the cause in the Prometheus label, e.g. ```mailbox_full```.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DropCause::UnhandledType => "unhandled_type",
+            DropCause::ActorRemoved => "actor_removed",
+            DropCause::BeforeStart => "before_start",
+            DropCause::TtlExpired => "ttl_expired",
+            DropCause::MailboxFull => "mailbox_full",
+            DropCause::SerializationFailed => "serialization_failed",
+            DropCause::PeerDead => "peer_dead",
+        }
+    }
+}
+
+/// A Message the local machine dropped without handling it, reported to the [watchers](struct.Environment.html#method.watch_dropped_messages)
+/// and counted in the [Metrics](struct.Metrics.html#structfield.messages_dropped).
+///
+/// Drops the sender learns about through an error, like a [send_message](../actor/struct.ActorRef.html#method.send_message) to a full mailbox, are not reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageDropped {
+    /// Why the Message was dropped.
+    pub cause: DropCause,
+    /// The Actor the Message was sent to, if known.
+    pub target: Option<ActorId>,
+    /// The name of the Message's type, if the receiving Actor knows it.
+    pub message_type: Option<String>,
+}
+
+/// A Message or ActorRef lookup addressed to an earlier incarnation of a local keyed Actor,
+/// reported to the [watchers](struct.Environment.html#method.watch_stale_incarnations) following the [IncarnationPolicy](enum.IncarnationPolicy.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaleIncarnation {
+    /// The addressed id, with the earlier incarnation.
+    pub stale: ActorId,
+    /// The id of the Actor living now, with the current incarnation.
+    pub current: ActorId,
+    /// The Actor that sent the Message, ```None``` if it was sent from outside of an Actor or is unknown.
+    pub sender: Option<ActorId>,
+    /// The serialized Message, ```None``` for an internal Token or an ActorRef [resolved](struct.Environment.html#method.to_actor_ref) from the stale id.
+    pub message: Option<Vec<u8>>,
+    /// Whether it reached the current incarnation anyway.
+    pub delivered: bool,
+}
+
+/// A Message to a remote Actor whose delivery was not confirmed under [reliable delivery](struct.EnvironmentOptions.html#method.reliable_delivery),
+/// reported to the [watchers](struct.Environment.html#method.watch_delivery_failures).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryFailure {
+    /// The Actor the Message was sent to.
+    pub actor: ActorId,
+    /// The Actor that sent the Message, ```None``` if it was sent from outside of an Actor.
+    pub sender: Option<ActorId>,
+    /// The serialized Message.
+    pub message: Vec<u8>,
+    /// How often the Message was sent.
+    pub attempts: u32,
+    /// Why the receiving machine rejected the Message, ```None``` if it never acknowledged it.
+    pub rejection: Option<String>,
+}
+
+/// Overall state of a machine, see [HealthReport](struct.HealthReport.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    /// Everything works as expected.
+    Ready,
+    /// The machine works, but lost a remote machine, had a panicking Actor or internal thread within the last minute or has Actors
+    /// exceeding the [mailbox alert](struct.EnvironmentOptions.html#method.mailbox_alerts) thresholds.
+    Degraded,
+    /// A lock is poisoned, every remote machine is lost or an internal thread was given up on, the machine should be restarted.
+    Failed,
+}
+
+/// The health of the local machine, returned by [health](struct.Environment.html#method.health).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The summarized state.
+    pub status: HealthStatus,
+    /// The local machine.
+    pub machine_id: MachineId,
+    /// The names of the Environment's poisoned locks.
+    pub poisoned_locks: Vec<String>,
+    /// The remote machines whose connection failed.
+    pub dead_peers: Vec<MachineId>,
+    /// Number of local Actors that panicked since the Environment was created.
+    pub actors_failed: usize,
+    /// Number of local Actors that panicked within the last minute.
+    pub recent_failures: usize,
+    /// Number of messages queued in all local mailboxes.
+    pub queued_messages: usize,
+    /// The local Actors exceeding the mailbox alert thresholds, empty if mailbox alerts are not configured.
+    pub backlogged_actors: Vec<ActorId>,
+    /// The internal threads of the Environment that panicked, by name.
+    pub threads: Vec<ThreadHealth>,
+    /// When the report was taken.
+    pub checked_at: SystemTime,
+}
+
+/// An internal thread of the Environment that panicked, part of the [HealthReport](struct.HealthReport.html).
+///
+/// The loop of a panicked thread is run again, unless it panicked too often within a minute. A thread given up on
+/// expires the Environment with [ExpirationReason::Fatal](enum.ExpirationReason.html#variant.Fatal).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadHealth {
+    /// The name of the thread, e.g. ```actlib-timer``` or ```actlib-receive-<machine>```.
+    pub name: String,
+    /// How often the thread panicked since the Environment was created.
+    pub panics: usize,
+    /// The message of the latest panic.
+    pub last_panic: String,
+    /// When the thread panicked last.
+    pub last_panic_at: SystemTime,
+    /// ```false``` once the thread was given up on.
+    pub running: bool,
+}
+
+impl HealthReport {
+    /// ```true``` unless the machine [failed](enum.HealthStatus.html#variant.Failed), for liveness probes.
+    pub fn is_alive(&self) -> bool {
+        self.status != HealthStatus::Failed
+    }
+
+    /// ```true``` if the machine is [ready](enum.HealthStatus.html#variant.Ready), for readiness probes.
+    pub fn is_ready(&self) -> bool {
+        self.status == HealthStatus::Ready
+    }
+}
+
+/// Claim check for a payload [stashed](struct.Environment.html#method.stash_payload) on a machine.
+///
+/// Small enough to be sent in a message instead of the payload itself, the receiver [redeems](struct.Environment.html#method.redeem) it when needed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PayloadTicket {
+    /// Identifies the payload on its machine.
+    pub id: Uuid,
+    /// The machine holding the payload.
+    pub owner: MachineId,
+    /// Size of the payload in bytes.
+    pub size: usize,
+}
+
+/// A Message sent by [broadcast_sequenced](struct.Environment.html#method.broadcast_sequenced), numbered per sending machine.
+///
+/// The numbers of one *origin* increase by one with every sequenced broadcast, so an Actor that handled number 3
+/// and then number 5 missed number 4, e.g. because it was spawned in between or its mailbox was full.
+/// Register it with the [impl_message_handler!](../macro.impl_message_handler.html) macro like any other Message, e.g. ```Sequenced<Tick> => Field::tick```.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sequenced<M> {
+    /// The machine the broadcast was sent from.
+    pub origin: MachineId,
+    /// The number of the broadcast, starting at 1 on every machine.
+    pub seq: u64,
+    /// The broadcast Message.
+    pub message: M,
+}
+
+/// A Message sent by [ask](../actor/struct.ActorRef.html#method.ask), expecting a reply.
+///
+/// Register it with the [impl_message_handler!](../macro.impl_message_handler.html) macro like any other Message, e.g. ```Request<GetScore> => Player::get_score```,
+/// and answer it with [Environment::reply](struct.Environment.html#method.reply).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request<M> {
+    /// The Message asked with.
+    pub message: M,
+    pub(crate) correlation_id: u64,
+    pub(crate) reply_to: MachineId,
+}
+
+/// The outcome of asking one Actor in a [scatter_gather](struct.Environment.html#method.scatter_gather).
+#[derive(Debug)]
+pub struct Response<R> {
+    /// The Actor that was asked.
+    pub responder: ActorId,
+    /// The reply of the Actor, or why there is none, e.g. a [Timeout](enum.ActlibError.html#variant.Timeout).
+    pub reply: Result<R, ActlibError>,
+}
+
+impl<M: Keyed> Keyed for Request<M> {
+    type Key = M::Key;
+
+    fn key(&self) -> Self::Key {
+        self.message.key()
+    }
+}
+
+/// Records that an Actor was spawned on the local machine, and by whom.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageRecord {
+    /// The spawned Actor.
+    pub actor: ActorId,
+    /// The type id the Actor was built from.
+    pub type_id: String,
+    /// The Actor that spawned this Actor, ```None``` if it was spawned from outside an Actor.
+    pub spawner: Option<ActorId>,
+    /// ```false``` once the Actor was removed.
+    pub alive: bool,
+}
+
+/// Two machines held an Actor with the same specified id, reported to the [watchers](struct.Environment.html#method.watch_id_conflicts) of both machines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdConflict {
+    /// The specified id.
+    pub id: Vec<u8>,
+    /// The type id of the Actor on the local machine.
+    pub type_id: String,
+    /// The Actor spawned first.
+    pub older: ActorId,
+    /// The Actor spawned later.
+    pub younger: ActorId,
+}
+
+/// The Environment knows about all [Actors](../actor/trait.Actor.html) in the system.
+///
+/// It can [spawn](struct.Environment.html#method.spawn) new actors and construct an [ActorRef](../actor/struct.ActorRef.html) from an identifier using [to_actor_ref](struct.Environment.html#method.to_actor_ref) and [find_actor_ref](struct.Environment.html#method.find_actor_ref).
+///
+/// Using the associated [EnvironmentExpirationChecker](struct.EnvironmentExpirationChecker.html) you can block the main thread until the Environment is [set_expired](struct.Environment#method.set_expired).
+#[derive(Clone, Debug)]
+pub struct Environment {
+    pub(crate) env: ArcEnvironment,
+}
+
+impl Environment {
+    /// Create a new Environment with several remote sibling Environments.
+    ///
+    /// This method has to be called on every machine.
+    /// The local IP address is automatically filtered and ignored.
+    ///
+    /// *own_port* is used to establish a TCP-connection to remote machines.
+    /// This function blocks until a TCP-Connection to every remote host has been established,
+    /// unless the options say to [wait for fewer peers](struct.EnvironmentOptions.html#method.wait_for_peers).
+    ///
+    /// The returned [EnvironmentExpirationChecker](struct.EnvironmentExpirationChecker.html) can be used to block the main thread until the Environment is [set_expired](struct.Environment#method.set_expired).
+    ///
+    /// It is not possible to add machines that were not given here after creation of the environment.
+    pub fn new(
+        own_port: u16,
+        remotes: &[SocketAddr],
+        actor_builder: ActorBuilder,
+    ) -> (Self, EnvironmentExpirationChecker) {
+        Environment::new_with_options(
+            own_port,
+            remotes,
+            actor_builder,
+            EnvironmentOptions::default(),
+        )
+    }
+
+    /// Like [new](struct.Environment.html#method.new), but tuned by the given [EnvironmentOptions](struct.EnvironmentOptions.html).
+    pub fn new_with_options(
+        own_port: u16,
+        remotes: &[SocketAddr],
+        actor_builder: ActorBuilder,
+        options: EnvironmentOptions,
+    ) -> (Self, EnvironmentExpirationChecker) {
+        let peers: Vec<Peer> = remotes.iter().cloned().map(Peer::from).collect();
+        Environment::new_with_peers(own_port, &peers, actor_builder, options)
+    }
+
+    /// Like [new_with_options](struct.Environment.html#method.new_with_options), but the remote machines may be given by hostname.
+    ///
+    /// Hostnames are resolved again whenever a connection is established, and may resolve to IPv4 or IPv6 addresses.
+    /// Remote machines are identified by their [MachineId](struct.MachineId.html) exchanged on connect, not by their address.
+    pub fn new_with_peers(
+        own_port: u16,
+        peers: &[Peer],
+        actor_builder: ActorBuilder,
+        options: EnvironmentOptions,
+    ) -> (Self, EnvironmentExpirationChecker) {
+        match Environment::try_new_with_peers(own_port, peers, actor_builder, options) {
+            Ok(created) => created,
+            Err(e) => {
+                error!("{:?}", e);
+                panic!("{:?}", e);
+            }
+        }
+    }
+
+    /// Like [new_with_peers](struct.Environment.html#method.new_with_peers), but fails instead of panicking if the Environment cannot be created.
+    ///
+    /// Fails with [NetworkError](enum.ActlibError.html#variant.NetworkError) if a remote machine cannot join, e.g. because
+    /// it runs an incompatible build, or if the remote machines did not connect within the [connect_timeout](struct.EnvironmentOptions.html#method.connect_timeout).
+    pub fn try_new_with_peers(
+        own_port: u16,
+        peers: &[Peer],
+        actor_builder: ActorBuilder,
+        options: EnvironmentOptions,
+    ) -> Result<(Self, EnvironmentExpirationChecker), ActlibError> {
+        let (termination_sender, termination_receiver) = channel();
+        let env = LocalEnvironment::new(
+            own_port,
+            peers.to_vec(),
+            actor_builder,
+            options,
+            termination_sender,
+        )?;
+        Ok((
+            Environment { env },
+            EnvironmentExpirationChecker {
+                termination_receiver,
+            },
+        ))
+    }
+
+    /// Like [new](struct.Environment.html#method.new), but fails with [NetworkError](enum.ActlibError.html#variant.NetworkError)
+    /// instead of blocking forever if the remote machines did not connect within *timeout*.
+    ///
+    /// To start with the machines connected in time instead, pass a [ConnectTimeout](struct.ConnectTimeout.html)
+    /// that [starts a partial cluster](struct.ConnectTimeout.html#method.start_partial) to [try_new_with_peers](struct.Environment.html#method.try_new_with_peers).
+    pub fn new_with_timeout(
+        own_port: u16,
+        remotes: &[SocketAddr],
+        actor_builder: ActorBuilder,
+        timeout: Duration,
+    ) -> Result<(Self, EnvironmentExpirationChecker), ActlibError> {
+        let peers: Vec<Peer> = remotes.iter().cloned().map(Peer::from).collect();
+        Environment::try_new_with_peers(
+            own_port,
+            &peers,
+            actor_builder,
+            EnvironmentOptions::new().connect_timeout(ConnectTimeout::new(timeout)),
+        )
+    }
+
+    /// Like [new_with_peers](struct.Environment.html#method.new_with_peers), but the initial Actors are spawned by *bootstrap* before any machine returns.
+    ///
+    /// Once all machines are connected, *bootstrap* runs on exactly one of them, the [bootstrap machine](struct.Environment.html#method.is_bootstrap_machine).
+    /// It spawns the Actors every machine relies on, e.g. with [spawn_with_id](struct.Environment.html#method.spawn_with_id), so they can be found by their ids.
+    /// Every machine returns only after *bootstrap* finished and its spawn requests reached the machine,
+    /// so the code following this call can message the initial Actors on every machine.
+    ///
+    /// Every machine has to be connected to every other machine. This method blocks indefinitely if the bootstrap machine is lost before it finished.
+    pub fn new_with_bootstrap<F: FnOnce(&Environment)>(
+        own_port: u16,
+        peers: &[Peer],
+        actor_builder: ActorBuilder,
+        options: EnvironmentOptions,
+        bootstrap: F,
+    ) -> (Self, EnvironmentExpirationChecker) {
+        // the bootstrap machine is only known once every machine is connected
+        let mut options = options;
+        options.wait_for_peers = None;
+        if let Some(connect_timeout) = &mut options.connect_timeout {
+            connect_timeout.partial = false;
+        }
+        let (env, expiration_checker) =
+            Environment::new_with_peers(own_port, peers, actor_builder, options);
+        if env.is_bootstrap_machine() {
+            bootstrap(&env);
+            env.env.announce_bootstrapped();
+        } else {
+            env.env.wait_for_bootstrap();
+        }
+        (env, expiration_checker)
+    }
+
+    /// ```true``` if this machine runs the bootstrap of [new_with_bootstrap](struct.Environment.html#method.new_with_bootstrap).
+    ///
+    /// The bootstrap machine has the lowest [MachineId](struct.MachineId.html) of all machines,
+    /// set a [fixed id](struct.EnvironmentOptions.html#method.machine_id) to choose it.
+    pub fn is_bootstrap_machine(&self) -> bool {
+        self.env.bootstrap_machine() == self.env.machine_id
+    }
+
+    /// Like [new](struct.Environment.html#method.new), but the local Actors run on the given [Runtime](enum.Runtime.html).
+    ///
+    /// With [Runtime::Async](enum.Runtime.html#variant.Async) the Actors share the worker threads of a tokio runtime,
+    /// instead of occupying an OS thread each.
+    pub fn new_with_runtime(
+        own_port: u16,
+        remotes: &[SocketAddr],
+        actor_builder: ActorBuilder,
+        runtime: Runtime,
+    ) -> (Self, EnvironmentExpirationChecker) {
+        Environment::new_with_options(
+            own_port,
+            remotes,
+            actor_builder,
+            EnvironmentOptions::new().runtime(runtime),
+        )
+    }
+
+    /// Run the *future* to completion on the current thread, e.g. to await a [ResponseFuture](../actor/struct.ResponseFuture.html) in a handler.
+    ///
+    /// On the async [Runtime](enum.Runtime.html) the worker thread is handed over to the other Actors meanwhile,
+    /// and the future may use the tokio timers and IO. Elsewhere the current thread is parked until the future is woken,
+    /// on the worker pool a spare thread runs the other Actors meanwhile.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        block_on(future)
+    }
+
+    /// Send *message* to *target* once *delay* has passed, without blocking the current thread.
+    ///
+    /// All timers of the Environment share a single thread. Cancel the message with the returned [TimerHandle](struct.TimerHandle.html),
+    /// pending messages are cancelled once the Environment [expires](struct.Environment.html#method.set_expired).
+    pub fn schedule_once<'de, M: Message<'de> + 'static>(
+        &self,
+        target: &ActorRef,
+        message: M,
+        delay: Duration,
+    ) -> TimerHandle {
+        target.send_delayed_message(message, delay)
+    }
+
+    /// Send a clone of *message* to *target* every *interval*, the first one after an *interval* has passed.
+    ///
+    /// The messages stop once the returned [TimerHandle](struct.TimerHandle.html) is cancelled,
+    /// a message cannot be sent, e.g. because *target* stopped, or the Environment [expires](struct.Environment.html#method.set_expired).
+    pub fn schedule_periodic<'de, M: Message<'de> + Clone + 'static>(
+        &self,
+        target: &ActorRef,
+        message: M,
+        interval: Duration,
+    ) -> TimerHandle {
+        target.send_periodic_message(message, interval)
+    }
+
+    /// Like [new](struct.Environment.html#method.new), but without the ability to specify additional remote machines.
+    pub fn new_local_only(actor_builder: ActorBuilder) -> (Self, EnvironmentExpirationChecker) {
+        Environment::new(0, &Vec::with_capacity(0), actor_builder)
+    }
+
+    /// Describe this Environment: the local address, the remote machines, the known Actor types and the library version.
+    ///
+    /// The library does not print anything on its own, use this to tell the user about the Environment.
+    pub fn info(&self) -> EnvironmentInfo {
+        self.env.info()
+    }
+
+    /// The connection state of every configured remote machine, in the order they were given.
+    ///
+    /// Remote machines the Environment did not [wait for](struct.EnvironmentOptions.html#method.wait_for_peers)
+    /// are ```Connecting``` until they are attached in the background.
+    pub fn peer_status(&self) -> Vec<PeerStatus> {
+        self.env.peer_status()
+    }
+
+    /// Every machine of the Environment with a known identity, the local machine first.
+    ///
+    /// Remote machines that did not introduce themselves yet are missing, see [peer_status](struct.Environment.html#method.peer_status).
+    pub fn machines(&self) -> Vec<MachineRef> {
+        self.env.machines()
+    }
+
+    /// Get notified whenever a remote machine connects late, loses its connection, reconnects or fails to connect.
+    pub fn watch_peers(&self) -> Receiver<PeerStatus> {
+        self.env.watch_peers()
+    }
+
+    /// Send a ping to the given machine, returning the round trip time once it answered.
+    ///
+    /// Fails with [NetworkError](enum.ActlibError.html#variant.NetworkError) if the machine is not connected,
+    /// or with [Timeout](enum.ActlibError.html#variant.Timeout) if it did not answer within *timeout*.
+    /// Either way the machine counts as down in the [cluster status](struct.Environment.html#method.cluster_status) until it answers a ping again.
+    ///
+    /// Accepts [MachineIds](../actor/struct.MachineId.html) as well as the [MachineRefs](struct.MachineRef.html) returned by [machines](struct.Environment.html#method.machines).
+    pub fn ping_machine<M: Into<MachineId>>(
+        &self,
+        machine: M,
+        timeout: Duration,
+    ) -> Result<Duration, ActlibError> {
+        self.env.ping_machine(machine.into(), timeout)
+    }
+
+    /// Whether every configured remote machine is up, in the order they were given.
+    ///
+    /// A machine is down while it is not connected, e.g. because it never came up, and after it missed
+    /// the pings of the [heartbeat](struct.EnvironmentOptions.html#method.heartbeat) or of [ping_machine](struct.Environment.html#method.ping_machine).
+    pub fn cluster_status(&self) -> Vec<MachineStatus> {
+        self.env.cluster_status()
+    }
+
+    /// Count the live Actors, mailbox backlogs, handled and remote messages, spawns and removals of the local machine.
+    ///
+    /// See [prometheus_exporter](struct.EnvironmentOptions.html#method.prometheus_exporter) to scrape them.
+    pub fn metrics(&self) -> Metrics {
+        self.env.metrics()
+    }
+
+    /// Describe the Actors living on the local machine: their type, spawner and mailbox.
+    pub fn introspect(&self) -> Introspection {
+        self.env.introspect()
+    }
+
+    /// Get notified about every conflicting specified id the local machine resolved.
+    ///
+    /// Conflicts are only detected if [id conflict detection](struct.EnvironmentOptions.html#method.id_conflict_detection) is enabled.
+    pub fn watch_id_conflicts(&self) -> Receiver<IdConflict> {
+        self.env.watch_id_conflicts()
+    }
+
+    /// Get notified about every local Actor whose mailbox exceeds the [MailboxAlerts](struct.MailboxAlerts.html) thresholds.
+    ///
+    /// Alerts are only raised if [mailbox alerts](struct.EnvironmentOptions.html#method.mailbox_alerts) are configured.
+    pub fn watch_mailbox_alerts(&self) -> Receiver<MailboxAlert> {
+        self.env.watch_mailbox_alerts()
+    }
+
+    /// Check the health of the local machine: poisoned locks, lost remote machines, panicked Actors and mailbox backlogs.
+    ///
+    /// Cheap enough to be polled by liveness and readiness probes.
+    pub fn health(&self) -> HealthReport {
+        self.env.health()
+    }
+
+    /// Get notified about every local Actor that panicked.
+    ///
+    /// What happens to the Actor afterwards is decided by the [FailurePolicy](struct.EnvironmentOptions.html#method.failure_policy).
+    pub fn watch_actor_failures(&self) -> Receiver<ActorFailed> {
+        self.env.watch_actor_failures()
+    }
+
+    /// Get notified about every Message the local machine dropped following [DeadPeerPolicy::DeadLetter](enum.DeadPeerPolicy.html#variant.DeadLetter),
+    /// or because a [respawn](enum.DeadPeerPolicy.html#variant.Respawn) failed.
+    pub fn watch_dead_letters(&self) -> Receiver<DeadLetter> {
+        self.env.watch_dead_letters()
+    }
 
-impl Environment {
-    /// Create a new Environment with several remote sibling Environments.
+    /// Get notified about every Message or ActorRef lookup addressed to an earlier incarnation of a local keyed Actor,
+    /// unless the [IncarnationPolicy](enum.IncarnationPolicy.html) allows them.
+    pub fn watch_stale_incarnations(&self) -> Receiver<StaleIncarnation> {
+        self.env.watch_stale_incarnations()
+    }
+
+    /// Get notified about every Message to a remote Actor that was rejected or never acknowledged,
+    /// requires [reliable delivery](struct.EnvironmentOptions.html#method.reliable_delivery).
+    pub fn watch_delivery_failures(&self) -> Receiver<DeliveryFailure> {
+        self.env.watch_delivery_failures()
+    }
+
+    /// Get notified about every Message the local machine dropped without handling it, whatever the [cause](enum.DropCause.html).
+    pub fn watch_dropped_messages(&self) -> Receiver<MessageDropped> {
+        self.env.watch_dropped_messages()
+    }
+
+    /// Send a [ClusterLoad](struct.ClusterLoad.html) message to the Actor of *subscriber* after every load measurement.
     ///
-    /// This method has to be called on every machine.
-    /// The local IP address is automatically filtered and ignored.
+    /// The load is only measured if the [load exchange](struct.EnvironmentOptions.html#method.load_exchange) is enabled.
+    /// The subscription ends with [unsubscribe_cluster_load](struct.Environment.html#method.unsubscribe_cluster_load) or once the Actor is gone.
+    pub fn subscribe_cluster_load(&self, subscriber: &ActorRef) {
+        self.env.subscribe_cluster_load(subscriber.clone())
+    }
+
+    /// End the subscription of the given Actor.
+    pub fn unsubscribe_cluster_load(&self, subscriber: &ActorId) {
+        self.env.unsubscribe_cluster_load(subscriber)
+    }
+
+    /// Send a [LifecycleEvent](enum.LifecycleEvent.html) to the Actor of *subscriber* whenever an Actor of type *type_id* is spawned or stopped on any machine.
     ///
-    /// *own_port* is used to establish a TCP-connection to remote machines.
-    /// This function blocks until a TCP-Connection to every remote host has been established.
+    /// Only Actors spawned after the subscription are reported, [introspect](struct.Environment.html#method.introspect) lists the local ones alive already.
+    /// The subscription ends with [unsubscribe_lifecycle](struct.Environment.html#method.unsubscribe_lifecycle) or once a machine fails to deliver an event to the Actor.
+    pub fn subscribe_lifecycle(&self, type_id: &str, subscriber: &ActorRef) {
+        self.env.subscribe_lifecycle(type_id, subscriber.clone())
+    }
+
+    /// End the lifecycle subscription of the given Actor on every machine.
+    pub fn unsubscribe_lifecycle(&self, type_id: &str, subscriber: &ActorId) {
+        self.env.unsubscribe_lifecycle(type_id, subscriber)
+    }
+
+    /// The latest load of every machine, empty if the [load exchange](struct.EnvironmentOptions.html#method.load_exchange) is disabled.
+    pub fn cluster_load(&self) -> ClusterLoad {
+        self.env.cluster_load()
+    }
+
+    /// The cluster-wide [Counter](../counters/struct.Counter.html) of the given name, every machine adds its share to it.
     ///
-    /// The returned [EnvironmentExpirationChecker](struct.EnvironmentExpirationChecker.html) can be used to block the main thread until the Environment is [set_expired](struct.Environment#method.set_expired).
+    /// The value is eventually consistent, the shares of the remote machines arrive every [counter interval](struct.EnvironmentOptions.html#method.counter_interval).
+    pub fn counter(&self, name: &str) -> Counter {
+        Counter::new(name, Arc::downgrade(&self.env.counters))
+    }
+
+    /// The cluster-wide [Gauge](../counters/struct.Gauge.html) of the given name, the sum of the shares every machine sets.
     ///
-    /// It is not possible to add new machines after creation of the environment.
-    pub fn new(
-        own_port: u16,
-        remotes: &[SocketAddr],
-        actor_builder: fn(&str) -> Result<Box<dyn Actor>, ActlibError>,
-    ) -> (Self, EnvironmentExpirationChecker) {
-        let (termination_sender, termination_receiver) = channel();
-        (
-            Environment {
-                env: LocalEnvironment::new(
-                    own_port,
-                    remotes.to_vec(),
-                    actor_builder,
-                    termination_sender,
-                ),
-            },
-            EnvironmentExpirationChecker {
-                termination_receiver,
-            },
-        )
+    /// The value is eventually consistent, the shares of the remote machines arrive every [counter interval](struct.EnvironmentOptions.html#method.counter_interval).
+    pub fn gauge(&self, name: &str) -> Gauge {
+        Gauge::new(name, Arc::downgrade(&self.env.counters))
     }
 
-    /// Like [new](struct.Environment.html#method.new), but without the ability to specify additional remote machines.
-    pub fn new_local_only(
-        actor_builder: fn(&str) -> Result<Box<dyn Actor>, ActlibError>,
-    ) -> (Self, EnvironmentExpirationChecker) {
-        Environment::new(0, &Vec::with_capacity(0), actor_builder)
+    /// The values of all [Counters and Gauges](../counters/index.html) used anywhere in the cluster, as far as this machine knows them.
+    pub fn counter_snapshot(&self) -> CounterSnapshot {
+        self.env.counters.snapshot()
+    }
+
+    /// Forward the messages sent to *from_id* to the Actor of *to_ref* for the given *ttl*,
+    /// e.g. after the Actor was migrated, restarted with a new id or moved by a rebalance.
+    ///
+    /// The entry is kept on the machine *from_id* lives on and takes effect once no Actor with *from_id* exists anymore.
+    /// Messages arriving from remote machines and [ActorRefs](../actor/struct.ActorRef.html) created by [to_actor_ref](struct.Environment.html#method.to_actor_ref) afterwards are forwarded,
+    /// ActorRefs to the replaced Actor created before it stopped are not.
+    /// Tokens like [Reset](../actor/struct.ActorRef.html#method.send_reset_message) are not forwarded.
+    ///
+    /// Fails with [InvalidState](enum.ActlibError.html#variant.InvalidState) if the aliases would forward messages in a loop.
+    pub fn alias(
+        &self,
+        from_id: ActorId,
+        to_ref: &ActorRef,
+        ttl: Duration,
+    ) -> Result<(), ActlibError> {
+        self.env.alias(from_id, to_ref.clone_id(), ttl)
+    }
+
+    /// Listen on *port* of every local address for connections from outside of the Environment, and send each
+    /// accepted connection as an [ExternalConnection](struct.ExternalConnection.html) to the local *handler*.
+    /// Returns the bound port, which differs from *port* if it is 0.
+    ///
+    /// Registering a port this machine listens on already replaces the handler, the socket stays bound.
+    /// While a handler with a user specified id is missing, e.g. being respawned, clients wait in the backlog of the socket;
+    /// the listener is closed once a handler with an automatically created id stops.
+    ///
+    /// Fails with [NetworkError](enum.ActlibError.html#variant.NetworkError) if the port cannot be bound,
+    /// and with [InvalidActorRef](enum.ActlibError.html#variant.InvalidActorRef) if the handler lives on another machine.
+    ///
+    /// ```rust,ignore
+    /// fn serve(collector: &mut CollectingActor, connection: &ExternalConnection) {
+    ///     if let Some(mut stream) = connection.take_stream() {
+    ///         let _ = stream.write_all(&collector.serialized_state());
+    ///     }
+    /// }
+    /// ```
+    pub fn register_external_listener(
+        &self,
+        port: u16,
+        handler: &ActorRef,
+    ) -> Result<u16, ActlibError> {
+        LocalEnvironment::register_external_listener(&self.env, port, handler.clone_id())
+    }
+
+    /// Stop listening on a port registered by [register_external_listener](struct.Environment.html#method.register_external_listener),
+    /// returning whether this machine listened on it.
+    pub fn unregister_external_listener(&self, port: u16) -> bool {
+        self.env.unregister_external_listener(port)
+    }
+
+    /// Pass the [external listener](struct.Environment.html#method.register_external_listener) on *port* to *handler*,
+    /// e.g. the Actor that replaced its handler on another machine.
+    ///
+    /// A handler on another machine listens on the same port of its machine. The local socket keeps serving the
+    /// current handler until the other machine listens, and is closed afterwards. A local handler takes over the socket as it is.
+    ///
+    /// Fails with [InvalidState](enum.ActlibError.html#variant.InvalidState) if the local machine does not listen on the port,
+    /// and with [NetworkError](enum.ActlibError.html#variant.NetworkError) or [Timeout](enum.ActlibError.html#variant.Timeout)
+    /// if the other machine does not listen on it, in which case the local socket stays as it is.
+    pub fn hand_over_external_listener(
+        &self,
+        port: u16,
+        handler: &ActorRef,
+    ) -> Result<(), ActlibError> {
+        LocalEnvironment::hand_over_external_listener(&self.env, port, handler.clone_id())
+    }
+
+    /// Register *name* for the Actor behind *actor_ref* on every machine, so it can be found by [lookup_name](struct.Environment.html#method.lookup_name).
+    ///
+    /// The name is removed once the Actor stops. Machines connecting later learn about it when they connect.
+    /// If two machines register the same name for different Actors at the same time, every machine settles on the lower ActorId.
+    ///
+    /// Fails with [InvalidState](enum.ActlibError.html#variant.InvalidState) if the name already refers to another Actor.
+    pub fn register_name(&self, name: &str, actor_ref: &ActorRef) -> Result<(), ActlibError> {
+        self.env.register_name(name, actor_ref.clone_id())
+    }
+
+    /// Remove a [registered name](struct.Environment.html#method.register_name) on every machine,
+    /// returning the Actor it referred to.
+    pub fn unregister_name(&self, name: &str) -> Option<ActorId> {
+        self.env.unregister_name(name)
+    }
+
+    /// The Actor [registered](struct.Environment.html#method.register_name) under *name*.
+    ///
+    /// No remote machine is asked, a name registered on a remote machine is known once its announcement arrived.
+    pub fn lookup_name(&self, name: &str) -> Option<ActorRef> {
+        let actor_id = self.env.lookup_name(name)?;
+        match self.env.to_actor_ref(actor_id) {
+            Ok(actor_ref) => Some(actor_ref),
+            Err(e) => {
+                log_err_as!(warn, e);
+                None
+            }
+        }
+    }
+
+    /// The handled messages per Actor type and message type on the local machine, since the Environment was created.
+    ///
+    /// Empty unless [handler statistics](struct.EnvironmentOptions.html#method.handler_stats) are enabled.
+    /// Also part of the [Introspection](struct.Introspection.html) and the state dumps.
+    pub fn handler_stats(&self) -> Vec<HandlerStats> {
+        self.env.handler_stats()
+    }
+
+    /// The time from the asks to their replies per requester type and responder type, the pairs with the most time first.
+    ///
+    /// Tells which Actor interactions dominate the end-to-end latency.
+    /// Empty unless [reply tracing](struct.EnvironmentOptions.html#method.trace_replies) is enabled.
+    pub fn reply_stats(&self) -> Vec<ReplyStats> {
+        self.env.reply_stats()
+    }
+
+    /// The forwarding entries kept on the local machine, with the number of messages they forwarded.
+    pub fn aliases(&self) -> Vec<AliasStats> {
+        self.env.aliases()
+    }
+
+    /// Keep a large payload on the local machine and return a [PayloadTicket](struct.PayloadTicket.html) to send instead of it.
+    ///
+    /// This keeps mailboxes and the connections between machines free of large messages nobody may ever read.
+    /// The payload is kept for the [payload lease](struct.EnvironmentOptions.html#method.payload_lease),
+    /// or until it is [released](struct.Environment.html#method.release_payload).
+    pub fn stash_payload(&self, payload: Vec<u8>) -> PayloadTicket {
+        self.env.stash_payload(payload)
+    }
+
+    /// Get the payload of a ticket, it stays stashed for other holders of the ticket.
+    ///
+    /// If the payload is kept on a remote machine, it is pulled from there and the current thread blocks until it arrived.
+    ///
+    /// The method can fail with [InvalidState](enum.ActlibError.html#variant.InvalidState) if the payload was released or its lease expired,
+    /// [Timeout](enum.ActlibError.html#variant.Timeout) or [NetworkError](enum.ActlibError.html#variant.NetworkError).
+    pub fn redeem(&self, ticket: &PayloadTicket) -> Result<Vec<u8>, ActlibError> {
+        self.env.redeem(ticket)
+    }
+
+    /// Drop the payload of a ticket before its lease expires, on whichever machine it is kept.
+    pub fn release_payload(&self, ticket: &PayloadTicket) -> Result<(), ActlibError> {
+        self.env.release_payload(ticket)
     }
 
     /// Spawn a given [Actor](../actor/trait.Actor.html) object inside this Environment.
@@ -97,8 +1764,74 @@ impl Environment {
     ///
     /// The return value is an [ActorRef](../actor/struct.ActorRef.html) object as the [Actor](../actor/trait.Actor.html) address.
     /// Use it to send messages to the now alive [Actor](../actor/trait.Actor.html).
+    ///
+    /// A spawn on a remote machine blocks until the machine acknowledged it. Fails with [SpawnFailed](enum.ActlibError.html#variant.SpawnFailed)
+    /// if the remote machine could not spawn the Actor, e.g. because it does not know the type, or with
+    /// [Timeout](enum.ActlibError.html#variant.Timeout) if it did not answer in time.
     pub fn spawn(&self, actor_type_id: &str) -> Result<ActorRef, ActlibError> {
-        LocalEnvironment::spawn(self.clone(), actor_type_id, SpawnId::Automatic)
+        self.spawn_with_options(actor_type_id, SpawnOptions::default())
+    }
+
+    /// Like [spawn](struct.Environment.html#method.spawn), but returns a [TypedActorRef](../actor/struct.TypedActorRef.html) only sending the Messages *A* handles.
+    ///
+    /// Fails with [InvalidState](enum.ActlibError.html#variant.InvalidState) before spawning, if the [actor_builder](../macro.actor_builder.html)
+    /// recorded other message types for the type id than *A* handles.
+    pub fn spawn_typed<A: Actor>(
+        &self,
+        actor_type_id: &str,
+    ) -> Result<TypedActorRef<A>, ActlibError> {
+        if !self.env.actor_builder.handles_like::<A>(actor_type_id) {
+            return Err(ActlibError::InvalidState(format!(
+                "{} does not build Actors handling the message types of {}",
+                actor_type_id,
+                std::any::type_name::<A>()
+            )));
+        }
+        self.spawn(actor_type_id).map(ActorRef::typed)
+    }
+
+    /// Like [spawn](struct.Environment.html#method.spawn), but the new Actor is parameterized with *args*,
+    /// passed serialized to its [on_init](../actor/trait.Actor.html#method.on_init) before it starts, also on a remote machine.
+    ///
+    /// Fails with [SpawnFailed](enum.ActlibError.html#variant.SpawnFailed) if on_init panicked, e.g. because the arguments do not fit the Actor.
+    pub fn spawn_with_args<T: Serialize>(
+        &self,
+        actor_type_id: &str,
+        args: T,
+    ) -> Result<ActorRef, ActlibError> {
+        let args = bincode::serialize(&args).map_err(|e| {
+            ActlibError::InvalidState(format!(
+                "Failed to serialize the arguments for {}: {:?}",
+                actor_type_id, e
+            ))
+        })?;
+        let options = SpawnOptions {
+            args: Some(args),
+            ..SpawnOptions::default()
+        };
+        LocalEnvironment::spawn(self.clone(), actor_type_id, SpawnId::Automatic, &options)
+    }
+
+    /// Like [spawn](struct.Environment.html#method.spawn), but the machine is picked respecting the given [SpawnOptions](struct.SpawnOptions.html).
+    ///
+    /// Fails with [SpawnFailed](enum.ActlibError.html#variant.SpawnFailed) if the options exclude every machine.
+    pub fn spawn_with_options(
+        &self,
+        actor_type_id: &str,
+        options: SpawnOptions,
+    ) -> Result<ActorRef, ActlibError> {
+        LocalEnvironment::spawn(self.clone(), actor_type_id, SpawnId::Automatic, &options)
+    }
+
+    /// Like [spawn](struct.Environment.html#method.spawn), but the Actor is placed on the given machine.
+    ///
+    /// Fails with [SpawnFailed](enum.ActlibError.html#variant.SpawnFailed) if the machine is not connected.
+    pub fn spawn_on(
+        &self,
+        actor_type_id: &str,
+        machine: &MachineRef,
+    ) -> Result<ActorRef, ActlibError> {
+        self.spawn_with_options(actor_type_id, SpawnOptions::new().on_machine(machine))
     }
 
     /// Like [spawn](struct.Environment.html#method.spawn), but the Actor is guaranteed to execute code only on the local machine.
@@ -107,6 +1840,7 @@ impl Environment {
             self.clone(),
             actor_type_id,
             SpawnId::SpawnHere(LocalId::Automatic(Uuid::new_v4())),
+            &SpawnOptions::default(),
         )
     }
 
@@ -116,6 +1850,9 @@ impl Environment {
     /// You can retrieve it from an associated ActorRef using ```actor_ref.clone_id().when_specified()```.
     ///
     /// Once spawned, the Actor can be found with [find_actor_ref](struct.Environment.html#method.find_actor_ref) until it is [removed](struct.Environment.html#method.remove).
+    ///
+    /// Spawning an id that is already alive returns the existing Actor if it has the same type, and fails with
+    /// [InvalidId](enum.ActlibError.html#variant.InvalidId) otherwise. Duplicated spawn requests from remote machines are handled the same way.
     pub fn spawn_local_with_id(
         &self,
         actor_type_id: &str,
@@ -125,6 +1862,7 @@ impl Environment {
             self.clone(),
             actor_type_id,
             SpawnId::SpawnHere(LocalId::Specified(actor_id)),
+            &SpawnOptions::default(),
         )
     }
 
@@ -142,39 +1880,153 @@ impl Environment {
             self.clone(),
             actor_type_id,
             SpawnId::User(LocalId::Specified(actor_id)),
+            &SpawnOptions::default(),
+        )
+    }
+
+    /// Spawn a pool of *size* Actors of the given type like [spawn](struct.Environment.html#method.spawn), behind a router Actor on the local machine
+    /// that distributes the messages sent to it over the workers following the [RouterKind](enum.RouterKind.html).
+    ///
+    /// Returns the ActorRef of the router, to send or [ask](../actor/struct.ActorRef.html#method.ask) the pool like a single Actor,
+    /// to [resize](struct.Environment.html#method.resize_pool) the pool or to [remove](struct.Environment.html#method.remove) it with all its workers.
+    /// See the [router](../router/index.html) module.
+    ///
+    /// Fails like [spawn](struct.Environment.html#method.spawn), the workers spawned so far are removed then.
+    pub fn spawn_pool(
+        &self,
+        actor_type_id: &str,
+        size: usize,
+        kind: RouterKind,
+    ) -> Result<ActorRef, ActlibError> {
+        let mut workers = Vec::with_capacity(size);
+        let mut started = Ok(());
+        while workers.len() < size && started.is_ok() {
+            match self.spawn(actor_type_id) {
+                Ok(worker) => workers.push(worker),
+                Err(e) => started = Err(e),
+            }
+        }
+        let router = started.and_then(|_| {
+            LocalEnvironment::start_local_actor(
+                self,
+                ROUTER_TYPE_ID,
+                Box::new(Router::new(actor_type_id, kind, workers.clone())),
+                LocalId::Automatic(Uuid::new_v4()),
+                current_actor(),
+                &SpawnOptions {
+                    serialized_mailbox: true,
+                    ..SpawnOptions::default()
+                },
+            )
+        });
+        if router.is_err() {
+            let mut env = self.clone();
+            for worker in workers {
+                env.remove(worker);
+            }
+        }
+        router
+    }
+
+    /// Grow or shrink a pool [spawned](struct.Environment.html#method.spawn_pool) on any machine to *size* workers.
+    ///
+    /// The router spawns or removes the workers once it handled the messages sent to the pool before, the workers spawned last are removed first.
+    pub fn resize_pool(&self, pool: &ActorRef, size: usize) -> Result<(), ActlibError> {
+        pool.send_message(ResizePool(size))
+    }
+
+    /// The workers of a pool [spawned](struct.Environment.html#method.spawn_pool) on this machine, oldest first.
+    ///
+    /// Fails like [query](../actor/struct.ActorRef.html#method.query), e.g. if *pool* is no local router.
+    pub fn pool_workers(&self, pool: &ActorRef) -> Result<Vec<ActorRef>, ActlibError> {
+        pool.query(
+            |router: &Router| router.workers.clone(),
+            Duration::from_secs(5),
         )
     }
 
     /// Remove the specified Actor from the Environment.
     ///
-    /// The [on_stop](../actor/trait.Actor#tymethod.on_stop) method is called.
+    /// The [on_stop_with_reason](../actor/trait.Actor#method.on_stop_with_reason) method is called with [StopReason::Removed](../actor/enum.StopReason.html).
     /// Afterwards, the Actor can't react to any new [Messages](../message/trait.Message.html).
+    ///
+    /// If [Capability::Remove](../options/enum.Capability.html#variant.Remove) is restricted, an Actor without it can only remove itself.
     pub fn remove(&mut self, actor_ref: ActorRef) {
+        if current_actor() != Some(actor_ref.clone_id()) {
+            if let Err(e) = self.env.check_capability(Capability::Remove) {
+                log_err_as!(error, e);
+                return;
+            }
+        }
         match &actor_ref.sender {
             ActorRefChannel::Local(s) => {
-                s.send(EitherMessage::Special(Token::Stop));
-            }
-            ActorRefChannel::Remote(s) => match bincode::serialize(&Token::Stop) {
-                Ok(token_serialized) => {
-                    match s.send((
-                        actor_ref.clone_id(),
-                        SerNetMessageContent::Token(token_serialized),
-                    )) {
-                        Ok(_) => {}
-                        Err(e) => log_err_as!(
-                            err,
-                            ActlibError::NetworkError(format!(
-                                "Failed to send Stop token: {:?}",
-                                e
-                            ))
-                        ),
+                let _ = s.send(EitherMessage::Special(Token::Stop(StopReason::Removed)), 0);
+            }
+            ActorRefChannel::Remote(s) => {
+                match actor_ref
+                    .wire_format
+                    .serialize(&Token::Stop(StopReason::Removed))
+                {
+                    Ok(token_serialized) => {
+                        match s.send((
+                            actor_ref.clone_id(),
+                            SerNetMessageContent::Token(token_serialized, current_actor()),
+                        )) {
+                            Ok(_) => {}
+                            Err(e) => log_err_as!(
+                                err,
+                                ActlibError::NetworkError(format!(
+                                    "Failed to send Stop token: {:?}",
+                                    e
+                                ))
+                            ),
+                        }
                     }
+                    Err(e) => log_err_as!(
+                        err,
+                        ActlibError::NetworkError(format!("Failed to send Stop token: {:?}", e))
+                    ),
                 }
-                Err(e) => log_err_as!(
-                    err,
+            }
+        }
+    }
+
+    /// Remove the specified Actor like [remove](struct.Environment.html#method.remove), forwarding its messages to *successor*
+    /// instead of dropping them, e.g. to replace a misbehaving Actor without losing the traffic in flight.
+    ///
+    /// The messages still queued when the Actor stops are forwarded regardless of its [StopPolicy](../options/enum.StopPolicy.html),
+    /// the messages arriving afterwards are forwarded by an [alias](struct.Environment.html#method.alias) for the *grace* period.
+    /// Messages passed to a local Actor without serialization only reach a successor on the same machine,
+    /// the others are reported as [dropped](struct.Environment.html#method.watch_dropped_messages).
+    ///
+    /// Fails with [InvalidState](enum.ActlibError.html#variant.InvalidState) if the forwarding would loop,
+    /// and like [alias](struct.Environment.html#method.alias) if the Actor lives on an unreachable machine.
+    /// If [Capability::Remove](../options/enum.Capability.html#variant.Remove) is restricted, an Actor without it can only remove itself.
+    pub fn remove_and_forward(
+        &mut self,
+        actor_ref: ActorRef,
+        successor: &ActorRef,
+        grace: Duration,
+    ) -> Result<(), ActlibError> {
+        if current_actor() != Some(actor_ref.clone_id()) {
+            self.env.check_capability(Capability::Remove)?;
+        }
+        // the alias takes over once the Actor is gone, so no message slips through in between
+        self.env
+            .alias(actor_ref.clone_id(), successor.clone_id(), grace)?;
+        let token = Token::StopAndForward(successor.clone_id());
+        match &actor_ref.sender {
+            ActorRefChannel::Local(s) => s.send(EitherMessage::Special(token), 0),
+            ActorRefChannel::Remote(s) => {
+                let token_serialized = actor_ref.wire_format.serialize(&token)?;
+                s.send((
+                    actor_ref.clone_id(),
+                    SerNetMessageContent::Token(token_serialized, current_actor()),
+                ))
+                .map_err(|e| {
                     ActlibError::NetworkError(format!("Failed to send Stop token: {:?}", e))
-                ),
-            },
+                })
+            }
         }
     }
 
@@ -186,6 +2038,18 @@ impl Environment {
         self.env.to_actor_ref(actor_id)
     }
 
+    /// Re-resolve the best channel for a long-lived [ActorRef](../actor/struct.ActorRef.html), e.g. after its Actor was migrated.
+    ///
+    /// If the Actor lives on the local machine, the returned ActorRef sends to its mailbox directly,
+    /// even if *actor_ref* was created for a remote machine, which is possible for User-specified ids.
+    /// If the Actor was replaced and [aliased](struct.Environment.html#method.alias) on the local machine,
+    /// the returned ActorRef points to the replacement instead, skipping the forwarding.
+    ///
+    /// No remote machine is asked, so otherwise *actor_ref* is returned unchanged.
+    pub fn refresh(&self, actor_ref: ActorRef) -> ActorRef {
+        self.env.refresh(actor_ref)
+    }
+
     /// Create the ActorRef for an alive Actor with a User-specified ActorId.
     ///
     /// First, check if the Actor is located locally. If not try every known remote machine.
@@ -195,32 +2059,87 @@ impl Environment {
     /// * *searcher* is the Actor querying the ActorRef.
     /// * *protect* ensures that the specified Actor, if it exists, will not be removed from its environment
     /// until the [drop_protector](struct.Environment.html#method.drop_protector) method is called with the *searcher* as *protector_id*.
+    ///
+    /// With [Placement::ConsistentHash](../options/enum.Placement.html#variant.ConsistentHash) only the machine the id hashes to is asked at first,
+    /// every machine only if the Actor was placed elsewhere, e.g. by [spawn_local_with_id](struct.Environment.html#method.spawn_local_with_id).
     pub fn find_actor_ref(
         &self,
         queried_id: &Vec<u8>,
         searcher: ActorId,
         protect: bool,
+    ) -> Result<Option<ActorRef>, ActlibError> {
+        if let Some(owner) = self.env.remote_owner_of_id(queried_id) {
+            let found = self.query_actor_ref(queried_id, &searcher, protect, Some(owner))?;
+            if found.is_some() {
+                return Ok(found);
+            }
+        }
+        self.query_actor_ref(queried_id, &searcher, protect, None)
+    }
+
+    /// Ask the local machine and then every remote machine, or only *only_machine*, for the Actor with the given specified id.
+    fn query_actor_ref(
+        &self,
+        queried_id: &Vec<u8>,
+        searcher: &ActorId,
+        protect: bool,
+        only_machine: Option<MachineId>,
     ) -> Result<Option<ActorRef>, ActlibError> {
         let (receiver, num_remotes) =
-            match self
-                .env
-                .find_actor_ref(queried_id, searcher.clone(), protect)
-            {
-                Ok((receiver, num_remotes)) => (receiver, num_remotes),
-                Err(e) => {
-                    return Err(e);
-                }
-            };
-        let mut result: Result<Option<ActorRef>, ActlibError> = Ok(None);
+            self.env
+                .find_actor_ref(queried_id, searcher.clone(), protect, only_machine)?;
         // listen for the answer of each remote machine
-        for _ in 0..num_remotes {
-            if let Ok(Some(actor_ref)) = receiver.recv() {
-                result = Ok(Some(actor_ref));
-                break;
+        let result = scheduler::blocking(|| {
+            for _ in 0..num_remotes {
+                if let Ok(Some(actor_ref)) = receiver.recv() {
+                    return Some(actor_ref);
+                }
             }
+            None
+        });
+        self.env.remove_remote_query(queried_id, searcher.clone());
+        Ok(result)
+    }
+
+    /// The machine an Actor [spawned with the id](struct.Environment.html#method.spawn_with_id) lives on
+    /// with [Placement::ConsistentHash](../options/enum.Placement.html#variant.ConsistentHash), computed without asking the other machines.
+    ///
+    /// Machines connected to the same machines compute the same machine for an id.
+    /// Only the ids of a machine joining or leaving the Environment change their machine.
+    pub fn machine_for_id(&self, actor_id: &[u8]) -> MachineId {
+        self.env
+            .machine_for_id(actor_id, &[])
+            .unwrap_or(self.env.machine_id)
+    }
+
+    /// Find the Actor with the given specified id, spawning it with [spawn_with_id](struct.Environment.html#method.spawn_with_id) if it does not exist.
+    ///
+    /// A new Actor is placed on the machine whose Actors recently looked up or messaged the id most often,
+    /// to keep its conversations local. If no machine is known, the *preferred_location* is used, then the regular placement.
+    ///
+    /// **Note:** Two machines calling this concurrently for the same id may both spawn the Actor,
+    /// see [id conflict detection](struct.EnvironmentOptions.html#method.id_conflict_detection).
+    pub fn find_or_spawn_near(
+        &self,
+        actor_type_id: &str,
+        key: Vec<u8>,
+        preferred_location: Option<MachineId>,
+    ) -> Result<ActorRef, ActlibError> {
+        let searcher = current_actor().unwrap_or_else(|| ActorId {
+            local_id: LocalId::Automatic(Uuid::new_v4()),
+            location: self.env.machine_id,
+            incarnation: 0,
+        });
+        if let Some(actor_ref) = self.find_actor_ref(&key, searcher, false)? {
+            return Ok(actor_ref);
         }
-        self.env.remove_remote_query(queried_id, searcher);
-        result
+        let options = self.env.near_spawn_options(&key, preferred_location);
+        LocalEnvironment::spawn(
+            self.clone(),
+            actor_type_id,
+            SpawnId::User(LocalId::Specified(key)),
+            &options,
+        )
     }
 
     /// Remove the *protect*-flag set by [find_actor_ref](struct.Environment.html#method.find_actor_ref).
@@ -233,15 +2152,315 @@ impl Environment {
     /// Mark this Environment as expired.
     ///
     /// This will [stop](../actor/trait.Actor.html#method.on_stop) all Actors and release the [wait_until_expiration](struct.EnvironmentExpirationChecker.html#method.wait_until_expiration) method.
+    ///
+    /// Every remote machine is asked to do the same and to report back how many Actors it stopped and how many Messages it discarded.
+    /// This method blocks until all reports arrived or a deadline passed.
+    ///
+    /// Fails if [Capability::Expire](../options/enum.Capability.html#variant.Expire) is restricted and the calling Actor does not hold it.
     pub fn set_expired(&self) -> Result<(), String> {
-        match self.env.send_expiration_signal() {
+        self.env
+            .check_capability(Capability::Expire)
+            .map_err(|e| format!("{:?}", e))?;
+        match self.env.send_expiration_signal(LOCAL_DRAIN_TIMEOUT) {
             Ok(_) => Ok(()),
             Err(e) => Err(format!("{:?}", e)),
         }
     }
 
+    /// Shut this Environment down gracefully, giving every Actor up to *timeout* to work off its mailbox.
+    ///
+    /// New spawns are rejected from now on. Every Actor handles the Messages already queued in its mailbox,
+    /// then runs its [on_stop_with_reason](../actor/trait.Actor.html#method.on_stop_with_reason) method, on every machine in parallel.
+    /// Only then [wait_until_expiration](struct.EnvironmentExpirationChecker.html#method.wait_until_expiration) is released.
+    ///
+    /// The returned [ExpirationResult](struct.ExpirationResult.html) lists the [Actors that failed to stop in time](struct.ExpirationResult.html#method.actors_not_stopped).
+    /// This method blocks until all machines reported or *timeout* (plus a grace period for the remote reports) passed.
+    ///
+    /// Fails if [Capability::Expire](../options/enum.Capability.html#variant.Expire) is restricted and the calling Actor does not hold it.
+    pub fn shutdown(&self, timeout: Duration) -> Result<ExpirationResult, String> {
+        self.env
+            .check_capability(Capability::Expire)
+            .map_err(|e| format!("{:?}", e))?;
+        self.env
+            .send_expiration_signal(timeout)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    /// ```true``` once the Environment started to [shut down](struct.Environment.html#method.shutdown) or [expire](struct.Environment.html#method.set_expired).
+    ///
+    /// From then on spawns and broadcasts fail with [ShuttingDown](enum.ActlibError.html#variant.ShuttingDown),
+    /// while the Actors still work off the Messages in their mailboxes.
+    pub fn is_shutting_down(&self) -> bool {
+        self.env.is_shutting_down()
+    }
+
     /// Send a Message to all known actors.
-    pub fn broadcast<'de, M: Message<'de> + Clone + 'static>(&self, message: M) {
-        self.env.broadcast(message)
+    ///
+    /// If [Capability::Broadcast](../options/enum.Capability.html#variant.Broadcast) is restricted, an Actor without it fails
+    /// with [PermissionDenied](enum.ActlibError.html#variant.PermissionDenied). While the Environment is [shutting down](struct.Environment.html#method.is_shutting_down),
+    /// every broadcast fails with [ShuttingDown](enum.ActlibError.html#variant.ShuttingDown). Nothing is sent then.
+    pub fn broadcast<'de, M: Message<'de> + Clone + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), ActlibError> {
+        self.env.check_broadcast()?;
+        self.env
+            .broadcast(message, self.env.caller(current_actor()));
+        Ok(())
+    }
+
+    /// Answer a [Request](struct.Request.html), completing the [ResponseFuture](../actor/struct.ResponseFuture.html) of the asker.
+    ///
+    /// The *reply* is serialized and sent to the machine of the asker, which drops it if nobody waits for it anymore.
+    /// Only the first reply to a Request is used.
+    pub fn reply<M, R: Serialize>(
+        &self,
+        request: &Request<M>,
+        reply: R,
+    ) -> Result<(), ActlibError> {
+        let reply = self.env.wire_format().serialize(&reply)?;
+        self.env
+            .reply(request.reply_to, request.correlation_id, reply)
+    }
+
+    /// [Ask](../actor/struct.ActorRef.html#method.ask) every Actor of *refs* the same *message* and gather their replies, the Actors may live on any machine.
+    ///
+    /// All asks are sent at once and share the *timeout*. The Actors that did not reply in time get a [Timeout](enum.ActlibError.html#variant.Timeout)
+    /// instead of a reply, the replies of the others are kept. Returns one [Response](struct.Response.html) per ActorRef, in the order of *refs*.
+    ///
+    /// ```rust,ignore
+    /// let partial_sums = env.scatter_gather::<_, i64>(&workers, SumRange(0..1000), Duration::from_secs(1));
+    /// let sum: i64 = partial_sums.into_iter().filter_map(|response| response.reply.ok()).sum();
+    /// ```
+    pub fn scatter_gather<'de, M, R>(
+        &self,
+        refs: &[ActorRef],
+        message: M,
+        timeout: Duration,
+    ) -> Vec<Response<R>>
+    where
+        M: Message<'de> + Clone + 'static,
+        R: DeserializeOwned,
+    {
+        let deadline = Instant::now() + timeout;
+        let asked: Vec<(ActorId, Result<ResponseFuture<R>, ActlibError>)> = refs
+            .iter()
+            .map(|actor_ref| (actor_ref.clone_id(), actor_ref.ask(message.clone())))
+            .collect();
+        asked
+            .into_iter()
+            .map(|(responder, asked)| {
+                let reply = asked
+                    .and_then(|reply| {
+                        reply.wait(deadline.saturating_duration_since(Instant::now()))
+                    })
+                    .map_err(|e| match e {
+                        ActlibError::Timeout(_) => ActlibError::Timeout(format!(
+                            "Actor {:?} did not reply within {:?}",
+                            responder, timeout
+                        )),
+                        e => e,
+                    });
+                Response { responder, reply }
+            })
+            .collect()
+    }
+
+    /// Gather the [exported state](../actor/trait.Actor.html#method.export_state) of every Actor of the given type on all connected machines, by the id of the Actor.
+    ///
+    /// Every Actor exports its state on its own thread between two handlers, ahead of its queued Messages like a [query](../actor/struct.ActorRef.html#method.query).
+    /// Actors that export nothing, do not answer within *timeout* or whose state is no *S* are left out, as is the calling Actor itself.
+    ///
+    /// ```rust,ignore
+    /// let fields: HashMap<ActorId, FieldState> = env.collect_states("FieldInstance", Duration::from_secs(2));
+    /// ```
+    pub fn collect_states<S: DeserializeOwned>(
+        &self,
+        actor_type_id: &str,
+        timeout: Duration,
+    ) -> HashMap<ActorId, S> {
+        self.env
+            .collect_states(actor_type_id, timeout)
+            .into_iter()
+            .filter_map(|(actor_id, state)| match bincode::deserialize(&state) {
+                Ok(state) => Some((actor_id, state)),
+                Err(e) => {
+                    warn!(
+                        "The state of {:?} is no {}: {:?}",
+                        actor_id,
+                        std::any::type_name::<S>(),
+                        e
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// The ids of the Actors alive on the local machine, only those built from *actor_type_id* if given.
+    pub fn list_local_actors(&self, actor_type_id: Option<&str>) -> Vec<ActorId> {
+        self.env.list_local_actors(actor_type_id)
+    }
+
+    /// Ask every connected machine for the ids of its Actors, only those built from *actor_type_id* if given.
+    ///
+    /// The returned [ActorListing](struct.ActorListing.html) holds the local Actors along with the remote ones,
+    /// it is complete once every machine answered or *timeout* passed.
+    ///
+    /// ```rust,ignore
+    /// let fields: Vec<ActorId> = env.list_cluster_actors(Some("FieldInstance"), Duration::from_secs(1)).await;
+    /// ```
+    pub fn list_cluster_actors(
+        &self,
+        actor_type_id: Option<&str>,
+        timeout: Duration,
+    ) -> ActorListing {
+        ActorListing {
+            slot: LocalEnvironment::list_cluster_actors(&self.env, actor_type_id, timeout),
+        }
+    }
+
+    /// Describe every Actor living on the local machine: its type id, its specified id and its [exported state](../actor/trait.Actor.html#method.export_state).
+    ///
+    /// The states are exported like for [collect_states](struct.Environment.html#method.collect_states), Actors that do not answer within *timeout* are included without state.
+    /// The snapshot is serializable, e.g. to be written to disk and [restored](struct.Environment.html#method.restore) by the next run.
+    ///
+    /// ```rust,ignore
+    /// let snapshot = env.snapshot(Duration::from_secs(1));
+    /// std::fs::write("actors.snapshot", bincode::serialize(&snapshot)?)?;
+    /// ```
+    pub fn snapshot(&self, timeout: Duration) -> Snapshot {
+        self.env.snapshot(timeout)
+    }
+
+    /// Spawn every Actor of the *snapshot* on the local machine, returning the outcome of each spawn in the order of the snapshot.
+    ///
+    /// Actors keep their specified id, Actors with an automatically created id get a new one.
+    /// The state of each Actor is handed to its [import_state](../actor/trait.Actor.html#method.import_state) before it starts.
+    /// Restoring a specified id that is alive returns the existing Actor, like [spawn_local_with_id](struct.Environment.html#method.spawn_local_with_id).
+    pub fn restore(&self, snapshot: &Snapshot) -> Vec<Result<ActorRef, ActlibError>> {
+        snapshot
+            .actors
+            .iter()
+            .map(|actor| {
+                let local_id = match &actor.specified_id {
+                    Some(id) => LocalId::Specified(id.clone()),
+                    None => LocalId::Automatic(Uuid::new_v4()),
+                };
+                LocalEnvironment::spawn(
+                    self.clone(),
+                    &actor.type_id,
+                    SpawnId::SpawnHere(local_id),
+                    &SpawnOptions {
+                        state: actor.state.clone().map(ExportedState),
+                        ..SpawnOptions::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Like [broadcast](struct.Environment.html#method.broadcast), but the Message is handed to the Actors in [slices](struct.BroadcastSlices.html)
+    /// on the timer thread, so the Environment stays responsive while a huge population of Actors gets it.
+    ///
+    /// The returned receiver gets a [BroadcastCompleted](struct.BroadcastCompleted.html) once the last local Actor got the Message.
+    /// Every remote machine slices its own Actors the same way, their completion is not reported.
+    /// Actors spawned after the broadcast do not get the Message, Actors removed before their slice are skipped.
+    ///
+    /// ```rust,ignore
+    /// let completed = env.broadcast_in_slices(WorldEvent::Earthquake, BroadcastSlices::new().slice_size(5000));
+    /// println!("{} Actors got it", completed.recv()?.actors);
+    /// ```
+    pub fn broadcast_in_slices<M: Message<'static> + Clone + 'static>(
+        &self,
+        message: M,
+        slices: BroadcastSlices,
+    ) -> Receiver<BroadcastCompleted> {
+        let (completed, receiver) = channel();
+        match self.env.check_broadcast() {
+            Ok(()) => LocalEnvironment::broadcast_in_slices(&self.env, message, slices, completed),
+            Err(e) => log_err_as!(error, e),
+        }
+        receiver
+    }
+
+    /// Like [broadcast](struct.Environment.html#method.broadcast), but the Message is wrapped in a [Sequenced](struct.Sequenced.html)
+    /// carrying the next number of the local machine, which is returned.
+    ///
+    /// Combined with [ordered broadcasts](struct.EnvironmentOptions.html#method.ordered_broadcasts) this gives round-based simulations
+    /// a deterministic delivery order and lets Actors detect the rounds they missed.
+    ///
+    /// Fails like [broadcast](struct.Environment.html#method.broadcast), a Message that is not sent takes no number.
+    pub fn broadcast_sequenced<'de, M: Message<'de> + Clone + 'static>(
+        &self,
+        message: M,
+    ) -> Result<u64, ActlibError> {
+        self.env.check_broadcast()?;
+        let seq = self.env.next_broadcast_seq();
+        self.env.broadcast(
+            Sequenced {
+                origin: self.env.machine_id,
+                seq,
+                message,
+            },
+            self.env.caller(current_actor()),
+        );
+        Ok(seq)
+    }
+
+    /// Like [broadcast](struct.Environment.html#method.broadcast), but all Actors on all machines receive the Messages broadcast this way in the same order.
+    ///
+    /// The Messages are sequenced by the current [leader](struct.Environment.html#method.leader), which sends them to every machine,
+    /// the sending one included, one at a time. This costs a round trip to the leader, so it is meant for low-rate control topics
+    /// like round transitions or configuration changes.
+    ///
+    /// The order holds while all machines are connected to each other and agree on the leader.
+    /// A Message sent while the leader becomes unreachable may arrive late or not at all.
+    pub fn broadcast_in_total_order<'de, M: Message<'de> + 'static>(
+        &self,
+        message: M,
+    ) -> Result<(), ActlibError> {
+        self.env.check_broadcast()?;
+        let content = SerializedMessage::of(&message, self.env.wire_format())?;
+        self.env.broadcast_in_total_order(content)
+    }
+
+    /// The machine sequencing the [broadcasts in total order](struct.Environment.html#method.broadcast_in_total_order):
+    /// the one with the lowest id among the local machine and the connected machines.
+    pub fn leader(&self) -> MachineId {
+        self.env.leader()
+    }
+
+    /// Like [broadcast](struct.Environment.html#method.broadcast), but the Message is sent once the *delay* passed.
+    ///
+    /// The Message is sent by the Environment's timer, the calling thread is not blocked.
+    pub fn broadcast_after<M: Message<'static> + Clone + 'static>(
+        &self,
+        message: M,
+        delay: Duration,
+    ) -> Result<(), ActlibError> {
+        self.broadcast_at(Instant::now() + delay, message)
+    }
+
+    /// Like [broadcast](struct.Environment.html#method.broadcast), but the Message is sent once the *instant* passed.
+    ///
+    /// The Message is sent by the Environment's timer, the calling thread is not blocked.
+    /// An *instant* in the past sends the Message right away.
+    /// The [capability](../options/enum.Capability.html#variant.Broadcast) of the caller is checked when scheduling,
+    /// failing like [broadcast](struct.Environment.html#method.broadcast) without scheduling anything.
+    /// Scheduled broadcasts are cancelled when the Environment expires.
+    pub fn broadcast_at<M: Message<'static> + Clone + 'static>(
+        &self,
+        instant: Instant,
+        message: M,
+    ) -> Result<(), ActlibError> {
+        self.env.check_broadcast()?;
+        // the remote machines check the capabilities of the scheduling Actor
+        let caller = self.env.caller(current_actor());
+        let env = self.env.clone();
+        self.env
+            .timer
+            .schedule_at(instant, move || env.broadcast(message, caller));
+        Ok(())
     }
 }
@@ -0,0 +1,400 @@
+//! This module provides ready-made [Actors](../actor/trait.Actor.html) for common chores.
+//!
+//! Built-in Actors are registered like any other Actor, using the [actor_builder!](../macro.actor_builder.html)-macro:
+//!
+//! ```rust,ignore
+//! let actor_builder = actor_builder!(
+//!     "PrintingActor" => PrintingActor,
+//!     "LineReaderActor" => LineReaderActor::default(),
+//!     "TickDriver" => TickDriver::default(),
+//! );
+//! ```
+
+use crate::actor::{Actor, ActorId, ActorRef, QueryableActor, StopReason};
+use crate::api::{ActlibError, Environment};
+use crate::environment::current_actor;
+use crate::impl_message_handler;
+use crate::message::MessageHandler;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a tailed file is checked for new lines.
+const TAIL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Where a [LineReaderActor](struct.LineReaderActor.html) reads its lines from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LineSource {
+    /// Read the standard input until it is closed.
+    Stdin,
+    /// Read the file once until its end.
+    File(PathBuf),
+    /// Read the file and keep waiting for appended lines, like ```tail -f```.
+    Tail(PathBuf),
+}
+
+/// Tells a [LineReaderActor](struct.LineReaderActor.html) to forward the lines of *source* to *target*.
+///
+/// A LineReaderActor reads one source at a time, a new ReadLines request ends the previous one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadLines {
+    /// Where to read from.
+    pub source: LineSource,
+    /// The Actor receiving every line as a ```String``` message, without the line break.
+    pub target: ActorId,
+}
+
+/// Reads lines from stdin or a file and forwards them as ```String``` messages, e.g. for interactive demos.
+///
+/// The Actor starts reading once it receives a [ReadLines](struct.ReadLines.html) request:
+///
+/// ```rust,ignore
+/// let reader = env.spawn_local("LineReaderActor")?;
+/// reader.send_message(ReadLines { source: LineSource::Stdin, target: printer.clone_id() })?;
+/// ```
+///
+/// The lines are read on a separate thread, the reading stops at the end of the source,
+/// when the target Actor is gone, or after this Actor is stopped and one more line was read.
+#[derive(Debug, Default)]
+pub struct LineReaderActor {
+    env: Option<Environment>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl_message_handler!(LineReaderActor: ReadLines => LineReaderActor::read_lines);
+
+impl Actor for LineReaderActor {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+impl LineReaderActor {
+    fn read_lines(&mut self, request: &ReadLines) {
+        let target = match &self.env {
+            Some(env) => match env.to_actor_ref(request.target.clone()) {
+                Ok(target) => target,
+                Err(e) => {
+                    warn!(
+                        "LineReaderActor cannot forward to {:?}: {:?}",
+                        request.target, e
+                    );
+                    return;
+                }
+            },
+            None => return,
+        };
+        // end the previous request
+        self.stopped.store(true, Ordering::Relaxed);
+        self.stopped = Arc::new(AtomicBool::new(false));
+        let stopped = self.stopped.clone();
+        let source = request.source.clone();
+        thread::spawn(move || {
+            let result = match &source {
+                LineSource::Stdin => {
+                    forward_lines(std::io::stdin().lock(), false, &target, &stopped)
+                }
+                LineSource::File(path) => File::open(path)
+                    .map_err(|e| ActlibError::InvalidState(format!("{:?}", e)))
+                    .and_then(|file| forward_lines(BufReader::new(file), false, &target, &stopped)),
+                LineSource::Tail(path) => File::open(path)
+                    .map_err(|e| ActlibError::InvalidState(format!("{:?}", e)))
+                    .and_then(|file| forward_lines(BufReader::new(file), true, &target, &stopped)),
+            };
+            match result {
+                Ok(lines) => info!("Forwarded {} lines from {:?}", lines, source),
+                Err(e) => warn!("Stopped reading lines from {:?}: {:?}", source, e),
+            }
+        });
+    }
+}
+
+/// Send every line of *reader* to *target*, returning the number of lines sent.
+///
+/// If *follow* is set, the end of the input is polled for new lines until *stopped* is set.
+fn forward_lines<R: BufRead>(
+    mut reader: R,
+    follow: bool,
+    target: &ActorRef,
+    stopped: &AtomicBool,
+) -> Result<usize, ActlibError> {
+    let mut lines = 0;
+    let mut line = String::new();
+    while !stopped.load(Ordering::Relaxed) {
+        match reader.read_line(&mut line) {
+            // end of input
+            Ok(0) => {
+                if !follow {
+                    break;
+                }
+                thread::sleep(TAIL_INTERVAL);
+            }
+            Ok(_) => {
+                // a tailed file may end within a line that is still being written
+                if follow && !line.ends_with('\n') {
+                    continue;
+                }
+                let content = line.trim_end_matches(['\n', '\r']).to_string();
+                target.send_message(content)?;
+                lines += 1;
+                line.clear();
+            }
+            Err(e) => return Err(ActlibError::InvalidState(format!("{:?}", e))),
+        }
+    }
+    Ok(lines)
+}
+
+/// One step of a simulation, sent by a [TickDriver](struct.TickDriver.html) to every member of its group.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tick {
+    /// Number of the tick, starting at 1.
+    pub n: u64,
+    /// The TickDriver waiting for the acknowledgement.
+    pub driver: ActorId,
+}
+
+impl Tick {
+    /// Tell the driver that the handling Actor completed this tick.
+    ///
+    /// Has to be called from within a handler of the member, fails with [InvalidState](../api/enum.ActlibError.html#variant.InvalidState) otherwise.
+    pub fn ack(&self, env: &Environment) -> Result<(), ActlibError> {
+        let member = current_actor().ok_or_else(|| {
+            ActlibError::InvalidState("A Tick can only be acknowledged by an Actor".to_string())
+        })?;
+        env.to_actor_ref(self.driver.clone())?
+            .send_message(TickAck { n: self.n, member })
+    }
+}
+
+/// A member completed a [Tick](struct.Tick.html), usually sent by [Tick::ack](struct.Tick.html#method.ack).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickAck {
+    /// Number of the completed tick.
+    pub n: u64,
+    /// The member that completed it.
+    pub member: ActorId,
+}
+
+/// Adds an Actor to the group of a [TickDriver](struct.TickDriver.html), starting with the next tick.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinTicks(pub ActorId);
+
+/// Removes an Actor from the group of a [TickDriver](struct.TickDriver.html), the current tick does not wait for it anymore.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaveTicks(pub ActorId);
+
+/// Starts (or reconfigures) a [TickDriver](struct.TickDriver.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartTicks {
+    /// The time between two ticks the driver aims for.
+    pub interval: Duration,
+    /// The time between two ticks the driver slows down to at most.
+    pub max_interval: Duration,
+}
+
+/// Stops a [TickDriver](struct.TickDriver.html), a later [StartTicks](struct.StartTicks.html) continues with the next tick number.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopTicks;
+
+/// Timer of a TickDriver, only the latest generation is honored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NextTick(u64);
+
+/// Progress of a [TickDriver](struct.TickDriver.html), see [status](struct.TickDriver.html#method.status).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickStatus {
+    /// Number of the last tick sent.
+    pub tick: u64,
+    /// Number of ticks acknowledged by every member.
+    pub completed_ticks: u64,
+    /// The current time between two ticks.
+    pub interval: Duration,
+    /// How often the driver had to slow down because a tick was not completed in time.
+    pub slowdowns: u64,
+    /// How long the members took for the last completed tick.
+    pub last_tick_duration: Option<Duration>,
+    /// Number of Actors in the group.
+    pub members: usize,
+}
+
+/// Sends a [Tick](struct.Tick.html) to every member of its group at a fixed rate, the core of a simulation loop.
+///
+/// Members join with [JoinTicks](struct.JoinTicks.html) and acknowledge every tick with [Tick::ack](struct.Tick.html#method.ack).
+/// If a tick is still not acknowledged by every member when the next one is due, the driver skips it and doubles the interval,
+/// up to the configured maximum. Once the members keep up again, the interval shrinks back towards the target.
+///
+/// ```rust,ignore
+/// let driver = env.spawn("TickDriver")?;
+/// driver.send_message(JoinTicks(field_ref.clone_id()))?;
+/// driver.send_message(StartTicks { interval: Duration::from_millis(50), max_interval: Duration::from_secs(1) })?;
+/// let status = driver.query(|driver: &TickDriver| driver.status(), Duration::from_secs(1))?;
+/// ```
+#[derive(Debug, Default)]
+pub struct TickDriver {
+    own_ref: Option<ActorRef>,
+    env: Option<Environment>,
+    members: HashSet<ActorId>,
+    /// Members that did not yet acknowledge the current tick
+    pending: HashSet<ActorId>,
+    running: bool,
+    /// Invalidates the timers of previous StartTicks requests
+    generation: u64,
+    tick: u64,
+    tick_started: Option<Instant>,
+    target_interval: Duration,
+    max_interval: Duration,
+    interval: Duration,
+    completed_ticks: u64,
+    slowdowns: u64,
+    last_tick_duration: Option<Duration>,
+}
+
+impl_message_handler!(TickDriver:
+    JoinTicks => TickDriver::join,
+    LeaveTicks => TickDriver::leave,
+    StartTicks => TickDriver::start,
+    StopTicks => TickDriver::stop,
+    TickAck => TickDriver::acknowledge,
+    NextTick => TickDriver::next_tick
+);
+
+impl Actor for TickDriver {
+    fn on_start(&mut self, local_env: Environment, own_ref: ActorRef) {
+        self.env = Some(local_env);
+        self.own_ref = Some(own_ref);
+    }
+}
+
+impl QueryableActor for TickDriver {}
+
+impl TickDriver {
+    /// The current progress of the driver.
+    pub fn status(&self) -> TickStatus {
+        TickStatus {
+            tick: self.tick,
+            completed_ticks: self.completed_ticks,
+            interval: self.interval,
+            slowdowns: self.slowdowns,
+            last_tick_duration: self.last_tick_duration,
+            members: self.members.len(),
+        }
+    }
+
+    fn join(&mut self, join: &JoinTicks) {
+        self.members.insert(join.0.clone());
+    }
+
+    fn leave(&mut self, leave: &LeaveTicks) {
+        self.members.remove(&leave.0);
+        self.pending.remove(&leave.0);
+        self.complete_if_acknowledged();
+    }
+
+    fn start(&mut self, start: &StartTicks) {
+        self.running = true;
+        self.generation += 1;
+        self.target_interval = start.interval;
+        self.max_interval = start.max_interval.max(start.interval);
+        self.interval = start.interval;
+        self.next_tick(&NextTick(self.generation));
+    }
+
+    fn stop(&mut self, _stop: &StopTicks) {
+        self.running = false;
+        self.generation += 1;
+    }
+
+    fn acknowledge(&mut self, ack: &TickAck) {
+        if ack.n == self.tick {
+            self.pending.remove(&ack.member);
+            self.complete_if_acknowledged();
+        }
+    }
+
+    fn next_tick(&mut self, next: &NextTick) {
+        if !self.running || next.0 != self.generation {
+            return;
+        }
+        if !self.pending.is_empty() {
+            // the members can't keep up, give them more time
+            self.interval = (self.interval * 2).min(self.max_interval);
+            self.slowdowns += 1;
+            warn!(
+                "Tick {} still waits for {} members, slowing down to {:?}",
+                self.tick,
+                self.pending.len(),
+                self.interval
+            );
+        } else {
+            self.send_tick();
+        }
+        if let Some(own_ref) = &self.own_ref {
+            own_ref.send_delayed_message(NextTick(self.generation), self.interval);
+        }
+    }
+
+    fn send_tick(&mut self) {
+        let env = match &self.env {
+            Some(env) => env,
+            None => return,
+        };
+        let driver = match &self.own_ref {
+            Some(own_ref) => own_ref.clone_id(),
+            None => return,
+        };
+        self.tick += 1;
+        self.tick_started = Some(Instant::now());
+        let tick = Tick {
+            n: self.tick,
+            driver,
+        };
+        let mut gone = Vec::new();
+        for member in &self.members {
+            match env.to_actor_ref(member.clone()) {
+                Ok(member_ref) => match member_ref.send_message(tick.clone()) {
+                    Ok(_) => {
+                        self.pending.insert(member.clone());
+                    }
+                    Err(ActlibError::InvalidActorRef(_)) | Err(ActlibError::ActorStopping(_)) => {
+                        gone.push(member.clone())
+                    }
+                    Err(e) => warn!("Failed to send tick to {:?}: {:?}", member, e),
+                },
+                Err(_) => gone.push(member.clone()),
+            }
+        }
+        for member in gone {
+            info!("Removed stopped Actor {:?} from the tick group", member);
+            self.members.remove(&member);
+        }
+        self.complete_if_acknowledged();
+    }
+
+    /// Record the completion of the current tick once every member acknowledged it.
+    fn complete_if_acknowledged(&mut self) {
+        if !self.pending.is_empty() {
+            return;
+        }
+        if let Some(started) = self.tick_started.take() {
+            let duration = started.elapsed();
+            self.completed_ticks += 1;
+            self.last_tick_duration = Some(duration);
+            if self.interval > self.target_interval && duration < self.target_interval {
+                // the members keep up again, return to the target rate step by step
+                self.interval = (self.interval * 3 / 4).max(self.target_interval);
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! This module defines the cluster-wide [Counters](struct.Counter.html) and [Gauges](struct.Gauge.html) of an [Environment](../api/struct.Environment.html).
+//!
+//! Every machine only changes its own share of a value, and sends its shares to the other machines every
+//! [counter interval](../options/struct.EnvironmentOptions.html#method.counter_interval).
+//! The value of a Counter or Gauge is the sum of the latest shares of all machines, so it is eventually consistent:
+//! changes on a remote machine show up within an interval, and no change is lost or counted twice.
+//!
+//! ```rust,ignore
+//! env.counter("players_total").add(1);
+//! env.gauge("players_online").add(1);
+//! println!("{} players online", env.gauge("players_online").value());
+//! ```
+
+use crate::actor::MachineId;
+use crate::errors::ActlibError;
+use crate::log_err_as;
+#[allow(unused_imports)]
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, Weak};
+use std::time::{Duration, Instant, SystemTime};
+
+/// The shares of a single machine, sent to the other machines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct CounterShares {
+    pub(crate) counters: HashMap<String, u64>,
+    pub(crate) gauges: HashMap<String, i64>,
+}
+
+/// The shares of all machines, kept by every Environment.
+#[derive(Debug)]
+pub(crate) struct CounterRegistry {
+    local: Mutex<CounterShares>,
+    /// The latest shares of every remote machine, with the time they arrived
+    remote: Mutex<HashMap<MachineId, (Instant, CounterShares)>>,
+    /// Gauges of a machine that did not report for this long are left out
+    gauge_expiry: Duration,
+}
+
+impl CounterRegistry {
+    pub(crate) fn new(interval: Duration) -> Self {
+        CounterRegistry {
+            local: Mutex::new(CounterShares::default()),
+            remote: Mutex::new(HashMap::new()),
+            gauge_expiry: interval * 3,
+        }
+    }
+
+    /// The shares of the local machine, ```None``` if no Counter or Gauge was used yet.
+    pub(crate) fn local_shares(&self) -> Option<CounterShares> {
+        match self.local.lock() {
+            Ok(local) if local.counters.is_empty() && local.gauges.is_empty() => None,
+            Ok(local) => Some(local.clone()),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    /// Replace the shares of the given remote machine.
+    pub(crate) fn record_remote(&self, machine: MachineId, shares: CounterShares) {
+        match self.remote.lock() {
+            Ok(mut remote) => {
+                remote.insert(machine, (Instant::now(), shares));
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    fn change_local<F: FnOnce(&mut CounterShares)>(&self, change: F) {
+        match self.local.lock() {
+            Ok(mut local) => change(&mut local),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// The values of all Counters and Gauges, the local shares plus the latest shares of the remote machines.
+    pub(crate) fn snapshot(&self) -> CounterSnapshot {
+        let mut snapshot = CounterSnapshot {
+            counters: HashMap::new(),
+            gauges: HashMap::new(),
+            taken_at: SystemTime::now(),
+        };
+        let mut add = |shares: &CounterShares, with_gauges: bool| {
+            for (name, value) in &shares.counters {
+                *snapshot.counters.entry(name.clone()).or_insert(0) += value;
+            }
+            if with_gauges {
+                for (name, value) in &shares.gauges {
+                    *snapshot.gauges.entry(name.clone()).or_insert(0) += value;
+                }
+            }
+        };
+        match self.local.lock() {
+            Ok(local) => add(&local, true),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        match self.remote.lock() {
+            Ok(remote) => {
+                for (received, shares) in remote.values() {
+                    add(shares, received.elapsed() < self.gauge_expiry);
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        snapshot
+    }
+}
+
+/// The values of all Counters and Gauges of the cluster, as far as the local machine knows them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterSnapshot {
+    /// The value of every Counter, by name.
+    pub counters: HashMap<String, u64>,
+    /// The value of every Gauge, by name.
+    pub gauges: HashMap<String, i64>,
+    /// When the snapshot was taken, by the local clock.
+    pub taken_at: SystemTime,
+}
+
+impl CounterSnapshot {
+    /// The value of the named Counter, 0 if it was never used.
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters.get(name).cloned().unwrap_or(0)
+    }
+
+    /// The value of the named Gauge, 0 if it was never used.
+    pub fn gauge(&self, name: &str) -> i64 {
+        self.gauges.get(name).cloned().unwrap_or(0)
+    }
+}
+
+/// A cluster-wide count that only grows, e.g. the number of players that ever joined.
+///
+/// Get it with [Environment::counter](../api/struct.Environment.html#method.counter). The value of a machine that left is kept.
+#[derive(Debug, Clone)]
+pub struct Counter {
+    name: String,
+    registry: Weak<CounterRegistry>,
+}
+
+impl Counter {
+    pub(crate) fn new(name: &str, registry: Weak<CounterRegistry>) -> Self {
+        Counter {
+            name: name.to_string(),
+            registry,
+        }
+    }
+
+    /// The name of this Counter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add *n* to the share of the local machine, ignored once the Environment is gone.
+    pub fn add(&self, n: u64) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.change_local(|local| {
+                let value = local.counters.entry(self.name.clone()).or_insert(0);
+                *value = value.saturating_add(n);
+            });
+        }
+    }
+
+    /// The cluster-wide value, including the latest shares of the remote machines.
+    pub fn value(&self) -> u64 {
+        match self.registry.upgrade() {
+            Some(registry) => registry.snapshot().counter(&self.name),
+            None => 0,
+        }
+    }
+}
+
+/// A cluster-wide value that goes up and down, e.g. the number of players online.
+///
+/// Get it with [Environment::gauge](../api/struct.Environment.html#method.gauge).
+/// The share of a machine that did not report for three counter intervals is left out.
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    name: String,
+    registry: Weak<CounterRegistry>,
+}
+
+impl Gauge {
+    pub(crate) fn new(name: &str, registry: Weak<CounterRegistry>) -> Self {
+        Gauge {
+            name: name.to_string(),
+            registry,
+        }
+    }
+
+    /// The name of this Gauge.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Set the share of the local machine to *value*, ignored once the Environment is gone.
+    pub fn set(&self, value: i64) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.change_local(|local| {
+                local.gauges.insert(self.name.clone(), value);
+            });
+        }
+    }
+
+    /// Add *delta* to the share of the local machine, ignored once the Environment is gone.
+    pub fn add(&self, delta: i64) {
+        if let Some(registry) = self.registry.upgrade() {
+            registry.change_local(|local| {
+                let value = local.gauges.entry(self.name.clone()).or_insert(0);
+                *value = value.saturating_add(delta);
+            });
+        }
+    }
+
+    /// The cluster-wide value, the sum of the shares of all machines.
+    pub fn value(&self) -> i64 {
+        match self.registry.upgrade() {
+            Some(registry) => registry.snapshot().gauge(&self.name),
+            None => 0,
+        }
+    }
+}
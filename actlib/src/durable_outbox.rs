@@ -0,0 +1,190 @@
+//! This module defines the write-ahead log behind the [durable outbox](../options/struct.EnvironmentOptions.html#method.durable_outbox).
+//!
+//! Every Tracked message is appended to the log before it is sent, and a record settling it is appended once
+//! it was acknowledged, rejected or given up on. After a restart the Tracked messages that were never settled are sent again,
+//! unless they exceeded the [retention](../options/struct.OutboxRetention.html) since they were first sent.
+//!
+//! Every record is its bincode serialized size as a little endian u32, followed by the record itself.
+//! A record torn by a crash while it was written is ignored, along with everything after it.
+
+use crate::actor::MachineId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// A Tracked message that was not settled: its delivery number, destination, serialized frame and when it was first sent.
+pub(crate) type Unsettled = (u64, MachineId, Vec<u8>, SystemTime);
+
+/// A single entry of the log.
+#[derive(Debug, Serialize, Deserialize)]
+enum OutboxRecord {
+    /// delivery_no, destination, the serialized Tracked message, as written by logs without the time it was first sent
+    Sent(u64, MachineId, Vec<u8>),
+    /// delivery_no: the Tracked message needs no further attempts
+    Settled(u64),
+    /// delivery_no, destination, the serialized Tracked message, milliseconds since the epoch when it was first sent
+    SentAt(u64, MachineId, Vec<u8>, u64),
+}
+
+/// The write-ahead log of the Tracked messages waiting for their acknowledgement.
+#[derive(Debug)]
+pub(crate) struct DurableOutbox {
+    path: PathBuf,
+    file: File,
+    /// Records in the file, settled or not
+    records: usize,
+}
+
+impl DurableOutbox {
+    /// Open the log at *path*, creating it if missing.
+    ///
+    /// Returns the Tracked messages that were never settled, with their delivery number and destination in the order they were sent.
+    /// The log is compacted to these messages.
+    pub(crate) fn open(path: &Path) -> std::io::Result<(DurableOutbox, Vec<Unsettled>)> {
+        let mut content = Vec::new();
+        match File::open(path) {
+            Ok(mut file) => {
+                file.read_to_end(&mut content)?;
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        let mut sent = Vec::new();
+        let mut settled = HashSet::new();
+        let mut rest = &content[..];
+        while rest.len() >= 4 {
+            let mut size = [0; 4];
+            size.copy_from_slice(&rest[..4]);
+            let size = u32::from_le_bytes(size) as usize;
+            if rest.len() < 4 + size {
+                break;
+            }
+            match bincode::deserialize::<OutboxRecord>(&rest[4..4 + size]) {
+                Ok(OutboxRecord::Sent(delivery_no, destination, frame)) => {
+                    // counts as sent now, so it is kept for the whole retention
+                    sent.push((delivery_no, destination, frame, SystemTime::now()))
+                }
+                Ok(OutboxRecord::SentAt(delivery_no, destination, frame, millis)) => sent.push((
+                    delivery_no,
+                    destination,
+                    frame,
+                    SystemTime::UNIX_EPOCH + Duration::from_millis(millis),
+                )),
+                Ok(OutboxRecord::Settled(delivery_no)) => {
+                    settled.insert(delivery_no);
+                }
+                Err(_) => break,
+            }
+            rest = &rest[4 + size..];
+        }
+        sent.retain(|(delivery_no, _, _, _)| !settled.contains(delivery_no));
+
+        let (file, records) = write_log(
+            path,
+            sent.iter()
+                .map(|(delivery_no, destination, frame, first_sent)| {
+                    (*delivery_no, *destination, &frame[..], *first_sent)
+                }),
+        )?;
+        let outbox = DurableOutbox {
+            path: path.to_path_buf(),
+            file,
+            records,
+        };
+        Ok((outbox, sent))
+    }
+
+    /// Append a Tracked message that is about to be sent, which was first sent at *first_sent*.
+    pub(crate) fn record_sent(
+        &mut self,
+        delivery_no: u64,
+        destination: MachineId,
+        frame: &[u8],
+        first_sent: SystemTime,
+    ) -> std::io::Result<()> {
+        self.append(&OutboxRecord::SentAt(
+            delivery_no,
+            destination,
+            frame.to_vec(),
+            epoch_millis(first_sent),
+        ))
+    }
+
+    /// Append that a Tracked message needs no further attempts.
+    pub(crate) fn record_settled(&mut self, delivery_no: u64) -> std::io::Result<()> {
+        self.append(&OutboxRecord::Settled(delivery_no))
+    }
+
+    /// Number of records in the log, settled or not.
+    pub(crate) fn records(&self) -> usize {
+        self.records
+    }
+
+    /// Replace the log with the given unsettled Tracked messages.
+    pub(crate) fn rewrite<'a, I: Iterator<Item = (u64, MachineId, &'a [u8], SystemTime)>>(
+        &mut self,
+        unsettled: I,
+    ) -> std::io::Result<()> {
+        let (file, records) = write_log(&self.path, unsettled)?;
+        self.file = file;
+        self.records = records;
+        Ok(())
+    }
+
+    fn append(&mut self, record: &OutboxRecord) -> std::io::Result<()> {
+        write_record(&mut self.file, record)?;
+        self.records += 1;
+        Ok(())
+    }
+}
+
+/// Write a log at *path* holding the given unsettled Tracked messages, returning it opened for appending with its number of records.
+///
+/// The new log is written next to the old one and renamed over it, so a crash leaves either of them.
+fn write_log<'a, I: Iterator<Item = (u64, MachineId, &'a [u8], SystemTime)>>(
+    path: &Path,
+    unsettled: I,
+) -> std::io::Result<(File, usize)> {
+    let temporary = temporary_path(path);
+    let mut file = File::create(&temporary)?;
+    let mut records = 0;
+    for (delivery_no, destination, frame, first_sent) in unsettled {
+        write_record(
+            &mut file,
+            &OutboxRecord::SentAt(
+                delivery_no,
+                destination,
+                frame.to_vec(),
+                epoch_millis(first_sent),
+            ),
+        )?;
+        records += 1;
+    }
+    file.sync_all()?;
+    std::fs::rename(&temporary, path)?;
+    Ok((OpenOptions::new().append(true).open(path)?, records))
+}
+
+/// Write the record with its size in a single write, so a crash tears at most the last record.
+fn write_record(file: &mut File, record: &OutboxRecord) -> std::io::Result<()> {
+    let bin = bincode::serialize(record).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    let mut framed = Vec::with_capacity(4 + bin.len());
+    framed.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    framed.extend_from_slice(&bin);
+    file.write_all(&framed)
+}
+
+fn epoch_millis(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut temporary = path.as_os_str().to_os_string();
+    temporary.push(".tmp");
+    PathBuf::from(temporary)
+}
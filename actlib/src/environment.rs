@@ -5,27 +5,69 @@
 //! and handles sending and receiving messages from [Actors](../actor/trait.Actor.html) that live on a remote machine.
 
 use crate::actor::*;
-use crate::api::Environment;
+use crate::api::{
+    ActorDescription, ActorFailed, ActorSnapshot, AliasStats, BroadcastCompleted, ClusterLoad,
+    DeadLetter, DeliveryFailure, DrainReport, DropCause, Environment, EnvironmentInfo,
+    ExpirationReason, ExpirationResult, ExternalConnection, HandlerStats, HealthReport,
+    HealthStatus, IdConflict, Introspection, LifecycleEvent, LineageRecord, LockWaits, MachineLoad,
+    MachineRef, MachineState, MachineStatus, MailboxAlert, MailboxBacklog, MessageDropped,
+    MetricRates, Metrics, PayloadTicket, PeerState, PeerStats, PeerStatus, ReplyStats, Snapshot,
+    StaleIncarnation, StopOutcome, StopRecord, Terminated,
+};
+use crate::counters::CounterRegistry;
+use crate::durable_outbox::{DurableOutbox, Unsettled};
 use crate::errors::ActlibError;
 use crate::log_err_as;
 use crate::message::*;
+use crate::options::*;
+use crate::scheduler::{self, ScheduledActor, Scheduler, Turn};
+use crate::supervision::Supervisor;
+use crate::timer::Timer;
+use crate::wire::WireFormat;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use indexmap::IndexMap;
 #[allow(unused_imports)]
-use log::{error, info, warn};
-use netchannel::{NetChannel, NetReceiver, NetSender};
-use std::collections::{HashMap, HashSet};
+use log::{debug, error, info, warn};
+use netchannel::{Backoff, NetChannel, NetReceiver, NetSender, Peer, Traffic};
+use rand::prelude::{thread_rng, SliceRandom};
+use serde::Serialize;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
+use std::future::Future;
+use std::io::{Read, Write};
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::{Ipv4Addr, TcpStream};
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::*;
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, Condvar, LockResult, Mutex, MutexGuard, RwLock, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
 use uuid::Uuid;
 
 /// Abbreviation for ```Arc<Mutex<LocalEnvironment>>```.
 pub(crate) type ArcEnvironment = Arc<LocalEnvironment>;
 
+/// The serialized exported states of Actors, by their id.
+type ExportedStates = Vec<(ActorId, Vec<u8>)>;
+
+/// Checks a user specified id, see [ActorBuilder::with_id_validator](struct.ActorBuilder.html#method.with_id_validator).
+type IdValidator = fn(&[u8]) -> Result<(), String>;
+
+/// The frames sent to and received from a remote machine.
+type MachineTraffic = (MachineId, Arc<Traffic>, Arc<Traffic>);
+
+/// The searchers waiting for the answer of a remote machine, by the queried id and the searcher.
+type RemoteQueries = HashMap<(Vec<u8>, ActorId), Sender<Option<ActorRef>>>;
+
 #[macro_export]
-/// This macro builds and **returns** an `actor_builder` function object expected by [Environment::new](./api/struct.Environment.html#method.new)[(_local_only)](./api/struct.Environment.html#method.new_local_only).
+/// This macro builds and **returns** an [ActorBuilder](./api/struct.ActorBuilder.html) object expected by [Environment::new](./api/struct.Environment.html#method.new)[(_local_only)](./api/struct.Environment.html#method.new_local_only).
 ///
 ///```
 /// let actor_builder = actor_builder!("ExampleActor" => ExampleActor{state: 32});
@@ -56,17 +98,147 @@ macro_rules! actor_builder {
                 )+
                 {Err(ActlibError::SpawnFailed(format!("Unknown actor type: {}", type_id)))}
             }
-        actor_builder
+        ActorBuilder::new(actor_builder, &[$($identifier),+])
+            .with_message_types(&[$(($identifier, $crate::message::message_types_of(|| $new_actor))),+])
         }
     };
 }
 
+/// Knows how to build a new Actor from its type id, and which type ids it knows.
+///
+/// Usually created by the [actor_builder!](../macro.actor_builder.html) macro.
+#[derive(Clone)]
+pub struct ActorBuilder {
+    build: fn(&str) -> Result<Box<dyn Actor>, ActlibError>,
+    type_ids: Vec<String>,
+    /// Checks for the user specified ids of some type ids
+    id_validators: HashMap<String, IdValidator>,
+    /// The message types handled by the Actors of every type id, if known
+    message_types: HashMap<String, &'static [&'static str]>,
+}
+
+impl ActorBuilder {
+    /// Create a new ActorBuilder from a build function and the type ids it is able to build.
+    pub fn new(build: fn(&str) -> Result<Box<dyn Actor>, ActlibError>, type_ids: &[&str]) -> Self {
+        ActorBuilder {
+            build,
+            type_ids: type_ids.iter().map(|type_id| type_id.to_string()).collect(),
+            id_validators: HashMap::new(),
+            message_types: HashMap::new(),
+        }
+    }
+
+    /// Record the names of the message types handled by the Actors of each type id, in the order they are tried.
+    ///
+    /// The [actor_builder!](../macro.actor_builder.html) macro does this at compile time, using the lists of the
+    /// [impl_message_handler!](../macro.impl_message_handler.html) macro. They are part of the [fingerprint](#method.fingerprint).
+    pub fn with_message_types(mut self, message_types: &[(&str, &'static [&'static str])]) -> Self {
+        for (type_id, names) in message_types {
+            self.message_types.insert(type_id.to_string(), names);
+        }
+        self
+    }
+
+    /// ```false``` if the Actors of the type id are known to handle other message types than an *A*.
+    pub(crate) fn handles_like<A: MessageHandler>(&self, type_id: &str) -> bool {
+        self.message_types
+            .get(type_id)
+            .is_none_or(|names| *names == A::message_type_names())
+    }
+
+    /// Whether the message types handled by the Actors of the type id are known, so any other type is unhandled.
+    pub(crate) fn knows_message_types(&self, type_id: &str) -> bool {
+        self.message_types
+            .get(type_id)
+            .is_some_and(|names| !names.is_empty())
+    }
+
+    /// A hash of the type ids and the message types their Actors handle, in the order they are tried.
+    ///
+    /// Machines exchange their fingerprints when connecting. If they differ, the machines would misinterpret each other's
+    /// messages, so the Environment refuses to start with [IncompatibleBuild](../api/enum.ActlibError.html#variant.IncompatibleBuild).
+    pub fn fingerprint(&self) -> u64 {
+        let mut type_ids: Vec<&String> = self.type_ids.iter().collect();
+        type_ids.sort();
+        type_ids.dedup();
+        let mut fingerprint = FNV_OFFSET_BASIS;
+        for type_id in type_ids {
+            fingerprint = fnv1a(fingerprint, type_id.as_bytes());
+            for name in self.message_types.get(type_id).copied().unwrap_or(&[]) {
+                // separators keep ["ab", "c"] and ["a", "bc"] apart
+                fingerprint = fnv1a(fingerprint, &[0]);
+                fingerprint = fnv1a(fingerprint, name.as_bytes());
+            }
+            fingerprint = fnv1a(fingerprint, &[1]);
+        }
+        fingerprint
+    }
+
+    /// Check every user specified id of the given type id with *validator* before an Actor is spawned for it.
+    ///
+    /// A rejected id fails the spawn with ```ActlibError::InvalidId```, carrying the reason returned by *validator*,
+    /// so the Actor never gets to see it. Automatic ids are not checked.
+    ///
+    /// ```rust,ignore
+    /// let actor_builder = actor_builder!("Field" => Field::new())
+    ///     .with_id_validator("Field", |id| {
+    ///         bincode::deserialize::<Position>(id)
+    ///             .map(|_| ())
+    ///             .map_err(|e| format!("{:?}", e))
+    ///     });
+    /// ```
+    pub fn with_id_validator(
+        mut self,
+        type_id: &str,
+        validator: fn(&[u8]) -> Result<(), String>,
+    ) -> Self {
+        self.id_validators.insert(type_id.to_string(), validator);
+        self
+    }
+
+    /// Check the user specified id of a new Actor of the given type id.
+    pub(crate) fn validate_id(&self, type_id: &str, local_id: &LocalId) -> Result<(), ActlibError> {
+        match (local_id, self.id_validators.get(type_id)) {
+            (LocalId::Specified(id), Some(validator)) => validator(id).map_err(|reason| {
+                ActlibError::InvalidId(format!(
+                    "{:?} is no valid id for {}: {}",
+                    id, type_id, reason
+                ))
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Build a new Actor of the given type id.
+    pub(crate) fn build(&self, type_id: &str) -> Result<Box<dyn Actor>, ActlibError> {
+        (self.build)(type_id)
+    }
+
+    /// The type ids this ActorBuilder is able to build.
+    pub fn type_ids(&self) -> &[String] {
+        &self.type_ids
+    }
+}
+
+impl Debug for ActorBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "ActorBuilder {{build: /*omitted*/, type_ids: {:?}, validated: {:?}}}",
+            self.type_ids,
+            self.id_validators.keys().collect::<Vec<_>>()
+        )
+    }
+}
+
 /// Environment holding the ```Sender```-End of a Channel to every [Actor](../actor/trait.Actor.html).
 ///
 /// It can spawn new [Actors](../actor/trait.Actor.html) and is responsible that messages to/from an external environment reach the specified [Actor](../actor/trait.Actor.html).
 pub(crate) struct LocalEnvironment {
     /// Holds the channels towards the mailbox of every Actor living in this Environment, indexed by it's ActorId
-    local_actor_channels: Mutex<HashMap<ActorId, Sender<EitherMessage>>>,
+    local_actor_channels: ContendedMutex<HashMap<ActorId, LocalActor>>,
+    /// Which Actor spawned which, only filled if lineage tracing is enabled.
+    lineage: Mutex<HashMap<ActorId, LineageRecord>>,
     /// Holds the sender of the channel to use for all ActorRefs with actors living on another machine.
     /// The channel content is a <b>tuple</b> of (ActorId,Box[Message as Any]).
     /// This is being held for future cloning when creating new ActorRefs.
@@ -76,28 +248,176 @@ pub(crate) struct LocalEnvironment {
     external_actor_ref_sender: Mutex<Sender<(ActorId, SerNetMessageContent)>>,
     /// Unique local address of this machine
     pub local_machine: SocketAddr,
+    /// Stable identity of this machine, used instead of its address
+    pub(crate) machine_id: MachineId,
+    /// The remote machines this Environment is connected to, late machines are added once they connect.
+    peers: RwLock<Vec<(MachineId, Peer)>>,
+    /// The connection state of every configured remote machine, in the order they were given.
+    peer_status: Mutex<Vec<PeerStatus>>,
+    /// Notified about every change of the connection state of a remote machine.
+    peer_watchers: Watchers<PeerStatus>,
     /// Mapping from Machine-identifier to associated TCP-connection.
-    net_senders: Mutex<IndexMap<IpAddr, NetSender>>,
+    net_senders: ContendedMutex<IndexMap<MachineId, NetSender>>,
+    /// Frames sent to and received from every remote machine.
+    traffic: RwLock<Vec<MachineTraffic>>,
     /// How to build a new Actor specified by a Type Id
-    actor_builder: fn(&str) -> Result<Box<dyn Actor>, ActlibError>,
+    pub(crate) actor_builder: ActorBuilder,
+    /// Options this Environment was created with
+    options: EnvironmentOptions,
     /// Sender-end of a channel the main thread is supposed to block on the Receiver.
-    termination_sender: Mutex<Sender<()>>,
+    termination_sender: Mutex<Sender<ExpirationResult>>,
+    /// Set while the local Actors are being drained, collects the number of discarded messages of each stopped Actor.
+    drain_listener: Mutex<Option<Sender<(StopRecord, usize)>>>,
+    /// Set while this machine waits for the DrainReports of the remote machines.
+    expiration_reports: Mutex<Option<Sender<DrainReport>>>,
     /// Load Balancer for distributing the spawn process of new Actors
     load_balancer: Mutex<LoadBalancer>,
     /// A map for alive-queries about actors located on a remote machine
     /// queried_id, searcher_id
-    remote_queries: Mutex<HashMap<(Vec<u8>, ActorId), Sender<Option<ActorRef>>>>,
+    remote_queries: ContendedMutex<RemoteQueries>,
     /// Actors protected by other Actors. They can't be removed.
     /// target_id, protector_id
     invincible_actors: RwLock<HashMap<ActorId, HashSet<ActorId>>>,
+    /// Notified about every resolved id conflict.
+    id_conflict_watchers: Watchers<IdConflict>,
+    /// Notified about every local Actor exceeding the mailbox thresholds.
+    mailbox_alert_watchers: Watchers<MailboxAlert>,
+    /// Notified about every local Actor that panicked.
+    actor_failure_watchers: Watchers<ActorFailed>,
+    /// When the local Actors panicked within the last FAILURE_WINDOW.
+    recent_failures: Mutex<VecDeque<Instant>>,
+    /// Number of local Actors that panicked since the Environment was created.
+    actors_failed: AtomicUsize,
+    /// The counters behind the metrics of the local machine.
+    instruments: Instruments,
+    /// Remote machines whose connection failed, with the error.
+    dead_links: Mutex<HashMap<MachineId, String>>,
+    /// Messages for remote machines whose connection is being re-established.
+    outbox: Mutex<HashMap<MachineId, Outbox>>,
+    /// The type id of every keyed Actor this machine spawned or found, only kept if dead peer policies are configured.
+    keyed_types: Mutex<HashMap<Vec<u8>, String>>,
+    /// Keyed Actors of disconnected machines that were respawned elsewhere, with their replacement.
+    redirects: Mutex<HashMap<ActorId, ActorId>>,
+    /// Notified about every Message dropped by a dead peer policy.
+    dead_letter_watchers: Watchers<DeadLetter>,
+    /// Notified about every Message or lookup addressed to an earlier incarnation of a local Actor.
+    stale_incarnation_watchers: Watchers<StaleIncarnation>,
+    /// Notified about every Message to a remote Actor whose delivery was not confirmed.
+    delivery_failure_watchers: Watchers<DeliveryFailure>,
+    /// Notified about every dropped Message.
+    message_dropped_watchers: Watchers<MessageDropped>,
+    /// The latest load of every machine, with the time it arrived.
+    machine_loads: Mutex<HashMap<MachineId, (Instant, MachineLoad)>>,
+    /// Actors receiving a ClusterLoad after every load measurement.
+    load_subscribers: Mutex<Vec<ActorRef>>,
+    /// The shares of every machine in the Counters and Gauges.
+    pub(crate) counters: Arc<CounterRegistry>,
+    /// Actors anywhere in the cluster receiving a LifecycleEvent for the local Actors, by Actor type id.
+    lifecycle_subscribers: Mutex<HashMap<String, Vec<ActorRef>>>,
+    /// Which machines recently looked up or messaged every specified id.
+    correspondents: Mutex<Correspondents>,
+    /// Machines without direct connection that relayed messages reached this machine from.
+    /// origin, peer the relay arrived from
+    learned_routes: Mutex<HashMap<MachineId, MachineId>>,
+    /// Forwarding entries for ids of local Actors that were replaced.
+    aliases: Mutex<HashMap<ActorId, Alias>>,
+    /// The registered names of Actors on every machine, replicated to all machines.
+    names: Mutex<HashMap<String, ActorId>>,
+    /// Number of the last sequenced broadcast sent from this machine.
+    broadcast_seq: AtomicU64,
+    /// Held by the leader while it sends a broadcast in total order to all machines and the local Actors.
+    total_order: Mutex<()>,
+    /// Set once the expiration started, new spawns are rejected from then on.
+    shutting_down: AtomicBool,
+    /// Set once the bootstrap machine finished the bootstrap.
+    bootstrapped: Mutex<bool>,
+    /// Wakes the constructors waiting for the bootstrap.
+    bootstrap_done: Condvar,
+    /// Handled messages, by Actor type id and message type name.
+    handler_stats: Mutex<HashMap<(String, &'static str), HandlerStats>>,
+    /// Answered asks, by the type ids of the requester and the responder.
+    reply_stats: Mutex<HashMap<(String, String), ReplyStats>>,
+    /// Payloads stashed on this machine, by the id of their ticket.
+    stashed_payloads: Mutex<HashMap<Uuid, StashedPayload>>,
+    /// Threads waiting for a payload from a remote machine, by request number.
+    pending_redemptions: Mutex<HashMap<u64, Sender<Option<Vec<u8>>>>>,
+    /// Threads waiting for a remote machine to acknowledge a spawn, by spawn number.
+    pending_spawns: Mutex<HashMap<u64, Sender<Result<u64, String>>>>,
+    /// Threads waiting for a remote machine to answer a ping with the time the answer arrived, by ping number.
+    pending_pings: Mutex<HashMap<u64, Sender<Instant>>>,
+    /// The outcome of the latest pings of every remote machine.
+    pings: Mutex<HashMap<MachineId, PingRecord>>,
+    /// The external listeners of the local machine, by port.
+    external_listeners: Mutex<HashMap<u16, ExternalListener>>,
+    /// Handovers of external listeners waiting for the remote machine, by handover_no.
+    pending_handovers: Mutex<HashMap<u64, Sender<Option<String>>>>,
+    /// Threads collecting the states of the Actors of a type from the remote machines, by request number.
+    pending_collections: Mutex<HashMap<u64, Sender<ExportedStates>>>,
+    /// Actor listings of the cluster waiting for the remote machines, by request_no
+    pending_listings: Mutex<HashMap<u64, Arc<ListingSlot>>>,
+    /// Messages to remote Actors waiting for their Delivered acknowledgement, by delivery number.
+    unacknowledged: Mutex<HashMap<u64, Unacknowledged>>,
+    /// The outcome of the recently received Tracked messages, so retries are acknowledged without delivering them again.
+    deliveries: Mutex<Deliveries>,
+    /// The log of the unacknowledged messages, if they are kept across restarts.
+    durable_outbox: Option<Mutex<DurableOutbox>>,
+    /// Registered local Actors whose start waits for the handler that spawned them, see [Context::spawn_after_handle](../actor/struct.Context.html#method.spawn_after_handle).
+    held_starts: Mutex<HashMap<ActorId, HeldStart>>,
+    /// Replies to the asks sent from this machine.
+    pub(crate) replies: Arc<PendingReplies>,
+    /// Watchers of the local Actors.
+    pub(crate) death_watches: Arc<DeathWatches>,
+    /// The maximum message size agreed on with every remote machine.
+    pub(crate) message_limits: Arc<MessageLimits>,
+    /// Runs the scheduled tasks of this Environment.
+    pub(crate) timer: Arc<Timer>,
+    /// Restarts the internal threads that panicked.
+    supervisor: Arc<Supervisor>,
+    /// Runs the mailbox loops of the local Actors on a pool of worker threads, if the [Runtime](../options/enum.Runtime.html) is the pool.
+    pub(crate) scheduler: Option<Scheduler>,
+    /// Runs the mailbox loops of the local Actors as tasks, if the [Runtime](../options/enum.Runtime.html) is async.
+    #[cfg(feature = "async-runtime")]
+    pub(crate) executor: Option<tokio::runtime::Runtime>,
+}
+
+#[cfg(feature = "async-runtime")]
+impl Drop for LocalEnvironment {
+    fn drop(&mut self) {
+        // the last reference may be dropped by an Actor task, where the runtime cannot wait for its workers
+        if let Some(executor) = self.executor.take() {
+            executor.shutdown_background();
+        }
+    }
 }
 
 impl Debug for LocalEnvironment {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        write!(f, "LocalEnvironment {{local_actor_channels: {:?}, external_actor_ref_sender: {:?}, local_machine: {:?}, net_senders: {:?}, actor_builder: /*omitted*/, termination_sender: {:?}, load_balancer: {:?}}}", self.local_actor_channels, self.external_actor_ref_sender, self.local_machine, self.net_senders, self.termination_sender, self.load_balancer)
+        write!(f, "LocalEnvironment {{local_actor_channels: {:?}, external_actor_ref_sender: {:?}, local_machine: {:?}, peers: {:?}, net_senders: {:?}, actor_builder: {:?}, termination_sender: {:?}, load_balancer: {:?}}}", self.local_actor_channels, self.external_actor_ref_sender, self.local_machine, self.peers, self.net_senders, self.actor_builder, self.termination_sender, self.load_balancer)
     }
 }
 
+/// Everything the Environment knows about an Actor living on this machine.
+#[derive(Debug)]
+pub(crate) struct LocalActor {
+    /// The channel towards the Actor's mailbox
+    pub(crate) sender: MailboxSender,
+    /// The type id the Actor was built from
+    pub(crate) type_id: String,
+    /// The Actor that spawned this Actor, ```None``` if it was spawned from outside an Actor
+    pub(crate) spawner: Option<ActorId>,
+    /// When the Actor was spawned, decides which Actor survives an id conflict
+    pub(crate) spawned_at: SystemTime,
+    /// The restricted operations the Actor may perform
+    pub(crate) capabilities: Vec<Capability>,
+}
+
+/// Serialized messages waiting for the connection to a remote machine to come back.
+#[derive(Debug, Default)]
+struct Outbox {
+    messages: VecDeque<Vec<u8>>,
+    bytes: usize,
+}
+
 /// How should the LocalId-part of the ActorId be created
 pub(crate) enum SpawnId {
     /// Create Automatic, currently using Uuid::new_v4
@@ -128,877 +448,8000 @@ impl SpawnId {
     }
 }
 
-/// Buffer-size for reading remote messages.
-const BUFFERSIZE: usize = 1 * 1024 * 512;
+thread_local! {
+    /// The Actor whose code is currently executed on this thread, with the number of messages it sent to itself so far.
+    static HANDLING_ACTOR: RefCell<Option<(ActorId, usize)>> = const { RefCell::new(None) };
+    /// The Environment of the Actor whose code is currently executed on this thread, see [Context](../actor/struct.Context.html).
+    static HANDLING_ENV: RefCell<Option<Environment>> = const { RefCell::new(None) };
+    /// The sender of the message handled on this thread, see [Context::sender](../actor/struct.Context.html#method.sender).
+    static HANDLING_SENDER: RefCell<Option<ActorId>> = const { RefCell::new(None) };
+    /// The key of the message handled on this thread, if it was sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed).
+    static HANDLING_KEY: Cell<Option<u64>> = const { Cell::new(None) };
+    /// Set by [Context::stash](../actor/struct.Context.html#method.stash) during the handler executed on this thread.
+    static STASH_CURRENT: Cell<bool> = const { Cell::new(false) };
+    /// Set by [Context::unstash_all](../actor/struct.Context.html#method.unstash_all) during the code executed on this thread.
+    static UNSTASH_ALL: Cell<bool> = const { Cell::new(false) };
+    /// Start of the current time slice of the handler executed on this thread, if a TimeBudget is configured.
+    static TIME_SLICE: RefCell<Option<(Instant, TimeBudget)>> = const { RefCell::new(None) };
+    /// Actors spawned with [Context::spawn_after_handle](../actor/struct.Context.html#method.spawn_after_handle) during the code executed on this thread.
+    static DEFERRED_STARTS: RefCell<Vec<ActorId>> = const { RefCell::new(Vec::new()) };
+}
 
-impl LocalEnvironment {
-    /// Create a new Environment.
-    ///
-    /// [Actors](../actor/trait.Actor.html) can be located either on the same machine,
-    /// or on any [Machine](../../netchannel/struct.Machine.html) given in the argument.
-    ///
-    /// *own_port* is used to establish a TCP-connection to remote machines.
-    ///
-    /// It is not possible to add new machines after creation of the environment.
-    pub(crate) fn new(
-        own_port: u16,
-        mut remotes: Vec<SocketAddr>,
-        actor_builder: fn(&str) -> Result<Box<dyn Actor>, ActlibError>,
-        termination_sender: Sender<()>,
-    ) -> ArcEnvironment {
-        // create the ActorRef -> Env channel for this environment
-        let (external_actor_ref_sender, external_actor_ref_receiver): (
-            Sender<(ActorId, SerNetMessageContent)>,
-            Receiver<(ActorId, SerNetMessageContent)>,
-        ) = channel();
+/// Starts the mailbox loop of an Actor spawned with [Context::spawn_after_handle](../actor/struct.Context.html#method.spawn_after_handle).
+struct HeldStart(Box<dyn FnOnce() + Send>);
 
-        // construct local machine identifier
-        let local_machine;
-        match get_if_addrs::get_if_addrs() {
-            Ok(ifaces) => {
-                match ifaces
+impl Debug for HeldStart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HeldStart")
+    }
+}
+
+/// Run the handler *f* within the optional time budget, returning the number of messages the Actor sent to itself meanwhile.
+///
+/// A handler exceeding its slice lets its Actor give way once more before the next message.
+fn run_handler<F: FnOnce()>(
+    env: &Environment,
+    actor_id: &ActorId,
+    budget: &Option<TimeBudget>,
+    f: F,
+) -> usize {
+    let budget = match budget {
+        Some(budget) => budget,
+        None => return run_as_actor(env, actor_id, f),
+    };
+    let started = Instant::now();
+    TIME_SLICE.with(|slice| slice.replace(Some((started, budget.clone()))));
+    let self_sends = run_as_actor(env, actor_id, f);
+    let exceeded = TIME_SLICE.with(|slice| match slice.replace(None) {
+        Some((slice_started, _)) => slice_started.elapsed() > budget.slice,
+        None => false,
+    });
+    if exceeded {
+        budget.penalty.apply();
+    }
+    self_sends
+}
+
+/// Give way to other Actors if the handler executed on this thread used up its time slice, see [checkpoint](../actor/fn.checkpoint.html).
+pub(crate) fn check_time_slice() -> bool {
+    TIME_SLICE.with(|slice| match &mut *slice.borrow_mut() {
+        Some((started, budget)) if started.elapsed() > budget.slice => {
+            budget.penalty.apply();
+            *started = Instant::now();
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Run *f* on behalf of the given Actor, returning the number of messages the Actor sent to itself meanwhile.
+fn run_as_actor<F: FnOnce()>(env: &Environment, actor_id: &ActorId, f: F) -> usize {
+    let previous = HANDLING_ACTOR.with(|handling| handling.replace(Some((actor_id.clone(), 0))));
+    let previous_env = HANDLING_ENV.with(|handling| handling.replace(Some(env.clone())));
+    let previous_deferred = DEFERRED_STARTS.with(|deferred| deferred.replace(Vec::new()));
+    STASH_CURRENT.with(|stash| stash.set(false));
+    UNSTASH_ALL.with(|unstash| unstash.set(false));
+    f();
+    HANDLING_ENV.with(|handling| handling.replace(previous_env));
+    let self_sends = HANDLING_ACTOR.with(|handling| match handling.replace(previous) {
+        Some((_, self_sends)) => self_sends,
+        None => 0,
+    });
+    // the children start once the code that spawned them returned, even if it panicked
+    for actor_id in DEFERRED_STARTS.with(|deferred| deferred.replace(previous_deferred)) {
+        env.env.start_held(&actor_id);
+    }
+    self_sends
+}
+
+/// Spawn an Actor that starts once the code executed on this thread returned, see [Context::spawn_after_handle](../actor/struct.Context.html#method.spawn_after_handle).
+pub(crate) fn spawn_after_handle(
+    actor_type_id: &str,
+    mut options: SpawnOptions,
+) -> Result<ActorRef, ActlibError> {
+    let env = match (current_env(), current_actor()) {
+        (Some(env), Some(_)) => env,
+        _ => {
+            return Err(ActlibError::InvalidState(
+                "Context::spawn_after_handle called outside of an Actor".to_string(),
+            ))
+        }
+    };
+    options.hold_start = true;
+    let actor_ref = LocalEnvironment::spawn(env, actor_type_id, SpawnId::Automatic, &options)?;
+    DEFERRED_STARTS.with(|deferred| deferred.borrow_mut().push(actor_ref.clone_id()));
+    Ok(actor_ref)
+}
+
+/// The Environment of the Actor whose code is currently executed on this thread, if any.
+pub(crate) fn current_env() -> Option<Environment> {
+    HANDLING_ENV.with(|handling| handling.borrow().clone())
+}
+
+/// The Actor that sent the message handled on this thread, if any.
+pub(crate) fn current_sender() -> Option<ActorId> {
+    HANDLING_SENDER.with(|sender| sender.borrow().clone())
+}
+
+/// The key of the message handled on this thread, if it was sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed).
+pub(crate) fn current_key() -> Option<u64> {
+    HANDLING_KEY.with(|key| key.get())
+}
+
+/// Keep the message handled on this thread for [unstash_all](../actor/struct.Context.html#method.unstash_all), or hand the stashed messages back.
+pub(crate) fn request_stash(unstash_all: bool) {
+    if unstash_all {
+        UNSTASH_ALL.with(|unstash| unstash.set(true));
+    } else {
+        STASH_CURRENT.with(|stash| stash.set(true));
+    }
+}
+
+/// Wakes a thread parked in [block_on](fn.block_on.html).
+struct ThreadWaker(std::thread::Thread);
+
+impl std::task::Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run the *future* to completion on the current thread.
+///
+/// On a worker of a multi-threaded tokio runtime the other tasks move to another worker meanwhile.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    #[cfg(feature = "async-runtime")]
+    {
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread {
+                return tokio::task::block_in_place(|| handle.block_on(future));
+            }
+        }
+    }
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut context = Context::from_waker(&waker);
+    scheduler::blocking(|| loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    })
+}
+
+/// Run *f*, turning a panic into its message.
+///
+/// The Actor's state may be inconsistent afterwards, the [FailurePolicy](../api/enum.FailurePolicy.html) decides whether it carries on.
+fn catch_panic<F: FnOnce()>(f: F) -> Result<(), String> {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "<no panic message>".to_string()
+        }
+    })
+}
+
+/// The Actor whose code is currently executed on this thread, if any.
+pub(crate) fn current_actor() -> Option<ActorId> {
+    HANDLING_ACTOR.with(|handling| {
+        handling
+            .borrow()
+            .as_ref()
+            .map(|(actor_id, _)| actor_id.clone())
+    })
+}
+
+/// Called for every message put into a local mailbox, counts the messages a handler sends to its own Actor.
+pub(crate) fn record_local_send(target: &ActorId) {
+    HANDLING_ACTOR.with(|handling| {
+        if let Some((actor_id, self_sends)) = &mut *handling.borrow_mut() {
+            if actor_id == target {
+                *self_sends += 1;
+            }
+        }
+    });
+}
+
+/// Watches the handlers of a single Actor for messages sent to itself, see [SelfSendGuard](../options/struct.SelfSendGuard.html).
+struct SelfSendMonitor {
+    guard: SelfSendGuard,
+    /// Number of consecutive handlers that sent a message to their own Actor
+    depth: usize,
+    /// Whether each of the last handled messages caused a self-send
+    recent: VecDeque<bool>,
+    /// Number of `true` entries in recent
+    recent_self_sends: usize,
+    depth_warned: bool,
+    ratio_warned: bool,
+}
+
+impl SelfSendMonitor {
+    fn new(guard: SelfSendGuard) -> Self {
+        SelfSendMonitor {
+            recent: VecDeque::with_capacity(guard.window),
+            guard,
+            depth: 0,
+            recent_self_sends: 0,
+            depth_warned: false,
+            ratio_warned: false,
+        }
+    }
+
+    /// Record whether a handler sent messages to its own Actor.
+    fn record(&mut self, actor_id: &ActorId, sent_to_self: bool) {
+        if sent_to_self {
+            self.depth += 1;
+            if self.depth > self.guard.max_depth && !self.depth_warned {
+                warn!(
+                    "Actor {:?} sent messages to itself in {} consecutive handlers, it may starve other senders.",
+                    actor_id, self.depth
+                );
+                self.depth_warned = true;
+            }
+            if self.depth > 1 {
+                self.guard.yield_policy.apply();
+            }
+        } else {
+            self.depth = 0;
+            self.depth_warned = false;
+        }
+
+        if self.guard.window == 0 {
+            return;
+        }
+        if self.recent.len() == self.guard.window {
+            if let Some(true) = self.recent.pop_front() {
+                self.recent_self_sends -= 1;
+            }
+        }
+        self.recent.push_back(sent_to_self);
+        if sent_to_self {
+            self.recent_self_sends += 1;
+        }
+        if self.recent.len() == self.guard.window {
+            let ratio = self.recent_self_sends as f64 / self.guard.window as f64;
+            if ratio > self.guard.max_ratio && !self.ratio_warned {
+                warn!(
+                    "Actor {:?} sent messages to itself in {:.0}% of the last {} handlers, it may starve other senders.",
+                    actor_id,
+                    ratio * 100.0,
+                    self.guard.window
+                );
+                self.ratio_warned = true;
+            } else if ratio <= self.guard.max_ratio {
+                self.ratio_warned = false;
+            }
+        }
+    }
+}
+
+/// A local Actor whose on_start method completed, handling the messages of its mailbox one by one.
+///
+/// The messages are taken from the mailbox by the thread or task running the Actor.
+struct RunningActor {
+    mailbox: Mailbox,
+    actor: Box<dyn Actor>,
+    env: Environment,
+    actor_id: ActorId,
+    type_id: String,
+    time_budget: Option<TimeBudget>,
+    self_send_monitor: Option<SelfSendMonitor>,
+    /// Messages put aside by [Context::stash](../actor/struct.Context.html#method.stash), oldest first.
+    stash: Vec<(Box<dyn Any + Send>, Option<ActorId>)>,
+}
+
+impl RunningActor {
+    /// Run the on_start method of the Actor, returning ```None``` if the Actor stopped right away.
+    fn start(
+        mut mailbox: Mailbox,
+        mut actor: Box<dyn Actor>,
+        env: Environment,
+        this_actor_ref: ActorRef,
+        type_id: String,
+    ) -> Option<Self> {
+        // keep a ActorId copy at hand
+        let this_actor_id = this_actor_ref.clone_id();
+
+        // actor is now registered and has a mailbox, call on_start
+        // no message is dequeued before on_start completed
+        let mut failure = None;
+        run_as_actor(&env, &this_actor_id, || {
+            failure = catch_panic(|| actor.on_start(env.clone(), this_actor_ref)).err();
+        });
+        if let Some(reason) = failure {
+            if env
+                .env
+                .report_failure(&this_actor_id, &type_id, None, reason)
+            {
+                LocalEnvironment::stop_failed_actor(
+                    &env,
+                    &mut actor,
+                    &mut mailbox,
+                    this_actor_id,
+                    type_id,
+                );
+                return None;
+            }
+        }
+
+        // everything that arrived while on_start was running is handled according to the policy
+        if env.env.options.pre_start_messages == PreStartMessages::Discard
+            && mailbox.buffer_pending() > 0
+        {
+            let discarded = mailbox.discard_buffered(|msg| {
+                matches!(msg, EitherMessage::Special(_) | EitherMessage::Query(_))
+            });
+            info!(
+                "Actor {:?} discarded {} messages received before on_start completed.",
+                this_actor_id,
+                discarded.len()
+            );
+            env.env.report_discarded(
+                actor.as_ref(),
+                &this_actor_id,
+                DropCause::BeforeStart,
+                discarded,
+            );
+        }
+
+        let time_budget = env.env.options.time_budget.clone();
+
+        let self_send_monitor = env
+            .env
+            .options
+            .self_send_guard
+            .clone()
+            .map(SelfSendMonitor::new);
+
+        Some(RunningActor {
+            mailbox,
+            actor,
+            env,
+            actor_id: this_actor_id,
+            type_id,
+            time_budget,
+            self_send_monitor,
+            stash: Vec::new(),
+        })
+    }
+
+    /// Stop the Actor on request, treating the pending messages according to the StopPolicy of its type.
+    ///
+    /// With a *successor*, the pending messages are forwarded to it instead, whatever the StopPolicy.
+    /// Returns ```false``` like [process](#method.process) does once the Actor stopped.
+    fn stop(&mut self, reason: StopReason, successor: Option<ActorId>) -> bool {
+        let policy = self
+            .env
+            .env
+            .options
+            .stop_policies
+            .get(&self.type_id)
+            .copied()
+            .unwrap_or_default();
+        let pending = match policy {
+            _ if successor.is_some() => None,
+            StopPolicy::DropRemaining => None,
+            StopPolicy::DrainThenStop => {
+                if !self.drain() {
+                    // a drained message made the Actor fail, it is stopped already
+                    return false;
+                }
+                None
+            }
+            StopPolicy::HandOver => Some(self.take_pending()),
+        };
+        let outcome = LocalEnvironment::run_on_stop(
+            &self.env,
+            &mut self.actor,
+            &self.actor_id,
+            reason,
+            pending,
+        );
+        self.env.env.remove(self.actor_id.clone());
+        let mut discarded = match successor {
+            Some(successor) => {
+                let stashed = std::mem::take(&mut self.stash)
                     .into_iter()
-                    .filter(|iface| !iface.is_loopback())
-                    .next()
-                {
-                    Some(interface) => {
-                        local_machine = SocketAddr::new(interface.ip(), own_port);
-                        println!(
-                            "Starting up Environment on local machine: {:?}",
-                            local_machine
-                        );
-                    }
-                    None => {
-                        panic!("Could not find local network connection");
+                    .map(|(msg, sender)| Envelope {
+                        message: EitherMessage::Regular(msg),
+                        size: 0,
+                        key: None,
+                        sender,
+                    });
+                let mut pending: Vec<Envelope> = stashed.collect();
+                pending.extend(self.mailbox.take_pending_envelopes());
+                self.env
+                    .env
+                    .forward_pending(&self.actor_id, successor, pending)
+            }
+            None => self.mailbox.discard_pending(),
+        };
+        discarded.extend(
+            std::mem::take(&mut self.stash)
+                .into_iter()
+                .map(|(msg, _sender)| EitherMessage::Regular(msg)),
+        );
+        let messages_discarded = discarded.len();
+        self.env.env.report_discarded(
+            self.actor.as_ref(),
+            &self.actor_id,
+            DropCause::ActorRemoved,
+            discarded,
+        );
+        self.env.env.report_drained(
+            StopRecord {
+                actor: self.actor_id.clone(),
+                type_id: self.type_id.clone(),
+                outcome,
+            },
+            messages_discarded,
+        );
+        false
+    }
+
+    /// Handle the stashed messages and those queued when the Actor was asked to stop, returning ```false``` if it failed meanwhile.
+    fn drain(&mut self) -> bool {
+        self.mailbox.unstash(std::mem::take(&mut self.stash));
+        for _ in 0..self.mailbox.buffer_pending() {
+            match self.mailbox.try_msg() {
+                // a repeated stop request is answered by the one being handled
+                Ok(Some(EitherMessage::Special(Token::Stop(_))))
+                | Ok(Some(EitherMessage::Special(Token::StopAndForward(_)))) => {}
+                Ok(Some(msg)) => {
+                    if !self.process(Ok(msg)) {
+                        return false;
                     }
                 }
-            }
-            Err(e) => {
-                panic!("Could not find local network connection: {:?}", e);
+                Ok(None) | Err(_) => break,
             }
         }
+        true
+    }
 
-        // remove self from remotes (if it was passed there)
-        remotes = remotes
+    /// Take the stashed and queued messages out of the mailbox, oldest first, skipping tokens and queries.
+    fn take_pending(&mut self) -> Vec<Box<dyn Any + Send>> {
+        let mut pending: Vec<Box<dyn Any + Send>> = std::mem::take(&mut self.stash)
             .into_iter()
-            .filter(|remote| remote.ip() != local_machine.ip())
+            .map(|(msg, _sender)| msg)
             .collect();
+        for _ in 0..self.mailbox.buffer_pending() {
+            match self.mailbox.try_msg() {
+                Ok(Some(EitherMessage::Regular(msg))) => pending.push(msg),
+                Ok(Some(EitherMessage::Serialized(msg_serialized))) => {
+                    if let Some(msg) = msg_serialized
+                        .deserialize(self.actor.as_ref(), self.env.env.options.wire_format)
+                    {
+                        pending.push(msg);
+                    }
+                }
+                // dropping an unanswerable query disconnects the waiting caller
+                Ok(Some(_)) => {}
+                Ok(None) | Err(_) => break,
+            }
+        }
+        pending
+    }
 
-        let num_machines = 1 + remotes.len();
-        // Create net_channels map
-        let net_senders = Mutex::new(IndexMap::with_capacity(remotes.len()));
-        let mut net_receivers = Vec::with_capacity(remotes.len());
+    /// Handle a message taken from the mailbox, returning ```false``` once the Actor stopped.
+    fn process(&mut self, msg: Result<EitherMessage, RecvError>) -> bool {
+        let failure = match self.dispatch(msg) {
+            Dispatched::Processed(failure) => failure,
+            Dispatched::Finished(running) => return running,
+            Dispatched::Handle(msg) => self.handle_message(msg),
+        };
+        self.conclude(failure)
+    }
+
+    /// Like [process](#method.process), but the future of an async handler is awaited instead of blocking the thread.
+    #[cfg(feature = "async-runtime")]
+    async fn process_async(&mut self, msg: Result<EitherMessage, RecvError>) -> bool {
+        let failure = match self.dispatch(msg) {
+            Dispatched::Processed(failure) => failure,
+            Dispatched::Finished(running) => return running,
+            Dispatched::Handle(msg) => self.handle_message_async(msg).await,
+        };
+        self.conclude(failure)
+    }
 
-        // connect to remote machines
-        for remote in &remotes {
-            let mut net_channel = NetChannel::new(local_machine.clone(), remote.clone());
-            match net_channel.split() {
-                Ok((sender, receiver)) => {
-                    if let Ok(mut senders) = net_senders.lock() {
-                        senders.insert(remote.ip(), sender);
-                        net_receivers.push(receiver);
+    /// Process the tokens and queries, unwrapping the messages for the handlers of the Actor.
+    fn dispatch(&mut self, msg: Result<EitherMessage, RecvError>) -> Dispatched {
+        let env = &self.env;
+        let dropped = self.mailbox.take_dropped();
+        if !dropped.is_empty() {
+            env.env.report_discarded(
+                self.actor.as_ref(),
+                &self.actor_id,
+                DropCause::MailboxFull,
+                dropped,
+            );
+        }
+        // The messages are handled sequentially, and special Token messages may be handled without direct outside visibility to the actlib API.
+        //
+        // A panic is caught and reported, along with the type of the message that caused it.
+        match msg {
+            Ok(EitherMessage::Special(Token::Stop(reason))) => {
+                // local case:
+                if let Ok(inv_actors) = env.env.invincible_actors.read() {
+                    if inv_actors.contains_key(&self.actor_id) {
+                        return Dispatched::Finished(true);
                     }
                 }
-                Err(e) => {
-                    panic!("Could not split NetChannel instance: {:?}", e);
+                Dispatched::Finished(self.stop(reason, None))
+            }
+            Ok(EitherMessage::Special(Token::StopAndForward(successor))) => {
+                if let Ok(inv_actors) = env.env.invincible_actors.read() {
+                    if inv_actors.contains_key(&self.actor_id) {
+                        return Dispatched::Finished(true);
+                    }
+                }
+                Dispatched::Finished(self.stop(StopReason::Removed, Some(successor)))
+            }
+            Ok(EitherMessage::Special(Token::Reset)) => {
+                // triggers the optional user-given on_reset function of this actor
+                let mut failure = None;
+                let actor = &mut self.actor;
+                run_as_actor(env, &self.actor_id, || {
+                    failure = catch_panic(|| actor.on_reset()).err();
+                });
+                Dispatched::Processed(failure.map(|reason| (None, reason)))
+            }
+            Ok(EitherMessage::Query(Query(query))) => {
+                // a query the Actor cannot answer drops its result sender, which disconnects the waiting caller
+                let mut failure = None;
+                let actor = self.actor.as_ref();
+                run_as_actor(env, &self.actor_id, || {
+                    failure = catch_panic(|| query(actor)).err();
+                });
+                Dispatched::Processed(failure.map(|reason| (None, reason)))
+            }
+            Ok(EitherMessage::Regular(msg)) => Dispatched::Handle(msg),
+            Ok(EitherMessage::Serialized(msg_serialized)) => {
+                match msg_serialized
+                    .deserialize(self.actor.as_ref(), self.env.env.options.wire_format)
+                {
+                    Some(msg) => Dispatched::Handle(msg),
+                    None => {
+                        env.env.report_dropped(
+                            DropCause::UnhandledType,
+                            Some(self.actor_id.clone()),
+                            None,
+                        );
+                        Dispatched::Processed(None)
+                    }
                 }
             }
+            Err(recv_error) => {
+                error!("Actor Mailbox ended! {:?}", recv_error);
+                // no one holds the sender end anymore (even Environment dropped)
+                // so it is save to stop here
+                Dispatched::Finished(false)
+            }
         }
+    }
 
-        // Create new Environment instance
-        let env = Arc::new(LocalEnvironment {
-            local_actor_channels: Mutex::new(HashMap::new()),
-            external_actor_ref_sender: Mutex::new(external_actor_ref_sender),
-            local_machine,
-            net_senders,
-            actor_builder,
-            termination_sender: Mutex::new(termination_sender),
-            load_balancer: Mutex::new(LoadBalancer::new(num_machines)),
-            remote_queries: Mutex::new(HashMap::new()),
-            invincible_actors: RwLock::new(HashMap::new()),
-        });
-
-        // if no remote exist there is no need to create threads dedicated to handling remote connections
-        if !remotes.is_empty() {
-            let env_remote_send = env.clone();
-            // Start listener Thread for message passing to an external environment.
-            //
-            // Messages are sent to this environment's receiver, serialized and send to the specified machine.
-            std::thread::spawn(move || {
-                LocalEnvironment::wait_for_local_messages(
-                    env_remote_send,
-                    external_actor_ref_receiver,
+    /// Hand the stashed messages back if asked to, and stop the Actor if it failed and the FailurePolicy says so.
+    ///
+    /// Returns ```false``` like [process](#method.process) does once the Actor stopped.
+    fn conclude(&mut self, failure: Option<(Option<&'static str>, String)>) -> bool {
+        if UNSTASH_ALL.with(|unstash| unstash.replace(false)) {
+            self.mailbox.unstash(std::mem::take(&mut self.stash));
+        }
+        if let Some((message_type, reason)) = failure {
+            if self
+                .env
+                .env
+                .report_failure(&self.actor_id, &self.type_id, message_type, reason)
+            {
+                LocalEnvironment::stop_failed_actor(
+                    &self.env,
+                    &mut self.actor,
+                    &mut self.mailbox,
+                    self.actor_id.clone(),
+                    self.type_id.clone(),
                 );
-            });
+                return false;
+            }
+        }
+        true
+    }
 
-            // start receive thread for each remote machine
-            for net_receiver in net_receivers.into_iter() {
-                let env_remote_receive = env.clone();
-                std::thread::spawn(move || {
-                    LocalEnvironment::wait_for_remote_messages(env_remote_receive, net_receiver);
+    /// Let the Actor handle a message, returning the message type and the panic message if the handler panicked.
+    ///
+    /// The future of an async handler is run to completion on the current thread.
+    fn handle_message(
+        &mut self,
+        msg: Box<dyn Any + Send>,
+    ) -> Option<(Option<&'static str>, String)> {
+        let handling = self.begin_handling(&*msg);
+        let started = match self.actor.handle_async(msg) {
+            Ok(handler) => {
+                let mut failure = None;
+                let self_sends = handling.run(&self.env, &self.actor_id, &self.time_budget, || {
+                    failure = catch_panic(|| block_on(handler)).err();
                 });
+                Ok((self_sends, failure))
+            }
+            Err(msg) => Err(msg),
+        };
+        let (self_sends, failure, borrowed) = match started {
+            Ok((self_sends, failure)) => (self_sends, failure, None),
+            Err(msg) => self.run_sync_handler(&handling, msg),
+        };
+        self.end_handling(handling, self_sends, borrowed, failure)
+    }
+
+    /// Like [handle_message](#method.handle_message), but the future of an async handler is awaited,
+    /// so the other tasks of the async runtime use the worker thread meanwhile.
+    #[cfg(feature = "async-runtime")]
+    async fn handle_message_async(
+        &mut self,
+        msg: Box<dyn Any + Send>,
+    ) -> Option<(Option<&'static str>, String)> {
+        let handling = self.begin_handling(&*msg);
+        let started = match self.actor.handle_async(msg) {
+            Ok(handler) => Ok(ScopedHandler {
+                handler,
+                env: &self.env,
+                actor_id: &self.actor_id,
+                budget: &self.time_budget,
+                handling: &handling,
+                self_sends: 0,
+                unstash_all: false,
             }
+            .await),
+            Err(msg) => Err(msg),
+        };
+        let (self_sends, failure, borrowed) = match started {
+            Ok((self_sends, failure)) => (self_sends, failure, None),
+            Err(msg) => self.run_sync_handler(&handling, msg),
+        };
+        self.end_handling(handling, self_sends, borrowed, failure)
+    }
+
+    /// Run the synchronous handler for the message, returning the number of self-sends, the panic message if it panicked
+    /// and the message if it was only borrowed, so it can still be stashed.
+    fn run_sync_handler(
+        &mut self,
+        handling: &Handling,
+        msg: Box<dyn Any + Send>,
+    ) -> (usize, Option<String>, Option<Box<dyn Any + Send>>) {
+        let actor = &mut self.actor;
+        let mut failure = None;
+        let mut borrowed = None;
+        let self_sends = handling.run(&self.env, &self.actor_id, &self.time_budget, || {
+            failure = catch_panic(|| {
+                if actor.handle_ref(&*msg) {
+                    borrowed = Some(msg);
+                } else {
+                    actor.handle(msg);
+                }
+            })
+            .err();
+        });
+        (self_sends, failure, borrowed)
+    }
+
+    /// Count the message and look up its type, before the handler runs.
+    fn begin_handling(&self, msg: &dyn Any) -> Handling {
+        Instruments::count(&self.env.env.instruments.messages_handled);
+        let message_type = self.actor.message_type_name(msg);
+        if message_type.is_none()
+            && self
+                .env
+                .env
+                .actor_builder
+                .knows_message_types(&self.type_id)
+        {
+            // handled like any other message, the handler ignores it
+            self.env.env.report_dropped(
+                DropCause::UnhandledType,
+                Some(self.actor_id.clone()),
+                None,
+            );
+        }
+        Handling {
+            message_type,
+            started: Instant::now(),
+            sender: self.mailbox.last_sender(),
+            key: self.mailbox.last_key(),
         }
+    }
 
-        return env;
+    /// Stash the handled message if asked to and record the handler, returning the message type and the panic message if the handler panicked.
+    fn end_handling(
+        &mut self,
+        handling: Handling,
+        self_sends: usize,
+        borrowed: Option<Box<dyn Any + Send>>,
+        failure: Option<String>,
+    ) -> Option<(Option<&'static str>, String)> {
+        if STASH_CURRENT.with(|stash| stash.replace(false)) {
+            match borrowed {
+                Some(msg) => self.stash.push((msg, handling.sender)),
+                None => warn!(
+                    "Actor {:?} cannot stash messages, its MessageHandler does not implement handle_ref.",
+                    self.actor_id
+                ),
+            }
+        }
+        if self.env.env.options.handler_stats {
+            self.env.env.record_handler_time(
+                &self.type_id,
+                handling.message_type.unwrap_or("<unknown>"),
+                handling.started.elapsed(),
+            );
+        }
+        if let Some(monitor) = &mut self.self_send_monitor {
+            monitor.record(&self.actor_id, self_sends > 0);
+        }
+        let message_type = handling.message_type;
+        failure.map(|reason| (message_type, reason))
     }
+}
 
-    /// private helper function used in the receiver thread for **foreign-to-local** messages
-    fn wait_for_remote_messages(env_remote_receive: ArcEnvironment, mut net_receiver: NetReceiver) {
-        loop {
-            // create buffer
-            let mut buffer = [0; BUFFERSIZE];
-            // read message from TCP stream
-            match net_receiver.read(&mut buffer) {
-                Ok(vec) => {
-                    for bin_message in vec {
-                        match bincode::deserialize::<NetMessage>(bin_message) {
-                            Ok(NetMessage::Broadcast(content)) => {
-                                match env_remote_receive.local_actor_channels.lock() {
-                                    Ok(channels) => {
-                                        let actor_ids: Vec<ActorId> =
-                                            channels.keys().map(Clone::clone).collect();
-                                        drop(channels);
-                                        // broadcast serialized Message to all Actors
-                                        for actor_id in actor_ids {
-                                            env_remote_receive.handle_net_message(
-                                                SerNetMessageContent::Message(content.clone()),
-                                                actor_id.clone(),
-                                            );
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error!("{:?}", ActlibError::from_poison_error(&e));
-                                    }
-                                }
-                            }
-                            Ok(NetMessage::SpawnByTypeId(actor_type_id, local_id)) => {
+/// How [process](struct.RunningActor.html#method.process) continues with a message taken from the mailbox.
+enum Dispatched {
+    /// The token or query was processed, with the panic message if the Actor panicked meanwhile.
+    Processed(Option<(Option<&'static str>, String)>),
+    /// Nothing left to do, ```false``` once the Actor stopped.
+    Finished(bool),
+    /// A message for the handlers of the Actor.
+    Handle(Box<dyn Any + Send>),
+}
+
+/// The message a local Actor is handling, see [begin_handling](struct.RunningActor.html#method.begin_handling).
+struct Handling {
+    message_type: Option<&'static str>,
+    started: Instant,
+    /// The sender of the message, see [Context::sender](../actor/struct.Context.html#method.sender).
+    sender: Option<ActorId>,
+    /// The key of the message, if it was sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed).
+    key: Option<u64>,
+}
+
+impl Handling {
+    /// Run the handler *f* like [run_handler](fn.run_handler.html), with the sender and key of the message at hand.
+    fn run<F: FnOnce()>(
+        &self,
+        env: &Environment,
+        actor_id: &ActorId,
+        budget: &Option<TimeBudget>,
+        f: F,
+    ) -> usize {
+        let previous_sender =
+            HANDLING_SENDER.with(|handling| handling.replace(self.sender.clone()));
+        let previous_key = HANDLING_KEY.with(|handling| handling.replace(self.key));
+        let self_sends = run_handler(env, actor_id, budget, f);
+        HANDLING_SENDER.with(|handling| handling.replace(previous_sender));
+        HANDLING_KEY.with(|handling| handling.replace(previous_key));
+        self_sends
+    }
+}
+
+/// Polls the future of an async handler on behalf of its Actor, like [Handling::run](struct.Handling.html#method.run) runs a synchronous handler.
+///
+/// The task of the Actor may continue on another worker thread after every await, so the context of the Actor is set up for every poll.
+/// Completes with the number of self-sends and the panic message if the handler panicked.
+#[cfg(feature = "async-runtime")]
+struct ScopedHandler<'a> {
+    handler: HandlerFuture<'a>,
+    env: &'a Environment,
+    actor_id: &'a ActorId,
+    budget: &'a Option<TimeBudget>,
+    handling: &'a Handling,
+    self_sends: usize,
+    /// Whether [Context::unstash_all](../actor/struct.Context.html#method.unstash_all) was called during any poll
+    unstash_all: bool,
+}
+
+#[cfg(feature = "async-runtime")]
+impl Future for ScopedHandler<'_> {
+    type Output = (usize, Option<String>);
+
+    fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let handler = &mut this.handler;
+        let mut polled = Poll::Pending;
+        let mut failure = None;
+        this.self_sends += this.handling.run(this.env, this.actor_id, this.budget, || {
+            failure = catch_panic(|| polled = handler.as_mut().poll(cx)).err();
+        });
+        this.unstash_all |= UNSTASH_ALL.with(|unstash| unstash.get());
+        if failure.is_none() && polled.is_pending() {
+            return Poll::Pending;
+        }
+        // picked up by RunningActor::conclude on this thread
+        UNSTASH_ALL.with(|unstash| unstash.set(this.unstash_all));
+        Poll::Ready((this.self_sends, failure))
+    }
+}
+
+/// Source of the message ids used to reassemble chunked NetMessages.
+static NEXT_CHUNKED_MESSAGE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Messages of a single remote machine that may be partially received at the same time.
+///
+/// A machine writes the Chunks of one message before the next, so more only remain of broken connections or misbehaving peers.
+const MAX_PARTIAL_MESSAGES: usize = 4;
+
+/// How long a partially received message is kept without another of its Chunks arriving.
+///
+/// The Chunks of a message are written back to back, a longer pause means the sender gave up on it.
+const PARTIAL_MESSAGE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Payload bytes per Chunk, leaving room for the Chunk header within a single frame.
+///
+/// JSON and MessagePack encode the bytes as numbers, taking up to 4 and 2 bytes for each of them.
+fn chunk_size(wire_format: WireFormat) -> usize {
+    let available = netchannel::MAX_FRAME_SIZE - 64;
+    match wire_format {
+        WireFormat::Bincode => available,
+        WireFormat::Json => available / 4,
+        WireFormat::MessagePack => available / 2,
+    }
+}
+
+/// The Chunks of a single NetMessage received so far.
+struct PartialMessage {
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+    bytes: usize,
+    last_chunk: Instant,
+}
+
+/// Collect the Chunks of oversized NetMessages arriving from a single remote machine.
+///
+/// At most [MAX_PARTIAL_MESSAGES](constant.MAX_PARTIAL_MESSAGES.html) messages with *max_message_size* bytes in total are kept,
+/// the oldest partial messages are dropped to make room for newer ones. Partial messages are also dropped
+/// once they did not get a Chunk for [PARTIAL_MESSAGE_TIMEOUT](constant.PARTIAL_MESSAGE_TIMEOUT.html), and with the connection.
+struct Reassembly {
+    partial: HashMap<u64, PartialMessage>,
+    bytes: usize,
+    max_message_size: usize,
+    chunk_size: usize,
+}
+
+impl Reassembly {
+    fn new(max_message_size: usize, wire_format: WireFormat) -> Self {
+        Reassembly {
+            partial: HashMap::new(),
+            bytes: 0,
+            max_message_size,
+            chunk_size: chunk_size(wire_format),
+        }
+    }
+
+    /// Add a Chunk, returning the serialized NetMessage once all of its Chunks arrived.
+    fn add(&mut self, message_id: u64, index: u32, count: u32, data: Vec<u8>) -> Option<Vec<u8>> {
+        // the sender splits messages into full Chunks, more of them cannot fit into the limit
+        let max_count = self.max_message_size / self.chunk_size + 1;
+        if index >= count || count as usize > max_count {
+            warn!(
+                "Dropped invalid chunk {} of {} for message {}.",
+                index, count, message_id
+            );
+            return None;
+        }
+        if !self.partial.contains_key(&message_id) {
+            self.make_room(MAX_PARTIAL_MESSAGES - 1, self.max_message_size);
+        }
+        let partial = self
+            .partial
+            .entry(message_id)
+            .or_insert_with(|| PartialMessage {
+                chunks: vec![None; count as usize],
+                received: 0,
+                bytes: 0,
+                last_chunk: Instant::now(),
+            });
+        if index as usize >= partial.chunks.len() || partial.chunks[index as usize].is_some() {
+            warn!(
+                "Dropped invalid chunk {} of {} for message {}.",
+                index, count, message_id
+            );
+            return None;
+        }
+        if partial.bytes + data.len() > self.max_message_size {
+            warn!(
+                "Dropped remote message {} exceeding the maximum message size of {} bytes.",
+                message_id, self.max_message_size
+            );
+            self.remove(message_id);
+            return None;
+        }
+        partial.bytes += data.len();
+        partial.received += 1;
+        partial.last_chunk = Instant::now();
+        let complete = partial.received == partial.chunks.len();
+        self.bytes += data.len();
+        partial.chunks[index as usize] = Some(data);
+        let max_bytes = self.max_message_size;
+        self.make_room(MAX_PARTIAL_MESSAGES, max_bytes);
+        if !complete {
+            return None;
+        }
+        let partial = self.remove(message_id)?;
+        let mut message = Vec::with_capacity(partial.bytes);
+        for chunk in partial.chunks.into_iter().flatten() {
+            message.extend_from_slice(&chunk);
+        }
+        Some(message)
+    }
+
+    /// Drop the partial messages that did not get a Chunk for [PARTIAL_MESSAGE_TIMEOUT](constant.PARTIAL_MESSAGE_TIMEOUT.html).
+    fn expire(&mut self) {
+        let expired: Vec<u64> = self
+            .partial
+            .iter()
+            .filter(|(_, partial)| partial.last_chunk.elapsed() >= PARTIAL_MESSAGE_TIMEOUT)
+            .map(|(message_id, _)| *message_id)
+            .collect();
+        for message_id in expired {
+            warn!(
+                "Dropped partially received remote message {}, its sender stopped sending Chunks.",
+                message_id
+            );
+            self.remove(message_id);
+        }
+    }
+
+    /// Drop the oldest partial messages until at most *messages* with *max_bytes* in total are left.
+    fn make_room(&mut self, messages: usize, max_bytes: usize) {
+        while self.partial.len() > messages || self.bytes > max_bytes {
+            let oldest = match self.partial.keys().min() {
+                Some(oldest) => *oldest,
+                None => return,
+            };
+            warn!(
+                "Dropped partially received remote message {} to make room for newer ones.",
+                oldest
+            );
+            self.remove(oldest);
+        }
+    }
+
+    fn remove(&mut self, message_id: u64) -> Option<PartialMessage> {
+        let partial = self.partial.remove(&message_id)?;
+        self.bytes -= partial.bytes;
+        Some(partial)
+    }
+}
+
+/// The 1 minute load average divided by the number of CPUs, only available on Linux.
+fn cpu_load() -> Option<f64> {
+    let load_average = std::fs::read_to_string("/proc/loadavg").ok()?;
+    let one_minute: f64 = load_average.split_whitespace().next()?.parse().ok()?;
+    let cpus = std::thread::available_parallelism().ok()?.get();
+    Some(one_minute / cpus as f64)
+}
+
+/// Write a single gzip-compressed JSON state dump.
+fn write_state_dump(path: &Path, introspection: &Introspection) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    serde_json::to_writer(&mut encoder, introspection)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// How long the local Actors get to run their on_stop method during expiration.
+pub(crate) const LOCAL_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// How long to wait for the DrainReports of remote machines during expiration.
+const REMOTE_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Number of specified ids whose correspondents are counted, the counters of an arbitrary id are dropped beyond.
+const CORRESPONDENT_KEYS: usize = 4096;
+
+/// Once a machine was counted this often for a specified id, all counters of the id are halved.
+const CORRESPONDENT_DECAY: u32 = 64;
+
+/// Forwards the messages for the id of a replaced local Actor, until it expires.
+#[derive(Debug)]
+struct Alias {
+    target: ActorId,
+    expires_at: Instant,
+    forwarded: u64,
+}
+
+/// A payload kept on this machine until its lease expires.
+#[derive(Debug)]
+struct StashedPayload {
+    payload: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// The replies awaited by the [asks](../actor/struct.ActorRef.html#method.ask) sent from this machine, by correlation id.
+///
+/// Replies are always routed to the machine of the asker, so the correlation ids only have to be unique per machine.
+#[derive(Debug)]
+pub(crate) struct PendingReplies {
+    pub(crate) machine_id: MachineId,
+    next_correlation_id: AtomicU64,
+    waiting: Mutex<HashMap<u64, Arc<ReplySlot>>>,
+    /// Whether to remember who asked when, see [trace_replies](../options/struct.EnvironmentOptions.html#method.trace_replies)
+    trace: bool,
+}
+
+/// The [watchers](../actor/struct.ActorRef.html#method.watch) of every Actor alive on the local machine.
+///
+/// Every local Actor has an entry from its registration until it is removed,
+/// so a watcher of an Actor without entry is notified right away.
+#[derive(Debug, Default)]
+pub(crate) struct DeathWatches {
+    watchers: Mutex<HashMap<ActorId, Vec<ActorRef>>>,
+}
+
+impl DeathWatches {
+    /// Start tracking a newly registered local Actor.
+    fn track(&self, actor_id: ActorId) {
+        match self.watchers.lock() {
+            Ok(mut watchers) => {
+                watchers.entry(actor_id).or_default();
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Send Terminated to *watcher* once the local Actor stopped, right away if it is not alive.
+    pub(crate) fn watch(&self, actor_id: &ActorId, watcher: ActorRef) {
+        match self.watchers.lock() {
+            Ok(mut watchers) => {
+                if let Some(actor_watchers) = watchers.get_mut(actor_id) {
+                    actor_watchers.push(watcher);
+                    return;
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        DeathWatches::notify(actor_id, &[watcher]);
+    }
+
+    /// Notify the watchers of a removed local Actor.
+    fn terminated(&self, actor_id: &ActorId) {
+        let watchers = match self.watchers.lock() {
+            Ok(mut watchers) => watchers.remove(actor_id).unwrap_or_default(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        DeathWatches::notify(actor_id, &watchers);
+    }
+
+    fn notify(actor_id: &ActorId, watchers: &[ActorRef]) {
+        for watcher in watchers {
+            if let Err(e) = watcher.send_message(Terminated(actor_id.clone())) {
+                info!(
+                    "Could not tell {:?} that {:?} terminated: {:?}",
+                    watcher.clone_id(),
+                    actor_id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// The largest message every remote machine accepts.
+///
+/// Machines exchange their [max_message_size](../options/struct.EnvironmentOptions.html#method.max_message_size) on connect,
+/// the smaller one applies to the messages in both directions.
+#[derive(Debug)]
+pub(crate) struct MessageLimits {
+    /// The limit of the local machine, also used for machines reached through a gateway.
+    local: usize,
+    negotiated: Mutex<HashMap<MachineId, usize>>,
+}
+
+impl MessageLimits {
+    fn new(local: usize) -> Self {
+        MessageLimits {
+            local,
+            negotiated: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Agree on the limit for *machine*, returning it.
+    fn negotiate(&self, machine: MachineId, remote_limit: usize) -> usize {
+        let limit = self.local.min(remote_limit);
+        match self.negotiated.lock() {
+            Ok(mut negotiated) => {
+                negotiated.insert(machine, limit);
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        limit
+    }
+
+    /// The largest message *machine* accepts.
+    pub(crate) fn limit(&self, machine: &MachineId) -> usize {
+        match self.negotiated.lock() {
+            Ok(negotiated) => negotiated.get(machine).copied().unwrap_or(self.local),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                self.local
+            }
+        }
+    }
+
+    /// Fail with [MessageTooLarge](../api/enum.ActlibError.html#variant.MessageTooLarge) if *machine* does not accept *size* bytes.
+    pub(crate) fn check(&self, machine: &MachineId, size: usize) -> Result<(), ActlibError> {
+        let limit = self.limit(machine);
+        if size > limit {
+            return Err(ActlibError::MessageTooLarge(format!(
+                "Message of {} bytes exceeds the maximum of {} bytes agreed on with {}",
+                size, limit, machine
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The shortest interval the [MetricRates](../api/struct.MetricRates.html) are measured over.
+const MIN_RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The receivers of one kind of event, watchers that hung up are dropped with the next event.
+#[derive(Debug)]
+struct Watchers<T>(Mutex<Vec<Sender<T>>>);
+
+impl<T: Clone> Watchers<T> {
+    fn new() -> Self {
+        Watchers(Mutex::new(Vec::new()))
+    }
+
+    /// Register a new watcher.
+    fn watch(&self) -> Receiver<T> {
+        let (sender, receiver) = channel();
+        match self.0.lock() {
+            Ok(mut watchers) => watchers.push(sender),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        receiver
+    }
+
+    /// Send *event* to every watcher.
+    fn notify(&self, event: &T) {
+        match self.0.lock() {
+            Ok(mut watchers) => watchers.retain(|watcher| watcher.send(event.clone()).is_ok()),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+}
+
+/// Counters of the local machine, behind [Environment::metrics](../api/struct.Environment.html#method.metrics).
+#[derive(Debug)]
+struct Instruments {
+    messages_handled: AtomicU64,
+    remote_messages_sent: AtomicU64,
+    remote_messages_received: AtomicU64,
+    actors_spawned: AtomicU64,
+    actors_removed: AtomicU64,
+    /// Dropped messages, in the order of [DropCause::ALL](../api/enum.DropCause.html#associatedconstant.ALL).
+    messages_dropped: [AtomicU64; 7],
+    /// Start and counter values of the current rate interval, with the rates of the previous one.
+    rate_base: Mutex<(Instant, [u64; 5], MetricRates)>,
+}
+
+impl Instruments {
+    fn new() -> Self {
+        Instruments {
+            messages_handled: AtomicU64::new(0),
+            remote_messages_sent: AtomicU64::new(0),
+            remote_messages_received: AtomicU64::new(0),
+            actors_spawned: AtomicU64::new(0),
+            actors_removed: AtomicU64::new(0),
+            messages_dropped: Default::default(),
+            rate_base: Mutex::new((Instant::now(), [0; 5], MetricRates::default())),
+        }
+    }
+
+    fn count(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The dropped messages of every cause.
+    fn messages_dropped(&self) -> HashMap<DropCause, u64> {
+        DropCause::ALL
+            .iter()
+            .map(|cause| {
+                (
+                    *cause,
+                    self.messages_dropped[*cause as usize].load(Ordering::Relaxed),
+                )
+            })
+            .collect()
+    }
+
+    fn values(&self) -> [u64; 5] {
+        [
+            self.messages_handled.load(Ordering::Relaxed),
+            self.remote_messages_sent.load(Ordering::Relaxed),
+            self.remote_messages_received.load(Ordering::Relaxed),
+            self.actors_spawned.load(Ordering::Relaxed),
+            self.actors_removed.load(Ordering::Relaxed),
+        ]
+    }
+
+    /// The rates of *values*, starting a new interval if the current one lasted long enough.
+    fn rates(&self, values: &[u64; 5]) -> MetricRates {
+        match self.rate_base.lock() {
+            Ok(mut rate_base) => {
+                let (started, base, rates) = &mut *rate_base;
+                let interval = started.elapsed();
+                if interval >= MIN_RATE_INTERVAL {
+                    let rate = |i: usize| {
+                        values[i].saturating_sub(base[i]) as f64 / interval.as_secs_f64()
+                    };
+                    *rates = MetricRates {
+                        interval,
+                        messages_handled: rate(0),
+                        remote_messages_sent: rate(1),
+                        remote_messages_received: rate(2),
+                        actors_spawned: rate(3),
+                        actors_removed: rate(4),
+                    };
+                    *started = Instant::now();
+                    *base = *values;
+                }
+                rates.clone()
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                MetricRates::default()
+            }
+        }
+    }
+}
+
+/// Upper bounds of the buckets the waits for a ContendedMutex are counted in, the last bucket is unbounded.
+const LOCK_WAIT_BOUNDS: [Duration; 7] = [
+    Duration::from_micros(1),
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// A Mutex that measures how long every lock waits, if [lock contention](../options/struct.EnvironmentOptions.html#method.lock_contention) is instrumented.
+///
+/// Used for the locks most likely to be sharded, its lock has the same result as the one of the Mutex.
+#[derive(Debug)]
+struct ContendedMutex<T> {
+    mutex: Mutex<T>,
+    /// Number of waits per bucket of LOCK_WAIT_BOUNDS with the summed up wait in nanoseconds, None if not instrumented
+    waits: Option<([AtomicU64; 8], AtomicU64)>,
+}
+
+impl<T> ContendedMutex<T> {
+    fn new(value: T, instrumented: bool) -> Self {
+        ContendedMutex {
+            mutex: Mutex::new(value),
+            waits: if instrumented {
+                Some((Default::default(), AtomicU64::new(0)))
+            } else {
+                None
+            },
+        }
+    }
+
+    fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        match &self.waits {
+            Some((buckets, total_nanos)) => {
+                let started = Instant::now();
+                let guard = self.mutex.lock();
+                let wait = started.elapsed();
+                let bucket = LOCK_WAIT_BOUNDS
+                    .iter()
+                    .position(|bound| wait <= *bound)
+                    .unwrap_or(LOCK_WAIT_BOUNDS.len());
+                buckets[bucket].fetch_add(1, Ordering::Relaxed);
+                total_nanos.fetch_add(wait.as_nanos() as u64, Ordering::Relaxed);
+                guard
+            }
+            None => self.mutex.lock(),
+        }
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.mutex.is_poisoned()
+    }
+
+    /// The wait-time histogram of the lock named *lock*, None if not instrumented.
+    fn waits(&self, lock: &str) -> Option<LockWaits> {
+        let (buckets, total_nanos) = self.waits.as_ref()?;
+        let mut count = 0;
+        let mut cumulative = Vec::with_capacity(LOCK_WAIT_BOUNDS.len());
+        for (bound, waits) in LOCK_WAIT_BOUNDS.iter().zip(buckets.iter()) {
+            count += waits.load(Ordering::Relaxed);
+            cumulative.push((*bound, count));
+        }
+        count += buckets[LOCK_WAIT_BOUNDS.len()].load(Ordering::Relaxed);
+        Some(LockWaits {
+            lock: lock.to_string(),
+            buckets: cumulative,
+            count,
+            total_wait: Duration::from_nanos(total_nanos.load(Ordering::Relaxed)),
+        })
+    }
+}
+
+/// An external listener of the local machine, see [register_external_listener](../api/struct.Environment.html#method.register_external_listener).
+#[derive(Debug)]
+struct ExternalListener {
+    /// The local Actor the accepted connections are sent to
+    handler: ActorId,
+    /// Tells the accepting thread of a replaced listener to stop
+    generation: u64,
+}
+
+/// Keep the *stream* until the handler takes it, and send the handler an ExternalConnection for it.
+fn pass_external_connection(handler: &ActorRef, port: u16, stream: TcpStream, peer: SocketAddr) {
+    if let Err(e) = stream.set_nonblocking(false) {
+        warn!("Failed to accept a connection on port {}: {:?}", port, e);
+        return;
+    }
+    let connection_no = NEXT_CONNECTION_NO.fetch_add(1, Ordering::Relaxed);
+    match ACCEPTED_CONNECTIONS.lock() {
+        Ok(mut accepted) => {
+            // connections the handler never took are closed
+            accepted
+                .retain(|(_, _, accepted_at)| accepted_at.elapsed() < EXTERNAL_CONNECTION_LEASE);
+            accepted.push((connection_no, stream, Instant::now()));
+        }
+        Err(e) => {
+            log_err_as!(error, ActlibError::from_poison_error(&e));
+            return;
+        }
+    }
+    let connection = ExternalConnection {
+        port,
+        peer,
+        connection_no,
+    };
+    if let Err(e) = handler.send_message(connection) {
+        warn!(
+            "Closed a connection on port {}, its handler {:?} is gone: {:?}",
+            port,
+            handler.clone_id(),
+            e
+        );
+        take_external_connection(connection_no);
+    }
+}
+
+/// Take the stream of an accepted [ExternalConnection](../api/struct.ExternalConnection.html), ```None``` if it was taken already or closed.
+pub(crate) fn take_external_connection(connection_no: u64) -> Option<TcpStream> {
+    match ACCEPTED_CONNECTIONS.lock() {
+        Ok(mut accepted) => {
+            let index = accepted
+                .iter()
+                .position(|(no, _, _)| *no == connection_no)?;
+            Some(accepted.swap_remove(index).1)
+        }
+        Err(e) => {
+            log_err_as!(error, ActlibError::from_poison_error(&e));
+            None
+        }
+    }
+}
+
+/// Where the Actors listed by the machines are gathered for an [ActorListing](../api/struct.ActorListing.html).
+#[derive(Debug, Default)]
+pub(crate) struct ListingSlot {
+    pub(crate) state: Mutex<ListingState>,
+    pub(crate) finished: Condvar,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ListingState {
+    /// The Actors listed so far
+    pub(crate) actors: Vec<ActorId>,
+    /// Number of asked machines that did not answer yet
+    missing: usize,
+    /// Set once every machine answered or the timeout passed
+    pub(crate) done: bool,
+    /// Woken once the listing is done, if the ActorListing was polled
+    pub(crate) waker: Option<Waker>,
+}
+
+impl ListingSlot {
+    /// Add the Actors listed by one machine, or give up on the machines that did not answer with ```None```.
+    ///
+    /// The listing is done once the last machine answered.
+    fn add(&self, listed: Option<Vec<ActorId>>) {
+        match self.state.lock() {
+            Ok(mut state) => {
+                if state.done {
+                    return;
+                }
+                match listed {
+                    Some(actors) => {
+                        state.actors.extend(actors);
+                        state.missing = state.missing.saturating_sub(1);
+                    }
+                    None => {
+                        if state.missing > 0 {
+                            warn!(
+                                "{} machines did not list their Actors in time.",
+                                state.missing
+                            );
+                        }
+                        state.missing = 0;
+                    }
+                }
+                if state.missing == 0 {
+                    state.done = true;
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                    self.finished.notify_all();
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+}
+
+/// Where a reply waits to be picked up by its [ResponseFuture](../actor/struct.ResponseFuture.html).
+#[derive(Debug, Default)]
+pub(crate) struct ReplySlot {
+    pub(crate) state: Mutex<ReplyState>,
+    pub(crate) arrived: Condvar,
+    /// The asking Actor and when it asked, only set if replies are traced
+    asked: Option<(Option<ActorId>, Instant)>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ReplyState {
+    /// The serialized reply, taken by the ResponseFuture
+    pub(crate) reply: Option<Vec<u8>>,
+    /// Woken once the reply arrives, if the ResponseFuture was polled
+    pub(crate) waker: Option<Waker>,
+}
+
+impl PendingReplies {
+    fn new(machine_id: MachineId, trace: bool) -> Self {
+        PendingReplies {
+            machine_id,
+            next_correlation_id: AtomicU64::new(0),
+            waiting: Mutex::new(HashMap::new()),
+            trace,
+        }
+    }
+
+    /// Wait for a new reply, returning its correlation id and the slot it will arrive at.
+    pub(crate) fn register(&self) -> Result<(u64, Arc<ReplySlot>), ActlibError> {
+        let correlation_id = self.next_correlation_id.fetch_add(1, Ordering::Relaxed);
+        let slot = Arc::new(ReplySlot {
+            asked: match self.trace {
+                true => Some((current_actor(), Instant::now())),
+                false => None,
+            },
+            ..ReplySlot::default()
+        });
+        match self.waiting.lock() {
+            Ok(mut waiting) => {
+                waiting.insert(correlation_id, slot.clone());
+                Ok((correlation_id, slot))
+            }
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Stop waiting for the reply, e.g. because its ResponseFuture was dropped.
+    pub(crate) fn forget(&self, correlation_id: u64) {
+        match self.waiting.lock() {
+            Ok(mut waiting) => {
+                waiting.remove(&correlation_id);
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Hand a reply to whoever waits for it, replies nobody waits for anymore are dropped.
+    ///
+    /// If replies are traced, *traced* gets the asking Actor and the time since it asked, before the asker is woken.
+    fn fulfil<F: FnOnce(Option<ActorId>, Duration)>(
+        &self,
+        correlation_id: u64,
+        reply: Vec<u8>,
+        traced: F,
+    ) {
+        let slot = match self.waiting.lock() {
+            Ok(mut waiting) => waiting.remove(&correlation_id),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        match slot {
+            Some(slot) => {
+                if let Some((asker, asked_at)) = &slot.asked {
+                    traced(asker.clone(), asked_at.elapsed());
+                }
+                match slot.state.lock() {
+                    Ok(mut state) => {
+                        state.reply = Some(reply);
+                        if let Some(waker) = state.waker.take() {
+                            waker.wake();
+                        }
+                    }
+                    Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                }
+                slot.arrived.notify_all();
+            }
+            None => info!(
+                "Dropped the reply to ask {}, nobody waits for it.",
+                correlation_id
+            ),
+        }
+    }
+}
+
+/// Source of the numbers matching a RedeemedPayload to its RedeemPayload request.
+static NEXT_REDEMPTION_NO: AtomicU64 = AtomicU64::new(0);
+
+/// How long to wait for a payload stashed on a remote machine.
+const REDEEM_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Source of the numbers matching a SpawnAck to its SpawnByTypeId request.
+static NEXT_SPAWN_NO: AtomicU64 = AtomicU64::new(0);
+
+/// How long to wait for a remote machine to acknowledge a spawn.
+const SPAWN_ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Source of the numbers matching a TookOverListener to its HandOverListener request.
+static NEXT_HANDOVER_NO: AtomicU64 = AtomicU64::new(0);
+
+/// How long to wait for a remote machine to take over an external listener.
+const HANDOVER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Source of the generations telling the accepting thread of a replaced external listener to stop.
+static NEXT_LISTENER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Source of the numbers of the accepted [ExternalConnections](../api/struct.ExternalConnection.html).
+static NEXT_CONNECTION_NO: AtomicU64 = AtomicU64::new(0);
+
+/// Connections accepted on an external listener waiting for their handler to take them: connection_no, stream, accepted at.
+static ACCEPTED_CONNECTIONS: Mutex<Vec<(u64, TcpStream, Instant)>> = Mutex::new(Vec::new());
+
+/// How long an accepted connection waits for its handler to take it.
+const EXTERNAL_CONNECTION_LEASE: Duration = Duration::from_secs(60);
+
+/// How often an external listener looks for new connections and a changed handler.
+const EXTERNAL_LISTENER_POLL: Duration = Duration::from_millis(10);
+
+/// Source of the numbers matching CollectedStates to their CollectStates request.
+static NEXT_COLLECTION_NO: AtomicU64 = AtomicU64::new(0);
+
+/// Source of the numbers matching ListedActors to their ListActors request.
+static NEXT_LISTING_NO: AtomicU64 = AtomicU64::new(0);
+
+/// Source of the numbers matching a Pong to its Ping.
+static NEXT_PING_NO: AtomicU64 = AtomicU64::new(0);
+
+/// The outcome of the latest pings of a remote machine.
+#[derive(Debug, Clone, Default)]
+struct PingRecord {
+    round_trip: Option<Duration>,
+    answered_at: Option<Instant>,
+    /// Pings in a row the machine did not answer
+    missed: u32,
+}
+
+/// A ping that was sent to a remote machine and waits for its Pong.
+struct SentPing {
+    machine: MachineId,
+    ping_no: u64,
+    sent_at: Instant,
+    answered: Receiver<Instant>,
+}
+
+/// Source of the numbers matching a Delivered acknowledgement to its Tracked message.
+///
+/// Raised to the current time in nanoseconds at startup, so a machine restarting with the same id does not reuse numbers the receivers remember.
+static NEXT_DELIVERY_NO: AtomicU64 = AtomicU64::new(0);
+
+/// Source of the incarnations of the local Actors with a user specified id.
+///
+/// Raised to the current time in nanoseconds at startup, so an id respawned after a restart gets a new incarnation.
+static NEXT_INCARNATION: AtomicU64 = AtomicU64::new(1);
+
+/// Number of records in the durable outbox before it is compacted to the unacknowledged messages.
+const OUTBOX_COMPACTION_RECORDS: usize = 4096;
+
+/// How long the outcome of a received Tracked message is remembered, a retry arriving later is delivered again.
+const DELIVERY_MEMORY: Duration = Duration::from_secs(600);
+
+/// How long a panicked Actor degrades the [health](../api/struct.Environment.html#method.health) of its machine.
+const FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+/// How often a relayed message may be passed on, before it is dropped as caught in a routing loop.
+const MAX_RELAY_HOPS: u8 = 8;
+
+/// Lightweight counters of the machines that recently looked up or messaged a specified id.
+///
+/// Halving the counters keeps the recent correspondents in front, without remembering any timestamps.
+#[derive(Debug, Default)]
+struct Correspondents {
+    counts: HashMap<Vec<u8>, HashMap<MachineId, u32>>,
+}
+
+impl Correspondents {
+    /// Count a lookup or message from *machine* for the specified id.
+    fn record(&mut self, key: &[u8], machine: MachineId) {
+        if !self.counts.contains_key(key) && self.counts.len() >= CORRESPONDENT_KEYS {
+            if let Some(evicted) = self.counts.keys().next().cloned() {
+                self.counts.remove(&evicted);
+            }
+        }
+        let counts = self.counts.entry(key.to_vec()).or_default();
+        let count = counts.entry(machine).or_insert(0);
+        *count += 1;
+        if *count >= CORRESPONDENT_DECAY {
+            for count in counts.values_mut() {
+                *count /= 2;
+            }
+            counts.retain(|_, count| *count > 0);
+        }
+    }
+
+    /// The machine counted most often for the specified id, *preferred* wins ties and is the fallback.
+    fn busiest(&self, key: &[u8], preferred: Option<MachineId>) -> Option<MachineId> {
+        let counts = match self.counts.get(key) {
+            Some(counts) => counts,
+            None => return preferred,
+        };
+        let preferred_count = preferred
+            .and_then(|machine| counts.get(&machine))
+            .cloned()
+            .unwrap_or(0);
+        match counts.iter().max_by_key(|(_, count)| **count) {
+            Some((machine, count)) if *count > preferred_count => Some(*machine),
+            _ => preferred,
+        }
+    }
+}
+
+/// A Message to a remote Actor sent under reliable delivery, kept until it is acknowledged.
+#[derive(Debug)]
+struct Unacknowledged {
+    delivery_no: u64,
+    /// The machine the Message was sent to, which may differ from the Actor's after a redirect.
+    destination: MachineId,
+    /// The serialized Tracked message, sent again as is.
+    frame: Vec<u8>,
+    actor: ActorId,
+    sender: Option<ActorId>,
+    message: Vec<u8>,
+    sent_at: Instant,
+    /// When the Message was first sent, also before a restart, for the retention of the durable outbox.
+    first_sent: SystemTime,
+    attempts: u32,
+}
+
+/// The outcome of the Tracked messages received within the DELIVERY_MEMORY, oldest first.
+#[derive(Debug, Default)]
+struct Deliveries {
+    outcomes: HashMap<(MachineId, u64), Option<String>>,
+    received: VecDeque<(Instant, (MachineId, u64))>,
+}
+
+impl Deliveries {
+    /// The outcome of an earlier delivery of the Tracked message, forgetting the ones received too long ago.
+    fn outcome(&mut self, delivery: &(MachineId, u64)) -> Option<Option<String>> {
+        while let Some((received, _)) = self.received.front() {
+            if received.elapsed() < DELIVERY_MEMORY {
+                break;
+            }
+            if let Some((_, forgotten)) = self.received.pop_front() {
+                self.outcomes.remove(&forgotten);
+            }
+        }
+        self.outcomes.get(delivery).cloned()
+    }
+
+    fn record(&mut self, delivery: (MachineId, u64), outcome: Option<String>) {
+        self.outcomes.insert(delivery, outcome);
+        self.received.push_back((Instant::now(), delivery));
+    }
+}
+
+/// How long to wait for the stopped Actor of an id conflict before giving up on the respawn.
+const RESPAWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl LocalEnvironment {
+    /// Create a new Environment.
+    ///
+    /// [Actors](../actor/trait.Actor.html) can be located either on the same machine,
+    /// or on any [Machine](../../netchannel/struct.Machine.html) given in the argument.
+    ///
+    /// *own_port* is used to establish a TCP-connection to remote machines.
+    ///
+    /// It is not possible to add new machines after creation of the environment.
+    ///
+    /// Fails if a remote machine cannot join, or if the required machines did not connect within the [ConnectTimeout](../options/struct.ConnectTimeout.html).
+    pub(crate) fn new(
+        own_port: u16,
+        mut remotes: Vec<Peer>,
+        actor_builder: ActorBuilder,
+        options: EnvironmentOptions,
+        termination_sender: Sender<ExpirationResult>,
+    ) -> Result<ArcEnvironment, ActlibError> {
+        // create the ActorRef -> Env channel for this environment
+        let (external_actor_ref_sender, external_actor_ref_receiver): (
+            Sender<(ActorId, SerNetMessageContent)>,
+            Receiver<(ActorId, SerNetMessageContent)>,
+        ) = channel();
+
+        // construct local machine identifier
+        let local_machine;
+        match options.local_address {
+            Some(ip) => local_machine = SocketAddr::new(ip, own_port),
+            None => match get_if_addrs::get_if_addrs() {
+                Ok(ifaces) => {
+                    // IPv6 link-local addresses are only valid together with an interface scope
+                    match ifaces.into_iter().find(|iface| {
+                        !iface.is_loopback()
+                            && match iface.ip() {
+                                IpAddr::V4(_) => true,
+                                IpAddr::V6(ip) => (ip.segments()[0] & 0xffc0) != 0xfe80,
+                            }
+                    }) {
+                        Some(interface) => {
+                            local_machine = SocketAddr::new(interface.ip(), own_port);
+                        }
+                        None => {
+                            panic!("Could not find local network connection");
+                        }
+                    }
+                }
+                Err(e) => {
+                    panic!("Could not find local network connection: {:?}", e);
+                }
+            },
+        }
+
+        // remove self from remotes (if it was passed there)
+        remotes = remotes
+            .into_iter()
+            .filter(|remote| !remote.matches(local_machine.ip()))
+            .collect();
+        let machine_id = options.machine_id.unwrap_or_else(MachineId::random);
+
+        // connect to the remote machines in parallel, every machine introduces itself before waiting for the others
+        let fingerprint = actor_builder.fingerprint();
+        let (connected_sender, connected) = channel();
+        for (index, remote) in remotes.iter().enumerate() {
+            let connected_sender = connected_sender.clone();
+            let remote = remote.clone();
+            let options = options.clone();
+            std::thread::spawn(move || {
+                let connection = LocalEnvironment::connect_peer(
+                    local_machine,
+                    &remote,
+                    machine_id,
+                    fingerprint,
+                    &options,
+                );
+                // the Environment may be gone already
+                let _ = connected_sender.send((index, remote, connection));
+            });
+        }
+        drop(connected_sender);
+
+        // wait for the required remote machines, the others are attached once they connect
+        let required = options
+            .wait_for_peers
+            .map_or(remotes.len(), |n| n.min(remotes.len()));
+        let deadline = options
+            .connect_timeout
+            .as_ref()
+            .map(|connect_timeout| Instant::now() + connect_timeout.timeout);
+        let mut connections = Vec::with_capacity(required);
+        while connections.len() < required {
+            let received = match deadline {
+                Some(deadline) => {
+                    connected.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None => connected.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok((index, remote, Ok(connection))) => {
+                    connections.push((index, remote, connection))
+                }
+                Ok((_, _, Err(e))) => return Err(ActlibError::NetworkError(e)),
+                Err(RecvTimeoutError::Timeout) => {
+                    let waited = options
+                        .connect_timeout
+                        .as_ref()
+                        .map(|connect_timeout| (connect_timeout.timeout, connect_timeout.partial));
+                    match waited {
+                        Some((timeout, true)) => {
+                            warn!(
+                                "Only {} of {} remote machines connected within {:?}, the others are connected in the background.",
+                                connections.len(),
+                                required,
+                                timeout
+                            );
+                            break;
+                        }
+                        _ => {
+                            return Err(ActlibError::NetworkError(format!(
+                                "Only {} of {} remote machines connected within {:?}",
+                                connections.len(),
+                                required,
+                                waited.map(|(timeout, _)| timeout).unwrap_or_default()
+                            )));
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(ActlibError::NetworkError(
+                        "Could not connect to the remote machines".to_string(),
+                    ));
+                }
+            }
+        }
+        // the initial machines keep the order they were given in
+        connections.sort_by_key(|(index, _, _)| *index);
+        let attached = connections.len();
+
+        let message_limits = Arc::new(MessageLimits::new(options.max_message_size));
+        let peer_status = remotes
+            .iter()
+            .map(|remote| PeerStatus {
+                peer: remote.clone(),
+                machine_id: None,
+                state: PeerState::Connecting,
+            })
+            .collect();
+
+        // the remote machines are added as they are attached
+        let load_balancer = Mutex::new(LoadBalancer::new(1, options.placement.clone()));
+
+        let workers = options.worker_threads.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|cores| cores.get())
+                .unwrap_or(1)
+        });
+        let scheduler = match options.runtime {
+            Runtime::Pool => Some(Scheduler::start(workers)),
+            _ => None,
+        };
+
+        #[cfg(feature = "async-runtime")]
+        let executor = match options.runtime {
+            Runtime::Threads | Runtime::Pool => None,
+            Runtime::Async => match tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(workers)
+                .thread_name("actlib-actor")
+                .enable_all()
+                .build()
+            {
+                Ok(executor) => Some(executor),
+                Err(e) => {
+                    error!(
+                        "Failed to start the async runtime, running every Actor on its own thread: {:?}",
+                        e
+                    );
+                    None
+                }
+            },
+        };
+
+        let counters = Arc::new(CounterRegistry::new(options.counter_interval));
+
+        if let Ok(since_epoch) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+            NEXT_DELIVERY_NO.fetch_max(since_epoch.as_nanos() as u64, Ordering::Relaxed);
+            NEXT_INCARNATION.fetch_max(since_epoch.as_nanos() as u64, Ordering::Relaxed);
+        }
+        let (durable_outbox, unsettled) = match &options.durable_outbox {
+            Some(path) => match DurableOutbox::open(path) {
+                Ok((outbox, unsettled)) => (Some(Mutex::new(outbox)), unsettled),
+                Err(e) => {
+                    error!(
+                        "Cannot open the durable outbox {:?}, Messages are not kept across restarts: {:?}",
+                        path, e
+                    );
+                    (None, Vec::new())
+                }
+            },
+            None => (None, Vec::new()),
+        };
+        let replies = Arc::new(PendingReplies::new(machine_id, options.trace_replies));
+        let lock_contention = options.lock_contention;
+        let supervisor = Supervisor::new();
+
+        // Create new Environment instance
+        let env = Arc::new(LocalEnvironment {
+            local_actor_channels: ContendedMutex::new(HashMap::new(), lock_contention),
+            lineage: Mutex::new(HashMap::new()),
+            external_actor_ref_sender: Mutex::new(external_actor_ref_sender),
+            local_machine,
+            machine_id,
+            peers: RwLock::new(Vec::with_capacity(remotes.len())),
+            peer_status: Mutex::new(peer_status),
+            peer_watchers: Watchers::new(),
+            net_senders: ContendedMutex::new(
+                IndexMap::with_capacity(remotes.len()),
+                lock_contention,
+            ),
+            traffic: RwLock::new(Vec::with_capacity(remotes.len())),
+            actor_builder,
+            options,
+            termination_sender: Mutex::new(termination_sender),
+            drain_listener: Mutex::new(None),
+            expiration_reports: Mutex::new(None),
+            load_balancer,
+            remote_queries: ContendedMutex::new(HashMap::new(), lock_contention),
+            invincible_actors: RwLock::new(HashMap::new()),
+            id_conflict_watchers: Watchers::new(),
+            mailbox_alert_watchers: Watchers::new(),
+            actor_failure_watchers: Watchers::new(),
+            recent_failures: Mutex::new(VecDeque::new()),
+            actors_failed: AtomicUsize::new(0),
+            instruments: Instruments::new(),
+            dead_links: Mutex::new(HashMap::new()),
+            outbox: Mutex::new(HashMap::new()),
+            keyed_types: Mutex::new(HashMap::new()),
+            redirects: Mutex::new(HashMap::new()),
+            dead_letter_watchers: Watchers::new(),
+            stale_incarnation_watchers: Watchers::new(),
+            delivery_failure_watchers: Watchers::new(),
+            message_dropped_watchers: Watchers::new(),
+            machine_loads: Mutex::new(HashMap::new()),
+            load_subscribers: Mutex::new(Vec::new()),
+            counters,
+            lifecycle_subscribers: Mutex::new(HashMap::new()),
+            correspondents: Mutex::new(Correspondents::default()),
+            learned_routes: Mutex::new(HashMap::new()),
+            aliases: Mutex::new(HashMap::new()),
+            names: Mutex::new(HashMap::new()),
+            broadcast_seq: AtomicU64::new(0),
+            total_order: Mutex::new(()),
+            shutting_down: AtomicBool::new(false),
+            bootstrapped: Mutex::new(false),
+            bootstrap_done: Condvar::new(),
+            handler_stats: Mutex::new(HashMap::new()),
+            reply_stats: Mutex::new(HashMap::new()),
+            stashed_payloads: Mutex::new(HashMap::new()),
+            pending_redemptions: Mutex::new(HashMap::new()),
+            pending_spawns: Mutex::new(HashMap::new()),
+            pending_pings: Mutex::new(HashMap::new()),
+            pings: Mutex::new(HashMap::new()),
+            external_listeners: Mutex::new(HashMap::new()),
+            pending_handovers: Mutex::new(HashMap::new()),
+            pending_collections: Mutex::new(HashMap::new()),
+            pending_listings: Mutex::new(HashMap::new()),
+            unacknowledged: Mutex::new(HashMap::new()),
+            deliveries: Mutex::new(Deliveries::default()),
+            durable_outbox,
+            held_starts: Mutex::new(HashMap::new()),
+            replies,
+            death_watches: Arc::new(DeathWatches::default()),
+            message_limits,
+            timer: Timer::start(&supervisor),
+            supervisor,
+            scheduler,
+            #[cfg(feature = "async-runtime")]
+            executor,
+        });
+
+        // an internal thread given up on leaves the machine crippled, the main thread learns about it
+        let env_given_up = Arc::downgrade(&env);
+        env.supervisor.on_given_up(move |name, panic| {
+            if let Some(env) = env_given_up.upgrade() {
+                let _ = env.release_termination(ExpirationResult {
+                    reason: ExpirationReason::Fatal(format!(
+                        "The internal thread {} was given up on: {}",
+                        name, panic
+                    )),
+                    ..ExpirationResult::default()
+                });
+            }
+        });
+
+        let env_remote_send = env.clone();
+        // Start listener Thread for message passing to an external environment.
+        //
+        // Messages are sent to this environment's receiver, serialized and send to the specified machine.
+        // Messages for aliased local ids take the same way, so they are forwarded in order.
+        env.supervisor.spawn("actlib-relay".to_string(), move || {
+            LocalEnvironment::wait_for_local_messages(
+                &env_remote_send,
+                &external_actor_ref_receiver,
+            );
+        });
+
+        // attach the initial remote machines, each gets its own receive thread
+        for (index, remote, connection) in connections {
+            LocalEnvironment::attach_peer(&env, index, remote, connection);
+        }
+        // the late remote machines are attached in the background
+        if attached < remotes.len() {
+            let env_late = Arc::downgrade(&env);
+            env.supervisor.spawn("actlib-attach".to_string(), move || {
+                LocalEnvironment::attach_late_peers(env_late.clone(), &connected);
+            });
+        }
+
+        if let Some(detection) = env.options.id_conflict_detection.clone() {
+            if !remotes.is_empty() {
+                LocalEnvironment::spawn_periodic(
+                    &env,
+                    "actlib-id-exchange",
+                    detection.interval,
+                    LocalEnvironment::exchange_specified_ids,
+                );
+            }
+        }
+
+        if let Some(alerts) = env.options.mailbox_alerts.clone() {
+            // Actors that were already reported and still exceed a threshold
+            let mut alerted = HashSet::new();
+            LocalEnvironment::spawn_periodic(
+                &env,
+                "actlib-mailbox-alerts",
+                alerts.interval,
+                move |env| LocalEnvironment::sample_mailboxes(env, &alerts, &mut alerted),
+            );
+        }
+
+        if let Some(delivery) = env.options.reliable_delivery.clone() {
+            // checking more often than the timeout keeps the retries close to it
+            let interval = (delivery.ack_timeout / 4).max(Duration::from_millis(1));
+            LocalEnvironment::spawn_periodic(&env, "actlib-retry", interval, move |env| {
+                LocalEnvironment::retry_unacknowledged(env, &delivery)
+            });
+        }
+        if !unsettled.is_empty() {
+            env.resend_unsettled(unsettled);
+        }
+
+        if let Some(heartbeat) = env.options.heartbeat.clone() {
+            LocalEnvironment::spawn_periodic(
+                &env,
+                "actlib-heartbeat",
+                heartbeat.interval,
+                move |env| LocalEnvironment::send_heartbeats(env, &heartbeat),
+            );
+        }
+
+        if let Some(exchange) = env.options.load_exchange.clone() {
+            LocalEnvironment::spawn_periodic(
+                &env,
+                "actlib-load-exchange",
+                exchange.interval,
+                move |env| LocalEnvironment::exchange_load(env, &exchange),
+            );
+        }
+
+        LocalEnvironment::spawn_periodic(
+            &env,
+            "actlib-counters",
+            env.options.counter_interval,
+            LocalEnvironment::exchange_counters,
+        );
+
+        if let Some(state_dump) = env.options.state_dump.clone() {
+            match std::fs::create_dir_all(&state_dump.directory) {
+                Ok(()) => {
+                    let mut sequence_no: usize = 0;
+                    LocalEnvironment::spawn_periodic(
+                        &env,
+                        "actlib-state-dump",
+                        state_dump.interval,
+                        move |env| {
+                            LocalEnvironment::dump_state(env, &state_dump, sequence_no);
+                            sequence_no += 1;
+                        },
+                    );
+                }
+                Err(e) => error!(
+                    "Cannot create state dump directory {:?}: {:?}",
+                    state_dump.directory, e
+                ),
+            }
+        }
+
+        if let Some(port) = env.options.prometheus_port {
+            match TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))) {
+                Ok(listener) => {
+                    let env_metrics = Arc::downgrade(&env);
+                    env.supervisor.spawn("actlib-metrics".to_string(), move || {
+                        LocalEnvironment::export_metrics(env_metrics.clone(), &listener);
+                    });
+                }
+                Err(e) => error!("Cannot serve the metrics on port {}: {:?}", port, e),
+            }
+        }
+
+        info!("Started up Environment: {:?}", env.info());
+
+        Ok(env)
+    }
+
+    /// Describe this Environment.
+    pub(crate) fn info(&self) -> EnvironmentInfo {
+        EnvironmentInfo {
+            machine_id: self.machine_id,
+            local_addr: self.local_machine,
+            listen_addr: netchannel::server_addr(),
+            peers: self.peers(),
+            actor_types: self.actor_builder.type_ids().to_vec(),
+            fingerprint: self.actor_builder.fingerprint(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+
+    /// The remote machines this Environment is connected to.
+    fn peers(&self) -> Vec<(MachineId, Peer)> {
+        match self.peers.read() {
+            Ok(peers) => peers.clone(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Split a NetChannel to a remote machine, encrypting it first if [TLS](../options/struct.EnvironmentOptions.html#method.tls) is configured.
+    #[cfg_attr(not(feature = "tls"), allow(unused_variables, unused_mut))]
+    fn split_channel(
+        mut net_channel: NetChannel,
+        options: &EnvironmentOptions,
+    ) -> std::io::Result<(NetSender, NetReceiver)> {
+        #[cfg(feature = "tls")]
+        {
+            if let Some(tls) = &options.tls {
+                net_channel = net_channel.with_tls(tls.clone());
+            }
+        }
+        net_channel.split()
+    }
+
+    /// Connect to a remote machine and exchange the Hello, checking that it runs a compatible build under another id.
+    fn connect_peer(
+        local_machine: SocketAddr,
+        remote: &Peer,
+        machine_id: MachineId,
+        fingerprint: u64,
+        options: &EnvironmentOptions,
+    ) -> Result<PeerConnection, String> {
+        let hello = options
+            .wire_format
+            .serialize(&NetMessage::Hello(
+                machine_id,
+                fingerprint,
+                options.max_message_size,
+            ))
+            .map_err(|e| format!("Could not serialize Hello: {:?}", e))?;
+        // the remote server closes connections it does not expect yet, so broken connections are tried again
+        let mut backoff = Backoff::new(Duration::from_millis(50), Duration::from_secs(2));
+        let (sender, receiver, frame) = loop {
+            let net_channel = NetChannel::with_peer(local_machine, remote);
+            let (mut sender, mut receiver) =
+                match LocalEnvironment::split_channel(net_channel, options) {
+                    Ok(halves) => halves,
+                    // a certificate the remote does not accept is not fixed by trying again
+                    Err(e)
+                        if e.kind() == std::io::ErrorKind::NotConnected
+                            || e.kind() == std::io::ErrorKind::InvalidData =>
+                    {
+                        return Err(format!("Could not split NetChannel instance: {:?}", e));
+                    }
+                    Err(e) => {
+                        debug!(
+                            "Securing the connection to {} failed, retrying: {:?}",
+                            remote, e
+                        );
+                        std::thread::sleep(backoff.next_delay());
+                        continue;
+                    }
+                };
+            match sender.write(&hello).and_then(|_| receiver.read_frame()) {
+                Ok(frame) => break (sender, receiver, frame),
+                Err(e) => {
+                    debug!(
+                        "Introducing this machine to {} failed, retrying: {:?}",
+                        remote, e
+                    );
+                    std::thread::sleep(backoff.next_delay());
+                }
+            }
+        };
+        let (remote_id, remote_limit) = match options.wire_format.deserialize(&frame) {
+            Ok(NetMessage::Hello(remote_id, remote_fingerprint, remote_limit)) => {
+                if remote_fingerprint != fingerprint {
+                    return Err(format!(
+                        "{:?}",
+                        ActlibError::IncompatibleBuild(format!(
+                            "{} registered other Actor or message types (fingerprint {:016x}, this machine {:016x})",
+                            remote, remote_fingerprint, fingerprint
+                        ))
+                    ));
+                }
+                (remote_id, remote_limit)
+            }
+            Ok(_) => {
+                return Err(format!(
+                    "Expected Hello from {}, got another NetMessage",
+                    remote
+                ))
+            }
+            Err(e) => {
+                return Err(format!(
+                "Could not deserialize Hello from {}, it may use another wire format than {}: {:?}",
+                remote, options.wire_format, e
+            ))
+            }
+        };
+        if remote_id == machine_id {
+            return Err(format!(
+                "{} uses the machine id of this machine: {}",
+                remote, machine_id
+            ));
+        }
+        Ok(PeerConnection {
+            machine_id: remote_id,
+            max_message_size: remote_limit,
+            sender,
+            receiver,
+        })
+    }
+
+    /// Make a connected remote machine available for messages and spawns, and start its receive thread.
+    ///
+    /// *index* is the position of the machine among the configured remote machines.
+    fn attach_peer(env: &ArcEnvironment, index: usize, remote: Peer, connection: PeerConnection) {
+        let remote_id = connection.machine_id;
+        // the receiver refuses larger frames before buffering them
+        let limit = env
+            .message_limits
+            .negotiate(remote_id, connection.max_message_size);
+        let receiver = connection.receiver.with_max_frame_size(limit);
+        match env.traffic.write() {
+            Ok(mut traffic) => {
+                traffic.push((remote_id, connection.sender.traffic(), receiver.traffic()))
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        match env.net_senders.lock() {
+            Ok(mut senders) => {
+                senders.insert(remote_id, connection.sender);
+                // a late machine learns the names registered before it connected
+                if let Some(bin) = env.names_message() {
+                    if let Err(e) = env.send_to_machine(&mut senders, remote_id, &bin) {
+                        warn!("Could not send the names to {}: {:?}", remote_id, e);
+                    }
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        match env.peers.write() {
+            Ok(mut peers) => peers.push((remote_id, remote)),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        // the machine no of a spawn is the index of the sender, so the balancer learns about the machine last
+        match env.load_balancer.lock() {
+            Ok(mut balancer) => balancer.add_machine(),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        env.set_peer_state(|i, _| i == index, Some(remote_id), PeerState::Connected);
+
+        let env_remote_receive = env.clone();
+        let mut receiver = receiver;
+        env.supervisor
+            .spawn(format!("actlib-receive-{}", remote_id), move || {
+                LocalEnvironment::wait_for_remote_messages(
+                    &env_remote_receive,
+                    remote_id,
+                    &mut receiver,
+                );
+            });
+    }
+
+    /// Attach the remote machines that connect after the Environment was created.
+    fn attach_late_peers(
+        env: Weak<LocalEnvironment>,
+        connected: &Receiver<(usize, Peer, Result<PeerConnection, String>)>,
+    ) {
+        for (index, remote, connection) in connected.iter() {
+            let env = match env.upgrade() {
+                Some(env) => env,
+                None => return,
+            };
+            if env.is_shutting_down() {
+                return;
+            }
+            match connection {
+                Ok(connection) => {
+                    info!("{} joined the Environment late.", remote);
+                    LocalEnvironment::attach_peer(&env, index, remote, connection);
+                }
+                Err(e) => {
+                    error!("{}", e);
+                    env.set_peer_state(|i, _| i == index, None, PeerState::Failed(e));
+                }
+            }
+        }
+    }
+
+    /// Change the connection state of the matching remote machines and notify the watchers.
+    fn set_peer_state<F: Fn(usize, &PeerStatus) -> bool>(
+        &self,
+        matches: F,
+        machine_id: Option<MachineId>,
+        state: PeerState,
+    ) {
+        let changed: Vec<PeerStatus> = match self.peer_status.lock() {
+            Ok(mut peer_status) => peer_status
+                .iter_mut()
+                .enumerate()
+                .filter(|(i, status)| matches(*i, status) && status.state != state)
+                .map(|(_, status)| {
+                    status.machine_id = machine_id.or(status.machine_id);
+                    status.state = state.clone();
+                    status.clone()
+                })
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        for status in changed {
+            self.peer_watchers.notify(&status);
+        }
+    }
+
+    /// The connection state of every configured remote machine.
+    pub(crate) fn peer_status(&self) -> Vec<PeerStatus> {
+        match self.peer_status.lock() {
+            Ok(peer_status) => peer_status.clone(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Every machine with a known identity, the local machine first.
+    pub(crate) fn machines(&self) -> Vec<MachineRef> {
+        let actor_counts: HashMap<MachineId, usize> = match self.machine_loads.lock() {
+            Ok(loads) => loads
+                .values()
+                .map(|(_, load)| (load.machine, load.actors))
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                HashMap::new()
+            }
+        };
+        let mut machines = vec![MachineRef {
+            id: self.machine_id,
+            address: Peer::Addr(self.local_machine),
+            status: PeerState::Connected,
+            actors: Some(self.measure_load().actors),
+        }];
+        for status in self.peer_status() {
+            if let Some(id) = status.machine_id {
+                machines.push(MachineRef {
+                    id,
+                    address: status.peer,
+                    status: status.state,
+                    actors: actor_counts.get(&id).cloned(),
+                });
+            }
+        }
+        machines
+    }
+
+    /// Register a watcher for the connection state changes of the remote machines.
+    pub(crate) fn watch_peers(&self) -> Receiver<PeerStatus> {
+        self.peer_watchers.watch()
+    }
+
+    /// Write a serialized NetMessage to a remote machine, splitting it into Chunks if it exceeds a single frame.
+    ///
+    /// Messages the machine does not accept are refused before anything is written.
+    fn write_net_message(
+        &self,
+        machine: MachineId,
+        net_sender: &mut NetSender,
+        bin: &[u8],
+    ) -> std::io::Result<usize> {
+        if let Err(e) = self.message_limits.check(&machine, bin.len()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{:?}", e),
+            ));
+        }
+        if bin.len() <= netchannel::MAX_FRAME_SIZE {
+            return net_sender.write(bin);
+        }
+        let message_id = NEXT_CHUNKED_MESSAGE_ID.fetch_add(1, Ordering::Relaxed);
+        let chunk_size = chunk_size(self.options.wire_format);
+        let count = bin.len().div_ceil(chunk_size);
+        for (index, data) in bin.chunks(chunk_size).enumerate() {
+            let chunk = NetMessage::Chunk(message_id, index as u32, count as u32, data.to_vec());
+            match self.options.wire_format.serialize(&chunk) {
+                Ok(bin_chunk) => {
+                    net_sender.write(&bin_chunk)?;
+                }
+                Err(e) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("Failed to serialize chunk: {:?}", e),
+                    ))
+                }
+            }
+        }
+        Ok(bin.len())
+    }
+
+    /// Remember the peer a relayed message from *origin* arrived from, to send replies back the same way.
+    fn learn_route(&self, origin: MachineId, peer: MachineId) {
+        if origin == peer || self.options.routes.contains_key(&origin) {
+            return;
+        }
+        match self.learned_routes.lock() {
+            Ok(mut routes) => {
+                routes.insert(origin, peer);
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// The peer to pass messages for *machine* to, configured routes take precedence over learned ones.
+    fn gateway_of(&self, machine: MachineId) -> Option<MachineId> {
+        if let Some(gateway) = self.options.routes.get(&machine) {
+            return Some(*gateway);
+        }
+        match self.learned_routes.lock() {
+            Ok(routes) => routes.get(&machine).cloned(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    /// Pass a serialized NetMessage on towards *destination*, directly if connected or else through its gateway.
+    fn relay(
+        &self,
+        senders: &mut IndexMap<MachineId, NetSender>,
+        destination: MachineId,
+        origin: MachineId,
+        hops_left: u8,
+        bin: Vec<u8>,
+    ) -> std::io::Result<usize> {
+        let next_hop = if senders.contains_key(&destination) {
+            Some(destination)
+        } else {
+            self.gateway_of(destination)
+        };
+        let (next_hop, net_sender) =
+            match next_hop.and_then(|next_hop| senders.get_mut(&next_hop).map(|s| (next_hop, s))) {
+                Some(hop) => hop,
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::NotConnected,
+                        format!("No route to {}", destination),
+                    ))
+                }
+            };
+        match self.options.wire_format.serialize(&NetMessage::Relay(
+            destination,
+            origin,
+            hops_left,
+            bin,
+        )) {
+            Ok(bin_relay) => self.write_net_message(next_hop, net_sender, &bin_relay),
+            Err(e) => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Failed to serialize relayed message: {:?}", e),
+            )),
+        }
+    }
+
+    /// Write a serialized NetMessage to *machine*, relayed through its gateway if there is no direct connection.
+    fn send_to_machine(
+        &self,
+        senders: &mut IndexMap<MachineId, NetSender>,
+        machine: MachineId,
+        bin: &[u8],
+    ) -> std::io::Result<usize> {
+        match senders.get_mut(&machine) {
+            Some(net_sender) => {
+                if let Some(buffered) = self.buffer_while_reconnecting(machine, bin) {
+                    return buffered;
+                }
+                let result = self.write_net_message(machine, net_sender, bin);
+                match &result {
+                    // oversized messages are refused before anything is written
+                    Err(e) if e.kind() != std::io::ErrorKind::InvalidInput => {
+                        // wakes up the receive thread of the connection, which re-establishes it
+                        let _ = net_sender.shutdown();
+                        self.mark_link_dead(machine, format!("{:?}", e));
+                        if let Some(buffered) = self.buffer_while_reconnecting(machine, bin) {
+                            return buffered;
+                        }
+                    }
+                    _ => {}
+                }
+                result
+            }
+            None => self.relay(
+                senders,
+                machine,
+                self.machine_id,
+                MAX_RELAY_HOPS,
+                bin.to_vec(),
+            ),
+        }
+    }
+
+    /// Write a serialized NetMessage to every connected machine and every machine reachable through a gateway.
+    ///
+    /// Returns the result of each machine.
+    fn send_to_all_machines(
+        &self,
+        senders: &mut IndexMap<MachineId, NetSender>,
+        bin: &[u8],
+    ) -> Vec<(MachineId, std::io::Result<usize>)> {
+        let mut machines: Vec<MachineId> = senders.keys().cloned().collect();
+        let mut relayed: Vec<MachineId> = self.options.routes.keys().cloned().collect();
+        match self.learned_routes.lock() {
+            Ok(routes) => relayed.extend(routes.keys().cloned()),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        for machine in relayed {
+            if machine != self.machine_id && !machines.contains(&machine) {
+                machines.push(machine);
+            }
+        }
+        machines
+            .into_iter()
+            .map(|machine| (machine, self.send_to_machine(senders, machine, bin)))
+            .collect()
+    }
+
+    /// Queue a message for a machine whose connection is being re-established.
+    ///
+    /// Returns ```None``` if the connection is up, or an error if the buffer of the machine is full.
+    fn buffer_while_reconnecting(
+        &self,
+        machine: MachineId,
+        bin: &[u8],
+    ) -> Option<std::io::Result<usize>> {
+        let max_buffered_bytes = self.options.reconnect.as_ref()?.max_buffered_bytes;
+        match self.outbox.lock() {
+            Ok(mut outbox) => {
+                let outbox = outbox.get_mut(&machine)?;
+                if outbox.bytes + bin.len() > max_buffered_bytes {
+                    return Some(Err(std::io::Error::new(
+                        std::io::ErrorKind::NotConnected,
+                        format!(
+                            "Reconnecting to {}, {} bytes are buffered already",
+                            machine, outbox.bytes
+                        ),
+                    )));
+                }
+                outbox.messages.push_back(bin.to_vec());
+                outbox.bytes += bin.len();
+                Some(Ok(bin.len()))
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    /// Re-establish the connection to a remote machine with exponential backoff, returning its new receiving half.
+    ///
+    /// Returns ```None``` if reconnecting is disabled, gave up or the Environment is shutting down.
+    fn reconnect(&self, remote: MachineId) -> Option<NetReceiver> {
+        let reconnect = self.options.reconnect.clone()?;
+        let peer = self
+            .peers()
+            .into_iter()
+            .find(|(machine, _)| *machine == remote)
+            .map(|(_, peer)| peer.clone())?;
+        let mut backoff = Backoff::new(reconnect.initial_delay, reconnect.max_delay);
+        let started = Instant::now();
+        loop {
+            std::thread::sleep(backoff.next_delay());
+            if self.is_shutting_down() {
+                return None;
+            }
+            if let Some(give_up_after) = reconnect.give_up_after {
+                if started.elapsed() >= give_up_after {
+                    let discarded = match self.outbox.lock() {
+                        Ok(mut outbox) => outbox
+                            .remove(&remote)
+                            .map(|outbox| outbox.messages)
+                            .unwrap_or_default(),
+                        Err(e) => {
+                            log_err_as!(error, ActlibError::from_poison_error(&e));
+                            VecDeque::new()
+                        }
+                    };
+                    error!(
+                        "Gave up reconnecting to {} after {:?}, discarded {} buffered messages.",
+                        remote,
+                        give_up_after,
+                        discarded.len()
+                    );
+                    for frame in discarded {
+                        self.report_dropped_frame(DropCause::PeerDead, &frame);
+                    }
+                    return None;
+                }
+            }
+            match NetChannel::reconnect(self.local_machine, &peer, reconnect.max_delay)
+                .and_then(|net_channel| LocalEnvironment::split_channel(net_channel, &self.options))
+                .and_then(|(sender, receiver)| self.handshake(remote, sender, receiver))
+            {
+                Ok(receiver) => return Some(receiver),
+                Err(e) => debug!("Reconnecting to {} failed: {:?}", remote, e),
+            }
+        }
+    }
+
+    /// Introduce this machine on a re-established connection, which has to lead to the same remote machine.
+    ///
+    /// The buffered messages are sent before the connection is used for new ones.
+    fn handshake(
+        &self,
+        remote: MachineId,
+        mut sender: NetSender,
+        mut receiver: NetReceiver,
+    ) -> std::io::Result<NetReceiver> {
+        let known_traffic = match self.traffic.read() {
+            Ok(traffic) => traffic
+                .iter()
+                .find(|(id, _, _)| *id == remote)
+                .map(|(_, sent, received)| (sent.clone(), received.clone())),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        if let Some((sent, received)) = known_traffic {
+            sender = sender.with_traffic(sent);
+            receiver = receiver.with_traffic(received);
+        }
+        let fingerprint = self.actor_builder.fingerprint();
+        match self.options.wire_format.serialize(&NetMessage::Hello(
+            self.machine_id,
+            fingerprint,
+            self.options.max_message_size,
+        )) {
+            Ok(hello) => {
+                sender.write(&hello)?;
+            }
+            Err(e) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Could not serialize Hello: {:?}", e),
+                ))
+            }
+        }
+        match self
+            .options
+            .wire_format
+            .deserialize(&receiver.read_frame()?)
+        {
+            Ok(NetMessage::Hello(remote_id, remote_fingerprint, remote_limit))
+                if remote_id == remote && remote_fingerprint == fingerprint =>
+            {
+                // the remote machine may have restarted with another limit
+                let limit = self.message_limits.negotiate(remote, remote_limit);
+                receiver = receiver.with_max_frame_size(limit);
+            }
+            Ok(NetMessage::Hello(remote_id, remote_fingerprint, _)) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "Expected {} with fingerprint {:016x}, {} with fingerprint {:016x} answered",
+                        remote, fingerprint, remote_id, remote_fingerprint
+                    ),
+                ))
+            }
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Expected Hello",
+                ))
+            }
+        }
+
+        match self.net_senders.lock() {
+            Ok(mut senders) => {
+                // holding the lock on the senders, no new message overtakes the buffered ones
+                let buffered = match self.outbox.lock() {
+                    Ok(mut outbox) => outbox.remove(&remote).unwrap_or_default(),
+                    Err(e) => {
+                        log_err_as!(error, ActlibError::from_poison_error(&e));
+                        Outbox::default()
+                    }
+                };
+                let count = buffered.messages.len();
+                for bin in buffered.messages {
+                    if let Err(e) = self.write_net_message(remote, &mut sender, &bin) {
+                        error!(
+                            "Lost buffered messages for {} after reconnecting: {:?}",
+                            remote, e
+                        );
+                        break;
+                    }
+                }
+                // the remote machine may have restarted and forgotten the names
+                if let Some(bin) = self.names_message() {
+                    if let Err(e) = self.write_net_message(remote, &mut sender, &bin) {
+                        warn!("Could not send the names to {}: {:?}", remote, e);
+                    }
+                }
+                senders.insert(remote, sender);
+                match self.dead_links.lock() {
+                    Ok(mut dead_links) => {
+                        dead_links.remove(&remote);
+                    }
+                    Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                }
+                self.set_peer_state(
+                    |_, status| status.machine_id == Some(remote),
+                    None,
+                    PeerState::Connected,
+                );
+                info!(
+                    "Reconnected to {}, sent {} buffered messages.",
+                    remote, count
+                );
+                Ok(receiver)
+            }
+            Err(e) => Err(std::io::Error::other(format!(
+                "{:?}",
+                ActlibError::from_poison_error(&e)
+            ))),
+        }
+    }
+
+    /// Close the connection to a remote machine, so it notices the connection is gone as well.
+    fn shutdown_link(&self, remote: MachineId) {
+        match self.net_senders.lock() {
+            Ok(senders) => {
+                if let Some(net_sender) = senders.get(&remote) {
+                    let _ = net_sender.shutdown();
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// private helper function used in the receiver thread for **foreign-to-local** messages
+    fn wait_for_remote_messages(
+        env_remote_receive: &ArcEnvironment,
+        remote: MachineId,
+        net_receiver: &mut NetReceiver,
+    ) {
+        let mut reassembly = Reassembly::new(
+            env_remote_receive.message_limits.limit(&remote),
+            env_remote_receive.options.wire_format,
+        );
+        loop {
+            // read messages from TCP stream
+            match net_receiver.read_frames() {
+                Ok(frames) => {
+                    reassembly.expire();
+                    let mut frames = VecDeque::from(frames);
+                    while let Some(bin_message) = frames.pop_front() {
+                        match env_remote_receive
+                            .options
+                            .wire_format
+                            .deserialize::<NetMessage>(&bin_message)
+                        {
+                            Ok(NetMessage::Chunk(message_id, index, count, data)) => {
+                                // a completed message is handled like any other frame
+                                if let Some(message) =
+                                    reassembly.add(message_id, index, count, data)
+                                {
+                                    frames.push_front(message);
+                                }
+                            }
+                            Ok(NetMessage::Relay(destination, origin, hops_left, message)) => {
+                                env_remote_receive.learn_route(origin, remote);
+                                if destination == env_remote_receive.machine_id {
+                                    // the relayed message is handled like any other frame
+                                    frames.push_front(message);
+                                } else if hops_left == 0 {
+                                    warn!(
+                                        "Dropped a message from {} to {}, it was relayed {} times.",
+                                        origin, destination, MAX_RELAY_HOPS
+                                    );
+                                    env_remote_receive
+                                        .report_dropped_frame(DropCause::PeerDead, &message);
+                                } else {
+                                    match env_remote_receive.net_senders.lock() {
+                                        Ok(mut senders) => {
+                                            if let Err(e) = env_remote_receive.relay(
+                                                &mut senders,
+                                                destination,
+                                                origin,
+                                                hops_left - 1,
+                                                message.clone(),
+                                            ) {
+                                                warn!(
+                                                    "Failed to relay a message from {} to {}: {:?}",
+                                                    origin, destination, e
+                                                );
+                                                env_remote_receive.report_dropped_frame(
+                                                    DropCause::PeerDead,
+                                                    &message,
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            log_err_as!(error, ActlibError::from_poison_error(&e))
+                                        }
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::Broadcast(content, caller)) => {
+                                match env_remote_receive
+                                    .check_remote_capability(&caller, Capability::Broadcast)
+                                {
+                                    Ok(()) => env_remote_receive.deliver_broadcast(content),
+                                    Err(e) => log_err_as!(warn, e),
+                                }
+                            }
+                            Ok(NetMessage::SlicedBroadcast(content, slices, caller)) => {
+                                if let Err(e) = env_remote_receive
+                                    .check_remote_capability(&caller, Capability::Broadcast)
+                                {
+                                    log_err_as!(warn, e);
+                                    continue;
+                                }
+                                LocalEnvironment::deliver_in_slices(
+                                    env_remote_receive,
+                                    slices,
+                                    None,
+                                    move |env, actor_ids| {
+                                        actor_ids
+                                            .iter()
+                                            .filter(|actor_id| {
+                                                env.deliver_net_message(
+                                                    SerNetMessageContent::Message(
+                                                        content.clone(),
+                                                        None,
+                                                    ),
+                                                    (*actor_id).clone(),
+                                                )
+                                                .is_ok()
+                                            })
+                                            .count()
+                                    },
+                                );
+                            }
+                            Ok(NetMessage::OrderBroadcast(content, caller)) => {
+                                match env_remote_receive
+                                    .check_remote_capability(&caller, Capability::Broadcast)
+                                {
+                                    Ok(()) => {
+                                        env_remote_receive.sequence_broadcast(content, caller)
+                                    }
+                                    Err(e) => log_err_as!(warn, e),
+                                }
+                            }
+                            Ok(NetMessage::OrderedBroadcast(content, caller)) => {
+                                // the leader sends them one at a time, so they arrive in order
+                                match env_remote_receive
+                                    .check_remote_capability(&caller, Capability::Broadcast)
+                                {
+                                    Ok(()) => env_remote_receive.deliver_broadcast(content),
+                                    Err(e) => log_err_as!(warn, e),
+                                }
+                            }
+                            Ok(NetMessage::SpawnByTypeId(
+                                actor_type_id,
+                                local_id,
+                                spawner,
+                                capabilities,
+                                requester,
+                                spawn_no,
+                                hold_start,
+                                args,
+                            )) => {
                                 // spawn a new actor on this machine with matching local_id to the sender of the NetMessage
-                                if let Err(e) = LocalEnvironment::spawn(
-                                    Environment {
-                                        env: env_remote_receive.clone(),
+                                // a duplicated request finds the Actor of the first one and spawns nothing
+                                let spawned = match capabilities
+                                    .iter()
+                                    .try_for_each(|capability| {
+                                        env_remote_receive
+                                            .check_remote_capability(&spawner, *capability)
+                                    })
+                                    .and_then(|_| {
+                                        LocalEnvironment::spawn(
+                                            Environment {
+                                                env: env_remote_receive.clone(),
+                                            },
+                                            &actor_type_id,
+                                            SpawnId::SpawnHere(local_id),
+                                            &SpawnOptions {
+                                                spawner: spawner.map(|caller| caller.actor),
+                                                capabilities,
+                                                hold_start,
+                                                args,
+                                                ..SpawnOptions::default()
+                                            },
+                                        )
+                                    }) {
+                                    Ok(actor_ref) => Ok(actor_ref.actor_id.incarnation),
+                                    Err(e) => {
+                                        match e {
+                                            // the sender validated with an incompatible ActorBuilder,
+                                            // or the id is taken by an Actor of another type
+                                            ActlibError::InvalidId(_) => {
+                                                warn!("Rejected remote spawn: {:?}", e);
+                                            }
+                                            // the sender did not learn about the shutdown yet
+                                            ActlibError::ShuttingDown(_) => {
+                                                warn!("Rejected remote spawn: {:?}", e);
+                                            }
+                                            // the spawner may not grant the capabilities
+                                            ActlibError::PermissionDenied(_) => {
+                                                warn!("Rejected remote spawn: {:?}", e);
+                                            }
+                                            // e.g. the type is unknown to this machine, the requester learns about it
+                                            _ => error!("Remote spawn failed: {:?}", e),
+                                        }
+                                        Err(format!("{:?}", e))
+                                    }
+                                };
+                                match env_remote_receive
+                                    .options
+                                    .wire_format
+                                    .serialize(&NetMessage::SpawnAck(spawn_no, spawned))
+                                {
+                                    Ok(bin) => match env_remote_receive.net_senders.lock() {
+                                        Ok(mut senders) => {
+                                            if let Err(e) = env_remote_receive.send_to_machine(
+                                                &mut senders,
+                                                requester,
+                                                &bin,
+                                            ) {
+                                                warn!(
+                                                    "Could not acknowledge the spawn to {}: {:?}",
+                                                    requester, e
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            log_err_as!(error, ActlibError::from_poison_error(&e))
+                                        }
+                                    },
+                                    Err(e) => warn!("Failed to serialize SpawnAck: {:?}", e),
+                                }
+                            }
+                            Ok(NetMessage::SpawnAck(spawn_no, spawned)) => {
+                                match env_remote_receive.pending_spawns.lock() {
+                                    Ok(mut spawns) => {
+                                        if let Some(sender) = spawns.remove(&spawn_no) {
+                                            // the spawner may have given up already
+                                            let _ = sender.send(spawned);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log_err_as!(error, ActlibError::from_poison_error(&e))
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::StartHeld(actor_id)) => {
+                                env_remote_receive.start_held(&actor_id);
+                            }
+                            Ok(NetMessage::Message(actor_id, msg, sender)) => {
+                                // relay User Message
+                                Instruments::count(
+                                    &env_remote_receive.instruments.remote_messages_received,
+                                );
+                                env_remote_receive.handle_net_message(
+                                    SerNetMessageContent::Message(msg, sender),
+                                    actor_id,
+                                );
+                            }
+                            Ok(NetMessage::KeyedMessage(actor_id, key, msg, sender)) => {
+                                // relay keyed User Message
+                                Instruments::count(
+                                    &env_remote_receive.instruments.remote_messages_received,
+                                );
+                                env_remote_receive.handle_net_message(
+                                    SerNetMessageContent::Keyed(key, msg, sender),
+                                    actor_id,
+                                );
+                            }
+                            Ok(NetMessage::Tracked(origin, delivery_no, bin)) => {
+                                env_remote_receive.receive_tracked(origin, delivery_no, &bin);
+                            }
+                            Ok(NetMessage::Delivered(delivery_no, rejection)) => {
+                                env_remote_receive.confirm_delivery(delivery_no, rejection);
+                            }
+                            Ok(NetMessage::SpecialToken(actor_id, bin_token, caller)) => {
+                                // relay Token Message, the Tokens removing the Actor are restricted
+                                let removes = matches!(
+                                    env_remote_receive
+                                        .options
+                                        .wire_format
+                                        .deserialize::<Token>(&bin_token),
+                                    Ok(Token::Stop(_)) | Ok(Token::StopAndForward(_))
+                                );
+                                match env_remote_receive
+                                    .check_remote_capability(&caller, Capability::Remove)
+                                {
+                                    Err(e) if removes => log_err_as!(warn, e),
+                                    _ => env_remote_receive.handle_net_message(
+                                        SerNetMessageContent::Token(
+                                            bin_token,
+                                            caller.map(|caller| caller.actor),
+                                        ),
+                                        actor_id,
+                                    ),
+                                }
+                            }
+                            Ok(NetMessage::RemoveProtector(protector_id, target_id)) => {
+                                // remove protector for target id, so it can be removed (if all are removed)
+                                env_remote_receive.remove_protector(protector_id, target_id);
+                            }
+                            Ok(NetMessage::QuerySpecifiedId(
+                                queried_id,
+                                sender_machine,
+                                searcher,
+                                protected,
+                            )) => {
+                                env_remote_receive
+                                    .record_correspondent(&queried_id, sender_machine);
+                                // build dummy ActorId for local search
+                                let actor_id: ActorId = ActorId {
+                                    local_id: LocalId::Specified(queried_id.clone()),
+                                    location: env_remote_receive.machine_id,
+                                    incarnation: 0,
+                                };
+                                // does this actor exist on THIS machine?
+                                // if yes, `result` holds the local machine to be handed out
+                                let result = {
+                                    match env_remote_receive.local_actor_channels.lock() {
+                                        Ok(channels) => match channels.get_key_value(&actor_id) {
+                                            Some((current, actor)) => {
+                                                let type_id = actor.type_id.clone();
+                                                let incarnation = current.incarnation;
+                                                if protected {
+                                                    env_remote_receive
+                                                        .add_protector(searcher.clone(), actor_id);
+                                                }
+                                                Some((
+                                                    env_remote_receive.machine_id,
+                                                    type_id,
+                                                    incarnation,
+                                                ))
+                                            }
+                                            None => None,
+                                        },
+                                        Err(e) => {
+                                            error!("{:?}", ActlibError::from_poison_error(&e));
+                                            None
+                                        }
+                                    }
+                                };
+                                let result_msg = NetMessage::QuerySpecifiedIdResult(
+                                    queried_id, searcher, result,
+                                );
+                                if let Ok(serialized_msg) = env_remote_receive
+                                    .options
+                                    .wire_format
+                                    .serialize(&result_msg)
+                                {
+                                    match env_remote_receive.net_senders.lock() {
+                                        Ok(mut senders) => {
+                                            // send result to querying machine
+                                            if let Err(e) = env_remote_receive.send_to_machine(
+                                                &mut senders,
+                                                sender_machine,
+                                                &serialized_msg,
+                                            ) {
+                                                // the connection was dropped, nothing we can do here
+                                                warn!(
+                                                    "Failed to answer the Actor query of {}: {:?}",
+                                                    sender_machine, e
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            error!("{:?}", ActlibError::from_poison_error(&e));
+                                        }
+                                    }
+                                } else {
+                                    warn!("Warning: Failed to serialize result message of type QuerySpecifiedIdResult");
+                                }
+                            }
+                            Ok(NetMessage::QuerySpecifiedIdResult(
+                                queried_id,
+                                searcher_id,
+                                result,
+                            )) => {
+                                match result {
+                                    Some((machine, type_id, incarnation)) => {
+                                        // found queried_id on machine
+                                        env_remote_receive
+                                            .remember_keyed_type(&queried_id, &type_id);
+                                        match env_remote_receive.remote_queries.lock() {
+                                            Ok(mut queries) => {
+                                                if let Some(sender) = queries
+                                                    .remove(&(queried_id.clone(), searcher_id))
+                                                {
+                                                    if let Ok(actor_ref_sender) = env_remote_receive
+                                                        .external_actor_ref_sender
+                                                        .lock()
+                                                    {
+                                                        // send result
+                                                        let _ = sender.send(Some(ActorRef::new(
+                                                            ActorId {
+                                                                local_id: LocalId::Specified(
+                                                                    queried_id,
+                                                                ),
+                                                                location: machine,
+                                                                incarnation,
+                                                            },
+                                                            ActorRefChannel::Remote(
+                                                                actor_ref_sender.clone(),
+                                                            ),
+                                                            Arc::downgrade(
+                                                                &env_remote_receive.timer,
+                                                            ),
+                                                            Arc::downgrade(
+                                                                &env_remote_receive.replies,
+                                                            ),
+                                                            Arc::downgrade(
+                                                                &env_remote_receive.death_watches,
+                                                            ),
+                                                            Arc::downgrade(
+                                                                &env_remote_receive.message_limits,
+                                                            ),
+                                                            env_remote_receive.options.wire_format,
+                                                        )));
+                                                    }
+                                                }
+                                            }
+                                            Err(e) => log_err_as!(
+                                                error,
+                                                ActlibError::from_poison_error(&e)
+                                            ),
+                                        }
+                                    }
+                                    None => {
+                                        // didn't find queried_id on remote machine
+                                        match env_remote_receive.remote_queries.lock() {
+                                            Ok(queries) => {
+                                                if let Some(sender) =
+                                                    queries.get(&(queried_id, searcher_id))
+                                                {
+                                                    // channel might be closed, if another remote already send Some(...)
+                                                    // we don't have to unblock anyone in that case
+                                                    let _ = sender.send(None);
+                                                }
+                                            }
+                                            Err(e) => log_err_as!(
+                                                error,
+                                                ActlibError::from_poison_error(&e)
+                                            ),
+                                        }
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::SendExpirationSignal(
+                                requester,
+                                drain_timeout,
+                                caller,
+                            )) => {
+                                // the requester lists this machine as unreported
+                                if let Err(e) = env_remote_receive
+                                    .check_remote_capability(&caller, Capability::Expire)
+                                {
+                                    log_err_as!(warn, e);
+                                    continue;
+                                }
+                                env_remote_receive
+                                    .shutting_down
+                                    .store(true, Ordering::SeqCst);
+                                let report = env_remote_receive.drain_local_actors(drain_timeout);
+                                match env_remote_receive
+                                    .options
+                                    .wire_format
+                                    .serialize(&NetMessage::ExpirationReport(report.clone()))
+                                {
+                                    Ok(ser_report) => match env_remote_receive.net_senders.lock() {
+                                        Ok(mut senders) => {
+                                            // the requester is shutting down, if it is gone already there is nothing left to report to
+                                            let _ = env_remote_receive.send_to_machine(
+                                                &mut senders,
+                                                requester,
+                                                &ser_report,
+                                            );
+                                        }
+                                        Err(e) => {
+                                            error!("{:?}", ActlibError::from_poison_error(&e));
+                                        }
+                                    },
+                                    Err(e) => {
+                                        warn!("Failed to serialize DrainReport: {:?}", e);
+                                    }
+                                }
+                                // this only returns Err(_) when no one is waiting on the termination_receiver
+                                let _ = env_remote_receive.release_termination(ExpirationResult {
+                                    reason: ExpirationReason::RemoteRequest(requester),
+                                    reports: vec![report],
+                                    unreported: Vec::new(),
+                                });
+                            }
+                            Ok(NetMessage::ExpirationReport(report)) => {
+                                match env_remote_receive.expiration_reports.lock() {
+                                    Ok(reports) => {
+                                        if let Some(sender) = &*reports {
+                                            let _ = sender.send(report);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!("{:?}", ActlibError::from_poison_error(&e));
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::SpecifiedIds(remote, specified_ids)) => {
+                                LocalEnvironment::resolve_id_conflicts(
+                                    env_remote_receive,
+                                    remote,
+                                    specified_ids,
+                                );
+                            }
+                            Ok(NetMessage::Load(load)) => {
+                                env_remote_receive.record_load(load);
+                            }
+                            Ok(NetMessage::Counters(remote, shares)) => {
+                                env_remote_receive.counters.record_remote(remote, shares);
+                            }
+                            Ok(NetMessage::RedeemPayload(payload_id, requester, request_no)) => {
+                                let payload = env_remote_receive.stashed_payload(&payload_id);
+                                match env_remote_receive
+                                    .options
+                                    .wire_format
+                                    .serialize(&NetMessage::RedeemedPayload(request_no, payload))
+                                {
+                                    Ok(bin) => match env_remote_receive.net_senders.lock() {
+                                        Ok(mut senders) => {
+                                            if let Err(e) = env_remote_receive.send_to_machine(
+                                                &mut senders,
+                                                requester,
+                                                &bin,
+                                            ) {
+                                                warn!(
+                                                    "Failed to send payload to {}: {:?}",
+                                                    requester, e
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            log_err_as!(error, ActlibError::from_poison_error(&e))
+                                        }
+                                    },
+                                    Err(e) => warn!("Failed to serialize payload: {:?}", e),
+                                }
+                            }
+                            Ok(NetMessage::RedeemedPayload(request_no, payload)) => {
+                                match env_remote_receive.pending_redemptions.lock() {
+                                    Ok(mut redemptions) => {
+                                        if let Some(sender) = redemptions.remove(&request_no) {
+                                            // the requester may have given up already
+                                            let _ = sender.send(payload);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log_err_as!(error, ActlibError::from_poison_error(&e))
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::CollectStates(
+                                type_id,
+                                requester,
+                                request_no,
+                                timeout,
+                            )) => {
+                                // the Actors answer between their handlers, the connection is read meanwhile
+                                let env = env_remote_receive.clone();
+                                std::thread::spawn(move || {
+                                    let states = env.export_local_states(
+                                        Some(&type_id),
+                                        Instant::now() + timeout,
+                                    );
+                                    env.send_collected_states(requester, request_no, states);
+                                });
+                            }
+                            Ok(NetMessage::CollectedStates(request_no, states)) => {
+                                match env_remote_receive.pending_collections.lock() {
+                                    Ok(collections) => {
+                                        if let Some(sender) = collections.get(&request_no) {
+                                            // the collector may have given up already
+                                            let _ = sender.send(states);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log_err_as!(error, ActlibError::from_poison_error(&e))
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::Reply(correlation_id, reply, responder_type)) => {
+                                env_remote_receive.fulfil_reply(
+                                    correlation_id,
+                                    reply,
+                                    responder_type,
+                                );
+                            }
+                            Ok(NetMessage::SubscribeLifecycle(type_id, subscriber)) => {
+                                match env_remote_receive.to_actor_ref(subscriber) {
+                                    Ok(subscriber) => env_remote_receive
+                                        .add_lifecycle_subscriber(type_id, subscriber),
+                                    Err(e) => log_err_as!(warn, e),
+                                }
+                            }
+                            Ok(NetMessage::UnsubscribeLifecycle(type_id, subscriber)) => {
+                                env_remote_receive
+                                    .remove_lifecycle_subscriber(&type_id, &subscriber);
+                            }
+                            Ok(NetMessage::Names(names)) => {
+                                for (name, actor_id) in names {
+                                    env_remote_receive.adopt_name(name, actor_id);
+                                }
+                            }
+                            Ok(NetMessage::UnregisterName(name, actor_id)) => {
+                                env_remote_receive.remove_name(&name, &actor_id);
+                            }
+                            Ok(NetMessage::HandOverListener(
+                                port,
+                                handler,
+                                requester,
+                                handover_no,
+                            )) => {
+                                let failure = LocalEnvironment::register_external_listener(
+                                    env_remote_receive,
+                                    port,
+                                    handler,
+                                )
+                                .err()
+                                .map(|e| format!("{:?}", e));
+                                match env_remote_receive
+                                    .options
+                                    .wire_format
+                                    .serialize(&NetMessage::TookOverListener(handover_no, failure))
+                                {
+                                    Ok(bin) => match env_remote_receive.net_senders.lock() {
+                                        Ok(mut senders) => {
+                                            if let Err(e) = env_remote_receive.send_to_machine(
+                                                &mut senders,
+                                                requester,
+                                                &bin,
+                                            ) {
+                                                warn!(
+                                                    "Could not acknowledge the handover of port {} to {}: {:?}",
+                                                    port, requester, e
+                                                );
+                                            }
+                                        }
+                                        Err(e) => {
+                                            log_err_as!(error, ActlibError::from_poison_error(&e))
+                                        }
                                     },
-                                    &actor_type_id,
-                                    SpawnId::SpawnHere(local_id),
-                                ) {
-                                    error!("{:?}", e);
-                                    panic!("{:?}", e)
-                                    // only possibility for this error is when spawn(..) can't acquire the lock because of bad poison.
-                                    // this is an invalid state and warrants a poison
+                                    Err(e) => {
+                                        warn!("Failed to serialize TookOverListener: {:?}", e)
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::TookOverListener(handover_no, failure)) => {
+                                match env_remote_receive.pending_handovers.lock() {
+                                    Ok(mut handovers) => {
+                                        if let Some(sender) = handovers.remove(&handover_no) {
+                                            // the requester may have given up already
+                                            let _ = sender.send(failure);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log_err_as!(error, ActlibError::from_poison_error(&e))
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::ListActors(type_id, requester, request_no)) => {
+                                let actors =
+                                    env_remote_receive.list_local_actors(type_id.as_deref());
+                                env_remote_receive
+                                    .send_listed_actors(requester, request_no, actors);
+                            }
+                            Ok(NetMessage::ListedActors(request_no, actors)) => {
+                                let slot = match env_remote_receive.pending_listings.lock() {
+                                    Ok(listings) => listings.get(&request_no).cloned(),
+                                    Err(e) => {
+                                        log_err_as!(error, ActlibError::from_poison_error(&e));
+                                        None
+                                    }
+                                };
+                                // the listing may have given up already
+                                if let Some(slot) = slot {
+                                    slot.add(Some(actors));
+                                }
+                            }
+                            Ok(NetMessage::Ping(requester, ping_no)) => {
+                                env_remote_receive.send_pong(requester, ping_no);
+                            }
+                            Ok(NetMessage::Pong(ping_no)) => {
+                                let arrived = Instant::now();
+                                match env_remote_receive.pending_pings.lock() {
+                                    Ok(mut pings) => {
+                                        if let Some(sender) = pings.remove(&ping_no) {
+                                            // the pinging thread may have given up already
+                                            let _ = sender.send(arrived);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        log_err_as!(error, ActlibError::from_poison_error(&e))
+                                    }
+                                }
+                            }
+                            Ok(NetMessage::ReleasePayload(payload_id)) => {
+                                env_remote_receive.remove_stashed_payload(&payload_id);
+                            }
+                            Ok(NetMessage::Alias(from, to, ttl)) => {
+                                if let Err(e) = env_remote_receive.alias(from, to, ttl) {
+                                    warn!("Failed to register alias from {}: {:?}", remote, e);
+                                }
+                            }
+                            Ok(NetMessage::Watch(watched, watcher)) => {
+                                env_remote_receive.watch(&watched, watcher);
+                            }
+                            Ok(NetMessage::Bootstrapped) => {
+                                env_remote_receive.mark_bootstrapped();
+                            }
+                            Ok(NetMessage::Hello(machine_id, _, _)) => {
+                                // only expected as the first frame, which was handled on connect
+                                warn!("Unexpected Hello from {}, ignored.", machine_id);
+                            }
+                            Err(e) => {
+                                // do nothing. Deserialize failed, unrecognised message
+                                warn!(
+                                    "Warning: Failed to deserialize remote messsage: {:?} ({:?})",
+                                    bin_message, e
+                                );
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    env_remote_receive.mark_link_dead(remote, format!("{:?}", e));
+                    env_remote_receive.shutdown_link(remote);
+                    match env_remote_receive.reconnect(remote) {
+                        Some(receiver) => {
+                            // chunks of the dropped connection never complete
+                            *net_receiver = receiver;
+                            reassembly = Reassembly::new(
+                                env_remote_receive.message_limits.limit(&remote),
+                                env_remote_receive.options.wire_format,
+                            );
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+
+    // private helper function used in the receiver thread for **local-to-foreign** messages.
+    //
+    // The messages for remote machines are passed on to a worker per machine, which serializes and sends them.
+    // So messages for different machines are serialized in parallel, and a message keeps its order with the other messages for its machine.
+    fn wait_for_local_messages(
+        env_remote_send: &ArcEnvironment,
+        external_actor_ref_receiver: &Receiver<(ActorId, SerNetMessageContent)>,
+    ) {
+        let mut peer_workers: HashMap<MachineId, Sender<(ActorId, SerNetMessageContent)>> =
+            HashMap::new();
+        // Waits for messages and handles them sequentially
+        loop {
+            match external_actor_ref_receiver.recv() {
+                // a outgoing net message always has the form (ActorId,SerializedNetMessageContent)
+                // with SerializedNetMessageContent being either ::Message(Vec<u8>) or ::Token(Vec<u8>)
+                Ok((actor_id, content)) if actor_id.location == env_remote_send.machine_id => {
+                    // the id of a replaced local Actor
+                    env_remote_send.handle_net_message(content, actor_id);
+                }
+                Ok((actor_id, content)) => {
+                    // decided before locking the senders, as respawning may send as well
+                    let actor_id = match LocalEnvironment::route_around_dead_peer(
+                        env_remote_send,
+                        actor_id,
+                        &content,
+                    ) {
+                        Some(actor_id) => actor_id,
+                        None => continue,
+                    };
+                    if actor_id.location == env_remote_send.machine_id {
+                        // respawned on the local machine
+                        env_remote_send.handle_net_message(content, actor_id);
+                        continue;
+                    }
+                    let worker = peer_workers.entry(actor_id.location).or_insert_with(|| {
+                        let (sender, receiver) = channel();
+                        let env_worker = env_remote_send.clone();
+                        env_remote_send
+                            .supervisor
+                            .spawn(format!("actlib-send-{}", actor_id.location), move || {
+                                LocalEnvironment::serialize_for_peer(&env_worker, &receiver)
+                            });
+                        sender
+                    });
+                    if let Err(e) = worker.send((actor_id, content)) {
+                        error!(
+                            "The serialization worker of a remote machine is gone: {:?}",
+                            e
+                        );
+                    }
+                }
+                Err(_) => {
+                    // No one holds the sender end any more, so this thread can terminate
+                    warn!("All copies of the Sender part of the local-to-environment/remote channel dropped, stopping the worker thread");
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Serialize and send the messages for a single remote machine, until the thread relaying them is gone.
+    ///
+    /// The messages are serialized before the senders are locked, so the workers only wait for each other while writing.
+    fn serialize_for_peer(
+        env_remote_send: &ArcEnvironment,
+        messages: &Receiver<(ActorId, SerNetMessageContent)>,
+    ) {
+        while let Ok((actor_id, content)) = messages.recv() {
+            let location = actor_id.location;
+            let target = actor_id.clone();
+            let net_msg = match content {
+                SerNetMessageContent::Message(msg, sender) => {
+                    if let LocalId::Specified(key) = &actor_id.local_id {
+                        env_remote_send.record_correspondent(key, env_remote_send.machine_id);
+                    }
+                    NetMessage::Message(actor_id, msg, sender)
+                }
+                SerNetMessageContent::Keyed(coalescing_key, msg, sender) => {
+                    if let LocalId::Specified(key) = &actor_id.local_id {
+                        env_remote_send.record_correspondent(key, env_remote_send.machine_id);
+                    }
+                    NetMessage::KeyedMessage(actor_id, coalescing_key, msg, sender)
+                }
+                SerNetMessageContent::Token(tok, sender) => {
+                    NetMessage::SpecialToken(actor_id, tok, env_remote_send.caller(sender))
+                }
+                SerNetMessageContent::Watch(watcher) => NetMessage::Watch(actor_id, watcher),
+            };
+            let user_message = matches!(
+                net_msg,
+                NetMessage::Message(..) | NetMessage::KeyedMessage(..)
+            );
+            // try to serialize the message, silently failing if not possible
+            let tuple_serialized = match env_remote_send.options.wire_format.serialize(&net_msg) {
+                Ok(tuple_serialized)
+                    if user_message && env_remote_send.options.reliable_delivery.is_some() =>
+                {
+                    match env_remote_send.track_delivery(
+                        location,
+                        net_msg,
+                        tuple_serialized,
+                        SystemTime::now(),
+                    ) {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            warn!("Serializing NetMessage failed: {:?}", e);
+                            env_remote_send.report_dropped(
+                                DropCause::SerializationFailed,
+                                Some(target),
+                                None,
+                            );
+                            continue;
+                        }
+                    }
+                }
+                Ok(tuple_serialized) => tuple_serialized,
+                Err(e) => {
+                    warn!("Serializing NetMessage failed: {:?}", e);
+                    if user_message {
+                        env_remote_send.report_dropped(
+                            DropCause::SerializationFailed,
+                            Some(target),
+                            None,
+                        );
+                    }
+                    continue;
+                }
+            };
+            match env_remote_send.net_senders.lock() {
+                Ok(mut senders) => {
+                    match env_remote_send.send_to_machine(&mut senders, location, &tuple_serialized)
+                    {
+                        Ok(_) if user_message => {
+                            Instruments::count(&env_remote_send.instruments.remote_messages_sent)
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            warn!("Warning: Write on net_sender failed: {:?}", e);
+                            // tracked messages are retried instead
+                            if user_message && env_remote_send.options.reliable_delivery.is_none() {
+                                let cause = match e.kind() {
+                                    std::io::ErrorKind::InvalidInput => {
+                                        DropCause::SerializationFailed
+                                    }
+                                    _ => DropCause::PeerDead,
+                                };
+                                env_remote_send.report_dropped(cause, Some(target), None);
+                            }
+                        }
+                    }
+                }
+                Err(e) => error!("{:?}", ActlibError::from_poison_error(&e)),
+            }
+        }
+    }
+
+    /// Remove the [Actor](../actor/trait.Actor.html) associated with the [ActorId](../actor/struct.ActorId.html) from the Environment.
+    fn remove(&self, actor_id: ActorId) {
+        if actor_id.location != self.machine_id {
+            // remote case:
+            match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    let location = actor_id.location;
+                    // serialize on-stop message to trigger the remove method over at the remote machine
+                    match self
+                        .options
+                        .wire_format
+                        .serialize(&Token::Stop(StopReason::Removed))
+                    {
+                        Ok(bin_token) => {
+                            match self
+                                .options
+                                .wire_format
+                                .serialize(&NetMessage::SpecialToken(
+                                    actor_id,
+                                    bin_token,
+                                    self.caller(current_actor()),
+                                )) {
+                                Ok(bin_msg) => {
+                                    if let Err(e) =
+                                        self.send_to_machine(&mut senders, location, &bin_msg)
+                                    {
+                                        error!("Could not send Stop command to machine {:?}: {:?}. Message Dropped.", location, e);
+                                    }
+                                }
+                                Err(_) => {
+                                    warn!("Could not send Stop command to remote machine because the NetMessage could not be serialized.");
+                                }
+                            }
+                        }
+                        Err(_) => {
+                            error!("Could not send Stop command to remote machine because the Stop Token could not be serialized.");
+                        }
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            }
+        } else {
+            // local case:
+            if let Ok(inv_actors) = self.invincible_actors.read() {
+                if inv_actors.contains_key(&actor_id) {
+                    return;
+                }
+            }
+            let removed = match self.local_actor_channels.lock() {
+                Ok(mut channels) => channels.remove(&actor_id),
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    None
+                }
+            };
+            if let Some(local_actor) = removed {
+                Instruments::count(&self.instruments.actors_removed);
+                self.unregister_names_of(&actor_id);
+                self.death_watches.terminated(&actor_id);
+                self.notify_lifecycle(
+                    &local_actor.type_id,
+                    LifecycleEvent::Stopped(actor_id.clone()),
+                );
+            }
+            if self.options.trace_lineage {
+                match self.lineage.lock() {
+                    Ok(mut lineage) => {
+                        if let Some(record) = lineage.get_mut(&actor_id) {
+                            record.alive = false;
+                        }
+                    }
+                    Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                }
+            }
+        }
+    }
+
+    /// Create the ActorRef for an alive Actor with a User-specified ActorId.
+    /// First see if the Actor is located locally, if not try every known remote machine, or only *only_machine* if given.
+    /// If the Actor is located on a remote Machine block the current thread until an answer was received.
+    pub(crate) fn find_actor_ref(
+        &self,
+        queried_id: &Vec<u8>,
+        searcher: ActorId,
+        protected: bool,
+        only_machine: Option<MachineId>,
+    ) -> Result<(Receiver<Option<ActorRef>>, usize), ActlibError> {
+        self.record_correspondent(queried_id, self.machine_id);
+        // build local variant for comparison with existing actors
+        let target_actor_id = ActorId {
+            local_id: LocalId::Specified(queried_id.clone()),
+            location: self.machine_id,
+            incarnation: 0,
+        };
+        let (sender, receiver) = channel();
+        // Local search
+        match self.local_actor_channels.lock() {
+            Ok(channels) => {
+                if channels.contains_key(&target_actor_id) {
+                    // This is Some() only if the sending actor wants to make the target actor invincible
+                    if protected {
+                        self.add_protector(searcher.clone(), target_actor_id.clone());
+                    }
+                    // get local actor's sender
+                    match channels.get_key_value(&target_actor_id) {
+                        Some((current, local_actor)) => {
+                            // pre-fill the receiver's sender part that will be returned.
+                            // in this local case, this will be the only element of this channel that will be waited for.
+                            let new_actor_ref = ActorRef::new(
+                                current.clone(),
+                                ActorRefChannel::Local(local_actor.sender.clone()),
+                                Arc::downgrade(&self.timer),
+                                Arc::downgrade(&self.replies),
+                                Arc::downgrade(&self.death_watches),
+                                Arc::downgrade(&self.message_limits),
+                                self.options.wire_format,
+                            );
+                            sender.send(Some(new_actor_ref));
+                            Ok((receiver, 1)) // 1: this will be the only message in this channel
+                        }
+                        None => Err(ActlibError::ActorNotFound(
+                            "Requested Actor was removed just a short time ago.".to_string(),
+                        )),
+                    }
+                } else {
+                    // remote Search
+                    drop(channels); // drop MutexGuard, not needed in else case
+                                    // register LocalEnvironment level sender to propagate answers from remotes back to the receiver that is handed out at the end of this function
+                    match self.remote_queries.lock() {
+                        Ok(mut queries) => {
+                            queries.insert((queried_id.clone(), searcher.clone()), sender);
+                            drop(queries); // drop lock after use
+                            match self.net_senders.lock() {
+                                Ok(mut senders) => {
+                                    if let Ok(net_message) = self.options.wire_format.serialize(
+                                        &NetMessage::QuerySpecifiedId(
+                                            queried_id.clone(),
+                                            self.machine_id,
+                                            searcher.clone(),
+                                            protected,
+                                        ),
+                                    ) {
+                                        let sent = match only_machine {
+                                            Some(machine) => vec![(
+                                                machine,
+                                                self.send_to_machine(
+                                                    &mut senders,
+                                                    machine,
+                                                    &net_message,
+                                                ),
+                                            )],
+                                            None => self
+                                                .send_to_all_machines(&mut senders, &net_message),
+                                        };
+                                        let mut num_remotes = 0;
+                                        for (machine, result) in sent {
+                                            match result {
+                                                Ok(_) => num_remotes += 1,
+                                                // only the machines that got the query will answer
+                                                Err(e) => warn!(
+                                                    "Failed to write Actor Query to {}: {:?}",
+                                                    machine, e
+                                                ),
+                                            }
+                                        }
+                                        Ok((receiver, num_remotes))
+                                    } else {
+                                        if let Ok(mut queries) = self.remote_queries.lock() {
+                                            queries.remove(&(queried_id.clone(), searcher));
+                                        }
+                                        error!("Error: Serializing the ActorRef query failed!");
+                                        Err(ActlibError::NetworkError(
+                                            "Serializing the ActorRef query failed!".to_string(),
+                                        ))
+                                    }
+                                }
+                                Err(e) => Err(ActlibError::from_poison_error(&e)),
+                            }
+                        }
+                        Err(e) => Err(ActlibError::from_poison_error(&e)),
+                    }
+                }
+            }
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Create a new [ActorRef](../actor/struct.ActorRef.html) corresponding to the [ActorId](../actor/struct.ActorId.html).
+    ///
+    /// [ActorRefs](../actor/struct.ActorRef.html) for local [Actors](../actor/trait.Actor.html) are only created if it exists.
+    ///
+    /// [ActorRefs](../actor/struct.ActorRef.html) for remote [Actors](../actor/trait.Actor.html) are always created.
+    pub(crate) fn to_actor_ref(&self, actor_id: ActorId) -> Result<ActorRef, ActlibError> {
+        if actor_id.location == self.machine_id {
+            match self.local_actor_channels.lock() {
+                Ok(channels) => {
+                    if let Some((current, local_actor)) = channels.get_key_value(&actor_id) {
+                        if !self.admits_incarnation(&actor_id, current, None, None) {
+                            return Err(ActlibError::ActorNotFound(format!(
+                                "{} is incarnation {} of its id, not {}",
+                                current.to_string(),
+                                current.incarnation,
+                                actor_id.incarnation
+                            )));
+                        }
+                        Ok(ActorRef::new(
+                            actor_id,
+                            ActorRefChannel::Local(local_actor.sender.clone()),
+                            Arc::downgrade(&self.timer),
+                            Arc::downgrade(&self.replies),
+                            Arc::downgrade(&self.death_watches),
+                            Arc::downgrade(&self.message_limits),
+                            self.options.wire_format,
+                        ))
+                    } else if self.is_aliased(&actor_id) {
+                        drop(channels);
+                        // messages pass through the Environment, which forwards them
+                        match self.external_actor_ref_sender.lock() {
+                            Ok(sender) => Ok(ActorRef::new(
+                                actor_id,
+                                ActorRefChannel::Remote(sender.clone()),
+                                Arc::downgrade(&self.timer),
+                                Arc::downgrade(&self.replies),
+                                Arc::downgrade(&self.death_watches),
+                                Arc::downgrade(&self.message_limits),
+                                self.options.wire_format,
+                            )),
+                            Err(e) => Err(ActlibError::from_poison_error(&e)),
+                        }
+                    } else {
+                        Err(ActlibError::ActorNotFound(format!(
+                            "Did not find local actor with id {:?}",
+                            actor_id
+                        )))
+                    }
+                }
+                Err(e) => Err(ActlibError::from_poison_error(&e)),
+            }
+        } else {
+            match self.external_actor_ref_sender.lock() {
+                Ok(sender) => Ok(ActorRef::new(
+                    actor_id,
+                    ActorRefChannel::Remote(sender.clone()),
+                    Arc::downgrade(&self.timer),
+                    Arc::downgrade(&self.replies),
+                    Arc::downgrade(&self.death_watches),
+                    Arc::downgrade(&self.message_limits),
+                    self.options.wire_format,
+                )),
+                Err(e) => Err(ActlibError::from_poison_error(&e)),
+            }
+        }
+    }
+
+    /// Resolve the best channel for the Actor of *actor_ref*, using what this machine knows about it.
+    ///
+    /// Local aliases of replaced Actors are followed, and a user specified id living on this machine is
+    /// reached locally, whatever location *actor_ref* assumed. Anything else leaves *actor_ref* unchanged.
+    pub(crate) fn refresh(&self, actor_ref: ActorRef) -> ActorRef {
+        let mut actor_id = actor_ref.clone_id();
+        loop {
+            let here = match &actor_id.local_id {
+                LocalId::Specified(id) if actor_id.location != self.machine_id => ActorId {
+                    local_id: LocalId::Specified(id.clone()),
+                    location: self.machine_id,
+                    incarnation: 0,
+                },
+                _ => actor_id.clone(),
+            };
+            match self.local_actor_channels.lock() {
+                Ok(channels) => {
+                    if let Some((current, local_actor)) = channels.get_key_value(&here) {
+                        return ActorRef::new(
+                            current.clone(),
+                            ActorRefChannel::Local(local_actor.sender.clone()),
+                            Arc::downgrade(&self.timer),
+                            Arc::downgrade(&self.replies),
+                            Arc::downgrade(&self.death_watches),
+                            Arc::downgrade(&self.message_limits),
+                            self.options.wire_format,
+                        );
+                    }
+                }
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    return actor_ref;
+                }
+            }
+            if actor_id.location != self.machine_id {
+                break;
+            }
+            // aliases never form a loop, so following them terminates
+            let target = match self.aliases.lock() {
+                Ok(aliases) => aliases
+                    .get(&actor_id)
+                    .filter(|alias| alias.expires_at > Instant::now())
+                    .map(|alias| alias.target.clone()),
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    None
+                }
+            };
+            match target {
+                Some(target) => actor_id = target,
+                None => break,
+            }
+        }
+        if actor_id == actor_ref.actor_id {
+            actor_ref
+        } else {
+            // the alias target lives on another machine
+            self.to_actor_ref(actor_id).unwrap_or(actor_ref)
+        }
+    }
+
+    fn add_protector(&self, protector_id: ActorId, target_id: ActorId) {
+        match self.invincible_actors.write() {
+            Ok(mut inv_actors) => {
+                if let Some(protectors) = inv_actors.get_mut(&target_id) {
+                    protectors.insert(protector_id);
+                } else {
+                    let mut hs = HashSet::new();
+                    hs.insert(protector_id);
+                    inv_actors.insert(target_id, hs);
+                }
+            }
+            Err(e) => {
+                error!("{:?}", e);
+            }
+        }
+    }
+
+    /// Removes the given protector from protecting the given target actor. Will send this request to all remote environments too.
+    pub(crate) fn remove_protector(&self, protector_id: ActorId, target_id: ActorId) {
+        match self.invincible_actors.write() {
+            Ok(mut invincible_actors) => {
+                if let Some(protectors) = invincible_actors.get_mut(&target_id) {
+                    protectors.remove(&protector_id);
+                } else {
+                    // drop the write guard since its not needed in the else case
+                    drop(invincible_actors);
+                    //
+                    match self.net_senders.lock() {
+                        Ok(mut senders) => {
+                            match self
+                                .options
+                                .wire_format
+                                .serialize(&NetMessage::RemoveProtector(
+                                    protector_id.clone(),
+                                    target_id.clone(),
+                                )) {
+                                Ok(msg) => {
+                                    for (addr, result) in
+                                        self.send_to_all_machines(&mut senders, &msg)
+                                    {
+                                        if let Err(e) = result {
+                                            warn!("Unable to send RemoveProtector to remote {:?}, possible MemLeak! Error Message: {:?}", addr, e);
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    warn!("Unable to serialize RemoveProtector, possible MemLeak! Error Message: {:?}", e);
                                 }
                             }
-                            Ok(NetMessage::Message(actor_id, msg)) => {
-                                // relay User Message
-                                env_remote_receive.handle_net_message(
-                                    SerNetMessageContent::Message(msg),
-                                    actor_id,
-                                );
+                        }
+                        Err(e) => log_err_as!(error, e),
+                    }
+                }
+            }
+            Err(e) => log_err_as!(error, e),
+        }
+    }
+
+    pub(crate) fn remove_remote_query(&self, queried_id: &Vec<u8>, searcher: ActorId) {
+        if let Ok(mut queries) = self.remote_queries.lock() {
+            queries.remove(&(queried_id.clone(), searcher));
+        } else {
+            warn!("Unable to acquire remote_queries lock in remove_remote_query, possible MemLeak");
+        }
+    }
+
+    /// This method is called when an incoming message from another machine is detected.
+    fn handle_net_message(&self, message_or_token: SerNetMessageContent, actor_id: ActorId) {
+        // the reason was logged already
+        let _ = self.deliver_net_message(message_or_token, actor_id);
+    }
+
+    /// Pass an incoming message from another machine on to the local Actor, returning why it was not enqueued.
+    fn deliver_net_message(
+        &self,
+        message_or_token: SerNetMessageContent,
+        actor_id: ActorId,
+    ) -> Result<(), ActlibError> {
+        if let SerNetMessageContent::Watch(watcher) = message_or_token {
+            self.watch(&actor_id, watcher);
+            return Ok(());
+        }
+        match self.local_actor_channels.lock() {
+            Ok(channels) => {
+                match channels.get_key_value(&actor_id) {
+                    Some((current, LocalActor { sender, .. })) => {
+                        let (from, message) = match &message_or_token {
+                            SerNetMessageContent::Message(bin, from)
+                            | SerNetMessageContent::Keyed(_, bin, from) => {
+                                (from.clone(), Some(bin.bytes.clone()))
                             }
-                            Ok(NetMessage::SpecialToken(actor_id, bin_token)) => {
-                                // relay Token Message
-                                env_remote_receive.handle_net_message(
-                                    SerNetMessageContent::Token(bin_token),
-                                    actor_id,
-                                );
+                            _ => (None, None),
+                        };
+                        if actor_id.incarnation != current.incarnation
+                            && !self.admits_incarnation(&actor_id, current, from, message)
+                        {
+                            self.report_dropped(
+                                DropCause::ActorRemoved,
+                                Some(actor_id.clone()),
+                                None,
+                            );
+                            return Err(ActlibError::ActorNotFound(format!(
+                                "{} is incarnation {} of its id, not {}",
+                                current.to_string(),
+                                current.incarnation,
+                                actor_id.incarnation
+                            )));
+                        }
+                        let sent = match message_or_token {
+                            SerNetMessageContent::Message(bin, from) => {
+                                let size = bin.bytes.len();
+                                sender.send_from(EitherMessage::Serialized(bin), size, from)
                             }
-                            Ok(NetMessage::RemoveProtector(protector_id, target_id)) => {
-                                // remove protector for target id, so it can be removed (if all are removed)
-                                env_remote_receive.remove_protector(protector_id, target_id);
+                            SerNetMessageContent::Keyed(key, bin, from) => {
+                                let size = bin.bytes.len();
+                                sender.send_keyed(EitherMessage::Serialized(bin), size, key, from)
                             }
-                            Ok(NetMessage::QuerySpecifiedId(
-                                queried_id,
-                                sender_ip_addr,
-                                searcher,
-                                protected,
-                            )) => {
-                                // build dummy ActorId for local search
-                                let actor_id: ActorId = ActorId {
-                                    local_id: LocalId::Specified(queried_id.clone()),
-                                    location: env_remote_receive.local_machine.ip(),
-                                };
-                                // does this actor exist on THIS machine?
-                                // if yes, `result` holds the local ip to be handed out
-                                let result = {
-                                    match env_remote_receive.local_actor_channels.lock() {
-                                        Ok(channels) => {
-                                            if channels.contains_key(&actor_id) {
-                                                if protected {
-                                                    env_remote_receive
-                                                        .add_protector(searcher.clone(), actor_id);
-                                                }
-                                                Some(env_remote_receive.local_machine.ip())
-                                            } else {
-                                                None
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!("{:?}", ActlibError::from_poison_error(&e));
-                                            None
-                                        }
-                                    }
-                                };
-                                let result_msg = NetMessage::QuerySpecifiedIdResult(
-                                    queried_id, searcher, result,
-                                );
-                                if let Ok(serialized_msg) = bincode::serialize(&result_msg) {
-                                    match env_remote_receive.net_senders.lock() {
-                                        Ok(mut senders) =>
-                                            match senders.get_mut(&sender_ip_addr) {
-                                                Some(net_sender) => {
-                                                    // send result to querying machine
-                                                    let _ = net_sender.write(&serialized_msg);
-                                                    // if this fails the connection was dropped
-                                                    // nothing we can do here
-                                                }
-                                                None => log_err_as!(error, ActlibError::ActorNotFound("Failed to find Actor channel to relay remote message to local actor!".to_string()))
-                                        },
-                                        Err(e) => {
-                                            error!("{:?}", ActlibError::from_poison_error(&e));
-                                        }
-                                    }
-                                } else {
-                                    warn!("Warning: Failed to serialize result message of type QuerySpecifiedIdResult");
-                                }
+                            SerNetMessageContent::Watch(_) => {
+                                unreachable!(
+                                    "Watch requests are handled before locking the channels"
+                                )
                             }
-                            Ok(NetMessage::QuerySpecifiedIdResult(
-                                queried_id,
-                                searcher_id,
-                                result,
-                            )) => {
-                                match result {
-                                    Some(ip_addr) => {
-                                        // found queried_id on machine with ip_addr
-                                        match env_remote_receive.remote_queries.lock() {
-                                            Ok(mut queries) => {
-                                                if let Some(sender) = queries
-                                                    .remove(&(queried_id.clone(), searcher_id))
-                                                {
-                                                    if let Ok(actor_ref_sender) = env_remote_receive
-                                                        .external_actor_ref_sender
-                                                        .lock()
-                                                    {
-                                                        // send result
-                                                        let _ = sender.send(Some(ActorRef::new(
-                                                            ActorId {
-                                                                local_id: LocalId::Specified(
-                                                                    queried_id,
-                                                                ),
-                                                                location: ip_addr,
-                                                            },
-                                                            ActorRefChannel::Remote(
-                                                                actor_ref_sender.clone(),
-                                                            ),
-                                                        )));
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => log_err_as!(
-                                                error,
-                                                ActlibError::from_poison_error(&e)
-                                            ),
+                            SerNetMessageContent::Token(bin, _) => {
+                                match self.options.wire_format.deserialize::<Token>(&bin) {
+                                    Ok(token) => {
+                                        // special Tokens that are handled only by the Actor itself are passed on as a message to the actor
+                                        if let Err(e) =
+                                            sender.send(EitherMessage::Special(token), 0)
+                                        {
+                                            info!("Received remote Token message but internal actor channel is closed, probably because the actor does not exist anymore: {:?}", e);
                                         }
                                     }
-                                    None => {
-                                        // didn't find queried_id on remote machine
-                                        match env_remote_receive.remote_queries.lock() {
-                                            Ok(queries) => {
-                                                if let Some(sender) =
-                                                    queries.get(&(queried_id, searcher_id))
-                                                {
-                                                    // channel might be closed, if another remote already send Some(...)
-                                                    // we don't have to unblock anyone in that case
-                                                    let _ = sender.send(None);
-                                                }
-                                            }
-                                            Err(e) => log_err_as!(
-                                                error,
-                                                ActlibError::from_poison_error(&e)
-                                            ),
-                                        }
+                                    Err(_e) => {
+                                        warn!("Unable to de-serialize Token message from remote, system state potentially compromised.");
                                     }
                                 }
+                                return Ok(());
                             }
-                            Ok(NetMessage::SendExpirationSignal) => {
-                                // this only returns Err(_) when no one is waiting on the termination_receiver
-                                let _ = env_remote_receive.send_expiration_signal();
+                        };
+                        match &sent {
+                            Ok(_) => {}
+                            Err(ActlibError::MailboxFull(e)) => {
+                                warn!("Dropped remote message for Actor {:?}: {}", actor_id, e);
+                                self.report_dropped(DropCause::MailboxFull, Some(actor_id), None);
+                            }
+                            Err(ActlibError::ActorStopping(e)) => {
+                                warn!("Dropped remote message for Actor {:?}: {}", actor_id, e);
+                                self.report_dropped(DropCause::ActorRemoved, Some(actor_id), None);
+                            }
+                            Err(e) => {
+                                info!("Received remote message but internal actor channel is closed, probably because the actor does not exist anymore: {:?}", e);
+                                self.report_dropped(DropCause::ActorRemoved, Some(actor_id), None);
+                            }
+                        }
+                        sent
+                    }
+                    None => {
+                        drop(channels);
+                        self.forward_to_alias(actor_id, message_or_token)
+                    }
+                }
+            }
+            Err(e) => {
+                log_err_as!(warn, e);
+                Err(ActlibError::from_poison_error(&e))
+            }
+        }
+    }
+
+    /// Send Terminated to the *watcher* once the local Actor stopped.
+    fn watch(&self, actor_id: &ActorId, watcher: ActorId) {
+        match self.to_actor_ref(watcher.clone()) {
+            Ok(watcher_ref) => self.death_watches.watch(actor_id, watcher_ref),
+            Err(e) => warn!("Could not reach watcher {:?}: {:?}", watcher, e),
+        }
+    }
+
+    /// Forward a message for a local Actor that does not exist anymore to its alias, if there is one.
+    ///
+    /// Tokens are not forwarded, they were meant for the replaced Actor.
+    fn forward_to_alias(
+        &self,
+        actor_id: ActorId,
+        message_or_token: SerNetMessageContent,
+    ) -> Result<(), ActlibError> {
+        let target = match (&message_or_token, self.aliases.lock()) {
+            (SerNetMessageContent::Message(_, _), Ok(mut aliases))
+            | (SerNetMessageContent::Keyed(_, _, _), Ok(mut aliases)) => {
+                match aliases.get_mut(&actor_id) {
+                    Some(alias) if alias.expires_at > Instant::now() => {
+                        alias.forwarded += 1;
+                        Some(alias.target.clone())
+                    }
+                    Some(_) => {
+                        aliases.remove(&actor_id);
+                        None
+                    }
+                    None => None,
+                }
+            }
+            (_, Ok(_)) => None,
+            (_, Err(e)) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        match (target, message_or_token) {
+            (Some(target), SerNetMessageContent::Message(bin, sender))
+            | (Some(target), SerNetMessageContent::Keyed(_, bin, sender)) => {
+                let forwarded = self
+                    .to_actor_ref(target.clone())
+                    .and_then(|target_ref| target_ref.send_serialized_from(bin, sender));
+                if let Err(e) = &forwarded {
+                    warn!(
+                        "Failed to forward a message for {:?} to {:?}: {:?}",
+                        actor_id, target, e
+                    );
+                }
+                forwarded
+            }
+            (_, message_or_token) => {
+                warn!(
+                    "Actor {:?} not found. Remote message {:?} ignored.",
+                    actor_id, message_or_token
+                );
+                if let SerNetMessageContent::Message(..) | SerNetMessageContent::Keyed(..) =
+                    message_or_token
+                {
+                    self.report_dropped(DropCause::ActorRemoved, Some(actor_id.clone()), None);
+                }
+                Err(ActlibError::ActorNotFound(format!(
+                    "Actor {:?} not found",
+                    actor_id
+                )))
+            }
+        }
+    }
+
+    /// Forward the *pending* messages of a removed local Actor to its *successor*, returning those that were not forwarded.
+    ///
+    /// Messages passed without serialization only reach a local successor. Tokens and queries are not forwarded.
+    fn forward_pending(
+        &self,
+        actor_id: &ActorId,
+        successor: ActorId,
+        pending: Vec<Envelope>,
+    ) -> Vec<EitherMessage> {
+        let successor_ref = match self.to_actor_ref(successor.clone()) {
+            Ok(successor_ref) => successor_ref,
+            Err(e) => {
+                warn!(
+                    "Failed to forward the messages of {:?} to {:?}: {:?}",
+                    actor_id, successor, e
+                );
+                return pending
+                    .into_iter()
+                    .map(|envelope| envelope.message)
+                    .collect();
+            }
+        };
+        let mut not_forwarded = Vec::new();
+        let mut forwarded = 0;
+        for envelope in pending {
+            let sent = match (&successor_ref.sender, envelope.message) {
+                (_, message @ EitherMessage::Special(_))
+                | (_, message @ EitherMessage::Query(_)) => {
+                    not_forwarded.push(message);
+                    continue;
+                }
+                (ActorRefChannel::Local(s), message) => match envelope.key {
+                    Some(key) => s.send_keyed(message, envelope.size, key, envelope.sender),
+                    None => s.send_from(message, envelope.size, envelope.sender),
+                },
+                (ActorRefChannel::Remote(_), EitherMessage::Serialized(message)) => {
+                    successor_ref.send_serialized_from(message, envelope.sender)
+                }
+                (ActorRefChannel::Remote(_), message) => {
+                    not_forwarded.push(message);
+                    continue;
+                }
+            };
+            match sent {
+                Ok(()) => forwarded += 1,
+                Err(e) => {
+                    warn!(
+                        "Failed to forward a message of {:?} to {:?}: {:?}",
+                        actor_id, successor, e
+                    );
+                    let cause = match e {
+                        ActlibError::MailboxFull(_) => DropCause::MailboxFull,
+                        _ => DropCause::ActorRemoved,
+                    };
+                    self.report_dropped(cause, Some(successor.clone()), None);
+                }
+            }
+        }
+        debug!(
+            "Forwarded {} queued messages of {:?} to {:?}.",
+            forwarded, actor_id, successor
+        );
+        match self.aliases.lock() {
+            Ok(mut aliases) => {
+                if let Some(alias) = aliases.get_mut(actor_id) {
+                    alias.forwarded += forwarded;
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        not_forwarded
+    }
+
+    /// Wrap the serialized user Message for the *destination* machine into a Tracked message, kept until it is acknowledged.
+    ///
+    /// *first_sent* is when the Message was sent for the first time, earlier than now if it comes from the durable outbox.
+    fn track_delivery(
+        &self,
+        destination: MachineId,
+        net_msg: NetMessage,
+        bin: Vec<u8>,
+        first_sent: SystemTime,
+    ) -> Result<Vec<u8>, ActlibError> {
+        let (actor, message, sender) = match net_msg {
+            NetMessage::Message(actor, message, sender)
+            | NetMessage::KeyedMessage(actor, _, message, sender) => (actor, message, sender),
+            _ => return Ok(bin),
+        };
+        let delivery_no = NEXT_DELIVERY_NO.fetch_add(1, Ordering::Relaxed);
+        let frame = self.options.wire_format.serialize(&NetMessage::Tracked(
+            self.machine_id,
+            delivery_no,
+            bin,
+        ))?;
+        match self.unacknowledged.lock() {
+            Ok(mut unacknowledged) => {
+                if let Some(outbox) = &self.durable_outbox {
+                    match outbox.lock() {
+                        Ok(mut outbox) => {
+                            if let Err(e) =
+                                outbox.record_sent(delivery_no, destination, &frame, first_sent)
+                            {
+                                warn!("Failed to write a Message to the durable outbox: {:?}", e);
+                            }
+                        }
+                        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                    }
+                }
+                unacknowledged.insert(
+                    delivery_no,
+                    Unacknowledged {
+                        delivery_no,
+                        destination,
+                        frame: frame.clone(),
+                        actor,
+                        sender,
+                        message: message.bytes,
+                        sent_at: Instant::now(),
+                        first_sent,
+                        attempts: 1,
+                    },
+                );
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        Ok(frame)
+    }
+
+    /// Deliver a Tracked message from the *origin* machine once, and acknowledge it every time it arrives.
+    fn receive_tracked(&self, origin: MachineId, delivery_no: u64, bin: &[u8]) {
+        let outcome = match self.deliveries.lock() {
+            Ok(mut deliveries) => match deliveries.outcome(&(origin, delivery_no)) {
+                // a retry, the acknowledgement got lost or late
+                Some(outcome) => outcome,
+                None => {
+                    let delivered = match self.options.wire_format.deserialize::<NetMessage>(bin) {
+                        Ok(NetMessage::Message(actor_id, msg, sender)) => {
+                            Instruments::count(&self.instruments.remote_messages_received);
+                            self.deliver_net_message(
+                                SerNetMessageContent::Message(msg, sender),
+                                actor_id,
+                            )
+                        }
+                        Ok(NetMessage::KeyedMessage(actor_id, key, msg, sender)) => {
+                            Instruments::count(&self.instruments.remote_messages_received);
+                            self.deliver_net_message(
+                                SerNetMessageContent::Keyed(key, msg, sender),
+                                actor_id,
+                            )
+                        }
+                        Ok(_) => Err(ActlibError::InvalidState(
+                            "Only Messages to Actors are tracked".to_string(),
+                        )),
+                        Err(e) => Err(ActlibError::NetworkError(format!("{:?}", e))),
+                    };
+                    let outcome = delivered.err().map(|e| format!("{:?}", e));
+                    deliveries.record((origin, delivery_no), outcome.clone());
+                    outcome
+                }
+            },
+            Err(e) => Some(format!("{:?}", ActlibError::from_poison_error(&e))),
+        };
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::Delivered(delivery_no, outcome))
+        {
+            Ok(bin) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    // the origin sends the Message again if the acknowledgement does not arrive
+                    if let Err(e) = self.send_to_machine(&mut senders, origin, &bin) {
+                        warn!("Could not acknowledge a Message to {}: {:?}", origin, e);
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize Delivered: {:?}", e),
+        }
+    }
+
+    /// Settle a Tracked message the receiving machine acknowledged, reporting it if it was rejected.
+    fn confirm_delivery(&self, delivery_no: u64, rejection: Option<String>) {
+        let unacknowledged = match self.unacknowledged.lock() {
+            Ok(mut unacknowledged) => unacknowledged.remove(&delivery_no),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        if unacknowledged.is_some() {
+            self.record_settled(delivery_no);
+        }
+        // acknowledgements of retries arrive after the Message was settled
+        if let (Some(unacknowledged), Some(rejection)) = (unacknowledged, rejection) {
+            self.report_delivery_failure(unacknowledged, Some(rejection));
+        }
+    }
+
+    /// Remove a Tracked message from the durable outbox, if there is one.
+    fn record_settled(&self, delivery_no: u64) {
+        if let Some(outbox) = &self.durable_outbox {
+            match outbox.lock() {
+                Ok(mut outbox) => {
+                    if let Err(e) = outbox.record_settled(delivery_no) {
+                        warn!("Failed to settle a Message in the durable outbox: {:?}", e);
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            }
+        }
+    }
+
+    /// Send the Messages again that the durable outbox kept from before the restart, as new Tracked messages.
+    fn resend_unsettled(&self, unsettled: Vec<Unsettled>) {
+        info!(
+            "Sending {} unacknowledged Messages from the durable outbox again.",
+            unsettled.len()
+        );
+        let max_age = self.options.outbox_retention.max_age;
+        for (delivery_no, destination, frame, first_sent) in unsettled {
+            let tracked = match self.options.wire_format.deserialize::<NetMessage>(&frame) {
+                Ok(NetMessage::Tracked(_, _, bin)) => self
+                    .options
+                    .wire_format
+                    .deserialize::<NetMessage>(&bin)
+                    .and_then(|net_msg| self.track_delivery(destination, net_msg, bin, first_sent)),
+                Ok(_) => Err(ActlibError::InvalidState(
+                    "Only Tracked messages are kept in the durable outbox".to_string(),
+                )),
+                Err(e) => Err(e),
+            };
+            let expired = first_sent.elapsed().is_ok_and(|age| age > max_age);
+            match tracked {
+                // given up on and reported with the next retries
+                Ok(_) if expired => {}
+                Ok(frame) => match self.net_senders.lock() {
+                    Ok(mut senders) => {
+                        // retried like every other Tracked message if the destination is not connected yet
+                        let _ = self.send_to_machine(&mut senders, destination, &frame);
+                    }
+                    Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                },
+                Err(e) => warn!("Dropped a Message from the durable outbox: {:?}", e),
+            }
+            // kept under its new delivery number
+            self.record_settled(delivery_no);
+        }
+    }
+
+    /// Tell the watchers about a Message whose delivery was not confirmed.
+    fn report_delivery_failure(&self, unacknowledged: Unacknowledged, rejection: Option<String>) {
+        warn!(
+            "The delivery of a Message to {:?} failed after {} attempts: {}",
+            unacknowledged.actor,
+            unacknowledged.attempts,
+            rejection.as_deref().unwrap_or("never acknowledged")
+        );
+        let failure = DeliveryFailure {
+            actor: unacknowledged.actor,
+            sender: unacknowledged.sender,
+            message: unacknowledged.message,
+            attempts: unacknowledged.attempts,
+            rejection,
+        };
+        self.delivery_failure_watchers.notify(&failure);
+    }
+
+    /// Forward the messages for *from* to *to* for the given time, on the machine *from* lives on.
+    pub(crate) fn alias(
+        &self,
+        from: ActorId,
+        to: ActorId,
+        ttl: Duration,
+    ) -> Result<(), ActlibError> {
+        if from.location != self.machine_id {
+            let bin =
+                self.options
+                    .wire_format
+                    .serialize(&NetMessage::Alias(from.clone(), to, ttl))?;
+            return match self.net_senders.lock() {
+                Ok(mut senders) => match self.send_to_machine(&mut senders, from.location, &bin) {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(ActlibError::NetworkError(format!(
+                        "Failed to send alias of {:?}: {:?}",
+                        from, e
+                    ))),
+                },
+                Err(e) => Err(ActlibError::from_poison_error(&e)),
+            };
+        }
+        match self.aliases.lock() {
+            Ok(mut aliases) => {
+                // a chain of aliases leading back to *from* would forward its messages forever
+                let mut next = Some(&to);
+                while let Some(target) = next {
+                    if *target == from {
+                        return Err(ActlibError::InvalidState(format!(
+                            "Aliasing {:?} to {:?} creates a forwarding loop",
+                            from, to
+                        )));
+                    }
+                    next = aliases.get(target).map(|alias| &alias.target);
+                }
+                aliases.insert(
+                    from,
+                    Alias {
+                        target: to,
+                        expires_at: Instant::now() + ttl,
+                        forwarded: 0,
+                    },
+                );
+                Ok(())
+            }
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Keep a payload on this machine for the payload lease.
+    pub(crate) fn stash_payload(&self, payload: Vec<u8>) -> PayloadTicket {
+        let ticket = PayloadTicket {
+            id: Uuid::new_v4(),
+            owner: self.machine_id,
+            size: payload.len(),
+        };
+        match self.stashed_payloads.lock() {
+            Ok(mut payloads) => {
+                let now = Instant::now();
+                payloads.retain(|_, stashed| stashed.expires_at > now);
+                payloads.insert(
+                    ticket.id,
+                    StashedPayload {
+                        payload,
+                        expires_at: now + self.options.payload_lease,
+                    },
+                );
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        ticket
+    }
+
+    /// A copy of the payload stashed on this machine, ```None``` if it was released or expired.
+    fn stashed_payload(&self, payload_id: &Uuid) -> Option<Vec<u8>> {
+        match self.stashed_payloads.lock() {
+            Ok(payloads) => payloads
+                .get(payload_id)
+                .filter(|stashed| stashed.expires_at > Instant::now())
+                .map(|stashed| stashed.payload.clone()),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    fn remove_stashed_payload(&self, payload_id: &Uuid) {
+        match self.stashed_payloads.lock() {
+            Ok(mut payloads) => {
+                payloads.remove(payload_id);
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Get the payload of a ticket, asking its owner if it is kept on a remote machine.
+    pub(crate) fn redeem(&self, ticket: &PayloadTicket) -> Result<Vec<u8>, ActlibError> {
+        let payload = if ticket.owner == self.machine_id {
+            self.stashed_payload(&ticket.id)
+        } else {
+            let request_no = NEXT_REDEMPTION_NO.fetch_add(1, Ordering::Relaxed);
+            let (sender, receiver) = channel();
+            match self.pending_redemptions.lock() {
+                Ok(mut redemptions) => {
+                    redemptions.insert(request_no, sender);
+                }
+                Err(e) => return Err(ActlibError::from_poison_error(&e)),
+            }
+            let result = self
+                .options
+                .wire_format
+                .serialize(&NetMessage::RedeemPayload(
+                    ticket.id,
+                    self.machine_id,
+                    request_no,
+                ))
+                .map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))
+                .and_then(|bin| match self.net_senders.lock() {
+                    Ok(mut senders) => self
+                        .send_to_machine(&mut senders, ticket.owner, &bin)
+                        .map_err(|e| {
+                            ActlibError::NetworkError(format!(
+                                "Failed to request payload from {}: {:?}",
+                                ticket.owner, e
+                            ))
+                        }),
+                    Err(e) => Err(ActlibError::from_poison_error(&e)),
+                })
+                .and_then(|_| {
+                    scheduler::blocking(|| receiver.recv_timeout(REDEEM_TIMEOUT)).map_err(|_| {
+                        ActlibError::Timeout(format!(
+                            "No payload from {} within {:?}",
+                            ticket.owner, REDEEM_TIMEOUT
+                        ))
+                    })
+                });
+            if let Ok(mut redemptions) = self.pending_redemptions.lock() {
+                redemptions.remove(&request_no);
+            }
+            result?
+        };
+        payload.ok_or_else(|| {
+            ActlibError::InvalidState(format!(
+                "Payload {} was released or its lease expired",
+                ticket.id
+            ))
+        })
+    }
+
+    /// Gather the exported states of the Actors of the given type on this and every connected machine within *timeout*.
+    pub(crate) fn collect_states(&self, type_id: &str, timeout: Duration) -> ExportedStates {
+        let deadline = Instant::now() + timeout;
+        let request_no = NEXT_COLLECTION_NO.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = channel();
+        match self.pending_collections.lock() {
+            Ok(mut collections) => {
+                collections.insert(request_no, sender);
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        // the remote machines keep a quarter of the timeout to send their states back
+        let remote_timeout = timeout * 3 / 4;
+        let connected: Vec<MachineId> = self
+            .peer_status()
+            .into_iter()
+            .filter(|status| status.state == PeerState::Connected)
+            .filter_map(|status| status.machine_id)
+            .collect();
+        let mut asked = 0;
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::CollectStates(
+                type_id.to_string(),
+                self.machine_id,
+                request_no,
+                remote_timeout,
+            )) {
+            Ok(bin) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    for machine in connected {
+                        match self.send_to_machine(&mut senders, machine, &bin) {
+                            Ok(_) => asked += 1,
+                            Err(e) => warn!("Failed to collect the states on {}: {:?}", machine, e),
+                        }
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize the collection request: {:?}", e),
+        }
+        let mut states = self.export_local_states(Some(type_id), deadline);
+        for _ in 0..asked {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match scheduler::blocking(|| receiver.recv_timeout(timeout)) {
+                Ok(remote_states) => states.extend(remote_states),
+                Err(_) => {
+                    warn!("Not every machine sent the states of {} in time.", type_id);
+                    break;
+                }
+            }
+        }
+        if let Ok(mut collections) = self.pending_collections.lock() {
+            collections.remove(&request_no);
+        }
+        states
+    }
+
+    /// The ids of the Actors alive on the local machine, of the given type or of any type.
+    pub(crate) fn list_local_actors(&self, type_id: Option<&str>) -> Vec<ActorId> {
+        match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .iter()
+                .filter(|(_, local_actor)| {
+                    type_id.is_none_or(|type_id| local_actor.type_id == type_id)
+                })
+                .map(|(actor_id, _)| actor_id.clone())
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Ask every connected machine for the ids of its Actors of the given type or of any type,
+    /// returning the slot they are gathered in along with the local ones.
+    ///
+    /// The slot is done once every machine answered or after *timeout*.
+    pub(crate) fn list_cluster_actors(
+        env: &ArcEnvironment,
+        type_id: Option<&str>,
+        timeout: Duration,
+    ) -> Arc<ListingSlot> {
+        let request_no = NEXT_LISTING_NO.fetch_add(1, Ordering::Relaxed);
+        let connected: Vec<MachineId> = env
+            .peer_status()
+            .into_iter()
+            .filter(|status| status.state == PeerState::Connected)
+            .filter_map(|status| status.machine_id)
+            .collect();
+        let slot = Arc::new(ListingSlot {
+            state: Mutex::new(ListingState {
+                actors: env.list_local_actors(type_id),
+                missing: connected.len(),
+                ..ListingState::default()
+            }),
+            ..ListingSlot::default()
+        });
+        if connected.is_empty() {
+            slot.add(None);
+            return slot;
+        }
+        match env.pending_listings.lock() {
+            Ok(mut listings) => {
+                listings.insert(request_no, slot.clone());
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        match env.options.wire_format.serialize(&NetMessage::ListActors(
+            type_id.map(String::from),
+            env.machine_id,
+            request_no,
+        )) {
+            Ok(bin) => match env.net_senders.lock() {
+                Ok(mut senders) => {
+                    for machine in connected {
+                        if let Err(e) = env.send_to_machine(&mut senders, machine, &bin) {
+                            warn!("Failed to list the Actors on {}: {:?}", machine, e);
+                            // it will not answer, so it lists nothing
+                            slot.add(Some(Vec::new()));
+                        }
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize the listing request: {:?}", e),
+        }
+        let weak_env = Arc::downgrade(env);
+        let timer_slot = slot.clone();
+        std::thread::spawn(move || {
+            if let Ok(state) = timer_slot.state.lock() {
+                let _ = timer_slot
+                    .finished
+                    .wait_timeout_while(state, timeout, |state| !state.done);
+            }
+            timer_slot.add(None);
+            if let Some(env) = weak_env.upgrade() {
+                if let Ok(mut listings) = env.pending_listings.lock() {
+                    listings.remove(&request_no);
+                }
+            }
+        });
+        slot
+    }
+
+    /// Send a ping to *machine*, returning the round trip time once it answered within *timeout*.
+    pub(crate) fn ping_machine(
+        &self,
+        machine: MachineId,
+        timeout: Duration,
+    ) -> Result<Duration, ActlibError> {
+        let ping = self.send_ping(machine)?;
+        self.await_pong(ping, Instant::now() + timeout)
+    }
+
+    /// Send a Ping to *machine*, counting it as missed if it cannot be sent.
+    fn send_ping(&self, machine: MachineId) -> Result<SentPing, ActlibError> {
+        let ping_no = NEXT_PING_NO.fetch_add(1, Ordering::Relaxed);
+        let (sender, answered) = channel();
+        match self.pending_pings.lock() {
+            Ok(mut pings) => {
+                pings.insert(ping_no, sender);
+            }
+            Err(e) => return Err(ActlibError::from_poison_error(&e)),
+        }
+        let sent_at = Instant::now();
+        let sent = self
+            .options
+            .wire_format
+            .serialize(&NetMessage::Ping(self.machine_id, ping_no))
+            .and_then(|bin| match self.net_senders.lock() {
+                Ok(mut senders) => self
+                    .send_to_machine(&mut senders, machine, &bin)
+                    .map_err(|e| {
+                        ActlibError::NetworkError(format!("Failed to ping {}: {:?}", machine, e))
+                    }),
+                Err(e) => Err(ActlibError::from_poison_error(&e)),
+            });
+        if let Err(e) = sent {
+            if let Ok(mut pings) = self.pending_pings.lock() {
+                pings.remove(&ping_no);
+            }
+            self.record_ping(machine, None);
+            return Err(e);
+        }
+        Ok(SentPing {
+            machine,
+            ping_no,
+            sent_at,
+            answered,
+        })
+    }
+
+    /// Wait until the *ping* was answered or the *deadline* passed, recording the outcome.
+    fn await_pong(&self, ping: SentPing, deadline: Instant) -> Result<Duration, ActlibError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let answered = scheduler::blocking(|| ping.answered.recv_timeout(timeout));
+        if let Ok(mut pings) = self.pending_pings.lock() {
+            pings.remove(&ping.ping_no);
+        }
+        match answered {
+            Ok(arrived) => {
+                let round_trip = arrived.saturating_duration_since(ping.sent_at);
+                self.record_ping(ping.machine, Some(round_trip));
+                Ok(round_trip)
+            }
+            Err(_) => {
+                self.record_ping(ping.machine, None);
+                Err(ActlibError::Timeout(format!(
+                    "{} did not answer the ping within {:?}",
+                    ping.machine,
+                    deadline.saturating_duration_since(ping.sent_at)
+                )))
+            }
+        }
+    }
+
+    /// Record the round trip time of an answered ping of *machine*, or ```None``` for a missed one.
+    fn record_ping(&self, machine: MachineId, round_trip: Option<Duration>) {
+        let missed_beats = self.missed_beats();
+        match self.pings.lock() {
+            Ok(mut pings) => {
+                let record = pings.entry(machine).or_default();
+                match round_trip {
+                    Some(round_trip) => {
+                        if record.missed >= missed_beats {
+                            info!("{} answers pings again.", machine);
+                        }
+                        record.round_trip = Some(round_trip);
+                        record.answered_at = Some(Instant::now());
+                        record.missed = 0;
+                    }
+                    None => {
+                        record.missed += 1;
+                        if record.missed == missed_beats {
+                            warn!(
+                                "{} did not answer {} pings, it is down.",
+                                machine, missed_beats
+                            );
+                        }
+                    }
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Number of missed pings in a row after which a machine is down.
+    fn missed_beats(&self) -> u32 {
+        self.options
+            .heartbeat
+            .as_ref()
+            .map_or(1, |heartbeat| heartbeat.missed_beats.max(1))
+    }
+
+    /// Answer a Ping of *requester*.
+    fn send_pong(&self, requester: MachineId, ping_no: u64) {
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::Pong(ping_no))
+        {
+            Ok(bin) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    if let Err(e) = self.send_to_machine(&mut senders, requester, &bin) {
+                        warn!("Failed to answer the ping of {}: {:?}", requester, e);
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize a Pong: {:?}", e),
+        }
+    }
+
+    /// Whether every configured remote machine is up, in the order they were given.
+    pub(crate) fn cluster_status(&self) -> Vec<MachineStatus> {
+        let missed_beats = self.missed_beats();
+        let pings = match self.pings.lock() {
+            Ok(pings) => pings.clone(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                HashMap::new()
+            }
+        };
+        self.peer_status()
+            .into_iter()
+            .map(|status| {
+                let record = status
+                    .machine_id
+                    .and_then(|machine| pings.get(&machine).cloned())
+                    .unwrap_or_default();
+                let state = if status.state == PeerState::Connected && record.missed < missed_beats
+                {
+                    MachineState::Up
+                } else {
+                    MachineState::Down
+                };
+                MachineStatus {
+                    peer: status.peer,
+                    machine_id: status.machine_id,
+                    state,
+                    round_trip: record.round_trip,
+                    since_answer: record.answered_at.map(|answered_at| answered_at.elapsed()),
+                    missed_pings: record.missed,
+                }
+            })
+            .collect()
+    }
+
+    /// Call *tick* on a supervised thread named *name* every *interval*, until the Environment is dropped.
+    fn spawn_periodic<F>(env: &ArcEnvironment, name: &str, interval: Duration, mut tick: F)
+    where
+        F: FnMut(&ArcEnvironment) + Send + 'static,
+    {
+        let env_weak = Arc::downgrade(env);
+        env.supervisor.spawn(name.to_string(), move || loop {
+            std::thread::sleep(interval);
+            match env_weak.upgrade() {
+                Some(env) => tick(&env),
+                None => break,
+            }
+        });
+    }
+
+    /// Ping every connected machine, waiting for the answers at most for the timeout of the *heartbeat*.
+    fn send_heartbeats(env: &ArcEnvironment, heartbeat: &Heartbeat) {
+        let connected: Vec<MachineId> = env
+            .peer_status()
+            .into_iter()
+            .filter(|status| status.state == PeerState::Connected)
+            .filter_map(|status| status.machine_id)
+            .collect();
+        // all machines are pinged at once, so a slow one does not delay the others
+        let sent: Vec<SentPing> = connected
+            .into_iter()
+            .filter_map(|machine| match env.send_ping(machine) {
+                Ok(ping) => Some(ping),
+                Err(e) => {
+                    debug!("Heartbeat not sent: {:?}", e);
+                    None
+                }
+            })
+            .collect();
+        let deadline = Instant::now() + heartbeat.timeout;
+        for ping in sent {
+            if let Err(e) = env.await_pong(ping, deadline) {
+                debug!("Heartbeat not answered: {:?}", e);
+            }
+        }
+    }
+
+    /// Answer a ListActors request of *requester* with the *actors* alive on this machine.
+    fn send_listed_actors(&self, requester: MachineId, request_no: u64, actors: Vec<ActorId>) {
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::ListedActors(request_no, actors))
+        {
+            Ok(bin) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    if let Err(e) = self.send_to_machine(&mut senders, requester, &bin) {
+                        warn!("Failed to list the Actors for {}: {:?}", requester, e);
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize the listed Actors: {:?}", e),
+        }
+    }
+
+    /// Export the state of every local Actor of the given type, or of any type, as far as they answer before *deadline*.
+    ///
+    /// The Actor calling this is left out, it would wait for its own answer.
+    fn export_local_states(&self, type_id: Option<&str>, deadline: Instant) -> ExportedStates {
+        let local_actors: Vec<(ActorId, MailboxSender)> = match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .iter()
+                .filter(|(_, local_actor)| {
+                    type_id.is_none_or(|type_id| local_actor.type_id == type_id)
+                })
+                .map(|(actor_id, local_actor)| (actor_id.clone(), local_actor.sender.clone()))
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        let caller = current_actor();
+        let (state_sender, state_receiver) = channel();
+        let mut asked = 0;
+        for (actor_id, sender) in local_actors {
+            if caller.as_ref() == Some(&actor_id) {
+                continue;
+            }
+            let state_sender = state_sender.clone();
+            let query = Query(Box::new(move |actor: &dyn Actor| {
+                // the collector may have given up already
+                let _ = state_sender.send((actor_id, actor.export_state()));
+            }));
+            if sender.send(EitherMessage::Query(query), 0).is_ok() {
+                asked += 1;
+            }
+        }
+        // Actors stopping before they answered disconnect the receiver once all of them are gone
+        drop(state_sender);
+        let mut states = Vec::with_capacity(asked);
+        for _ in 0..asked {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match scheduler::blocking(|| state_receiver.recv_timeout(timeout)) {
+                Ok((actor_id, Some(ExportedState(state)))) => states.push((actor_id, state)),
+                Ok((_, None)) => {}
+                Err(_) => break,
+            }
+        }
+        states
+    }
+
+    /// Describe every local Actor with the state it exports within *timeout*.
+    pub(crate) fn snapshot(&self, timeout: Duration) -> Snapshot {
+        let deadline = Instant::now() + timeout;
+        let mut actors: Vec<ActorSnapshot> = match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .iter()
+                .map(|(actor_id, local_actor)| ActorSnapshot {
+                    id: actor_id.clone(),
+                    type_id: local_actor.type_id.clone(),
+                    specified_id: actor_id.clone().when_specified(),
+                    state: None,
+                })
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        let mut states: HashMap<ActorId, Vec<u8>> = self
+            .export_local_states(None, deadline)
+            .into_iter()
+            .collect();
+        for actor in actors.iter_mut() {
+            actor.state = states.remove(&actor.id);
+        }
+        actors.sort_by(|a, b| a.id.cmp(&b.id));
+        Snapshot {
+            machine_id: self.machine_id,
+            taken_at: SystemTime::now(),
+            actors,
+        }
+    }
+
+    /// Answer a CollectStates request of *requester* with the *states* exported on this machine.
+    fn send_collected_states(&self, requester: MachineId, request_no: u64, states: ExportedStates) {
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::CollectedStates(request_no, states))
+        {
+            Ok(bin) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    if let Err(e) = self.send_to_machine(&mut senders, requester, &bin) {
+                        warn!(
+                            "Failed to send the collected states to {}: {:?}",
+                            requester, e
+                        );
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize the collected states: {:?}", e),
+        }
+    }
+
+    /// Send the serialized reply to an ask to the machine of the asker.
+    pub(crate) fn reply(
+        &self,
+        reply_to: MachineId,
+        correlation_id: u64,
+        reply: Vec<u8>,
+    ) -> Result<(), ActlibError> {
+        let responder_type = match self.options.trace_replies {
+            true => current_actor().and_then(|responder| self.actor_type(&responder)),
+            false => None,
+        };
+        if reply_to == self.machine_id {
+            self.fulfil_reply(correlation_id, reply, responder_type);
+            return Ok(());
+        }
+        let bin = self.options.wire_format.serialize(&NetMessage::Reply(
+            correlation_id,
+            reply,
+            responder_type,
+        ))?;
+        match self.net_senders.lock() {
+            Ok(mut senders) => match self.send_to_machine(&mut senders, reply_to, &bin) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ActlibError::NetworkError(format!(
+                    "Failed to send reply to {}: {:?}",
+                    reply_to, e
+                ))),
+            },
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Drop the payload of a ticket on the machine keeping it.
+    pub(crate) fn release_payload(&self, ticket: &PayloadTicket) -> Result<(), ActlibError> {
+        if ticket.owner == self.machine_id {
+            self.remove_stashed_payload(&ticket.id);
+            return Ok(());
+        }
+        let bin = self
+            .options
+            .wire_format
+            .serialize(&NetMessage::ReleasePayload(ticket.id))?;
+        match self.net_senders.lock() {
+            Ok(mut senders) => match self.send_to_machine(&mut senders, ticket.owner, &bin) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ActlibError::NetworkError(format!(
+                    "Failed to release payload {}: {:?}",
+                    ticket.id, e
+                ))),
+            },
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Returns ```true``` if messages for *actor_id* are currently forwarded.
+    fn is_aliased(&self, actor_id: &ActorId) -> bool {
+        match self.aliases.lock() {
+            Ok(aliases) => aliases
+                .get(actor_id)
+                .is_some_and(|alias| alias.expires_at > Instant::now()),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                false
+            }
+        }
+    }
+
+    /// The forwarding entries of this machine, dropping the expired ones.
+    pub(crate) fn aliases(&self) -> Vec<AliasStats> {
+        match self.aliases.lock() {
+            Ok(mut aliases) => {
+                let now = Instant::now();
+                aliases.retain(|_, alias| alias.expires_at > now);
+                aliases
+                    .iter()
+                    .map(|(from, alias)| AliasStats {
+                        from: from.clone(),
+                        to: alias.target.clone(),
+                        forwarded: alias.forwarded,
+                        expires_in: alias.expires_at - now,
+                    })
+                    .collect()
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Spawn a given [Actor](../actor/trait.Actor.html) object inside this Environment.
+    ///
+    /// This method registers the [Actor](../actor/trait.Actor.html) inside this Environment and subsequently calls it's [on_start](../actor/trait.Actor.html#method.on_start) Method.
+    ///
+    /// The return value is an [ActorRef](../actor/struct.ActorRef.html) object as the [Actor](../actor/trait.Actor.html) address.
+    /// Use it to send messages to the now alive [Actor](../actor/trait.Actor.html).
+    pub(crate) fn spawn(
+        env: Environment,
+        actor_type_id: &str,
+        local_id: SpawnId,
+        options: &SpawnOptions,
+    ) -> Result<ActorRef, ActlibError> {
+        let local_environment = &env.env;
+        if local_environment.is_shutting_down() {
+            return Err(ActlibError::ShuttingDown(format!(
+                "The Environment is shutting down, cannot spawn {}",
+                actor_type_id
+            )));
+        }
+        let spawner = match &options.spawner {
+            Some(spawner) => Some(spawner.clone()),
+            None => {
+                // the grants of remote spawn requests were checked against their spawner on arrival
+                for capability in &options.capabilities {
+                    local_environment.check_capability(*capability)?;
+                }
+                current_actor()
+            }
+        };
+
+        match &local_id {
+            SpawnId::User(id) | SpawnId::SpawnHere(id) => {
+                local_environment
+                    .actor_builder
+                    .validate_id(actor_type_id, id)?;
+                if let LocalId::Specified(key) = id {
+                    local_environment.remember_keyed_type(key, actor_type_id);
+                }
+            }
+            SpawnId::Automatic => {}
+        }
+
+        let mut machine_no = 0;
+        if let Some(machine) = options.machine.filter(|_| !local_id.is_spawn_here()) {
+            if options.excluded_machines.contains(&machine) {
+                return Err(ActlibError::SpawnFailed(format!(
+                    "{} is excluded: {:?}",
+                    machine, options.excluded_machines
+                )));
+            }
+            machine_no = match local_environment.machine_no(&machine) {
+                Some(no) => no,
+                None => {
+                    return Err(ActlibError::SpawnFailed(format!(
+                        "{} is not connected",
+                        machine
+                    )));
+                }
+            };
+        } else if let (Placement::ConsistentHash, SpawnId::User(LocalId::Specified(key))) =
+            (&local_environment.options.placement, &local_id)
+        {
+            let machine = local_environment
+                .machine_for_id(key, &options.excluded_machines)
+                .ok_or_else(|| {
+                    ActlibError::SpawnFailed(format!(
+                        "Every machine is excluded: {:?}",
+                        options.excluded_machines
+                    ))
+                })?;
+            machine_no = local_environment
+                .machine_no(&machine)
+                .ok_or_else(|| ActlibError::SpawnFailed(format!("{} is not connected", machine)))?;
+        } else if !local_id.is_spawn_here() {
+            let excluded = local_environment.excluded_machine_nos(&options.excluded_machines);
+            match local_environment.load_balancer.lock() {
+                Ok(mut balancer) => match balancer.next_machine_no(&excluded) {
+                    Some(no) => machine_no = no,
+                    None => {
+                        return Err(ActlibError::SpawnFailed(format!(
+                            "Every machine is excluded: {:?}",
+                            options.excluded_machines
+                        )));
+                    }
+                },
+                Err(e) => {
+                    warn!("Could not acquire LoadBalancer Mutex lock, defaulted to local spawn.");
+                }
+            }
+        }
+        match machine_no {
+            0 => {
+                let mut new_actor = local_environment.actor_builder.build(actor_type_id)?;
+                if let Some(state) = &options.state {
+                    new_actor.import_state(state.clone());
+                }
+                if let Some(args) = &options.args {
+                    if let Err(reason) = catch_panic(|| new_actor.on_init(args)) {
+                        return Err(ActlibError::SpawnFailed(format!(
+                            "{} panicked in on_init: {}",
+                            actor_type_id, reason
+                        )));
+                    }
+                }
+
+                LocalEnvironment::start_local_actor(
+                    &env,
+                    actor_type_id,
+                    new_actor,
+                    local_id.unwrap_or_automatic(),
+                    spawner,
+                    options,
+                )
+            }
+            remote_machine_no => {
+                let new_actor_local_id = match local_id {
+                    SpawnId::Automatic => LocalId::Automatic(Uuid::new_v4()),
+                    SpawnId::User(id) => id,
+                    SpawnId::SpawnHere(_) => {
+                        unreachable!("SpawnHere wanted to send to remote machine.")
+                    }
+                };
+                // the remote machine acknowledges the spawn, so no ActorRef to a missing Actor is handed out
+                let spawn_no = NEXT_SPAWN_NO.fetch_add(1, Ordering::Relaxed);
+                let (ack_sender, ack_receiver) = channel();
+                match local_environment.pending_spawns.lock() {
+                    Ok(mut spawns) => {
+                        spawns.insert(spawn_no, ack_sender);
+                    }
+                    Err(e) => return Err(ActlibError::from_poison_error(&e)),
+                }
+                // machine no that is returned from the load balancer is 1 higher than the index, because id 0 is local.
+                let sent = match local_environment.net_senders.lock() {
+                    Ok(mut senders) => match senders.get_index_mut(remote_machine_no - 1) {
+                        Some((machine, net_sender)) => {
+                            let machine = *machine;
+                            local_environment
+                                .options
+                                .wire_format
+                                .serialize(&NetMessage::SpawnByTypeId(
+                                    actor_type_id.to_string(),
+                                    new_actor_local_id.clone(),
+                                    local_environment.caller(spawner),
+                                    options.capabilities.clone(),
+                                    local_environment.machine_id,
+                                    spawn_no,
+                                    options.hold_start,
+                                    options.args.clone(),
+                                ))
+                                .map_err(|_| {
+                                    ActlibError::SpawnFailed(
+                                        "Failed to serialize SpawnByTypeId message".to_string(),
+                                    )
+                                })
+                                .and_then(|msg| {
+                                    local_environment
+                                        .write_net_message(machine, net_sender, &msg)
+                                        .map_err(|e| {
+                                            ActlibError::SpawnFailed(format!(
+                                                "Failed to send SpawnByTypeId message to {}: {:?}",
+                                                machine, e
+                                            ))
+                                        })
+                                })
+                                .map(|_size| machine)
+                        }
+                        None => Err(ActlibError::InvalidState(format!(
+                            "Error: LoadBalancer returned machine no that is invalid: {}",
+                            machine_no
+                        ))),
+                    },
+                    Err(e) => Err(ActlibError::from_poison_error(&e)),
+                };
+                let acknowledged = sent.and_then(|machine| {
+                    match scheduler::blocking(|| ack_receiver.recv_timeout(SPAWN_ACK_TIMEOUT)) {
+                        Ok(Ok(incarnation)) => Ok((machine, incarnation)),
+                        Ok(Err(failure)) => Err(ActlibError::SpawnFailed(format!(
+                            "{} could not spawn {}: {}",
+                            machine, actor_type_id, failure
+                        ))),
+                        Err(_) => Err(ActlibError::Timeout(format!(
+                            "{} did not acknowledge the spawn of {} within {:?}, it may still happen",
+                            machine, actor_type_id, SPAWN_ACK_TIMEOUT
+                        ))),
+                    }
+                });
+                if let Ok(mut spawns) = local_environment.pending_spawns.lock() {
+                    spawns.remove(&spawn_no);
+                }
+                let (location, incarnation) = acknowledged?;
+                local_environment.to_actor_ref(ActorId {
+                    local_id: new_actor_local_id,
+                    location,
+                    incarnation,
+                })
+            }
+        }
+    }
+
+    /// Register and start an Actor that was already built, e.g. by the ActorBuilder, on this machine.
+    ///
+    /// A duplicated spawn of a user specified id returns the existing Actor instead, if it has the same type.
+    pub(crate) fn start_local_actor(
+        env: &Environment,
+        actor_type_id: &str,
+        new_actor: Box<dyn Actor>,
+        local_id: LocalId,
+        spawner: Option<ActorId>,
+        options: &SpawnOptions,
+    ) -> Result<ActorRef, ActlibError> {
+        let local_environment = &env.env;
+        let incarnation = match &local_id {
+            LocalId::Specified(_) => NEXT_INCARNATION.fetch_add(1, Ordering::Relaxed),
+            LocalId::Automatic(_) => 0,
+        };
+        let actor_id = ActorId {
+            local_id,
+            location: local_environment.machine_id,
+            incarnation,
+        };
+
+        // create new channel for the new actor's mailbox
+        let quota = local_environment
+            .options
+            .mailbox_quotas
+            .get(actor_type_id)
+            .cloned();
+        let track_age = local_environment.options.mailbox_alerts.is_some();
+        let coalescing = local_environment
+            .options
+            .coalescing_mailboxes
+            .contains(actor_type_id);
+        #[allow(unused_mut)]
+        let (mut mailbox_sender, mut mailbox) = mailbox(quota, track_age, coalescing);
+        if options.serialized_mailbox {
+            mailbox_sender.serialize_messages();
+        }
+        #[cfg(feature = "async-runtime")]
+        {
+            if local_environment.executor.is_some() {
+                mailbox.notified_by(&mut mailbox_sender);
+            }
+        }
+        let scheduled = local_environment.scheduler.as_ref().map(|scheduler| {
+            let scheduled = scheduler.prepare();
+            mailbox_sender.woken_by(scheduler.waker(&scheduled));
+            scheduled
+        });
+
+        // create new ActorRef pointing to the new actor instance
+        let actor_ref = ActorRef::new(
+            actor_id.clone(),
+            ActorRefChannel::Local(mailbox_sender.clone()),
+            Arc::downgrade(&local_environment.timer),
+            Arc::downgrade(&local_environment.replies),
+            Arc::downgrade(&local_environment.death_watches),
+            Arc::downgrade(&local_environment.message_limits),
+            local_environment.options.wire_format,
+        );
+
+        // register channel in this environment
+        match local_environment.local_actor_channels.lock() {
+            Ok(mut channels) => {
+                // a duplicated spawn request must not start a second Actor with the same id
+                if let Some((existing_id, existing)) = channels.get_key_value(&actor_id) {
+                    if existing.type_id != actor_type_id {
+                        return Err(ActlibError::InvalidId(format!(
+                            "{:?} is taken by an Actor of type {}",
+                            actor_id, existing.type_id
+                        )));
+                    }
+                    return Ok(ActorRef::new(
+                        existing_id.clone(),
+                        ActorRefChannel::Local(existing.sender.clone()),
+                        Arc::downgrade(&local_environment.timer),
+                        Arc::downgrade(&local_environment.replies),
+                        Arc::downgrade(&local_environment.death_watches),
+                        Arc::downgrade(&local_environment.message_limits),
+                        local_environment.options.wire_format,
+                    ));
+                }
+                // tracked before it is registered, so no watcher takes it for stopped
+                local_environment.death_watches.track(actor_id.clone());
+                channels.insert(
+                    actor_id.clone(),
+                    LocalActor {
+                        sender: mailbox_sender,
+                        type_id: actor_type_id.to_string(),
+                        spawner: spawner.clone(),
+                        spawned_at: SystemTime::now(),
+                        capabilities: options.capabilities.clone(),
+                    },
+                );
+            }
+            Err(e) => {
+                return Err(ActlibError::SpawnFailed(format!(
+                    "Failed to insert Actor to Environment: {:?}",
+                    ActlibError::from_poison_error(&e)
+                )));
+            }
+        }
+
+        if local_environment.options.trace_lineage {
+            match local_environment.lineage.lock() {
+                Ok(mut lineage) => {
+                    lineage.insert(
+                        actor_id.clone(),
+                        LineageRecord {
+                            actor: actor_id.clone(),
+                            type_id: actor_type_id.to_string(),
+                            spawner,
+                            alive: true,
+                        },
+                    );
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            }
+        }
+        Instruments::count(&local_environment.instruments.actors_spawned);
+        local_environment
+            .notify_lifecycle(actor_type_id, LifecycleEvent::Spawned(actor_id.clone()));
+
+        // start the mailbox loop, on the worker pool, on its own thread or as a task
+        // it will loop over received messages, breaking on error
+        let actor_ref_clone = actor_ref.clone();
+
+        let env_clone = env.clone();
+
+        let type_id = actor_type_id.to_string();
+
+        if options.hold_start {
+            // messages queue up in the mailbox until the spawner's handler returned
+            let start = move || {
+                let local_environment = env_clone.env.clone();
+                local_environment.run_actor(
+                    scheduled,
+                    mailbox,
+                    new_actor,
+                    env_clone,
+                    actor_ref_clone,
+                    type_id,
+                )
+            };
+            match local_environment.held_starts.lock() {
+                Ok(mut held) => {
+                    held.insert(actor_id, HeldStart(Box::new(start)));
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            }
+        } else {
+            local_environment.run_actor(
+                scheduled,
+                mailbox,
+                new_actor,
+                env_clone,
+                actor_ref_clone,
+                type_id,
+            );
+        }
+
+        Ok(actor_ref)
+    }
+
+    /// Describe the Actors living on this machine.
+    pub(crate) fn introspect(&self) -> Introspection {
+        let actors = match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .iter()
+                .map(|(actor_id, local_actor)| ActorDescription {
+                    id: actor_id.clone(),
+                    type_id: local_actor.type_id.clone(),
+                    spawner: local_actor.spawner.clone(),
+                    queued_messages: local_actor.sender.queued_messages(),
+                    queued_bytes: local_actor.sender.queued_bytes(),
+                    oldest_message_age: local_actor.sender.oldest_message_age(),
+                })
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        let lineage = match self.lineage.lock() {
+            Ok(lineage) => lineage.values().cloned().collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        let peers = match self.traffic.read() {
+            Ok(traffic) => traffic
+                .iter()
+                .map(|(peer, sent, received)| PeerStats {
+                    peer: *peer,
+                    frames_sent: sent.frames(),
+                    bytes_sent: sent.bytes(),
+                    frames_received: received.frames(),
+                    bytes_received: received.bytes(),
+                })
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        Introspection {
+            machine: self.local_machine,
+            machine_id: self.machine_id,
+            taken_at: SystemTime::now(),
+            actors,
+            lineage,
+            peers,
+            handlers: self.handler_stats(),
+            replies: self.reply_stats(),
+        }
+    }
+
+    /// Add a handler run of the given Actor type and message type to the statistics.
+    fn record_handler_time(&self, type_id: &str, message_type: &'static str, time: Duration) {
+        match self.handler_stats.lock() {
+            Ok(mut stats) => stats
+                .entry((type_id.to_string(), message_type))
+                .or_insert_with(|| HandlerStats::new(type_id, message_type))
+                .record(time),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Hand a reply to its ResponseFuture, adding it to the reply statistics if it was traced.
+    fn fulfil_reply(&self, correlation_id: u64, reply: Vec<u8>, responder_type: Option<String>) {
+        self.replies.fulfil(correlation_id, reply, |asker, time| {
+            self.record_reply(asker, responder_type, time)
+        });
+    }
+
+    /// Add a traced reply to the statistics of its requester type and responder type.
+    fn record_reply(&self, asker: Option<ActorId>, responder_type: Option<String>, time: Duration) {
+        let requester_type = match asker {
+            Some(asker) => self
+                .actor_type(&asker)
+                .unwrap_or_else(|| "<unknown>".to_string()),
+            None => "<outside>".to_string(),
+        };
+        let responder_type = responder_type.unwrap_or_else(|| "<unknown>".to_string());
+        match self.reply_stats.lock() {
+            Ok(mut stats) => stats
+                .entry((requester_type.clone(), responder_type.clone()))
+                .or_insert_with(|| ReplyStats::new(&requester_type, &responder_type))
+                .record(time),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// The type id of the local Actor, ```None``` if it is not alive.
+    fn actor_type(&self, actor_id: &ActorId) -> Option<String> {
+        match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .get(actor_id)
+                .map(|local_actor| local_actor.type_id.clone()),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    /// The reply statistics of all pairs of requester type and responder type, the most time first.
+    pub(crate) fn reply_stats(&self) -> Vec<ReplyStats> {
+        let mut replies: Vec<ReplyStats> = match self.reply_stats.lock() {
+            Ok(stats) => stats.values().cloned().collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        replies.sort_by_key(|reply| std::cmp::Reverse(reply.total_time));
+        replies
+    }
+
+    /// The handler statistics of all Actor types and message types, sorted by type id and message type.
+    pub(crate) fn handler_stats(&self) -> Vec<HandlerStats> {
+        let mut handlers: Vec<HandlerStats> = match self.handler_stats.lock() {
+            Ok(stats) => stats.values().cloned().collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        handlers.sort_by(|a, b| (&a.type_id, &a.message_type).cmp(&(&b.type_id, &b.message_type)));
+        handlers
+    }
+
+    /// Write the introspection data to the file *sequence_no* of the rolling set, removing the file that fell out of it.
+    fn dump_state(env: &ArcEnvironment, state_dump: &StateDump, sequence_no: usize) {
+        let introspection = env.introspect();
+        let file_name = |sequence_no: usize| {
+            state_dump.directory.join(format!(
+                "{}-{:08}.json.gz",
+                introspection.machine_id, sequence_no
+            ))
+        };
+        if let Err(e) = write_state_dump(&file_name(sequence_no), &introspection) {
+            warn!("Failed to write state dump: {:?}", e);
+        }
+        if sequence_no >= state_dump.keep {
+            // the file may have never been written
+            let _ = std::fs::remove_file(file_name(sequence_no - state_dump.keep));
+        }
+    }
+
+    /// Count the live Actors, mailbox backlogs, handled and remote messages, spawns and removals of the local machine.
+    pub(crate) fn metrics(&self) -> Metrics {
+        let mut live_actors = HashMap::new();
+        let mailboxes = match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .iter()
+                .map(|(actor_id, local_actor)| {
+                    *live_actors.entry(local_actor.type_id.clone()).or_insert(0) += 1;
+                    MailboxBacklog {
+                        actor: actor_id.clone(),
+                        type_id: local_actor.type_id.clone(),
+                        queued_messages: local_actor.sender.queued_messages(),
+                        queued_bytes: local_actor.sender.queued_bytes(),
+                    }
+                })
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        let values = self.instruments.values();
+        Metrics {
+            machine_id: self.machine_id,
+            taken_at: SystemTime::now(),
+            live_actors,
+            mailboxes,
+            messages_handled: values[0],
+            remote_messages_sent: values[1],
+            remote_messages_received: values[2],
+            actors_spawned: values[3],
+            actors_removed: values[4],
+            messages_dropped: self.instruments.messages_dropped(),
+            rates: self.instruments.rates(&values),
+            lock_waits: vec![
+                self.local_actor_channels.waits("local_actor_channels"),
+                self.net_senders.waits("net_senders"),
+                self.remote_queries.waits("remote_queries"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        }
+    }
+
+    /// Answer every HTTP request on *listener* with the metrics in the Prometheus text format, until the Environment is gone.
+    fn export_metrics(env: Weak<LocalEnvironment>, listener: &TcpListener) {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Failed to accept a metrics request: {:?}", e);
+                    continue;
+                }
+            };
+            let metrics = match env.upgrade() {
+                Some(env) => env.metrics(),
+                None => break,
+            };
+            // the request itself does not matter, every path serves the metrics
+            let mut request = [0; 1024];
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(1)));
+            let _ = stream.read(&mut request);
+            let body = metrics.to_prometheus();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()) {
+                debug!("Failed to serve the metrics: {:?}", e);
+            }
+        }
+    }
+
+    /// Register a new watcher for resolved id conflicts.
+    pub(crate) fn watch_id_conflicts(&self) -> Receiver<IdConflict> {
+        self.id_conflict_watchers.watch()
+    }
+
+    /// Register a new watcher for mailbox alerts.
+    pub(crate) fn watch_mailbox_alerts(&self) -> Receiver<MailboxAlert> {
+        self.mailbox_alert_watchers.watch()
+    }
+
+    /// Check the mailboxes of all local Actors against the thresholds, alerting about those not in *alerted* yet.
+    fn sample_mailboxes(
+        env: &ArcEnvironment,
+        alerts: &MailboxAlerts,
+        alerted: &mut HashSet<ActorId>,
+    ) {
+        let mut exceeding = Vec::new();
+        match env.local_actor_channels.lock() {
+            Ok(channels) => {
+                for (actor_id, local_actor) in &*channels {
+                    let queued_messages = local_actor.sender.queued_messages();
+                    let oldest_message_age = local_actor.sender.oldest_message_age();
+                    let too_deep = alerts
+                        .max_depth
+                        .is_some_and(|max_depth| queued_messages > max_depth);
+                    let too_old = match (alerts.max_age, oldest_message_age) {
+                        (Some(max_age), Some(age)) => age > max_age,
+                        _ => false,
+                    };
+                    if too_deep || too_old {
+                        exceeding.push(MailboxAlert {
+                            actor: actor_id.clone(),
+                            type_id: local_actor.type_id.clone(),
+                            queued_messages,
+                            oldest_message_age,
+                        });
+                    }
+                }
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return;
+            }
+        }
+        let still_alerted: HashSet<ActorId> =
+            exceeding.iter().map(|alert| alert.actor.clone()).collect();
+        for alert in exceeding {
+            if alerted.contains(&alert.actor) {
+                continue;
+            }
+            warn!(
+                "Slow consumer: {} Actor {:?} has {} queued messages, the oldest waiting for {:?}.",
+                alert.type_id, alert.actor, alert.queued_messages, alert.oldest_message_age
+            );
+            env.mailbox_alert_watchers.notify(&alert);
+        }
+        *alerted = still_alerted;
+    }
+
+    /// Measure the load of the local machine.
+    fn measure_load(&self) -> MachineLoad {
+        let (actors, queued_messages, queued_bytes) = match self.local_actor_channels.lock() {
+            Ok(channels) => channels.values().fold(
+                (0, 0, 0),
+                |(actors, queued_messages, queued_bytes), local_actor| {
+                    (
+                        actors + 1,
+                        queued_messages + local_actor.sender.queued_messages(),
+                        queued_bytes + local_actor.sender.queued_bytes(),
+                    )
+                },
+            ),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                (0, 0, 0)
+            }
+        };
+        MachineLoad {
+            machine: self.machine_id,
+            cpu_load: cpu_load(),
+            actors,
+            queued_messages,
+            queued_bytes,
+            measured_at: SystemTime::now(),
+        }
+    }
+
+    /// Remember the latest load of a machine.
+    fn record_load(&self, load: MachineLoad) {
+        match self.machine_loads.lock() {
+            Ok(mut loads) => {
+                loads.insert(load.machine, (Instant::now(), load));
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// The latest load of every machine, the local machine first.
+    pub(crate) fn cluster_load(&self) -> ClusterLoad {
+        let mut machines: Vec<MachineLoad> = match self.machine_loads.lock() {
+            Ok(loads) => loads.values().map(|(_, load)| load.clone()).collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        let local_machine_id = self.machine_id;
+        machines.sort_by_key(|load| (load.machine != local_machine_id, load.machine));
+        ClusterLoad { machines }
+    }
+
+    /// Register a new subscriber for the cluster load.
+    pub(crate) fn subscribe_cluster_load(&self, subscriber: ActorRef) {
+        match self.load_subscribers.lock() {
+            Ok(mut subscribers) => subscribers.push(subscriber),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Remove every subscription of the given Actor.
+    pub(crate) fn unsubscribe_cluster_load(&self, subscriber: &ActorId) {
+        match self.load_subscribers.lock() {
+            Ok(mut subscribers) => {
+                subscribers.retain(|actor_ref| &actor_ref.actor_id != subscriber)
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Subscribe an Actor to the lifecycle of the Actors of the given type on every machine.
+    pub(crate) fn subscribe_lifecycle(&self, type_id: &str, subscriber: ActorRef) {
+        self.announce_lifecycle_subscription(NetMessage::SubscribeLifecycle(
+            type_id.to_string(),
+            subscriber.actor_id.clone(),
+        ));
+        self.add_lifecycle_subscriber(type_id.to_string(), subscriber);
+    }
+
+    /// End the lifecycle subscription of the given Actor on every machine.
+    pub(crate) fn unsubscribe_lifecycle(&self, type_id: &str, subscriber: &ActorId) {
+        self.announce_lifecycle_subscription(NetMessage::UnsubscribeLifecycle(
+            type_id.to_string(),
+            subscriber.clone(),
+        ));
+        self.remove_lifecycle_subscriber(type_id, subscriber);
+    }
+
+    /// Listen on *port* for connections from outside of the Environment and pass them to the local *handler*, returning the bound port.
+    ///
+    /// If the port is listened on already, only the handler is replaced and the socket stays bound.
+    pub(crate) fn register_external_listener(
+        env: &ArcEnvironment,
+        port: u16,
+        handler: ActorId,
+    ) -> Result<u16, ActlibError> {
+        if handler.location != env.machine_id {
+            return Err(ActlibError::InvalidActorRef(format!(
+                "{:?} lives on {}, hand the listener over to it instead",
+                handler, handler.location
+            )));
+        }
+        match env.local_actor_channels.lock() {
+            Ok(channels) if channels.contains_key(&handler) => {}
+            Ok(_) => {
+                return Err(ActlibError::ActorNotFound(format!(
+                    "Did not find local actor with id {:?}",
+                    handler
+                )))
+            }
+            Err(e) => return Err(ActlibError::from_poison_error(&e)),
+        }
+        match env.external_listeners.lock() {
+            Ok(mut listeners) => {
+                if let Some(listener) = listeners.get_mut(&port) {
+                    listener.handler = handler;
+                    return Ok(port);
+                }
+                let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))
+                    .and_then(|listener| {
+                        listener.set_nonblocking(true)?;
+                        Ok(listener)
+                    })
+                    .map_err(|e| {
+                        ActlibError::NetworkError(format!(
+                            "Cannot listen on port {}: {:?}",
+                            port, e
+                        ))
+                    })?;
+                let port = listener
+                    .local_addr()
+                    .map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))?
+                    .port();
+                let generation = NEXT_LISTENER_GENERATION.fetch_add(1, Ordering::Relaxed);
+                listeners.insert(
+                    port,
+                    ExternalListener {
+                        handler,
+                        generation,
+                    },
+                );
+                let env_listener = Arc::downgrade(env);
+                std::thread::spawn(move || {
+                    LocalEnvironment::accept_external_connections(
+                        env_listener,
+                        listener,
+                        port,
+                        generation,
+                    );
+                });
+                Ok(port)
+            }
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Stop listening on *port*, returning whether it was listened on.
+    pub(crate) fn unregister_external_listener(&self, port: u16) -> bool {
+        match self.external_listeners.lock() {
+            Ok(mut listeners) => listeners.remove(&port).is_some(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                false
+            }
+        }
+    }
+
+    /// Pass the external listener on *port* to the *handler*, which may live on another machine.
+    ///
+    /// The local socket keeps serving the current handler until the machine of the new one listens on the port.
+    pub(crate) fn hand_over_external_listener(
+        env: &ArcEnvironment,
+        port: u16,
+        handler: ActorId,
+    ) -> Result<(), ActlibError> {
+        let generation = match env.external_listeners.lock() {
+            Ok(listeners) => match listeners.get(&port) {
+                Some(listener) => listener.generation,
+                None => {
+                    return Err(ActlibError::InvalidState(format!(
+                        "No external listener on port {}",
+                        port
+                    )))
+                }
+            },
+            Err(e) => return Err(ActlibError::from_poison_error(&e)),
+        };
+        if handler.location == env.machine_id {
+            return LocalEnvironment::register_external_listener(env, port, handler).map(|_| ());
+        }
+        let machine = handler.location;
+        let handover_no = NEXT_HANDOVER_NO.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = channel();
+        match env.pending_handovers.lock() {
+            Ok(mut handovers) => {
+                handovers.insert(handover_no, sender);
+            }
+            Err(e) => return Err(ActlibError::from_poison_error(&e)),
+        }
+        let sent = env
+            .options
+            .wire_format
+            .serialize(&NetMessage::HandOverListener(
+                port,
+                handler,
+                env.machine_id,
+                handover_no,
+            ))
+            .map_err(|e| ActlibError::NetworkError(format!("{:?}", e)))
+            .and_then(|bin| match env.net_senders.lock() {
+                Ok(mut senders) => env
+                    .send_to_machine(&mut senders, machine, &bin)
+                    .map(|_| ())
+                    .map_err(|e| {
+                        ActlibError::NetworkError(format!(
+                            "Failed to hand port {} over to {}: {:?}",
+                            port, machine, e
+                        ))
+                    }),
+                Err(e) => Err(ActlibError::from_poison_error(&e)),
+            });
+        let outcome = sent.and_then(|_| {
+            match scheduler::blocking(|| receiver.recv_timeout(HANDOVER_TIMEOUT)) {
+                Ok(None) => Ok(()),
+                Ok(Some(failure)) => Err(ActlibError::NetworkError(format!(
+                    "{} could not take over port {}: {}",
+                    machine, port, failure
+                ))),
+                Err(_) => Err(ActlibError::Timeout(format!(
+                    "{} did not take over port {} within {:?}",
+                    machine, port, HANDOVER_TIMEOUT
+                ))),
+            }
+        });
+        if let Ok(mut handovers) = env.pending_handovers.lock() {
+            handovers.remove(&handover_no);
+        }
+        if outcome.is_ok() {
+            env.close_external_listener(port, generation);
+        }
+        outcome
+    }
+
+    /// Stop listening on *port*, unless the listener was replaced since it got the *generation*.
+    fn close_external_listener(&self, port: u16, generation: u64) {
+        match self.external_listeners.lock() {
+            Ok(mut listeners) => {
+                if listeners.get(&port).map(|listener| listener.generation) == Some(generation) {
+                    listeners.remove(&port);
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Accept the connections on the *listener* and send them to its current handler, until it is unregistered or the Environment is gone.
+    ///
+    /// While a handler with a user specified id is missing, e.g. being respawned, the connections wait in the backlog of the socket.
+    fn accept_external_connections(
+        env: Weak<LocalEnvironment>,
+        listener: TcpListener,
+        port: u16,
+        generation: u64,
+    ) {
+        loop {
+            let env = match env.upgrade() {
+                Some(env) => env,
+                None => break,
+            };
+            let handler = match env.external_listeners.lock() {
+                Ok(listeners) => match listeners.get(&port) {
+                    Some(listener) if listener.generation == generation => listener.handler.clone(),
+                    _ => break,
+                },
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    break;
+                }
+            };
+            let mailbox = match env.local_actor_channels.lock() {
+                Ok(channels) => channels
+                    .get(&handler)
+                    .map(|local_actor| local_actor.sender.clone()),
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    break;
+                }
+            };
+            let mailbox = match mailbox {
+                Some(mailbox) => mailbox,
+                None if matches!(handler.local_id, LocalId::Automatic(_)) => {
+                    // an automatically created id never returns
+                    warn!(
+                        "Closing the external listener on port {}, its handler {:?} stopped",
+                        port, handler
+                    );
+                    env.close_external_listener(port, generation);
+                    break;
+                }
+                None => {
+                    drop(env);
+                    std::thread::sleep(EXTERNAL_LISTENER_POLL);
+                    continue;
+                }
+            };
+            match listener.accept() {
+                Ok((stream, peer)) => {
+                    let handler_ref = ActorRef::new(
+                        handler,
+                        ActorRefChannel::Local(mailbox),
+                        Arc::downgrade(&env.timer),
+                        Arc::downgrade(&env.replies),
+                        Arc::downgrade(&env.death_watches),
+                        Arc::downgrade(&env.message_limits),
+                        env.options.wire_format,
+                    );
+                    pass_external_connection(&handler_ref, port, stream, peer);
+                }
+                Err(e) => {
+                    if e.kind() != std::io::ErrorKind::WouldBlock {
+                        warn!("Failed to accept a connection on port {}: {:?}", port, e);
+                    }
+                    drop(env);
+                    std::thread::sleep(EXTERNAL_LISTENER_POLL);
+                }
+            }
+        }
+    }
+
+    /// Register *name* for the given Actor on every machine.
+    ///
+    /// Fails if the name already refers to another Actor.
+    pub(crate) fn register_name(&self, name: &str, actor_id: ActorId) -> Result<(), ActlibError> {
+        match self.names.lock() {
+            Ok(mut names) => match names.get(name) {
+                Some(registered) if *registered != actor_id => {
+                    return Err(ActlibError::InvalidState(format!(
+                        "The name {} is registered for {:?} already",
+                        name, registered
+                    )))
+                }
+                Some(_) => return Ok(()),
+                None => {
+                    names.insert(name.to_string(), actor_id.clone());
+                }
+            },
+            Err(e) => return Err(ActlibError::from_poison_error(&e)),
+        }
+        self.announce_names(NetMessage::Names(vec![(name.to_string(), actor_id)]));
+        Ok(())
+    }
+
+    /// Remove *name* on every machine, returning the Actor it referred to.
+    pub(crate) fn unregister_name(&self, name: &str) -> Option<ActorId> {
+        let removed = match self.names.lock() {
+            Ok(mut names) => names.remove(name),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        if let Some(actor_id) = &removed {
+            self.announce_names(NetMessage::UnregisterName(
+                name.to_string(),
+                actor_id.clone(),
+            ));
+        }
+        removed
+    }
+
+    /// Remove the names of a stopped local Actor on every machine.
+    fn unregister_names_of(&self, actor_id: &ActorId) {
+        let removed: Vec<String> = match self.names.lock() {
+            Ok(mut names) => {
+                let removed = names
+                    .iter()
+                    .filter(|(_, registered)| *registered == actor_id)
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                names.retain(|_, registered| registered != actor_id);
+                removed
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        for name in removed {
+            self.announce_names(NetMessage::UnregisterName(name, actor_id.clone()));
+        }
+    }
+
+    /// The Actor registered under *name*, as far as the local machine knows.
+    pub(crate) fn lookup_name(&self, name: &str) -> Option<ActorId> {
+        match self.names.lock() {
+            Ok(names) => names.get(name).cloned(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    /// Take over a name registered on a remote machine.
+    ///
+    /// If two machines registered the name for different Actors at the same time, every machine keeps the lower ActorId.
+    fn adopt_name(&self, name: String, actor_id: ActorId) {
+        match self.names.lock() {
+            Ok(mut names) => match names.get_mut(&name) {
+                Some(registered) if *registered == actor_id => {}
+                Some(registered) => {
+                    warn!(
+                        "The name {} was registered for {:?} and {:?}, keeping the lower id",
+                        name, registered, actor_id
+                    );
+                    if actor_id < *registered {
+                        *registered = actor_id;
+                    }
+                }
+                None => {
+                    names.insert(name, actor_id);
+                }
+            },
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Forget a name unregistered on a remote machine, unless it refers to another Actor by now.
+    fn remove_name(&self, name: &str, actor_id: &ActorId) {
+        match self.names.lock() {
+            Ok(mut names) => {
+                if names.get(name) == Some(actor_id) {
+                    names.remove(name);
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// All known names, serialized for a machine that connected, ```None``` if there are none.
+    fn names_message(&self) -> Option<Vec<u8>> {
+        let names: Vec<(String, ActorId)> = match self.names.lock() {
+            Ok(names) => names
+                .iter()
+                .map(|(name, actor_id)| (name.clone(), actor_id.clone()))
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return None;
+            }
+        };
+        if names.is_empty() {
+            return None;
+        }
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::Names(names))
+        {
+            Ok(bin) => Some(bin),
+            Err(e) => {
+                warn!("Failed to serialize the names: {:?}", e);
+                None
+            }
+        }
+    }
+
+    fn announce_names(&self, net_msg: NetMessage) {
+        match self.options.wire_format.serialize(&net_msg) {
+            Ok(bin_msg) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    for (remote, result) in self.send_to_all_machines(&mut senders, &bin_msg) {
+                        if let Err(e) = result {
+                            warn!("Could not announce a name to {:?}: {:?}", remote, e);
+                        }
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize a name: {:?}", e),
+        }
+    }
+
+    /// The machine running the bootstrap: the lowest id among this machine and its remote machines.
+    pub(crate) fn bootstrap_machine(&self) -> MachineId {
+        self.peers()
+            .iter()
+            .map(|(machine_id, _)| *machine_id)
+            .fold(self.machine_id, std::cmp::min)
+    }
+
+    /// Release the remote machines waiting for the bootstrap.
+    ///
+    /// The spawn requests of the bootstrap were written to the same connections before, so they are handled first.
+    pub(crate) fn announce_bootstrapped(&self) {
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::Bootstrapped)
+        {
+            Ok(bin_msg) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    for (remote, result) in self.send_to_all_machines(&mut senders, &bin_msg) {
+                        if let Err(e) = result {
+                            warn!("Could not announce the bootstrap to {:?}: {:?}", remote, e);
+                        }
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize Bootstrapped: {:?}", e),
+        }
+        self.mark_bootstrapped();
+    }
+
+    fn mark_bootstrapped(&self) {
+        match self.bootstrapped.lock() {
+            Ok(mut bootstrapped) => {
+                *bootstrapped = true;
+                self.bootstrap_done.notify_all();
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Block until the bootstrap machine announced the end of the bootstrap.
+    pub(crate) fn wait_for_bootstrap(&self) {
+        match self.bootstrapped.lock() {
+            Ok(mut bootstrapped) => {
+                while !*bootstrapped {
+                    bootstrapped = match self.bootstrap_done.wait(bootstrapped) {
+                        Ok(bootstrapped) => bootstrapped,
+                        Err(e) => {
+                            log_err_as!(error, ActlibError::from_poison_error(&e));
+                            return;
+                        }
+                    };
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    fn announce_lifecycle_subscription(&self, net_msg: NetMessage) {
+        match self.options.wire_format.serialize(&net_msg) {
+            Ok(bin_msg) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    for (remote, result) in self.send_to_all_machines(&mut senders, &bin_msg) {
+                        if let Err(e) = result {
+                            warn!(
+                                "Could not announce lifecycle subscription to {:?}: {:?}",
+                                remote, e
+                            );
+                        }
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize lifecycle subscription: {:?}", e),
+        }
+    }
+
+    fn add_lifecycle_subscriber(&self, type_id: String, subscriber: ActorRef) {
+        match self.lifecycle_subscribers.lock() {
+            Ok(mut subscribers) => subscribers.entry(type_id).or_default().push(subscriber),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    fn remove_lifecycle_subscriber(&self, type_id: &str, subscriber: &ActorId) {
+        match self.lifecycle_subscribers.lock() {
+            Ok(mut subscribers) => {
+                if let Some(type_subscribers) = subscribers.get_mut(type_id) {
+                    type_subscribers.retain(|actor_ref| &actor_ref.actor_id != subscriber);
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Send the event to the subscribers of the type, dropping the subscribers that are gone.
+    fn notify_lifecycle(&self, type_id: &str, event: LifecycleEvent) {
+        match self.lifecycle_subscribers.lock() {
+            Ok(mut subscribers) => {
+                if let Some(type_subscribers) = subscribers.get_mut(type_id) {
+                    type_subscribers
+                        .retain(|subscriber| subscriber.send_message(event.clone()).is_ok());
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Measure the local load, send it to all machines and notify the subscribers.
+    fn exchange_load(env: &ArcEnvironment, exchange: &LoadExchange) {
+        let load = env.measure_load();
+        match env
+            .options
+            .wire_format
+            .serialize(&NetMessage::Load(load.clone()))
+        {
+            Ok(bin_msg) => match env.net_senders.lock() {
+                Ok(mut senders) => {
+                    // if this fails the connection broke down, the next round tries again
+                    let _ = env.send_to_all_machines(&mut senders, &bin_msg);
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize the machine load: {:?}", e),
+        }
+        env.record_load(load);
+        match env.machine_loads.lock() {
+            Ok(mut loads) => {
+                // machines that stopped reporting are gone or unreachable
+                loads.retain(|_, (received, _)| received.elapsed() < exchange.interval * 3)
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        let cluster_load = env.cluster_load();
+        match env.load_subscribers.lock() {
+            Ok(mut subscribers) => subscribers
+                .retain(|subscriber| subscriber.send_message(cluster_load.clone()).is_ok()),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        };
+    }
+
+    /// Send the Tracked messages that were not acknowledged in time again.
+    ///
+    /// Messages still unacknowledged after the last retry are reported as failed.
+    fn retry_unacknowledged(env: &ArcEnvironment, delivery: &ReliableDelivery) {
+        let keep_while_down = env.durable_outbox.is_some();
+        let down: HashSet<MachineId> = if keep_while_down {
+            match env.dead_links.lock() {
+                Ok(dead_links) => dead_links.keys().cloned().collect(),
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    HashSet::new()
+                }
+            }
+        } else {
+            HashSet::new()
+        };
+        let mut retries = Vec::new();
+        let mut failures = Vec::new();
+        match env.unacknowledged.lock() {
+            Ok(mut unacknowledged) => {
+                if keep_while_down {
+                    failures.extend(env.beyond_outbox_retention(&mut unacknowledged));
+                }
+                let due: Vec<u64> = unacknowledged
+                    .iter()
+                    .filter(|(_, message)| message.sent_at.elapsed() >= delivery.ack_timeout)
+                    .map(|(delivery_no, _)| *delivery_no)
+                    .collect();
+                for delivery_no in due {
+                    if down.contains(&unacknowledged[&delivery_no].destination) {
+                        // waits for the machine to reconnect without using up its retries
+                        if let Some(message) = unacknowledged.get_mut(&delivery_no) {
+                            message.sent_at = Instant::now();
+                        }
+                    } else if unacknowledged[&delivery_no].attempts > delivery.max_retries {
+                        failures.extend(
+                            unacknowledged
+                                .remove(&delivery_no)
+                                .map(|failure| (failure, DropCause::PeerDead)),
+                        );
+                    } else if let Some(message) = unacknowledged.get_mut(&delivery_no) {
+                        message.attempts += 1;
+                        message.sent_at = Instant::now();
+                        retries.push((message.destination, message.frame.clone()));
+                    }
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        if !retries.is_empty() {
+            match env.net_senders.lock() {
+                Ok(mut senders) => {
+                    for (destination, frame) in retries {
+                        // a failed retry counts as an attempt all the same
+                        let _ = env.send_to_machine(&mut senders, destination, &frame);
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            }
+        }
+        for (failure, cause) in failures {
+            env.record_settled(failure.delivery_no);
+            env.report_dropped(cause, Some(failure.actor.clone()), None);
+            env.report_delivery_failure(failure, None);
+        }
+        env.compact_durable_outbox();
+    }
+
+    /// Remove the unacknowledged messages beyond the retention of the durable outbox, the oldest first.
+    ///
+    /// Messages older than the retention expired, the others exceed the size kept for their unreachable destination.
+    fn beyond_outbox_retention(
+        &self,
+        unacknowledged: &mut HashMap<u64, Unacknowledged>,
+    ) -> Vec<(Unacknowledged, DropCause)> {
+        let retention = &self.options.outbox_retention;
+        let mut newest_first: Vec<(u64, &Unacknowledged)> = unacknowledged
+            .iter()
+            .map(|(delivery_no, message)| (*delivery_no, message))
+            .collect();
+        newest_first.sort_by_key(|(delivery_no, _)| std::cmp::Reverse(*delivery_no));
+        let mut kept_bytes: HashMap<MachineId, usize> = HashMap::new();
+        let mut beyond = Vec::new();
+        for (delivery_no, message) in newest_first {
+            let bytes = kept_bytes.entry(message.destination).or_insert(0);
+            *bytes += message.frame.len();
+            let too_old = message
+                .first_sent
+                .elapsed()
+                .is_ok_and(|age| age > retention.max_age);
+            if too_old {
+                beyond.push((delivery_no, DropCause::TtlExpired));
+            } else if *bytes > retention.max_bytes {
+                beyond.push((delivery_no, DropCause::PeerDead));
+            }
+        }
+        beyond.sort_unstable_by_key(|(delivery_no, _)| *delivery_no);
+        beyond
+            .into_iter()
+            .filter_map(|(delivery_no, cause)| {
+                unacknowledged
+                    .remove(&delivery_no)
+                    .map(|message| (message, cause))
+            })
+            .collect()
+    }
+
+    /// Rewrite the durable outbox with the unacknowledged messages only, once it holds many settled ones.
+    fn compact_durable_outbox(&self) {
+        let outbox = match &self.durable_outbox {
+            Some(outbox) => outbox,
+            None => return,
+        };
+        match self.unacknowledged.lock() {
+            Ok(unacknowledged) => match outbox.lock() {
+                Ok(mut outbox) => {
+                    if outbox.records() < OUTBOX_COMPACTION_RECORDS
+                        || outbox.records() < 2 * unacknowledged.len()
+                    {
+                        return;
+                    }
+                    let mut unsettled: Vec<(&u64, &Unacknowledged)> =
+                        unacknowledged.iter().collect();
+                    // sent again in this order after a restart
+                    unsettled.sort_by_key(|(delivery_no, _)| **delivery_no);
+                    if let Err(e) =
+                        outbox.rewrite(unsettled.into_iter().map(|(delivery_no, message)| {
+                            (
+                                *delivery_no,
+                                message.destination,
+                                &message.frame[..],
+                                message.first_sent,
+                            )
+                        }))
+                    {
+                        warn!("Failed to compact the durable outbox: {:?}", e);
+                    }
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Send the local shares of the Counters and Gauges to every remote machine.
+    ///
+    /// The whole shares are sent every time, so a machine that connected later or missed a round catches up.
+    fn exchange_counters(env: &ArcEnvironment) {
+        let shares = match env.counters.local_shares() {
+            Some(shares) => shares,
+            None => return,
+        };
+        match env
+            .options
+            .wire_format
+            .serialize(&NetMessage::Counters(env.machine_id, shares))
+        {
+            Ok(bin_msg) => match env.net_senders.lock() {
+                Ok(mut senders) => {
+                    // if this fails the connection broke down, the next round tries again
+                    let _ = env.send_to_all_machines(&mut senders, &bin_msg);
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize the counter shares: {:?}", e),
+        }
+    }
+
+    /// The specified ids of all local Actors, with the time they were spawned.
+    fn specified_ids(&self) -> Vec<(Vec<u8>, SystemTime)> {
+        match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .iter()
+                .filter_map(|(actor_id, local_actor)| match &actor_id.local_id {
+                    LocalId::Specified(id) => Some((id.clone(), local_actor.spawned_at)),
+                    LocalId::Automatic(_) => None,
+                })
+                .collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+
+    /// Send the specified ids of all local Actors to every remote machine.
+    fn exchange_specified_ids(env: &ArcEnvironment) {
+        let message = NetMessage::SpecifiedIds(env.machine_id, env.specified_ids());
+        match env.options.wire_format.serialize(&message) {
+            Ok(bin_msg) => match env.net_senders.lock() {
+                Ok(mut senders) => {
+                    // if this fails the connection broke down, the next round tries again
+                    let _ = env.send_to_all_machines(&mut senders, &bin_msg);
+                }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize specified ids: {:?}", e),
+        }
+    }
+
+    /// Resolve the conflicts between the specified ids held by a remote machine and the local Actors.
+    ///
+    /// Both machines see the same conflict, so every machine only takes care of its own Actor.
+    fn resolve_id_conflicts(
+        env: &ArcEnvironment,
+        remote: MachineId,
+        remote_ids: Vec<(Vec<u8>, SystemTime)>,
+    ) {
+        let policy = match &env.options.id_conflict_detection {
+            Some(detection) => detection.policy.clone(),
+            None => return,
+        };
+        let local_machine_id = env.machine_id;
+        let mut conflicts = Vec::new();
+        match env.local_actor_channels.lock() {
+            Ok(channels) => {
+                for (id, remote_spawned_at) in remote_ids {
+                    let local_actor_id = ActorId {
+                        local_id: LocalId::Specified(id.clone()),
+                        location: local_machine_id,
+                        incarnation: 0,
+                    };
+                    if let Some(local_actor) = channels.get(&local_actor_id) {
+                        let remote_actor_id = ActorId {
+                            local_id: LocalId::Specified(id.clone()),
+                            location: remote,
+                            incarnation: 0,
+                        };
+                        let local_is_older = (local_actor.spawned_at, local_machine_id)
+                            < (remote_spawned_at, remote);
+                        let conflict = if local_is_older {
+                            IdConflict {
+                                id,
+                                type_id: local_actor.type_id.clone(),
+                                older: local_actor_id,
+                                younger: remote_actor_id,
                             }
-                            Err(e) => {
-                                // do nothing. Deserialize failed, unrecognised message
-                                warn!(
-                                    "Warning: Failed to deserialize remote messsage: {:?} ({:?})",
-                                    bin_message, e
-                                );
+                        } else {
+                            IdConflict {
+                                id,
+                                type_id: local_actor.type_id.clone(),
+                                older: remote_actor_id,
+                                younger: local_actor_id,
                             }
+                        };
+                        conflicts.push((conflict, local_is_older, local_actor.sender.clone()));
+                    }
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+
+        for (conflict, local_is_older, sender) in conflicts {
+            warn!(
+                "Actor id {:?} is held by {} and {}, resolving with {:?}",
+                conflict.id, conflict.older.location, conflict.younger.location, policy
+            );
+            let stop = || {
+                let _ = sender.send(
+                    EitherMessage::Special(Token::Stop(StopReason::IdConflict)),
+                    0,
+                );
+            };
+            match &policy {
+                ConflictPolicy::KeepOldest => {
+                    if !local_is_older {
+                        stop();
+                    }
+                }
+                ConflictPolicy::Merge(hook) => {
+                    if !local_is_older {
+                        hook(&Environment { env: env.clone() }, &conflict);
+                        stop();
+                    }
+                }
+                ConflictPolicy::KillBothAndRespawn => {
+                    stop();
+                    if local_is_older {
+                        let env = env.clone();
+                        let conflict = conflict.clone();
+                        std::thread::spawn(move || LocalEnvironment::respawn(env, conflict));
+                    }
+                }
+            }
+            env.id_conflict_watchers.notify(&conflict);
+        }
+    }
+
+    /// Spawn a fresh Actor for the older side of an id conflict, once the stopped Actor is gone.
+    fn respawn(env: ArcEnvironment, conflict: IdConflict) {
+        let started = Instant::now();
+        loop {
+            match env.local_actor_channels.lock() {
+                Ok(channels) => {
+                    if !channels.contains_key(&conflict.older) {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    return;
+                }
+            }
+            if started.elapsed() > RESPAWN_TIMEOUT {
+                warn!(
+                    "Actor {:?} did not stop, it is not respawned.",
+                    conflict.older
+                );
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if let Err(e) = LocalEnvironment::spawn(
+            Environment { env },
+            &conflict.type_id,
+            SpawnId::SpawnHere(LocalId::Specified(conflict.id)),
+            &SpawnOptions::default(),
+        ) {
+            error!("Failed to respawn conflicting Actor: {:?}", e);
+        }
+    }
+
+    /// Count a lookup of or a message to the specified id from the given machine.
+    fn record_correspondent(&self, key: &[u8], machine: MachineId) {
+        match self.correspondents.lock() {
+            Ok(mut correspondents) => correspondents.record(key, machine),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Options restricting a spawn to the machine the specified id recently corresponded with most.
+    ///
+    /// Falls back to the *preferred* machine, or the regular placement if no machine is known.
+    pub(crate) fn near_spawn_options(
+        &self,
+        key: &[u8],
+        preferred: Option<MachineId>,
+    ) -> SpawnOptions {
+        let local_machine_id = self.machine_id;
+        let peers = self.peers();
+        let known = |machine: &MachineId| {
+            *machine == local_machine_id || peers.iter().any(|(peer, _)| peer == machine)
+        };
+        let target = match self.correspondents.lock() {
+            Ok(correspondents) => correspondents.busiest(key, preferred.filter(known)),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                preferred
+            }
+        };
+        match target.filter(known) {
+            Some(target) => {
+                let others: Vec<MachineId> = std::iter::once(local_machine_id)
+                    .chain(peers.iter().map(|(peer, _)| *peer))
+                    .filter(|machine| *machine != target)
+                    .collect();
+                SpawnOptions::new().exclude_machines(&others)
+            }
+            None => SpawnOptions::default(),
+        }
+    }
+
+    /// The machine the given Actor id hashes to among the local and the connected remote machines, skipping the excluded ones.
+    ///
+    /// Every machine ranks the machines by their [rendezvous weight](fn.rendezvous_weight.html) for the id, so they agree as long as they are connected to the same machines.
+    /// A machine that lost its connection is skipped until it reconnects, only its ids move meanwhile.
+    /// Returns ```None``` if every machine is excluded.
+    pub(crate) fn machine_for_id(&self, key: &[u8], excluded: &[MachineId]) -> Option<MachineId> {
+        let mut machines = vec![self.machine_id];
+        match self.net_senders.lock() {
+            Ok(senders) => machines.extend(senders.keys().copied()),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        let dead: Vec<MachineId> = match self.dead_links.lock() {
+            Ok(dead_links) => dead_links.keys().cloned().collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        machines
+            .into_iter()
+            .filter(|machine| !excluded.contains(machine) && !dead.contains(machine))
+            .max_by_key(|machine| (rendezvous_weight(machine.as_bytes(), key), *machine))
+    }
+
+    /// The remote machine the given Actor id hashes to with [Placement::ConsistentHash](../options/enum.Placement.html#variant.ConsistentHash),
+    /// ```None``` with other placements or if the id hashes to the local machine.
+    pub(crate) fn remote_owner_of_id(&self, key: &[u8]) -> Option<MachineId> {
+        if self.options.placement != Placement::ConsistentHash {
+            return None;
+        }
+        self.machine_for_id(key, &[])
+            .filter(|owner| *owner != self.machine_id)
+    }
+
+    /// Translate machine ids to the machine numbers used by the LoadBalancer.
+    fn excluded_machine_nos(&self, excluded_machines: &[MachineId]) -> Vec<usize> {
+        let mut excluded = Vec::with_capacity(excluded_machines.len());
+        if excluded_machines.is_empty() {
+            return excluded;
+        }
+        if excluded_machines.contains(&self.machine_id) {
+            excluded.push(0);
+        }
+        match self.net_senders.lock() {
+            Ok(senders) => {
+                for (index, (machine, _)) in senders.iter().enumerate() {
+                    if excluded_machines.contains(machine) {
+                        // machine no 0 is the local machine
+                        excluded.push(index + 1);
+                    }
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        excluded
+    }
+
+    /// Start an Actor spawned by [Context::spawn_after_handle](../actor/struct.Context.html#method.spawn_after_handle),
+    /// on the local machine or by asking the remote machine it lives on.
+    pub(crate) fn start_held(&self, actor_id: &ActorId) {
+        if actor_id.location != self.machine_id {
+            match self
+                .options
+                .wire_format
+                .serialize(&NetMessage::StartHeld(actor_id.clone()))
+            {
+                Ok(bin) => match self.net_senders.lock() {
+                    Ok(mut senders) => {
+                        if let Err(e) = self.send_to_machine(&mut senders, actor_id.location, &bin)
+                        {
+                            warn!("Could not start {:?}: {:?}", actor_id, e);
                         }
                     }
+                    Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                },
+                Err(e) => warn!("Failed to serialize StartHeld: {:?}", e),
+            }
+            return;
+        }
+        let start = match self.held_starts.lock() {
+            Ok(mut held) => held.remove(actor_id),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        match start {
+            Some(HeldStart(start)) => start(),
+            // a duplicated spawn request found the Actor of the first one
+            None => debug!("{:?} is not waiting to be started", actor_id),
+        }
+    }
+
+    /// The number the LoadBalancer knows the given machine by, ```None``` if it is not connected.
+    fn machine_no(&self, machine: &MachineId) -> Option<usize> {
+        if *machine == self.machine_id {
+            // machine no 0 is the local machine
+            return Some(0);
+        }
+        match self.net_senders.lock() {
+            Ok(senders) => senders.get_index_of(machine).map(|index| index + 1),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                None
+            }
+        }
+    }
+
+    /// Run the Actor on the current thread until it stopped.
+    fn actor_mailbox_loop(
+        mailbox: Mailbox,
+        actor: Box<dyn Actor>,
+        env: Environment,
+        this_actor_ref: ActorRef,
+        type_id: String,
+    ) {
+        if let Some(mut running) = RunningActor::start(mailbox, actor, env, this_actor_ref, type_id)
+        {
+            loop {
+                let msg = running.mailbox.wait_for_msg();
+                if !running.process(msg) {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Run the Actor as a task on the async runtime until it stopped, the task is idle while the mailbox is empty or an async handler awaits.
+    #[cfg(feature = "async-runtime")]
+    async fn actor_mailbox_task(
+        mailbox: Mailbox,
+        actor: Box<dyn Actor>,
+        env: Environment,
+        this_actor_ref: ActorRef,
+        type_id: String,
+    ) {
+        if let Some(mut running) = RunningActor::start(mailbox, actor, env, this_actor_ref, type_id)
+        {
+            loop {
+                let msg = running.mailbox.wait_for_msg_async().await;
+                if !running.process_async(msg).await {
+                    break;
                 }
-                Err(_) => {
-                    panic!("Warning: NetReceiver::read returned error.");
-                    // we don't re-acquire the net connection anytime, so this is effectively a terminating condition. but scary likely.
+            }
+        }
+    }
+
+    /// The turns of the Actor on the worker pool, the first one runs its on_start method.
+    fn actor_mailbox_turns(
+        mailbox: Mailbox,
+        actor: Box<dyn Actor>,
+        env: Environment,
+        this_actor_ref: ActorRef,
+        type_id: String,
+    ) -> impl FnMut(usize) -> Turn + Send {
+        let mut starting = Some((mailbox, actor, env, this_actor_ref, type_id));
+        let mut running = None;
+        move |messages| {
+            if let Some((mailbox, actor, env, this_actor_ref, type_id)) = starting.take() {
+                running = RunningActor::start(mailbox, actor, env, this_actor_ref, type_id);
+            }
+            let running: &mut RunningActor = match &mut running {
+                Some(running) => running,
+                None => return Turn::Stopped,
+            };
+            for _ in 0..messages {
+                let msg = match running.mailbox.try_msg() {
+                    Ok(Some(msg)) => Ok(msg),
+                    Ok(None) => return Turn::Idle,
+                    Err(e) => Err(e),
+                };
+                if !running.process(msg) {
+                    return Turn::Stopped;
                 }
             }
+            Turn::Busy
         }
     }
 
-    // private helper function used in the receiver thread for **local-to-foreign** messages.
-    fn wait_for_local_messages(
-        env_remote_send: ArcEnvironment,
-        external_actor_ref_receiver: Receiver<(ActorId, SerNetMessageContent)>,
+    /// Start the mailbox loop of a new local Actor, on the worker pool, on its own thread or as a task of the async runtime.
+    ///
+    /// The Actor is *scheduled* on the pool if it was prepared for it.
+    fn run_actor(
+        &self,
+        scheduled: Option<Arc<ScheduledActor>>,
+        mailbox: Mailbox,
+        actor: Box<dyn Actor>,
+        env: Environment,
+        this_actor_ref: ActorRef,
+        type_id: String,
     ) {
-        // Waits for messages and handles them sequentially
-        loop {
-            match external_actor_ref_receiver.recv() {
-                // a outgoing net message always has the form (ActorId,SerializedNetMessageContent)
-                // with SerializedNetMessageContent being either ::Message(Vec<u8>) or ::Token(Vec<u8>)
-                Ok((actor_id, content)) => {
-                    match env_remote_send.net_senders.lock() {
-                        Ok(mut senders) => {
-                            if let Some(net_sender) = senders.get_mut(&actor_id.location) {
-                                match content {
-                                    SerNetMessageContent::Message(msg) => {
-                                        // try to serialize the message, silently failing if not possible
-                                        if let Ok(tuple_serialized) = bincode::serialize(
-                                            &NetMessage::Message(actor_id.clone(), msg.clone()),
-                                        ) {
-                                            if let Err(e) = net_sender.write(&tuple_serialized) {
-                                                warn!(
-                                                    "Warning: Write on net_sender failed: {:?}",
-                                                    e
-                                                );
-                                            }
-                                        } else {
-                                            warn!(
-                                                "Serializing NetMessage failed: {:?}",
-                                                (actor_id, msg)
-                                            );
-                                        }
-                                    }
-                                    SerNetMessageContent::Token(tok) => {
-                                        // try to serialize the message, silently failing if not possible
-                                        if let Ok(tuple_serialized) = bincode::serialize(
-                                            &NetMessage::SpecialToken(actor_id, tok),
-                                        ) {
-                                            if let Err(e) = net_sender.write(&tuple_serialized) {
-                                                warn!(
-                                                    "Warning: Write on net_sender failed: {:?}",
-                                                    e
-                                                );
-                                            }
-                                        }
-                                    }
-                                }
-                            } else {
-                                warn!(
-                                    "Error: Could not get NetSender for {:?}",
-                                    &actor_id.location
-                                );
-                            }
-                        }
-                        Err(e) => error!("{:?}", ActlibError::from_poison_error(&e)),
-                    }
-                }
-                Err(_) => {
-                    // No one holds the sender end any more, so this thread can terminate
-                    warn!("All copies of the Sender part of the local-to-environment/remote channel dropped, stopping the worker thread");
-                    break;
-                }
+        if let (Some(scheduler), Some(scheduled)) = (&self.scheduler, scheduled) {
+            scheduler.start_actor(
+                &scheduled,
+                LocalEnvironment::actor_mailbox_turns(mailbox, actor, env, this_actor_ref, type_id),
+            );
+            return;
+        }
+        #[cfg(feature = "async-runtime")]
+        {
+            if let Some(executor) = &self.executor {
+                executor.spawn(LocalEnvironment::actor_mailbox_task(
+                    mailbox,
+                    actor,
+                    env,
+                    this_actor_ref,
+                    type_id,
+                ));
+                return;
             }
         }
+        std::thread::spawn(move || {
+            LocalEnvironment::actor_mailbox_loop(mailbox, actor, env, this_actor_ref, type_id);
+        });
     }
 
-    /// Remove the [Actor](../actor/trait.Actor.html) associated with the [ActorId](../actor/struct.ActorId.html) from the Environment.
-    fn remove(&self, actor_id: ActorId) {
-        if actor_id.location != self.local_machine.ip() {
-            // remote case:
-            match self.net_senders.lock() {
-                Ok(mut senders) => {
-                    match senders.get_mut(&actor_id.location) {
-                        Some(sender) => {
-                            // serialize on-stop message to trigger the remove method over at the remote machine
-                            match bincode::serialize(&Token::Stop) {
-                                Ok(bin_token) => {
-                                    match bincode::serialize(&NetMessage::SpecialToken(
-                                        actor_id, bin_token,
-                                    )) {
-                                        Ok(bin_msg) => {
-                                            sender.write(&bin_msg);
-                                        }
-                                        Err(_) => {
-                                            warn!("Could not send Stop command to remote machine because the NetMessage could not be serialized.");
-                                        }
-                                    }
-                                }
-                                Err(_) => {
-                                    error!("Could not send Stop command to remote machine because the Stop Token could not be serialized.");
-                                }
-                            }
-                        }
-                        None => {
-                            error!( "Could not find net sender object to machine {:?}. Message Dropped.", actor_id.location );
-                        }
-                    }
-                }
-                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+    /// Stop an Actor that panicked, its mailbox is discarded.
+    fn stop_failed_actor(
+        env: &Environment,
+        actor: &mut Box<dyn Actor>,
+        mailbox: &mut Mailbox,
+        actor_id: ActorId,
+        type_id: String,
+    ) {
+        let outcome =
+            LocalEnvironment::run_on_stop(env, actor, &actor_id, StopReason::Failed, None);
+        env.env.remove(actor_id.clone());
+        let discarded = mailbox.discard_pending();
+        let messages_discarded = discarded.len();
+        env.env.report_discarded(
+            actor.as_ref(),
+            &actor_id,
+            DropCause::ActorRemoved,
+            discarded,
+        );
+        env.env.report_drained(
+            StopRecord {
+                actor: actor_id,
+                type_id,
+                outcome,
+            },
+            messages_discarded,
+        );
+    }
+
+    /// Run the on_stop method of the Actor, or on_stop_with_pending if *pending* messages are handed over, catching a panic.
+    fn run_on_stop(
+        env: &Environment,
+        actor: &mut Box<dyn Actor>,
+        actor_id: &ActorId,
+        reason: StopReason,
+        pending: Option<Vec<Box<dyn Any + Send>>>,
+    ) -> StopOutcome {
+        let mut outcome = StopOutcome::Completed;
+        run_as_actor(env, actor_id, || {
+            if let Err(reason) = catch_panic(|| match pending {
+                Some(pending) => actor.on_stop_with_pending(reason, pending),
+                None => actor.on_stop_with_reason(reason),
+            }) {
+                warn!("Actor {:?} panicked in on_stop: {}", actor_id, reason);
+                outcome = StopOutcome::Panicked(reason);
             }
-        } else {
-            // local case:
-            if let Ok(inv_actors) = self.invincible_actors.read() {
-                if inv_actors.contains_key(&actor_id) {
-                    return;
+        });
+        outcome
+    }
+
+    /// Notify the watchers about a panicked Actor, returning whether the Actor has to stop.
+    fn report_failure(
+        &self,
+        actor_id: &ActorId,
+        type_id: &str,
+        message_type: Option<&str>,
+        reason: String,
+    ) -> bool {
+        let stop = self.options.failure_policy == FailurePolicy::Stop;
+        self.actors_failed.fetch_add(1, Ordering::Relaxed);
+        match self.recent_failures.lock() {
+            Ok(mut failures) => failures.push_back(Instant::now()),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+        let failure = ActorFailed {
+            actor: actor_id.clone(),
+            type_id: type_id.to_string(),
+            message_type: message_type.map(str::to_string),
+            reason,
+            stopped: stop,
+        };
+        error!(
+            "{} Actor {:?} panicked handling {}: {}",
+            failure.type_id,
+            failure.actor,
+            message_type.unwrap_or("no message"),
+            failure.reason
+        );
+        self.actor_failure_watchers.notify(&failure);
+        stop
+    }
+
+    /// Remember that the connection to a remote machine failed, logging the first error.
+    ///
+    /// Messages to the machine are buffered from now on if it is going to be reconnected.
+    fn mark_link_dead(&self, machine: MachineId, error: String) {
+        let newly_dead = match self.dead_links.lock() {
+            Ok(mut dead_links) => {
+                if let Entry::Vacant(entry) = dead_links.entry(machine) {
+                    error!("Lost the connection to {}: {}", machine, error);
+                    entry.insert(error);
+                    true
+                } else {
+                    false
                 }
             }
-            match self.local_actor_channels.lock() {
-                Ok(mut channels) => {
-                    channels.remove(&actor_id);
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                false
+            }
+        };
+        if newly_dead {
+            self.set_peer_state(
+                |_, status| status.machine_id == Some(machine),
+                None,
+                PeerState::Disconnected,
+            );
+        }
+        if newly_dead && self.options.reconnect.is_some() && !self.is_shutting_down() {
+            match self.outbox.lock() {
+                Ok(mut outbox) => {
+                    outbox.entry(machine).or_default();
                 }
                 Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
             }
         }
     }
 
-    /// Create the ActorRef for an alive Actor with a User-specified ActorId.
-    /// First see if the Actor is located locally, if not try every known remote machine.
-    /// If the Actor is located on a remote Machine block the current thread until an answer was received.
-    pub(crate) fn find_actor_ref(
-        &self,
-        queried_id: &Vec<u8>,
-        searcher: ActorId,
-        protected: bool,
-    ) -> Result<(Receiver<Option<ActorRef>>, usize), ActlibError> {
-        // build local variant for comparison with existing actors
-        let target_actor_id = ActorId {
-            local_id: LocalId::Specified(queried_id.clone()),
-            location: self.local_machine.ip(),
+    /// Check the locks, the connections, the recently panicked Actors and the mailboxes of this machine.
+    pub(crate) fn health(&self) -> HealthReport {
+        let mut poisoned_locks = Vec::new();
+        let locks = [
+            (
+                "local_actor_channels",
+                self.local_actor_channels.is_poisoned(),
+            ),
+            ("net_senders", self.net_senders.is_poisoned()),
+            (
+                "external_actor_ref_sender",
+                self.external_actor_ref_sender.is_poisoned(),
+            ),
+            ("termination_sender", self.termination_sender.is_poisoned()),
+            ("load_balancer", self.load_balancer.is_poisoned()),
+            ("remote_queries", self.remote_queries.is_poisoned()),
+            ("invincible_actors", self.invincible_actors.is_poisoned()),
+            ("aliases", self.aliases.is_poisoned()),
+        ];
+        for (name, poisoned) in locks.iter() {
+            if *poisoned {
+                poisoned_locks.push(name.to_string());
+            }
+        }
+
+        let peers = self.peers();
+        let mut dead_peers: Vec<MachineId> = match self.dead_links.lock() {
+            Ok(dead_links) => dead_links.keys().cloned().collect(),
+            Err(_) => {
+                poisoned_locks.push("dead_links".to_string());
+                Vec::new()
+            }
         };
-        let (sender, receiver) = channel();
-        // Local search
-        match self.local_actor_channels.lock() {
-            Ok(mut channels) => {
-                if channels.contains_key(&target_actor_id) {
-                    // This is Some() only if the sending actor wants to make the target actor invincible
-                    if protected {
-                        self.add_protector(searcher.clone(), target_actor_id.clone());
-                    }
-                    // get local actor's sender
-                    match channels.get_mut(&target_actor_id) {
-                        Some(actor_ref_sender) => {
-                            // pre-fill the receiver's sender part that will be returned.
-                            // in this local case, this will be the only element of this channel that will be waited for.
-                            let new_actor_ref = ActorRef::new(
-                                target_actor_id,
-                                ActorRefChannel::Local(actor_ref_sender.clone()),
-                            );
-                            sender.send(Some(new_actor_ref));
-                            Ok((receiver, 1)) // 1: this will be the only message in this channel
-                        }
-                        None => Err(ActlibError::ActorNotFound(
-                            "Requested Actor was removed just a short time ago.".to_string(),
-                        )),
-                    }
-                } else {
-                    // remote Search
-                    drop(channels); // drop MutexGuard, not needed in else case
-                                    // register LocalEnvironment level sender to propagate answers from remotes back to the receiver that is handed out at the end of this function
-                    match self.remote_queries.lock() {
-                        Ok(mut queries) => {
-                            queries.insert((queried_id.clone(), searcher.clone()), sender);
-                            drop(queries); // drop lock after use
-                            match self.net_senders.lock() {
-                                Ok(mut senders) => {
-                                    for (_remote_machine, net_sender) in &mut *senders {
-                                        if let Ok(net_message) =
-                                            bincode::serialize(&NetMessage::QuerySpecifiedId(
-                                                queried_id.clone(),
-                                                self.local_machine.ip(),
-                                                searcher.clone(),
-                                                protected,
-                                            ))
-                                        {
-                                            if let Err(e) = net_sender.write(&net_message) {
-                                                if let Ok(mut queries) = self.remote_queries.lock()
-                                                {
-                                                    queries.remove(&(queried_id.clone(), searcher));
-                                                }
-                                                warn!("Failed to write Actor Query to remote stream, potentially deadlocking an actor waiting for response!");
-                                                return Err(ActlibError::NetworkError("Failed to write Actor Query to remote stream, potentially deadlocking an actor waiting for response!".to_string()));
-                                            }
-                                        } else {
-                                            error!("Error: Serializing the ActorRef query failed!");
-                                        }
-                                    }
-                                    Ok((receiver, senders.len()))
-                                }
-                                Err(e) => Err(ActlibError::from_poison_error(&e)),
-                            }
-                        }
-                        Err(e) => Err(ActlibError::from_poison_error(&e)),
+        dead_peers.sort();
+
+        let recent_failures = match self.recent_failures.lock() {
+            Ok(mut failures) => {
+                while let Some(failed_at) = failures.front() {
+                    if failed_at.elapsed() < FAILURE_WINDOW {
+                        break;
                     }
+                    failures.pop_front();
                 }
+                failures.len()
             }
-            Err(e) => Err(ActlibError::from_poison_error(&e)),
-        }
-    }
+            Err(_) => {
+                poisoned_locks.push("recent_failures".to_string());
+                0
+            }
+        };
 
-    /// Create a new [ActorRef](../actor/struct.ActorRef.html) corresponding to the [ActorId](../actor/struct.ActorId.html).
-    ///
-    /// [ActorRefs](../actor/struct.ActorRef.html) for local [Actors](../actor/trait.Actor.html) are only created if it exists.
-    ///
-    /// [ActorRefs](../actor/struct.ActorRef.html) for remote [Actors](../actor/trait.Actor.html) are always created.
-    pub(crate) fn to_actor_ref(&self, actor_id: ActorId) -> Result<ActorRef, ActlibError> {
-        if actor_id.location == self.local_machine.ip() {
-            match self.local_actor_channels.lock() {
-                Ok(channels) => {
-                    if let Some(sender) = channels.get(&actor_id) {
-                        Ok(ActorRef::new(
-                            actor_id,
-                            ActorRefChannel::Local(sender.clone()),
-                        ))
-                    } else {
-                        Err(ActlibError::ActorNotFound(format!(
-                            "Did not find local actor with id {:?}",
-                            actor_id
-                        )))
+        let mut queued_messages = 0;
+        let mut backlogged_actors = Vec::new();
+        if let Ok(channels) = self.local_actor_channels.lock() {
+            for (actor_id, local_actor) in &*channels {
+                let queued = local_actor.sender.queued_messages();
+                queued_messages += queued;
+                if let Some(alerts) = &self.options.mailbox_alerts {
+                    let too_deep = alerts.max_depth.is_some_and(|max| queued > max);
+                    let too_old = match (alerts.max_age, local_actor.sender.oldest_message_age()) {
+                        (Some(max), Some(age)) => age > max,
+                        _ => false,
+                    };
+                    if too_deep || too_old {
+                        backlogged_actors.push(actor_id.clone());
                     }
                 }
-                Err(e) => Err(ActlibError::from_poison_error(&e)),
             }
+        }
+
+        let threads = self.supervisor.threads();
+        let recent_thread_panics = threads.iter().any(|thread| {
+            thread
+                .last_panic_at
+                .elapsed()
+                .map_or(true, |elapsed| elapsed < FAILURE_WINDOW)
+        });
+
+        let status = if !poisoned_locks.is_empty()
+            || (!peers.is_empty() && dead_peers.len() == peers.len())
+            || threads.iter().any(|thread| !thread.running)
+        {
+            HealthStatus::Failed
+        } else if !dead_peers.is_empty()
+            || recent_failures > 0
+            || recent_thread_panics
+            || !backlogged_actors.is_empty()
+        {
+            HealthStatus::Degraded
         } else {
-            match self.external_actor_ref_sender.lock() {
-                Ok(sender) => Ok(ActorRef::new(
-                    actor_id,
-                    ActorRefChannel::Remote(sender.clone()),
-                )),
-                Err(e) => Err(ActlibError::from_poison_error(&e)),
-            }
+            HealthStatus::Ready
+        };
+        HealthReport {
+            status,
+            machine_id: self.machine_id,
+            poisoned_locks,
+            dead_peers,
+            actors_failed: self.actors_failed.load(Ordering::Relaxed),
+            recent_failures,
+            queued_messages,
+            backlogged_actors,
+            threads,
+            checked_at: SystemTime::now(),
         }
     }
 
-    fn add_protector(&self, protector_id: ActorId, target_id: ActorId) {
-        match self.invincible_actors.write() {
-            Ok(mut inv_actors) => {
-                if let Some(protectors) = inv_actors.get_mut(&target_id) {
-                    protectors.insert(protector_id);
-                } else {
-                    let mut hs = HashSet::new();
-                    hs.insert(protector_id);
-                    inv_actors.insert(target_id, hs);
-                }
+    /// Register a new watcher for panicked Actors.
+    pub(crate) fn watch_actor_failures(&self) -> Receiver<ActorFailed> {
+        self.actor_failure_watchers.watch()
+    }
+
+    pub(crate) fn watch_dead_letters(&self) -> Receiver<DeadLetter> {
+        self.dead_letter_watchers.watch()
+    }
+
+    pub(crate) fn watch_stale_incarnations(&self) -> Receiver<StaleIncarnation> {
+        self.stale_incarnation_watchers.watch()
+    }
+
+    /// Whether something addressed to *actor_id* may reach the local Actor *current* under the incarnation policy.
+    ///
+    /// Addressing an earlier incarnation is reported to the watchers, unless the policy allows it.
+    fn admits_incarnation(
+        &self,
+        actor_id: &ActorId,
+        current: &ActorId,
+        sender: Option<ActorId>,
+        message: Option<Vec<u8>>,
+    ) -> bool {
+        if actor_id.incarnation == 0
+            || current.incarnation == 0
+            || actor_id.incarnation == current.incarnation
+        {
+            return true;
+        }
+        let delivered = match self.options.incarnation_policy {
+            IncarnationPolicy::Allow => return true,
+            IncarnationPolicy::Notify => true,
+            IncarnationPolicy::RejectStale => false,
+        };
+        warn!(
+            "{:?} addresses incarnation {} of its id, the Actor living now is incarnation {}",
+            actor_id, actor_id.incarnation, current.incarnation
+        );
+        let stale = StaleIncarnation {
+            stale: actor_id.clone(),
+            current: current.clone(),
+            sender,
+            message,
+            delivered,
+        };
+        self.stale_incarnation_watchers.notify(&stale);
+        delivered
+    }
+
+    pub(crate) fn watch_delivery_failures(&self) -> Receiver<DeliveryFailure> {
+        self.delivery_failure_watchers.watch()
+    }
+
+    pub(crate) fn watch_dropped_messages(&self) -> Receiver<MessageDropped> {
+        self.message_dropped_watchers.watch()
+    }
+
+    /// Count a Message the local machine dropped and tell the watchers about it.
+    pub(crate) fn report_dropped(
+        &self,
+        cause: DropCause,
+        target: Option<ActorId>,
+        message_type: Option<String>,
+    ) {
+        Instruments::count(&self.instruments.messages_dropped[cause as usize]);
+        let dropped = MessageDropped {
+            cause,
+            target,
+            message_type,
+        };
+        self.message_dropped_watchers.notify(&dropped);
+    }
+
+    /// Report the user Message in a serialized NetMessage *frame* that was not sent on, other frames are skipped.
+    fn report_dropped_frame(&self, cause: DropCause, frame: &[u8]) {
+        match self.options.wire_format.deserialize::<NetMessage>(frame) {
+            Ok(NetMessage::Message(actor_id, ..)) | Ok(NetMessage::KeyedMessage(actor_id, ..)) => {
+                self.report_dropped(cause, Some(actor_id), None)
             }
-            Err(e) => {
-                error!("{:?}", e);
+            // Tracked messages are given up on by the retries of reliable delivery
+            _ => {}
+        }
+    }
+
+    /// Report the *messages* a local Actor took out of its mailbox without handling them, skipping tokens and queries.
+    fn report_discarded(
+        &self,
+        actor: &dyn Actor,
+        actor_id: &ActorId,
+        cause: DropCause,
+        messages: Vec<EitherMessage>,
+    ) {
+        for message in messages {
+            let message_type = match message {
+                EitherMessage::Regular(message) => actor.message_type_name(&*message),
+                EitherMessage::Serialized(message) => message
+                    .deserialize(actor, self.options.wire_format)
+                    .and_then(|message| actor.message_type_name(&*message)),
+                EitherMessage::Special(_) | EitherMessage::Query(_) => continue,
+            };
+            self.report_dropped(
+                cause,
+                Some(actor_id.clone()),
+                message_type.map(str::to_string),
+            );
+        }
+    }
+
+    /// Remember the type id of a keyed Actor, so its dead peer policy can be looked up.
+    fn remember_keyed_type(&self, key: &[u8], type_id: &str) {
+        if !self.options.dead_peer_policies.contains_key(type_id) {
+            return;
+        }
+        match self.keyed_types.lock() {
+            Ok(mut keyed_types) => {
+                keyed_types.insert(key.to_vec(), type_id.to_string());
             }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
         }
     }
 
-    /// Removes the given protector from protecting the given target actor. Will send this request to all remote environments too.
-    pub(crate) fn remove_protector(&self, protector_id: ActorId, target_id: ActorId) {
-        match self.invincible_actors.write() {
-            Ok(mut invincible_actors) => {
-                if let Some(protectors) = invincible_actors.get_mut(&target_id) {
-                    protectors.remove(&protector_id);
-                } else {
-                    // drop the write guard since its not needed in the else case
-                    drop(invincible_actors);
-                    //
-                    match self.net_senders.lock() {
-                        Ok(mut senders) => {
-                            for (addr, net_channel) in &mut *senders {
-                                match bincode::serialize(&NetMessage::RemoveProtector(
-                                    protector_id.clone(),
-                                    target_id.clone(),
-                                )) {
-                                    Ok(msg) => {
-                                        net_channel.write(&msg);
-                                    }
-                                    Err(e) => {
-                                        warn!("Unable to send RemoveProtector to remote {:?}, possible MemLeak! Error Message: {:?}", addr, e);
-                                    }
-                                }
+    /// Apply the [DeadPeerPolicy](../options/enum.DeadPeerPolicy.html) of a keyed Actor whose machine is disconnected.
+    ///
+    /// Returns the Actor the Message goes to, or ```None``` if it was dropped.
+    fn route_around_dead_peer(
+        env: &ArcEnvironment,
+        actor_id: ActorId,
+        content: &SerNetMessageContent,
+    ) -> Option<ActorId> {
+        if env.options.dead_peer_policies.is_empty() {
+            return Some(actor_id);
+        }
+        let actor_id = env.redirected(actor_id);
+        let message = match content {
+            SerNetMessageContent::Message(message, _)
+            | SerNetMessageContent::Keyed(_, message, _) => message,
+            _ => return Some(actor_id),
+        };
+        let key = match &actor_id.local_id {
+            LocalId::Specified(key) => key.clone(),
+            LocalId::Automatic(_) => return Some(actor_id),
+        };
+        let dead_machines: Vec<MachineId> = match env.dead_links.lock() {
+            Ok(dead_links) => dead_links.keys().cloned().collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return Some(actor_id);
+            }
+        };
+        if !dead_machines.contains(&actor_id.location) {
+            return Some(actor_id);
+        }
+        let type_id = match env.keyed_types.lock() {
+            Ok(keyed_types) => keyed_types.get(&key).cloned()?,
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return Some(actor_id);
+            }
+        };
+        match env.options.dead_peer_policies.get(&type_id) {
+            Some(DeadPeerPolicy::DeadLetter) => {}
+            Some(DeadPeerPolicy::Respawn) => {
+                match LocalEnvironment::spawn(
+                    Environment { env: env.clone() },
+                    &type_id,
+                    SpawnId::User(LocalId::Specified(key)),
+                    &SpawnOptions::new().exclude_machines(&dead_machines),
+                ) {
+                    Ok(replacement) => {
+                        let replacement = replacement.clone_id();
+                        info!(
+                            "Respawned {:?} of the disconnected machine {} as {:?}.",
+                            actor_id, actor_id.location, replacement
+                        );
+                        match env.redirects.lock() {
+                            Ok(mut redirects) => {
+                                // the replacement may live where an earlier redirect started, e.g. on a returned machine
+                                redirects.remove(&replacement);
+                                redirects.insert(actor_id, replacement.clone());
                             }
+                            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
                         }
-                        Err(e) => log_err_as!(error, e),
+                        return Some(replacement);
                     }
+                    Err(e) => error!(
+                        "Failed to respawn {:?} of the disconnected machine {}: {:?}",
+                        actor_id, actor_id.location, e
+                    ),
                 }
             }
-            Err(e) => log_err_as!(error, e),
+            Some(DeadPeerPolicy::Queue) | None => return Some(actor_id),
         }
+        warn!(
+            "Dropped a Message for {:?}, its machine {} is disconnected.",
+            actor_id, actor_id.location
+        );
+        env.report_dropped(DropCause::PeerDead, Some(actor_id.clone()), None);
+        let dead_letter = DeadLetter {
+            actor: actor_id,
+            type_id,
+            message: message.bytes.clone(),
+        };
+        env.dead_letter_watchers.notify(&dead_letter);
+        None
     }
 
-    pub(crate) fn remove_remote_query(&self, queried_id: &Vec<u8>, searcher: ActorId) {
-        if let Ok(mut queries) = self.remote_queries.lock() {
-            queries.remove(&(queried_id.clone(), searcher));
-        } else {
-            warn!("Unable to acquire remote_queries lock in remove_remote_query, possible MemLeak");
+    /// The replacement a keyed Actor of a disconnected machine was respawned as, or *actor_id* itself.
+    fn redirected(&self, mut actor_id: ActorId) -> ActorId {
+        match self.redirects.lock() {
+            Ok(redirects) => {
+                // a replacement may have been respawned in turn, redirects never form a loop
+                while let Some(replacement) = redirects.get(&actor_id) {
+                    actor_id = replacement.clone();
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
         }
+        actor_id
     }
 
-    /// This method is called when an incoming message from another machine is detected.
-    fn handle_net_message(&self, message_or_token: SerNetMessageContent, actor_id: ActorId) {
-        match self.local_actor_channels.lock() {
-            Ok(mut channels) => {
-                match channels.get_mut(&actor_id) {
-                    Some(sender) => {
-                        match message_or_token {
-                            SerNetMessageContent::Message(bin) => {
-                                if let Err(e) = sender.send(EitherMessage::Serialized(bin)) {
-                                    info!("Received remote message but internal actor channel is closed, probably because the actor does not exist anymore: {:?}", e);
-                                }
-                            }
-                            SerNetMessageContent::Token(bin) => {
-                                match bincode::deserialize::<Token>(&bin) {
-                                    Ok(token) => {
-                                        // special Tokens that are handled only by the Actor itself are passed on as a message to the actor
-                                        if let Err(e) = sender.send(EitherMessage::Special(token)) {
-                                            info!("Received remote Token message but internal actor channel is closed, probably because the actor does not exist anymore: {:?}", e);
-                                        }
-                                    }
-                                    Err(_e) => {
-                                        warn!("Unable to de-serialize Token message from remote, system state potentially compromised.");
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    None => {
-                        warn!(
-                            "Actor {:?} not found. Remote message {:?} ignored.",
-                            actor_id, message_or_token
-                        );
-                    }
-                }
-            }
-            Err(e) => log_err_as!(warn, e),
+    /// Whether the expiration of this Environment started.
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Check that a broadcast may be sent now: the Environment is not shutting down and the caller holds the capability.
+    pub(crate) fn check_broadcast(&self) -> Result<(), ActlibError> {
+        if self.is_shutting_down() {
+            return Err(ActlibError::ShuttingDown(
+                "The Environment is shutting down, cannot broadcast".to_string(),
+            ));
         }
+        self.check_capability(Capability::Broadcast)
     }
 
-    /// Spawn a given [Actor](../actor/trait.Actor.html) object inside this Environment.
+    /// The format the messages to remote machines are encoded in.
+    pub(crate) fn wire_format(&self) -> WireFormat {
+        self.options.wire_format
+    }
+
+    /// Check that the calling Actor holds the *capability*, if it is restricted at all.
     ///
-    /// This method registers the [Actor](../actor/trait.Actor.html) inside this Environment and subsequently calls it's [on_start](../actor/trait.Actor.html#method.on_start) Method.
+    /// Calls from outside of an Actor are always allowed.
+    pub(crate) fn check_capability(&self, capability: Capability) -> Result<(), ActlibError> {
+        if !self.options.restricted.contains(&capability) {
+            return Ok(());
+        }
+        let caller = match current_actor() {
+            Some(caller) => caller,
+            None => return Ok(()),
+        };
+        match self.local_actor_channels.lock() {
+            Ok(channels) => match channels.get(&caller) {
+                Some(actor) if actor.capabilities.contains(&capability) => Ok(()),
+                _ => Err(ActlibError::PermissionDenied(format!(
+                    "{:?} is not granted {:?}",
+                    caller, capability
+                ))),
+            },
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// The local Actor that asks a remote machine for an operation, with its capabilities.
+    pub(crate) fn caller(&self, actor: Option<ActorId>) -> Option<Caller> {
+        let actor = actor?;
+        let capabilities = match self.local_actor_channels.lock() {
+            Ok(channels) => channels
+                .get(&actor)
+                .map(|local_actor| local_actor.capabilities.clone())
+                .unwrap_or_default(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        Some(Caller {
+            actor,
+            capabilities,
+        })
+    }
+
+    /// Check that the remote Actor asking for an operation holds the *capability*, if this machine restricts it.
     ///
-    /// The return value is an [ActorRef](../actor/struct.ActorRef.html) object as the [Actor](../actor/trait.Actor.html) address.
-    /// Use it to send messages to the now alive [Actor](../actor/trait.Actor.html).
-    pub(crate) fn spawn(
-        env: Environment,
-        actor_type_id: &str,
-        local_id: SpawnId,
-    ) -> Result<ActorRef, ActlibError> {
-        let local_environment = &env.env;
+    /// The machine of the Actor vouches for its capabilities. Requests from outside of an Actor are allowed, like local ones.
+    fn check_remote_capability(
+        &self,
+        caller: &Option<Caller>,
+        capability: Capability,
+    ) -> Result<(), ActlibError> {
+        if !self.options.restricted.contains(&capability) {
+            return Ok(());
+        }
+        match caller {
+            Some(caller) if !caller.capabilities.contains(&capability) => {
+                Err(ActlibError::PermissionDenied(format!(
+                    "{:?} on {} is not granted {:?}",
+                    caller.actor, caller.actor.location, capability
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
 
-        let mut machine_no = 0;
-        if !local_id.is_spawn_here() {
-            match local_environment.load_balancer.lock() {
-                Ok(mut balancer) => {
-                    machine_no = balancer.next_machine_no();
-                }
-                Err(e) => {
-                    warn!("Could not acquire LoadBalancer Mutex lock, defaulted to local spawn.");
+    /// Expire the whole Environment.
+    ///
+    /// Remote machines are asked to stop their Actors and report back, the local Actors are drained,
+    /// and the aggregated [ExpirationResult](../api/struct.ExpirationResult.html) is handed to the waiting main thread.
+    /// Every machine gives its Actors *drain_timeout* to work off their mailboxes and stop.
+    pub(crate) fn send_expiration_signal(
+        &self,
+        drain_timeout: Duration,
+    ) -> Result<ExpirationResult, SendError<ExpirationResult>> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let (report_sender, report_receiver) = channel();
+        match self.expiration_reports.lock() {
+            Ok(mut reports) => *reports = Some(report_sender),
+            Err(_e) => return Err(SendError(ExpirationResult::default())),
+        }
+        // Send Expiration-Message to remote machines
+        let mut pending_remotes = Vec::new();
+        let started = Instant::now();
+        match self.net_senders.lock() {
+            Ok(mut senders) => {
+                if let Ok(ser_net_msg) =
+                    &self
+                        .options
+                        .wire_format
+                        .serialize(&NetMessage::SendExpirationSignal(
+                            self.machine_id,
+                            drain_timeout,
+                            self.caller(current_actor()),
+                        ))
+                {
+                    // we want to shutdown here, so we don't care about crashed remotes anymore
+                    // they simply show up as unreported
+                    for (remote, _result) in self.send_to_all_machines(&mut senders, ser_net_msg) {
+                        pending_remotes.push(remote);
+                    }
                 }
+                drop(senders);
             }
+            Err(_e) => return Err(SendError(ExpirationResult::default())),
         }
-        match machine_no {
-            0 => {
-                let new_actor = (local_environment.actor_builder)(&actor_type_id)?;
 
-                let actor_id = ActorId {
-                    local_id: local_id.unwrap_or_automatic(),
-                    location: local_environment.local_machine.ip(),
-                };
+        let mut result = ExpirationResult {
+            reason: ExpirationReason::LocalRequest,
+            reports: vec![self.drain_local_actors(drain_timeout)],
+            unreported: Vec::new(),
+        };
 
-                // create new channel for the new actor's mailbox
-                let (mailbox_sender, mailbox_receiver) = channel();
+        // collect the reports of the remote machines, they drained in parallel to the local Actors
+        let deadline = started + drain_timeout + REMOTE_DRAIN_TIMEOUT;
+        while !pending_remotes.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match report_receiver.recv_timeout(deadline - now) {
+                Ok(report) => {
+                    pending_remotes.retain(|remote| *remote != report.machine);
+                    result.reports.push(report);
+                }
+                Err(_) => break,
+            }
+        }
+        result.unreported = pending_remotes;
+        if let Ok(mut reports) = self.expiration_reports.lock() {
+            *reports = None;
+        }
 
-                // create new ActorRef pointing to the new actor instance
-                let actor_ref = ActorRef::new(
-                    actor_id.clone(),
-                    ActorRefChannel::Local(mailbox_sender.clone()),
-                );
+        self.release_termination(result.clone())?;
+        Ok(result)
+    }
 
-                // register channel in this environment
-                match local_environment.local_actor_channels.lock() {
-                    Ok(mut channels) => {
-                        channels.insert(actor_id.clone(), mailbox_sender);
+    /// Send Token::Stop to all local Actors and wait until they stopped or the drain deadline passed.
+    ///
+    /// The Stop token queues up behind the pending messages, so every Actor works off its mailbox first.
+    fn drain_local_actors(&self, drain_timeout: Duration) -> DrainReport {
+        // pending delayed messages must not reach Actors that are stopping
+        let delayed_messages_cancelled = self.timer.cancel_all();
+        let (drain_sender, drain_receiver) = channel();
+        if let Ok(mut listener) = self.drain_listener.lock() {
+            *listener = Some(drain_sender);
+        }
+        let mut signaled = Vec::new();
+        match self.local_actor_channels.lock() {
+            Ok(local_actor_channels) => {
+                for (actor_id, local_actor) in local_actor_channels.iter() {
+                    // we want to shutdown so we don't care about non-responsive actors here
+                    if local_actor
+                        .sender
+                        .send(EitherMessage::Special(Token::Stop(StopReason::Expired)), 0)
+                        .is_ok()
+                    {
+                        signaled.push((actor_id.clone(), local_actor.type_id.clone()));
                     }
-                    Err(e) => {
-                        return Err(ActlibError::SpawnFailed(
-                            "Failed to insert Actor to Environment".to_string(),
-                        ));
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+
+        // wait for the actors, so they don't try to use stdout during shutdown (causes panic)
+        let mut actors_stopped = 0;
+        let mut messages_discarded = 0;
+        let mut stop_records = Vec::with_capacity(signaled.len());
+        let actors_signaled = signaled.len();
+        let deadline = Instant::now() + drain_timeout;
+        // expiring from a handler must not keep the draining Actors from the worker pool
+        scheduler::blocking(|| {
+            while actors_stopped < actors_signaled {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                match drain_receiver.recv_timeout(deadline - now) {
+                    Ok((record, discarded)) => {
+                        if signaled
+                            .iter()
+                            .any(|(actor_id, _)| *actor_id == record.actor)
+                        {
+                            actors_stopped += 1;
+                            messages_discarded += discarded;
+                            stop_records.push(record);
+                        }
                     }
+                    Err(_) => break,
                 }
+            }
+        });
+        if let Ok(mut listener) = self.drain_listener.lock() {
+            *listener = None;
+        }
+        // whatever did not report is still busy with its mailbox or on_stop
+        let mut actors_not_stopped = Vec::new();
+        for (actor_id, type_id) in signaled {
+            if !stop_records.iter().any(|record| record.actor == actor_id) {
+                actors_not_stopped.push(actor_id.clone());
+                stop_records.push(StopRecord {
+                    actor: actor_id,
+                    type_id,
+                    outcome: StopOutcome::TimedOut,
+                });
+            }
+        }
 
-                // spawn mailbox check thread
-                // it will loop over received messages, breaking on error
-                let actor_ref_clone = actor_ref.clone();
+        DrainReport {
+            machine: self.machine_id,
+            actors_signaled,
+            actors_stopped,
+            actors_not_stopped,
+            messages_discarded,
+            delayed_messages_cancelled,
+            stop_records,
+        }
+    }
 
-                let env_clone = env.clone();
+    /// Called by an Actor after it stopped, with the number of messages left in its mailbox.
+    fn report_drained(&self, record: StopRecord, messages_discarded: usize) {
+        if let Ok(listener) = self.drain_listener.lock() {
+            if let Some(sender) = &*listener {
+                let _ = sender.send((record, messages_discarded));
+            }
+        }
+    }
 
-                std::thread::spawn(move || {
-                    LocalEnvironment::actor_mailbox_loop(
-                        mailbox_receiver,
-                        new_actor,
-                        env_clone,
-                        actor_ref_clone,
-                    );
-                });
+    /// Release the main thread blocking on the EnvironmentExpirationChecker.
+    fn release_termination(
+        &self,
+        result: ExpirationResult,
+    ) -> Result<(), SendError<ExpirationResult>> {
+        // the Actors are gone, release the port for the next Environment
+        if let Some(addr) = netchannel::shutdown_server() {
+            info!("Stopped listening on {}", addr);
+        }
+        match self.termination_sender.lock() {
+            Ok(sender) => sender.send(result),
+            Err(_) => Err(SendError(ExpirationResult::default())),
+        }
+    }
+
+    /// The number of the next [sequenced broadcast](../api/struct.Environment.html#method.broadcast_sequenced) sent from this machine.
+    pub(crate) fn next_broadcast_seq(&self) -> u64 {
+        self.broadcast_seq.fetch_add(1, Ordering::SeqCst) + 1
+    }
 
-                return Ok(actor_ref);
+    /// Enqueue a serialized Message that was broadcast for every local Actor.
+    fn deliver_broadcast(&self, content: SerializedMessage) {
+        match self.local_actor_channels.lock() {
+            Ok(channels) => {
+                let mut actor_ids: Vec<ActorId> = channels.keys().map(Clone::clone).collect();
+                drop(channels);
+                if self.options.ordered_broadcasts {
+                    actor_ids.sort();
+                }
+                // broadcast serialized Message to all Actors
+                for actor_id in actor_ids {
+                    self.handle_net_message(
+                        SerNetMessageContent::Message(content.clone(), None),
+                        actor_id.clone(),
+                    );
+                }
             }
-            remote_machine_no => {
-                // machine no that is returned from the load balancer is 1 higher than the index, because id 0 is local.
-                match local_environment.net_senders.lock() {
-                    Ok(mut senders) => match senders.get_index_mut(remote_machine_no - 1) {
-                        Some((machine, net_sender)) => {
-                            let new_actor_local_id = match local_id {
-                                SpawnId::Automatic => LocalId::Automatic(Uuid::new_v4()),
-                                SpawnId::User(id) => id,
-                                SpawnId::SpawnHere(_) => {
-                                    unreachable!("SpawnHere wanted to send to remote machine.")
-                                }
-                            };
-                            let machine_clone = machine.clone();
-
-                            match bincode::serialize(&NetMessage::SpawnByTypeId(
-                                actor_type_id.to_string(),
-                                new_actor_local_id.clone(),
-                            )) {
-                                Ok(msg) => match net_sender.write(&msg) {
-                                    Ok(_size) => {
-                                        return local_environment.to_actor_ref(ActorId {
-                                            local_id: new_actor_local_id,
-                                            location: machine_clone,
-                                        });
-                                    }
-                                    Err(e) => {
-                                        return Err(ActlibError::SpawnFailed(
-                                            "Failed to serialize SpawnByTypeId message".to_string(),
-                                        ));
-                                    }
-                                },
-                                Err(_) => {
-                                    return Err(ActlibError::SpawnFailed(
-                                        "Failed to serialize SpawnByTypeId message".to_string(),
-                                    ));
-                                }
-                            }
+            Err(e) => {
+                error!("{:?}", ActlibError::from_poison_error(&e));
+            }
+        }
+    }
+
+    /// Send a Message to every remote machine and to the local Actors alive now, handed to them in slices on the timer thread.
+    ///
+    /// Reports to *completed* once the last local Actor got the Message.
+    pub(crate) fn broadcast_in_slices<M: Message<'static> + Clone + 'static>(
+        env: &ArcEnvironment,
+        message: M,
+        slices: BroadcastSlices,
+        completed: Sender<BroadcastCompleted>,
+    ) {
+        match SerializedMessage::of(&message, env.options.wire_format) {
+            Ok(ser_msg) => {
+                match env
+                    .options
+                    .wire_format
+                    .serialize(&NetMessage::SlicedBroadcast(
+                        ser_msg,
+                        slices.clone(),
+                        env.caller(current_actor()),
+                    )) {
+                    Ok(ser_net_msg) => match env.net_senders.lock() {
+                        Ok(mut senders) => {
+                            // if this fails the connection broke down
+                            let _ = env.send_to_all_machines(&mut senders, &ser_net_msg);
                         }
-                        None => Err(ActlibError::InvalidState(format!(
-                            "Error: LoadBalancer returned machine no that is invalid: {}",
-                            machine_no
-                        ))),
+                        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
                     },
-                    Err(e) => Err(ActlibError::from_poison_error(&e)),
+                    Err(e) => warn!("Failed to serialize a sliced broadcast: {:?}", e),
                 }
             }
+            Err(e) => warn!("Failed to serialize a sliced broadcast: {:?}", e),
         }
+        let mut size = None;
+        LocalEnvironment::deliver_in_slices(env, slices, Some(completed), move |env, actor_ids| {
+            match env.local_actor_channels.lock() {
+                Ok(channels) => actor_ids
+                    .iter()
+                    .filter_map(|actor_id| channels.get(actor_id))
+                    .filter(|local_actor| {
+                        let size = env.measured_size(&local_actor.sender, &message, &mut size);
+                        local_actor
+                            .sender
+                            .send(EitherMessage::Regular(Box::new(message.clone())), size)
+                            .is_ok()
+                    })
+                    .count(),
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    0
+                }
+            }
+        });
     }
 
-    fn actor_mailbox_loop(
-        mailbox_receiver: Receiver<EitherMessage>,
-        mut actor: Box<dyn Actor>,
-        env: Environment,
-        this_actor_ref: ActorRef,
+    /// Hand a broadcast to the local Actors alive now, *slice_size* of them every *interval* on the timer thread.
+    ///
+    /// *deliver* hands it to a slice of Actors, returning how many got it. Actors removed meanwhile are skipped,
+    /// *completed* learns about the last slice.
+    fn deliver_in_slices<F: FnMut(&LocalEnvironment, &[ActorId]) -> usize + Send + 'static>(
+        env: &ArcEnvironment,
+        slices: BroadcastSlices,
+        completed: Option<Sender<BroadcastCompleted>>,
+        mut deliver: F,
     ) {
-        // create actor's mailbox
-        let mailbox = Mailbox::new(mailbox_receiver);
+        let mut actor_ids: Vec<ActorId> = match env.local_actor_channels.lock() {
+            Ok(channels) => channels.keys().cloned().collect(),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        };
+        if env.options.ordered_broadcasts {
+            actor_ids.sort();
+        }
+        let slice_size = slices.slice_size.max(1);
+        let started = Instant::now();
+        let weak_env = Arc::downgrade(env);
+        let mut next = 0;
+        let mut slice_count = 0;
+        let mut delivered = 0;
+        env.timer
+            .schedule_periodic(Instant::now(), slices.interval, move || {
+                let env = match weak_env.upgrade() {
+                    Some(env) => env,
+                    None => return false,
+                };
+                let end = (next + slice_size).min(actor_ids.len());
+                delivered += deliver(&env, &actor_ids[next..end]);
+                next = end;
+                slice_count += 1;
+                if next < actor_ids.len() {
+                    return true;
+                }
+                if let Some(completed) = &completed {
+                    let _ = completed.send(BroadcastCompleted {
+                        actors: delivered,
+                        slices: slice_count,
+                        elapsed: started.elapsed(),
+                    });
+                }
+                false
+            });
+    }
 
-        // keep a ActorId copy at hand
-        let this_actor_id = this_actor_ref.clone_id();
+    /// The machine sequencing the broadcasts in total order, the lowest id among the local and the connected machines.
+    pub(crate) fn leader(&self) -> MachineId {
+        self.peer_status()
+            .into_iter()
+            .filter(|status| status.state == PeerState::Connected)
+            .filter_map(|status| status.machine_id)
+            .chain(std::iter::once(self.machine_id))
+            .min()
+            .unwrap_or(self.machine_id)
+    }
 
-        // actor is now registered and has a mailbox, call on_start
-        actor.on_start(env.clone(), this_actor_ref);
+    /// Have the leader broadcast a serialized Message in total order.
+    pub(crate) fn broadcast_in_total_order(
+        &self,
+        content: SerializedMessage,
+    ) -> Result<(), ActlibError> {
+        let leader = self.leader();
+        let caller = self.caller(current_actor());
+        if leader == self.machine_id {
+            self.sequence_broadcast(content, caller);
+            return Ok(());
+        }
+        let bin = self
+            .options
+            .wire_format
+            .serialize(&NetMessage::OrderBroadcast(content, caller))?;
+        match self.net_senders.lock() {
+            Ok(mut senders) => match self.send_to_machine(&mut senders, leader, &bin) {
+                Ok(_) => Ok(()),
+                Err(e) => Err(ActlibError::NetworkError(format!(
+                    "Failed to send the broadcast to the leader {}: {:?}",
+                    leader, e
+                ))),
+            },
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
 
-        loop {
-            // The Actor listens for messages incoming to it's mailbox.
-            // The messages are handled sequentially, and special Token messages may be handled without direct outside visibility to the actlib API.
-            //
-            match mailbox.wait_for_msg() {
-                Ok(EitherMessage::Special(Token::Stop)) => {
-                    // local case:
-                    if let Ok(inv_actors) = env.env.invincible_actors.read() {
-                        if inv_actors.contains_key(&this_actor_id) {
-                            continue;
+    /// As the leader, broadcast a serialized Message to all remote machines and the local Actors, one Message at a time.
+    ///
+    /// The *caller* is passed on, every machine checks its capabilities itself.
+    fn sequence_broadcast(&self, content: SerializedMessage, caller: Option<Caller>) {
+        let _sequencing = match self.total_order.lock() {
+            Ok(sequencing) => sequencing,
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return;
+            }
+        };
+        match self
+            .options
+            .wire_format
+            .serialize(&NetMessage::OrderedBroadcast(content.clone(), caller))
+        {
+            Ok(bin) => match self.net_senders.lock() {
+                Ok(mut senders) => {
+                    for (machine, result) in self.send_to_all_machines(&mut senders, &bin) {
+                        if let Err(e) = result {
+                            warn!(
+                                "Failed to send an ordered broadcast to {}: {:?}",
+                                machine, e
+                            );
                         }
                     }
-                    actor.on_stop();
-                    env.env.remove(this_actor_id);
-                    break;
-                }
-                Ok(EitherMessage::Special(Token::Reset)) => {
-                    // triggers the optional user-given on_reset function of this actor
-                    actor.on_reset();
-                }
-                Ok(EitherMessage::Regular(msg)) => {
-                    actor.handle(msg);
-                }
-                Ok(EitherMessage::Serialized(msg_serialized)) => {
-                    if let Some(msg) = actor.deserialize_to_any(&msg_serialized) {
-                        actor.handle(msg);
-                    }
-                }
-                Err(recv_error) => {
-                    error!("Actor Mailbox ended! {:?}", recv_error);
-                    // no one holds the sender end anymore (even Environment dropped)
-                    // so it is save to stop here
-                    break;
                 }
-            }
+                Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+            },
+            Err(e) => warn!("Failed to serialize an ordered broadcast: {:?}", e),
         }
+        self.deliver_broadcast(content);
     }
 
-    pub(crate) fn send_expiration_signal(&self) -> Result<(), SendError<()>> {
-        // Send Expiration-Message to remote machines
-        // They will send it back, but we don't care about that since we shut down
-        match self.net_senders.lock() {
-            Ok(mut senders) => {
-                for (_, net_sender) in &mut *senders {
-                    if let Ok(ser_net_msg) = &bincode::serialize(&NetMessage::SendExpirationSignal)
-                    {
-                        // we want to shutdown here, so we don't care about crashed remotes anymore
-                        let _ = net_sender.write(&ser_net_msg);
-                    }
-                }
-                drop(senders);
-            }
-            Err(_e) => return Err(SendError(())),
-        }
-        // send Token::Stop to all actors
-        match self.local_actor_channels.lock() {
-            Ok(local_actor_channels) => {
-                for (_actor_id, actor_sender) in local_actor_channels.iter() {
-                    // we want to shutdown so we don't care about non-responsive actors here
-                    let _ = actor_sender.send(EitherMessage::Special(Token::Stop));
-                }
-            }
-            Err(_) => return Err(SendError(())),
-        }
-        // wait a bit so actors don't try to use stdout during shutdown (causes panic)
-        std::thread::sleep(std::time::Duration::from_millis(500));
-        match self.termination_sender.lock() {
-            Ok(sender) => sender.send(()),
-            Err(_) => Err(SendError(())),
+    /// The size a broadcast *message* is accounted with in the mailbox of *sender*, measured once for all mailboxes that [need it](../message/struct.MailboxSender.html#method.measures_size).
+    fn measured_size<M: Serialize>(
+        &self,
+        sender: &MailboxSender,
+        message: &M,
+        measured: &mut Option<usize>,
+    ) -> usize {
+        if !sender.measures_size() {
+            return 0;
         }
+        *measured.get_or_insert_with(|| self.options.wire_format.serialized_size(message))
     }
 
     /// Send a Message to all known actors located on this environment.
-    pub(crate) fn broadcast<'de, M: Message<'de> + Clone + 'static>(&self, message: M) {
+    ///
+    /// The remote machines check the capabilities of the *caller* that sent it.
+    pub(crate) fn broadcast<'de, M: Message<'de> + Clone + 'static>(
+        &self,
+        message: M,
+        caller: Option<Caller>,
+    ) {
         match self.local_actor_channels.lock() {
             Ok(channels) => {
-                for (_actor_id, sender) in &*channels {
-                    let _ = sender.send(EitherMessage::Regular(Box::new(message.clone())));
+                let mut size = None;
+                let mut local_actors: Vec<(&ActorId, &LocalActor)> = channels.iter().collect();
+                if self.options.ordered_broadcasts {
+                    local_actors.sort_by_key(|(actor_id, _)| *actor_id);
+                }
+                for (_actor_id, local_actor) in local_actors {
+                    let size = self.measured_size(&local_actor.sender, &message, &mut size);
+                    let _ = local_actor
+                        .sender
+                        .send(EitherMessage::Regular(Box::new(message.clone())), size);
                 }
             }
             Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
         }
         match self.net_senders.lock() {
             Ok(mut senders) => {
-                for (_, net_sender) in &mut *senders {
-                    if let Ok(ser_msg) = bincode::serialize(&message) {
-                        if let Ok(ser_net_msg) =
-                            &bincode::serialize(&NetMessage::Broadcast(ser_msg))
-                        {
-                            // if this fails the connection broke down
-                            // nothing we can do here
-                            let _ = net_sender.write(&ser_net_msg);
-                        }
+                if let Ok(ser_msg) = SerializedMessage::of(&message, self.options.wire_format) {
+                    if let Ok(ser_net_msg) = &self
+                        .options
+                        .wire_format
+                        .serialize(&NetMessage::Broadcast(ser_msg, caller))
+                    {
+                        // if this fails the connection broke down
+                        // nothing we can do here
+                        let _ = self.send_to_all_machines(&mut senders, ser_net_msg);
                     }
                 }
             }
@@ -1007,25 +8450,77 @@ impl LocalEnvironment {
     }
 }
 
-/// Simple Round Robin load balancer
+/// The rendezvous weight of a *key* on a *node*, e.g. an Actor id on a machine, the key belongs to the node with the highest weight.
+///
+/// Depends on nothing but the bytes, so every machine computes the same weights.
+pub(crate) fn rendezvous_weight(node: &[u8], key: &[u8]) -> u64 {
+    let mut weight = fnv1a(fnv1a(FNV_OFFSET_BASIS, node), key);
+    // the finalizer of splitmix64 spreads the weights of similar keys over all nodes
+    weight = (weight ^ (weight >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    weight = (weight ^ (weight >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    weight ^ (weight >> 31)
+}
+
+/// A connection to a remote machine that introduced itself.
+struct PeerConnection {
+    machine_id: MachineId,
+    /// The largest message the remote machine accepts
+    max_message_size: usize,
+    sender: NetSender,
+    receiver: NetReceiver,
+}
+
+/// Simple load balancer, either Round Robin or Random
 /// next_machine_no() returns integers from 0 to num_machines excluding,
-/// restarting at 0 after each iteration
+/// restarting at 0 after each iteration when using Round Robin
 #[derive(Debug)]
 struct LoadBalancer {
     counter: usize,
     num_machines: usize,
+    placement: Placement,
 }
 
 impl LoadBalancer {
-    fn new(num_machines: usize) -> Self {
+    fn new(num_machines: usize, placement: Placement) -> Self {
         LoadBalancer {
             counter: 0,
             num_machines,
+            placement,
+        }
+    }
+
+    /// Count a remote machine that connected after the Environment was created.
+    fn add_machine(&mut self) {
+        self.num_machines += 1;
+    }
+
+    /// Returns the machine no for the next Actor, skipping the excluded machine nos.
+    ///
+    /// Returns ```None``` if every machine is excluded.
+    fn next_machine_no(&mut self, excluded: &[usize]) -> Option<usize> {
+        let allowed: Vec<usize> = (0..self.num_machines)
+            .filter(|machine_no| !excluded.contains(machine_no))
+            .collect();
+        if allowed.is_empty() {
+            return None;
+        }
+        match self.placement {
+            Placement::Random => allowed.choose(&mut thread_rng()).copied(),
+            Placement::RoundRobin | Placement::ConsistentHash => {
+                // every machine is visited at least once per round
+                for _ in 0..=self.num_machines {
+                    let machine_no = self.next_round_robin_no();
+                    if allowed.contains(&machine_no) {
+                        return Some(machine_no);
+                    }
+                }
+                allowed.first().copied()
+            }
         }
     }
 
     /// Returns numbers incrementally until num_machines is reached, then restarts at 0.
-    fn next_machine_no(&mut self) -> usize {
+    fn next_round_robin_no(&mut self) -> usize {
         if self.counter < self.num_machines {
             let res = self.counter.clone();
             self.counter += 1;
@@ -9,6 +9,14 @@ pub enum ActlibError {
     InvalidState(String),
     NetworkError(String),
     InvalidActorRef(String),
+    MailboxFull(String),
+    ActorStopping(String),
+    Timeout(String),
+    InvalidId(String),
+    IncompatibleBuild(String),
+    PermissionDenied(String),
+    MessageTooLarge(String),
+    ShuttingDown(String),
 }
 
 impl ActlibError {
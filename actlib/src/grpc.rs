@@ -0,0 +1,335 @@
+//! This module bridges registered service [Actors](../actor/trait.Actor.html) to gRPC, so components not written in Rust can talk to them.
+//!
+//! Only available with the ```grpc``` feature. The interface is defined in ```proto/actor_bridge.proto```:
+//!
+//! - ```Ask``` sends a request to a service Actor and waits for a single reply.
+//! - ```Subscribe``` sends a request to a service Actor and streams every reply until the Actor [finishes](struct.GrpcCall.html#method.finish) the call.
+//!
+//! A service Actor handles the [GrpcCall](struct.GrpcCall.html) message and answers with [reply](struct.GrpcCall.html#method.reply).
+//! Service Actors have to live on the machine that runs the bridge.
+//!
+//! ```rust,ignore
+//! impl_message_handler!(SensorActor: GrpcCall => SensorActor::handle_call);
+//!
+//! let bridge = GrpcBridge::new().register("sensors", sensor_actor_ref);
+//! bridge.serve("0.0.0.0:50051".parse().unwrap());
+//! ```
+
+use crate::actor::ActorRef;
+use crate::api::ActlibError;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::codegen::*;
+use tonic::{Request, Response, Status};
+
+/// A request from a gRPC client, sent to the registered service Actor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcCall {
+    /// Identifies the call when replying.
+    pub call_id: u64,
+    /// The method given by the client.
+    pub method: String,
+    /// The payload given by the client, its encoding is up to client and Actor.
+    pub payload: Vec<u8>,
+    /// ```true``` for ```Subscribe``` calls that accept any number of replies.
+    pub streaming: bool,
+}
+
+impl GrpcCall {
+    /// Answer the call.
+    ///
+    /// An ```Ask``` call is completed by its first reply, a ```Subscribe``` call stays open until [finish](#method.finish) is called.
+    ///
+    /// Returns ```false``` if the client is gone, e.g. because it cancelled the call or ran into its timeout.
+    pub fn reply(&self, payload: Vec<u8>) -> bool {
+        let mut calls = match pending_calls().lock() {
+            Ok(calls) => calls,
+            Err(e) => {
+                error!("{:?}", ActlibError::from_poison_error(&e));
+                return false;
+            }
+        };
+        match calls.remove(&self.call_id) {
+            Some(PendingCall::Unary(sender)) => sender.send(payload).is_ok(),
+            Some(PendingCall::Streaming(sender)) => {
+                let open = sender.send(Ok(ActorReply { payload })).is_ok();
+                if open {
+                    calls.insert(self.call_id, PendingCall::Streaming(sender));
+                }
+                open
+            }
+            None => false,
+        }
+    }
+
+    /// End a ```Subscribe``` call, the client's stream ends after all previous replies.
+    pub fn finish(&self) {
+        match pending_calls().lock() {
+            Ok(mut calls) => {
+                calls.remove(&self.call_id);
+            }
+            Err(e) => error!("{:?}", ActlibError::from_poison_error(&e)),
+        }
+    }
+}
+
+/// Where the replies of an open call go.
+#[derive(Debug)]
+enum PendingCall {
+    Unary(oneshot::Sender<Vec<u8>>),
+    Streaming(mpsc::UnboundedSender<Result<ActorReply, Status>>),
+}
+
+/// Source of the ids of GrpcCalls.
+static NEXT_CALL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Every call waiting for replies, shared by all bridges of this process.
+fn pending_calls() -> &'static Mutex<HashMap<u64, PendingCall>> {
+    static PENDING_CALLS: OnceLock<Mutex<HashMap<u64, PendingCall>>> = OnceLock::new();
+    PENDING_CALLS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Request of the ActorBridge gRPC service.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ActorRequest {
+    /// Name the service Actor was registered with.
+    #[prost(string, tag = "1")]
+    pub service: String,
+    /// Passed on to the service Actor.
+    #[prost(string, tag = "2")]
+    pub method: String,
+    /// Passed on to the service Actor.
+    #[prost(bytes = "vec", tag = "3")]
+    pub payload: Vec<u8>,
+}
+
+/// Reply of the ActorBridge gRPC service.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ActorReply {
+    /// Given by the service Actor.
+    #[prost(bytes = "vec", tag = "1")]
+    pub payload: Vec<u8>,
+}
+
+/// Maps gRPC requests to registered service Actors.
+#[derive(Debug, Clone)]
+pub struct GrpcBridge {
+    services: HashMap<String, ActorRef>,
+    ask_timeout: Duration,
+}
+
+impl Default for GrpcBridge {
+    fn default() -> Self {
+        GrpcBridge {
+            services: HashMap::new(),
+            ask_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl GrpcBridge {
+    /// Create a bridge without any services.
+    pub fn new() -> Self {
+        GrpcBridge::default()
+    }
+
+    /// Make the Actor available to gRPC clients under the given service name.
+    pub fn register(mut self, service: &str, actor_ref: ActorRef) -> Self {
+        self.services.insert(service.to_string(), actor_ref);
+        self
+    }
+
+    /// How long an ```Ask``` call waits for the reply, 5 seconds by default.
+    pub fn ask_timeout(mut self, timeout: Duration) -> Self {
+        self.ask_timeout = timeout;
+        self
+    }
+
+    /// Serve the registered services on the given address.
+    ///
+    /// The server runs on its own thread, the current thread is not blocked.
+    pub fn serve(self, addr: SocketAddr) {
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    error!("Failed to start the gRPC bridge runtime: {:?}", e);
+                    return;
+                }
+            };
+            info!("Serving gRPC bridge on {}", addr);
+            let server = ActorBridgeServer {
+                bridge: Arc::new(self),
+            };
+            if let Err(e) = runtime.block_on(
+                tonic::transport::Server::builder()
+                    .add_service(server)
+                    .serve(addr),
+            ) {
+                error!("gRPC bridge stopped: {:?}", e);
+            }
+        });
+    }
+
+    /// Send a new GrpcCall to the requested service Actor, registering where its replies go.
+    ///
+    /// The Status is boxed, it is large compared to the call id.
+    fn dispatch(&self, request: ActorRequest, pending: PendingCall) -> Result<u64, Box<Status>> {
+        let actor_ref = match self.services.get(&request.service) {
+            Some(actor_ref) => actor_ref,
+            None => {
+                return Err(Box::new(Status::not_found(format!(
+                    "Unknown service {}",
+                    request.service
+                ))))
+            }
+        };
+        let call = GrpcCall {
+            call_id: NEXT_CALL_ID.fetch_add(1, Ordering::Relaxed),
+            method: request.method,
+            payload: request.payload,
+            streaming: match pending {
+                PendingCall::Unary(_) => false,
+                PendingCall::Streaming(_) => true,
+            },
+        };
+        let call_id = call.call_id;
+        match pending_calls().lock() {
+            Ok(mut calls) => {
+                calls.insert(call_id, pending);
+            }
+            Err(e) => return Err(Box::new(Status::internal(format!("{:?}", e)))),
+        }
+        if let Err(e) = actor_ref.send_message(call) {
+            forget_call(call_id);
+            return Err(Box::new(Status::unavailable(format!("{:?}", e))));
+        }
+        Ok(call_id)
+    }
+
+    async fn ask(&self, request: ActorRequest) -> Result<ActorReply, Status> {
+        let (sender, receiver) = oneshot::channel();
+        let call_id = self
+            .dispatch(request, PendingCall::Unary(sender))
+            .map_err(|status| *status)?;
+        match tokio::time::timeout(self.ask_timeout, receiver).await {
+            Ok(Ok(payload)) => Ok(ActorReply { payload }),
+            Ok(Err(_)) => Err(Status::aborted("The service Actor dropped the call")),
+            Err(_) => {
+                forget_call(call_id);
+                Err(Status::deadline_exceeded(format!(
+                    "No reply within {:?}",
+                    self.ask_timeout
+                )))
+            }
+        }
+    }
+
+    fn subscribe(
+        &self,
+        request: ActorRequest,
+    ) -> Result<UnboundedReceiverStream<Result<ActorReply, Status>>, Box<Status>> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.dispatch(request, PendingCall::Streaming(sender))?;
+        Ok(UnboundedReceiverStream::new(receiver))
+    }
+}
+
+/// Drop a call nobody waits for anymore.
+fn forget_call(call_id: u64) {
+    if let Ok(mut calls) = pending_calls().lock() {
+        calls.remove(&call_id);
+    }
+}
+
+/// The tonic service implementing ```actlib.ActorBridge```, as generated from ```proto/actor_bridge.proto```.
+#[derive(Debug, Clone)]
+struct ActorBridgeServer {
+    bridge: Arc<GrpcBridge>,
+}
+
+impl tonic::server::NamedService for ActorBridgeServer {
+    const NAME: &'static str = "actlib.ActorBridge";
+}
+
+impl<B> Service<http::Request<B>> for ActorBridgeServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<tonic::body::BoxBody>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let bridge = self.bridge.clone();
+        match req.uri().path() {
+            "/actlib.ActorBridge/Ask" => Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                Ok(grpc.unary(AskService(bridge), req).await)
+            }),
+            "/actlib.ActorBridge/Subscribe" => Box::pin(async move {
+                let mut grpc = tonic::server::Grpc::new(tonic::codec::ProstCodec::default());
+                Ok(grpc.server_streaming(SubscribeService(bridge), req).await)
+            }),
+            _ => Box::pin(async move {
+                // gRPC reports errors with status 200 and the code in the headers
+                let mut response = http::Response::new(empty_body());
+                let headers = response.headers_mut();
+                headers.insert(
+                    "grpc-status",
+                    http::HeaderValue::from(tonic::Code::Unimplemented as i32),
+                );
+                headers.insert(
+                    http::header::CONTENT_TYPE,
+                    tonic::metadata::GRPC_CONTENT_TYPE,
+                );
+                Ok(response)
+            }),
+        }
+    }
+}
+
+struct AskService(Arc<GrpcBridge>);
+
+impl tonic::server::UnaryService<ActorRequest> for AskService {
+    type Response = ActorReply;
+    type Future = BoxFuture<Response<ActorReply>, Status>;
+
+    fn call(&mut self, request: Request<ActorRequest>) -> Self::Future {
+        let bridge = self.0.clone();
+        Box::pin(async move { bridge.ask(request.into_inner()).await.map(Response::new) })
+    }
+}
+
+struct SubscribeService(Arc<GrpcBridge>);
+
+impl tonic::server::ServerStreamingService<ActorRequest> for SubscribeService {
+    type Response = ActorReply;
+    type ResponseStream = UnboundedReceiverStream<Result<ActorReply, Status>>;
+    type Future = BoxFuture<Response<Self::ResponseStream>, Status>;
+
+    fn call(&mut self, request: Request<ActorRequest>) -> Self::Future {
+        let bridge = self.0.clone();
+        Box::pin(async move {
+            bridge
+                .subscribe(request.into_inner())
+                .map(Response::new)
+                .map_err(|status| *status)
+        })
+    }
+}
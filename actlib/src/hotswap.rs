@@ -0,0 +1,319 @@
+//! This module loads [Actor](../actor/trait.Actor.html) implementations from dynamic libraries, so a running cluster can pick up a new version of a handler.
+//!
+//! Only available with the ```hotswap``` feature.
+//!
+//! A plugin library is loaded for an Actor type id with [load_plugin](fn.load_plugin.html).
+//! Every [PluginActor](struct.PluginActor.html) spawned afterwards uses that library, Actors spawned earlier keep the version they were built with.
+//! Loading a library again for the same type id replaces it for subsequently spawned Actors, without restarting the process.
+//!
+//! ```rust,ignore
+//! let actor_builder = actor_builder!("Field" => PluginActor::new("Field"));
+//! load_plugin("Field", "./target/release/libfield_v2.so")?;
+//! ```
+//!
+//! # The C ABI
+//!
+//! Rust trait objects have no stable layout across compilations, so plugins export plain C functions
+//! and exchange messages serialized with ```bincode```:
+//!
+//! ```c
+//! // has to return ACTLIB_PLUGIN_ABI_VERSION
+//! uint32_t actlib_plugin_abi_version(void);
+//! // create the state of a new Actor of the given type id, NULL if the type id is unknown
+//! void *actlib_plugin_create(const uint8_t *type_id, size_t type_id_len);
+//! // handle a serialized message, messages to other Actors are sent with host->send
+//! void actlib_plugin_handle(void *state, const uint8_t *message, size_t message_len, const ActlibHost *host);
+//! // release the state once the Actor is gone
+//! void actlib_plugin_destroy(void *state);
+//! ```
+//!
+//! The state returned by ```actlib_plugin_create``` has to be thread-agnostic: its Actor never handles two messages at once,
+//! but may move to another worker thread between two messages, and may be destroyed on yet another one.
+//! Plugins must not keep thread-local data or thread-bound handles in it.
+//!
+//! Messages are passed to the plugin exactly as received from a remote machine, the bincode serialization of the sender's message type.
+//! Local senders use [send_serialized](../actor/struct.ActorRef.html#method.send_serialized), typed local messages can't be passed on and are dropped with a warning.
+
+use crate::actor::{Actor, ActorId, ActorRef};
+use crate::api::{ActlibError, Environment};
+use crate::message::MessageHandler;
+use libloading::Library;
+use log::{error, info, warn};
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// The ABI version a plugin has to report, changed whenever the C ABI changes.
+pub const ACTLIB_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Handed to ```actlib_plugin_handle```, lets the plugin act on behalf of its Actor.
+#[repr(C)]
+pub struct ActlibHost {
+    /// Opaque, passed back to *send*.
+    pub context: *mut c_void,
+    /// The bincode serialized [ActorId](../actor/struct.ActorId.html) of the handling Actor.
+    pub own_id: *const u8,
+    /// Length of *own_id*.
+    pub own_id_len: usize,
+    /// Send a serialized message to the Actor with the given bincode serialized ActorId, returns 0 on success.
+    pub send: extern "C" fn(
+        context: *mut c_void,
+        target: *const u8,
+        target_len: usize,
+        message: *const u8,
+        message_len: usize,
+    ) -> i32,
+}
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type CreateFn = unsafe extern "C" fn(type_id: *const u8, type_id_len: usize) -> *mut c_void;
+type HandleFn = unsafe extern "C" fn(
+    state: *mut c_void,
+    message: *const u8,
+    message_len: usize,
+    host: *const ActlibHost,
+);
+type DestroyFn = unsafe extern "C" fn(state: *mut c_void);
+
+/// A loaded plugin library, kept loaded until every Actor built from it is gone.
+#[derive(Debug)]
+struct PluginLibrary {
+    path: String,
+    /// Counts the libraries loaded for the same type id, starting at 1
+    version: u64,
+    create: CreateFn,
+    handle: HandleFn,
+    destroy: DestroyFn,
+    // dropped last, the function pointers above point into it
+    _library: Library,
+}
+
+impl PluginLibrary {
+    fn load(path: &Path, version: u64) -> Result<Self, ActlibError> {
+        let load_error = |e: libloading::Error| {
+            ActlibError::InvalidState(format!("Failed to load plugin {:?}: {:?}", path, e))
+        };
+        // SAFETY: loading a library runs its initialisation code, plugins are trusted like the rest of the binary
+        unsafe {
+            let library = Library::new(path).map_err(load_error)?;
+            let abi_version = *library
+                .get::<AbiVersionFn>(b"actlib_plugin_abi_version\0")
+                .map_err(load_error)?;
+            if abi_version() != ACTLIB_PLUGIN_ABI_VERSION {
+                return Err(ActlibError::InvalidState(format!(
+                    "Plugin {:?} implements ABI version {}, expected {}",
+                    path,
+                    abi_version(),
+                    ACTLIB_PLUGIN_ABI_VERSION
+                )));
+            }
+            let create = *library
+                .get::<CreateFn>(b"actlib_plugin_create\0")
+                .map_err(load_error)?;
+            let handle = *library
+                .get::<HandleFn>(b"actlib_plugin_handle\0")
+                .map_err(load_error)?;
+            let destroy = *library
+                .get::<DestroyFn>(b"actlib_plugin_destroy\0")
+                .map_err(load_error)?;
+            Ok(PluginLibrary {
+                path: path.display().to_string(),
+                version,
+                create,
+                handle,
+                destroy,
+                _library: library,
+            })
+        }
+    }
+}
+
+/// The current library of every type id, shared by all Environments of this process.
+fn plugins() -> &'static Mutex<HashMap<String, Arc<PluginLibrary>>> {
+    static PLUGINS: OnceLock<Mutex<HashMap<String, Arc<PluginLibrary>>>> = OnceLock::new();
+    PLUGINS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Load the plugin library at *path* for every [PluginActor](struct.PluginActor.html) of the given type id spawned from now on.
+///
+/// Returns the version of the loaded library, counting the libraries loaded for the type id.
+/// Fails with [InvalidState](../api/enum.ActlibError.html#variant.InvalidState) if the library can't be loaded or implements another ABI version,
+/// the previous library stays in use then.
+pub fn load_plugin<P: AsRef<Path>>(actor_type_id: &str, path: P) -> Result<u64, ActlibError> {
+    let mut plugins = plugins()
+        .lock()
+        .map_err(|e| ActlibError::from_poison_error(&e))?;
+    let version = plugins
+        .get(actor_type_id)
+        .map_or(1, |previous| previous.version + 1);
+    let library = PluginLibrary::load(path.as_ref(), version)?;
+    info!(
+        "Loaded plugin {} version {} for {}",
+        library.path, version, actor_type_id
+    );
+    plugins.insert(actor_type_id.to_string(), Arc::new(library));
+    Ok(version)
+}
+
+/// The plugin state of an Actor, owned by that Actor.
+struct PluginState(*mut c_void);
+
+// SAFETY: the state is only used by the Actor owning it, which handles one message at a time.
+// Between two turns the Actor may move to another worker thread, so plugins must not tie their state to a thread,
+// as required by the plugin ABI.
+unsafe impl Send for PluginState {}
+
+/// An Actor whose handler is implemented by the plugin library loaded for its type id, see the [module documentation](index.html).
+pub struct PluginActor {
+    type_id: String,
+    library: Option<Arc<PluginLibrary>>,
+    state: PluginState,
+    env: Option<Environment>,
+    own_id: Vec<u8>,
+}
+
+impl std::fmt::Debug for PluginActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PluginActor {{type_id: {:?}, library: {:?}}}",
+            self.type_id,
+            self.library
+                .as_ref()
+                .map(|library| (&library.path, library.version))
+        )
+    }
+}
+
+impl PluginActor {
+    /// Build an Actor from the library currently loaded for the given type id.
+    ///
+    /// Without a library, or if the library doesn't know the type id, every message is dropped with a warning.
+    pub fn new(actor_type_id: &str) -> Self {
+        let library = match plugins().lock() {
+            Ok(plugins) => plugins.get(actor_type_id).cloned(),
+            Err(e) => {
+                error!("{:?}", ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        let state = match &library {
+            // SAFETY: the plugin only reads type_id.len() bytes
+            Some(library) => unsafe {
+                (library.create)(actor_type_id.as_ptr(), actor_type_id.len())
+            },
+            None => std::ptr::null_mut(),
+        };
+        PluginActor {
+            type_id: actor_type_id.to_string(),
+            library,
+            state: PluginState(state),
+            env: None,
+            own_id: Vec::new(),
+        }
+    }
+
+    /// The version of the library this Actor was built from, ```None``` if no library was loaded.
+    pub fn plugin_version(&self) -> Option<u64> {
+        self.library.as_ref().map(|library| library.version)
+    }
+
+    fn handle_serialized(&mut self, message: &[u8]) {
+        let (library, env) = match (&self.library, &self.env) {
+            (Some(library), Some(env)) if !self.state.0.is_null() => (library, env),
+            _ => {
+                warn!(
+                    "No plugin handles messages of {}, message dropped.",
+                    self.type_id
+                );
+                return;
+            }
+        };
+        let host = ActlibHost {
+            context: env as *const Environment as *mut c_void,
+            own_id: self.own_id.as_ptr(),
+            own_id_len: self.own_id.len(),
+            send: host_send,
+        };
+        // SAFETY: the state was created by this library, the host and message outlive the call
+        unsafe { (library.handle)(self.state.0, message.as_ptr(), message.len(), &host) };
+    }
+}
+
+impl Drop for PluginActor {
+    fn drop(&mut self) {
+        if let Some(library) = &self.library {
+            if !self.state.0.is_null() {
+                // SAFETY: the state was created by this library and is not used anymore
+                unsafe { (library.destroy)(self.state.0) };
+            }
+        }
+    }
+}
+
+/// Wraps serialized messages, so they reach the plugin without knowing their type.
+struct Serialized(Vec<u8>);
+
+impl MessageHandler for PluginActor {
+    fn handle(&mut self, message: Box<dyn Any>) {
+        match message.downcast::<Serialized>() {
+            Ok(serialized) => self.handle_serialized(&serialized.0),
+            Err(_) => warn!(
+                "{} can't pass a typed local message to its plugin, use send_serialized. Message dropped.",
+                self.type_id
+            ),
+        }
+    }
+
+    fn deserialize_to_any(&self, message: &[u8]) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(Serialized(message.to_vec())))
+    }
+}
+
+impl Actor for PluginActor {
+    fn on_start(&mut self, local_env: Environment, own_ref: ActorRef) {
+        match bincode::serialize(&own_ref.clone_id()) {
+            Ok(own_id) => self.own_id = own_id,
+            Err(e) => error!("Failed to serialize the id of a PluginActor: {:?}", e),
+        }
+        self.env = Some(local_env);
+        if self.state.0.is_null() {
+            warn!(
+                "No plugin loaded for {}, the Actor ignores every message.",
+                self.type_id
+            );
+        }
+    }
+}
+
+/// ```ActlibHost::send``` of every PluginActor.
+extern "C" fn host_send(
+    context: *mut c_void,
+    target: *const u8,
+    target_len: usize,
+    message: *const u8,
+    message_len: usize,
+) -> i32 {
+    // SAFETY: context is the Environment of the handling PluginActor, valid during actlib_plugin_handle,
+    // target and message are valid for the given lengths as promised by the plugin
+    let (env, target, message) = unsafe {
+        (
+            &*(context as *const Environment),
+            std::slice::from_raw_parts(target, target_len),
+            std::slice::from_raw_parts(message, message_len),
+        )
+    };
+    let result = bincode::deserialize::<ActorId>(target)
+        .map_err(|e| ActlibError::InvalidActorRef(format!("{:?}", e)))
+        .and_then(|target| env.to_actor_ref(target))
+        .and_then(|target| target.send_serialized(message.to_vec()));
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            warn!("Plugin failed to send a message: {:?}", e);
+            -1
+        }
+    }
+}
@@ -4,7 +4,7 @@
 //! and the ability to send [messages](./message/trait.Message.html) to other locally known actors
 //! as well as [spawn](./api/struct.Environment.html#method.spawn) new actors on demand.
 //!
-//! Actors can perform required initialization and cleanup by implementing the ```on_start```, ```on_reset``` and ```on_stop``` methods.
+//! Actors can perform required initialization and cleanup by implementing the ```on_start```, ```on_reset``` and ```on_stop_with_reason``` methods.
 //!
 //! The library furthermore provides a means of abstraction where the [Environment](./api/struct.Environment.html)
 //! that holds the actors can be seamlessly distributed along multiple machines without changing the API usage
@@ -36,7 +36,7 @@
 //!         }
 //!     }
 //!
-//!     // Each Actor has its own thread to handle messages.
+//!     // The Actors handle their messages on a pool of worker threads.
 //!     // Since all of them terminate once the main function finishes
 //!     // we have to block the current thread until the ```Environment::set_expired()```-method is called.
 //!     // Note: This doesn't happen here, so we block indefinitely (until the user hits 'Ctrl+C').
@@ -47,7 +47,31 @@
 //! ```
 
 pub mod actor;
+#[cfg(feature = "amqp")]
+pub mod amqp;
 pub mod api;
+pub mod builtin;
+pub mod counters;
+pub(crate) mod durable_outbox;
 pub(crate) mod environment;
 pub(crate) mod errors;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "hotswap")]
+pub mod hotswap;
 pub mod message;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub(crate) mod options;
+pub mod router;
+#[cfg(feature = "scenarios")]
+pub mod scenarios;
+pub(crate) mod scheduler;
+pub mod session;
+#[cfg(feature = "stress")]
+pub mod stress;
+#[cfg(feature = "streams")]
+pub mod subscription;
+pub(crate) mod supervision;
+pub(crate) mod timer;
+pub mod wire;
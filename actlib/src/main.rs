@@ -26,8 +26,8 @@ impl Actor for ExampleActor {
         println!("{:?}", "ON_START called");
     }
 
-    fn on_stop(&mut self) {
-        println!("{:?}", "ON_STOP called.");
+    fn on_stop_with_reason(&mut self, reason: StopReason) {
+        println!("ON_STOP called: {:?}", reason);
     }
 }
 
@@ -69,7 +69,7 @@ impl Actor for StateActor {
         );
     }
 
-    fn on_stop(&mut self) {
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
         println!(
             "Goodbye from {:?}. My final state is: {}",
             self.own_ref.as_ref().unwrap().clone_id(),
@@ -87,11 +87,13 @@ impl Actor for StateActor {
     }
 }
 
-fn state_handle_ping(mut actor: &mut StateActor, Ping(i, _str): &Ping) {
+fn state_handle_ping(actor: &mut StateActor, Ping(i, _str): &Ping) {
     println!("Received message {} with state {}.", i, actor.state);
     actor.state = *i;
     if let Some(self_ref) = &actor.own_ref {
-        self_ref.send_message(QueryState);
+        if let Err(e) = self_ref.send_message(QueryState) {
+            println!("Could not query the own state: {:?}", e);
+        }
     }
 }
 
@@ -123,7 +125,8 @@ fn wait_a_bit() {
 //                     if num_bytes == 0 {
 //                         break;
 //                     }
-//                     if let Ok(env_serialize) = bincode::serialize(env_clone.get_state()) {
+//                     let snapshot = env_clone.snapshot(time::Duration::from_secs(1));
+//                     if let Ok(env_serialize) = bincode::serialize(&snapshot) {
 //                         writer.write(&env_serialize[..]);
 //                     }
 //                 }
@@ -139,6 +142,20 @@ fn main() {
         Ok(hostname) => hostname.into_string().unwrap(),
         Err(error) => panic!("{:?}", error),
     };
+    // the config and the traffic between the machines use the same format, bincode unless ACTLIB_WIRE_FORMAT names another
+    let wire_format: WireFormat = match std::env::var("ACTLIB_WIRE_FORMAT") {
+        Ok(name) => match name.parse() {
+            Ok(wire_format) => wire_format,
+            Err(_) => {
+                eprintln!(
+                    "Unknown ACTLIB_WIRE_FORMAT {:?}, expected bincode, json or msgpack",
+                    name
+                );
+                std::process::exit(1);
+            }
+        },
+        Err(_) => WireFormat::default(),
+    };
     // load remote machines from a configuration file
     let mut cfg = match File::open("./machines.cfg") {
         Ok(cfg) => cfg,
@@ -151,13 +168,13 @@ fn main() {
             ),
         },
     };
-    let mut cfg_contents: [u8; 1024] = [0; 1024];
-    if let Err(e) = cfg.read(&mut cfg_contents) {
+    let mut cfg_contents = Vec::new();
+    if let Err(e) = cfg.read_to_end(&mut cfg_contents) {
         panic!("Failed to read config: {}", e);
     };
-    let remotes = match bincode::deserialize::<[SocketAddr; 2]>(&cfg_contents) {
+    let remotes = match wire_format.deserialize::<[SocketAddr; 2]>(&cfg_contents) {
         Ok(remotes) => remotes.to_vec(),
-        Err(e) => panic!("Desrealisation of config failed: {}", e),
+        Err(e) => panic!("Desrealisation of config failed: {:?}", e),
     };
 
     println!("actlib main: {:?}, we are {:?}", remotes, hostname);
@@ -173,18 +190,30 @@ fn main() {
     );
 
     // let env = Environment::new(&remotes);
-    let (mut env, expiration_checker) = Environment::new(4020, &remotes, actor_builder);
+    let peers: Vec<Peer> = remotes.iter().cloned().map(Peer::from).collect();
+    let mut initial_actors = None;
+    let (mut env, expiration_checker) = Environment::new_with_bootstrap(
+        4020,
+        &peers,
+        actor_builder,
+        EnvironmentOptions::new()
+            // StateActor sends QueryState to itself, warn if this ever turns into a loop
+            .self_send_guard(SelfSendGuard::default())
+            .wire_format(wire_format),
+        |env| initial_actors = Some((env.spawn("ExampleActor"), env.spawn("StateActor"))),
+    );
     // let (mut env, expiration_checker) = Environment::new_local_only(actor_builder);
     // let env_clone = env.clone();
     // std::thread::spawn(move || {
     //     control_listener(control_remote, env_clone);
     // });
 
-    if &hostname == "agakauitai" {
+    // only the bootstrap machine spawned the Actors
+    if let Some((spawned_example, spawned_state)) = initial_actors {
         let actor_example;
         let actor_state;
 
-        match env.spawn("ExampleActor") {
+        match spawned_example {
             Ok(actor_ref) => {
                 actor_example = actor_ref;
             }
@@ -194,7 +223,7 @@ fn main() {
             }
         }
 
-        match env.spawn("StateActor") {
+        match spawned_state {
             Ok(actor_ref) => {
                 actor_state = actor_ref;
             }
@@ -244,12 +273,21 @@ fn main() {
         println!("done.");
 
         // set expire (comment to check if it blocks otherwise)
-        
+
         let _ignored = env.set_expired();
     }
     // wait until expiration
     println!("Wait until expiration.");
-    if let Err(e) = expiration_checker.wait_until_expiration() {
-        panic!("Something went wrong: {:?}", e);
+    match expiration_checker.wait_until_expiration() {
+        // tell which on_stop methods actually ran
+        Ok(result) => {
+            for record in result.stop_records() {
+                println!(
+                    "{} {:?}: {:?}",
+                    record.type_id, record.actor, record.outcome
+                );
+            }
+        }
+        Err(e) => panic!("Something went wrong: {:?}", e),
     }
 }
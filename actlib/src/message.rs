@@ -1,12 +1,25 @@
 //! This module defines traits describing the ability to be passed as, or receive a [Message](trait.Message.html).
 
 use crate::actor::*;
+use crate::api::{ActlibError, DrainReport, MachineLoad};
+use crate::counters::CounterShares;
 pub use crate::impl_message_handler;
+use crate::options::{BroadcastSlices, Capability, MailboxQuota, QuotaPolicy};
+use crate::wire::WireFormat;
+use log::warn;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::any::Any;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
-use std::net::IpAddr;
-use std::sync::mpsc::{Receiver, RecvError};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use uuid::Uuid;
 
 /// Trait to enable types to [handle](#tymethod.handle) [Messages](trait.Message.html).
 ///
@@ -19,6 +32,28 @@ pub trait MessageHandler {
     /// **Note:** It is expected that this function terminates.
     fn handle(&mut self, message: Box<dyn Any>);
 
+    /// Like [handle](#tymethod.handle), but the message is only borrowed, returning ```false``` if borrowed messages are not supported.
+    ///
+    /// Required to [stash](../actor/struct.Context.html#method.stash) messages.
+    /// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements this method, the default supports nothing.
+    fn handle_ref(&mut self, _message: &dyn Any) -> bool {
+        false
+    }
+
+    /// Start the async handler for the *message*, handing the message back if its type has no async handler.
+    ///
+    /// The returned future borrows the Actor until it completes, the next message is only taken from the mailbox afterwards.
+    /// On the async [Runtime](../api/enum.Runtime.html) the task of the Actor awaits it, so the other Actors use the worker thread meanwhile,
+    /// on the other runtimes the thread of the Actor is blocked until it completes.
+    /// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements this method for the handlers listed after ```; async```,
+    /// the default has no async handlers.
+    fn handle_async(
+        &mut self,
+        message: Box<dyn Any + Send>,
+    ) -> Result<HandlerFuture<'_>, Box<dyn Any + Send>> {
+        Err(message)
+    }
+
     /// Specify how to deserialize a message to an ```std::any::Any``` trait object.
     ///
     /// This method is called, before an incoming message from an external environment is relayed to a local actor.
@@ -28,6 +63,320 @@ pub trait MessageHandler {
     ///
     /// **Note:** It is expected that this function terminates.
     fn deserialize_to_any(&self, message: &[u8]) -> Option<Box<dyn Any + Send>>;
+
+    /// Decode a message in the [WireFormat](../wire/enum.WireFormat.html) *format* whose type is known by its [tag](fn.message_tag.html),
+    /// returning ```None``` if the type is not handled.
+    ///
+    /// Messages sent with [send_message](../actor/struct.ActorRef.html#method.send_message) carry the tag of their type,
+    /// so structurally similar types, e.g. two newtypes of ```u64```, are not mistaken for one another.
+    /// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements this method, the default ignores tag and format,
+    /// falling back to [deserialize_to_any](#tymethod.deserialize_to_any).
+    fn deserialize_tagged(
+        &self,
+        _tag: u64,
+        message: &[u8],
+        _format: WireFormat,
+    ) -> Option<Box<dyn Any + Send>> {
+        self.deserialize_to_any(message)
+    }
+
+    /// Give [queries](../actor/struct.ActorRef.html#method.query) access to the concrete Actor type.
+    ///
+    /// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements this method, the default refuses every query.
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+
+    /// The name of the message's type, used for the [handler statistics](../api/struct.HandlerStats.html).
+    ///
+    /// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements this method, the default knows no names.
+    fn message_type_name(&self, _message: &dyn Any) -> Option<&'static str> {
+        None
+    }
+
+    /// The names of all message types this Actor type handles, in the order they are tried, used for the [build fingerprint](../api/struct.ActorBuilder.html#method.fingerprint).
+    ///
+    /// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements this function, the default knows no names.
+    fn message_type_names() -> &'static [&'static str]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+}
+
+/// The future of an [async handler](trait.MessageHandler.html#method.handle_async), borrowing its Actor until it completes.
+pub type HandlerFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+/// The [message type names](trait.MessageHandler.html#method.message_type_names) of the Actor type built by *new_actor*, which is never called.
+///
+/// Lets the [actor_builder!](../macro.actor_builder.html)-macro learn the message types at compile time.
+#[doc(hidden)]
+pub fn message_types_of<A: MessageHandler, F: FnOnce() -> A>(
+    _new_actor: F,
+) -> &'static [&'static str] {
+    A::message_type_names()
+}
+
+/// Marks that an Actor type handles Messages of type *M*, so a [TypedActorRef](../actor/struct.TypedActorRef.html) may send them.
+///
+/// The [impl_message_handler!](../macro.impl_message_handler.html)-macro implements it for every listed message type.
+pub trait Handles<M> {}
+
+/// A bundle of handlers shared by several Actor types, e.g. for the system Messages every Actor answers.
+///
+/// Define one with the [handler_set!](../macro.handler_set.html)-macro and mix it into the
+/// [impl_message_handler!](../macro.impl_message_handler.html) of every Actor type *A* using it.
+pub trait HandlerSet<A> {
+    /// Call the handler for the *message*, returning ```false``` if the set has none for its type.
+    fn handle(actor: &mut A, message: &dyn Any) -> bool;
+
+    /// Deserialize the *message* to the first type of the set it can be deserialized to.
+    fn deserialize_to_any(message: &[u8]) -> Option<Box<dyn Any + Send>>;
+
+    /// Decode the *message* in the *format* to the type of the set with the [tag](fn.message_tag.html), if there is one.
+    ///
+    /// The [handler_set!](../macro.handler_set.html)-macro implements this function, the default ignores tag and format,
+    /// falling back to [deserialize_to_any](#tymethod.deserialize_to_any).
+    fn deserialize_tagged(
+        _tag: u64,
+        message: &[u8],
+        _format: WireFormat,
+    ) -> Option<Box<dyn Any + Send>> {
+        Self::deserialize_to_any(message)
+    }
+
+    /// The name of the message's type, if the set handles it.
+    fn message_type_name(message: &dyn Any) -> Option<&'static str>;
+
+    /// The names of all message types the set handles, in the order they are tried.
+    fn message_type_names() -> &'static [&'static str];
+}
+
+/// Handlers registered one Message type at a time, an alternative to the [impl_message_handler!](../macro.impl_message_handler.html)-macro.
+///
+/// An Actor type returns its registry from [RegisteredHandlers::handlers](trait.RegisteredHandlers.html#tymethod.handlers),
+/// so handlers can be registered conditionally and registries from several modules combined [with](#method.with) each other.
+///
+/// ```rust,ignore
+/// impl RegisteredHandlers for Field {
+///     fn handlers() -> HandlerRegistry<Self> {
+///         let registry = HandlerRegistry::<Self>::new()
+///             .on::<PlayerEnters>(|field, enters, _ctx| field.enter(enters))
+///             .with(movement::handlers());
+///         if cfg!(feature = "debug-view") {
+///             return registry.on::<Inspect>(|field, _, _ctx| field.inspect());
+///         }
+///         registry
+///     }
+/// }
+/// ```
+pub struct HandlerRegistry<A> {
+    handlers: Vec<RegisteredHandler<A>>,
+}
+
+/// Calls the handler of a single Message type with a message of that type.
+type RegisteredHandle<A> = Box<dyn Fn(&mut A, &dyn Any) + Send + Sync>;
+
+/// Deserializes a message of a single Message type from the given format.
+type RegisteredDeserialize = fn(&[u8], WireFormat) -> Option<Box<dyn Any + Send>>;
+
+/// The handler of a single Message type.
+struct RegisteredHandler<A> {
+    type_id: TypeId,
+    type_name: &'static str,
+    /// The message_tag of the type
+    tag: u64,
+    handle: RegisteredHandle<A>,
+    deserialize: RegisteredDeserialize,
+}
+
+impl<A> Debug for HandlerRegistry<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HandlerRegistry")
+            .field("message_types", &self.message_type_names())
+            .finish()
+    }
+}
+
+impl<A> Default for HandlerRegistry<A> {
+    fn default() -> Self {
+        HandlerRegistry {
+            handlers: Vec::new(),
+        }
+    }
+}
+
+impl<A> HandlerRegistry<A> {
+    /// Create a registry without handlers.
+    pub fn new() -> Self {
+        HandlerRegistry::default()
+    }
+
+    /// Handle the Messages of type *M* with *handler*, replacing an earlier handler of *M*.
+    ///
+    /// The handler gets the Actor, the Message and the [Context](../actor/struct.Context.html) of the Actor.
+    /// Messages from remote machines are deserialized to the registered type with their [tag](fn.message_tag.html),
+    /// untagged ones to the registered types in the order they were first registered.
+    pub fn on<M: DeserializeOwned + Send + 'static>(
+        mut self,
+        handler: impl Fn(&mut A, &M, &Context) + Send + Sync + 'static,
+    ) -> Self {
+        let registered = RegisteredHandler {
+            type_id: TypeId::of::<M>(),
+            type_name: std::any::type_name::<M>(),
+            tag: message_tag::<M>(),
+            handle: Box::new(move |actor, message| {
+                if let Some(message_typed) = message.downcast_ref::<M>() {
+                    handler(actor, message_typed, &Context);
+                }
+            }),
+            deserialize: |message, format| {
+                format
+                    .deserialize::<M>(message)
+                    .ok()
+                    .map(|message| Box::new(message) as Box<dyn Any + Send>)
+            },
+        };
+        match self
+            .handlers
+            .iter_mut()
+            .find(|handler| handler.type_id == registered.type_id)
+        {
+            Some(earlier) => *earlier = registered,
+            None => self.handlers.push(registered),
+        }
+        self
+    }
+
+    /// Add every handler of *other*, replacing the handlers of this registry for the same Message types.
+    pub fn with(mut self, other: HandlerRegistry<A>) -> Self {
+        for registered in other.handlers {
+            match self
+                .handlers
+                .iter_mut()
+                .find(|handler| handler.type_id == registered.type_id)
+            {
+                Some(earlier) => *earlier = registered,
+                None => self.handlers.push(registered),
+            }
+        }
+        self
+    }
+
+    /// The names of the registered Message types, in the order they are tried.
+    pub fn message_type_names(&self) -> Vec<&'static str> {
+        self.handlers
+            .iter()
+            .map(|handler| handler.type_name)
+            .collect()
+    }
+
+    fn find(&self, message: &dyn Any) -> Option<&RegisteredHandler<A>> {
+        let type_id = message.type_id();
+        self.handlers
+            .iter()
+            .find(|handler| handler.type_id == type_id)
+    }
+}
+
+/// Implements the [MessageHandler](trait.MessageHandler.html) of an Actor type with a [HandlerRegistry](struct.HandlerRegistry.html),
+/// instead of the [impl_message_handler!](../macro.impl_message_handler.html)-macro.
+///
+/// The registry is built once per Actor type, when the first Actor of the type needs it, and shared by all of them.
+/// Its Message types do not count as [handled](trait.Handles.html) by a [TypedActorRef](../actor/struct.TypedActorRef.html).
+pub trait RegisteredHandlers: Sized + 'static {
+    /// The handlers of this Actor type.
+    fn handlers() -> HandlerRegistry<Self>;
+}
+
+/// The registry of the Actor type *A*, built on first use.
+fn registry_of<A: RegisteredHandlers>() -> &'static HandlerRegistry<A> {
+    static REGISTRIES: OnceLock<RwLock<HashMap<TypeId, &'static (dyn Any + Send + Sync)>>> =
+        OnceLock::new();
+    let registries = REGISTRIES.get_or_init(|| RwLock::new(HashMap::new()));
+    // the registries are only ever added, so a poisoned lock holds nothing broken
+    let built = registries
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&TypeId::of::<A>())
+        .copied();
+    let registry = match built {
+        Some(registry) => registry,
+        None => {
+            let registry = A::handlers();
+            *registries
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(TypeId::of::<A>())
+                .or_insert_with(|| Box::leak(Box::new(registry)))
+        }
+    };
+    match registry.downcast_ref::<HandlerRegistry<A>>() {
+        Some(registry) => registry,
+        None => unreachable!("registries are stored by the TypeId of their Actor type"),
+    }
+}
+
+impl<A: RegisteredHandlers> MessageHandler for A {
+    fn handle(&mut self, message: Box<dyn Any>) {
+        self.handle_ref(&*message);
+    }
+
+    fn handle_ref(&mut self, message: &dyn Any) -> bool {
+        // messages of other types are ignored, like by the impl_message_handler!-macro
+        if let Some(handler) = registry_of::<A>().find(message) {
+            (handler.handle)(self, message);
+        }
+        true
+    }
+
+    fn deserialize_to_any(&self, message: &[u8]) -> Option<Box<dyn Any + Send>> {
+        registry_of::<A>()
+            .handlers
+            .iter()
+            .find_map(|handler| (handler.deserialize)(message, WireFormat::Bincode))
+    }
+
+    fn deserialize_tagged(
+        &self,
+        tag: u64,
+        message: &[u8],
+        format: WireFormat,
+    ) -> Option<Box<dyn Any + Send>> {
+        registry_of::<A>()
+            .handlers
+            .iter()
+            .find(|handler| handler.tag == tag)
+            .and_then(|handler| (handler.deserialize)(message, format))
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    fn message_type_name(&self, message: &dyn Any) -> Option<&'static str> {
+        registry_of::<A>()
+            .find(message)
+            .map(|handler| handler.type_name)
+    }
+
+    fn message_type_names() -> &'static [&'static str] {
+        static NAMES: OnceLock<RwLock<HashMap<TypeId, &'static [&'static str]>>> = OnceLock::new();
+        let names = NAMES.get_or_init(|| RwLock::new(HashMap::new()));
+        if let Some(names) = names
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&TypeId::of::<A>())
+        {
+            return names;
+        }
+        names
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(TypeId::of::<A>())
+            .or_insert_with(|| registry_of::<A>().message_type_names().leak())
+    }
 }
 
 /// Trait that enables a type to be send to an [Actor](../actor/trait.Actor.html).
@@ -39,6 +388,100 @@ pub trait Message<'de>: Debug + Send + Serialize + Deserialize<'de> {}
 // This way the Message-trait is truly a shortcut to all required traits.
 impl<'de, T: Debug + Send + Serialize + Deserialize<'de>> Message<'de> for T {}
 
+/// A Message superseding the queued Messages of the same type with the same key.
+///
+/// Sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed) to an Actor with a [coalescing mailbox](../api/struct.EnvironmentOptions.html#method.coalescing_mailbox),
+/// the Message replaces a Message with the same key that is still queued, keeping its place in the mailbox.
+/// The Actor only sees the freshest state, e.g. one state update per Actor.
+pub trait Keyed {
+    type Key: Hash;
+
+    /// The key deciding which queued Messages this Message replaces.
+    fn key(&self) -> Self::Key;
+}
+
+/// Hash the type and key of the message with FNV-1a, so the key can be compared on every machine.
+pub(crate) fn coalescing_key<M: Keyed>(message: &M) -> u64 {
+    let mut hasher = Fnv1aHasher(FNV_OFFSET_BASIS);
+    std::any::type_name::<M>().hash(&mut hasher);
+    message.key().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// A Hasher feeding the bytes to [fnv1a](fn.fnv1a.html), integers in little-endian byte order and ```usize``` as ```u64```,
+/// so keys hash the same on machines of any endianness and pointer width.
+struct Fnv1aHasher(u64);
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 = fnv1a(self.0, bytes);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as u64);
+    }
+}
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// FNV-1a, unlike the hashers of the standard library guaranteed to give the same result on every machine and Rust version.
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// The tag identifying the Message type *M* on the wire, a hash of its type name.
+///
+/// Remote messages carry the tag of their type, the receiving Actor deserializes them with
+/// [deserialize_tagged](trait.MessageHandler.html#method.deserialize_tagged) to exactly that type.
+pub fn message_tag<M: ?Sized + 'static>() -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, std::any::type_name::<M>().as_bytes())
+}
+
 #[macro_export]
 /// This macro tries to implement the [MessageHandler](message/trait.MessageHandler.html)-Trait for the specified type.
 ///
@@ -53,10 +496,29 @@ impl<'de, T: Debug + Send + Serialize + Deserialize<'de>> Message<'de> for T {}
 /// * This is repeated for every specified *$message_type => $handle_function* pair.
 ///
 /// The [deserialize_to_any](message/trait.MessageHandler.html#tymethod.deserialize_to_any)-method is implemented in a similar fashion,
-/// replacing ```downcast_ref``` with ```bincode::deserialize```.
+/// replacing ```downcast_ref``` with ```bincode::deserialize```. The [deserialize_tagged](message/trait.MessageHandler.html#method.deserialize_tagged)-method
+/// only deserializes to the $message_type with the tag of the message.
 ///
 /// **Note:** It is expected that all $handle_function terminate.
 ///
+/// [Handler sets](message/trait.HandlerSet.html) listed after ```; with``` are tried in order once no own handler matched,
+/// so an own handler takes precedence over the one of a set for the same type:
+///
+/// ```rust,ignore
+/// impl_message_handler!(FieldInstance: PlayerEnters => FieldInstance::handle_incoming_actor; with StandardLifecycleHandlers);
+/// ```
+///
+/// The Message types of the sets do not count as [handled](message/trait.Handles.html) by a [TypedActorRef](actor/struct.TypedActorRef.html).
+///
+/// Async handlers listed after ```; async``` are implemented by [handle_async](message/trait.MessageHandler.html#method.handle_async),
+/// they take the same arguments and return a future. Their Message types have to be ```Sync```, as the future borrows the message:
+///
+/// ```rust,ignore
+/// async fn load_chunk(field: &mut FieldInstance, request: &Request<LoadChunk>) { /* ... */ }
+///
+/// impl_message_handler!(FieldInstance: PlayerEnters => FieldInstance::handle_incoming_actor; async Request<LoadChunk> => load_chunk);
+/// ```
+///
 /// For example, calling the macro as
 /// ```rust
 /// impl_message_handler!(ExampleActor, String => my_handle_function)
@@ -81,7 +543,7 @@ impl<'de, T: Debug + Send + Serialize + Deserialize<'de>> Message<'de> for T {}
 /// }
 /// ```
 macro_rules! impl_message_handler {
-    ($actor_type:ty: $($message_type:ty => $handle_function:expr),*$(,)?) => {
+    ($actor_type:ty: $($message_type:ty => $handle_function:expr),*$(,)? $(; async $($async_type:ty => $async_function:expr),+$(,)?)? $(; with $($handler_set:ty),+$(,)?)?) => {
         impl MessageHandler for $actor_type {
             fn handle(&mut self, message: Box<dyn std::any::Any>) {
                 $(
@@ -90,12 +552,49 @@ macro_rules! impl_message_handler {
                     } else
                 )*
                 {
+                    $($(
+                        if <$handler_set as $crate::message::HandlerSet<Self>>::handle(self, &*message) {
+                            return;
+                        }
+                    )+)?
                     // log::warn!("All downcast-attempts failed.");
                     // all conversion attempts failed
                     // ignore message
                 }
             }
 
+            fn handle_ref(&mut self, message: &dyn std::any::Any) -> bool {
+                $(
+                    if let Some(message_typed) = message.downcast_ref::<$message_type>() {
+                        $handle_function(self, message_typed);
+                    } else
+                )*
+                {
+                    $($(
+                        if <$handler_set as $crate::message::HandlerSet<Self>>::handle(self, message) {
+                            return true;
+                        }
+                    )+)?
+                    // ignore message
+                }
+                true
+            }
+
+            fn handle_async(
+                &mut self,
+                message: Box<dyn std::any::Any + Send>,
+            ) -> Result<$crate::message::HandlerFuture<'_>, Box<dyn std::any::Any + Send>> {
+                $($(
+                    let message = match message.downcast::<$async_type>() {
+                        Ok(message_typed) => {
+                            return Ok(Box::pin(async move { $async_function(self, &*message_typed).await }));
+                        }
+                        Err(message) => message,
+                    };
+                )+)?
+                Err(message)
+            }
+
             fn deserialize_to_any(&self, message: &[u8]) -> Option<Box<dyn std::any::Any + Send>> {
                 let result: Option<Box<dyn std::any::Any + Send>>;
                 $(
@@ -103,45 +602,787 @@ macro_rules! impl_message_handler {
                         result = Some(Box::new(message_deserialized));
                     } else
                 )*
+                $($(
+                    if let Ok(message_deserialized) = bincode::deserialize::<$async_type>(&message) {
+                        result = Some(Box::new(message_deserialized));
+                    } else
+                )+)?
                 {
-                    // all conversion attempts failed
+                    // all conversion attempts failed, unless a handler set knows the type
                     // log::warn!("All desrealisation-attempts failed.");
-                    result = None;
+                    result = None
+                        $($(.or_else(|| {
+                            <$handler_set as $crate::message::HandlerSet<Self>>::deserialize_to_any(message)
+                        }))+)?;
                 }
                 result
             }
+
+            fn deserialize_tagged(&self, tag: u64, message: &[u8], format: $crate::wire::WireFormat) -> Option<Box<dyn std::any::Any + Send>> {
+                $(
+                    if tag == $crate::message::message_tag::<$message_type>() {
+                        return format.deserialize::<$message_type>(message)
+                            .ok()
+                            .map(|message_deserialized| Box::new(message_deserialized) as Box<dyn std::any::Any + Send>);
+                    }
+                )*
+                $($(
+                    if tag == $crate::message::message_tag::<$async_type>() {
+                        return format.deserialize::<$async_type>(message)
+                            .ok()
+                            .map(|message_deserialized| Box::new(message_deserialized) as Box<dyn std::any::Any + Send>);
+                    }
+                )+)?
+                // a type of a handler set, or not handled at all
+                None
+                    $($(.or_else(|| {
+                        <$handler_set as $crate::message::HandlerSet<Self>>::deserialize_tagged(tag, message, format)
+                    }))+)?
+            }
+
+            fn as_any(&self) -> Option<&dyn std::any::Any> {
+                Some(self)
+            }
+
+            fn message_type_name(&self, message: &dyn std::any::Any) -> Option<&'static str> {
+                $(
+                    if message.is::<$message_type>() {
+                        Some(stringify!($message_type))
+                    } else
+                )*
+                $($(
+                    if message.is::<$async_type>() {
+                        Some(stringify!($async_type))
+                    } else
+                )+)?
+                {
+                    None
+                        $($(.or_else(|| {
+                            <$handler_set as $crate::message::HandlerSet<Self>>::message_type_name(message)
+                        }))+)?
+                }
+            }
+
+            fn message_type_names() -> &'static [&'static str] {
+                static NAMES: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
+                NAMES.get_or_init(|| {
+                    #[allow(unused_mut)]
+                    let mut names: Vec<&'static str> = vec![$(stringify!($message_type)),*];
+                    $($(
+                        names.push(stringify!($async_type));
+                    )+)?
+                    $($(
+                        names.extend_from_slice(
+                            <$handler_set as $crate::message::HandlerSet<Self>>::message_type_names(),
+                        );
+                    )+)?
+                    names
+                })
+            }
+        }
+
+        $(
+            impl $crate::message::Handles<$message_type> for $actor_type {}
+        )*
+        $($(
+            impl $crate::message::Handles<$async_type> for $actor_type {}
+        )+)?
+    };
+}
+
+#[macro_export]
+/// This macro defines a [HandlerSet](message/trait.HandlerSet.html), a bundle of handlers to mix into the
+/// [impl_message_handler!](macro.impl_message_handler.html) of several Actor types.
+///
+/// The first argument is the name of the unit struct implementing the set, optionally followed by the Actor type parameter
+/// and a single bound its handlers need, ```Actor``` by default. The following arguments are of the form
+/// ```$message_type => $handle_function``` separated by commas, like for impl_message_handler!.
+///
+/// ```rust,ignore
+/// fn answer_ping<A: Actor>(actor: &mut A, ping: &HealthPing) { /* ... */ }
+/// fn resync<A: Resyncable>(actor: &mut A, request: &ResyncRequest) { actor.resync(request) }
+///
+/// handler_set!(pub StandardLifecycleHandlers: HealthPing => answer_ping);
+/// handler_set!(pub ResyncHandlers<A: Resyncable>: ResyncRequest => resync);
+///
+/// impl_message_handler!(CollectingActor: UpdateState => update_state; with StandardLifecycleHandlers, ResyncHandlers);
+/// ```
+macro_rules! handler_set {
+    ($(#[$meta:meta])* $vis:vis $set:ident: $($message_type:ty => $handle_function:expr),+$(,)?) => {
+        $crate::handler_set!($(#[$meta])* $vis $set<A: $crate::actor::Actor>: $($message_type => $handle_function),+);
+    };
+    ($(#[$meta:meta])* $vis:vis $set:ident<$actor:ident: $bound:path>: $($message_type:ty => $handle_function:expr),+$(,)?) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy)]
+        $vis struct $set;
+
+        impl<$actor: $bound> $crate::message::HandlerSet<$actor> for $set {
+            fn handle(actor: &mut $actor, message: &dyn std::any::Any) -> bool {
+                $(
+                    if let Some(message_typed) = message.downcast_ref::<$message_type>() {
+                        $handle_function(actor, message_typed);
+                        return true;
+                    }
+                )+
+                false
+            }
+
+            fn deserialize_to_any(message: &[u8]) -> Option<Box<dyn std::any::Any + Send>> {
+                $(
+                    if let Ok(message_deserialized) = bincode::deserialize::<$message_type>(message) {
+                        return Some(Box::new(message_deserialized));
+                    }
+                )+
+                None
+            }
+
+            fn deserialize_tagged(tag: u64, message: &[u8], format: $crate::wire::WireFormat) -> Option<Box<dyn std::any::Any + Send>> {
+                $(
+                    if tag == $crate::message::message_tag::<$message_type>() {
+                        return format.deserialize::<$message_type>(message)
+                            .ok()
+                            .map(|message_deserialized| Box::new(message_deserialized) as Box<dyn std::any::Any + Send>);
+                    }
+                )+
+                None
+            }
+
+            fn message_type_name(message: &dyn std::any::Any) -> Option<&'static str> {
+                $(
+                    if message.is::<$message_type>() {
+                        return Some(stringify!($message_type));
+                    }
+                )+
+                None
+            }
+
+            fn message_type_names() -> &'static [&'static str] {
+                &[$(stringify!($message_type)),+]
+            }
         }
     };
 }
 
+/// Create the sending and receiving end of a new Actor's mailbox, limited by the optional quota.
+///
+/// If *track_age* is set, the time every message was enqueued is remembered until it is handled.
+/// If *coalescing* is set, keyed messages replace the queued message with the same key.
+pub(crate) fn mailbox(
+    quota: Option<MailboxQuota>,
+    track_age: bool,
+    coalescing: bool,
+) -> (MailboxSender, Mailbox) {
+    let (sender, receiver) = channel();
+    let slots = if coalescing {
+        Some(Arc::new(Mutex::new(HashMap::new())))
+    } else {
+        None
+    };
+    let stats = Arc::new(MailboxStats {
+        enqueued_at: if track_age {
+            Some(Mutex::new(VecDeque::new()))
+        } else {
+            None
+        },
+        ..MailboxStats::default()
+    });
+    (
+        MailboxSender {
+            sender,
+            stats: stats.clone(),
+            quota: quota.clone(),
+            slots: slots.clone(),
+            waker: None,
+            serialized: false,
+        },
+        Mailbox {
+            receiver,
+            buffer: VecDeque::new(),
+            stats,
+            quota,
+            slots,
+            last_sender: None,
+            last_key: None,
+            dropped: Vec::new(),
+            #[cfg(feature = "async-runtime")]
+            notify: None,
+        },
+    )
+}
+
+/// Bookkeeping of an Actor's mailbox, shared by all senders and the Mailbox itself.
+#[derive(Debug, Default)]
+pub(crate) struct MailboxStats {
+    /// Number of messages enqueued, but not yet handled
+    pub(crate) queued_messages: AtomicUsize,
+    /// Serialized size of all messages enqueued, but not yet handled
+    pub(crate) queued_bytes: AtomicUsize,
+    /// Number of messages dropped because of the mailbox quota
+    pub(crate) dropped_messages: AtomicUsize,
+    /// Number of queries and forwarding Stop requests enqueued, but not yet taken out
+    pub(crate) queued_urgent: AtomicUsize,
+    /// Set once a Stop request was enqueued, the Actor discards every later message
+    pub(crate) stopping: AtomicBool,
+    /// Set once the Mailbox was dropped, later messages fail as for an Actor that is gone
+    pub(crate) gone: AtomicBool,
+    /// When the messages not yet handled were enqueued, oldest first, only tracked if mailbox alerts are configured
+    pub(crate) enqueued_at: Option<Mutex<VecDeque<Instant>>>,
+}
+
+impl MailboxStats {
+    /// Whether the Actor was asked to stop, but its Mailbox is not gone yet.
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::Relaxed) && !self.gone.load(Ordering::Relaxed)
+    }
+}
+
+/// A message in transit to an Actor, together with its serialized size.
+#[derive(Debug)]
+pub(crate) struct Envelope {
+    pub(crate) message: EitherMessage,
+    pub(crate) size: usize,
+    /// Set for keyed messages, in a coalescing mailbox the message may have been replaced since
+    pub(crate) key: Option<u64>,
+    /// The Actor that sent the message, ```None``` if it was sent from outside of an Actor
+    pub(crate) sender: Option<ActorId>,
+}
+
+/// The latest message for a key that is queued in a coalescing mailbox.
+#[derive(Debug)]
+pub(crate) struct CoalescedSlot {
+    /// The message replacing the queued one, ```None``` until a second message with the key arrived
+    replacement: Option<EitherMessage>,
+    /// The size the queued message is accounted with
+    size: usize,
+}
+
+/// Queued keyed messages of a coalescing mailbox, shared by all senders and the Mailbox itself.
+type CoalescedSlots = Arc<Mutex<HashMap<u64, CoalescedSlot>>>;
+
+impl Envelope {
+    fn is_special(&self) -> bool {
+        matches!(
+            self.message,
+            EitherMessage::Special(_) | EitherMessage::Query(_)
+        )
+    }
+
+    /// Queries and forwarding Stop requests are taken out before any other message.
+    fn is_urgent(&self) -> bool {
+        matches!(
+            self.message,
+            EitherMessage::Query(_) | EitherMessage::Special(Token::StopAndForward(_))
+        )
+    }
+}
+
+/// The sending end of an Actor's mailbox, keeping track of the enqueued bytes.
+#[derive(Debug, Clone)]
+pub(crate) struct MailboxSender {
+    sender: Sender<Envelope>,
+    stats: Arc<MailboxStats>,
+    quota: Option<MailboxQuota>,
+    slots: Option<CoalescedSlots>,
+    /// Wakes the task or the scheduled Actor, unless its mailbox loop runs on its own thread
+    waker: Option<MailboxWaker>,
+    /// Set for Actors forwarding their messages, e.g. routers, local senders serialize the messages like remote ones
+    serialized: bool,
+}
+
+/// Called for every message put into a mailbox, and once a sender is dropped.
+#[derive(Clone)]
+struct MailboxWaker(Arc<dyn Fn() + Send + Sync>);
+
+impl std::fmt::Debug for MailboxWaker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MailboxWaker")
+    }
+}
+
+impl MailboxSender {
+    /// Put a message of the given serialized size into the mailbox.
+    ///
+    /// Fails with [MailboxFull](../api/enum.ActlibError.html#variant.MailboxFull) if the quota rejects the message,
+    /// [ActorStopping](../api/enum.ActlibError.html#variant.ActorStopping) if the Actor was asked to stop,
+    /// or [InvalidActorRef](../api/enum.ActlibError.html#variant.InvalidActorRef) if the Actor is gone.
+    pub(crate) fn send(&self, message: EitherMessage, size: usize) -> Result<(), ActlibError> {
+        self.send_from(message, size, None)
+    }
+
+    /// Like [send](#method.send), the receiving Actor learns the given *sender* from its [Context](../actor/struct.Context.html#method.sender).
+    pub(crate) fn send_from(
+        &self,
+        message: EitherMessage,
+        size: usize,
+        sender: Option<ActorId>,
+    ) -> Result<(), ActlibError> {
+        self.enqueue(Envelope {
+            message,
+            size,
+            key: None,
+            sender,
+        })
+    }
+
+    /// Like [send_from](#method.send_from), but in a coalescing mailbox the message replaces a queued message with the same key.
+    ///
+    /// The replacement keeps the sender of the queued message.
+    pub(crate) fn send_keyed(
+        &self,
+        message: EitherMessage,
+        size: usize,
+        key: u64,
+        sender: Option<ActorId>,
+    ) -> Result<(), ActlibError> {
+        let slots = match &self.slots {
+            Some(slots) => slots,
+            None => {
+                return self.enqueue(Envelope {
+                    message,
+                    size,
+                    key: Some(key),
+                    sender,
+                })
+            }
+        };
+        match slots.lock() {
+            Ok(mut slots) => match slots.get_mut(&key) {
+                Some(slot) => {
+                    if self.stats.is_stopping() {
+                        return Err(ActlibError::ActorStopping(
+                            "The Actor behind this ActorRef is stopping".to_string(),
+                        ));
+                    }
+                    self.stats.queued_bytes.fetch_add(size, Ordering::Relaxed);
+                    self.stats
+                        .queued_bytes
+                        .fetch_sub(slot.size, Ordering::Relaxed);
+                    slot.replacement = Some(message);
+                    slot.size = size;
+                    Ok(())
+                }
+                None => {
+                    // enqueue while holding the lock, so the Mailbox finds the slot of every keyed envelope
+                    self.enqueue(Envelope {
+                        message,
+                        size,
+                        key: Some(key),
+                        sender,
+                    })?;
+                    slots.insert(
+                        key,
+                        CoalescedSlot {
+                            replacement: None,
+                            size,
+                        },
+                    );
+                    Ok(())
+                }
+            },
+            Err(e) => Err(ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    fn enqueue(&self, envelope: Envelope) -> Result<(), ActlibError> {
+        let size = envelope.size;
+        match &envelope.message {
+            EitherMessage::Special(Token::Stop(_)) => {
+                self.stats.stopping.store(true, Ordering::Relaxed);
+            }
+            _ if !envelope.is_special() && self.stats.is_stopping() => {
+                return Err(ActlibError::ActorStopping(
+                    "The Actor behind this ActorRef is stopping".to_string(),
+                ));
+            }
+            _ => {}
+        }
+        if let Some(quota) = &self.quota {
+            if quota.policy == QuotaPolicy::Reject && !envelope.is_special() {
+                let queued_bytes = self.stats.queued_bytes.load(Ordering::Relaxed);
+                if queued_bytes + size > quota.max_bytes {
+                    self.stats.dropped_messages.fetch_add(1, Ordering::Relaxed);
+                    return Err(ActlibError::MailboxFull(format!(
+                        "{} bytes queued, a message of {} bytes exceeds the quota of {} bytes",
+                        queued_bytes, size, quota.max_bytes
+                    )));
+                }
+            }
+        }
+        // count before sending, so the Mailbox never subtracts more than was added
+        let is_urgent = envelope.is_urgent();
+        self.stats.queued_messages.fetch_add(1, Ordering::Relaxed);
+        self.stats.queued_bytes.fetch_add(size, Ordering::Relaxed);
+        if is_urgent {
+            self.stats.queued_urgent.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(enqueued_at) = &self.stats.enqueued_at {
+            if let Ok(mut enqueued_at) = enqueued_at.lock() {
+                enqueued_at.push_back(Instant::now());
+            }
+        }
+        match self.sender.send(envelope) {
+            Ok(_) => {
+                self.wake();
+                Ok(())
+            }
+            Err(_e) => {
+                if let Some(enqueued_at) = &self.stats.enqueued_at {
+                    if let Ok(mut enqueued_at) = enqueued_at.lock() {
+                        enqueued_at.pop_back();
+                    }
+                }
+                self.stats.queued_messages.fetch_sub(1, Ordering::Relaxed);
+                self.stats.queued_bytes.fetch_sub(size, Ordering::Relaxed);
+                if is_urgent {
+                    self.stats.queued_urgent.fetch_sub(1, Ordering::Relaxed);
+                }
+                Err(ActlibError::InvalidActorRef(
+                    "This ActorRef is no longer connected to an Actor".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+impl MailboxSender {
+    /// Call *waker* for every message put into the mailbox, required for running it on the [Scheduler](../scheduler/struct.Scheduler.html).
+    pub(crate) fn woken_by<F: Fn() + Send + Sync + 'static>(&mut self, waker: F) {
+        self.waker = Some(MailboxWaker(Arc::new(waker)));
+    }
+
+    /// Let the local senders serialize their messages, so the Actor can forward them to any machine.
+    pub(crate) fn serialize_messages(&mut self) {
+        self.serialized = true;
+    }
+
+    /// Whether the size of every message is measured, only needed for a mailbox with a quota or while mailbox alerts are configured.
+    pub(crate) fn measures_size(&self) -> bool {
+        self.quota.is_some() || self.stats.enqueued_at.is_some()
+    }
+
+    /// Wrap a message sent by a local sender for this mailbox, with the size it is accounted with.
+    ///
+    /// The size is the one in the *format* on the wire, 0 unless this mailbox [measures it](#method.measures_size) or the message is serialized anyway.
+    pub(crate) fn wrap<M: Serialize + Send + 'static>(
+        &self,
+        message: M,
+        format: WireFormat,
+    ) -> Result<(EitherMessage, usize), ActlibError> {
+        if self.serialized {
+            let message = SerializedMessage::of(&message, format)?;
+            let size = message.bytes.len();
+            return Ok((EitherMessage::Serialized(message), size));
+        }
+        let size = if self.measures_size() {
+            format.serialized_size(&message)
+        } else {
+            0
+        };
+        Ok((EitherMessage::Regular(Box::new(message)), size))
+    }
+
+    /// Wake the task or the scheduled Actor, a wake-up while it is busy is kept for its next wait.
+    fn wake(&self) {
+        if let Some(MailboxWaker(waker)) = &self.waker {
+            waker();
+        }
+    }
+}
+
+impl Drop for MailboxSender {
+    /// The task or the scheduled Actor has to notice once the last sender is gone.
+    fn drop(&mut self) {
+        self.wake();
+    }
+}
+
+impl MailboxSender {
+    /// Number of messages enqueued, but not yet handled.
+    pub(crate) fn queued_messages(&self) -> usize {
+        self.stats.queued_messages.load(Ordering::Relaxed)
+    }
+
+    /// Serialized size of all messages enqueued, but not yet handled, as far as they were [measured](#method.measures_size).
+    pub(crate) fn queued_bytes(&self) -> usize {
+        self.stats.queued_bytes.load(Ordering::Relaxed)
+    }
+
+    /// How long the oldest message not yet handled waits, ```None``` if the mailbox is empty or the age is not tracked.
+    ///
+    /// Queries overtake the other messages, so the age is approximate while queries are queued.
+    pub(crate) fn oldest_message_age(&self) -> Option<Duration> {
+        match &self.stats.enqueued_at {
+            Some(enqueued_at) => match enqueued_at.lock() {
+                Ok(enqueued_at) => enqueued_at.front().map(|instant| instant.elapsed()),
+                Err(_) => None,
+            },
+            None => None,
+        }
+    }
+}
+
 /// An specialization of the ```std::sync::mpsc::Receiver```-type that only exposes a limited set of methods.
 pub(crate) struct Mailbox {
-    receiver: Receiver<EitherMessage>, // buffered receiving end of a channel
+    receiver: Receiver<Envelope>, // buffered receiving end of a channel
+    buffer: VecDeque<Envelope>,   // messages taken from the channel, but not yet handled
+    stats: Arc<MailboxStats>,
+    quota: Option<MailboxQuota>,
+    slots: Option<CoalescedSlots>,
+    /// The sender of the message taken out last
+    last_sender: Option<ActorId>,
+    /// The key of the message taken out last, if it was sent with send_keyed
+    last_key: Option<u64>,
+    /// Messages dropped because of the quota, until the Actor reports them
+    dropped: Vec<EitherMessage>,
+    #[cfg(feature = "async-runtime")]
+    notify: Option<Arc<tokio::sync::Notify>>,
+}
+
+impl Drop for Mailbox {
+    /// The senders have to tell an Actor that is gone from one that is stopping.
+    fn drop(&mut self) {
+        self.stats.gone.store(true, Ordering::Relaxed);
+    }
 }
 
 impl Mailbox {
-    /// Create a new Mailbox.
-    pub(crate) fn new(receiver: Receiver<EitherMessage>) -> Mailbox {
-        Mailbox { receiver }
+    /// Let the *sender* wake the task waiting for this mailbox, required for running it on the async runtime.
+    #[cfg(feature = "async-runtime")]
+    pub(crate) fn notified_by(&mut self, sender: &mut MailboxSender) {
+        let notify = Arc::new(tokio::sync::Notify::new());
+        self.notify = Some(notify.clone());
+        sender.woken_by(move || notify.notify_one());
+    }
+
+    /// The Actor that sent the message taken out of the mailbox last, if any.
+    pub(crate) fn last_sender(&self) -> Option<ActorId> {
+        self.last_sender.clone()
+    }
+
+    /// The key of the message taken out last, ```None``` unless it was sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed).
+    pub(crate) fn last_key(&self) -> Option<u64> {
+        self.last_key
+    }
+
+    /// Take the messages the quota dropped since the last call, oldest first.
+    pub(crate) fn take_dropped(&mut self) -> Vec<EitherMessage> {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// Take the next message without waiting, ```None``` if the mailbox is empty.
+    ///
+    /// Like [wait_for_msg](#method.wait_for_msg) otherwise, the [Scheduler](../scheduler/struct.Scheduler.html) calls it once woken.
+    pub(crate) fn try_msg(&mut self) -> Result<Option<EitherMessage>, RecvError> {
+        self.next_msg(false)
     }
 
     /// Attempts to wait for a value on this Mailbox, returning an error if the corresponding channel has hung up.
     ///
+    /// Queries and forwarding Stop requests are returned first, regardless of their position in the mailbox, then buffered messages.
+    /// If the quota drops the oldest messages, messages are dropped here until the mailbox fits the quota again,
+    /// they are kept for [take_dropped](#method.take_dropped).
     /// Every remark from ```std::sync::mpsc::Receiver::recv``` apply to this method as well.
-    pub(crate) fn wait_for_msg(&self) -> Result<EitherMessage, RecvError> {
-        self.receiver.recv() // blocking
+    pub(crate) fn wait_for_msg(&mut self) -> Result<EitherMessage, RecvError> {
+        match self.next_msg(true)? {
+            Some(message) => Ok(message),
+            None => unreachable!("A blocking receive returned without a message."),
+        }
+    }
+
+    /// Like [wait_for_msg](#method.wait_for_msg), but the task gives way to the other Actors while the mailbox is empty.
+    #[cfg(feature = "async-runtime")]
+    pub(crate) async fn wait_for_msg_async(&mut self) -> Result<EitherMessage, RecvError> {
+        loop {
+            if let Some(message) = self.next_msg(false)? {
+                return Ok(message);
+            }
+            // a message enqueued since the check left a wake-up behind, so none is missed
+            match &self.notify {
+                Some(notify) => notify.notified().await,
+                None => return self.wait_for_msg(),
+            }
+        }
+    }
+
+    /// Take the next message, waiting for one if *block* is set, or returning ```None``` if the mailbox is empty otherwise.
+    fn next_msg(&mut self, block: bool) -> Result<Option<EitherMessage>, RecvError> {
+        let mut dropped = 0;
+        loop {
+            if let Some(urgent) = self.take_urgent() {
+                self.last_sender = urgent.sender;
+                self.last_key = None;
+                return Ok(Some(urgent.message));
+            }
+            let mut envelope = match self.buffer.pop_front() {
+                Some(envelope) => envelope,
+                None if block => self.receiver.recv()?, // blocking
+                None => match self.receiver.try_recv() {
+                    Ok(envelope) => envelope,
+                    Err(TryRecvError::Empty) => return Ok(None),
+                    Err(TryRecvError::Disconnected) => return Err(RecvError),
+                },
+            };
+            self.resolve(&mut envelope);
+            let queued_bytes = self.stats.queued_bytes.load(Ordering::Relaxed);
+            self.take(&envelope);
+            if let Some(quota) = &self.quota {
+                if quota.policy == QuotaPolicy::DropOldest
+                    && !envelope.is_special()
+                    && queued_bytes > quota.max_bytes
+                {
+                    self.stats.dropped_messages.fetch_add(1, Ordering::Relaxed);
+                    dropped += 1;
+                    self.dropped.push(envelope.message);
+                    continue;
+                }
+            }
+            if dropped > 0 {
+                warn!(
+                    "Dropped the {} oldest messages of a mailbox exceeding its quota.",
+                    dropped
+                );
+            }
+            self.last_sender = envelope.sender;
+            self.last_key = envelope.key;
+            return Ok(Some(envelope.message));
+        }
+    }
+
+    /// Move every message that is currently queued into the buffer, returning how many there are.
+    pub(crate) fn buffer_pending(&mut self) -> usize {
+        self.buffer.extend(self.receiver.try_iter());
+        self.buffer.len()
+    }
+
+    /// Drop every buffered message for which *keep* returns ```false```, returning the dropped ones.
+    pub(crate) fn discard_buffered<F: Fn(&EitherMessage) -> bool>(
+        &mut self,
+        keep: F,
+    ) -> Vec<EitherMessage> {
+        self.take_buffered(keep)
+            .into_iter()
+            .map(|envelope| envelope.message)
+            .collect()
+    }
+
+    /// Take every buffered message for which *keep* returns ```false``` out of the mailbox, with its sender.
+    fn take_buffered<F: Fn(&EitherMessage) -> bool>(&mut self, keep: F) -> Vec<Envelope> {
+        let mut kept = VecDeque::with_capacity(self.buffer.len());
+        let mut taken = Vec::new();
+        for mut envelope in self.buffer.drain(..).collect::<Vec<_>>() {
+            if keep(&envelope.message) {
+                kept.push_back(envelope);
+            } else {
+                self.resolve(&mut envelope);
+                self.take(&envelope);
+                taken.push(envelope);
+            }
+        }
+        self.buffer = kept;
+        taken
+    }
+
+    /// Take every message that is currently queued out of the mailbox, with their senders, oldest first.
+    pub(crate) fn take_pending_envelopes(&mut self) -> Vec<Envelope> {
+        self.buffer_pending();
+        self.take_buffered(|_| false)
+    }
+
+    /// Drop every message that is currently queued, returning the dropped ones.
+    pub(crate) fn discard_pending(&mut self) -> Vec<EitherMessage> {
+        self.buffer_pending();
+        self.discard_buffered(|_| false)
+    }
+
+    /// Take the oldest pending query or forwarding Stop request out of the mailbox, skipping every other message.
+    fn take_urgent(&mut self) -> Option<Envelope> {
+        if self.stats.queued_urgent.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        self.buffer_pending();
+        let position = self
+            .buffer
+            .iter()
+            .position(|envelope| envelope.is_urgent())?;
+        let urgent = self.buffer.remove(position)?;
+        self.take(&urgent);
+        Some(urgent)
+    }
+
+    /// Swap a keyed message for its latest replacement, later messages with the key are queued anew.
+    fn resolve(&self, envelope: &mut Envelope) {
+        if let (Some(key), Some(slots)) = (envelope.key, &self.slots) {
+            match slots.lock() {
+                Ok(mut slots) => {
+                    if let Some(slot) = slots.remove(&key) {
+                        if let Some(replacement) = slot.replacement {
+                            envelope.message = replacement;
+                        }
+                        envelope.size = slot.size;
+                    }
+                }
+                Err(e) => warn!("{:?}", ActlibError::from_poison_error(&e)),
+            }
+        }
+    }
+
+    /// Put the stashed *messages* back in front of the mailbox with their senders, the oldest first.
+    pub(crate) fn unstash(&mut self, messages: Vec<(Box<dyn Any + Send>, Option<ActorId>)>) {
+        for (message, sender) in messages.into_iter().rev() {
+            self.stats.queued_messages.fetch_add(1, Ordering::Relaxed);
+            if let Some(enqueued_at) = &self.stats.enqueued_at {
+                if let Ok(mut enqueued_at) = enqueued_at.lock() {
+                    enqueued_at.push_front(Instant::now());
+                }
+            }
+            self.buffer.push_front(Envelope {
+                message: EitherMessage::Regular(message),
+                size: 0,
+                key: None,
+                sender,
+            });
+        }
+    }
+
+    /// Remove a message taken out of the mailbox from the bookkeeping.
+    fn take(&self, envelope: &Envelope) {
+        if envelope.is_urgent() {
+            self.stats.queued_urgent.fetch_sub(1, Ordering::Relaxed);
+        }
+        self.stats.queued_messages.fetch_sub(1, Ordering::Relaxed);
+        self.stats
+            .queued_bytes
+            .fetch_sub(envelope.size, Ordering::Relaxed);
+        if let Some(enqueued_at) = &self.stats.enqueued_at {
+            if let Ok(mut enqueued_at) = enqueued_at.lock() {
+                enqueued_at.pop_front();
+            }
+        }
     }
 }
 
 /// Either type variant vocalized to the use case: An EitherMessage is either a regular message or a serialized message.
 #[derive(Debug)]
 pub(crate) enum EitherMessage {
-    /// A serialized message of type ```String```
-    Serialized(Vec<u8>),
+    /// A serialized message, tagged with its type if known
+    Serialized(SerializedMessage),
     /// A non-serialized message of type ```Box<dyn Any + Send>```
     Regular(Box<dyn Any + Send>),
     /// Special Message-Token
     Special(Token),
+    /// A query reading the Actor's state, answered before any other message
+    Query(Query),
+}
+
+/// A closure reading an Actor's state on the Actor's own thread, see [ActorRef::query](../actor/struct.ActorRef.html#method.query).
+pub(crate) struct Query(pub(crate) QueryFn);
+
+type QueryFn = Box<dyn FnOnce(&dyn Actor) + Send>;
+
+impl Debug for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Query")
+    }
 }
 
 /// Special Message-Token we send at specific points in the program.
@@ -149,34 +1390,172 @@ pub(crate) enum EitherMessage {
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) enum Token {
     /// Special Message-Token signaling a Stop-Request to an Actor.
-    Stop,
+    Stop(StopReason),
     /// Special Message-Token signaling a Reset-Request to an Actor.
     Reset,
+    /// Special Message-Token signaling a Remove-Request to an Actor, its queued messages are forwarded to the given successor.
+    StopAndForward(ActorId),
+}
+
+/// The Actor that asked for a [restricted](../options/struct.EnvironmentOptions.html#method.restrict) operation on a remote machine,
+/// with the capabilities its own machine granted it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Caller {
+    pub(crate) actor: ActorId,
+    pub(crate) capabilities: Vec<Capability>,
 }
 
 /// Messages that can be send to a remote Environment.
 #[derive(Serialize, Deserialize)]
 pub(crate) enum NetMessage {
-    /// A User-defined, serialized Message
-    Message(ActorId, Vec<u8>),
-    /// binary serialized [Token]
-    SpecialToken(ActorId, Vec<u8>),
-    /// Spawn an Actor using the specified TypeId and LocalId
-    SpawnByTypeId(String, LocalId),
-    /// queried_id, return_addr, searcher_id, protected?
-    QuerySpecifiedId(Vec<u8>, IpAddr, ActorId, bool),
-    /// queried_id, searcher_id, result
-    QuerySpecifiedIdResult(Vec<u8>, ActorId, Option<IpAddr>),
+    /// A User-defined, serialized Message, with the Actor that sent it
+    Message(ActorId, SerializedMessage, Option<ActorId>),
+    /// binary serialized [Token], with the Actor that sent it
+    SpecialToken(ActorId, Vec<u8>, Option<Caller>),
+    /// A User-defined, serialized Message with its coalescing key and the Actor that sent it
+    KeyedMessage(ActorId, u64, SerializedMessage, Option<ActorId>),
+    /// origin, delivery_no, a serialized Message or KeyedMessage the receiver acknowledges with Delivered
+    Tracked(MachineId, u64, Vec<u8>),
+    /// delivery_no, None if the Message was enqueued or the reason it was rejected
+    Delivered(u64, Option<String>),
+    /// Spawn an Actor using the specified TypeId and LocalId, spawned by the given Actor and granted the Capabilities,
+    /// then answer the requester with a SpawnAck for the spawn_no. If held, the Actor starts on a StartHeld.
+    /// The bincode serialized arguments, if any, are passed to the Actor's on_init
+    SpawnByTypeId(
+        String,
+        LocalId,
+        Option<Caller>,
+        Vec<Capability>,
+        MachineId,
+        u64,
+        bool,
+        Option<Vec<u8>>,
+    ),
+    /// spawn_no, the incarnation of the spawned Actor or the reason it was not spawned
+    SpawnAck(u64, Result<u64, String>),
+    /// Start the Actor held by its SpawnByTypeId, its spawner's handler returned
+    StartHeld(ActorId),
+    /// queried_id, return_machine, searcher_id, protected?
+    QuerySpecifiedId(Vec<u8>, MachineId, ActorId, bool),
+    /// queried_id, searcher_id, result: the machine, type id and incarnation of the found Actor
+    QuerySpecifiedIdResult(Vec<u8>, ActorId, Option<(MachineId, String, u64)>),
     /// RemoveProtector(protector: ActorId, target: ActorId)`
     RemoveProtector(ActorId, ActorId),
-    /// Broadcast this Message to all Actors
-    Broadcast(Vec<u8>),
-    /// call send_expiration_signal
-    SendExpirationSignal,
+    /// Broadcast this Message to all Actors, sent by the given Actor
+    Broadcast(SerializedMessage, Option<Caller>),
+    /// A Message to broadcast in total order, sent to the leader which sequences it
+    OrderBroadcast(SerializedMessage, Option<Caller>),
+    /// A Message the leader sequenced, broadcast to all Actors in the order they arrive
+    OrderedBroadcast(SerializedMessage, Option<Caller>),
+    /// Stop all local Actors within the drain timeout and answer with an ExpirationReport to the given machine,
+    /// asked for by the given Actor
+    SendExpirationSignal(MachineId, Duration, Option<Caller>),
+    /// How the sending machine wound down after a SendExpirationSignal
+    ExpirationReport(DrainReport),
+    /// sender, specified ids of all Actors living on the sender with the time they were spawned
+    SpecifiedIds(MachineId, Vec<(Vec<u8>, SystemTime)>),
+    /// destination, origin, hops_left, a serialized NetMessage forwarded by the machines in between
+    Relay(MachineId, MachineId, u8, Vec<u8>),
+    /// from, to, ttl: forward the messages for a replaced Actor living on the receiver
+    Alias(ActorId, ActorId, Duration),
+    /// The machine id, the build fingerprint and the maximum message size of the sender, the first frame on every connection
+    Hello(MachineId, u64, usize),
+    /// message_id, chunk_index, chunk_count, part of a serialized NetMessage too large for a single frame
+    Chunk(u64, u32, u32, Vec<u8>),
+    /// The current load of the sending machine
+    Load(MachineLoad),
+    /// payload_id, requester, request_no: send the stashed payload to the requester
+    RedeemPayload(Uuid, MachineId, u64),
+    /// request_no, the payload or None if it is gone
+    RedeemedPayload(u64, Option<Vec<u8>>),
+    /// payload_id: drop the stashed payload
+    ReleasePayload(Uuid),
+    /// type id, requester, request_no, timeout: export the states of the local Actors of the type within the timeout,
+    /// then answer the requester with CollectedStates for the request_no
+    CollectStates(String, MachineId, u64, Duration),
+    /// request_no, the exported states of the Actors on the sender
+    CollectedStates(u64, Vec<(ActorId, Vec<u8>)>),
+    /// correlation_id, the serialized reply to an ask sent from the receiver, the type id of the responder if replies are traced
+    Reply(u64, Vec<u8>, Option<String>),
+    /// type id, subscriber: send a LifecycleEvent for every Actor of the type to the subscriber
+    SubscribeLifecycle(String, ActorId),
+    /// type id, subscriber: end the lifecycle subscription
+    UnsubscribeLifecycle(String, ActorId),
+    /// The sender ran the bootstrap, every spawn request of it arrived before
+    Bootstrapped,
+    /// watched, watcher: send Terminated to the watcher once the watched Actor living on the receiver stopped
+    Watch(ActorId, ActorId),
+    /// The shares of the sending machine in every Counter and Gauge it used
+    Counters(MachineId, CounterShares),
+    /// name, Actor: registered names known to the sender
+    Names(Vec<(String, ActorId)>),
+    /// name, Actor: the name no longer refers to the Actor
+    UnregisterName(String, ActorId),
+    /// port, handler, requester, handover_no: listen on the port for the handler living on the receiver,
+    /// then answer the requester with TookOverListener for the handover_no
+    HandOverListener(u16, ActorId, MachineId, u64),
+    /// handover_no, None if the receiver listens on the port or the reason it does not
+    TookOverListener(u64, Option<String>),
+    /// type id or None for every type, requester, request_no: answer the requester with ListedActors for the request_no
+    ListActors(Option<String>, MachineId, u64),
+    /// request_no, the Actors alive on the sender
+    ListedActors(u64, Vec<ActorId>),
+    /// A serialized Message for every Actor on the receiver, handed out in slices, sent by the given Actor
+    SlicedBroadcast(SerializedMessage, BroadcastSlices, Option<Caller>),
+    /// requester, ping_no: answer the requester with a Pong for the ping_no
+    Ping(MachineId, u64),
+    /// ping_no: the receiver answers its Ping
+    Pong(u64),
+}
+
+/// A message serialized with ```bincode```, with the [tag](fn.message_tag.html) of its type.
+///
+/// Messages passed in serialized form by the user, e.g. with [send_serialized](../actor/struct.ActorRef.html#method.send_serialized),
+/// carry no tag, the receiving Actor tries its message types in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SerializedMessage {
+    pub(crate) tag: Option<u64>,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl SerializedMessage {
+    /// Encode the *message* in the *format*, tagged with its type.
+    pub(crate) fn of<M: Serialize + 'static>(
+        message: &M,
+        format: WireFormat,
+    ) -> Result<Self, ActlibError> {
+        Ok(SerializedMessage {
+            tag: Some(message_tag::<M>()),
+            bytes: format.serialize(message)?,
+        })
+    }
+
+    /// A message serialized elsewhere, of unknown type.
+    pub(crate) fn untagged(bytes: Vec<u8>) -> Self {
+        SerializedMessage { tag: None, bytes }
+    }
+
+    /// Decode the message to a type the *handler* handles, a tagged message from the *format* it was encoded in.
+    pub(crate) fn deserialize<H: MessageHandler + ?Sized>(
+        &self,
+        handler: &H,
+        format: WireFormat,
+    ) -> Option<Box<dyn Any + Send>> {
+        match self.tag {
+            Some(tag) => handler.deserialize_tagged(tag, &self.bytes, format),
+            None => handler.deserialize_to_any(&self.bytes),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) enum SerNetMessageContent {
-    Message(Vec<u8>),
-    Token(Vec<u8>),
+    /// A message with the Actor that sent it
+    Message(SerializedMessage, Option<ActorId>),
+    /// A serialized [Token] with the Actor that sent it
+    Token(Vec<u8>, Option<ActorId>),
+    /// A message sent with send_keyed, with its coalescing key and the Actor that sent it
+    Keyed(u64, SerializedMessage, Option<ActorId>),
+    /// The watcher to notify once the Actor stopped
+    Watch(ActorId),
 }
@@ -0,0 +1,168 @@
+//! This module connects [Actors](../actor/trait.Actor.html) to an MQTT broker.
+//!
+//! Only available with the ```mqtt``` feature.
+//!
+//! - An [MqttIngress](struct.MqttIngress.html) subscribes to a topic and forwards every decoded payload as a message to an Actor.
+//! - An [MqttEgress](struct.MqttEgress.html) publishes selected messages to a topic, e.g. from within a message handler.
+//!
+//! ```rust,ignore
+//! let options = MqttOptions::new("grid", "broker.lab", 1883);
+//! MqttIngress::new(options, "sensors/+/temperature", decode_temperature).forward_to(field_ref);
+//! ```
+
+use crate::actor::ActorRef;
+use crate::api::ActlibError;
+use crate::message::Message;
+use log::{error, info, warn};
+use rumqttc::{Client, Connection, ConnectionError, Event, Packet};
+pub use rumqttc::{MqttOptions, QoS};
+use std::thread;
+use std::time::Duration;
+
+/// Capacity of the request queue between a client and its connection.
+const REQUEST_CAPACITY: usize = 64;
+
+/// How long to wait before reconnecting after a connection error.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Subscribes to an MQTT topic and forwards every payload to an Actor, converted by a user-supplied decoder.
+#[derive(Debug, Clone)]
+pub struct MqttIngress<M> {
+    options: MqttOptions,
+    topic: String,
+    qos: QoS,
+    decoder: fn(&[u8]) -> Option<M>,
+}
+
+impl<M: Message<'static> + 'static> MqttIngress<M> {
+    /// Create an ingress for the given topic (wildcards are allowed).
+    ///
+    /// Payloads for which the *decoder* returns ```None``` are dropped with a warning.
+    pub fn new(options: MqttOptions, topic: &str, decoder: fn(&[u8]) -> Option<M>) -> Self {
+        MqttIngress {
+            options,
+            topic: topic.to_string(),
+            qos: QoS::AtLeastOnce,
+            decoder,
+        }
+    }
+
+    /// Subscribe with the given quality of service, ```AtLeastOnce``` by default.
+    pub fn qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Start forwarding the decoded payloads to the given Actor.
+    ///
+    /// The ingress runs on its own thread and reconnects after connection errors.
+    /// It stops once the Actor is gone.
+    pub fn forward_to(self, target: ActorRef) {
+        let (client, connection) = Client::new(self.options, REQUEST_CAPACITY);
+        if let Err(e) = client.subscribe(self.topic.as_str(), self.qos) {
+            error!("Failed to subscribe to MQTT topic {}: {:?}", self.topic, e);
+            return;
+        }
+        let topic = self.topic;
+        let decoder = self.decoder;
+        thread::spawn(move || {
+            info!("Forwarding MQTT topic {} to {:?}", topic, target.clone_id());
+            drive(connection, |payload| {
+                match decoder(payload) {
+                    Some(message) => {
+                        if let Err(ActlibError::InvalidActorRef(_))
+                        | Err(ActlibError::ActorStopping(_)) = target.send_message(message)
+                        {
+                            // nobody left to forward to
+                            let _ = client.disconnect();
+                            return false;
+                        }
+                    }
+                    None => warn!("Dropped undecodable payload from MQTT topic {}", topic),
+                }
+                true
+            });
+        });
+    }
+}
+
+/// Publishes messages to an MQTT topic, converted by a user-supplied encoder.
+///
+/// The egress can be cloned and moved into Actors, which then publish from their handlers.
+#[derive(Clone)]
+pub struct MqttEgress<M> {
+    client: Client,
+    topic: String,
+    qos: QoS,
+    encoder: fn(&M) -> Vec<u8>,
+}
+
+impl<M> MqttEgress<M> {
+    /// Connect to the broker, publishing to the given topic.
+    ///
+    /// The connection is driven on its own thread until every clone of the egress is dropped.
+    pub fn connect(options: MqttOptions, topic: &str, encoder: fn(&M) -> Vec<u8>) -> Self {
+        let (client, connection) = Client::new(options, REQUEST_CAPACITY);
+        thread::spawn(move || drive(connection, |_| true));
+        MqttEgress {
+            client,
+            topic: topic.to_string(),
+            qos: QoS::AtLeastOnce,
+            encoder,
+        }
+    }
+
+    /// Publish with the given quality of service, ```AtLeastOnce``` by default.
+    pub fn qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Publish a message.
+    ///
+    /// Fails with [NetworkError](../api/enum.ActlibError.html#variant.NetworkError) if the connection is gone.
+    pub fn publish(&self, message: &M) -> Result<(), ActlibError> {
+        match self.client.publish(
+            self.topic.as_str(),
+            self.qos,
+            false,
+            (self.encoder)(message),
+        ) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(ActlibError::NetworkError(format!(
+                "Failed to publish to MQTT topic {}: {:?}",
+                self.topic, e
+            ))),
+        }
+    }
+}
+
+impl<M> std::fmt::Debug for MqttEgress<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MqttEgress {{topic: {:?}, qos: {:?}}}",
+            self.topic, self.qos
+        )
+    }
+}
+
+/// Run the connection's event loop, passing every received payload to *on_publish* until it returns ```false```.
+fn drive<F: FnMut(&[u8]) -> bool>(mut connection: Connection, mut on_publish: F) {
+    for event in connection.iter() {
+        match event {
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                if !on_publish(&publish.payload) {
+                    break;
+                }
+            }
+            Ok(_) => {}
+            // every client is dropped
+            Err(ConnectionError::RequestsDone) => break,
+            Err(e) => {
+                warn!("MQTT connection error, reconnecting: {:?}", e);
+                thread::sleep(RECONNECT_DELAY);
+            }
+        }
+    }
+}
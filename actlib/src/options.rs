@@ -0,0 +1,1154 @@
+//! This module defines the [EnvironmentOptions](struct.EnvironmentOptions.html) used to tune an [Environment](../api/struct.Environment.html).
+//!
+//! Every option is optional, the defaults match the behaviour of [Environment::new](../api/struct.Environment.html#method.new).
+
+use crate::actor::{ActorId, ExportedState, MachineId};
+use crate::api::{Environment, IdConflict};
+use crate::wire::WireFormat;
+#[cfg(feature = "tls")]
+pub use netchannel::TlsConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Options to tune the behaviour of an [Environment](../api/struct.Environment.html).
+///
+/// Pass them to [Environment::new_with_options](../api/struct.Environment.html#method.new_with_options).
+#[derive(Debug, Clone)]
+pub struct EnvironmentOptions {
+    pub(crate) self_send_guard: Option<SelfSendGuard>,
+    pub(crate) pre_start_messages: PreStartMessages,
+    pub(crate) placement: Placement,
+    pub(crate) mailbox_quotas: HashMap<String, MailboxQuota>,
+    pub(crate) coalescing_mailboxes: HashSet<String>,
+    pub(crate) restricted: HashSet<Capability>,
+    pub(crate) trace_lineage: bool,
+    pub(crate) handler_stats: bool,
+    pub(crate) lock_contention: bool,
+    pub(crate) trace_replies: bool,
+    pub(crate) ordered_broadcasts: bool,
+    pub(crate) failure_policy: FailurePolicy,
+    pub(crate) max_message_size: usize,
+    pub(crate) state_dump: Option<StateDump>,
+    pub(crate) prometheus_port: Option<u16>,
+    pub(crate) id_conflict_detection: Option<IdConflictDetection>,
+    pub(crate) mailbox_alerts: Option<MailboxAlerts>,
+    pub(crate) load_exchange: Option<LoadExchange>,
+    pub(crate) heartbeat: Option<Heartbeat>,
+    pub(crate) reconnect: Option<Reconnect>,
+    pub(crate) payload_lease: Duration,
+    pub(crate) time_budget: Option<TimeBudget>,
+    pub(crate) routes: HashMap<MachineId, MachineId>,
+    pub(crate) machine_id: Option<MachineId>,
+    pub(crate) local_address: Option<IpAddr>,
+    pub(crate) wait_for_peers: Option<usize>,
+    pub(crate) connect_timeout: Option<ConnectTimeout>,
+    pub(crate) runtime: Runtime,
+    pub(crate) worker_threads: Option<usize>,
+    pub(crate) dead_peer_policies: HashMap<String, DeadPeerPolicy>,
+    pub(crate) incarnation_policy: IncarnationPolicy,
+    pub(crate) stop_policies: HashMap<String, StopPolicy>,
+    pub(crate) counter_interval: Duration,
+    pub(crate) reliable_delivery: Option<ReliableDelivery>,
+    pub(crate) durable_outbox: Option<PathBuf>,
+    pub(crate) outbox_retention: OutboxRetention,
+    pub(crate) wire_format: WireFormat,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsConfig>,
+}
+
+impl Default for EnvironmentOptions {
+    fn default() -> Self {
+        EnvironmentOptions {
+            self_send_guard: None,
+            pre_start_messages: PreStartMessages::default(),
+            placement: Placement::default(),
+            mailbox_quotas: HashMap::new(),
+            coalescing_mailboxes: HashSet::new(),
+            restricted: HashSet::new(),
+            trace_lineage: false,
+            handler_stats: false,
+            lock_contention: false,
+            trace_replies: false,
+            ordered_broadcasts: false,
+            failure_policy: FailurePolicy::default(),
+            max_message_size: 16 * 1024 * 1024,
+            state_dump: None,
+            prometheus_port: None,
+            id_conflict_detection: None,
+            mailbox_alerts: None,
+            load_exchange: None,
+            heartbeat: None,
+            reconnect: Some(Reconnect::default()),
+            payload_lease: Duration::from_secs(300),
+            time_budget: None,
+            routes: HashMap::new(),
+            machine_id: None,
+            local_address: None,
+            wait_for_peers: None,
+            connect_timeout: None,
+            runtime: Runtime::default(),
+            worker_threads: None,
+            dead_peer_policies: HashMap::new(),
+            incarnation_policy: IncarnationPolicy::default(),
+            stop_policies: HashMap::new(),
+            counter_interval: Duration::from_secs(1),
+            reliable_delivery: None,
+            durable_outbox: None,
+            outbox_retention: OutboxRetention::default(),
+            wire_format: WireFormat::default(),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+impl EnvironmentOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        EnvironmentOptions::default()
+    }
+
+    /// Watch every Actor for messages it sends to itself in a tight loop.
+    pub fn self_send_guard(mut self, guard: SelfSendGuard) -> Self {
+        self.self_send_guard = Some(guard);
+        self
+    }
+
+    /// Decide what happens to messages that reach an Actor before its [on_start](../actor/trait.Actor.html#method.on_start) method completed.
+    pub fn pre_start_messages(mut self, policy: PreStartMessages) -> Self {
+        self.pre_start_messages = policy;
+        self
+    }
+
+    /// Decide how the Environment picks the machine for a new Actor.
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Limit the bytes queued in the mailbox of every local Actor of the given type id.
+    pub fn mailbox_quota(mut self, actor_type_id: &str, quota: MailboxQuota) -> Self {
+        self.mailbox_quotas.insert(actor_type_id.to_string(), quota);
+        self
+    }
+
+    /// Let [keyed](../message/trait.Keyed.html) Messages replace the queued Messages with the same key
+    /// in the mailbox of every local Actor of the given type id.
+    ///
+    /// Only Messages sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed) are coalesced, all others queue up as usual.
+    pub fn coalescing_mailbox(mut self, actor_type_id: &str) -> Self {
+        self.coalescing_mailboxes.insert(actor_type_id.to_string());
+        self
+    }
+
+    /// Only let Actors [granted](struct.SpawnOptions.html#method.grant) the *capability* perform the operations it guards.
+    ///
+    /// Code running outside of an Actor, like the main thread, is never restricted.
+    /// The requests of remote Actors are checked on arrival as well, their own machine vouches for their capabilities,
+    /// so a machine enforces its restrictions even if the remote machines do not restrict the capability.
+    /// Requests refused this way are dropped with a warning.
+    pub fn restrict(mut self, capability: Capability) -> Self {
+        self.restricted.insert(capability);
+        self
+    }
+
+    /// Remember which Actor spawned which, see [Environment::introspect](../api/struct.Environment.html#method.introspect).
+    ///
+    /// **Note:** The records of removed Actors are kept as well, so memory grows with every spawn.
+    pub fn trace_lineage(mut self) -> Self {
+        self.trace_lineage = true;
+        self
+    }
+
+    /// Count the handled messages and measure the handler durations per Actor type and message type,
+    /// see [Environment::handler_stats](../api/struct.Environment.html#method.handler_stats).
+    pub fn handler_stats(mut self) -> Self {
+        self.handler_stats = true;
+        self
+    }
+
+    /// Measure how long the busiest internal locks of the Environment wait, as histograms in the [metrics](../api/struct.Environment.html#method.metrics).
+    ///
+    /// The instrumented locks guard the local mailboxes, the connections to the remote machines and the pending alive-queries.
+    pub fn lock_contention(mut self) -> Self {
+        self.lock_contention = true;
+        self
+    }
+
+    /// Correlate every [ask](../actor/struct.ActorRef.html#method.ask) with its reply and measure the time in between
+    /// per pair of requester type and responder type, see [Environment::reply_stats](../api/struct.Environment.html#method.reply_stats).
+    ///
+    /// Enable it on every machine, the machine of the responder tells the machine of the requester its type.
+    pub fn trace_replies(mut self) -> Self {
+        self.trace_replies = true;
+        self
+    }
+
+    /// Deliver [broadcasts](../api/struct.Environment.html#method.broadcast) to the local Actors sorted by their ActorId,
+    /// instead of in the arbitrary order of the Environment's registry.
+    ///
+    /// Each machine enqueues the broadcasts it receives in the order they arrive, so two broadcasts from the same machine
+    /// are seen in the same order by every Actor. Use [sequenced broadcasts](../api/struct.Environment.html#method.broadcast_sequenced)
+    /// to detect missed ones.
+    pub fn ordered_broadcasts(mut self) -> Self {
+        self.ordered_broadcasts = true;
+        self
+    }
+
+    /// What happens to an Actor whose handler panicked, [FailurePolicy::Stop](enum.FailurePolicy.html#variant.Stop) by default.
+    ///
+    /// Either way the failure is reported to the [watchers](../api/struct.Environment.html#method.watch_actor_failures).
+    pub fn failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Limit the serialized size of a single message sent to or received from a remote machine, 16 MiB by default.
+    ///
+    /// Messages larger than a network frame are transparently split into chunks and reassembled by the receiver.
+    /// Larger messages are not sent, and partially received ones are dropped with a warning. The receiver keeps the chunks
+    /// of a few messages per remote machine with at most this many bytes in total, and drops them when the connection breaks.
+    ///
+    /// Machines agree on the smaller of their limits when connecting. Sending a larger message to a remote Actor fails right away
+    /// with [MessageTooLarge](../api/enum.ActlibError.html#variant.MessageTooLarge), and a machine sending larger frames anyway loses its connection.
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = bytes;
+        self
+    }
+
+    /// Periodically write the [introspection](../api/struct.Environment.html#method.introspect) data to disk, e.g. for post-mortem analysis.
+    pub fn state_dump(mut self, state_dump: StateDump) -> Self {
+        self.state_dump = Some(state_dump);
+        self
+    }
+
+    /// Serve the [metrics](../api/struct.Environment.html#method.metrics) of the local machine in the Prometheus text format,
+    /// over HTTP on the given port of every local address.
+    pub fn prometheus_exporter(mut self, port: u16) -> Self {
+        self.prometheus_port = Some(port);
+        self
+    }
+
+    /// Detect and resolve Actors that hold the same specified id on two machines at once.
+    pub fn id_conflict_detection(mut self, detection: IdConflictDetection) -> Self {
+        self.id_conflict_detection = Some(detection);
+        self
+    }
+
+    /// Periodically sample the mailbox of every local Actor and alert about slow consumers.
+    pub fn mailbox_alerts(mut self, alerts: MailboxAlerts) -> Self {
+        self.mailbox_alerts = Some(alerts);
+        self
+    }
+
+    /// Periodically exchange the load of every machine, see [subscribe_cluster_load](../api/struct.Environment.html#method.subscribe_cluster_load).
+    pub fn load_exchange(mut self, exchange: LoadExchange) -> Self {
+        self.load_exchange = Some(exchange);
+        self
+    }
+
+    /// Periodically ping every connected machine, see [cluster_status](../api/struct.Environment.html#method.cluster_status).
+    pub fn heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Encrypt the traffic with every remote machine with TLS, requires the ```tls``` feature.
+    ///
+    /// Every machine has to use TLS with a certificate signed by the same authority, see
+    /// [TlsConfig::from_pem_files](struct.TlsConfig.html#method.from_pem_files).
+    /// A remote machine presenting a certificate that is not accepted is [Failed](../api/enum.PeerState.html#variant.Failed).
+    ///
+    /// ```rust,ignore
+    /// let tls = TlsConfig::from_pem_files("machine.pem", "machine.key", "ca.pem").unwrap();
+    /// let (env, expiration_checker) =
+    ///     Environment::new_with_options(7300, &remotes, actor_builder, EnvironmentOptions::new().tls(tls));
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Decide how a dropped connection to a remote machine is re-established, the [defaults](struct.Reconnect.html) are used otherwise.
+    pub fn reconnect(mut self, reconnect: Reconnect) -> Self {
+        self.reconnect = Some(reconnect);
+        self
+    }
+
+    /// Give up on a remote machine once its connection dropped, messages to it fail from then on.
+    pub fn without_reconnect(mut self) -> Self {
+        self.reconnect = None;
+        self
+    }
+
+    /// Keep [stashed payloads](../api/struct.Environment.html#method.stash_payload) for the given time, 5 minutes by default.
+    pub fn payload_lease(mut self, lease: Duration) -> Self {
+        self.payload_lease = lease;
+        self
+    }
+
+    /// Bound the time a handler runs without giving way to other Actors, see [checkpoint](../actor/fn.checkpoint.html).
+    pub fn time_budget(mut self, budget: TimeBudget) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Reach the *destination* machine through the *gateway* machine instead of a direct connection,
+    /// e.g. for machines behind a NAT that can only reach a single gateway.
+    ///
+    /// The *destination* must not be passed as a remote to the Environment, the *gateway* must be.
+    /// The gateway forwards the messages, following its own routes if it is not connected to the destination either.
+    /// The destination learns the way back from the relayed messages, so it does not need a route of its own.
+    ///
+    /// **Note:** Relayed machines are reachable by their Actors, but new Actors are only [spawned](../api/struct.Environment.html#method.spawn) on directly connected machines.
+    pub fn route(mut self, destination: MachineId, gateway: MachineId) -> Self {
+        self.routes.insert(destination, gateway);
+        self
+    }
+
+    /// Identify the local machine by the given id, instead of a new random id on every start.
+    ///
+    /// Use [MachineId::load_or_create](../api/struct.MachineId.html#method.load_or_create) to keep the id across restarts,
+    /// so routes and logs refer to the same machine even if its address changed.
+    pub fn machine_id(mut self, machine_id: MachineId) -> Self {
+        self.machine_id = Some(machine_id);
+        self
+    }
+
+    /// Address the local machine by the given IP, instead of the first network interface that is not a loopback.
+    ///
+    /// Useful on machines with several network interfaces, or to run several machines on a single host, e.g. on ```127.0.0.1``` and ```127.0.0.2```.
+    pub fn local_address(mut self, ip: IpAddr) -> Self {
+        self.local_address = Some(ip);
+        self
+    }
+
+    /// Return from creating the Environment once *n* remote machines are connected, instead of waiting for all of them.
+    ///
+    /// The other machines are connected in the background, and join the placement of new Actors once they are.
+    /// Follow them with [Environment::peer_status](../api/struct.Environment.html#method.peer_status) and
+    /// [Environment::watch_peers](../api/struct.Environment.html#method.watch_peers).
+    /// With ```0``` the first machine up can start with local-only work right away.
+    ///
+    /// Ignored by [new_with_bootstrap](../api/struct.Environment.html#method.new_with_bootstrap), which needs every machine.
+    pub fn wait_for_peers(mut self, n: usize) -> Self {
+        self.wait_for_peers = Some(n);
+        self
+    }
+
+    /// Stop waiting for the remote machines when creating the Environment once the [ConnectTimeout](struct.ConnectTimeout.html) passed.
+    ///
+    /// [Environment::try_new_with_peers](../api/struct.Environment.html#method.try_new_with_peers) fails with
+    /// [NetworkError](../api/enum.ActlibError.html#variant.NetworkError) then, unless the timeout [starts a partial cluster](struct.ConnectTimeout.html#method.start_partial).
+    /// The other constructors panic instead.
+    pub fn connect_timeout(mut self, connect_timeout: ConnectTimeout) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Decide where the mailbox loops of the local Actors run, [Runtime::Pool](enum.Runtime.html#variant.Pool) by default.
+    pub fn runtime(mut self, runtime: Runtime) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// Run the local Actors on the given number of worker threads, one per core by default.
+    ///
+    /// Applies to [Runtime::Pool](enum.Runtime.html#variant.Pool) and [Runtime::Async](enum.Runtime.html#variant.Async).
+    pub fn worker_threads(mut self, workers: usize) -> Self {
+        self.worker_threads = Some(workers.max(1));
+        self
+    }
+
+    /// Decide what happens to Messages for keyed Actors of the given type id while their machine is disconnected,
+    /// [DeadPeerPolicy::Queue](enum.DeadPeerPolicy.html#variant.Queue) by default.
+    ///
+    /// Keyed Actors are the ones with a User-specified id. The policy applies to those this machine spawned,
+    /// or [found](../api/struct.Environment.html#method.find_actor_ref) on a remote machine, as only their type id is known here.
+    pub fn dead_peer_policy(mut self, actor_type_id: &str, policy: DeadPeerPolicy) -> Self {
+        self.dead_peer_policies
+            .insert(actor_type_id.to_string(), policy);
+        self
+    }
+
+    /// Decide what happens to Messages addressed to an earlier incarnation of a local keyed Actor,
+    /// [IncarnationPolicy::Allow](enum.IncarnationPolicy.html#variant.Allow) by default.
+    pub fn incarnation_policy(mut self, policy: IncarnationPolicy) -> Self {
+        self.incarnation_policy = policy;
+        self
+    }
+
+    /// Decide what happens to the Messages left in the mailbox of a local Actor of the given type id when it is asked to stop,
+    /// [StopPolicy::DropRemaining](enum.StopPolicy.html#variant.DropRemaining) by default.
+    ///
+    /// A failed Actor that stops under [FailurePolicy::Stop](enum.FailurePolicy.html#variant.Stop) always drops them.
+    pub fn stop_policy(mut self, actor_type_id: &str, policy: StopPolicy) -> Self {
+        self.stop_policies.insert(actor_type_id.to_string(), policy);
+        self
+    }
+
+    /// Send the local shares of the [Counters](../counters/struct.Counter.html) and [Gauges](../counters/struct.Gauge.html) to the other machines every *interval*, every second by default.
+    pub fn counter_interval(mut self, interval: Duration) -> Self {
+        self.counter_interval = interval;
+        self
+    }
+
+    /// Confirm the delivery of every Message sent to a remote Actor, retrying the unconfirmed ones,
+    /// see [ReliableDelivery](struct.ReliableDelivery.html).
+    ///
+    /// Only the sending machine needs the option, every machine acknowledges the Messages it is asked to.
+    pub fn reliable_delivery(mut self, delivery: ReliableDelivery) -> Self {
+        self.reliable_delivery = Some(delivery);
+        self
+    }
+
+    /// Write every Message to a remote Actor to a log at *path* before it is sent, until it is acknowledged, rejected or given up on.
+    ///
+    /// Messages that were not settled when the process crashed are sent again once the Environment with the same log is started anew,
+    /// so every Message is delivered at least once. Enables [reliable delivery](#method.reliable_delivery) with the defaults, if it was not configured.
+    ///
+    /// Use the same [machine id](#method.machine_id) across restarts, so the acknowledgements of Messages sent before a restart find their way back.
+    /// The log is not synced to disk for every Message, it survives crashes of the process but not of the operating system.
+    ///
+    /// Messages to a machine whose connection is down are kept in the log instead of being given up on after *max_retries*,
+    /// so they reach it once it restarted and reconnected, within the [retention](struct.OutboxRetention.html).
+    pub fn durable_outbox<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.durable_outbox = Some(path.into());
+        if self.reliable_delivery.is_none() {
+            self.reliable_delivery = Some(ReliableDelivery::default());
+        }
+        self
+    }
+
+    /// Bound the Messages kept in the [durable outbox](#method.durable_outbox) for machines that are down,
+    /// see [OutboxRetention](struct.OutboxRetention.html).
+    pub fn outbox_retention(mut self, retention: OutboxRetention) -> Self {
+        self.outbox_retention = retention;
+        self
+    }
+
+    /// Exchange the messages with the remote machines in *format*, [bincode](../wire/enum.WireFormat.html#variant.Bincode) by default.
+    ///
+    /// Every machine of the cluster has to use the same format.
+    pub fn wire_format(mut self, format: WireFormat) -> Self {
+        self.wire_format = format;
+        self
+    }
+}
+
+/// Acknowledged delivery of the Messages sent to remote Actors.
+///
+/// Every Message [sent](../actor/struct.ActorRef.html#method.send_message) to a remote Actor carries a delivery number,
+/// the receiving machine acknowledges it once the Message was enqueued in the mailbox of the Actor.
+/// A Message that is not acknowledged within *ack_timeout* is sent again, up to *max_retries* times, and the receiver drops the duplicates.
+/// Messages the receiver rejected, e.g. because the Actor does not exist, and Messages that were never acknowledged
+/// are reported to the [watchers](../api/struct.Environment.html#method.watch_delivery_failures).
+///
+/// **Note:** Retried Messages may overtake the ones sent after them.
+#[derive(Debug, Clone)]
+pub struct ReliableDelivery {
+    /// How long to wait for the acknowledgement before sending the Message again.
+    pub ack_timeout: Duration,
+    /// How often a Message is sent again before it is reported as failed.
+    pub max_retries: u32,
+}
+
+impl Default for ReliableDelivery {
+    fn default() -> Self {
+        ReliableDelivery {
+            ack_timeout: Duration::from_secs(1),
+            max_retries: 3,
+        }
+    }
+}
+
+impl ReliableDelivery {
+    /// Wait one second for every acknowledgement, retrying 3 times.
+    pub fn new() -> Self {
+        ReliableDelivery::default()
+    }
+
+    /// Send a Message again once it was not acknowledged within *ack_timeout*.
+    pub fn ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    /// Report a Message as failed once it was sent again *max_retries* times without being acknowledged.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+/// How much of the unacknowledged Messages the [durable outbox](struct.EnvironmentOptions.html#method.durable_outbox) keeps.
+///
+/// A Message that was first sent more than *max_age* ago, also before a restart, is given up on.
+/// So are the oldest Messages to a machine once the unacknowledged ones exceed *max_bytes*.
+/// They are reported to the [delivery failure watchers](../api/struct.Environment.html#method.watch_delivery_failures) as never acknowledged.
+#[derive(Debug, Clone)]
+pub struct OutboxRetention {
+    /// Maximal number of serialized bytes kept per machine.
+    pub max_bytes: usize,
+    /// Longest time a Message is kept since it was first sent.
+    pub max_age: Duration,
+}
+
+impl Default for OutboxRetention {
+    fn default() -> Self {
+        OutboxRetention {
+            max_bytes: 64 * 1024 * 1024,
+            max_age: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl OutboxRetention {
+    /// Keep up to 64MiB per machine, for an hour.
+    pub fn new() -> Self {
+        OutboxRetention::default()
+    }
+
+    /// Keep at most *max_bytes* of serialized Messages per machine.
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Keep a Message at most *max_age* after it was first sent.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+}
+
+/// What happens to a Message for a keyed Actor living on a machine whose connection was lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeadPeerPolicy {
+    /// Drop the Message and report it to the [dead letter watchers](../api/struct.Environment.html#method.watch_dead_letters).
+    DeadLetter,
+    /// Buffer the Message until the machine is [reconnected](struct.Reconnect.html), like every other Message.
+    #[default]
+    Queue,
+    /// Spawn the Actor with the same id on a connected machine and redirect this and every later Message to it.
+    ///
+    /// The redirect lasts until the Environment expires, also if the machine returns. Machines redirecting on their own
+    /// may spawn the id twice, see [id conflict detection](struct.EnvironmentOptions.html#method.id_conflict_detection).
+    Respawn,
+}
+
+/// What happens to a Message addressed to an earlier [incarnation](../actor/struct.ActorId.html#method.incarnation) of a local Actor,
+/// e.g. sent with an ActorRef kept across the removal and respawn of a keyed Actor.
+///
+/// Only Messages from remote machines and ActorRefs resolved from an ActorId are checked, as an ActorRef to a removed local Actor reaches no one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IncarnationPolicy {
+    /// Deliver the Message to the current incarnation.
+    #[default]
+    Allow,
+    /// Deliver the Message to the current incarnation and report it to the [watchers](../api/struct.Environment.html#method.watch_stale_incarnations).
+    Notify,
+    /// Drop the Message and report it to the [watchers](../api/struct.Environment.html#method.watch_stale_incarnations).
+    ///
+    /// Under [reliable delivery](struct.EnvironmentOptions.html#method.reliable_delivery) the sender learns about the rejection.
+    RejectStale,
+}
+
+/// What happens to the Messages left in the mailbox of an Actor when it is [removed](../api/struct.Environment.html#method.remove) or its Environment expires,
+/// e.g. the ones it [stashed](../actor/struct.Context.html#method.stash).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StopPolicy {
+    /// Call [on_stop_with_reason](../actor/trait.Actor.html#method.on_stop_with_reason) right away and drop the remaining Messages.
+    #[default]
+    DropRemaining,
+    /// Handle the Messages that were left when the Actor was asked to stop, then call on_stop.
+    ///
+    /// Messages the Actor stashes again are dropped.
+    DrainThenStop,
+    /// Pass the remaining Messages to [on_stop_with_pending](../actor/trait.Actor.html#method.on_stop_with_pending) instead of handling them,
+    /// e.g. to flush final updates at once.
+    HandOver,
+}
+
+/// Where the mailbox loops of the local Actors run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Runtime {
+    /// Every Actor runs on its own OS thread.
+    Threads,
+    /// The Actors share a fixed number of [worker threads](struct.EnvironmentOptions.html#method.worker_threads).
+    ///
+    /// An Actor with an empty mailbox does not occupy a thread, and a worker takes turns between the Actors with queued messages.
+    /// While a handler waits for an [ask](../actor/struct.ResponseFuture.html#method.wait), a [query](../actor/struct.ActorRef.html#method.query)
+    /// or in [Environment::block_on](../api/struct.Environment.html#method.block_on), a spare thread runs the other Actors.
+    /// Other blocking calls in a handler, e.g. sleeping or reading a socket, keep its worker from running other Actors.
+    #[default]
+    Pool,
+    /// Every Actor runs as a task on a tokio runtime with one worker thread per core, requires the ```async-runtime``` feature.
+    ///
+    /// An Actor with an empty mailbox does not occupy a thread, so many thousand Actors are cheap.
+    /// [Async handlers](../macro.impl_message_handler.html) are awaited by the task of their Actor, so the worker runs other Actors meanwhile.
+    /// While a synchronous handler waits for an [ask](../actor/struct.ResponseFuture.html#method.wait), a [query](../actor/struct.ActorRef.html#method.query)
+    /// or in [Environment::block_on](../api/struct.Environment.html#method.block_on), the other tasks move to another worker.
+    /// Other blocking calls in a handler, e.g. sleeping or reading a socket, keep its worker from running other Actors.
+    #[cfg(feature = "async-runtime")]
+    Async,
+}
+
+/// Cooperative time slicing, keeping the latency bounded when cheap and expensive handlers are mixed.
+///
+/// A handler running longer than *slice* gives way according to the *penalty* at its next [checkpoint](../actor/fn.checkpoint.html),
+/// and its Actor once more before handling the next message.
+#[derive(Debug, Clone)]
+pub struct TimeBudget {
+    /// How long a handler may run before giving way.
+    pub slice: Duration,
+    /// What the Actor thread does once the slice is used up.
+    pub penalty: YieldPolicy,
+}
+
+impl Default for TimeBudget {
+    fn default() -> Self {
+        TimeBudget {
+            slice: Duration::from_millis(10),
+            penalty: YieldPolicy::Yield,
+        }
+    }
+}
+
+impl TimeBudget {
+    /// Yield after 10 milliseconds.
+    pub fn new() -> Self {
+        TimeBudget::default()
+    }
+}
+
+/// How a [broadcast in slices](../api/struct.Environment.html#method.broadcast_in_slices) spreads the Message over the Actors.
+///
+/// Every *interval* the next *slice_size* Actors get the Message on the timer thread, so big populations
+/// do not hold up the sender nor the other users of the Environment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastSlices {
+    /// Number of Actors getting the Message per slice, at least one.
+    pub slice_size: usize,
+    /// Time between two slices.
+    pub interval: Duration,
+}
+
+impl Default for BroadcastSlices {
+    fn default() -> Self {
+        BroadcastSlices {
+            slice_size: 1000,
+            interval: Duration::from_millis(1),
+        }
+    }
+}
+
+impl BroadcastSlices {
+    /// 1000 Actors every millisecond.
+    pub fn new() -> Self {
+        BroadcastSlices::default()
+    }
+
+    /// Hand the Message to *slice_size* Actors per slice.
+    pub fn slice_size(mut self, slice_size: usize) -> Self {
+        self.slice_size = slice_size;
+        self
+    }
+
+    /// Wait *interval* between two slices.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Thresholds for the mailboxes of local Actors, checked every *interval*.
+///
+/// An Actor exceeding a threshold is logged and reported to the [watchers](../api/struct.Environment.html#method.watch_mailbox_alerts) once,
+/// it is reported again only after its mailbox was back within the thresholds.
+#[derive(Debug, Clone)]
+pub struct MailboxAlerts {
+    /// Time between two samples.
+    pub interval: Duration,
+    /// Number of queued messages tolerated, unlimited if ```None```.
+    pub max_depth: Option<usize>,
+    /// How long the oldest queued message may wait, unlimited if ```None```.
+    pub max_age: Option<Duration>,
+}
+
+impl Default for MailboxAlerts {
+    fn default() -> Self {
+        MailboxAlerts {
+            interval: Duration::from_secs(1),
+            max_depth: None,
+            max_age: None,
+        }
+    }
+}
+
+impl MailboxAlerts {
+    /// Sample every second, without any threshold.
+    pub fn new() -> Self {
+        MailboxAlerts::default()
+    }
+
+    /// Alert once more than *max_depth* messages are queued.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Alert once the oldest queued message waits longer than *max_age*.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+/// Exchange of the [MachineLoad](../api/struct.MachineLoad.html) of every machine.
+///
+/// Every *interval* each machine measures its own load, sends it to its peers
+/// and hands the latest load of all machines to the [subscribers](../api/struct.Environment.html#method.subscribe_cluster_load).
+/// The load of a machine that did not report for three intervals is left out.
+#[derive(Debug, Clone)]
+pub struct LoadExchange {
+    /// Time between two measurements.
+    pub interval: Duration,
+}
+
+impl Default for LoadExchange {
+    fn default() -> Self {
+        LoadExchange {
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl LoadExchange {
+    /// Exchange the load every second.
+    pub fn new() -> Self {
+        LoadExchange::default()
+    }
+
+    /// Exchange the load every *interval*.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// How long creating an [Environment](../api/struct.Environment.html) waits for its remote machines, see [EnvironmentOptions::connect_timeout](struct.EnvironmentOptions.html#method.connect_timeout).
+///
+/// Only the machines the Environment [waits for](struct.EnvironmentOptions.html#method.wait_for_peers) count.
+#[derive(Debug, Clone)]
+pub struct ConnectTimeout {
+    /// Time the remote machines have to connect.
+    pub timeout: Duration,
+    /// Start with the machines connected so far instead of failing.
+    pub partial: bool,
+}
+
+impl ConnectTimeout {
+    /// Fail if the remote machines did not connect within *timeout*.
+    pub fn new(timeout: Duration) -> Self {
+        ConnectTimeout {
+            timeout,
+            partial: false,
+        }
+    }
+
+    /// Start the Environment with the machines connected within the timeout, the others are connected in the background
+    /// as if the Environment did not [wait for](struct.EnvironmentOptions.html#method.wait_for_peers) them.
+    ///
+    /// Ignored by [new_with_bootstrap](../api/struct.Environment.html#method.new_with_bootstrap), which needs every machine.
+    pub fn start_partial(mut self) -> Self {
+        self.partial = true;
+        self
+    }
+}
+
+/// Pinging every connected machine periodically, so the [cluster status](../api/struct.Environment.html#method.cluster_status) tells which machines answer.
+///
+/// A machine that did not answer *missed_beats* pings in a row within *timeout* is ```Down``` until it answers again.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    /// Time between two pings.
+    pub interval: Duration,
+    /// How long a machine has to answer a ping.
+    pub timeout: Duration,
+    /// Number of unanswered pings in a row after which a machine is down.
+    pub missed_beats: u32,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_millis(500),
+            missed_beats: 3,
+        }
+    }
+}
+
+impl Heartbeat {
+    /// Ping every second, a machine is down after three pings not answered within half a second.
+    pub fn new() -> Self {
+        Heartbeat::default()
+    }
+
+    /// Ping every *interval*.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Give every machine *timeout* to answer a ping.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Consider a machine down after it did not answer *missed_beats* pings in a row, at least one.
+    pub fn missed_beats(mut self, missed_beats: u32) -> Self {
+        self.missed_beats = missed_beats.max(1);
+        self
+    }
+}
+
+/// Re-establishing a dropped connection to a remote machine.
+///
+/// The machines connect again after a delay starting at *initial_delay*, doubling with every failed attempt up to *max_delay*.
+/// Messages to the machine are buffered meanwhile and sent once the connection is back, in the order they were sent.
+/// Messages beyond *max_buffered_bytes* are rejected with a NotConnected error.
+#[derive(Debug, Clone)]
+pub struct Reconnect {
+    /// Delay before the first attempt.
+    pub initial_delay: Duration,
+    /// Longest delay between two attempts.
+    pub max_delay: Duration,
+    /// Maximal number of bytes buffered for a machine while its connection is down.
+    pub max_buffered_bytes: usize,
+    /// Stop trying after this long, ```None``` to try until the Environment expires.
+    pub give_up_after: Option<Duration>,
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Reconnect {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            max_buffered_bytes: 16 * 1024 * 1024,
+            give_up_after: None,
+        }
+    }
+}
+
+impl Reconnect {
+    /// Retry after 100ms, backing off to 10s, buffering up to 16MiB per machine, forever.
+    pub fn new() -> Self {
+        Reconnect::default()
+    }
+
+    /// Wait *initial_delay* before the first attempt.
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    /// Wait at most *max_delay* between two attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Buffer at most *max_buffered_bytes* per machine while its connection is down.
+    pub fn max_buffered_bytes(mut self, max_buffered_bytes: usize) -> Self {
+        self.max_buffered_bytes = max_buffered_bytes;
+        self
+    }
+
+    /// Give up on a machine whose connection could not be re-established within *give_up_after*.
+    pub fn give_up_after(mut self, give_up_after: Duration) -> Self {
+        self.give_up_after = Some(give_up_after);
+        self
+    }
+}
+
+/// Detection of Actors [spawned with the same id](../api/struct.Environment.html#method.spawn_with_id) on two machines,
+/// e.g. because both sides of a network partition spawned it.
+///
+/// Every *interval* each machine tells its peers which specified ids it holds.
+/// A machine that holds one of these ids as well resolves the conflict for its own Actor according to the *policy*,
+/// and notifies the [watchers](../api/struct.Environment.html#method.watch_id_conflicts).
+#[derive(Debug, Clone)]
+pub struct IdConflictDetection {
+    /// Time between two exchanges of the specified ids.
+    pub interval: Duration,
+    /// How a detected conflict is resolved.
+    pub policy: ConflictPolicy,
+}
+
+impl IdConflictDetection {
+    /// Exchange the specified ids every 5 seconds.
+    pub fn new(policy: ConflictPolicy) -> Self {
+        IdConflictDetection {
+            interval: Duration::from_secs(5),
+            policy,
+        }
+    }
+}
+
+/// How two Actors with the same specified id are reduced to one.
+///
+/// The Actor spawned first is the older one, ties are broken by the lower machine id.
+/// Stopped Actors are passed [StopReason::IdConflict](../actor/enum.StopReason.html#variant.IdConflict).
+#[derive(Debug, Clone)]
+pub enum ConflictPolicy {
+    /// Stop the younger Actor.
+    KeepOldest,
+    /// Call the hook on the machine of the younger Actor before it is stopped,
+    /// e.g. to hand its state over to the older Actor.
+    Merge(fn(&Environment, &IdConflict)),
+    /// Stop both Actors and spawn a fresh one on the machine of the older Actor.
+    KillBothAndRespawn,
+}
+
+/// Periodic dump of the [Introspection](../api/struct.Introspection.html) data to a rolling set of files.
+///
+/// Every *interval* a gzip-compressed JSON file named ```<machine id>-<sequence number>.json.gz``` is written to *directory*,
+/// only the newest *keep* files are kept.
+#[derive(Debug, Clone)]
+pub struct StateDump {
+    pub(crate) directory: PathBuf,
+    pub(crate) interval: Duration,
+    pub(crate) keep: usize,
+}
+
+impl StateDump {
+    /// Dump every second into the given directory, created if missing, keeping the last 64 files.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        StateDump {
+            directory: directory.into(),
+            interval: Duration::from_secs(1),
+            keep: 64,
+        }
+    }
+
+    /// Wait *interval* between two dumps.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Keep the newest *keep* files, at least one.
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+}
+
+/// Upper limit for the serialized size of all messages queued in an Actor's mailbox.
+///
+/// Messages are measured in the configured [WireFormat](../wire/enum.WireFormat.html), local messages only for mailboxes with a quota.
+/// Stop and Reset requests are never limited.
+#[derive(Debug, Clone)]
+pub struct MailboxQuota {
+    /// Maximal number of bytes queued.
+    pub max_bytes: usize,
+    /// What happens to messages exceeding the quota.
+    pub policy: QuotaPolicy,
+}
+
+/// What happens to messages that would exceed a [MailboxQuota](struct.MailboxQuota.html).
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuotaPolicy {
+    /// Reject the new message, [send_message](../actor/struct.ActorRef.html#method.send_message) fails with [MailboxFull](../api/enum.ActlibError.html#variant.MailboxFull).
+    ///
+    /// Messages from remote machines are dropped with a warning.
+    Reject,
+    /// Accept the new message, the oldest queued messages are dropped before they are handled until the mailbox fits the quota again.
+    ///
+    /// Every dropped message is reported to the [watchers](../api/struct.Environment.html#method.watch_dropped_messages).
+    DropOldest,
+}
+
+/// How an [Environment](../api/struct.Environment.html) picks the machine a new Actor is spawned on.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Placement {
+    /// Take turns over all machines, starting with the local one.
+    #[default]
+    RoundRobin,
+    /// Pick a machine at random.
+    Random,
+    /// Place every Actor [spawned with an id](../api/struct.Environment.html#method.spawn_with_id) on the machine the id hashes to,
+    /// so every machine knows where an id lives without asking the others, see [Environment::machine_for_id](../api/struct.Environment.html#method.machine_for_id).
+    /// The other Actors take turns like with RoundRobin.
+    ///
+    /// Only the ids of a joining or leaving machine move, the others keep their machine.
+    /// [find_actor_ref](../api/struct.Environment.html#method.find_actor_ref) asks the machine an id hashes to first.
+    ConsistentHash,
+}
+
+/// How a [pool](../api/struct.Environment.html#method.spawn_pool) distributes the messages sent to its router over the workers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RouterKind {
+    /// Take turns over the workers.
+    RoundRobin,
+    /// Send every message to every worker.
+    Broadcast,
+    /// Send every message to the worker its key hashes to, so the messages with the same key reach the same worker.
+    ///
+    /// The key of a message sent with [send_keyed](../actor/struct.ActorRef.html#method.send_keyed) or [ask_keyed](../actor/struct.ActorRef.html#method.ask_keyed)
+    /// is its [Keyed](../message/trait.Keyed.html) key, the key of any other message its serialized content.
+    ///
+    /// Only the messages of an added or removed worker move to other workers when the pool is [resized](../api/struct.Environment.html#method.resize_pool).
+    ConsistentHash,
+}
+
+/// Constraints for a single [spawn](../api/struct.Environment.html#method.spawn_with_options).
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    pub(crate) excluded_machines: Vec<MachineId>,
+    /// Set for spawn requests from remote machines, otherwise the spawning Actor is taken from the current thread
+    pub(crate) spawner: Option<ActorId>,
+    pub(crate) capabilities: Vec<Capability>,
+    pub(crate) machine: Option<MachineId>,
+    /// Set by [Context::spawn_after_handle](../actor/struct.Context.html#method.spawn_after_handle), the Actor is registered but not started
+    pub(crate) hold_start: bool,
+    /// Set by [Environment::restore](../api/struct.Environment.html#method.restore), imported before the Actor starts
+    pub(crate) state: Option<ExportedState>,
+    /// Set by [Environment::spawn_with_args](../api/struct.Environment.html#method.spawn_with_args), passed to on_init before the Actor starts
+    pub(crate) args: Option<Vec<u8>>,
+    /// Set for routers, local senders serialize their messages so they can be forwarded to any machine
+    pub(crate) serialized_mailbox: bool,
+}
+
+impl SpawnOptions {
+    /// Create options without any constraints.
+    pub fn new() -> Self {
+        SpawnOptions::default()
+    }
+
+    /// Never place the Actor on one of the given machines.
+    ///
+    /// Useful for Actors that need resources only some machines provide, like open firewall ports.
+    ///
+    /// Accepts [MachineIds](../actor/struct.MachineId.html) as well as the [MachineRefs](../api/struct.MachineRef.html)
+    /// returned by [Environment::machines](../api/struct.Environment.html#method.machines).
+    pub fn exclude_machines<M: Clone + Into<MachineId>>(mut self, machines: &[M]) -> Self {
+        self.excluded_machines
+            .extend(machines.iter().cloned().map(Into::into));
+        self
+    }
+
+    /// Place the Actor on the given machine instead of letting the [Placement](enum.Placement.html) pick one.
+    ///
+    /// The spawn fails with [SpawnFailed](../api/enum.ActlibError.html#variant.SpawnFailed)
+    /// if the machine is not connected or [excluded](struct.SpawnOptions.html#method.exclude_machines).
+    pub fn on_machine<M: Into<MachineId>>(mut self, machine: M) -> Self {
+        self.machine = Some(machine.into());
+        self
+    }
+
+    /// Allow the Actor the operations guarded by a [restricted](struct.EnvironmentOptions.html#method.restrict) *capability*.
+    ///
+    /// An Actor can only grant the capabilities it holds itself.
+    pub fn grant(mut self, capability: Capability) -> Self {
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+        self
+    }
+}
+
+/// An operation that can be [restricted](struct.EnvironmentOptions.html#method.restrict) to the Actors holding the capability.
+///
+/// A restricted operation called by an Actor without the capability fails with [PermissionDenied](../api/enum.ActlibError.html#variant.PermissionDenied),
+/// or is refused with an error log if it does not return a Result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Capability {
+    /// [Remove](../api/struct.Environment.html#method.remove) other Actors, an Actor can always remove itself.
+    Remove,
+    /// [Broadcast](../api/struct.Environment.html#method.broadcast) Messages to all Actors.
+    Broadcast,
+    /// [Expire](../api/struct.Environment.html#method.set_expired) or [shut down](../api/struct.Environment.html#method.shutdown) the whole Environment.
+    Expire,
+}
+
+/// What happens to messages that reach an Actor before its [on_start](../actor/trait.Actor.html#method.on_start) method completed.
+///
+/// Regardless of the policy no handler is ever called before on_start completed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum PreStartMessages {
+    /// Keep the messages in the mailbox and handle them in order of arrival once on_start completed.
+    #[default]
+    Deliver,
+    /// Drop the messages and report them as [dropped](../api/struct.Environment.html#method.watch_dropped_messages), a Stop or Reset request is still honored.
+    ///
+    /// This includes messages the Actor sends to itself from within on_start.
+    Discard,
+}
+
+/// What happens to an Actor whose handler, [on_start](../actor/trait.Actor.html#tymethod.on_start) or [on_reset](../actor/trait.Actor.html#method.on_reset) panicked.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum FailurePolicy {
+    /// Stop the Actor with [StopReason::Failed](../actor/enum.StopReason.html#variant.Failed) and discard its mailbox.
+    #[default]
+    Stop,
+    /// Drop the message and carry on with the next one, trusting the Actor's state to be consistent.
+    Resume,
+}
+
+/// Detection of Actors that keep sending messages to themselves and starve every other sender.
+///
+/// Every handled message is checked for messages the handler sent to its own Actor.
+/// A warning is logged once the number of consecutive self-sending handlers exceeds *max_depth*,
+/// or the share of self-sending handlers among the last *window* handled messages exceeds *max_ratio*.
+#[derive(Debug, Clone)]
+pub struct SelfSendGuard {
+    /// Number of consecutive self-sending handlers tolerated without a warning.
+    pub max_depth: usize,
+    /// Share (between 0.0 and 1.0) of self-sending handlers tolerated without a warning.
+    pub max_ratio: f64,
+    /// Number of handled messages the ratio is computed over.
+    pub window: usize,
+    /// What the Actor thread does between two consecutive self-sending handlers.
+    pub yield_policy: YieldPolicy,
+}
+
+impl Default for SelfSendGuard {
+    fn default() -> Self {
+        SelfSendGuard {
+            max_depth: 1000,
+            max_ratio: 0.9,
+            window: 1000,
+            yield_policy: YieldPolicy::Never,
+        }
+    }
+}
+
+/// What an Actor thread does between two consecutive handlers that sent messages to their own Actor.
+#[derive(Debug, Clone, PartialEq)]
+pub enum YieldPolicy {
+    /// Continue with the next message right away.
+    Never,
+    /// Offer the rest of the time slice to other threads.
+    Yield,
+    /// Sleep for the given Duration.
+    Sleep(Duration),
+}
+
+impl YieldPolicy {
+    /// Give way to other threads as configured.
+    pub(crate) fn apply(&self) {
+        match self {
+            YieldPolicy::Never => {}
+            YieldPolicy::Yield => std::thread::yield_now(),
+            YieldPolicy::Sleep(duration) => std::thread::sleep(*duration),
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! This module distributes the messages sent to a single [ActorRef](../actor/struct.ActorRef.html) over a pool of identical Actors,
+//! e.g. to parallelize a map-reduce without wiring the workers by hand.
+//!
+//! - [Environment::spawn_pool](../api/struct.Environment.html#method.spawn_pool) spawns the workers like any other Actor, so they may live on any machine.
+//! - The router of the pool is an Actor of type [ROUTER_TYPE_ID](constant.ROUTER_TYPE_ID.html) on the local machine.
+//! - The router forwards every message it receives to the workers following the [RouterKind](../api/enum.RouterKind.html), the workers see the original sender.
+//! - [Environment::resize_pool](../api/struct.Environment.html#method.resize_pool) spawns or removes workers. Workers that stop leave the pool.
+//! - Removing the router removes its workers.
+//!
+//! ```rust,ignore
+//! let pool = env.spawn_pool("WorkerActor", 4, RouterKind::RoundRobin)?;
+//! let partial_sums: Vec<ResponseFuture<u64>> = ranges.into_iter().map(|range| pool.ask(SumRange(range))).collect::<Result<_, _>>()?;
+//! env.resize_pool(&pool, 8)?;
+//! ```
+
+use crate::actor::{Actor, ActorId, ActorRef, Context, QueryableActor, StopReason};
+use crate::api::{ActlibError, DropCause, Environment, Terminated};
+use crate::environment::{current_key, rendezvous_weight};
+use crate::message::{message_tag, MessageHandler, SerializedMessage};
+use crate::options::RouterKind;
+use crate::wire::WireFormat;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+
+/// The type id the routers of the pools are spawned with, e.g. listed by [list_local_actors](../api/struct.Environment.html#method.list_local_actors).
+pub const ROUTER_TYPE_ID: &str = "actlib::Router";
+
+/// Asks a router to grow or shrink its pool to the given number of workers.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct ResizePool(pub(crate) usize);
+
+/// A message for the workers, forwarded as it arrived.
+struct Routed(SerializedMessage);
+
+/// Forwards the messages sent to a pool to its workers.
+#[derive(Debug)]
+pub(crate) struct Router {
+    actor_type_id: String,
+    kind: RouterKind,
+    pub(crate) workers: Vec<ActorRef>,
+    /// The worker that gets the next message with RouterKind::RoundRobin
+    next: usize,
+    env: Option<Environment>,
+    own_ref: Option<ActorRef>,
+}
+
+impl Router {
+    /// A router for the already spawned *workers* of type *actor_type_id*.
+    pub(crate) fn new(actor_type_id: &str, kind: RouterKind, workers: Vec<ActorRef>) -> Self {
+        Router {
+            actor_type_id: actor_type_id.to_string(),
+            kind,
+            workers,
+            next: 0,
+            env: None,
+            own_ref: None,
+        }
+    }
+
+    /// Forward the *message* to the workers following the RouterKind.
+    fn route(&mut self, message: SerializedMessage) {
+        if self.workers.is_empty() {
+            self.drop_without_workers();
+            return;
+        }
+        let sender = Context::sender_id();
+        let key = current_key();
+        match self.kind {
+            RouterKind::RoundRobin => {
+                let next = self.next % self.workers.len();
+                self.next = next + 1;
+                self.forward(&self.workers[next], message, key, sender);
+            }
+            RouterKind::Broadcast => {
+                for worker in &self.workers {
+                    self.forward(worker, message.clone(), key, sender.clone());
+                }
+            }
+            RouterKind::ConsistentHash => {
+                let hashed = match key {
+                    Some(key) => key.to_le_bytes().to_vec(),
+                    None => message.bytes.clone(),
+                };
+                let worker = self.workers.iter().max_by_key(|worker| {
+                    let node = bincode::serialize(&worker.actor_id).unwrap_or_default();
+                    rendezvous_weight(&node, &hashed)
+                });
+                match worker {
+                    Some(worker) => self.forward(worker, message, key, sender),
+                    None => self.drop_without_workers(),
+                }
+            }
+        }
+    }
+
+    /// Report a message that reached the router while the pool had no workers.
+    fn drop_without_workers(&self) {
+        warn!(
+            "The pool of {} has no workers, dropped a message.",
+            self.actor_type_id
+        );
+        if let Some(env) = &self.env {
+            env.env.report_dropped(
+                DropCause::ActorRemoved,
+                self.own_ref.as_ref().map(ActorRef::clone_id),
+                None,
+            );
+        }
+    }
+
+    /// Forward the *message* on behalf of its *sender*, keeping its key if it was sent with send_keyed.
+    ///
+    /// A message the worker does not accept is reported as dropped.
+    fn forward(
+        &self,
+        worker: &ActorRef,
+        message: SerializedMessage,
+        key: Option<u64>,
+        sender: Option<ActorId>,
+    ) {
+        let forwarded = match key {
+            Some(key) => worker.send_serialized_keyed_from(message, key, sender),
+            None => worker.send_serialized_from(message, sender),
+        };
+        if let Err(e) = forwarded {
+            warn!(
+                "Failed to forward a message to the worker {:?}: {:?}",
+                worker.actor_id, e
+            );
+            if let Some(env) = &self.env {
+                let cause = match e {
+                    ActlibError::MailboxFull(_) => DropCause::MailboxFull,
+                    _ => DropCause::ActorRemoved,
+                };
+                env.env.report_dropped(cause, Some(worker.clone_id()), None);
+            }
+        }
+    }
+
+    /// Spawn or remove workers until the pool has *size* workers, the workers spawned last are removed first.
+    fn resize(&mut self, size: usize) {
+        let mut env = match &self.env {
+            Some(env) => env.clone(),
+            None => return,
+        };
+        while self.workers.len() > size {
+            if let Some(worker) = self.workers.pop() {
+                env.remove(worker);
+            }
+        }
+        while self.workers.len() < size {
+            match env.spawn(&self.actor_type_id) {
+                Ok(worker) => {
+                    self.watch(&worker);
+                    self.workers.push(worker);
+                }
+                Err(e) => {
+                    warn!(
+                        "Could not grow the pool of {} to {} workers: {:?}",
+                        self.actor_type_id, size, e
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Learn about the worker stopping, so it leaves the pool.
+    fn watch(&self, worker: &ActorRef) {
+        if let Some(own_ref) = &self.own_ref {
+            if let Err(e) = worker.watch(own_ref) {
+                warn!("Could not watch the worker {:?}: {:?}", worker.actor_id, e);
+            }
+        }
+    }
+}
+
+impl Actor for Router {
+    fn on_start(&mut self, local_env: Environment, own_ref: ActorRef) {
+        self.env = Some(local_env);
+        self.own_ref = Some(own_ref);
+        for worker in &self.workers {
+            self.watch(worker);
+        }
+    }
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        if let Some(mut env) = self.env.take() {
+            for worker in self.workers.drain(..) {
+                env.remove(worker);
+            }
+        }
+    }
+}
+
+impl QueryableActor for Router {}
+
+impl MessageHandler for Router {
+    fn handle(&mut self, message: Box<dyn Any>) {
+        let message = match message.downcast::<Routed>() {
+            Ok(routed) => return self.route(routed.0),
+            Err(message) => message,
+        };
+        if let Some(Terminated(worker)) = message.downcast_ref::<Terminated>() {
+            self.workers.retain(|w| w.actor_id != *worker);
+        } else if let Some(ResizePool(size)) = message.downcast_ref::<ResizePool>() {
+            self.resize(*size);
+        }
+    }
+
+    fn as_any(&self) -> Option<&dyn Any> {
+        Some(self)
+    }
+
+    fn deserialize_to_any(&self, message: &[u8]) -> Option<Box<dyn Any + Send>> {
+        Some(Box::new(Routed(SerializedMessage::untagged(
+            message.to_vec(),
+        ))))
+    }
+
+    fn deserialize_tagged(
+        &self,
+        tag: u64,
+        message: &[u8],
+        format: WireFormat,
+    ) -> Option<Box<dyn Any + Send>> {
+        if tag == message_tag::<Terminated>() {
+            format
+                .deserialize::<Terminated>(message)
+                .ok()
+                .map(|terminated| Box::new(terminated) as Box<dyn Any + Send>)
+        } else if tag == message_tag::<ResizePool>() {
+            format
+                .deserialize::<ResizePool>(message)
+                .ok()
+                .map(|resize| Box::new(resize) as Box<dyn Any + Send>)
+        } else {
+            Some(Box::new(Routed(SerializedMessage {
+                tag: Some(tag),
+                bytes: message.to_vec(),
+            })))
+        }
+    }
+}
@@ -0,0 +1,652 @@
+//! This module provides the example programs of this repository as instrumented scenarios, so they can be used as large scale integration tests.
+//!
+//! Both scenarios run on a caller provided [Environment](../api/struct.Environment.html),
+//! which has to be built with the [scenario actor_builder](fn.actor_builder.html) (or an equivalent one), local only or distributed:
+//!
+//! - The [work tree](fn.run_work_tree.html) splits a workload along a binary tree of Actors and sums up the results, like the *work-distributer-test*.
+//! - The [grid](fn.run_grid.html) moves players across an infinite grid of field Actors spawned on demand, like *infinigryd*.
+//!
+//! Every run checks its invariants and returns an error if one is violated, so the scenarios can be used within ```#[test]```s:
+//!
+//! ```rust,ignore
+//! let (env, _expiration_checker) = Environment::new_local_only(scenarios::actor_builder());
+//! let report = scenarios::run_work_tree(&env, &WorkTreeScenario::new(vec![3; 64]))?;
+//! assert_eq!(report.actors_spawned, 127);
+//! ```
+
+use crate::api::*;
+use crate::log_err_as;
+use log::*;
+use rand::prelude::{thread_rng, SliceRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Type id of the Actors of the work tree.
+pub const WORKER_TYPE_ID: &str = "ScenarioWorker";
+/// Type id of the fields of the grid.
+pub const FIELD_TYPE_ID: &str = "ScenarioField";
+/// Type id of the Actor collecting the player counts of the grid.
+pub const COUNTER_TYPE_ID: &str = "ScenarioCounter";
+
+/// Source of the ids of the runs, so several runs can share an Environment.
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The results of the running scenarios of this process, by run id.
+fn pending_runs() -> &'static Mutex<HashMap<u64, Sender<RunEvent>>> {
+    static PENDING_RUNS: OnceLock<Mutex<HashMap<u64, Sender<RunEvent>>>> = OnceLock::new();
+    PENDING_RUNS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// What the Actors of a run report to the waiting runner.
+#[derive(Debug)]
+enum RunEvent {
+    /// The root of the work tree is done.
+    WorkDone { result: i64, actors_spawned: usize },
+    /// A field answered a CountPlayers request.
+    FieldCounted { players: usize },
+}
+
+/// Register a new run, returning its id and the receiving end of its events.
+fn register_run() -> (u64, std::sync::mpsc::Receiver<RunEvent>) {
+    let run = NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed);
+    let (sender, receiver) = channel();
+    match pending_runs().lock() {
+        Ok(mut runs) => {
+            runs.insert(run, sender);
+        }
+        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+    }
+    (run, receiver)
+}
+
+fn unregister_run(run: u64) {
+    match pending_runs().lock() {
+        Ok(mut runs) => {
+            runs.remove(&run);
+        }
+        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+    }
+}
+
+/// Hand an event to the runner of *run*, ignored if the run is over or runs on another machine.
+fn report(run: u64, event: RunEvent) {
+    match pending_runs().lock() {
+        Ok(runs) => {
+            if let Some(sender) = runs.get(&run) {
+                let _ = sender.send(event);
+            }
+        }
+        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+    }
+}
+
+/// Builds the Actors of all scenarios.
+pub fn actor_builder() -> ActorBuilder {
+    crate::actor_builder!(
+        WORKER_TYPE_ID => Worker::new(),
+        FIELD_TYPE_ID => Field::new(),
+        COUNTER_TYPE_ID => Counter
+    )
+}
+
+/// Configuration of a [work tree run](fn.run_work_tree.html).
+#[derive(Debug, Clone)]
+pub struct WorkTreeScenario {
+    workload: Vec<i64>,
+    timeout: Duration,
+}
+
+impl WorkTreeScenario {
+    /// Sum up twice every value of the *workload*, one Actor per value.
+    pub fn new(workload: Vec<i64>) -> Self {
+        WorkTreeScenario {
+            workload,
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Fail the run if no result arrived in time, 60 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// The outcome of a successful [work tree run](fn.run_work_tree.html).
+#[derive(Debug, Clone)]
+pub struct WorkTreeReport {
+    /// The sum computed by the Actors, equal to twice the sum of the workload.
+    pub result: i64,
+    /// Number of Actors spawned, the root included.
+    pub actors_spawned: usize,
+    /// Time from spawning the root until the result arrived.
+    pub elapsed: Duration,
+}
+
+/// Split the workload along a binary tree of Actors, spawned with the regular placement, and wait for the sum.
+///
+/// Fails with ```ActlibError::Timeout``` if the result does not arrive in time,
+/// or with ```ActlibError::InvalidState``` if it is wrong.
+pub fn run_work_tree(
+    env: &Environment,
+    scenario: &WorkTreeScenario,
+) -> Result<WorkTreeReport, ActlibError> {
+    let (run, receiver) = register_run();
+    let started = Instant::now();
+    // the root reports to this machine, so it has to live here
+    let root = match env.spawn_local(WORKER_TYPE_ID) {
+        Ok(root) => root,
+        Err(e) => {
+            unregister_run(run);
+            return Err(e);
+        }
+    };
+    let sent = root.send_message(WorkOn {
+        run,
+        parent: None,
+        side: Side::Left,
+        workload: scenario.workload.clone(),
+    });
+    let outcome = match sent {
+        Ok(()) => receiver.recv_timeout(scenario.timeout),
+        Err(e) => {
+            unregister_run(run);
+            return Err(e);
+        }
+    };
+    unregister_run(run);
+    env.clone().remove(root);
+    let expected: i64 = scenario.workload.iter().map(|value| value * 2).sum();
+    match outcome {
+        Ok(RunEvent::WorkDone {
+            result,
+            actors_spawned,
+        }) => {
+            if result != expected {
+                return Err(ActlibError::InvalidState(format!(
+                    "Work tree computed {}, expected {}",
+                    result, expected
+                )));
+            }
+            Ok(WorkTreeReport {
+                result,
+                actors_spawned,
+                elapsed: started.elapsed(),
+            })
+        }
+        Ok(event) => Err(ActlibError::InvalidState(format!(
+            "Unexpected event in a work tree run: {:?}",
+            event
+        ))),
+        Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => {
+            Err(ActlibError::Timeout(format!(
+                "Work tree did not finish within {:?}",
+                scenario.timeout
+            )))
+        }
+    }
+}
+
+/// Which half of its parent's workload a worker handles.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+/// Handle a part of the workload, answering the parent (or the runner) with a PartialResult.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WorkOn {
+    run: u64,
+    parent: Option<ActorId>,
+    side: Side,
+    workload: Vec<i64>,
+}
+
+/// The sum of a child's part of the workload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PartialResult {
+    run: u64,
+    side: Side,
+    result: i64,
+    actors_spawned: usize,
+}
+
+/// A node of the work tree, it reaches its Environment through the [Context](../actor/struct.Context.html).
+#[derive(Debug)]
+struct Worker {
+    task: Option<WorkOn>,
+    children: Vec<(Side, ActorRef)>,
+    partial_results: Vec<PartialResult>,
+}
+
+impl Worker {
+    fn new() -> Self {
+        Worker {
+            task: None,
+            children: Vec::with_capacity(2),
+            partial_results: Vec::with_capacity(2),
+        }
+    }
+
+    fn work_on(&mut self, task: &WorkOn) {
+        self.task = Some(task.clone());
+        if task.workload.len() <= 1 {
+            let result = task.workload.iter().map(|value| value * 2).sum();
+            self.answer(result, 1);
+            return;
+        }
+        let (left, right) = task.workload.split_at(task.workload.len() / 2);
+        for (side, workload) in [(Side::Left, left), (Side::Right, right)] {
+            if let Err(e) = self.spawn_child(task.run, side, workload.to_vec()) {
+                error!("Work tree run {} lost a subtree: {:?}", task.run, e);
+            }
+        }
+    }
+
+    fn spawn_child(&mut self, run: u64, side: Side, workload: Vec<i64>) -> Result<(), ActlibError> {
+        let child = Context::env().spawn(WORKER_TYPE_ID)?;
+        child.send_message(WorkOn {
+            run,
+            parent: Some(Context::self_id()),
+            side,
+            workload,
+        })?;
+        self.children.push((side, child));
+        Ok(())
+    }
+
+    fn collect(&mut self, partial_result: &PartialResult) {
+        self.partial_results.push(partial_result.clone());
+        // the child is done, free its thread
+        if let Some(index) = self
+            .children
+            .iter()
+            .position(|(side, _)| *side == partial_result.side)
+        {
+            let (_, child) = self.children.remove(index);
+            Context::env().remove(child);
+        }
+        if self.partial_results.len() == 2 {
+            let result = self.partial_results.iter().map(|p| p.result).sum();
+            let actors_spawned = self
+                .partial_results
+                .iter()
+                .map(|p| p.actors_spawned)
+                .sum::<usize>();
+            self.answer(result, actors_spawned + 1);
+        }
+    }
+
+    fn answer(&self, result: i64, actors_spawned: usize) {
+        let task = match &self.task {
+            Some(task) => task,
+            None => return,
+        };
+        match &task.parent {
+            Some(parent) => {
+                let sent = Context::env()
+                    .to_actor_ref(parent.clone())
+                    .and_then(|parent| {
+                        parent.send_message(PartialResult {
+                            run: task.run,
+                            side: task.side,
+                            result,
+                            actors_spawned,
+                        })
+                    });
+                if let Err(e) = sent {
+                    error!("Work tree run {} lost a result: {:?}", task.run, e);
+                }
+            }
+            None => report(
+                task.run,
+                RunEvent::WorkDone {
+                    result,
+                    actors_spawned,
+                },
+            ),
+        }
+    }
+}
+
+impl Actor for Worker {}
+
+impl_message_handler!(Worker:
+    WorkOn => Worker::work_on,
+    PartialResult => Worker::collect
+);
+
+/// Configuration of a [grid run](fn.run_grid.html).
+#[derive(Debug, Clone)]
+pub struct GridScenario {
+    players: u64,
+    step: Duration,
+    duration: Duration,
+    settle_time: Duration,
+}
+
+impl GridScenario {
+    /// Let the given number of players wander the grid, starting at the origin.
+    pub fn new(players: u64) -> Self {
+        GridScenario {
+            players,
+            step: Duration::from_millis(100),
+            duration: Duration::from_secs(5),
+            settle_time: Duration::from_secs(1),
+        }
+    }
+
+    /// How long a player stays on a field before moving on, 100ms by default.
+    pub fn step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// How long the players move before they are counted, 5 seconds by default.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// How long to wait for moving players to arrive and for the counts of the fields, 1 second by default.
+    pub fn settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+}
+
+/// The outcome of a successful [grid run](fn.run_grid.html).
+#[derive(Debug, Clone)]
+pub struct GridReport {
+    /// Number of players found on the fields, equal to the number of players started with.
+    pub players_counted: u64,
+    /// Number of fields holding at least one player at the end of the run.
+    pub fields_occupied: usize,
+    /// Number of moves from one field to another, made by fields on this machine.
+    pub moves: u64,
+}
+
+/// Number of moves of all grid runs, read before and after a run.
+static GRID_MOVES: AtomicU64 = AtomicU64::new(0);
+
+/// Let players wander the grid, then freeze it and check that no player got lost or duplicated.
+///
+/// Fields are spawned with [find_or_spawn_near](../api/struct.Environment.html#method.find_or_spawn_near) and removed once empty.
+/// The counting Actor lives on this machine, the fields anywhere.
+///
+/// Fails with ```ActlibError::InvalidState``` if the counted players differ from the started ones.
+pub fn run_grid(env: &Environment, scenario: &GridScenario) -> Result<GridReport, ActlibError> {
+    let (run, receiver) = register_run();
+    let moves_before = GRID_MOVES.load(Ordering::Relaxed);
+    let result = start_grid(env, run, scenario).and_then(|counter| {
+        std::thread::sleep(scenario.duration);
+        env.broadcast(Freeze { run, frozen: true })?;
+        // moves already under way still arrive
+        std::thread::sleep(scenario.settle_time);
+        env.broadcast(CountPlayers {
+            run,
+            counter: counter.clone_id(),
+        })?;
+        let mut players_counted = 0;
+        let mut fields_occupied = 0;
+        let deadline = Instant::now() + scenario.settle_time;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match receiver.recv_timeout(remaining) {
+                Ok(RunEvent::FieldCounted { players }) => {
+                    players_counted += players as u64;
+                    if players > 0 {
+                        fields_occupied += 1;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => break,
+            }
+        }
+        env.clone().remove(counter);
+        Ok((players_counted, fields_occupied))
+    });
+    unregister_run(run);
+    let (players_counted, fields_occupied) = result?;
+    if players_counted != scenario.players {
+        return Err(ActlibError::InvalidState(format!(
+            "Grid counted {} players, started with {}",
+            players_counted, scenario.players
+        )));
+    }
+    Ok(GridReport {
+        players_counted,
+        fields_occupied,
+        moves: GRID_MOVES.load(Ordering::Relaxed) - moves_before,
+    })
+}
+
+/// Spawn the counter and the origin field and place the players on it.
+fn start_grid(
+    env: &Environment,
+    run: u64,
+    scenario: &GridScenario,
+) -> Result<ActorRef, ActlibError> {
+    let counter = env.spawn_local(COUNTER_TYPE_ID)?;
+    let origin = env.find_or_spawn_near(FIELD_TYPE_ID, field_key(run, (0, 0))?, None)?;
+    for player in 0..scenario.players {
+        origin.send_message(PlayerEnters {
+            run,
+            player,
+            step: scenario.step,
+        })?;
+    }
+    Ok(counter)
+}
+
+/// The specified id of the field of *run* at *position*.
+fn field_key(run: u64, position: (i64, i64)) -> Result<Vec<u8>, ActlibError> {
+    bincode::serialize(&(run, position)).map_err(|e| ActlibError::SpawnFailed(format!("{:?}", e)))
+}
+
+/// Steps a player can take.
+const DIRECTIONS: [(i64, i64); 4] = [(0, 1), (-1, 0), (0, -1), (1, 0)];
+
+/// A player arrives on the field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerEnters {
+    run: u64,
+    player: u64,
+    step: Duration,
+}
+
+/// Sent by a field to itself, the player moves on to the neighbour in *direction*.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerLeaves {
+    run: u64,
+    player: u64,
+    step: Duration,
+    direction: (i64, i64),
+}
+
+/// Broadcast to stop all players of a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Freeze {
+    run: u64,
+    frozen: bool,
+}
+
+/// Broadcast to make every field of a run report its players to the counter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CountPlayers {
+    run: u64,
+    counter: ActorId,
+}
+
+/// The players of a single field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PlayerCount {
+    run: u64,
+    players: usize,
+}
+
+/// A field of the grid, identified by its run and position.
+#[derive(Debug)]
+struct Field {
+    env: Option<Environment>,
+    own_ref: Option<ActorRef>,
+    run: u64,
+    position: (i64, i64),
+    players: HashSet<u64>,
+    frozen: bool,
+}
+
+impl Field {
+    fn new() -> Self {
+        Field {
+            env: None,
+            own_ref: None,
+            run: 0,
+            position: (0, 0),
+            players: HashSet::new(),
+            frozen: false,
+        }
+    }
+
+    fn player_enters(&mut self, enters: &PlayerEnters) {
+        self.players.insert(enters.player);
+        // unwrap is safe here, since DIRECTIONS is non-empty
+        let direction = *DIRECTIONS.choose(&mut thread_rng()).unwrap();
+        if let Some(own_ref) = &self.own_ref {
+            own_ref.send_delayed_message(
+                PlayerLeaves {
+                    run: enters.run,
+                    player: enters.player,
+                    step: enters.step,
+                    direction,
+                },
+                enters.step,
+            );
+        }
+    }
+
+    fn player_leaves(&mut self, leaves: &PlayerLeaves) {
+        if self.frozen || !self.players.contains(&leaves.player) {
+            return;
+        }
+        let (env, own_id) = match (&self.env, &self.own_ref) {
+            (Some(env), Some(own_ref)) => (env.clone(), own_ref.clone_id()),
+            _ => return,
+        };
+        let target = (
+            self.position.0 + leaves.direction.0,
+            self.position.1 + leaves.direction.1,
+        );
+        let key = match field_key(self.run, target) {
+            Ok(key) => key,
+            Err(e) => {
+                error!("{:?}", e);
+                return;
+            }
+        };
+        match env.find_actor_ref(&key, own_id.clone(), true) {
+            Ok(Some(neighbour)) => {
+                let entered = neighbour.send_message(PlayerEnters {
+                    run: leaves.run,
+                    player: leaves.player,
+                    step: leaves.step,
+                });
+                env.drop_protector(own_id, neighbour.clone_id());
+                match entered {
+                    Ok(()) => {
+                        self.players.remove(&leaves.player);
+                        GRID_MOVES.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(e) => warn!("Player {} stays, neighbour is gone: {:?}", leaves.player, e),
+                }
+                if self.players.is_empty() {
+                    if let Some(own_ref) = self.own_ref.clone() {
+                        env.clone().remove(own_ref);
+                    }
+                }
+            }
+            Ok(None) => match env.find_or_spawn_near(FIELD_TYPE_ID, key, Some(own_id.location())) {
+                // try again, now that the neighbour exists
+                Ok(_) => {
+                    if let Some(own_ref) = &self.own_ref {
+                        let _ = own_ref.send_message(leaves.clone());
+                    }
+                }
+                Err(e) => error!("Failed to spawn a field: {:?}", e),
+            },
+            Err(e) => error!("Failed to find a field: {:?}", e),
+        }
+    }
+
+    fn freeze(&mut self, freeze: &Freeze) {
+        if freeze.run == self.run {
+            self.frozen = freeze.frozen;
+        }
+    }
+
+    fn count_players(&mut self, count: &CountPlayers) {
+        if count.run != self.run {
+            return;
+        }
+        if let Some(env) = &self.env {
+            let sent = env.to_actor_ref(count.counter.clone()).and_then(|counter| {
+                counter.send_message(PlayerCount {
+                    run: self.run,
+                    players: self.players.len(),
+                })
+            });
+            if let Err(e) = sent {
+                error!("Field failed to report its players: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Actor for Field {
+    fn on_start(&mut self, mut local_env: Environment, own_ref: ActorRef) {
+        let position = own_ref
+            .clone_id()
+            .when_specified()
+            .and_then(|key| bincode::deserialize::<(u64, (i64, i64))>(&key).ok());
+        match position {
+            Some((run, position)) => {
+                self.run = run;
+                self.position = position;
+                self.env = Some(local_env);
+                self.own_ref = Some(own_ref);
+            }
+            None => {
+                warn!("Spawned a field without a valid specified id.");
+                local_env.remove(own_ref);
+            }
+        }
+    }
+}
+
+impl_message_handler!(Field:
+    PlayerEnters => Field::player_enters,
+    PlayerLeaves => Field::player_leaves,
+    Freeze => Field::freeze,
+    CountPlayers => Field::count_players
+);
+
+/// Hands the counts of the fields to the runner on its machine.
+#[derive(Debug)]
+struct Counter;
+
+impl Actor for Counter {}
+
+fn count(_counter: &mut Counter, count: &PlayerCount) {
+    report(
+        count.run,
+        RunEvent::FieldCounted {
+            players: count.players,
+        },
+    );
+}
+
+impl_message_handler!(Counter: PlayerCount => count);
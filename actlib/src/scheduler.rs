@@ -0,0 +1,302 @@
+//! This module defines the Scheduler running the mailbox loops of the local Actors on a fixed-size pool of worker threads.
+//!
+//! An Actor is queued once a message arrives in its empty mailbox, and a worker handles a batch of its messages before
+//! it takes the next queued Actor. Actors with an empty mailbox occupy no thread, so hundreds of them are cheap.
+//! A worker that blocks in an ask, a query or block_on starts a spare worker, which retires once the call returned.
+
+use crate::errors::ActlibError;
+use crate::log_err_as;
+#[allow(unused_imports)]
+use log::{error, info, warn};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// How many messages a worker handles in a row before the Actor is queued again, so busy Actors take turns.
+const MESSAGES_PER_TURN: usize = 32;
+
+/// Neither queued nor run, the next message queues the Actor.
+const IDLE: u8 = 0;
+/// Waiting in the queue for a worker.
+const QUEUED: u8 = 1;
+/// Run by a worker, or not started yet.
+const RUNNING: u8 = 2;
+/// Run by a worker, and a message arrived meanwhile.
+const NOTIFIED: u8 = 3;
+/// Stopped, later messages are never handled.
+const STOPPED: u8 = 4;
+
+thread_local! {
+    /// The pool of the worker running on this thread, if any.
+    static WORKER_POOL: RefCell<Option<Arc<Pool>>> = const { RefCell::new(None) };
+}
+
+/// What a turn of an Actor on a worker ended with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Turn {
+    /// The mailbox is empty.
+    Idle,
+    /// The batch was used up, more messages may be queued.
+    Busy,
+    /// The Actor stopped.
+    Stopped,
+}
+
+/// Handles up to the given number of messages of an Actor.
+struct TurnFn(Box<dyn FnMut(usize) -> Turn + Send>);
+
+impl std::fmt::Debug for TurnFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TurnFn")
+    }
+}
+
+/// A local Actor as seen by the Scheduler.
+pub(crate) struct ScheduledActor {
+    state: AtomicU8,
+    turn: Mutex<Option<TurnFn>>,
+}
+
+impl std::fmt::Debug for ScheduledActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ScheduledActor {{state: {}}}",
+            self.state.load(Ordering::Relaxed)
+        )
+    }
+}
+
+/// The queue of the Actors waiting for a worker, shared by the workers.
+#[derive(Debug, Default)]
+struct Pool {
+    queue: Mutex<VecDeque<Arc<ScheduledActor>>>,
+    available: Condvar,
+    /// Set once the Scheduler was dropped, the workers exit after their current turn
+    shutdown: AtomicBool,
+}
+
+impl Pool {
+    fn push(&self, actor: Arc<ScheduledActor>) {
+        match self.queue.lock() {
+            Ok(mut queue) => {
+                queue.push_back(actor);
+                self.available.notify_one();
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Wait for the next queued Actor, ```None``` once the pool shuts down or the spare worker *retired*.
+    fn next(&self, retired: &Option<Arc<AtomicBool>>) -> Option<Arc<ScheduledActor>> {
+        let mut queue = match self.queue.lock() {
+            Ok(queue) => queue,
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return None;
+            }
+        };
+        loop {
+            if self.shutdown.load(Ordering::Acquire) {
+                return None;
+            }
+            if let Some(retired) = retired {
+                if retired.load(Ordering::Acquire) {
+                    // the wake-up may have been meant for a queued Actor
+                    if !queue.is_empty() {
+                        self.available.notify_one();
+                    }
+                    return None;
+                }
+            }
+            if let Some(actor) = queue.pop_front() {
+                return Some(actor);
+            }
+            queue = match self.available.wait(queue) {
+                Ok(queue) => queue,
+                Err(e) => {
+                    log_err_as!(error, ActlibError::from_poison_error(&e));
+                    return None;
+                }
+            };
+        }
+    }
+
+    /// Start a worker thread, a spare one exits once *retired* is set.
+    fn spawn_worker(self: &Arc<Self>, name: String, retired: Option<Arc<AtomicBool>>) {
+        let pool = self.clone();
+        let spawned = std::thread::Builder::new().name(name).spawn(move || {
+            WORKER_POOL.with(|worker_pool| *worker_pool.borrow_mut() = Some(pool.clone()));
+            while let Some(actor) = pool.next(&retired) {
+                actor.run_turn(&pool);
+            }
+        });
+        if let Err(e) = spawned {
+            error!("Failed to start a worker thread: {:?}", e);
+        }
+    }
+}
+
+impl ScheduledActor {
+    /// Queue the Actor, unless it is queued or run already.
+    fn wake(self: &Arc<Self>, pool: &Arc<Pool>) {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            let next = match state {
+                IDLE => QUEUED,
+                RUNNING => NOTIFIED,
+                _ => return,
+            };
+            match self
+                .state
+                .compare_exchange(state, next, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => {
+                    if next == QUEUED {
+                        pool.push(self.clone());
+                    }
+                    return;
+                }
+                Err(current) => state = current,
+            }
+        }
+    }
+
+    /// Handle a batch of messages, then queue the Actor again if more may have arrived.
+    fn run_turn(self: &Arc<Self>, pool: &Arc<Pool>) {
+        self.state.store(RUNNING, Ordering::Release);
+        let turn = match self.turn.lock() {
+            Ok(mut turn) => match turn.as_mut() {
+                Some(TurnFn(run)) => {
+                    let outcome = run(MESSAGES_PER_TURN);
+                    if outcome == Turn::Stopped {
+                        // drops the Actor on the worker that stopped it
+                        *turn = None;
+                    }
+                    outcome
+                }
+                None => Turn::Stopped,
+            },
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Turn::Stopped
+            }
+        };
+        match turn {
+            Turn::Stopped => self.state.store(STOPPED, Ordering::Release),
+            Turn::Busy => {
+                self.state.store(QUEUED, Ordering::Release);
+                pool.push(self.clone());
+            }
+            Turn::Idle => {
+                // a message that arrived during the turn may not have been seen
+                if self
+                    .state
+                    .compare_exchange(RUNNING, IDLE, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    self.state.store(QUEUED, Ordering::Release);
+                    pool.push(self.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Runs the local Actors on a fixed number of worker threads, until it is dropped by its Environment.
+#[derive(Debug)]
+pub(crate) struct Scheduler {
+    pool: Arc<Pool>,
+}
+
+impl Scheduler {
+    /// Create a scheduler and start its *workers* threads, at least one.
+    pub(crate) fn start(workers: usize) -> Scheduler {
+        let workers = workers.max(1);
+        let pool = Arc::new(Pool::default());
+        for no in 0..workers {
+            pool.spawn_worker(format!("actlib-worker-{}", no), None);
+        }
+        Scheduler { pool }
+    }
+
+    /// Prepare a not yet started Actor, messages arriving before [start](#method.start) are handled afterwards.
+    pub(crate) fn prepare(&self) -> Arc<ScheduledActor> {
+        Arc::new(ScheduledActor {
+            state: AtomicU8::new(RUNNING),
+            turn: Mutex::new(None),
+        })
+    }
+
+    /// A function queueing the prepared *actor*, to be called for every message put into its mailbox.
+    pub(crate) fn waker(&self, actor: &Arc<ScheduledActor>) -> impl Fn() + Send + Sync + 'static {
+        let pool = self.pool.clone();
+        let actor = actor.clone();
+        move || actor.wake(&pool)
+    }
+
+    /// Queue the prepared *actor*, the workers call *turn* until it returns [Turn::Stopped](enum.Turn.html#variant.Stopped).
+    pub(crate) fn start_actor<F: FnMut(usize) -> Turn + Send + 'static>(
+        &self,
+        actor: &Arc<ScheduledActor>,
+        turn: F,
+    ) {
+        match actor.turn.lock() {
+            Ok(mut actor_turn) => *actor_turn = Some(TurnFn(Box::new(turn))),
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                return;
+            }
+        }
+        actor.state.store(QUEUED, Ordering::Release);
+        self.pool.push(actor.clone());
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.pool.shutdown.store(true, Ordering::Release);
+        self.pool.available.notify_all();
+    }
+}
+
+/// Retires a spare worker once the blocking call returned or panicked.
+struct SpareWorker {
+    pool: Arc<Pool>,
+    retired: Arc<AtomicBool>,
+}
+
+impl Drop for SpareWorker {
+    fn drop(&mut self) {
+        self.retired.store(true, Ordering::Release);
+        // take the lock, so the spare cannot miss the wake-up between its check and its wait
+        let _queue = self.pool.queue.lock();
+        self.pool.available.notify_all();
+    }
+}
+
+/// Run the blocking call *f*, on a worker thread a spare worker takes over the queued Actors meanwhile.
+///
+/// The blocked worker may wait for an Actor that would otherwise never get a thread.
+/// On a worker of a multi-threaded tokio runtime the other tasks move to another worker instead.
+pub(crate) fn blocking<T, F: FnOnce() -> T>(f: F) -> T {
+    #[cfg(feature = "async-runtime")]
+    {
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread {
+                return tokio::task::block_in_place(f);
+            }
+        }
+    }
+    let pool = WORKER_POOL.with(|worker_pool| worker_pool.borrow().clone());
+    match pool {
+        Some(pool) => {
+            let retired = Arc::new(AtomicBool::new(false));
+            pool.spawn_worker("actlib-spare-worker".to_string(), Some(retired.clone()));
+            let _spare = SpareWorker { pool, retired };
+            f()
+        }
+        None => f(),
+    }
+}
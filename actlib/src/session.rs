@@ -0,0 +1,235 @@
+//! This module binds external clients to dedicated session [Actors](../actor/trait.Actor.html), so stateful protocols can be built on top of the actor system.
+//!
+//! A [SessionServer](struct.SessionServer.html) accepts TCP connections and spawns one local session Actor per connection:
+//!
+//! - The session Actor first receives [SessionOpened](struct.SessionOpened.html), then a [SessionInput](struct.SessionInput.html) for every frame the client sends.
+//! - It answers the client with [ClientSession::send](struct.ClientSession.html#method.send).
+//! - The Actor is [removed](../api/struct.Environment.html#method.remove) once the client disconnects,
+//!   the connection is closed once the Actor [closes](struct.ClientSession.html#method.close) the session,
+//!   or once the client sends a frame to an Actor that is gone.
+//!
+//! Frames are a 4 byte big endian length followed by the payload, its encoding is up to client and Actor.
+//!
+//! ```rust,ignore
+//! impl_message_handler!(ChatSession:
+//!     SessionOpened => ChatSession::opened,
+//!     SessionInput => ChatSession::input
+//! );
+//!
+//! SessionServer::new(env.clone(), "ChatSession").serve("0.0.0.0:4030".parse().unwrap())?;
+//! ```
+
+use crate::actor::ActorRef;
+use crate::api::{ActlibError, Environment};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Source of the ids of ClientSessions.
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The connection of every open session, shared by all servers of this process.
+fn open_sessions() -> &'static Mutex<HashMap<u64, Arc<Mutex<TcpStream>>>> {
+    static OPEN_SESSIONS: OnceLock<Mutex<HashMap<u64, Arc<Mutex<TcpStream>>>>> = OnceLock::new();
+    OPEN_SESSIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The connection of a single client, handed to its session Actor.
+///
+/// Only usable on the machine running the [SessionServer](struct.SessionServer.html).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientSession {
+    /// Identifies the session on the local machine.
+    pub session_id: u64,
+    /// The address of the client.
+    pub peer: SocketAddr,
+}
+
+impl ClientSession {
+    /// Send a frame with the given payload to the client.
+    ///
+    /// Returns ```false``` if the client is gone.
+    pub fn send(&self, payload: &[u8]) -> bool {
+        let stream = match open_sessions().lock() {
+            Ok(sessions) => match sessions.get(&self.session_id) {
+                Some(stream) => stream.clone(),
+                None => return false,
+            },
+            Err(e) => {
+                error!("{:?}", ActlibError::from_poison_error(&e));
+                return false;
+            }
+        };
+        let mut frame = Vec::with_capacity(payload.len() + 4);
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(payload);
+        let result = match stream.lock() {
+            Ok(mut stream) => stream.write_all(&frame),
+            Err(e) => {
+                error!("{:?}", ActlibError::from_poison_error(&e));
+                return false;
+            }
+        };
+        result.is_ok()
+    }
+
+    /// Close the connection, the session Actor is removed afterwards.
+    pub fn close(&self) {
+        let stream = match open_sessions().lock() {
+            Ok(mut sessions) => sessions.remove(&self.session_id),
+            Err(e) => {
+                error!("{:?}", ActlibError::from_poison_error(&e));
+                None
+            }
+        };
+        if let Some(stream) = stream {
+            if let Ok(stream) = stream.lock() {
+                // the reading thread notices and removes the Actor
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+/// The first message of every session Actor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionOpened {
+    /// The connection the Actor is responsible for.
+    pub session: ClientSession,
+}
+
+/// A frame the client sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionInput {
+    /// The connection the frame arrived on.
+    pub session: ClientSession,
+    /// The payload of the frame.
+    pub payload: Vec<u8>,
+}
+
+/// Accepts external clients, spawning a session Actor of the given type for every connection.
+#[derive(Debug, Clone)]
+pub struct SessionServer {
+    env: Environment,
+    session_type_id: String,
+    max_frame_size: usize,
+}
+
+impl SessionServer {
+    /// Create a server spawning Actors of the given type id, which has to be known to the [actor_builder](../macro.actor_builder.html).
+    pub fn new(env: Environment, session_type_id: &str) -> Self {
+        SessionServer {
+            env,
+            session_type_id: session_type_id.to_string(),
+            max_frame_size: 16 * 1024 * 1024,
+        }
+    }
+
+    /// Close connections announcing a larger frame, 16 MiB by default.
+    pub fn max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes;
+        self
+    }
+
+    /// Accept clients on the given address, returning the address actually bound.
+    ///
+    /// Connections are accepted on a thread of their own, the current thread is not blocked.
+    pub fn serve(self, addr: SocketAddr) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        info!(
+            "Serving {} sessions on {}",
+            self.session_type_id, local_addr
+        );
+        std::thread::spawn(move || loop {
+            match listener.accept() {
+                Ok((stream, peer)) => self.open(stream, peer),
+                Err(e) => warn!("Failed to accept a session client: {:?}", e),
+            }
+        });
+        Ok(local_addr)
+    }
+
+    /// Spawn the session Actor of a new connection and pass it everything the client sends.
+    fn open(&self, mut stream: TcpStream, peer: SocketAddr) {
+        let writer = match stream.try_clone() {
+            Ok(writer) => writer,
+            Err(e) => {
+                warn!("Failed to open a session for {}: {:?}", peer, e);
+                return;
+            }
+        };
+        let actor_ref = match self.env.spawn_local(&self.session_type_id) {
+            Ok(actor_ref) => actor_ref,
+            Err(e) => {
+                warn!("Failed to spawn a session Actor for {}: {:?}", peer, e);
+                let _ = stream.shutdown(Shutdown::Both);
+                return;
+            }
+        };
+        let session = ClientSession {
+            session_id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            peer,
+        };
+        match open_sessions().lock() {
+            Ok(mut sessions) => {
+                sessions.insert(session.session_id, Arc::new(Mutex::new(writer)));
+            }
+            Err(e) => error!("{:?}", ActlibError::from_poison_error(&e)),
+        }
+        if let Err(e) = actor_ref.send_message(SessionOpened {
+            session: session.clone(),
+        }) {
+            warn!("Session Actor for {} is gone: {:?}", peer, e);
+        }
+        let mut env = self.env.clone();
+        let max_frame_size = self.max_frame_size;
+        std::thread::spawn(move || {
+            forward_frames(&mut stream, &session, &actor_ref, max_frame_size);
+            session.close();
+            env.remove(actor_ref);
+        });
+    }
+}
+
+/// Send every frame of the client to its session Actor, until either of them is gone.
+fn forward_frames(
+    stream: &mut TcpStream,
+    session: &ClientSession,
+    actor_ref: &ActorRef,
+    max_frame_size: usize,
+) {
+    loop {
+        let mut len = [0; 4];
+        if stream.read_exact(&mut len).is_err() {
+            // the client disconnected or the session was closed
+            return;
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if len > max_frame_size {
+            warn!(
+                "Session client {} announced a frame of {} bytes, closing.",
+                session.peer, len
+            );
+            return;
+        }
+        let mut payload = vec![0; len];
+        if stream.read_exact(&mut payload).is_err() {
+            return;
+        }
+        if actor_ref
+            .send_message(SessionInput {
+                session: session.clone(),
+                payload,
+            })
+            .is_err()
+        {
+            // the session Actor is gone, so is the session
+            return;
+        }
+    }
+}
@@ -0,0 +1,357 @@
+//! This module provides a synthetic workload to measure an Environment before deploying the real Actors on it.
+//!
+//! A [run](fn.run.html) spawns a number of [StressSink](struct.StressSink.html) Actors, some of them on remote machines,
+//! and sends them messages of a given size at a fixed rate. Every sink echoes the messages back to a
+//! [StressCollector](struct.StressCollector.html) on the local machine, which measures the round trip.
+//!
+//! The Environment has to be built with the [stress actor_builder](fn.actor_builder.html),
+//! or one that registers the two Actor types under their type ids as well:
+//!
+//! ```rust,ignore
+//! let actor_builder = actor_builder!(
+//!     "Field" => Field::new(),
+//!     stress::SINK_TYPE_ID => stress::StressSink::new(),
+//!     stress::COLLECTOR_TYPE_ID => stress::StressCollector::new()
+//! );
+//! let report = stress::run(&env, &StressWorkload::new(64, 1000).remote_share(0.5))?;
+//! println!("{:.0} msgs/s, p99 {:?}", report.throughput, report.latency_percentile(99.0));
+//! ```
+
+use crate::api::*;
+use crate::log_err_as;
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Type id of the Actors receiving the synthetic messages.
+pub const SINK_TYPE_ID: &str = "StressSink";
+/// Type id of the Actor measuring the round trips.
+pub const COLLECTOR_TYPE_ID: &str = "StressCollector";
+
+/// Source of the ids of the runs, so several runs can share an Environment.
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The start of a running run, and where its round trip times go.
+type PendingRun = (Instant, Sender<Duration>);
+
+/// The start and the round trip times of the running runs of this process, by run id.
+fn pending_runs() -> &'static Mutex<HashMap<u64, PendingRun>> {
+    static PENDING_RUNS: OnceLock<Mutex<HashMap<u64, PendingRun>>> = OnceLock::new();
+    PENDING_RUNS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Register a new run starting now, returning its id, its start and the receiving end of its round trip times.
+fn register_run() -> (u64, Instant, Receiver<Duration>) {
+    let run = NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed);
+    let started = Instant::now();
+    let (sender, receiver) = channel();
+    match pending_runs().lock() {
+        Ok(mut runs) => {
+            runs.insert(run, (started, sender));
+        }
+        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+    }
+    (run, started, receiver)
+}
+
+fn unregister_run(run: u64) {
+    match pending_runs().lock() {
+        Ok(mut runs) => {
+            runs.remove(&run);
+        }
+        Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+    }
+}
+
+/// Builds the Actors of a stress run.
+pub fn actor_builder() -> ActorBuilder {
+    crate::actor_builder!(
+        SINK_TYPE_ID => StressSink::new(),
+        COLLECTOR_TYPE_ID => StressCollector::new()
+    )
+}
+
+/// Configuration of a [stress run](fn.run.html).
+#[derive(Debug, Clone)]
+pub struct StressWorkload {
+    actors: usize,
+    messages_per_second: u64,
+    payload_size: usize,
+    remote_share: f64,
+    duration: Duration,
+    settle_time: Duration,
+}
+
+impl StressWorkload {
+    /// Send *messages_per_second* messages to *actors* sinks in total, round robin.
+    pub fn new(actors: usize, messages_per_second: u64) -> Self {
+        StressWorkload {
+            actors,
+            messages_per_second,
+            payload_size: 64,
+            remote_share: 0.0,
+            duration: Duration::from_secs(10),
+            settle_time: Duration::from_secs(1),
+        }
+    }
+
+    /// Bytes of payload carried by every message, 64 by default.
+    pub fn payload_size(mut self, bytes: usize) -> Self {
+        self.payload_size = bytes;
+        self
+    }
+
+    /// Share of the sinks spawned on remote machines, between 0.0 and 1.0, 0.0 by default.
+    ///
+    /// The remote sinks are placed by the regular placement, excluding the local machine.
+    pub fn remote_share(mut self, share: f64) -> Self {
+        self.remote_share = share.clamp(0.0, 1.0);
+        self
+    }
+
+    /// How long messages are sent, 10 seconds by default.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// How long to wait for the last echoes once sending stopped, 1 second by default.
+    pub fn settle_time(mut self, settle_time: Duration) -> Self {
+        self.settle_time = settle_time;
+        self
+    }
+}
+
+/// The outcome of a [stress run](fn.run.html).
+#[derive(Debug, Clone)]
+pub struct StressReport {
+    /// Number of sinks spawned on the local machine.
+    pub local_actors: usize,
+    /// Number of sinks spawned on remote machines.
+    pub remote_actors: usize,
+    /// Number of messages sent to the sinks.
+    pub messages_sent: u64,
+    /// Number of messages echoed back in time, the others were lost or too slow.
+    pub messages_echoed: u64,
+    /// Time from the first message sent until sending stopped.
+    pub elapsed: Duration,
+    /// Echoed messages per second.
+    pub throughput: f64,
+    /// The round trip time of every echoed message, shortest first.
+    pub latencies: Vec<Duration>,
+}
+
+impl StressReport {
+    /// The round trip time *percentile* percent of the echoed messages were faster than, ```None``` if none was echoed.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * (self.latencies.len() - 1) as f64)
+            .round() as usize;
+        Some(self.latencies[rank])
+    }
+}
+
+/// Spawn the sinks, send them messages at the configured rate and measure the round trips.
+///
+/// Blocks for the duration and the settle time of the *workload*. The sinks and the collector are removed afterwards.
+///
+/// Fails like [spawn](../api/struct.Environment.html#method.spawn) if the Actors cannot be spawned,
+/// e.g. with ```ActlibError::SpawnFailed``` if remote sinks are requested, but there is no remote machine.
+pub fn run(env: &Environment, workload: &StressWorkload) -> Result<StressReport, ActlibError> {
+    let (run, started, receiver) = register_run();
+    let result = spawn_actors(env, workload).map(|(collector, sinks)| {
+        let sent = send_messages(run, started, &collector, &sinks, workload);
+        (collector, sinks, sent)
+    });
+    let (collector, sinks, (messages_sent, elapsed)) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            unregister_run(run);
+            return Err(e);
+        }
+    };
+    let mut latencies = Vec::with_capacity(messages_sent as usize);
+    let deadline = Instant::now() + workload.settle_time;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(remaining) {
+            Ok(latency) => {
+                latencies.push(latency);
+                if latencies.len() as u64 == messages_sent {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    unregister_run(run);
+    let mut env = env.clone();
+    for sink in sinks {
+        env.remove(sink);
+    }
+    env.remove(collector);
+    latencies.sort();
+    let remote_actors = (workload.actors as f64 * workload.remote_share).round() as usize;
+    Ok(StressReport {
+        local_actors: workload.actors - remote_actors,
+        remote_actors,
+        messages_sent,
+        messages_echoed: latencies.len() as u64,
+        elapsed,
+        throughput: latencies.len() as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+        latencies,
+    })
+}
+
+/// Spawn the collector on the local machine and the sinks, the remote share of them on other machines.
+fn spawn_actors(
+    env: &Environment,
+    workload: &StressWorkload,
+) -> Result<(ActorRef, Vec<ActorRef>), ActlibError> {
+    let collector = env.spawn_local(COLLECTOR_TYPE_ID)?;
+    let remote_actors = (workload.actors as f64 * workload.remote_share).round() as usize;
+    let remote_options = SpawnOptions::new().exclude_machines(&[env.info().machine_id]);
+    let mut sinks = Vec::with_capacity(workload.actors);
+    for i in 0..workload.actors {
+        let sink = if i < remote_actors {
+            env.spawn_with_options(SINK_TYPE_ID, remote_options.clone())
+        } else {
+            env.spawn_local(SINK_TYPE_ID)
+        };
+        match sink {
+            Ok(sink) => sinks.push(sink),
+            Err(e) => {
+                let mut env = env.clone();
+                for sink in sinks {
+                    env.remove(sink);
+                }
+                env.remove(collector);
+                return Err(e);
+            }
+        }
+    }
+    Ok((collector, sinks))
+}
+
+/// Send the messages round robin at the configured rate, returning how many were sent and how long it took.
+fn send_messages(
+    run: u64,
+    started: Instant,
+    collector: &ActorRef,
+    sinks: &[ActorRef],
+    workload: &StressWorkload,
+) -> (u64, Duration) {
+    if sinks.is_empty() || workload.messages_per_second == 0 {
+        return (0, Duration::from_secs(0));
+    }
+    let interval = Duration::from_secs(1).as_secs_f64() / workload.messages_per_second as f64;
+    let payload = vec![0u8; workload.payload_size];
+    let sending_since = Instant::now();
+    let mut sent = 0u64;
+    while sending_since.elapsed() < workload.duration {
+        // catch up in a burst if sending fell behind the schedule
+        let due = (sending_since.elapsed().as_secs_f64() / interval) as u64 + 1;
+        while sent < due {
+            let sink = &sinks[sent as usize % sinks.len()];
+            let probe = Probe {
+                run,
+                sent_at: started.elapsed(),
+                reply_to: collector.clone_id(),
+                payload: payload.clone(),
+            };
+            if let Err(e) = sink.send_message(probe) {
+                warn!(
+                    "Stress run {} could not send to {:?}: {:?}",
+                    run,
+                    sink.clone_id(),
+                    e
+                );
+            }
+            sent += 1;
+        }
+        let next = Duration::from_secs_f64(sent as f64 * interval);
+        if let Some(wait) = next.checked_sub(sending_since.elapsed()) {
+            std::thread::sleep(wait.min(workload.duration.saturating_sub(sending_since.elapsed())));
+        }
+    }
+    (sent, sending_since.elapsed())
+}
+
+/// A synthetic message, echoed to the collector.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Probe {
+    run: u64,
+    /// When the probe was sent, relative to the start of the run
+    sent_at: Duration,
+    reply_to: ActorId,
+    payload: Vec<u8>,
+}
+
+/// The echo of a Probe, without its payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Echo {
+    run: u64,
+    sent_at: Duration,
+}
+
+/// Receives the synthetic messages of a run and echoes them to its collector.
+#[derive(Debug, Default)]
+pub struct StressSink;
+
+impl StressSink {
+    /// Create a sink, to be registered under [SINK_TYPE_ID](constant.SINK_TYPE_ID.html).
+    pub fn new() -> Self {
+        StressSink
+    }
+
+    fn echo(&mut self, probe: &Probe) {
+        let echoed = Context::env()
+            .to_actor_ref(probe.reply_to.clone())
+            .and_then(|collector| {
+                collector.send_message(Echo {
+                    run: probe.run,
+                    sent_at: probe.sent_at,
+                })
+            });
+        if let Err(e) = echoed {
+            warn!("Stress run {} lost an echo: {:?}", probe.run, e);
+        }
+    }
+}
+
+impl Actor for StressSink {}
+
+impl_message_handler!(StressSink: Probe => StressSink::echo);
+
+/// Measures the round trip of the echoed messages, living on the machine of the run.
+#[derive(Debug, Default)]
+pub struct StressCollector;
+
+impl StressCollector {
+    /// Create a collector, to be registered under [COLLECTOR_TYPE_ID](constant.COLLECTOR_TYPE_ID.html).
+    pub fn new() -> Self {
+        StressCollector
+    }
+
+    fn collect(&mut self, echo: &Echo) {
+        match pending_runs().lock() {
+            Ok(runs) => {
+                if let Some((started, sender)) = runs.get(&echo.run) {
+                    let _ = sender.send(started.elapsed().saturating_sub(echo.sent_at));
+                }
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+}
+
+impl Actor for StressCollector {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(StressCollector: Echo => StressCollector::collect);
@@ -0,0 +1,293 @@
+//! This module turns the updates an [Actor](../actor/trait.Actor.html) publishes into a ```futures::Stream```,
+//! so async code, e.g. a dashboard or a websocket gateway, consumes them without an adapter Actor of its own.
+//!
+//! Only available with the ```streams``` feature.
+//!
+//! - [ActorRef::subscribe](../actor/struct.ActorRef.html#method.subscribe) spawns a local proxy Actor, which sends a [Subscribe](struct.Subscribe.html) to the publisher.
+//! - The publisher keeps its subscribers in [Subscribers](struct.Subscribers.html) and [publishes](struct.Subscribers.html#method.publish) every update to them.
+//! - The proxy passes the updates on to the [UpdateStream](struct.UpdateStream.html). Dropping the stream removes the proxy, which sends an [Unsubscribe](struct.Unsubscribe.html).
+//!
+//! ```rust,ignore
+//! impl_message_handler!(Ticker:
+//!     Subscribe<Price> => |ticker: &mut Ticker, request: &Subscribe<Price>| ticker.subscribers.subscribe(request),
+//!     Unsubscribe<Price> => |ticker: &mut Ticker, request: &Unsubscribe<Price>| ticker.subscribers.unsubscribe(request),
+//! );
+//!
+//! let mut prices = ticker_ref.subscribe::<Price>(&env)?;
+//! while let Some(price) = prices.next().await {
+//!     socket.send(render(&price)).await?;
+//! }
+//! ```
+
+use crate::actor::{Actor, ActorId, ActorRef, ActorRefChannel, Context, LocalId, StopReason};
+use crate::api::{ActlibError, Environment, Terminated};
+use crate::environment::LocalEnvironment;
+use crate::message::{message_tag, EitherMessage, MessageHandler, Token};
+use crate::options::SpawnOptions;
+use crate::wire::WireFormat;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::{Stream, StreamExt};
+use log::info;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{self, Poll};
+use uuid::Uuid;
+
+/// The type id the subscription proxies are spawned with, e.g. listed by [list_local_actors](../api/struct.Environment.html#method.list_local_actors).
+pub const SUBSCRIPTION_TYPE_ID: &str = "actlib::Subscription";
+
+/// Asks an Actor to send its updates of type *U* to the *subscriber* until it receives an [Unsubscribe](struct.Unsubscribe.html).
+///
+/// Register it with the [impl_message_handler!](../macro.impl_message_handler.html) macro of the publishing Actor,
+/// subscriptions to different update types of the same Actor are told apart by *U*.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Subscribe<U> {
+    /// The Actor the updates are sent to
+    pub subscriber: ActorId,
+    #[serde(skip)]
+    update: PhantomData<fn() -> U>,
+}
+
+/// Ends the subscription of the *subscriber* to the updates of type *U*, sent once its [UpdateStream](struct.UpdateStream.html) was dropped.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Unsubscribe<U> {
+    /// The Actor that no longer wants the updates
+    pub subscriber: ActorId,
+    #[serde(skip)]
+    update: PhantomData<fn() -> U>,
+}
+
+impl<U> Subscribe<U> {
+    /// Subscribe the Actor of *subscriber*.
+    pub fn new(subscriber: ActorId) -> Self {
+        Subscribe {
+            subscriber,
+            update: PhantomData,
+        }
+    }
+}
+
+impl<U> Unsubscribe<U> {
+    /// Unsubscribe the Actor of *subscriber*.
+    pub fn new(subscriber: ActorId) -> Self {
+        Unsubscribe {
+            subscriber,
+            update: PhantomData,
+        }
+    }
+}
+
+/// The subscribers of a publishing Actor to its updates of type *U*.
+///
+/// Its methods are meant to be called from the handlers of the publishing Actor.
+#[derive(Debug)]
+pub struct Subscribers<U> {
+    subscribers: Vec<ActorRef>,
+    update: PhantomData<fn(U)>,
+}
+
+impl<U> Default for Subscribers<U> {
+    fn default() -> Self {
+        Subscribers {
+            subscribers: Vec::new(),
+            update: PhantomData,
+        }
+    }
+}
+
+impl<U: Debug + Send + Serialize + DeserializeOwned + Clone + 'static> Subscribers<U> {
+    /// Create an empty set of subscribers.
+    pub fn new() -> Self {
+        Subscribers::default()
+    }
+
+    /// Add the subscriber of the *request*, a subscriber is only added once.
+    ///
+    /// Panics outside of an Actor, like [Context::env](../actor/struct.Context.html#method.env).
+    pub fn subscribe(&mut self, request: &Subscribe<U>) {
+        if self
+            .subscribers
+            .iter()
+            .any(|subscriber| subscriber.actor_id == request.subscriber)
+        {
+            return;
+        }
+        match Context::env().to_actor_ref(request.subscriber.clone()) {
+            Ok(subscriber) => self.subscribers.push(subscriber),
+            Err(e) => info!(
+                "Ignored the subscription of {:?}: {:?}",
+                request.subscriber, e
+            ),
+        }
+    }
+
+    /// Remove the subscriber of the *request*.
+    pub fn unsubscribe(&mut self, request: &Unsubscribe<U>) {
+        self.subscribers
+            .retain(|subscriber| subscriber.actor_id != request.subscriber);
+    }
+
+    /// Send the *update* to every subscriber, dropping the subscribers that cannot be reached anymore.
+    pub fn publish(&mut self, update: &U) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send_message(update.clone()).is_ok());
+    }
+
+    /// The number of subscribers.
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Whether nobody subscribed.
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+}
+
+/// The updates of type *U* an Actor published since the [subscription](../actor/struct.ActorRef.html#method.subscribe), oldest first.
+///
+/// The stream ends once the publisher stopped. Dropping it ends the subscription.
+#[derive(Debug)]
+pub struct UpdateStream<U> {
+    updates: UnboundedReceiver<U>,
+    proxy: ActorRef,
+}
+
+impl<U> Stream for UpdateStream<U> {
+    type Item = U;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<U>> {
+        self.updates.poll_next_unpin(cx)
+    }
+}
+
+impl<U> Drop for UpdateStream<U> {
+    fn drop(&mut self) {
+        // the proxy is always local, it unsubscribes in on_stop
+        if let ActorRefChannel::Local(s) = &self.proxy.sender {
+            let _ = s.send(EitherMessage::Special(Token::Stop(StopReason::Removed)), 0);
+        }
+    }
+}
+
+/// Passes the updates of the publisher on to an UpdateStream.
+#[derive(Debug)]
+struct SubscriptionProxy<U> {
+    publisher: ActorRef,
+    own_id: Option<ActorId>,
+    /// ```None``` once the publisher stopped, which ends the stream
+    updates: Option<UnboundedSender<U>>,
+}
+
+impl<U: Debug + Send + Serialize + DeserializeOwned + 'static> Actor for SubscriptionProxy<U> {
+    fn on_start(&mut self, _local_env: Environment, own_ref: ActorRef) {
+        if let Err(e) = self.publisher.watch(&own_ref) {
+            info!("Could not watch {:?}: {:?}", self.publisher.actor_id, e);
+        }
+        if let Err(e) = self
+            .publisher
+            .send_message(Subscribe::<U>::new(own_ref.clone_id()))
+        {
+            info!(
+                "Could not subscribe to {:?}: {:?}",
+                self.publisher.actor_id, e
+            );
+        }
+        self.own_id = Some(own_ref.clone_id());
+    }
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        if let (Some(own_id), Some(_)) = (self.own_id.take(), &self.updates) {
+            let _ = self.publisher.send_message(Unsubscribe::<U>::new(own_id));
+        }
+    }
+}
+
+impl<U: Debug + Send + Serialize + DeserializeOwned + 'static> MessageHandler
+    for SubscriptionProxy<U>
+{
+    fn handle(&mut self, message: Box<dyn Any>) {
+        match message.downcast::<U>() {
+            Ok(update) => {
+                if let Some(updates) = &self.updates {
+                    // the stream is being dropped, which removes this proxy
+                    let _ = updates.unbounded_send(*update);
+                }
+            }
+            Err(message) => {
+                if message.downcast_ref::<Terminated>().is_some() {
+                    self.updates = None;
+                    let mut env = Context::env();
+                    env.remove(Context::self_ref());
+                }
+            }
+        }
+    }
+
+    fn deserialize_to_any(&self, message: &[u8]) -> Option<Box<dyn Any + Send>> {
+        self.deserialize_tagged(message_tag::<U>(), message, WireFormat::Bincode)
+    }
+
+    fn deserialize_tagged(
+        &self,
+        tag: u64,
+        message: &[u8],
+        format: WireFormat,
+    ) -> Option<Box<dyn Any + Send>> {
+        if tag == message_tag::<U>() {
+            format
+                .deserialize::<U>(message)
+                .ok()
+                .map(|update| Box::new(update) as Box<dyn Any + Send>)
+        } else if tag == message_tag::<Terminated>() {
+            format
+                .deserialize::<Terminated>(message)
+                .ok()
+                .map(|terminated| Box::new(terminated) as Box<dyn Any + Send>)
+        } else {
+            None
+        }
+    }
+}
+
+impl ActorRef {
+    /// Subscribe to the updates of type *U* the Actor behind this ActorRef publishes, e.g. with [Subscribers](../subscription/struct.Subscribers.html).
+    ///
+    /// A local proxy Actor of type [SUBSCRIPTION_TYPE_ID](../subscription/constant.SUBSCRIPTION_TYPE_ID.html) is spawned in *env*,
+    /// it sends the publisher a [Subscribe](../subscription/struct.Subscribe.html) and passes the updates on to the returned stream.
+    /// The publisher may live on any machine.
+    ///
+    /// Only available with the ```streams``` feature.
+    pub fn subscribe<U: Debug + Send + Serialize + DeserializeOwned + 'static>(
+        &self,
+        env: &Environment,
+    ) -> Result<UpdateStream<U>, ActlibError> {
+        if env.env.is_shutting_down() {
+            return Err(ActlibError::ShuttingDown(format!(
+                "The Environment is shutting down, cannot subscribe to {:?}",
+                self.actor_id
+            )));
+        }
+        let (sender, updates) = unbounded();
+        let proxy = SubscriptionProxy {
+            publisher: self.clone(),
+            own_id: None,
+            updates: Some(sender),
+        };
+        let proxy = LocalEnvironment::start_local_actor(
+            env,
+            SUBSCRIPTION_TYPE_ID,
+            Box::new(proxy),
+            LocalId::Automatic(Uuid::new_v4()),
+            Context::try_self_id(),
+            &SpawnOptions::default(),
+        )?;
+        Ok(UpdateStream { updates, proxy })
+    }
+}
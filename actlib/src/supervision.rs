@@ -0,0 +1,149 @@
+//! This module defines the supervision of the internal threads every [Environment](../api/struct.Environment.html) runs.
+//!
+//! The receive loops, the relay loop, the timer and the periodic exchanges run on threads of their own. A loop that panics
+//! is run again on its thread, so a single bad message does not cut the machine off. A loop panicking too often within
+//! a short time is given up on, the Environment learns about it through the callback it [registered](struct.Supervisor.html#method.on_given_up).
+//! Every panic is kept for the [health](../api/struct.Environment.html#method.health) of the machine.
+
+use crate::api::ThreadHealth;
+use crate::errors::ActlibError;
+use crate::log_err_as;
+#[allow(unused_imports)]
+use log::{error, info, warn};
+use std::collections::{HashMap, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often a loop may panic within the RESTART_WINDOW before it is given up on.
+const MAX_RESTARTS: usize = 5;
+
+/// The time the restarts of a loop are counted in.
+const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+/// Called with the name of a thread that was given up on and its last panic message.
+type GivenUp = Box<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Keeps the internal threads of an Environment running and remembers their panics.
+#[derive(Default)]
+pub(crate) struct Supervisor {
+    /// The threads that panicked, by name
+    threads: Mutex<HashMap<String, ThreadHealth>>,
+    given_up: Mutex<Option<GivenUp>>,
+}
+
+impl std::fmt::Debug for Supervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Supervisor {{threads: {:?}}}", self.threads)
+    }
+}
+
+impl Supervisor {
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Supervisor::default())
+    }
+
+    /// Call *given_up* for every thread that is given up on from now on.
+    pub(crate) fn on_given_up<F: Fn(&str, &str) + Send + Sync + 'static>(&self, given_up: F) {
+        match self.given_up.lock() {
+            Ok(mut callback) => *callback = Some(Box::new(given_up)),
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    /// Start a thread named *name* that runs the loop *body* until it returns, running it again after a panic.
+    ///
+    /// The *body* is expected to pick up where it left off, e.g. by waiting on the same channel again.
+    pub(crate) fn spawn<F: FnMut() + Send + 'static>(self: &Arc<Self>, name: String, body: F) {
+        let supervisor = self.clone();
+        let thread_name = name.clone();
+        if let Err(e) = std::thread::Builder::new()
+            .name(name.clone())
+            .spawn(move || supervisor.run(&thread_name, body))
+        {
+            error!("Failed to start the thread {}: {:?}", name, e);
+        }
+    }
+
+    /// Run *body* on the current thread until it returns, or until it panicked too often.
+    pub(crate) fn run<F: FnMut()>(&self, name: &str, mut body: F) {
+        let mut restarts: VecDeque<Instant> = VecDeque::new();
+        loop {
+            let payload = match std::panic::catch_unwind(AssertUnwindSafe(&mut body)) {
+                Ok(()) => return,
+                Err(payload) => payload,
+            };
+            let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "<no panic message>".to_string()
+            };
+            while let Some(restarted_at) = restarts.front() {
+                if restarted_at.elapsed() < RESTART_WINDOW {
+                    break;
+                }
+                restarts.pop_front();
+            }
+            let running = restarts.len() < MAX_RESTARTS;
+            self.record(name, &message, running);
+            if !running {
+                error!(
+                    "The thread {} panicked {} times within {:?}, giving up on it: {}",
+                    name,
+                    MAX_RESTARTS + 1,
+                    RESTART_WINDOW,
+                    message
+                );
+                match self.given_up.lock() {
+                    Ok(given_up) => {
+                        if let Some(given_up) = &*given_up {
+                            given_up(name, &message);
+                        }
+                    }
+                    Err(e) => error!("{}", e),
+                }
+                return;
+            }
+            warn!("The thread {} panicked, restarting it: {}", name, message);
+            restarts.push_back(Instant::now());
+        }
+    }
+
+    fn record(&self, name: &str, message: &str, running: bool) {
+        match self.threads.lock() {
+            Ok(mut threads) => {
+                let health = threads
+                    .entry(name.to_string())
+                    .or_insert_with(|| ThreadHealth {
+                        name: name.to_string(),
+                        panics: 0,
+                        last_panic: String::new(),
+                        last_panic_at: SystemTime::now(),
+                        running,
+                    });
+                health.panics += 1;
+                health.last_panic = message.to_string();
+                health.last_panic_at = SystemTime::now();
+                health.running = running;
+            }
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// The threads that panicked, sorted by name.
+    pub(crate) fn threads(&self) -> Vec<ThreadHealth> {
+        match self.threads.lock() {
+            Ok(threads) => {
+                let mut threads: Vec<ThreadHealth> = threads.values().cloned().collect();
+                threads.sort_by(|a, b| a.name.cmp(&b.name));
+                threads
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                Vec::new()
+            }
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! This module defines the Timer every [Environment](../api/struct.Environment.html) uses to run scheduled tasks.
+//!
+//! A single thread per Environment waits for the next due task, instead of one sleeping thread per task.
+//! Every task can be cancelled through its [TimerHandle](../api/struct.TimerHandle.html).
+//! Once the Environment expires, pending tasks are cancelled, so no delayed message reaches an Actor during shutdown.
+
+use crate::errors::ActlibError;
+use crate::log_err_as;
+use crate::supervision::Supervisor;
+#[allow(unused_imports)]
+use log::{error, info, warn};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// How often an idle timer thread checks whether its Environment is gone.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The work of a task, run once or repeated until it returns ```false```.
+enum Task {
+    Once(Box<dyn FnOnce() + Send>),
+    Periodic(Duration, Box<dyn FnMut() -> bool + Send>),
+}
+
+/// A task waiting for its due time.
+struct ScheduledTask {
+    due: Instant,
+    /// Keeps tasks with the same due time in order of scheduling, and identifies the task for its handle
+    sequence_no: u64,
+    /// Shared with the handle, set once the task will not run again
+    done: Arc<AtomicBool>,
+    task: Task,
+}
+
+impl PartialEq for ScheduledTask {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ScheduledTask {}
+
+impl PartialOrd for ScheduledTask {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScheduledTask {
+    /// Reversed, so the BinaryHeap returns the task due first.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.due, other.sequence_no).cmp(&(self.due, self.sequence_no))
+    }
+}
+
+#[derive(Default)]
+struct TimerTasks {
+    queue: BinaryHeap<ScheduledTask>,
+    next_sequence_no: u64,
+    /// Set once the tasks were cancelled, later tasks are dropped right away
+    cancelled: bool,
+}
+
+impl std::fmt::Debug for TimerTasks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "TimerTasks {{scheduled: {}, cancelled: {}}}",
+            self.queue.len(),
+            self.cancelled
+        )
+    }
+}
+
+/// Runs scheduled tasks on a dedicated thread, until it is dropped by its Environment.
+#[derive(Debug)]
+pub(crate) struct Timer {
+    tasks: Mutex<TimerTasks>,
+    wakeup: Condvar,
+}
+
+impl Timer {
+    /// Create a timer and start its thread, a panicking task is dropped and the thread carries on with the next one.
+    pub(crate) fn start(supervisor: &Arc<Supervisor>) -> Arc<Timer> {
+        let timer = Arc::new(Timer {
+            tasks: Mutex::new(TimerTasks::default()),
+            wakeup: Condvar::new(),
+        });
+        let timer_thread = timer.clone();
+        supervisor.spawn("actlib-timer".to_string(), move || timer_thread.run());
+        timer
+    }
+
+    /// Run *task* on the timer thread once *due* has passed.
+    ///
+    /// Tasks are expected to terminate quickly, every later task waits for them.
+    /// Tasks scheduled after [cancel_all](#method.cancel_all) are dropped without running.
+    pub(crate) fn schedule_at<F: FnOnce() + Send + 'static>(
+        self: &Arc<Self>,
+        due: Instant,
+        task: F,
+    ) -> TimerHandle {
+        self.schedule(due, Task::Once(Box::new(task)))
+    }
+
+    /// Run *task* on the timer thread once *due* has passed, and every *interval* after, until it returns ```false```.
+    ///
+    /// A run that is late is not repeated, the following runs keep the interval to it.
+    pub(crate) fn schedule_periodic<F: FnMut() -> bool + Send + 'static>(
+        self: &Arc<Self>,
+        due: Instant,
+        interval: Duration,
+        task: F,
+    ) -> TimerHandle {
+        self.schedule(due, Task::Periodic(interval, Box::new(task)))
+    }
+
+    fn schedule(self: &Arc<Self>, due: Instant, task: Task) -> TimerHandle {
+        let done = Arc::new(AtomicBool::new(false));
+        let mut id = 0;
+        match self.tasks.lock() {
+            Ok(tasks) if tasks.cancelled => done.store(true, AtomicOrdering::SeqCst),
+            Ok(mut tasks) => {
+                id = tasks.next_sequence_no;
+                tasks.next_sequence_no += 1;
+                tasks.queue.push(ScheduledTask {
+                    due,
+                    sequence_no: id,
+                    done: done.clone(),
+                    task,
+                });
+                self.wakeup.notify_one();
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                done.store(true, AtomicOrdering::SeqCst);
+            }
+        }
+        TimerHandle {
+            id,
+            done,
+            timer: Arc::downgrade(self),
+        }
+    }
+
+    /// Drop the pending task with the given id, if it is still queued.
+    fn remove(&self, id: u64) {
+        match self.tasks.lock() {
+            Ok(mut tasks) => tasks.queue.retain(|scheduled| scheduled.sequence_no != id),
+            Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+        }
+    }
+
+    /// Drop every pending task and every task scheduled from now on, returning the number of dropped pending tasks.
+    pub(crate) fn cancel_all(&self) -> usize {
+        match self.tasks.lock() {
+            Ok(mut tasks) => {
+                tasks.cancelled = true;
+                let cancelled = tasks.queue.len();
+                for scheduled in tasks.queue.drain() {
+                    scheduled.done.store(true, AtomicOrdering::SeqCst);
+                }
+                cancelled
+            }
+            Err(e) => {
+                log_err_as!(error, ActlibError::from_poison_error(&e));
+                0
+            }
+        }
+    }
+
+    /// Wait for the due tasks and run them, until the timer is only referenced by its own thread.
+    fn run(self: &Arc<Self>) {
+        loop {
+            let task = {
+                let mut tasks = match self.tasks.lock() {
+                    Ok(tasks) => tasks,
+                    Err(e) => {
+                        log_err_as!(error, ActlibError::from_poison_error(&e));
+                        return;
+                    }
+                };
+                loop {
+                    let now = Instant::now();
+                    let timeout = match tasks.queue.peek() {
+                        Some(next) if next.due <= now => break,
+                        Some(next) => (next.due - now).min(IDLE_CHECK_INTERVAL),
+                        None => IDLE_CHECK_INTERVAL,
+                    };
+                    if Arc::strong_count(self) == 1 {
+                        // the Environment is gone, nobody is interested in the remaining tasks
+                        return;
+                    }
+                    tasks = match self.wakeup.wait_timeout(tasks, timeout) {
+                        Ok((tasks, _timeout)) => tasks,
+                        Err(e) => {
+                            log_err_as!(error, ActlibError::from_poison_error(&e));
+                            return;
+                        }
+                    };
+                }
+                tasks.queue.pop()
+            };
+            if let Some(scheduled) = task {
+                self.run_task(scheduled);
+            }
+        }
+    }
+
+    /// Run a due task, unless it was cancelled meanwhile, and queue its next run if it is periodic.
+    fn run_task(&self, scheduled: ScheduledTask) {
+        if scheduled.done.load(AtomicOrdering::SeqCst) {
+            return;
+        }
+        let _done_on_panic = DoneOnPanic(scheduled.done.clone());
+        match scheduled.task {
+            Task::Once(task) => {
+                if !scheduled.done.swap(true, AtomicOrdering::SeqCst) {
+                    task();
+                }
+            }
+            Task::Periodic(interval, mut task) => {
+                if !task() {
+                    scheduled.done.store(true, AtomicOrdering::SeqCst);
+                    return;
+                }
+                match self.tasks.lock() {
+                    // the handle may have been cancelled while the task ran
+                    Ok(tasks) if tasks.cancelled || scheduled.done.load(AtomicOrdering::SeqCst) => {
+                        scheduled.done.store(true, AtomicOrdering::SeqCst);
+                    }
+                    Ok(mut tasks) => tasks.queue.push(ScheduledTask {
+                        due: (scheduled.due + interval).max(Instant::now()),
+                        sequence_no: scheduled.sequence_no,
+                        done: scheduled.done,
+                        task: Task::Periodic(interval, task),
+                    }),
+                    Err(e) => log_err_as!(error, ActlibError::from_poison_error(&e)),
+                }
+            }
+        }
+    }
+}
+
+/// Marks a task as done if it panics, the restarted timer thread carries on without it.
+struct DoneOnPanic(Arc<AtomicBool>);
+
+impl Drop for DoneOnPanic {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.store(true, AtomicOrdering::SeqCst);
+        }
+    }
+}
+
+/// A pending delayed or periodic message, returned by [Environment::schedule_once](../api/struct.Environment.html#method.schedule_once),
+/// [Environment::schedule_periodic](../api/struct.Environment.html#method.schedule_periodic) and
+/// [ActorRef::send_delayed_message](../actor/struct.ActorRef.html#method.send_delayed_message).
+///
+/// Dropping the handle does not cancel the timer.
+#[derive(Debug, Clone)]
+pub struct TimerHandle {
+    id: u64,
+    done: Arc<AtomicBool>,
+    timer: Weak<Timer>,
+}
+
+impl TimerHandle {
+    /// A handle of a timer that never runs, e.g. because its Environment is gone.
+    pub(crate) fn inactive() -> Self {
+        TimerHandle {
+            id: 0,
+            done: Arc::new(AtomicBool::new(true)),
+            timer: Weak::new(),
+        }
+    }
+
+    /// Stop the timer, no further message is sent.
+    ///
+    /// Returns ```false``` if the timer had already fired its only message, was cancelled before, or its Environment expired.
+    pub fn cancel(&self) -> bool {
+        if self.done.swap(true, AtomicOrdering::SeqCst) {
+            return false;
+        }
+        if let Some(timer) = self.timer.upgrade() {
+            timer.remove(self.id);
+        }
+        true
+    }
+
+    /// ```true``` while the timer may still send a message.
+    pub fn is_active(&self) -> bool {
+        !self.done.load(AtomicOrdering::SeqCst) && self.timer.strong_count() > 0
+    }
+}
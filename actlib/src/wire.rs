@@ -0,0 +1,110 @@
+//! This module defines the [WireFormat](enum.WireFormat.html) the machines of a cluster exchange their messages in.
+//!
+//! The format encodes every NetMessage between the machines and the Messages they carry. Bincode is the most compact,
+//! JSON and MessagePack let peers not written in Rust, e.g. a web worker, take part.
+
+use crate::errors::ActlibError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+/// The encoding of the messages exchanged between machines, chosen with [EnvironmentOptions::wire_format](../options/struct.EnvironmentOptions.html#method.wire_format).
+///
+/// Every machine of a cluster has to use the same format, the machines fail to introduce themselves to each other otherwise.
+/// Messages passed with [send_serialized](../actor/struct.ActorRef.html#method.send_serialized) are expected in bincode, whatever the format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum WireFormat {
+    /// [bincode](https://docs.rs/bincode), compact and only understood by Rust peers.
+    #[default]
+    Bincode,
+    /// JSON, Messages and NetMessages as serde represents them.
+    ///
+    /// Maps need keys serialized as strings, Messages with other keys cannot be sent.
+    Json,
+    /// [MessagePack](https://msgpack.org), with the names of struct fields.
+    MessagePack,
+}
+
+impl WireFormat {
+    /// Encode *value* in this format.
+    pub fn serialize<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, ActlibError> {
+        let encoded = match self {
+            WireFormat::Bincode => bincode::serialize(value).map_err(|e| format!("{:?}", e)),
+            WireFormat::Json => serde_json::to_vec(value).map_err(|e| format!("{:?}", e)),
+            WireFormat::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| format!("{:?}", e))
+            }
+        };
+        encoded.map_err(|e| ActlibError::NetworkError(format!("Failed to encode {}: {}", self, e)))
+    }
+
+    /// The number of bytes *value* takes in this format, without keeping the encoding.
+    ///
+    /// Used to account local messages against [mailbox quotas](../options/struct.MailboxQuota.html), 0 if the value cannot be encoded.
+    pub(crate) fn serialized_size<T: Serialize + ?Sized>(self, value: &T) -> usize {
+        let mut counter = ByteCounter(0);
+        let counted = match self {
+            WireFormat::Bincode => return bincode::serialized_size(value).unwrap_or(0) as usize,
+            WireFormat::Json => serde_json::to_writer(&mut counter, value).is_ok(),
+            WireFormat::MessagePack => rmp_serde::encode::write_named(&mut counter, value).is_ok(),
+        };
+        if counted {
+            counter.0
+        } else {
+            0
+        }
+    }
+
+    /// Decode a value of type *T* encoded in this format.
+    pub fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, ActlibError> {
+        let decoded = match self {
+            WireFormat::Bincode => bincode::deserialize(bytes).map_err(|e| format!("{:?}", e)),
+            WireFormat::Json => serde_json::from_slice(bytes).map_err(|e| format!("{:?}", e)),
+            WireFormat::MessagePack => rmp_serde::from_slice(bytes).map_err(|e| format!("{:?}", e)),
+        };
+        decoded.map_err(|e| ActlibError::NetworkError(format!("Failed to decode {}: {}", self, e)))
+    }
+}
+
+/// Counts the bytes written to it instead of keeping them.
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl fmt::Display for WireFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireFormat::Bincode => write!(f, "bincode"),
+            WireFormat::Json => write!(f, "json"),
+            WireFormat::MessagePack => write!(f, "msgpack"),
+        }
+    }
+}
+
+impl FromStr for WireFormat {
+    type Err = ActlibError;
+
+    /// Parse the name printed by ```Display```, e.g. from a configuration file or an environment variable.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "bincode" => Ok(WireFormat::Bincode),
+            "json" => Ok(WireFormat::Json),
+            "msgpack" | "messagepack" => Ok(WireFormat::MessagePack),
+            other => Err(ActlibError::InvalidState(format!(
+                "Unknown wire format {:?}, expected bincode, json or msgpack",
+                other
+            ))),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! A panicking handler is reported as an ActorFailed event, and the FailurePolicy decides whether the Actor carries on.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static HANDLED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Fragile;
+
+impl Actor for Fragile {}
+
+/// Panics on zero.
+fn divide(_: &mut Fragile, divisor: &u32) {
+    if *divisor == 0 {
+        panic!("division by zero");
+    }
+    let handled = HANDLED.lock().unwrap();
+    handled.as_ref().unwrap().send(100 / divisor).unwrap();
+}
+
+impl_message_handler!(Fragile: u32 => divide);
+
+#[test]
+fn failures_are_reported_and_handled_by_policy() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+
+    // stopped by default
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Fragile" => Fragile));
+    let failures = env.watch_actor_failures();
+    let fragile = env.spawn("Fragile").unwrap();
+    fragile.send_message(0u32).unwrap();
+    let failure = failures.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(failure.actor, fragile.clone_id());
+    assert_eq!(failure.type_id, "Fragile");
+    assert_eq!(failure.message_type.as_deref(), Some("u32"));
+    assert!(failure.reason.contains("division by zero"));
+    assert!(failure.stopped);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !env.list_local_actors(Some("Fragile")).is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(env.list_local_actors(Some("Fragile")).is_empty());
+
+    // resumed with the next message
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Fragile" => Fragile),
+        EnvironmentOptions::new().failure_policy(FailurePolicy::Resume),
+    );
+    let failures = env.watch_actor_failures();
+    let fragile = env.spawn("Fragile").unwrap();
+    fragile.send_message(0u32).unwrap();
+    fragile.send_message(4u32).unwrap();
+    assert!(
+        !failures
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+            .stopped
+    );
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 25);
+    assert_eq!(
+        env.list_local_actors(Some("Fragile")),
+        vec![fragile.clone_id()]
+    );
+}
@@ -0,0 +1,75 @@
+//! An alias forwards the messages sent to a replaced Actor to its replacement, until the alias expires.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static RECEIVED: Mutex<Option<Sender<(ActorId, u32)>>> = Mutex::new(None);
+
+#[derive(Debug, Default)]
+struct Replica {
+    id: Option<ActorId>,
+}
+
+impl Actor for Replica {
+    fn on_start(&mut self, _local_env: Environment, own_ref: ActorRef) {
+        self.id = Some(own_ref.clone_id());
+    }
+}
+
+fn receive(replica: &mut Replica, message: &u32) {
+    let received = RECEIVED.lock().unwrap();
+    received
+        .as_ref()
+        .unwrap()
+        .send((replica.id.clone().unwrap(), *message))
+        .unwrap();
+}
+
+impl_message_handler!(Replica: u32 => receive);
+
+#[test]
+fn messages_to_replaced_actors_are_forwarded_until_the_alias_expires() {
+    let (tx, rx) = channel();
+    *RECEIVED.lock().unwrap() = Some(tx);
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Replica" => Replica::default()));
+    let replaced = env.spawn("Replica").unwrap();
+    let replaced_id = replaced.clone_id();
+    let replacement = env.spawn("Replica").unwrap();
+
+    env.alias(
+        replaced_id.clone(),
+        &replacement,
+        Duration::from_millis(300),
+    )
+    .unwrap();
+    // an alias back to the replaced Actor would forward forever
+    assert!(matches!(
+        env.alias(replacement.clone_id(), &replaced, Duration::from_secs(1)),
+        Err(ActlibError::InvalidState(_))
+    ));
+    env.remove(replaced);
+    thread::sleep(Duration::from_millis(50));
+
+    env.to_actor_ref(replaced_id.clone())
+        .unwrap()
+        .send_message(1u32)
+        .unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        (replacement.clone_id(), 1)
+    );
+    let aliases = env.aliases();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].from, replaced_id);
+    assert_eq!(aliases[0].to, replacement.clone_id());
+    assert_eq!(aliases[0].forwarded, 1);
+    assert!(aliases[0].expires_in <= Duration::from_millis(300));
+
+    thread::sleep(Duration::from_millis(400));
+    assert!(env.aliases().is_empty());
+    assert!(env.to_actor_ref(replaced_id).is_err());
+}
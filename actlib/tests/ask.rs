@@ -0,0 +1,57 @@
+//! An ask returns a future for the typed reply of the asked Actor.
+
+use actlib::api::*;
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+struct Doubler {
+    env: Option<Environment>,
+}
+
+impl Actor for Doubler {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+/// Zero is not worth an answer.
+fn double(doubler: &mut Doubler, request: &Request<u32>) {
+    if request.message != 0 {
+        let env = doubler.env.as_ref().unwrap();
+        env.reply(request, request.message as u64 * 2).unwrap();
+    }
+}
+
+impl_message_handler!(Doubler: Request<u32> => double);
+
+#[test]
+fn asks_are_answered_with_typed_replies() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Doubler" => Doubler::default()));
+    let doubler = env.spawn("Doubler").unwrap();
+
+    // concurrent asks get their own replies
+    let first = doubler.ask::<_, u64>(21u32).unwrap();
+    let second = doubler.ask::<_, u64>(100u32).unwrap();
+    assert_eq!(second.wait(Duration::from_secs(5)).unwrap(), 200);
+    assert_eq!(first.wait(Duration::from_secs(5)).unwrap(), 42);
+    assert_eq!(
+        env.block_on(doubler.ask::<_, u64>(1u32).unwrap()).unwrap(),
+        2
+    );
+
+    assert!(matches!(
+        doubler
+            .ask::<_, u64>(0u32)
+            .unwrap()
+            .wait(Duration::from_millis(100)),
+        Err(ActlibError::Timeout(_))
+    ));
+    assert!(matches!(
+        doubler
+            .ask::<_, String>(21u32)
+            .unwrap()
+            .wait(Duration::from_secs(5)),
+        Err(ActlibError::InvalidState(_))
+    ));
+}
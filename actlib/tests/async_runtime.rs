@@ -0,0 +1,154 @@
+//! On the async runtime many Actors share a few worker threads, and handlers may await replies without starving the others.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static HANDLED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+fn report(value: u32) {
+    let handled = HANDLED.lock().unwrap();
+    handled.as_ref().unwrap().send(value).unwrap();
+}
+
+#[derive(Debug)]
+struct Cell;
+
+impl Actor for Cell {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(Cell: u32 => |_: &mut Cell, value: &u32| report(*value));
+
+#[derive(Debug)]
+struct Doubler {
+    env: Option<Environment>,
+}
+
+impl Actor for Doubler {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn double(doubler: &mut Doubler, request: &Request<u32>) {
+    let env = doubler.env.as_ref().unwrap();
+    env.reply(request, request.message * 2).unwrap();
+}
+
+impl_message_handler!(Doubler: Request<u32> => double);
+
+#[derive(Debug)]
+struct Asker {
+    env: Option<Environment>,
+}
+
+impl Actor for Asker {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn ask_doubler(asker: &mut Asker, doubler: &ActorId) {
+    let env = asker.env.as_ref().unwrap();
+    let doubler = env.to_actor_ref(doubler.clone()).unwrap();
+    let reply: u32 = env.block_on(doubler.ask(21u32).unwrap()).unwrap();
+    report(reply);
+}
+
+impl_message_handler!(Asker: ActorId => ask_doubler);
+
+#[test]
+fn actors_run_as_tasks() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+    let (env, expiration_checker) = Environment::new_with_runtime(
+        0,
+        &[],
+        actor_builder!(
+            "Cell" => Cell,
+            "Doubler" => Doubler { env: None },
+            "Asker" => Asker { env: None }
+        ),
+        Runtime::Async,
+    );
+
+    let cells: Vec<ActorRef> = (0..2000).map(|_| env.spawn("Cell").unwrap()).collect();
+    for (i, cell) in cells.iter().enumerate() {
+        cell.send_message(i as u32).unwrap();
+    }
+    let mut handled: Vec<u32> = (0..2000)
+        .map(|_| rx.recv_timeout(Duration::from_secs(10)).unwrap())
+        .collect();
+    handled.sort();
+    assert_eq!(handled, (0..2000).collect::<Vec<u32>>());
+
+    let doubler = env.spawn("Doubler").unwrap();
+    let asker = env.spawn("Asker").unwrap();
+    asker.send_message(doubler.clone_id()).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 42);
+
+    env.shutdown(Duration::from_secs(5)).unwrap();
+    assert!(expiration_checker
+        .wait_until_expiration()
+        .unwrap()
+        .is_clean());
+}
+
+static AWAITED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+fn report_awaited(value: u32) {
+    let awaited = AWAITED.lock().unwrap();
+    awaited.as_ref().unwrap().send(value).unwrap();
+}
+
+#[derive(Debug)]
+struct AsyncAsker {
+    env: Option<Environment>,
+    own_id: Option<ActorId>,
+}
+
+impl Actor for AsyncAsker {
+    fn on_start(&mut self, local_env: Environment, own_ref: ActorRef) {
+        self.env = Some(local_env);
+        self.own_id = Some(own_ref.clone_id());
+    }
+}
+
+async fn await_doubler(asker: &mut AsyncAsker, doubler: &ActorId) {
+    let env = asker.env.as_ref().unwrap();
+    let doubler = env.to_actor_ref(doubler.clone()).unwrap();
+    let reply: u32 = doubler.ask(21u32).unwrap().await.unwrap();
+    // the task may have moved to another worker, the Context still knows the Actor
+    assert_eq!(Context::self_id(), asker.own_id.clone().unwrap());
+    report_awaited(reply);
+}
+
+impl_message_handler!(AsyncAsker: u32 => |_: &mut AsyncAsker, value: &u32| report_awaited(*value); async ActorId => await_doubler);
+
+#[test]
+fn async_handlers_are_awaited() {
+    let (tx, rx) = channel();
+    *AWAITED.lock().unwrap() = Some(tx);
+    // the Doubler answers on the only worker while the AsyncAsker awaits the reply
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!(
+            "Doubler" => Doubler { env: None },
+            "AsyncAsker" => AsyncAsker { env: None, own_id: None }
+        ),
+        EnvironmentOptions::new()
+            .runtime(Runtime::Async)
+            .worker_threads(1),
+    );
+    let doubler = env.spawn("Doubler").unwrap();
+    let asker = env.spawn("AsyncAsker").unwrap();
+
+    // the next message waits until the async handler completed
+    asker.send_message(doubler.clone_id()).unwrap();
+    asker.send_message(7u32).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 42);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 7);
+}
@@ -0,0 +1,58 @@
+//! With the load exchange enabled, subscribed Actors receive the load of every machine after each measurement.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static LOADS: Mutex<Option<Sender<ClusterLoad>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Throttle;
+
+impl Actor for Throttle {}
+
+fn observe(_: &mut Throttle, load: &ClusterLoad) {
+    let loads = LOADS.lock().unwrap();
+    loads.as_ref().unwrap().send(load.clone()).unwrap();
+}
+
+#[derive(Debug)]
+struct Sleeper;
+
+impl Actor for Sleeper {}
+
+impl_message_handler!(Throttle: ClusterLoad => observe);
+impl_message_handler!(Sleeper: u64 => |_: &mut Sleeper, millis: &u64| thread::sleep(Duration::from_millis(*millis)));
+
+#[test]
+fn subscribers_see_the_backlog() {
+    let (tx, rx) = channel();
+    *LOADS.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Throttle" => Throttle, "Sleeper" => Sleeper),
+        EnvironmentOptions::new()
+            .load_exchange(LoadExchange::new().interval(Duration::from_millis(50))),
+    );
+    let throttle = env.spawn("Throttle").unwrap();
+    let sleeper = env.spawn("Sleeper").unwrap();
+    env.subscribe_cluster_load(&throttle);
+    for _ in 0..10 {
+        sleeper.send_message(100u64).unwrap();
+    }
+
+    let load = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(load.machines.len(), 1);
+    assert_eq!(load.machines[0].machine, env.info().machine_id);
+    assert_eq!(load.machines[0].actors, 2);
+    assert!(load.queued_messages() >= 5, "{:?}", load);
+    assert_eq!(env.cluster_load().machines.len(), 1);
+
+    env.unsubscribe_cluster_load(&throttle.clone_id());
+    thread::sleep(Duration::from_millis(100));
+    rx.try_iter().for_each(drop);
+    assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+}
@@ -0,0 +1,47 @@
+//! A remote machine that never came up is reported as down, and pinging an unknown machine fails.
+
+use actlib::api::*;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: () => |_: &mut Idle, _: &()| {});
+
+#[test]
+fn machines_that_never_came_up_are_down() {
+    // the hostname never resolves, so the remote machine never comes up
+    let missing = Peer::Host("missing-machine.invalid".to_string(), 7000);
+    let (env, _expiration_checker) = Environment::new_with_peers(
+        0,
+        std::slice::from_ref(&missing),
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new()
+            .wait_for_peers(0)
+            .heartbeat(Heartbeat::new().interval(Duration::from_millis(20))),
+    );
+    std::thread::sleep(Duration::from_millis(100));
+
+    let status = env.cluster_status();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].peer, missing);
+    assert_eq!(status[0].machine_id, None);
+    assert_eq!(status[0].state, MachineState::Down);
+    assert!(!status[0].is_up());
+    assert_eq!(status[0].round_trip, None);
+
+    // a machine that is not connected cannot be pinged
+    let unknown = MachineId::random();
+    match env.ping_machine(unknown, Duration::from_millis(100)) {
+        Err(ActlibError::NetworkError(_)) | Err(ActlibError::Timeout(_)) => {}
+        other => panic!("expected the ping to fail, got {:?}", other),
+    }
+}
+
+#[test]
+fn a_local_only_environment_has_no_remote_machines() {
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!("Idle" => Idle));
+    assert!(env.cluster_status().is_empty());
+}
@@ -0,0 +1,85 @@
+//! In a coalescing mailbox a keyed message replaces the queued message with the same key, keeping its place.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static HANDLED: Mutex<Option<Sender<(String, UpdateState)>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UpdateState {
+    field: u32,
+    players: u32,
+}
+
+impl Keyed for UpdateState {
+    type Key = u32;
+
+    fn key(&self) -> u32 {
+        self.field
+    }
+}
+
+#[derive(Debug)]
+struct Collector {
+    name: &'static str,
+}
+
+impl Actor for Collector {}
+
+/// Keeps the Collector busy while the updates queue up.
+fn block(_: &mut Collector, millis: &u64) {
+    thread::sleep(Duration::from_millis(*millis));
+}
+
+fn update(collector: &mut Collector, update: &UpdateState) {
+    let handled = HANDLED.lock().unwrap();
+    let handled = handled.as_ref().unwrap();
+    handled
+        .send((collector.name.to_string(), update.clone()))
+        .unwrap();
+}
+
+impl_message_handler!(Collector: u64 => block, UpdateState => update);
+
+fn state(field: u32, players: u32) -> UpdateState {
+    UpdateState { field, players }
+}
+
+#[test]
+fn queued_updates_are_replaced() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!(
+            "Coalescing" => Collector { name: "coalescing" },
+            "Queueing" => Collector { name: "queueing" }
+        ),
+        EnvironmentOptions::new().coalescing_mailbox("Coalescing"),
+    );
+
+    for type_id in ["Coalescing", "Queueing"] {
+        let collector = env.spawn(type_id).unwrap();
+        collector.send_message(200u64).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        for (field, players) in [(1, 1), (2, 1), (1, 2), (1, 3), (2, 2)] {
+            collector.send_keyed(state(field, players)).unwrap();
+        }
+    }
+
+    let mut coalescing = Vec::new();
+    let mut queueing = Vec::new();
+    while let Ok((name, update)) = rx.recv_timeout(Duration::from_millis(500)) {
+        match name.as_str() {
+            "coalescing" => coalescing.push(update),
+            _ => queueing.push(update),
+        }
+    }
+    assert_eq!(coalescing, vec![state(1, 3), state(2, 2)]);
+    assert_eq!(queueing.len(), 5);
+}
@@ -0,0 +1,73 @@
+//! The exported states of all Actors of a type are collected in one call, Actors exporting nothing are left out.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Summary {
+    figures: u32,
+}
+
+#[derive(Debug, Default)]
+struct Field {
+    figures: u32,
+    // not part of the exported projection
+    history: Vec<u32>,
+}
+
+impl Actor for Field {
+    fn export_state(&self) -> Option<ExportedState> {
+        ExportedState::new(&Summary {
+            figures: self.figures,
+        })
+        .ok()
+    }
+}
+
+fn place(field: &mut Field, request: &Request<u32>) {
+    field.figures += request.message;
+    field.history.push(request.message);
+    Context::env().reply(request, field.figures).unwrap();
+}
+
+impl_message_handler!(Field: Request<u32> => place);
+
+#[derive(Debug)]
+struct Silent;
+
+impl Actor for Silent {}
+
+impl_message_handler!(Silent: u32 => |_: &mut Silent, _: &u32| {});
+
+#[test]
+fn states_of_every_actor_of_a_type_are_collected() {
+    let (env, _expiration_checker) = Environment::new_local_only(
+        actor_builder!("Field" => Field::default(), "Silent" => Silent),
+    );
+    let mut expected = HashMap::new();
+    for figures in 1..=3u32 {
+        let field = env.spawn("Field").unwrap();
+        // the states are exported ahead of queued messages, so wait for them to be handled
+        for _ in 0..2 {
+            let _: u32 = field
+                .ask(figures)
+                .unwrap()
+                .wait(Duration::from_secs(5))
+                .unwrap();
+        }
+        expected.insert(
+            field.clone_id(),
+            Summary {
+                figures: 2 * figures,
+            },
+        );
+    }
+    env.spawn("Silent").unwrap();
+
+    let states: HashMap<ActorId, Summary> = env.collect_states("Field", Duration::from_secs(5));
+    assert_eq!(states, expected);
+    let silent: HashMap<ActorId, Summary> = env.collect_states("Silent", Duration::from_secs(5));
+    assert!(silent.is_empty());
+}
@@ -0,0 +1,42 @@
+//! Fixtures shared by the integration tests, every test crate uses only some of them.
+#![allow(dead_code)]
+
+use actlib::api::*;
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*, with the *options* of the test.
+///
+/// The machines of a test run in the same process, so each one needs its own loopback address, e.g. 127.0.0.1 and 127.0.0.2.
+pub fn start_machine(
+    ip: [u8; 4],
+    port: u16,
+    peer: impl Into<Peer>,
+    actor_builder: ActorBuilder,
+    options: EnvironmentOptions,
+) -> (Environment, EnvironmentExpirationChecker) {
+    Environment::new_with_peers(
+        port,
+        &[peer.into()],
+        actor_builder,
+        options.local_address(IpAddr::V4(Ipv4Addr::from(ip))),
+    )
+}
+
+/// Like [start_machine], for tests that do not wait for the Environment to expire.
+pub fn machine(
+    ip: [u8; 4],
+    port: u16,
+    peer: impl Into<Peer>,
+    actor_builder: ActorBuilder,
+    options: EnvironmentOptions,
+) -> Environment {
+    let (env, _expiration_checker) = start_machine(ip, port, peer, actor_builder, options);
+    env
+}
+
+/// An id of the same shape as *actor_id*, but on a machine that was never connected.
+pub fn on_unknown_machine(actor_id: ActorId) -> ActorId {
+    let mut id = serde_json::to_value(actor_id).unwrap();
+    id["location"] = serde_json::to_value(MachineId::random()).unwrap();
+    serde_json::from_value(id).unwrap()
+}
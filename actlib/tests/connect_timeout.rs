@@ -0,0 +1,52 @@
+//! Creating an Environment gives up on unreachable remote machines after the connect timeout.
+
+use actlib::api::*;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: () => |_: &mut Idle, _: &()| {});
+
+fn missing_machine() -> Peer {
+    // the hostname never resolves, so the remote machine never connects
+    Peer::Host("missing-machine.invalid".to_string(), 7000)
+}
+
+#[test]
+fn unreachable_machines_fail_the_environment() {
+    let started = Instant::now();
+    let created = Environment::try_new_with_peers(
+        0,
+        &[missing_machine()],
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new().connect_timeout(ConnectTimeout::new(Duration::from_millis(200))),
+    );
+    match created {
+        Err(ActlibError::NetworkError(reason)) => assert!(reason.contains("0 of 1")),
+        Err(e) => panic!("expected a NetworkError, got {:?}", e),
+        Ok(_) => panic!("expected the Environment to fail"),
+    }
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn a_partial_cluster_starts_without_the_unreachable_machines() {
+    let created = Environment::try_new_with_peers(
+        0,
+        &[missing_machine()],
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new()
+            .connect_timeout(ConnectTimeout::new(Duration::from_millis(200)).start_partial()),
+    );
+    let (env, _expiration_checker) = created.unwrap();
+
+    assert!(env.spawn("Idle").is_ok());
+    let status = env.cluster_status();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].peer, missing_machine());
+    assert!(!status[0].is_up());
+}
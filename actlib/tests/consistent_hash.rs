@@ -0,0 +1,148 @@
+//! With consistent-hash placement, Actors spawned with an id live on the machine the id hashes to.
+//!
+//! The two-machine test runs them on 127.0.0.1 and 127.0.0.2, the remote one in a process of its own.
+
+mod common;
+
+use actlib::api::*;
+use std::io::Read;
+use std::net::SocketAddr;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Cell;
+
+impl Actor for Cell {}
+
+impl_message_handler!(Cell: () => |_: &mut Cell, _: &()| {});
+
+#[test]
+fn ids_hash_to_the_connected_machines() {
+    // the hostname never resolves, so the remote machine never connects and owns no ids
+    let missing = Peer::Host("missing-machine.invalid".to_string(), 7000);
+    let (env, _expiration_checker) = Environment::new_with_peers(
+        0,
+        &[missing],
+        actor_builder!("Cell" => Cell),
+        EnvironmentOptions::new()
+            .wait_for_peers(0)
+            .placement(Placement::ConsistentHash),
+    );
+    let local = env.machines()[0].id;
+
+    for n in 0..16u8 {
+        let key = vec![n];
+        assert_eq!(env.machine_for_id(&key), local);
+        let cell = env.spawn_with_id("Cell", key.clone()).unwrap();
+        assert_eq!(cell.clone_id().location(), local);
+
+        let searcher = cell.clone_id();
+        let found = env.find_actor_ref(&key, searcher, false).unwrap();
+        assert_eq!(found.map(|found| found.clone_id()), Some(cell.clone_id()));
+    }
+    // Actors without an id are placed as usual
+    assert!(env.spawn("Cell").is_ok());
+    assert_eq!(env.list_local_actors(Some("Cell")).len(), 17);
+}
+
+/// Set for the process running the remote machine of the two-machine test.
+const REMOTE_MACHINE_VAR: &str = "ACTLIB_CONSISTENT_HASH_REMOTE";
+
+#[derive(Debug, Default)]
+struct Placer {
+    env: Option<Environment>,
+}
+
+impl Actor for Placer {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+/// Answer the machine the id hashes to, as seen from this machine.
+fn place(placer: &mut Placer, request: &Request<Vec<u8>>) {
+    let env = placer.env.as_ref().unwrap();
+    env.reply(request, env.machine_for_id(&request.message))
+        .unwrap();
+}
+
+impl_message_handler!(Placer: Request<Vec<u8>> => place);
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Placer" => Placer::default()),
+        EnvironmentOptions::new().placement(Placement::ConsistentHash),
+    )
+}
+
+/// Run the remote machine of the two-machine test until the test process closes its stdin.
+#[test]
+#[ignore]
+fn remote_machine() {
+    if std::env::var(REMOTE_MACHINE_VAR).is_err() {
+        return;
+    }
+    let _env = machine(
+        [127, 0, 0, 2],
+        42812,
+        SocketAddr::from(([127, 0, 0, 1], 42811)),
+    );
+    let _ = std::io::stdin().read(&mut [0]);
+}
+
+/// The machine every id hashes to, as seen by the *placer*.
+fn placements(placer: &ActorRef, keys: &[Vec<u8>]) -> Vec<MachineId> {
+    keys.iter()
+        .map(|key| {
+            placer
+                .ask::<_, MachineId>(key.clone())
+                .unwrap()
+                .wait(Duration::from_secs(5))
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn connected_machines_agree_on_the_placement() {
+    // in a process of its own, so it can leave by being killed
+    let mut remote = Command::new(std::env::current_exe().unwrap())
+        .args(["remote_machine", "--exact", "--ignored", "--quiet"])
+        .env(REMOTE_MACHINE_VAR, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let env = machine(
+        [127, 0, 0, 1],
+        42811,
+        SocketAddr::from(([127, 0, 0, 2], 42812)),
+    );
+    let machines = env.machines();
+    assert_eq!(machines.len(), 2);
+    let local_placer = env.spawn_local("Placer").unwrap();
+    let remote_placer = env.spawn_on("Placer", &machines[1]).unwrap();
+    let keys: Vec<Vec<u8>> = (0..64u8).map(|n| vec![n]).collect();
+
+    let before = placements(&local_placer, &keys);
+    assert_eq!(placements(&remote_placer, &keys), before);
+    assert!(before.contains(&machines[0].id));
+    assert!(before.contains(&machines[1].id));
+
+    // the remote machine leaves, only its ids move
+    remote.kill().unwrap();
+    let _ = remote.wait();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut after = placements(&local_placer, &keys);
+    while after.contains(&machines[1].id) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(50));
+        after = placements(&local_placer, &keys);
+    }
+    assert_eq!(after, vec![machines[0].id; keys.len()]);
+}
@@ -0,0 +1,55 @@
+//! Handlers reach their Environment and own ActorRef through the Context, without keeping them in the Actor.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static SEEN: Mutex<Option<Sender<(String, ActorId)>>> = Mutex::new(None);
+
+fn report(step: &str) {
+    let seen = SEEN.lock().unwrap();
+    seen.as_ref()
+        .unwrap()
+        .send((step.to_string(), Context::self_id()))
+        .unwrap();
+}
+
+#[derive(Debug)]
+struct Greeter;
+
+impl Actor for Greeter {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {
+        report("on_start");
+        Context::self_ref().send_message(1u32).unwrap();
+    }
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        report("on_stop");
+    }
+}
+
+fn greet(_: &mut Greeter, _: &u32) {
+    report("handler");
+    let env = Context::env();
+    env.clone().remove(Context::self_ref());
+}
+
+impl_message_handler!(Greeter: u32 => greet);
+
+#[test]
+fn context_is_available_to_the_actor_only() {
+    let (tx, rx) = channel();
+    *SEEN.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Greeter" => Greeter));
+    assert!(Context::try_env().is_none());
+    assert!(Context::try_self_ref().is_none());
+
+    let greeter = env.spawn("Greeter").unwrap();
+    for expected in &["on_start", "handler", "on_stop"] {
+        let (step, actor_id) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(&step, expected);
+        assert_eq!(actor_id, greeter.clone_id());
+    }
+}
@@ -0,0 +1,48 @@
+//! Counters and Gauges add up the shares of the local Actors.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Player {
+    env: Option<Environment>,
+}
+
+impl Actor for Player {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        local_env.counter("players_total").add(1);
+        local_env.gauge("players_online").add(1);
+        self.env = Some(local_env);
+    }
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        if let Some(env) = &self.env {
+            env.gauge("players_online").add(-1);
+        }
+    }
+}
+
+impl_message_handler!(Player: u32 => |_: &mut Player, _: &u32| {});
+
+#[test]
+fn counters_and_gauges_add_up() {
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Player" => Player { env: None }));
+    let players: Vec<ActorRef> = (0..10).map(|_| env.spawn("Player").unwrap()).collect();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(env.counter("players_total").value(), 10);
+    assert_eq!(env.gauge("players_online").value(), 10);
+
+    for player in players.into_iter().take(4) {
+        env.remove(player);
+    }
+    thread::sleep(Duration::from_millis(200));
+    let snapshot = env.counter_snapshot();
+    assert_eq!(snapshot.counter("players_total"), 10);
+    assert_eq!(snapshot.gauge("players_online"), 6);
+    assert_eq!(snapshot.counter("never_used"), 0);
+
+    env.gauge("players_online").set(0);
+    assert_eq!(env.gauge("players_online").value(), 0);
+}
@@ -0,0 +1,41 @@
+//! Delayed messages still pending when the Environment expires are cancelled, and counted in the drain report.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static RECEIVED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Receiver;
+
+impl Actor for Receiver {}
+
+fn receive(_: &mut Receiver, message: &u32) {
+    let received = RECEIVED.lock().unwrap();
+    received.as_ref().unwrap().send(*message).unwrap();
+}
+
+impl_message_handler!(Receiver: u32 => receive);
+
+#[test]
+fn pending_delayed_messages_are_cancelled_on_shutdown() {
+    let (tx, rx) = channel();
+    *RECEIVED.lock().unwrap() = Some(tx);
+    let (env, expiration_checker) =
+        Environment::new_local_only(actor_builder!("Receiver" => Receiver));
+    let receiver = env.spawn("Receiver").unwrap();
+
+    receiver.send_delayed_message(1u32, Duration::from_millis(10));
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 1);
+    receiver.send_delayed_message(2u32, Duration::from_millis(300));
+    receiver.send_delayed_message(3u32, Duration::from_millis(400));
+
+    env.shutdown(Duration::from_millis(500)).unwrap();
+    let result = expiration_checker.wait_until_expiration().unwrap();
+    assert_eq!(result.reports[0].delayed_messages_cancelled, 2);
+    // sending later neither delivers nor panics
+    receiver.send_delayed_message(4u32, Duration::from_millis(10));
+    assert!(rx.recv_timeout(Duration::from_millis(600)).is_err());
+}
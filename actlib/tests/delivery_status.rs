@@ -0,0 +1,38 @@
+//! Local sends tell an Actor that was asked to stop apart from an Actor that is gone.
+
+use actlib::api::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Busy;
+
+impl Actor for Busy {}
+
+impl_message_handler!(Busy: u64 => |_: &mut Busy, millis: &u64| thread::sleep(Duration::from_millis(*millis)));
+
+#[test]
+fn stopping_and_gone_actors_are_told_apart() {
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Busy" => Busy));
+    let busy = env.spawn("Busy").unwrap();
+    assert!(busy.send_message(300u64).is_ok());
+    thread::sleep(Duration::from_millis(50));
+
+    // the Stop request waits behind the message being handled
+    env.remove(busy.clone());
+    assert!(matches!(
+        busy.send_message(0u64),
+        Err(ActlibError::ActorStopping(_))
+    ));
+
+    // once the Actor is gone, the sends fail for good
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut sent = busy.send_message(0u64);
+    while matches!(sent, Err(ActlibError::ActorStopping(_))) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+        sent = busy.send_message(0u64);
+    }
+    assert!(matches!(sent, Err(ActlibError::InvalidActorRef(_))));
+    assert!(env.to_actor_ref(busy.clone_id()).is_err());
+}
@@ -0,0 +1,55 @@
+//! Expiring an Environment stops every Actor and reports per machine how the Actors were drained.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Sleeper;
+
+impl Actor for Sleeper {}
+
+impl_message_handler!(Sleeper: u64 => |_: &mut Sleeper, millis: &u64| thread::sleep(Duration::from_millis(*millis)));
+
+#[test]
+fn idle_actors_drain_cleanly() {
+    let (env, expiration_checker) =
+        Environment::new_local_only(actor_builder!("Sleeper" => Sleeper));
+    for _ in 0..3 {
+        env.spawn("Sleeper").unwrap();
+    }
+
+    env.set_expired().unwrap();
+    let result = expiration_checker.wait_until_expiration().unwrap();
+    assert_eq!(result.reason, ExpirationReason::LocalRequest);
+    assert_eq!(result.reports.len(), 1);
+    assert!(result.unreported.is_empty());
+
+    let report = &result.reports[0];
+    assert_eq!(report.machine, env.info().machine_id);
+    assert_eq!(report.actors_signaled, 3);
+    assert_eq!(report.actors_stopped, 3);
+    assert!(report.actors_not_stopped.is_empty());
+    assert_eq!(result.messages_discarded(), 0);
+    assert!(result.is_clean());
+}
+
+#[test]
+fn busy_actors_are_reported() {
+    let (env, expiration_checker) =
+        Environment::new_local_only(actor_builder!("Sleeper" => Sleeper));
+    let sleeper = env.spawn("Sleeper").unwrap();
+    // the first Message outlives the drain deadline, the others are still queued
+    for _ in 0..3 {
+        sleeper.send_message(2000u64).unwrap();
+    }
+    thread::sleep(Duration::from_millis(100));
+
+    env.set_expired().unwrap();
+    let result = expiration_checker.wait_until_expiration().unwrap();
+    let report = &result.reports[0];
+    assert_eq!(report.actors_signaled, 1);
+    assert_eq!(report.actors_stopped, 0);
+    assert_eq!(result.actors_not_stopped(), vec![sleeper.clone_id()]);
+    assert!(!result.is_clean());
+}
@@ -0,0 +1,83 @@
+//! Every Message the local machine drops is reported with its cause, and counted in the metrics.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Slow;
+
+impl Actor for Slow {}
+
+/// Pause for the given milliseconds, failing on ```0```.
+fn handle(_: &mut Slow, pause: &u64) {
+    if *pause == 0 {
+        thread::sleep(Duration::from_millis(100));
+        panic!("failing on purpose");
+    }
+    thread::sleep(Duration::from_millis(*pause));
+}
+
+impl_message_handler!(Slow: u64 => handle);
+
+fn options() -> EnvironmentOptions {
+    EnvironmentOptions::new().mailbox_quota(
+        "Bounded",
+        MailboxQuota {
+            max_bytes: 16,
+            policy: QuotaPolicy::DropOldest,
+        },
+    )
+}
+
+fn start() -> (Environment, EnvironmentExpirationChecker) {
+    Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Slow" => Slow, "Bounded" => Slow),
+        options(),
+    )
+}
+
+fn next_drop(dropped: &std::sync::mpsc::Receiver<MessageDropped>) -> MessageDropped {
+    dropped.recv_timeout(Duration::from_secs(5)).unwrap()
+}
+
+#[test]
+fn drops_are_reported_with_their_cause() {
+    let (env, _expiration_checker) = start();
+    let dropped = env.watch_dropped_messages();
+    let slow = env.spawn("Slow").unwrap();
+
+    slow.send_message("not handled".to_string()).unwrap();
+    let unhandled = next_drop(&dropped);
+    assert_eq!(unhandled.cause, DropCause::UnhandledType);
+    assert_eq!(unhandled.target, Some(slow.clone_id()));
+
+    // the failing Actor discards the messages queued meanwhile
+    slow.send_message(0u64).unwrap();
+    slow.send_message(1u64).unwrap();
+    slow.send_message(1u64).unwrap();
+    for _ in 0..2 {
+        let removed = next_drop(&dropped);
+        assert_eq!(removed.cause, DropCause::ActorRemoved);
+        assert_eq!(removed.message_type.as_deref(), Some("u64"));
+    }
+
+    let bounded = env.spawn("Bounded").unwrap();
+    for _ in 0..4 {
+        bounded.send_message(100u64).unwrap();
+    }
+    let full = next_drop(&dropped);
+    assert_eq!(full.cause, DropCause::MailboxFull);
+    assert_eq!(full.target, Some(bounded.clone_id()));
+    assert_eq!(full.message_type.as_deref(), Some("u64"));
+
+    let metrics = env.metrics();
+    assert_eq!(metrics.messages_dropped[&DropCause::UnhandledType], 1);
+    assert_eq!(metrics.messages_dropped[&DropCause::ActorRemoved], 2);
+    assert_eq!(metrics.messages_dropped[&DropCause::PeerDead], 0);
+    assert!(metrics
+        .to_prometheus()
+        .contains("cause=\"unhandled_type\"} 1\n"));
+}
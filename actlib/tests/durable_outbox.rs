@@ -0,0 +1,66 @@
+//! Messages to remote Actors left unacknowledged in the durable outbox are sent again by the next Environment using it.
+
+mod common;
+
+use actlib::api::*;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Sink;
+
+impl Actor for Sink {}
+
+impl_message_handler!(Sink: u32 => |_: &mut Sink, _: &u32| {});
+
+fn start(log: &std::path::Path, max_retries: u32) -> (Environment, EnvironmentExpirationChecker) {
+    Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Sink" => Sink),
+        EnvironmentOptions::new()
+            .durable_outbox(log)
+            .reliable_delivery(
+                ReliableDelivery::new()
+                    .ack_timeout(Duration::from_millis(20))
+                    .max_retries(max_retries),
+            ),
+    )
+}
+
+#[test]
+fn unacknowledged_messages_survive_a_restart() {
+    let log = std::env::temp_dir().join(format!("actlib-durable-outbox-{}", std::process::id()));
+    let _ = std::fs::remove_file(&log);
+
+    let (env, _expiration_checker) = start(&log, u32::MAX);
+    let local = env.spawn("Sink").unwrap();
+    let unreachable = env
+        .to_actor_ref(common::on_unknown_machine(local.clone_id()))
+        .unwrap();
+    unreachable.send_message(7u32).unwrap();
+    // written to the log by the network thread
+    thread::sleep(Duration::from_millis(200));
+    assert!(std::fs::metadata(&log).unwrap().len() > 0);
+
+    // a record torn by a crash is ignored
+    OpenOptions::new()
+        .append(true)
+        .open(&log)
+        .unwrap()
+        .write_all(&[200, 0])
+        .unwrap();
+
+    // the first Environment keeps retrying, the second one takes over the log
+    let (restarted, _restarted_checker) = start(&log, 1);
+    let failures = restarted.watch_delivery_failures();
+    let failure = failures.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(failure.actor, unreachable.clone_id());
+    assert_eq!(failure.message, bincode::serialize(&7u32).unwrap());
+    assert_eq!(failure.rejection, None);
+    assert!(failures.recv_timeout(Duration::from_millis(300)).is_err());
+
+    let _ = std::fs::remove_file(&log);
+}
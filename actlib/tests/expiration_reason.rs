@@ -0,0 +1,36 @@
+//! A main loop polls for the expiration between its own work, and learns why the Environment expired.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+#[test]
+fn try_check_reports_the_local_request() {
+    let (env, expiration_checker) = Environment::new_local_only(actor_builder!("Idle" => Idle));
+    env.spawn("Idle").unwrap();
+    assert!(expiration_checker.try_check().is_none());
+
+    let expiring_env = env.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        expiring_env.set_expired().unwrap();
+    });
+    let mut rounds = 0;
+    let result = loop {
+        if let Some(result) = expiration_checker.try_check() {
+            break result;
+        }
+        rounds += 1;
+        thread::sleep(Duration::from_millis(10));
+    };
+    assert!(rounds > 0);
+    assert_eq!(result.reason, ExpirationReason::LocalRequest);
+    assert_eq!(result.actors_stopped(), 1);
+}
@@ -0,0 +1,80 @@
+//! External listeners are managed by the Environment: bind errors are returned, the socket survives a restart of its keyed handler and is handed over explicitly.
+
+use actlib::api::*;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Greeter(&'static str);
+
+impl Actor for Greeter {}
+
+fn greet(greeter: &mut Greeter, connection: &ExternalConnection) {
+    if let Some(mut stream) = connection.take_stream() {
+        stream.write_all(greeter.0.as_bytes()).unwrap();
+    }
+}
+
+impl_message_handler!(Greeter: ExternalConnection => greet);
+
+fn builder() -> ActorBuilder {
+    actor_builder!("Hello" => Greeter("hello"), "Bye" => Greeter("bye"))
+}
+
+/// Connect to the local *port* and read the greeting.
+fn greeting(port: u16) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut greeting = String::new();
+    stream.read_to_string(&mut greeting).unwrap();
+    greeting
+}
+
+#[test]
+fn connections_reach_the_current_handler() {
+    let (mut env, _expiration_checker) = Environment::new_local_only(builder());
+    let hello = env.spawn_with_id("Hello", b"greeter".to_vec()).unwrap();
+    let port = env.register_external_listener(0, &hello).unwrap();
+    assert_ne!(port, 0);
+    assert_eq!(greeting(port), "hello");
+
+    // the port is taken
+    let (other_env, _other_checker) = Environment::new_local_only(builder());
+    let other = other_env.spawn("Hello").unwrap();
+    assert!(matches!(
+        other_env.register_external_listener(port, &other),
+        Err(ActlibError::NetworkError(_))
+    ));
+
+    // the client waits while the keyed handler is respawned
+    env.remove(hello.clone());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while env.to_actor_ref(hello.clone_id()).is_ok() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let client = thread::spawn(move || greeting(port));
+    thread::sleep(Duration::from_millis(100));
+    env.spawn_with_id("Hello", b"greeter".to_vec()).unwrap();
+    assert_eq!(client.join().unwrap(), "hello");
+
+    let bye = env.spawn("Bye").unwrap();
+    env.hand_over_external_listener(port, &bye).unwrap();
+    assert_eq!(greeting(port), "bye");
+
+    // the listener closes with its automatically created handler
+    env.remove(bye);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while TcpStream::connect(("127.0.0.1", port)).is_ok() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+    assert!(!env.unregister_external_listener(port));
+    assert!(matches!(
+        env.hand_over_external_listener(port, &other),
+        Err(ActlibError::InvalidState(_))
+    ));
+}
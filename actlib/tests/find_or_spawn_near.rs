@@ -0,0 +1,56 @@
+//! find_or_spawn_near places a new keyed Actor on the machine that looked for it most often, and finds it afterwards.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::thread;
+
+#[derive(Debug)]
+struct Account;
+
+impl Actor for Account {}
+
+impl_message_handler!(Account: u32 => |_: &mut Account, _: &u32| {});
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Account" => Account),
+        EnvironmentOptions::new(),
+    )
+}
+
+#[test]
+fn new_actors_are_placed_near_their_correspondents() {
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42721));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42722));
+    let second = thread::spawn(move || machine([127, 0, 0, 2], 42722, first_addr));
+    let first = machine([127, 0, 0, 1], 42721, second_addr);
+    let second = second.join().unwrap();
+    let second_machine = second.info().machine_id;
+
+    // the second machine keeps asking for the account before it exists
+    let searcher = second.spawn_local("Account").unwrap().clone_id();
+    for _ in 0..3 {
+        assert!(second
+            .find_actor_ref(&b"account-7".to_vec(), searcher.clone(), false)
+            .unwrap()
+            .is_none());
+    }
+
+    let account = first
+        .find_or_spawn_near("Account", b"account-7".to_vec(), None)
+        .unwrap();
+    assert_eq!(account.clone_id().location(), second_machine);
+
+    let found = second
+        .find_or_spawn_near("Account", b"account-7".to_vec(), None)
+        .unwrap();
+    assert_eq!(found.clone_id(), account.clone_id());
+}
@@ -0,0 +1,61 @@
+//! Machines registering other Actor or message types refuse to connect instead of misinterpreting each other's messages.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+use actlib::api::*;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::thread;
+
+#[derive(Debug)]
+struct Field;
+
+impl Actor for Field {}
+
+impl_message_handler!(Field: u32 => |_: &mut Field, _: &u32| {});
+
+/// The same Actor, handling one more message type.
+#[derive(Debug)]
+struct NewerField;
+
+impl Actor for NewerField {}
+
+impl_message_handler!(NewerField: u32 => |_: &mut NewerField, _: &u32| {}, u8 => |_: &mut NewerField, _: &u8| {});
+
+#[test]
+fn fingerprints_cover_actor_and_message_types() {
+    let field = actor_builder!("Field" => Field).fingerprint();
+    assert_eq!(actor_builder!("Field" => Field).fingerprint(), field);
+    assert_ne!(actor_builder!("Field" => NewerField).fingerprint(), field);
+    assert_ne!(actor_builder!("Other" => Field).fingerprint(), field);
+
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!("Field" => Field));
+    assert_eq!(env.info().fingerprint, field);
+}
+
+#[test]
+fn mismatched_machines_refuse_to_start() {
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42771));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42772));
+    let second = thread::spawn(move || {
+        Environment::new_with_options(
+            42772,
+            &[first_addr],
+            actor_builder!("Field" => NewerField),
+            EnvironmentOptions::new().local_address(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))),
+        );
+    });
+    let first = thread::spawn(move || {
+        Environment::new_with_options(
+            42771,
+            &[second_addr],
+            actor_builder!("Field" => Field),
+            EnvironmentOptions::new().local_address(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
+        );
+    });
+
+    for machine in [first, second] {
+        let panic = machine.join().unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("IncompatibleBuild"), "{}", message);
+    }
+}
@@ -0,0 +1,67 @@
+//! A graceful shutdown lets every Actor work off its mailbox before on_stop runs, within the timeout.
+
+use actlib::api::*;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static HANDLED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct Worker;
+
+impl Actor for Worker {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        HANDLED.lock().unwrap().push("stop");
+    }
+}
+
+fn work(_: &mut Worker, millis: &u64) {
+    thread::sleep(Duration::from_millis(*millis));
+    HANDLED.lock().unwrap().push("work");
+}
+
+impl_message_handler!(Worker: u64 => work);
+
+#[derive(Debug)]
+struct Sleeper;
+
+impl Actor for Sleeper {}
+
+impl_message_handler!(Sleeper: u64 => |_: &mut Sleeper, millis: &u64| thread::sleep(Duration::from_millis(*millis)));
+
+#[test]
+fn mailboxes_are_drained_before_on_stop() {
+    let (env, expiration_checker) = Environment::new_local_only(actor_builder!("Worker" => Worker));
+    let worker = env.spawn("Worker").unwrap();
+    for _ in 0..5 {
+        worker.send_message(20u64).unwrap();
+    }
+
+    let result = env.shutdown(Duration::from_secs(5)).unwrap();
+    assert!(result.actors_not_stopped().is_empty());
+    assert_eq!(result.messages_discarded(), 0);
+    assert!(env.spawn("Worker").is_err());
+    expiration_checker.wait_until_expiration().unwrap();
+    assert_eq!(
+        *HANDLED.lock().unwrap(),
+        ["work", "work", "work", "work", "work", "stop"]
+    );
+}
+
+#[test]
+fn actors_missing_the_timeout_are_reported() {
+    let (env, expiration_checker) =
+        Environment::new_local_only(actor_builder!("Sleeper" => Sleeper));
+    let sleeper = env.spawn("Sleeper").unwrap();
+    for _ in 0..5 {
+        sleeper.send_message(200u64).unwrap();
+    }
+
+    let result = env.shutdown(Duration::from_millis(300)).unwrap();
+    assert_eq!(result.actors_not_stopped(), vec![sleeper.clone_id()]);
+    assert!(!result.is_clean());
+    expiration_checker.wait_until_expiration().unwrap();
+}
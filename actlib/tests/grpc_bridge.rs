@@ -0,0 +1,107 @@
+//! gRPC clients reach registered service Actors through the bridge, with a single reply for Ask and a stream of replies for Subscribe.
+
+use actlib::api::*;
+use actlib::grpc::{ActorReply, ActorRequest, GrpcBridge, GrpcCall};
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::Channel;
+
+#[derive(Debug)]
+struct Echo;
+
+impl Actor for Echo {}
+
+/// Answer with the reversed payload, as many times as the method asks for on a Subscribe.
+fn handle_call(_: &mut Echo, call: &GrpcCall) {
+    let mut payload = call.payload.clone();
+    payload.reverse();
+    if call.streaming {
+        let times: usize = call.method.parse().unwrap();
+        for _ in 0..times {
+            call.reply(payload.clone());
+        }
+        call.finish();
+    } else {
+        call.reply(payload);
+    }
+}
+
+impl_message_handler!(Echo: GrpcCall => handle_call);
+
+async fn connect(port: u16) -> tonic::client::Grpc<Channel> {
+    let endpoint = format!("http://127.0.0.1:{}", port);
+    for _ in 0..50 {
+        if let Ok(channel) = Channel::from_shared(endpoint.clone())
+            .unwrap()
+            .connect()
+            .await
+        {
+            return tonic::client::Grpc::new(channel);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("the bridge did not come up");
+}
+
+fn request(service: &str, method: &str) -> ActorRequest {
+    ActorRequest {
+        service: service.to_string(),
+        method: method.to_string(),
+        payload: vec![1, 2, 3],
+    }
+}
+
+#[test]
+fn clients_ask_and_subscribe_service_actors() {
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!("Echo" => Echo));
+    let echo = env.spawn("Echo").unwrap();
+    GrpcBridge::new()
+        .register("echo", echo)
+        .serve("127.0.0.1:50187".parse().unwrap());
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        let mut client = connect(50187).await;
+
+        client.ready().await.unwrap();
+        let reply: ActorReply = client
+            .unary(
+                tonic::Request::new(request("echo", "reverse")),
+                PathAndQuery::from_static("/actlib.ActorBridge/Ask"),
+                tonic::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(reply.payload, vec![3, 2, 1]);
+
+        client.ready().await.unwrap();
+        let mut replies = client
+            .server_streaming(
+                tonic::Request::new(request("echo", "3")),
+                PathAndQuery::from_static("/actlib.ActorBridge/Subscribe"),
+                tonic::codec::ProstCodec::<ActorRequest, ActorReply>::default(),
+            )
+            .await
+            .unwrap()
+            .into_inner();
+        let mut received = 0;
+        while let Some(reply) = replies.next().await {
+            assert_eq!(reply.unwrap().payload, vec![3, 2, 1]);
+            received += 1;
+        }
+        assert_eq!(received, 3);
+
+        client.ready().await.unwrap();
+        let unknown = client
+            .unary::<_, ActorReply, _>(
+                tonic::Request::new(request("missing", "reverse")),
+                PathAndQuery::from_static("/actlib.ActorBridge/Ask"),
+                tonic::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(unknown.code(), tonic::Code::NotFound);
+    });
+}
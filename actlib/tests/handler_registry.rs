@@ -0,0 +1,84 @@
+//! Actors can register their handlers at runtime with a HandlerRegistry, composed from several modules.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static WITH_RESET: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug, Default)]
+struct Tally {
+    total: u64,
+}
+
+impl Actor for Tally {}
+
+impl QueryableActor for Tally {}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Add(u64);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Reset;
+
+mod doubling {
+    use super::*;
+
+    /// Handlers of another module, taking precedence for Add.
+    pub fn handlers() -> HandlerRegistry<Tally> {
+        HandlerRegistry::<Tally>::new().on::<Add>(|tally, Add(n), _ctx| tally.total += 2 * n)
+    }
+}
+
+impl RegisteredHandlers for Tally {
+    fn handlers() -> HandlerRegistry<Self> {
+        let registry = HandlerRegistry::<Self>::new()
+            .on::<Add>(|tally, Add(n), _ctx| tally.total += n)
+            .with(doubling::handlers());
+        if WITH_RESET.load(Ordering::SeqCst) {
+            return registry.on::<Reset>(|tally, _, _ctx| tally.total = 0);
+        }
+        registry
+    }
+}
+
+fn total(tally: &ActorRef) -> u64 {
+    tally
+        .query(|tally: &Tally| tally.total, Duration::from_secs(1))
+        .unwrap()
+}
+
+/// Wait until the Tally reached *expected*.
+fn wait_for_total(tally: &ActorRef, expected: u64) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while total(tally) != expected && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(total(tally), expected);
+}
+
+#[test]
+fn registered_handlers_handle_their_message_types() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Tally" => Tally::default()));
+    let tally = env.spawn("Tally").unwrap();
+
+    tally.send_message(Add(3)).unwrap();
+    wait_for_total(&tally, 6);
+    // deserialized like a Message from a remote machine
+    tally
+        .send_serialized(bincode::serialize(&Reset).unwrap())
+        .unwrap();
+    wait_for_total(&tally, 0);
+    // not registered, ignored
+    tally.send_message("unknown".to_string()).unwrap();
+    tally.send_message(Add(1)).unwrap();
+    wait_for_total(&tally, 2);
+
+    let names = HandlerRegistry::<Tally>::new()
+        .on::<Add>(|_, _, _| {})
+        .message_type_names();
+    assert_eq!(names, vec![std::any::type_name::<Add>()]);
+}
@@ -0,0 +1,109 @@
+//! Handler sets are shared by several Actor types, an Actor's own handlers take precedence over the ones of its sets.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn record(event: String) {
+    EVENTS.lock().unwrap().push(event);
+}
+
+/// Wait until *count* events were recorded, returning them.
+fn events(count: usize) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while EVENTS.lock().unwrap().len() < count && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+    }
+    EVENTS.lock().unwrap().clone()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Ping(u32);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Rename(String);
+
+trait Named {
+    fn rename(&mut self, name: &str);
+}
+
+fn pong<A: Actor>(_: &mut A, ping: &Ping) {
+    record(format!("{} pong {}", std::any::type_name::<A>(), ping.0));
+}
+
+fn rename<A: Named>(actor: &mut A, rename: &Rename) {
+    actor.rename(&rename.0);
+}
+
+handler_set!(Lifecycle: Ping => pong);
+handler_set!(Renaming<A: Named>: Rename => rename);
+
+#[derive(Debug)]
+struct Worker {
+    name: String,
+}
+
+impl Actor for Worker {}
+
+impl Named for Worker {
+    fn rename(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+}
+
+fn work(worker: &mut Worker, task: &String) {
+    record(format!("{} works on {}", worker.name, task));
+}
+
+impl_message_handler!(Worker: String => work; with Lifecycle, Renaming);
+
+#[derive(Debug)]
+struct Monitor;
+
+impl Actor for Monitor {}
+
+fn own_pong(_: &mut Monitor, ping: &Ping) {
+    record(format!("Monitor answers {} itself", ping.0));
+}
+
+impl_message_handler!(Monitor: Ping => own_pong; with Lifecycle);
+
+#[test]
+fn handler_sets_are_mixed_into_several_actor_types() {
+    assert_eq!(
+        <Worker as MessageHandler>::message_type_names(),
+        &["String", "Ping", "Rename"]
+    );
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Worker" => Worker { name: "worker".to_string() },
+        "Monitor" => Monitor
+    ));
+    let worker = env.spawn("Worker").unwrap();
+    let monitor = env.spawn("Monitor").unwrap();
+
+    worker.send_message(Ping(1)).unwrap();
+    worker.send_message(Rename("renamed".to_string())).unwrap();
+    worker.send_message("a task".to_string()).unwrap();
+    // deserialized by the handler set, as the own handler's type does not fit
+    worker
+        .send_serialized(bincode::serialize(&Ping(2)).unwrap())
+        .unwrap();
+    let worker_events = events(3);
+    monitor.send_message(Ping(3)).unwrap();
+
+    let all = events(4);
+    assert_eq!(worker_events.len(), 3);
+    assert_eq!(
+        all,
+        vec![
+            format!("{} pong 1", std::any::type_name::<Worker>()),
+            "renamed works on a task".to_string(),
+            format!("{} pong 2", std::any::type_name::<Worker>()),
+            "Monitor answers 3 itself".to_string(),
+        ]
+    );
+}
@@ -0,0 +1,65 @@
+//! With handler statistics enabled, the handled messages and handler durations are counted per Actor type and message type.
+
+use actlib::api::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Field;
+
+impl Actor for Field {}
+
+/// Expensive, takes 20 milliseconds.
+fn force_leave(_: &mut Field, _: &u32) {
+    thread::sleep(Duration::from_millis(20));
+}
+
+/// Cheap.
+fn update(_: &mut Field, _: &u8) {}
+
+impl_message_handler!(Field: u32 => force_leave, u8 => update);
+
+#[test]
+fn the_expensive_message_type_stands_out() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Field" => Field),
+        EnvironmentOptions::new().handler_stats(),
+    );
+    let field = env.spawn("Field").unwrap();
+    for _ in 0..3 {
+        field.send_message(1u32).unwrap();
+    }
+    for _ in 0..10 {
+        field.send_message(1u8).unwrap();
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let handled = || {
+        env.handler_stats()
+            .iter()
+            .map(|stats| stats.handled)
+            .sum::<u64>()
+    };
+    while handled() < 13 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let stats = env.handler_stats();
+    let of = |message_type: &str| {
+        stats
+            .iter()
+            .find(|stats| stats.type_id == "Field" && stats.message_type == message_type)
+            .unwrap()
+    };
+    assert_eq!(of("u32").handled, 3);
+    assert_eq!(of("u32").histogram.iter().sum::<u64>(), 3);
+    assert!(of("u32").max_time >= Duration::from_millis(20));
+    assert!(of("u32").total_time >= Duration::from_millis(60));
+    assert_eq!(of("u8").handled, 10);
+
+    let introspection = env.introspect();
+    let shares = introspection.handler_time_shares("Field");
+    assert_eq!(shares[0].0, "u32");
+    assert!(shares[0].1 > 0.9);
+}
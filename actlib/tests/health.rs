@@ -0,0 +1,38 @@
+//! The health report of a machine turns degraded once one of its Actors panicked.
+
+use actlib::api::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Fragile;
+
+impl Actor for Fragile {}
+
+impl_message_handler!(Fragile: u32 => |_: &mut Fragile, _: &u32| panic!("broken"));
+
+#[test]
+fn panics_degrade_the_health() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Fragile" => Fragile));
+    let failures = env.watch_actor_failures();
+    let health = env.health();
+    assert_eq!(health.status, HealthStatus::Ready);
+    assert!(health.is_ready() && health.is_alive());
+    assert_eq!(health.machine_id, env.info().machine_id);
+    assert!(health.poisoned_locks.is_empty());
+    assert!(health.dead_peers.is_empty());
+
+    env.spawn("Fragile").unwrap().send_message(1u32).unwrap();
+    failures.recv_timeout(Duration::from_secs(5)).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while env.health().actors_failed == 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let health = env.health();
+    assert_eq!(health.status, HealthStatus::Degraded);
+    assert!(!health.is_ready() && health.is_alive());
+    assert_eq!(health.actors_failed, 1);
+    assert_eq!(health.recent_failures, 1);
+    assert_eq!(health.queued_messages, 0);
+}
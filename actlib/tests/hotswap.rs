@@ -0,0 +1,124 @@
+//! Actors spawned after a new plugin library was loaded use the new library, Actors spawned earlier keep theirs.
+//!
+//! The plugin libraries are compiled with rustc when the test runs.
+
+use actlib::api::*;
+use actlib::hotswap::{load_plugin, PluginActor};
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use std::{env, fs};
+
+/// Replies to the ActorId it receives with ```VERSION * 100``` plus the number of messages it handled so far.
+const PLUGIN: &str = r#"
+use std::ffi::c_void;
+
+#[repr(C)]
+pub struct ActlibHost {
+    context: *mut c_void,
+    own_id: *const u8,
+    own_id_len: usize,
+    send: extern "C" fn(*mut c_void, *const u8, usize, *const u8, usize) -> i32,
+}
+
+const ABI_VERSION: u32 = {abi_version};
+const VERSION: u32 = {version};
+
+#[no_mangle]
+pub extern "C" fn actlib_plugin_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+#[no_mangle]
+pub extern "C" fn actlib_plugin_create(_type_id: *const u8, _type_id_len: usize) -> *mut c_void {
+    Box::into_raw(Box::new(0u32)) as *mut c_void
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn actlib_plugin_handle(state: *mut c_void, message: *const u8, message_len: usize, host: *const ActlibHost) {
+    let handled = &mut *(state as *mut u32);
+    *handled += 1;
+    let reply = (VERSION * 100 + *handled).to_le_bytes();
+    let host = &*host;
+    (host.send)(host.context, message, message_len, reply.as_ptr(), reply.len());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn actlib_plugin_destroy(state: *mut c_void) {
+    drop(Box::from_raw(state as *mut u32));
+}
+"#;
+
+/// Compile the plugin as a library of the given *version* implementing the given *abi_version*.
+fn compile_plugin(version: u32, abi_version: u32) -> PathBuf {
+    let dir = env::temp_dir().join(format!("actlib-hotswap-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let source = dir.join(format!("plugin_v{}.rs", version));
+    fs::write(
+        &source,
+        PLUGIN
+            .replace("{abi_version}", &abi_version.to_string())
+            .replace("{version}", &version.to_string()),
+    )
+    .unwrap();
+    let library = dir.join(format!("{}plugin_v{}{}", DLL_PREFIX, version, DLL_SUFFIX));
+    let status = Command::new(env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+        .args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+        .arg(&library)
+        .arg(&source)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    library
+}
+
+static REPLIES: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Recorder;
+
+impl Actor for Recorder {}
+
+fn record(_: &mut Recorder, reply: &u32) {
+    let replies = REPLIES.lock().unwrap();
+    replies.as_ref().unwrap().send(*reply).unwrap();
+}
+
+impl_message_handler!(Recorder: u32 => record);
+
+#[test]
+fn new_actors_use_the_latest_plugin() {
+    let (tx, rx) = channel();
+    *REPLIES.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Field" => PluginActor::new("Field"),
+        "Recorder" => Recorder
+    ));
+    let recorder = env.spawn("Recorder").unwrap();
+    let reply_to = bincode::serialize(&recorder.clone_id()).unwrap();
+    let ask = |field: &ActorRef| {
+        field.send_serialized(reply_to.clone()).unwrap();
+        rx.recv_timeout(Duration::from_secs(5)).unwrap()
+    };
+
+    assert_eq!(load_plugin("Field", compile_plugin(1, 1)).unwrap(), 1);
+    let old_field = env.spawn("Field").unwrap();
+    assert_eq!(ask(&old_field), 101);
+    assert_eq!(ask(&old_field), 102);
+
+    assert_eq!(load_plugin("Field", compile_plugin(2, 1)).unwrap(), 2);
+    let new_field = env.spawn("Field").unwrap();
+    assert_eq!(ask(&new_field), 201);
+    // the Actor spawned earlier keeps its library and its state
+    assert_eq!(ask(&old_field), 103);
+
+    // a library implementing another ABI is rejected, the previous one stays in use
+    assert!(matches!(
+        load_plugin("Field", compile_plugin(3, 2)),
+        Err(ActlibError::InvalidState(_))
+    ));
+    assert_eq!(ask(&env.spawn("Field").unwrap()), 201);
+}
@@ -0,0 +1,73 @@
+//! Two machines that spawned an Actor with the same id while they were apart detect the conflict once they are connected,
+//! and the younger Actor gives way.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static STOPPED: Mutex<Option<Sender<StopReason>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Keeper;
+
+impl Actor for Keeper {
+    fn on_stop_with_reason(&mut self, reason: StopReason) {
+        let stopped = STOPPED.lock().unwrap();
+        stopped.as_ref().unwrap().send(reason).unwrap();
+    }
+}
+
+impl_message_handler!(Keeper: u32 => |_: &mut Keeper, _: &u32| {});
+
+/// Start a machine on *ip* and *port*, without waiting for its peer to connect.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Keeper" => Keeper),
+        EnvironmentOptions::new()
+            .wait_for_peers(0)
+            .id_conflict_detection(IdConflictDetection {
+                interval: Duration::from_millis(100),
+                policy: ConflictPolicy::KeepOldest,
+            }),
+    )
+}
+
+#[test]
+fn the_younger_actor_gives_way() {
+    let (tx, rx) = channel();
+    *STOPPED.lock().unwrap() = Some(tx);
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42711));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42712));
+
+    let first = machine([127, 0, 0, 1], 42711, second_addr);
+    let older = first
+        .spawn_local_with_id("Keeper", b"unique".to_vec())
+        .unwrap();
+    thread::sleep(Duration::from_millis(50));
+    let second = machine([127, 0, 0, 2], 42712, first_addr);
+    let conflicts = second.watch_id_conflicts();
+    let younger = second
+        .spawn_local_with_id("Keeper", b"unique".to_vec())
+        .unwrap();
+
+    let conflict = conflicts.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(conflict.id, b"unique".to_vec());
+    assert_eq!(conflict.type_id, "Keeper");
+    assert_eq!(conflict.older, older.clone_id());
+    assert_eq!(conflict.younger, younger.clone_id());
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        StopReason::IdConflict
+    );
+    assert!(older.send_message(1).is_ok());
+}
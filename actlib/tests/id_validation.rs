@@ -0,0 +1,38 @@
+//! User specified ids are checked by the validator of their Actor type before the Actor is spawned.
+
+use actlib::api::*;
+
+#[derive(Debug)]
+struct Field;
+
+impl Actor for Field {}
+
+impl_message_handler!(Field: u32 => |_: &mut Field, _: &u32| {});
+
+/// Field ids are two coordinates of 4 bytes each.
+fn is_position(id: &[u8]) -> Result<(), String> {
+    if id.len() == 8 {
+        Ok(())
+    } else {
+        Err(format!("{} bytes are no position", id.len()))
+    }
+}
+
+#[test]
+fn invalid_ids_fail_the_spawn() {
+    let (env, _expiration_checker) = Environment::new_local_only(
+        actor_builder!("Field" => Field, "Other" => Field).with_id_validator("Field", is_position),
+    );
+
+    let field = env.spawn_with_id("Field", vec![0; 8]).unwrap();
+    assert!(env.to_actor_ref(field.clone_id()).is_ok());
+    match env.spawn_with_id("Field", vec![0; 3]) {
+        Err(ActlibError::InvalidId(reason)) => assert!(reason.contains("3 bytes are no position")),
+        other => panic!("spawned with an invalid id: {:?}", other),
+    }
+    assert_eq!(env.list_local_actors(Some("Field")), vec![field.clone_id()]);
+
+    // automatic ids and other Actor types are not checked
+    assert!(env.spawn("Field").is_ok());
+    assert!(env.spawn_with_id("Other", vec![0; 3]).is_ok());
+}
@@ -0,0 +1,119 @@
+//! Spawning an id that is alive already must not start a second Actor, like a duplicated spawn request from a remote machine.
+//!
+//! Remote spawn requests take the same path as spawn_local_with_id on the receiving machine.
+//! Both machines of the remote test run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+static STARTED: AtomicUsize = AtomicUsize::new(0);
+static REMOTE_STARTED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct CountingActor;
+
+impl Actor for CountingActor {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {
+        STARTED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl_message_handler!(CountingActor: u32 => |_: &mut CountingActor, _: &u32| {});
+
+#[derive(Debug)]
+struct OtherActor;
+
+impl Actor for OtherActor {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(OtherActor: u32 => |_: &mut OtherActor, _: &u32| {});
+
+#[derive(Debug)]
+struct RemoteActor;
+
+impl Actor for RemoteActor {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {
+        REMOTE_STARTED.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl_message_handler!(RemoteActor: u32 => |_: &mut RemoteActor, _: &u32| {});
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*, placing Actors with an id by their hash.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("RemoteActor" => RemoteActor),
+        EnvironmentOptions::new().placement(Placement::ConsistentHash),
+    )
+}
+
+#[test]
+fn duplicated_spawn_returns_the_existing_actor() {
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "CountingActor" => CountingActor,
+        "OtherActor" => OtherActor
+    ));
+    let first = env
+        .spawn_local_with_id("CountingActor", vec![1, 2, 3])
+        .unwrap();
+    let second = env
+        .spawn_local_with_id("CountingActor", vec![1, 2, 3])
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(first.clone_id(), second.clone_id());
+    assert_eq!(STARTED.load(Ordering::SeqCst), 1);
+    assert_eq!(env.introspect().actors.len(), 1);
+    second.send_message(7u32).unwrap();
+
+    match env.spawn_local_with_id("OtherActor", vec![1, 2, 3]) {
+        Err(ActlibError::InvalidId(_)) => {}
+        other => panic!("Expected InvalidId, got {:?}", other),
+    }
+}
+
+#[test]
+fn duplicated_remote_spawn_requests_start_a_single_actor() {
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42801));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42802));
+    let second = thread::spawn(move || machine([127, 0, 0, 2], 42802, first_addr));
+    let first = machine([127, 0, 0, 1], 42801, second_addr);
+    let second = second.join().unwrap();
+    let second_machine = second.info().machine_id;
+    let key = (0..=u8::MAX)
+        .map(|n| vec![n])
+        .find(|key| first.machine_for_id(key) == second_machine)
+        .unwrap();
+
+    // the same spawn request reaches the second machine three times
+    let spawned = first.spawn_with_id("RemoteActor", key.clone()).unwrap();
+    let replayed = first.spawn_with_id("RemoteActor", key.clone()).unwrap();
+    let local_replay = second
+        .spawn_local_with_id("RemoteActor", key.clone())
+        .unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    // every acknowledgement names the Actor of the first request
+    assert_eq!(spawned.clone_id().location(), second_machine);
+    assert_eq!(replayed.clone_id(), spawned.clone_id());
+    assert_eq!(local_replay.clone_id(), spawned.clone_id());
+    assert_eq!(
+        replayed.clone_id().incarnation(),
+        spawned.clone_id().incarnation()
+    );
+    assert_eq!(REMOTE_STARTED.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        second.list_local_actors(Some("RemoteActor")),
+        vec![spawned.clone_id()]
+    );
+    assert!(first.list_local_actors(Some("RemoteActor")).is_empty());
+}
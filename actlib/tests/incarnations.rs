@@ -0,0 +1,85 @@
+//! A respawned keyed Actor is a new incarnation of its id, ActorIds of the earlier incarnation are handled by the IncarnationPolicy.
+
+use actlib::api::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Keyed;
+
+impl Actor for Keyed {}
+
+impl_message_handler!(Keyed: u32 => |_: &mut Keyed, _: &u32| {});
+
+fn start(policy: IncarnationPolicy) -> (Environment, EnvironmentExpirationChecker) {
+    Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Keyed" => Keyed),
+        EnvironmentOptions::new().incarnation_policy(policy),
+    )
+}
+
+/// Remove the keyed Actor and spawn its id again, returning the ids of both incarnations.
+fn respawn(env: &mut Environment) -> (ActorId, ActorId) {
+    let first = env.spawn_with_id("Keyed", b"key".to_vec()).unwrap();
+    env.remove(first.clone());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while env.to_actor_ref(first.clone_id()).is_ok() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let second = env.spawn_with_id("Keyed", b"key".to_vec()).unwrap();
+    (first.clone_id(), second.clone_id())
+}
+
+#[test]
+fn respawned_ids_are_new_incarnations() {
+    let (mut env, _expiration_checker) = start(IncarnationPolicy::Allow);
+    let stale = env.watch_stale_incarnations();
+    let (first, second) = respawn(&mut env);
+    assert_eq!(first, second);
+    assert_ne!(first.incarnation(), 0);
+    assert_ne!(first.incarnation(), second.incarnation());
+    let automatic = env.spawn("Keyed").unwrap().clone_id();
+    assert_eq!(automatic.incarnation(), 0);
+    // found by its key, so it is the current incarnation
+    let found = env
+        .find_actor_ref(&b"key".to_vec(), automatic, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.clone_id().incarnation(), second.incarnation());
+
+    assert!(env.to_actor_ref(first).is_ok());
+    assert!(stale.recv_timeout(Duration::from_millis(100)).is_err());
+}
+
+#[test]
+fn stale_incarnations_are_rejected() {
+    let (mut env, _expiration_checker) = start(IncarnationPolicy::RejectStale);
+    let stale = env.watch_stale_incarnations();
+    let (first, second) = respawn(&mut env);
+
+    assert!(matches!(
+        env.to_actor_ref(first.clone()),
+        Err(ActlibError::ActorNotFound(_))
+    ));
+    let report = stale.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(report.stale.incarnation(), first.incarnation());
+    assert_eq!(report.current.incarnation(), second.incarnation());
+    assert_eq!(report.message, None);
+    assert!(!report.delivered);
+    assert!(env.to_actor_ref(second).is_ok());
+}
+
+#[test]
+fn stale_incarnations_are_reported() {
+    let (mut env, _expiration_checker) = start(IncarnationPolicy::Notify);
+    let stale = env.watch_stale_incarnations();
+    let (first, second) = respawn(&mut env);
+
+    let actor_ref = env.to_actor_ref(first).unwrap();
+    actor_ref.send_message(1u32).unwrap();
+    let report = stale.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(report.current.incarnation(), second.incarnation());
+    assert!(report.delivered);
+}
@@ -0,0 +1,37 @@
+//! The Environment describes itself through Environment::info instead of printing a banner.
+
+use actlib::api::*;
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+#[derive(Debug)]
+struct Other;
+
+impl Actor for Other {}
+
+impl_message_handler!(Other: u32 => |_: &mut Other, _: &u32| {});
+
+#[test]
+fn info_describes_the_environment() {
+    let actor_builder = actor_builder!("Idle" => Idle, "Other" => Other);
+    assert_eq!(
+        actor_builder.type_ids(),
+        &["Idle".to_string(), "Other".to_string()]
+    );
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder);
+
+    let info = env.info();
+    assert_eq!(
+        info.actor_types,
+        vec!["Idle".to_string(), "Other".to_string()]
+    );
+    assert!(info.peers.is_empty());
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    let idle = env.spawn("Idle").unwrap();
+    assert_eq!(idle.clone_id().location(), info.machine_id);
+}
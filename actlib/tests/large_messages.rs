@@ -0,0 +1,76 @@
+//! Messages larger than a network frame are split into chunks and reassembled by the receiving machine.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Larger than the 16 MiB a single frame carries.
+const PAYLOAD_SIZE: usize = 20 * 1024 * 1024;
+
+static RECEIVED: Mutex<Option<Sender<(usize, u64)>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Sink;
+
+impl Actor for Sink {}
+
+/// Report the size and the byte sum of every payload.
+fn report(payload: &[u8]) {
+    let sum = payload.iter().map(|byte| *byte as u64).sum();
+    let received = RECEIVED.lock().unwrap();
+    received
+        .as_ref()
+        .unwrap()
+        .send((payload.len(), sum))
+        .unwrap();
+}
+
+impl_message_handler!(Sink: Vec<u8> => |_: &mut Sink, payload: &Vec<u8>| report(payload));
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Sink" => Sink),
+        EnvironmentOptions::new().max_message_size(64 * 1024 * 1024),
+    )
+}
+
+#[test]
+fn messages_larger_than_a_frame_arrive_in_one_piece() {
+    let (tx, rx) = channel();
+    *RECEIVED.lock().unwrap() = Some(tx);
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42781));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42782));
+    let second = thread::spawn(move || machine([127, 0, 0, 2], 42782, first_addr));
+    let first = machine([127, 0, 0, 1], 42781, second_addr);
+    let second = second.join().unwrap();
+
+    let remote = first
+        .machines()
+        .into_iter()
+        .find(|machine| machine.id == second.info().machine_id)
+        .unwrap();
+    let sink = first.spawn_on("Sink", &remote).unwrap();
+    let payload: Vec<u8> = (0..PAYLOAD_SIZE).map(|i| (i % 251) as u8).collect();
+    let sum = payload.iter().map(|byte| *byte as u64).sum();
+    sink.send_message(payload).unwrap();
+    // deserializing the Chunks byte by byte takes a few seconds in debug builds
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(60)).unwrap(),
+        (PAYLOAD_SIZE, sum)
+    );
+
+    // the connection carries regular messages afterwards
+    sink.send_message(vec![1u8, 2, 3]).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), (3, 6));
+}
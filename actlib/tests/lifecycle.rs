@@ -0,0 +1,57 @@
+//! Subscribers of an Actor type are told about every Actor of that type being spawned or stopped.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static EVENTS: Mutex<Option<Sender<LifecycleEvent>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Collector;
+
+impl Actor for Collector {}
+
+fn collect(_: &mut Collector, event: &LifecycleEvent) {
+    let events = EVENTS.lock().unwrap();
+    events.as_ref().unwrap().send(event.clone()).unwrap();
+}
+
+impl_message_handler!(Collector: LifecycleEvent => collect);
+
+#[derive(Debug)]
+struct Field;
+
+impl Actor for Field {}
+
+impl_message_handler!(Field: u32 => |_: &mut Field, _: &u32| {});
+
+#[test]
+fn spawns_and_stops_of_the_type_are_reported() {
+    let (tx, rx) = channel();
+    *EVENTS.lock().unwrap() = Some(tx);
+    let (mut env, _expiration_checker) = Environment::new_local_only(
+        actor_builder!("Collector" => Collector, "Field" => Field, "Other" => Field),
+    );
+    let collector = env.spawn("Collector").unwrap();
+    env.subscribe_lifecycle("Field", &collector);
+    thread::sleep(Duration::from_millis(50));
+
+    let field = env.spawn("Field").unwrap();
+    env.spawn("Other").unwrap();
+    match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        LifecycleEvent::Spawned(id) => assert_eq!(id, field.clone_id()),
+        other => panic!("expected the spawn, got {:?}", other),
+    }
+    env.remove(field.clone());
+    match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+        LifecycleEvent::Stopped(id) => assert_eq!(id, field.clone_id()),
+        other => panic!("expected the stop, got {:?}", other),
+    }
+
+    env.unsubscribe_lifecycle("Field", &collector.clone_id());
+    thread::sleep(Duration::from_millis(50));
+    env.spawn("Field").unwrap();
+    assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+}
@@ -0,0 +1,71 @@
+//! The LineReaderActor forwards every line of its source as a String message, and keeps following tailed files.
+
+use actlib::api::*;
+use actlib::builtin::{LineReaderActor, LineSource, ReadLines};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static LINES: Mutex<Option<Sender<String>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Collector;
+
+impl Actor for Collector {}
+
+fn handle_line(_: &mut Collector, line: &str) {
+    let lines = LINES.lock().unwrap();
+    lines.as_ref().unwrap().send(line.to_string()).unwrap();
+}
+
+impl_message_handler!(Collector: String => handle_line);
+
+fn temp_file(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn lines_of_files_are_forwarded() {
+    let (tx, rx) = channel();
+    *LINES.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "LineReaderActor" => LineReaderActor::default(),
+        "Collector" => Collector
+    ));
+    let collector = env.spawn("Collector").unwrap();
+
+    let file = temp_file("line_reader_file", "first\r\nsecond\n");
+    let reader = env.spawn("LineReaderActor").unwrap();
+    reader
+        .send_message(ReadLines {
+            source: LineSource::File(file.clone()),
+            target: collector.clone_id(),
+        })
+        .unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "first");
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "second");
+
+    // a tailed file is followed, lines are forwarded once they are complete
+    let tailed = temp_file("line_reader_tail", "old\n");
+    reader
+        .send_message(ReadLines {
+            source: LineSource::Tail(tailed.clone()),
+            target: collector.clone_id(),
+        })
+        .unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "old");
+    let mut appender = OpenOptions::new().append(true).open(&tailed).unwrap();
+    appender.write_all(b"new").unwrap();
+    appender.flush().unwrap();
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    appender.write_all(b" line\n").unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "new line");
+
+    std::fs::remove_file(file).unwrap();
+    std::fs::remove_file(tailed).unwrap();
+}
@@ -0,0 +1,60 @@
+//! With lineage tracing, the introspection tells which Actor spawned which.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static SPAWNED: Mutex<Option<Sender<ActorId>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Parent;
+
+impl Actor for Parent {}
+
+/// Spawn a child, which spawns a grandchild for a positive generation.
+fn spawn_child(_: &mut Parent, generation: &u32) {
+    let child = Context::env().spawn("Parent").unwrap();
+    SPAWNED
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .send(child.clone_id())
+        .unwrap();
+    if *generation > 0 {
+        child.send_message(generation - 1).unwrap();
+    }
+}
+
+impl_message_handler!(Parent: u32 => spawn_child);
+
+#[test]
+fn lineage_records_the_spawners() {
+    let (tx, rx) = channel();
+    *SPAWNED.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Parent" => Parent),
+        EnvironmentOptions::new().trace_lineage(),
+    );
+    let root = env.spawn("Parent").unwrap();
+    root.send_message(1u32).unwrap();
+    let child = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    let grandchild = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+    let introspection = env.introspect();
+    assert_eq!(introspection.actors.len(), 3);
+    let children: Vec<ActorId> = introspection
+        .children_of(&root.clone_id())
+        .into_iter()
+        .map(|record| record.actor.clone())
+        .collect();
+    assert_eq!(children, vec![child.clone()]);
+    assert_eq!(
+        introspection.ancestry(&grandchild),
+        vec![child, root.clone_id()]
+    );
+    assert!(introspection.ancestry(&root.clone_id()).is_empty());
+}
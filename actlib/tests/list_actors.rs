@@ -0,0 +1,51 @@
+//! The Actors alive on the local machine and across the cluster can be listed, optionally by their type.
+
+use actlib::api::*;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Field;
+
+impl Actor for Field {}
+
+impl_message_handler!(Field: u32 => |_: &mut Field, _: &u32| {});
+
+fn builder() -> ActorBuilder {
+    actor_builder!("Field" => Field, "Figure" => Field)
+}
+
+#[test]
+fn local_actors_are_listed_by_type() {
+    let (mut env, _expiration_checker) = Environment::new_local_only(builder());
+    let first = env.spawn("Field").unwrap();
+    let second = env.spawn_with_id("Field", b"second".to_vec()).unwrap();
+    let figure = env.spawn("Figure").unwrap();
+
+    let mut fields = env.list_local_actors(Some("Field"));
+    fields.sort();
+    let mut expected = vec![first.clone_id(), second.clone_id()];
+    expected.sort();
+    assert_eq!(fields, expected);
+    assert_eq!(env.list_local_actors(None).len(), 3);
+    assert!(env.list_local_actors(Some("Unknown")).is_empty());
+
+    env.remove(figure);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !env.list_local_actors(Some("Figure")).is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(env.list_local_actors(Some("Figure")).is_empty());
+}
+
+#[test]
+fn cluster_listings_complete_without_peers() {
+    let (env, _expiration_checker) = Environment::new_local_only(builder());
+    let field = env.spawn("Field").unwrap();
+    env.spawn("Figure").unwrap();
+
+    let listing = env.list_cluster_actors(Some("Field"), Duration::from_secs(5));
+    assert_eq!(listing.wait(), vec![field.clone_id()]);
+    let all = env.block_on(env.list_cluster_actors(None, Duration::from_secs(5)));
+    assert_eq!(all.len(), 2);
+}
@@ -0,0 +1,71 @@
+//! With lock contention instrumented, the metrics hold a wait-time histogram per internal lock.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Worker;
+
+impl Actor for Worker {}
+
+impl_message_handler!(Worker: u32 => |_: &mut Worker, _: &u32| {});
+
+#[test]
+fn lock_waits_are_measured_per_lock() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Worker" => Worker),
+        EnvironmentOptions::new().lock_contention(),
+    );
+    let senders: Vec<_> = (0..4)
+        .map(|_| {
+            let worker = env.spawn("Worker").unwrap();
+            thread::spawn(move || {
+                for n in 0..100u32 {
+                    worker.send_message(n).unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    thread::sleep(Duration::from_millis(100));
+
+    let metrics = env.metrics();
+    let locks: Vec<&str> = metrics
+        .lock_waits
+        .iter()
+        .map(|waits| waits.lock.as_str())
+        .collect();
+    assert_eq!(
+        locks,
+        vec!["local_actor_channels", "net_senders", "remote_queries"]
+    );
+    let channels = &metrics.lock_waits[0];
+    assert!(channels.count >= 4);
+    assert!(channels
+        .buckets
+        .windows(2)
+        .all(|pair| pair[0].0 < pair[1].0 && pair[0].1 <= pair[1].1));
+    assert!(channels.buckets.last().unwrap().1 <= channels.count);
+
+    let text = metrics.to_prometheus();
+    assert!(text.contains("# TYPE actlib_lock_wait_seconds histogram"));
+    assert!(text.contains(&format!(
+        "actlib_lock_wait_seconds_count{{machine=\"{}\",lock=\"local_actor_channels\"}} {}",
+        metrics.machine_id, channels.count
+    )));
+}
+
+#[test]
+fn lock_waits_are_not_measured_by_default() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker));
+    env.spawn("Worker").unwrap();
+    let metrics = env.metrics();
+    assert!(metrics.lock_waits.is_empty());
+    assert!(!metrics.to_prometheus().contains("actlib_lock_wait_seconds"));
+}
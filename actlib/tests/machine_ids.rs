@@ -0,0 +1,71 @@
+//! Machines are identified by a MachineId that can be kept across restarts, and their peers may be given by hostname.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::{env, fs, thread};
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+/// Start a machine on *ip* and *port* with the MachineId stored at *id_file*, connected to the *peer*.
+fn machine(ip: [u8; 4], port: u16, peer: Peer, id_file: &Path) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new().machine_id(MachineId::load_or_create(id_file).unwrap()),
+    )
+}
+
+#[test]
+fn machines_know_each_other_by_their_ids() {
+    let ids = env::temp_dir().join(format!("actlib-machine-ids-{}", std::process::id()));
+    fs::create_dir_all(&ids).unwrap();
+    let first_id = MachineId::load_or_create(ids.join("first")).unwrap();
+    // the id is kept across restarts
+    assert_eq!(
+        MachineId::load_or_create(ids.join("first")).unwrap(),
+        first_id
+    );
+    let second_id = MachineId::load_or_create(ids.join("second")).unwrap();
+    assert_ne!(first_id, second_id);
+
+    // localhost resolves to 127.0.0.1, where the first machine connects from
+    let first_by_hostname = Peer::Host("localhost".to_string(), 42741);
+    let second_ids = ids.clone();
+    let second = thread::spawn(move || {
+        machine(
+            [127, 0, 0, 2],
+            42742,
+            first_by_hostname,
+            &second_ids.join("second"),
+        )
+    });
+    let second_by_addr = Peer::from(SocketAddr::from(([127, 0, 0, 2], 42742)));
+    let first = machine(
+        [127, 0, 0, 1],
+        42741,
+        second_by_addr.clone(),
+        &ids.join("first"),
+    );
+    let second = second.join().unwrap();
+
+    assert_eq!(first.info().machine_id, first_id);
+    assert_eq!(second.info().machine_id, second_id);
+    assert_eq!(first.info().peers, vec![(second_id, second_by_addr)]);
+    assert_eq!(
+        second.info().peers,
+        vec![(first_id, Peer::Host("localhost".to_string(), 42741))]
+    );
+    let _ = fs::remove_dir_all(&ids);
+}
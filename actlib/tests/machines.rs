@@ -0,0 +1,40 @@
+//! The machines of an Environment are exposed as MachineRefs, which place Actors like MachineIds do.
+
+use actlib::api::*;
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+#[test]
+fn actors_are_placed_on_machine_refs() {
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!("Idle" => Idle));
+    let machines = env.machines();
+    assert_eq!(machines.len(), 1);
+    let local = &machines[0];
+    assert_eq!(local.id, env.info().machine_id);
+    assert!(local.is_connected());
+    assert_eq!(local.actors, Some(0));
+
+    let idle = env.spawn_on("Idle", local).unwrap();
+    assert_eq!(idle.clone_id().location(), local.id);
+    assert_eq!(env.machines()[0].actors, Some(1));
+
+    let unknown = MachineRef {
+        id: MachineId::random(),
+        address: Peer::Host("unknown-machine.invalid".to_string(), 7000),
+        status: PeerState::Connected,
+        actors: None,
+    };
+    match env.spawn_on("Idle", &unknown) {
+        Err(ActlibError::SpawnFailed(_)) => {}
+        other => panic!("spawned on an unknown machine: {:?}", other),
+    }
+    match env.spawn_with_options("Idle", SpawnOptions::new().exclude_machines(&machines)) {
+        Err(ActlibError::SpawnFailed(_)) => {}
+        other => panic!("spawned on an excluded machine: {:?}", other),
+    }
+}
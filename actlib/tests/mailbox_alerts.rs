@@ -0,0 +1,48 @@
+//! Sampled mailboxes exceeding the thresholds raise a MailboxAlert, once until they are back within the thresholds.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Sleeper;
+
+impl Actor for Sleeper {}
+
+impl_message_handler!(Sleeper: u64 => |_: &mut Sleeper, millis: &u64| thread::sleep(Duration::from_millis(*millis)));
+
+#[test]
+fn slow_consumers_are_reported_once() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Sleeper" => Sleeper),
+        EnvironmentOptions::new().mailbox_alerts(MailboxAlerts {
+            interval: Duration::from_millis(20),
+            max_depth: Some(2),
+            max_age: None,
+        }),
+    );
+    let alerts = env.watch_mailbox_alerts();
+    let sleeper = env.spawn("Sleeper").unwrap();
+    let idler = env.spawn("Sleeper").unwrap();
+    idler.send_message(0u64).unwrap();
+    for _ in 0..5 {
+        sleeper.send_message(100u64).unwrap();
+    }
+
+    let alert = alerts.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(alert.actor, sleeper.clone_id());
+    assert_eq!(alert.type_id, "Sleeper");
+    assert!(alert.queued_messages > 2);
+    // the mailbox stays too deep for several samples, but is reported only once
+    assert!(alerts.recv_timeout(Duration::from_millis(150)).is_err());
+
+    // once drained, the next backlog is reported again
+    thread::sleep(Duration::from_millis(800));
+    for _ in 0..5 {
+        sleeper.send_message(100u64).unwrap();
+    }
+    let alert = alerts.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(alert.actor, sleeper.clone_id());
+}
@@ -0,0 +1,65 @@
+//! Mailbox quotas measure local Messages in the configured WireFormat, mailboxes without a quota do not measure them.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Worker;
+
+impl Actor for Worker {}
+
+fn pause(_: &mut Worker, millis: &u64) {
+    thread::sleep(Duration::from_millis(*millis));
+}
+
+impl_message_handler!(Worker: u64 => pause, String => |_: &mut Worker, _: &String| {});
+
+/// The serialized size of the mailbox of *worker*, as reported by the metrics.
+fn queued_bytes(env: &Environment, worker: &ActorRef) -> usize {
+    env.metrics()
+        .mailboxes
+        .into_iter()
+        .find(|mailbox| mailbox.actor == worker.clone_id())
+        .unwrap()
+        .queued_bytes
+}
+
+#[test]
+fn quotas_count_the_bytes_of_the_wire_format() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Bounded" => Worker, "Unbounded" => Worker),
+        EnvironmentOptions::new()
+            .wire_format(WireFormat::Json)
+            .mailbox_quota(
+                "Bounded",
+                MailboxQuota {
+                    max_bytes: 30,
+                    policy: QuotaPolicy::Reject,
+                },
+            ),
+    );
+    let bounded = env.spawn("Bounded").unwrap();
+    bounded.send_message(500u64).unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    // 12 bytes in JSON each, bincode would take 18
+    let update = "0123456789".to_string();
+    bounded.send_message(update.clone()).unwrap();
+    bounded.send_message(update.clone()).unwrap();
+    assert_eq!(queued_bytes(&env, &bounded), 24);
+    match bounded.send_message(update.clone()) {
+        Err(ActlibError::MailboxFull(_)) => {}
+        other => panic!("the quota accepted the message: {:?}", other),
+    }
+
+    let unbounded = env.spawn("Unbounded").unwrap();
+    unbounded.send_message(500u64).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    for _ in 0..3 {
+        unbounded.send_message(update.clone()).unwrap();
+    }
+    assert_eq!(queued_bytes(&env, &unbounded), 0);
+}
@@ -0,0 +1,85 @@
+//! The metrics count live Actors, handled messages, spawns and removals, and are served in the Prometheus text format.
+
+use actlib::api::*;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Worker;
+
+impl Actor for Worker {}
+
+impl_message_handler!(Worker: u32 => |_: &mut Worker, _: &u32| {});
+
+/// Wait until *done* holds for the metrics of *env*.
+fn wait_for_metrics<F: Fn(&Metrics) -> bool>(env: &Environment, done: F) -> Metrics {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let metrics = env.metrics();
+        if done(&metrics) || Instant::now() > deadline {
+            return metrics;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn metrics_count_actors_and_messages() {
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker));
+    let workers: Vec<ActorRef> = (0..3).map(|_| env.spawn("Worker").unwrap()).collect();
+    for worker in workers.iter() {
+        for n in 0..10u32 {
+            worker.send_message(n).unwrap();
+        }
+    }
+    env.remove(workers[0].clone());
+
+    let metrics = wait_for_metrics(&env, |metrics| {
+        metrics.messages_handled == 30 && metrics.actors_removed == 1
+    });
+    assert_eq!(metrics.messages_handled, 30);
+    assert_eq!(metrics.actors_spawned, 3);
+    assert_eq!(metrics.actors_removed, 1);
+    assert_eq!(metrics.live_actors.get("Worker"), Some(&2));
+    assert_eq!(metrics.mailboxes.len(), 2);
+    assert_eq!(metrics.remote_messages_sent, 0);
+    assert_eq!(metrics.remote_messages_received, 0);
+
+    // the first rate interval started with the Environment
+    thread::sleep(Duration::from_secs(1));
+    let rates = env.metrics().rates;
+    assert!(rates.interval >= Duration::from_secs(1));
+    assert!(rates.messages_handled > 0.0);
+}
+
+#[test]
+fn prometheus_exporter_serves_the_metrics() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Worker" => Worker),
+        EnvironmentOptions::new().prometheus_exporter(19464),
+    );
+    let worker = env.spawn("Worker").unwrap();
+    worker.send_message(1u32).unwrap();
+    wait_for_metrics(&env, |metrics| metrics.messages_handled == 1);
+
+    let mut stream = TcpStream::connect(("127.0.0.1", 19464)).unwrap();
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("# TYPE actlib_live_actors gauge"));
+    assert!(response.contains("type_id=\"Worker\"} 1\n"));
+    assert!(response.contains("# TYPE actlib_messages_handled_total counter"));
+    let handled = response
+        .lines()
+        .find(|line| line.starts_with("actlib_messages_handled_total{"))
+        .unwrap();
+    assert!(handled.ends_with(" 1"));
+}
@@ -0,0 +1,148 @@
+//! The MQTT ingress forwards decoded payloads of its topic to an Actor, and the egress publishes encoded messages.
+//!
+//! A minimal broker speaking just enough MQTT 3.1.1 stands in for a real one.
+
+use actlib::api::*;
+use actlib::mqtt::{MqttEgress, MqttIngress, MqttOptions, QoS};
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static TEMPERATURES: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Thermometer;
+
+impl Actor for Thermometer {}
+
+fn handle_temperature(_: &mut Thermometer, temperature: &u32) {
+    let temperatures = TEMPERATURES.lock().unwrap();
+    temperatures.as_ref().unwrap().send(*temperature).unwrap();
+}
+
+impl_message_handler!(Thermometer: u32 => handle_temperature);
+
+fn decode_temperature(payload: &[u8]) -> Option<u32> {
+    std::str::from_utf8(payload).ok()?.parse().ok()
+}
+
+fn encode_temperature(temperature: &u32) -> Vec<u8> {
+    temperature.to_string().into_bytes()
+}
+
+/// Read a packet, returning its type and body.
+fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0u8];
+    stream.read_exact(&mut header).unwrap();
+    let mut len = 0usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        stream.read_exact(&mut byte).unwrap();
+        len |= ((byte[0] & 0x7f) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    let mut body = vec![0; len];
+    stream.read_exact(&mut body).unwrap();
+    (header[0] >> 4, body)
+}
+
+/// Read packets until one of the given type arrives.
+fn expect_packet(stream: &mut TcpStream, packet_type: u8) -> Vec<u8> {
+    loop {
+        let (received_type, body) = read_packet(stream);
+        if received_type == packet_type {
+            return body;
+        }
+    }
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = (topic.len() as u16).to_be_bytes().to_vec();
+    body.extend_from_slice(topic.as_bytes());
+    body.extend_from_slice(payload);
+    let mut packet = vec![0x30, body.len() as u8];
+    packet.extend(body);
+    packet
+}
+
+/// Accept a single client and acknowledge its connection.
+fn accept_client(listener: &TcpListener) -> TcpStream {
+    let (mut stream, _) = listener.accept().unwrap();
+    expect_packet(&mut stream, 1);
+    stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+    stream
+}
+
+#[test]
+fn ingress_forwards_decoded_payloads() {
+    let (tx, rx) = channel();
+    *TEMPERATURES.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Thermometer" => Thermometer));
+    let thermometer = env.spawn("Thermometer").unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let broker = thread::spawn(move || {
+        let mut stream = accept_client(&listener);
+        let subscribe = expect_packet(&mut stream, 8);
+        let topic_len = u16::from_be_bytes([subscribe[2], subscribe[3]]) as usize;
+        let topic = String::from_utf8(subscribe[4..4 + topic_len].to_vec()).unwrap();
+        stream
+            .write_all(&[0x90, 0x03, subscribe[0], subscribe[1], 0x00])
+            .unwrap();
+        stream
+            .write_all(&publish_packet("sensors/1/temperature", b"warm"))
+            .unwrap();
+        stream
+            .write_all(&publish_packet("sensors/1/temperature", b"21"))
+            .unwrap();
+        thread::sleep(Duration::from_secs(1));
+        topic
+    });
+
+    MqttIngress::new(
+        MqttOptions::new("ingress", "127.0.0.1", port),
+        "sensors/+/temperature",
+        decode_temperature,
+    )
+    .qos(QoS::AtMostOnce)
+    .forward_to(thermometer);
+
+    // the undecodable payload is dropped
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 21);
+    assert_eq!(broker.join().unwrap(), "sensors/+/temperature");
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn egress_publishes_encoded_messages() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let broker = thread::spawn(move || {
+        let mut stream = accept_client(&listener);
+        let publish = expect_packet(&mut stream, 3);
+        let topic_len = u16::from_be_bytes([publish[0], publish[1]]) as usize;
+        let topic = String::from_utf8(publish[2..2 + topic_len].to_vec()).unwrap();
+        (topic, publish[2 + topic_len..].to_vec())
+    });
+
+    let egress = MqttEgress::connect(
+        MqttOptions::new("egress", "127.0.0.1", port),
+        "heating/setpoint",
+        encode_temperature,
+    )
+    .qos(QoS::AtMostOnce);
+    egress.publish(&23).unwrap();
+
+    let (topic, payload) = broker.join().unwrap();
+    assert_eq!(topic, "heating/setpoint");
+    assert_eq!(payload, b"23");
+}
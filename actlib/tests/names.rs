@@ -0,0 +1,40 @@
+//! Actors are found by their registered name, which is released once they stop.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Collector;
+
+impl Actor for Collector {}
+
+impl_message_handler!(Collector: u32 => |_: &mut Collector, _: &u32| {});
+
+#[test]
+fn names_refer_to_their_actor_until_it_stops() {
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Collector" => Collector));
+    let collector = env.spawn("Collector").unwrap();
+    let other = env.spawn("Collector").unwrap();
+    assert!(env.lookup_name("collector").is_none());
+
+    env.register_name("collector", &collector).unwrap();
+    // registering again for the same Actor is fine, for another one it is not
+    env.register_name("collector", &collector).unwrap();
+    assert!(matches!(
+        env.register_name("collector", &other),
+        Err(ActlibError::InvalidState(_))
+    ));
+    let found = env.lookup_name("collector").unwrap();
+    assert_eq!(found.clone_id(), collector.clone_id());
+    found.send_message(1u32).unwrap();
+
+    assert_eq!(env.unregister_name("collector"), Some(collector.clone_id()));
+    assert!(env.lookup_name("collector").is_none());
+
+    env.register_name("collector", &other).unwrap();
+    env.remove(other);
+    thread::sleep(Duration::from_millis(100));
+    assert!(env.lookup_name("collector").is_none());
+}
@@ -0,0 +1,72 @@
+//! Ordered broadcasts reach the local Actors sorted by their ActorId, sequenced broadcasts are numbered per sending machine.
+
+use actlib::api::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static SEEN: Mutex<Vec<(ActorId, Sequenced<u32>)>> = Mutex::new(Vec::new());
+/// Holds the handlers back until both broadcasts were enqueued.
+static GATE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Default)]
+struct Listener {
+    id: Option<ActorId>,
+}
+
+impl Actor for Listener {
+    fn on_start(&mut self, _local_env: Environment, own_ref: ActorRef) {
+        self.id = Some(own_ref.clone_id());
+    }
+}
+
+fn listen(listener: &mut Listener, round: &Sequenced<u32>) {
+    while !GATE.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(5));
+    }
+    let id = listener.id.clone().unwrap();
+    SEEN.lock().unwrap().push((id, round.clone()));
+}
+
+impl_message_handler!(Listener: Sequenced<u32> => listen);
+
+#[test]
+fn broadcasts_are_delivered_in_id_order() {
+    // a single worker runs the Actors in the order their messages were enqueued, once the gate opens
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Listener" => Listener::default()),
+        EnvironmentOptions::new()
+            .ordered_broadcasts()
+            .worker_threads(1),
+    );
+    let mut listeners: Vec<ActorId> = (0..5)
+        .map(|_| env.spawn("Listener").unwrap().clone_id())
+        .collect();
+    listeners.sort();
+    // every Actor started and waits for messages
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(env.broadcast_sequenced(10u32).unwrap(), 1);
+    assert_eq!(env.broadcast_sequenced(20u32).unwrap(), 2);
+    GATE.store(true, Ordering::SeqCst);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while SEEN.lock().unwrap().len() < 10 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    let seen = SEEN.lock().unwrap();
+    assert_eq!(seen.len(), 10);
+    for (seq, message) in [(1, 10), (2, 20)] {
+        let round: Vec<&(ActorId, Sequenced<u32>)> =
+            seen.iter().filter(|(_, round)| round.seq == seq).collect();
+        let order: Vec<ActorId> = round.iter().map(|(id, _)| id.clone()).collect();
+        assert_eq!(order, listeners);
+        for (_, round) in round {
+            assert_eq!(round.origin, env.info().machine_id);
+            assert_eq!(round.message, message);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! The durable outbox keeps unacknowledged Messages within its retention, counting their age across restarts.
+
+mod common;
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Sink;
+
+impl Actor for Sink {}
+
+impl_message_handler!(Sink: Vec<u8> => |_: &mut Sink, _: &Vec<u8>| {});
+
+/// Start an Environment retrying forever within the *retention*.
+fn start(
+    log: &std::path::Path,
+    retention: OutboxRetention,
+) -> (Environment, EnvironmentExpirationChecker) {
+    Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Sink" => Sink),
+        EnvironmentOptions::new()
+            .durable_outbox(log)
+            .reliable_delivery(
+                ReliableDelivery::new()
+                    .ack_timeout(Duration::from_millis(20))
+                    .max_retries(u32::MAX),
+            )
+            .outbox_retention(retention),
+    )
+}
+
+fn log_path(test: &str) -> std::path::PathBuf {
+    let log = std::env::temp_dir().join(format!(
+        "actlib-outbox-retention-{}-{}",
+        test,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&log);
+    log
+}
+
+#[test]
+fn the_oldest_messages_beyond_the_size_are_given_up_on() {
+    let log = log_path("size");
+    let (env, _expiration_checker) = start(&log, OutboxRetention::new().max_bytes(1500));
+    let failures = env.watch_delivery_failures();
+    let local = env.spawn("Sink").unwrap();
+    let unreachable = env
+        .to_actor_ref(common::on_unknown_machine(local.clone_id()))
+        .unwrap();
+    for n in 0..3u8 {
+        unreachable.send_message(vec![n; 1000]).unwrap();
+    }
+
+    for n in 0..2u8 {
+        let failure = failures.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(failure.message, bincode::serialize(&vec![n; 1000]).unwrap());
+        assert_eq!(failure.rejection, None);
+    }
+    // the newest one fits and is retried
+    assert!(failures.recv_timeout(Duration::from_millis(300)).is_err());
+
+    let _ = std::fs::remove_file(&log);
+}
+
+#[test]
+fn the_age_counts_from_before_a_restart() {
+    let log = log_path("age");
+    let retention = OutboxRetention::new().max_age(Duration::from_millis(500));
+    let (env, _expiration_checker) = start(&log, retention.clone());
+    let local = env.spawn("Sink").unwrap();
+    let unreachable = env
+        .to_actor_ref(common::on_unknown_machine(local.clone_id()))
+        .unwrap();
+    unreachable.send_message(vec![7u8]).unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    let (restarted, _restarted_checker) = start(&log, retention);
+    let failures = restarted.watch_delivery_failures();
+    // kept for what is left of its retention
+    assert!(failures.recv_timeout(Duration::from_millis(100)).is_err());
+    let failure = failures.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(failure.actor, unreachable.clone_id());
+    assert_eq!(failure.message, bincode::serialize(&vec![7u8]).unwrap());
+
+    let _ = std::fs::remove_file(&log);
+}
@@ -0,0 +1,87 @@
+//! A stashed payload is redeemed with its ticket on any machine, until it is released or its lease expires.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+/// Long compared to pulling the payload of the test from the other machine, which takes a few hundred milliseconds in debug builds.
+const LEASE: Duration = Duration::from_secs(5);
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new().payload_lease(LEASE),
+    )
+}
+
+/// Wait until *env* can reach the machine *id*, which only happens once it introduced itself.
+fn await_machine(env: &Environment, id: MachineId) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !env
+        .machines()
+        .iter()
+        .any(|machine| machine.id == id && machine.status == PeerState::Connected)
+    {
+        assert!(
+            Instant::now() < deadline,
+            "machine {:?} did not connect",
+            id
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn payloads_are_redeemed_across_machines() {
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42751));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42752));
+    let second = thread::spawn(move || machine([127, 0, 0, 2], 42752, first_addr));
+    let first = machine([127, 0, 0, 1], 42751, second_addr);
+    let second = second.join().unwrap();
+    await_machine(&first, second.info().machine_id);
+    await_machine(&second, first.info().machine_id);
+
+    let payload = vec![7u8; 1 << 20];
+    let ticket = first.stash_payload(payload.clone());
+    assert_eq!(ticket.owner, first.info().machine_id);
+    assert_eq!(ticket.size, payload.len());
+    assert_eq!(first.redeem(&ticket).unwrap(), payload);
+    // pulled from the first machine, and still there afterwards
+    assert_eq!(second.redeem(&ticket).unwrap(), payload);
+    assert_eq!(second.redeem(&ticket).unwrap(), payload);
+
+    // released from the remote machine
+    second.release_payload(&ticket).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    assert!(matches!(
+        first.redeem(&ticket),
+        Err(ActlibError::InvalidState(_))
+    ));
+    assert!(matches!(
+        second.redeem(&ticket),
+        Err(ActlibError::InvalidState(_))
+    ));
+
+    let expiring = first.stash_payload(vec![1, 2, 3]);
+    thread::sleep(LEASE + Duration::from_millis(200));
+    assert!(matches!(
+        second.redeem(&expiring),
+        Err(ActlibError::InvalidState(_))
+    ));
+}
@@ -0,0 +1,44 @@
+//! An Environment waiting for none of its remote machines starts right away, and reports them as connecting.
+
+use actlib::api::*;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Echo;
+
+impl Actor for Echo {}
+
+fn echo(_: &mut Echo, request: &Request<u32>) {
+    Context::env().reply(request, request.message).unwrap();
+}
+
+impl_message_handler!(Echo: Request<u32> => echo);
+
+#[test]
+fn late_peers_do_not_block_local_work() {
+    // the hostname never resolves, so the remote machine stays in the background
+    let late = Peer::Host("late-machine.invalid".to_string(), 7000);
+    let (env, _expiration_checker) = Environment::new_with_peers(
+        0,
+        std::slice::from_ref(&late),
+        actor_builder!("Echo" => Echo),
+        EnvironmentOptions::new().wait_for_peers(0),
+    );
+    let peers = env.watch_peers();
+
+    let status = env.peer_status();
+    assert_eq!(status.len(), 1);
+    assert_eq!(status[0].peer, late);
+    assert_eq!(status[0].machine_id, None);
+    assert_eq!(status[0].state, PeerState::Connecting);
+    assert!(env.info().peers.is_empty());
+
+    // every Actor is placed on the local machine meanwhile
+    for n in 0..4u32 {
+        let echo = env.spawn("Echo").unwrap();
+        assert_eq!(echo.clone_id().location(), env.info().machine_id);
+        let reply: u32 = echo.ask(n).unwrap().wait(Duration::from_secs(5)).unwrap();
+        assert_eq!(reply, n);
+    }
+    assert!(peers.recv_timeout(Duration::from_millis(200)).is_err());
+}
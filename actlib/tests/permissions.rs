@@ -0,0 +1,239 @@
+//! Restricted operations must only succeed for Actors granted the Capability at spawn.
+//!
+//! Code outside of an Actor, like this test, is never restricted.
+//! A machine checks the requests of remote Actors on arrival, even if their own machine does not restrict anything.
+//!
+//! The machines of the remote test run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// Whether the spawn was refused and the machines that did not report back on the expiration.
+type RaidOutcome = (bool, Vec<MachineId>);
+
+static RESULTS: Mutex<Option<Sender<(bool, bool)>>> = Mutex::new(None);
+static RAIDED: Mutex<Option<Sender<RaidOutcome>>> = Mutex::new(None);
+static WITNESSED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug, Default)]
+struct Janitor {
+    env: Option<Environment>,
+}
+
+impl Actor for Janitor {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+/// Remove the target, then try to expire the Environment, reporting whether expiring was refused.
+fn tidy_up(janitor: &mut Janitor, target: &ActorId) {
+    let mut env = janitor.env.clone().unwrap();
+    let target = env.to_actor_ref(target.clone()).unwrap();
+    env.remove(target);
+    let expire_denied = match env.set_expired() {
+        Err(e) => e.contains("PermissionDenied"),
+        Ok(_) => false,
+    };
+    let spawn_denied = matches!(
+        env.spawn_with_options("Janitor", SpawnOptions::new().grant(Capability::Expire)),
+        Err(ActlibError::PermissionDenied(_))
+    );
+    let results = RESULTS.lock().unwrap();
+    results
+        .as_ref()
+        .unwrap()
+        .send((expire_denied, spawn_denied))
+        .unwrap();
+}
+
+impl_message_handler!(Janitor: ActorId => tidy_up);
+
+#[derive(Debug)]
+struct Idler;
+
+impl Actor for Idler {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(Idler: u32 => |_: &mut Idler, _: &u32| {});
+
+#[test]
+fn only_granted_actors_remove_and_expire() {
+    let (tx, rx) = channel();
+    *RESULTS.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!(
+            "Janitor" => Janitor::default(),
+            "Idler" => Idler
+        ),
+        EnvironmentOptions::new()
+            .restrict(Capability::Remove)
+            .restrict(Capability::Expire),
+    );
+    let unprivileged = env.spawn("Janitor").unwrap();
+    let privileged = env
+        .spawn_with_options("Janitor", SpawnOptions::new().grant(Capability::Remove))
+        .unwrap();
+    let idler = env.spawn("Idler").unwrap();
+    let idler_id = idler.clone_id();
+
+    unprivileged.send_message(idler_id.clone()).unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        (true, true)
+    );
+    thread::sleep(Duration::from_millis(100));
+    assert!(env
+        .introspect()
+        .actors
+        .iter()
+        .any(|actor| actor.id == idler_id));
+
+    privileged.send_message(idler_id.clone()).unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        (true, true)
+    );
+    thread::sleep(Duration::from_millis(100));
+    assert!(!env
+        .introspect()
+        .actors
+        .iter()
+        .any(|actor| actor.id == idler_id));
+}
+
+#[derive(Debug, Default)]
+struct Intruder {
+    env: Option<Environment>,
+}
+
+impl Actor for Intruder {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+/// Try every restricted operation on the machine of the target, reporting whether the spawn was refused
+/// and which machines did not report back on the expiration.
+fn raid(intruder: &mut Intruder, target: &ActorId) {
+    let mut env = intruder.env.clone().unwrap();
+    let local = env.info().machine_id;
+    let remote = env
+        .machines()
+        .into_iter()
+        .find(|machine| machine.id != local)
+        .unwrap();
+    env.remove(env.to_actor_ref(target.clone()).unwrap());
+    env.broadcast(2u32).unwrap();
+    let spawn_denied = match env.spawn_with_options(
+        "Witness",
+        SpawnOptions::new()
+            .on_machine(&remote)
+            .grant(Capability::Expire),
+    ) {
+        Err(e) => format!("{:?}", e).contains("PermissionDenied"),
+        Ok(_) => false,
+    };
+    let unreported = env.shutdown(Duration::from_millis(100)).unwrap().unreported;
+    let raided = RAIDED.lock().unwrap();
+    raided
+        .as_ref()
+        .unwrap()
+        .send((spawn_denied, unreported))
+        .unwrap();
+}
+
+impl_message_handler!(Intruder: ActorId => raid);
+
+#[derive(Debug)]
+struct Witness;
+
+impl Actor for Witness {}
+
+/// Report every broadcast that arrived.
+fn witness(_: &mut Witness, broadcast: &u32) {
+    let witnessed = WITNESSED.lock().unwrap();
+    witnessed.as_ref().unwrap().send(*broadcast).unwrap();
+}
+
+impl_message_handler!(Witness: u32 => witness);
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*, restricting the *capabilities*.
+fn machine(
+    ip: [u8; 4],
+    port: u16,
+    peer: SocketAddr,
+    capabilities: &[Capability],
+) -> (Environment, EnvironmentExpirationChecker) {
+    let options = capabilities
+        .iter()
+        .fold(EnvironmentOptions::new(), |options, capability| {
+            options.restrict(*capability)
+        });
+    common::start_machine(
+        ip,
+        port,
+        peer,
+        actor_builder!(
+            "Intruder" => Intruder::default(),
+            "Witness" => Witness
+        ),
+        options,
+    )
+}
+
+#[test]
+fn remote_requests_are_checked_on_arrival() {
+    let (raided_tx, raided_rx) = channel();
+    *RAIDED.lock().unwrap() = Some(raided_tx);
+    let (witnessed_tx, witnessed_rx) = channel();
+    *WITNESSED.lock().unwrap() = Some(witnessed_tx);
+    let lax_addr = SocketAddr::from(([127, 0, 0, 1], 42821));
+    let strict_addr = SocketAddr::from(([127, 0, 0, 2], 42822));
+    let strict = thread::spawn(move || {
+        machine(
+            [127, 0, 0, 2],
+            42822,
+            lax_addr,
+            &[
+                Capability::Remove,
+                Capability::Broadcast,
+                Capability::Expire,
+            ],
+        )
+        .0
+    });
+    // the intruder expires the lax machine, which hands the result to the checker
+    let (lax, _lax_checker) = machine([127, 0, 0, 1], 42821, strict_addr, &[]);
+    let strict = strict.join().unwrap();
+    let witness = strict.spawn_local("Witness").unwrap();
+
+    // outside of an Actor, broadcasts of the lax machine are not restricted
+    lax.broadcast(1u32).unwrap();
+    assert_eq!(
+        witnessed_rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        1
+    );
+
+    let intruder = lax.spawn_local("Intruder").unwrap();
+    intruder.send_message(witness.clone_id()).unwrap();
+    let (spawn_denied, unreported) = raided_rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(spawn_denied);
+    assert_eq!(unreported, vec![strict.info().machine_id]);
+    assert!(witnessed_rx.try_recv().is_err());
+    assert!(!strict.is_shutting_down());
+    assert!(strict
+        .introspect()
+        .actors
+        .iter()
+        .any(|actor| actor.id == witness.clone_id()));
+}
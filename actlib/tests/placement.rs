@@ -0,0 +1,39 @@
+//! SpawnOptions keep Actors off excluded machines, whatever Placement the Environment uses.
+
+use actlib::api::*;
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+#[test]
+fn random_placement_respects_exclusions() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new().placement(Placement::Random),
+    );
+    let local = env.info().machine_id;
+    for _ in 0..8 {
+        let idle = env.spawn("Idle").unwrap();
+        assert_eq!(idle.clone_id().location(), local);
+    }
+
+    // excluding a machine that is not connected changes nothing
+    let idle = env
+        .spawn_with_options(
+            "Idle",
+            SpawnOptions::new().exclude_machines(&[MachineId::random()]),
+        )
+        .unwrap();
+    assert_eq!(idle.clone_id().location(), local);
+
+    match env.spawn_with_options("Idle", SpawnOptions::new().exclude_machines(&[local])) {
+        Err(ActlibError::SpawnFailed(_)) => {}
+        other => panic!("spawned on the excluded machine: {:?}", other),
+    }
+}
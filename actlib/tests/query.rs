@@ -0,0 +1,54 @@
+//! Queries read an Actor's state on its own thread, ahead of the Messages waiting in its mailbox.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Account {
+    balance: i64,
+}
+
+impl Actor for Account {}
+
+impl QueryableActor for Account {}
+
+fn deposit(account: &mut Account, amount: &i64) {
+    thread::sleep(Duration::from_millis(100));
+    account.balance += amount;
+}
+
+impl_message_handler!(Account: i64 => deposit);
+
+#[derive(Debug)]
+struct Other;
+
+impl Actor for Other {}
+
+impl QueryableActor for Other {}
+
+impl_message_handler!(Other: i64 => |_: &mut Other, _: &i64| {});
+
+#[test]
+fn queries_overtake_pending_messages() {
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Account" => Account { balance: 0 },
+        "Other" => Other
+    ));
+    let account = env.spawn("Account").unwrap();
+    for _ in 0..5 {
+        account.send_message(10i64).unwrap();
+    }
+    thread::sleep(Duration::from_millis(50));
+
+    // answered before the deposits queued ahead of the query
+    let balance = account
+        .query(|account: &Account| account.balance, Duration::from_secs(5))
+        .unwrap();
+    assert!(balance < 50);
+
+    match account.query(|_: &Other| (), Duration::from_secs(5)) {
+        Err(ActlibError::InvalidActorRef(_)) => {}
+        other => panic!("queried an Actor of another type: {:?}", other),
+    }
+}
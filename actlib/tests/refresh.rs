@@ -0,0 +1,52 @@
+//! Refreshing an ActorRef resolves the best channel to its Actor, skipping the forwarding of a replaced Actor.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static RECEIVED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Replica;
+
+impl Actor for Replica {}
+
+fn receive(_: &mut Replica, message: &u32) {
+    let received = RECEIVED.lock().unwrap();
+    received.as_ref().unwrap().send(*message).unwrap();
+}
+
+impl_message_handler!(Replica: u32 => receive);
+
+#[test]
+fn refreshed_refs_point_to_the_replacement() {
+    let (tx, rx) = channel();
+    *RECEIVED.lock().unwrap() = Some(tx);
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Replica" => Replica));
+    let replaced = env.spawn("Replica").unwrap();
+    let replaced_id = replaced.clone_id();
+    let replacement = env.spawn("Replica").unwrap();
+
+    // a local Actor keeps its ActorRef
+    let refreshed = env.refresh(replacement.clone());
+    assert_eq!(refreshed.clone_id(), replacement.clone_id());
+
+    env.alias(replaced_id.clone(), &replacement, Duration::from_secs(10))
+        .unwrap();
+    env.remove(replaced);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while env.list_local_actors(None).len() > 1 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let forwarding = env.to_actor_ref(replaced_id).unwrap();
+    let refreshed = env.refresh(forwarding);
+    assert_eq!(refreshed.clone_id(), replacement.clone_id());
+    refreshed.send_message(1u32).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 1);
+    // sent directly, the alias did not forward anything
+    assert_eq!(env.aliases()[0].forwarded, 0);
+}
@@ -0,0 +1,120 @@
+//! A machine that is only connected to a gateway reaches the machines behind it through relayed messages.
+//!
+//! The gateway runs in the test process on 127.0.0.1, the laptop on 127.0.0.2 and the remote machine on 127.0.0.3
+//! run in child processes of this test, since every process only serves a single port.
+
+use actlib::api::*;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{env, fs};
+
+const TEST: &str = "broadcasts_reach_machines_behind_a_gateway";
+/// The machine a child process runs, ```laptop``` or ```remote```
+const ROLE: &str = "ACTLIB_RELAY_ROLE";
+/// The directory holding the MachineIds of all three machines
+const IDS: &str = "ACTLIB_RELAY_IDS";
+
+static HEARD: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Listener;
+
+impl Actor for Listener {}
+
+fn listen(_: &mut Listener, event: &u32) {
+    if let Some(heard) = HEARD.lock().unwrap().as_ref() {
+        let _ = heard.send(*event);
+    }
+}
+
+impl_message_handler!(Listener: u32 => listen);
+
+fn addr(machine: u8) -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, machine], 42730 + machine as u16))
+}
+
+/// Start the machine on 127.0.0.*machine*, connected to the *remotes*.
+fn machine(ids: &Path, machine: u8, remotes: &[u8], options: EnvironmentOptions) -> Environment {
+    let remotes: Vec<SocketAddr> = remotes.iter().map(|remote| addr(*remote)).collect();
+    let (env, _expiration_checker) = Environment::new_with_options(
+        addr(machine).port(),
+        &remotes,
+        actor_builder!("Listener" => Listener),
+        options
+            .local_address(IpAddr::V4(Ipv4Addr::new(127, 0, 0, machine)))
+            .machine_id(machine_id(ids, machine)),
+    );
+    env
+}
+
+fn machine_id(ids: &Path, machine: u8) -> MachineId {
+    MachineId::load_or_create(ids.join(machine.to_string())).unwrap()
+}
+
+/// Only connected to the gateway, broadcasts until it is killed.
+fn laptop(ids: &Path) {
+    let options = EnvironmentOptions::new().route(machine_id(ids, 3), machine_id(ids, 1));
+    let laptop = machine(ids, 2, &[1], options);
+    let deadline = Instant::now() + Duration::from_secs(20);
+    while Instant::now() < deadline {
+        laptop.broadcast(7u32).unwrap();
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Only connected to the gateway, succeeds once it heard the broadcast of the laptop.
+fn remote(ids: &Path) {
+    let (tx, rx) = channel();
+    *HEARD.lock().unwrap() = Some(tx);
+    let remote = machine(ids, 3, &[1], EnvironmentOptions::new());
+    remote.spawn_local("Listener").unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(20)).unwrap(), 7);
+}
+
+#[test]
+fn broadcasts_reach_machines_behind_a_gateway() {
+    if let Ok(ids) = env::var(IDS) {
+        match env::var(ROLE).as_deref() {
+            Ok("laptop") => return laptop(Path::new(&ids)),
+            Ok("remote") => return remote(Path::new(&ids)),
+            _ => {}
+        }
+    }
+    let ids = env::temp_dir().join(format!("actlib-relay-{}", std::process::id()));
+    fs::create_dir_all(&ids).unwrap();
+    for machine in 1..=3 {
+        machine_id(&ids, machine);
+    }
+    let child = |role: &str, ids: &PathBuf| {
+        Command::new(env::current_exe().unwrap())
+            .args([TEST, "--exact", "--nocapture"])
+            .env(ROLE, role)
+            .env(IDS, ids)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap()
+    };
+    let mut remote = child("remote", &ids);
+    let mut laptop = child("laptop", &ids);
+
+    // the gateway is the only machine connected to both
+    let _gateway = machine(&ids, 1, &[2, 3], EnvironmentOptions::new());
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let heard = loop {
+        match remote.try_wait().unwrap() {
+            Some(status) => break status.success(),
+            None if Instant::now() > deadline => break false,
+            None => thread::sleep(Duration::from_millis(50)),
+        }
+    };
+    let _ = remote.kill();
+    let _ = laptop.kill();
+    let _ = laptop.wait();
+    let _ = fs::remove_dir_all(&ids);
+    assert!(heard, "the remote machine never heard the laptop");
+}
@@ -0,0 +1,45 @@
+//! Under reliable delivery, Messages to remote Actors that are never acknowledged are retried and then reported as failed.
+
+mod common;
+
+use actlib::api::*;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Sink;
+
+impl Actor for Sink {}
+
+impl_message_handler!(Sink: u32 => |_: &mut Sink, _: &u32| {});
+
+#[test]
+fn unacknowledged_messages_are_reported() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Sink" => Sink),
+        EnvironmentOptions::new().reliable_delivery(
+            ReliableDelivery::new()
+                .ack_timeout(Duration::from_millis(50))
+                .max_retries(2),
+        ),
+    );
+    let failures = env.watch_delivery_failures();
+    let local = env.spawn("Sink").unwrap();
+    local.send_message(1u32).unwrap();
+
+    let unreachable = env
+        .to_actor_ref(common::on_unknown_machine(local.clone_id()))
+        .unwrap();
+    // the Message is only handed to the network thread
+    unreachable.send_message(2u32).unwrap();
+
+    let failure = failures.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(failure.actor, unreachable.clone_id());
+    assert_eq!(failure.sender, None);
+    assert_eq!(failure.attempts, 3);
+    assert_eq!(failure.rejection, None);
+    assert_eq!(failure.message, bincode::serialize(&2u32).unwrap());
+    // Messages to local Actors are not tracked
+    assert!(failures.recv_timeout(Duration::from_millis(300)).is_err());
+}
@@ -0,0 +1,86 @@
+//! A remote machine that cannot spawn an Actor tells the spawner, which gets SpawnFailed instead of a dangling ActorRef.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2.
+
+mod common;
+
+use actlib::api::*;
+use std::net::SocketAddr;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static SPAWNED: Mutex<Option<Sender<Result<ActorRef, ActlibError>>>> = Mutex::new(None);
+
+#[derive(Debug, Default)]
+struct Spawner {
+    env: Option<Environment>,
+}
+
+impl Actor for Spawner {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+/// Spawn the given type on the first remote machine, waiting for its answer on a worker of the pool.
+fn spawn_remote(spawner: &mut Spawner, actor_type_id: &str) {
+    let env = spawner.env.as_ref().unwrap();
+    let remote = env.machines().into_iter().nth(1).unwrap();
+    let spawned = env.spawn_on(actor_type_id, &remote);
+    let sender = SPAWNED.lock().unwrap();
+    sender.as_ref().unwrap().send(spawned).unwrap();
+}
+
+impl_message_handler!(Spawner: String => |spawner: &mut Spawner, actor_type_id: &String| spawn_remote(spawner, actor_type_id));
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*.
+fn machine(ip: [u8; 4], port: u16, peer: SocketAddr) -> Environment {
+    common::machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Spawner" => Spawner::default()),
+        EnvironmentOptions::new()
+            .runtime(Runtime::Pool)
+            .worker_threads(1),
+    )
+}
+
+#[test]
+fn unknown_remote_types_fail_to_spawn() {
+    let (tx, rx) = channel();
+    *SPAWNED.lock().unwrap() = Some(tx);
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42791));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42792));
+    let second = thread::spawn(move || machine([127, 0, 0, 2], 42792, first_addr));
+    let first = machine([127, 0, 0, 1], 42791, second_addr);
+    let second = second.join().unwrap();
+    let remote = first
+        .machines()
+        .into_iter()
+        .find(|machine| machine.id == second.info().machine_id)
+        .unwrap();
+
+    assert!(matches!(
+        first.spawn_on("Unknown", &remote),
+        Err(ActlibError::SpawnFailed(_))
+    ));
+    assert!(second.list_local_actors(None).is_empty());
+    let spawner = first.spawn_on("Spawner", &remote).unwrap();
+    assert_eq!(
+        second.list_local_actors(Some("Spawner")),
+        vec![spawner.clone_id()]
+    );
+
+    // from a handler, while the only worker of the pool waits for the answer
+    let local_spawner = first.spawn_local("Spawner").unwrap();
+    local_spawner.send_message("Unknown".to_string()).unwrap();
+    assert!(matches!(
+        rx.recv_timeout(Duration::from_secs(10)).unwrap(),
+        Err(ActlibError::SpawnFailed(_))
+    ));
+    local_spawner.send_message("Spawner".to_string()).unwrap();
+    assert!(rx.recv_timeout(Duration::from_secs(10)).unwrap().is_ok());
+}
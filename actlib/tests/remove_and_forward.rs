@@ -0,0 +1,70 @@
+//! A removed Actor hands its queued and later arriving messages to its successor instead of dropping them.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The jobs handled, with whether the steady Actor handled them.
+static HANDLED: Mutex<Vec<(bool, u32)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Job(u32);
+
+#[derive(Debug)]
+struct Worker {
+    steady: bool,
+}
+
+impl Actor for Worker {}
+
+fn work(worker: &mut Worker, Job(n): &Job) {
+    if !worker.steady && *n == 0 {
+        // stuck long enough for the next jobs to queue up
+        thread::sleep(Duration::from_millis(300));
+    }
+    HANDLED.lock().unwrap().push((worker.steady, *n));
+}
+
+impl_message_handler!(Worker: Job => work);
+
+#[test]
+fn queued_and_later_messages_reach_the_successor() {
+    let (mut env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Flaky" => Worker { steady: false },
+        "Steady" => Worker { steady: true },
+    ));
+    let flaky = env.spawn("Flaky").unwrap();
+    let steady = env.spawn("Steady").unwrap();
+    let flaky_id = flaky.clone_id();
+
+    for n in 0..=5 {
+        flaky.send_message(Job(n)).unwrap();
+    }
+    thread::sleep(Duration::from_millis(50));
+    env.remove_and_forward(flaky, &steady, Duration::from_secs(5))
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !env.list_local_actors(Some("Flaky")).is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(env.list_local_actors(Some("Flaky")).is_empty());
+    env.to_actor_ref(flaky_id.clone())
+        .unwrap()
+        .send_message(Job(6))
+        .unwrap();
+
+    let expected: Vec<(bool, u32)> = std::iter::once((false, 0))
+        .chain((1..=6).map(|n| (true, n)))
+        .collect();
+    while *HANDLED.lock().unwrap() != expected && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(*HANDLED.lock().unwrap(), expected);
+    let aliases = env.aliases();
+    assert_eq!(aliases.len(), 1);
+    assert_eq!(aliases[0].from, flaky_id);
+    assert_eq!(aliases[0].forwarded, 6);
+}
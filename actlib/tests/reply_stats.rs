@@ -0,0 +1,80 @@
+//! With reply tracing, every answered ask is counted for the pair of the asking and the answering Actor type.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static REPLIES: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Doubler;
+
+impl Actor for Doubler {}
+
+fn double(_: &mut Doubler, request: &Request<u32>) {
+    std::thread::sleep(Duration::from_millis(20));
+    Context::env().reply(request, request.message * 2).unwrap();
+}
+
+impl_message_handler!(Doubler: Request<u32> => double);
+
+#[derive(Debug)]
+struct Asker;
+
+impl Actor for Asker {}
+
+fn ask_doubler(_: &mut Asker, doubler: &ActorId) {
+    let doubler = Context::env().to_actor_ref(doubler.clone()).unwrap();
+    let reply: u32 = doubler
+        .ask(21u32)
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap();
+    let replies = REPLIES.lock().unwrap();
+    replies.as_ref().unwrap().send(reply).unwrap();
+}
+
+impl_message_handler!(Asker: ActorId => ask_doubler);
+
+#[test]
+fn replies_are_measured_per_requester_and_responder_type() {
+    let (tx, rx) = channel();
+    *REPLIES.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Doubler" => Doubler, "Asker" => Asker),
+        EnvironmentOptions::new().trace_replies(),
+    );
+    let doubler = env.spawn("Doubler").unwrap();
+    let asker = env.spawn("Asker").unwrap();
+
+    for _ in 0..2 {
+        asker.send_message(doubler.clone_id()).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 42);
+    }
+    let reply: u32 = doubler
+        .ask(1u32)
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(reply, 2);
+
+    let stats = env.reply_stats();
+    assert_eq!(stats.len(), 2);
+    let asked_by_actor = stats
+        .iter()
+        .find(|stats| stats.requester_type == "Asker")
+        .unwrap();
+    assert_eq!(asked_by_actor.responder_type, "Doubler");
+    assert_eq!(asked_by_actor.replies, 2);
+    assert!(asked_by_actor.mean_time() >= Duration::from_millis(20));
+    let asked_from_outside = stats
+        .iter()
+        .find(|stats| stats.requester_type == "<outside>")
+        .unwrap();
+    assert_eq!(asked_from_outside.responder_type, "Doubler");
+    assert_eq!(asked_from_outside.replies, 1);
+    assert_eq!(env.introspect().replies.len(), 2);
+}
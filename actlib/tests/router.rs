@@ -0,0 +1,199 @@
+//! A pool spawned behind a router distributes the messages sent to the router over its workers, and can be resized.
+
+use actlib::api::*;
+use actlib::router::ROUTER_TYPE_ID;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WhoAreYou(u32);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Count;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Pause(u64);
+
+impl Keyed for WhoAreYou {
+    type Key = u32;
+
+    fn key(&self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Debug, Default)]
+struct Worker {
+    env: Option<Environment>,
+    own_id: Option<ActorId>,
+    counted: u32,
+}
+
+impl Actor for Worker {
+    fn on_start(&mut self, local_env: Environment, own_ref: ActorRef) {
+        self.env = Some(local_env);
+        self.own_id = Some(own_ref.clone_id());
+    }
+}
+
+impl QueryableActor for Worker {}
+
+fn answer(worker: &mut Worker, request: &Request<WhoAreYou>) {
+    let env = worker.env.as_ref().unwrap();
+    env.reply(request, worker.own_id.clone().unwrap()).unwrap();
+}
+
+impl_message_handler!(Worker:
+    Request<WhoAreYou> => answer,
+    Count => |worker: &mut Worker, _: &Count| worker.counted += 1,
+    Pause => |_: &mut Worker, pause: &Pause| thread::sleep(Duration::from_millis(pause.0)),
+);
+
+fn ask_pool(pool: &ActorRef, n: u32) -> ActorId {
+    pool.ask::<WhoAreYou, ActorId>(WhoAreYou(n))
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap()
+}
+
+fn ask_pool_keyed(pool: &ActorRef, n: u32) -> ActorId {
+    pool.ask_keyed::<WhoAreYou, ActorId>(WhoAreYou(n))
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap()
+}
+
+fn wait_until<F: Fn() -> bool>(condition: F) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(condition());
+}
+
+#[test]
+fn round_robin_takes_turns_over_the_workers() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker::default()));
+    let pool = env.spawn_pool("Worker", 3, RouterKind::RoundRobin).unwrap();
+    assert_eq!(env.pool_workers(&pool).unwrap().len(), 3);
+    assert_eq!(env.list_local_actors(Some(ROUTER_TYPE_ID)).len(), 1);
+
+    let mut answers: HashMap<ActorId, usize> = HashMap::new();
+    for n in 0..6 {
+        *answers.entry(ask_pool(&pool, n)).or_default() += 1;
+    }
+    assert_eq!(answers.len(), 3);
+    assert!(answers.values().all(|answered| *answered == 2));
+}
+
+#[test]
+fn broadcast_reaches_every_worker() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker::default()));
+    let pool = env.spawn_pool("Worker", 3, RouterKind::Broadcast).unwrap();
+
+    pool.send_message(Count).unwrap();
+    pool.send_message(Count).unwrap();
+    for worker in env.pool_workers(&pool).unwrap() {
+        wait_until(|| {
+            worker
+                .query(|w: &Worker| w.counted, Duration::from_secs(1))
+                .unwrap()
+                == 2
+        });
+    }
+}
+
+#[test]
+fn consistent_hash_sends_the_same_key_to_the_same_worker() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker::default()));
+    let pool = env
+        .spawn_pool("Worker", 4, RouterKind::ConsistentHash)
+        .unwrap();
+
+    let first = ask_pool_keyed(&pool, 7);
+    for _ in 0..5 {
+        assert_eq!(ask_pool_keyed(&pool, 7), first);
+    }
+    let by_key: Vec<ActorId> = (0..32).map(|n| ask_pool_keyed(&pool, n)).collect();
+    let mut workers = by_key.clone();
+    workers.sort();
+    workers.dedup();
+    assert!(workers.len() > 1);
+
+    // growing the pool only moves the keys the new worker takes over
+    env.resize_pool(&pool, 5).unwrap();
+    wait_until(|| env.pool_workers(&pool).unwrap().len() == 5);
+    let added = env.pool_workers(&pool).unwrap()[4].clone_id();
+    for (n, worker) in by_key.iter().enumerate() {
+        let now = ask_pool_keyed(&pool, n as u32);
+        assert!(now == *worker || now == added);
+    }
+}
+
+#[test]
+fn pools_are_resized_and_removed_with_their_workers() {
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker::default()));
+    let pool = env.spawn_pool("Worker", 2, RouterKind::RoundRobin).unwrap();
+
+    env.resize_pool(&pool, 5).unwrap();
+    wait_until(|| env.pool_workers(&pool).unwrap().len() == 5);
+    assert_eq!(env.list_local_actors(Some("Worker")).len(), 5);
+
+    env.resize_pool(&pool, 1).unwrap();
+    wait_until(|| env.pool_workers(&pool).unwrap().len() == 1);
+    wait_until(|| env.list_local_actors(Some("Worker")).len() == 1);
+    let remaining = env.pool_workers(&pool).unwrap()[0].clone_id();
+    assert_eq!(ask_pool(&pool, 0), remaining);
+
+    env.remove(pool);
+    wait_until(|| env.list_local_actors(Some("Worker")).is_empty());
+    assert!(env.list_local_actors(Some(ROUTER_TYPE_ID)).is_empty());
+}
+
+#[test]
+fn messages_the_workers_reject_are_reported() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Worker" => Worker::default()),
+        EnvironmentOptions::new().mailbox_quota(
+            "Worker",
+            MailboxQuota {
+                max_bytes: 16,
+                policy: QuotaPolicy::Reject,
+            },
+        ),
+    );
+    let dropped = env.watch_dropped_messages();
+    let pool = env.spawn_pool("Worker", 1, RouterKind::RoundRobin).unwrap();
+    let worker = env.pool_workers(&pool).unwrap()[0].clone_id();
+
+    // the first Pause keeps the worker busy while the others fill its mailbox
+    for _ in 0..5 {
+        pool.send_message(Pause(300)).unwrap();
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let dropped = dropped.recv_timeout(deadline - Instant::now()).unwrap();
+        if dropped.cause == DropCause::MailboxFull {
+            assert_eq!(dropped.target, Some(worker));
+            break;
+        }
+    }
+}
+
+#[test]
+fn unknown_worker_types_fail_the_pool() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Worker" => Worker::default()));
+    assert!(env
+        .spawn_pool("Missing", 2, RouterKind::RoundRobin)
+        .is_err());
+    assert!(env.list_local_actors(Some(ROUTER_TYPE_ID)).is_empty());
+}
@@ -0,0 +1,82 @@
+//! A scatter-gather asks many Actors at once and keeps the replies that arrived before the timeout.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Square(u64);
+
+#[derive(Debug, Default)]
+struct Squarer {
+    env: Option<Environment>,
+}
+
+impl Actor for Squarer {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn square(squarer: &mut Squarer, request: &Request<Square>) {
+    let env = squarer.env.as_ref().unwrap();
+    env.reply(request, request.message.0 * request.message.0)
+        .unwrap();
+}
+
+impl_message_handler!(Squarer: Request<Square> => square);
+
+/// Handles the request, but never replies.
+#[derive(Debug)]
+struct Silent;
+
+impl Actor for Silent {}
+
+impl_message_handler!(Silent: Request<Square> => |_: &mut Silent, _: &Request<Square>| {});
+
+#[test]
+fn every_actor_replies() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Squarer" => Squarer::default()));
+    let squarers: Vec<ActorRef> = (0..4).map(|_| env.spawn("Squarer").unwrap()).collect();
+
+    let responses = env.scatter_gather::<_, u64>(&squarers, Square(7), Duration::from_secs(2));
+    assert_eq!(responses.len(), 4);
+    for (response, squarer) in responses.iter().zip(&squarers) {
+        assert_eq!(response.responder, squarer.clone_id());
+        assert_eq!(response.reply.as_ref().unwrap(), &49);
+    }
+}
+
+#[test]
+fn silent_actors_time_out_without_losing_the_other_replies() {
+    let (mut env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Squarer" => Squarer::default(),
+        "Silent" => Silent,
+    ));
+    let removed = env.spawn("Squarer").unwrap();
+    env.remove(removed.clone());
+    std::thread::sleep(Duration::from_millis(50));
+    let refs = vec![
+        env.spawn("Silent").unwrap(),
+        env.spawn("Squarer").unwrap(),
+        env.spawn("Silent").unwrap(),
+        removed,
+    ];
+
+    let started = Instant::now();
+    let responses = env.scatter_gather::<_, u64>(&refs, Square(3), Duration::from_millis(200));
+    // the silent Actors share the timeout
+    assert!(started.elapsed() < Duration::from_millis(390));
+
+    match &responses[0].reply {
+        Err(ActlibError::Timeout(_)) => {}
+        other => panic!("expected a Timeout, got {:?}", other),
+    }
+    assert_eq!(responses[1].reply.as_ref().unwrap(), &9);
+    match &responses[2].reply {
+        Err(ActlibError::Timeout(_)) => {}
+        other => panic!("expected a Timeout, got {:?}", other),
+    }
+    assert!(responses[3].reply.is_err());
+}
@@ -0,0 +1,24 @@
+//! The example programs, run as scenarios on a local only Environment.
+
+use actlib::api::*;
+use actlib::scenarios::{self, GridScenario, WorkTreeScenario};
+use std::time::Duration;
+
+#[test]
+fn work_tree_sums_up_the_workload() {
+    let (env, _expiration_checker) = Environment::new_local_only(scenarios::actor_builder());
+    let report = scenarios::run_work_tree(&env, &WorkTreeScenario::new(vec![3; 64])).unwrap();
+    assert_eq!(report.result, 384);
+    assert_eq!(report.actors_spawned, 127);
+}
+
+#[test]
+fn grid_keeps_every_player() {
+    let (env, _expiration_checker) = Environment::new_local_only(scenarios::actor_builder());
+    let scenario = GridScenario::new(16)
+        .step(Duration::from_millis(20))
+        .duration(Duration::from_secs(2));
+    let report = scenarios::run_grid(&env, &scenario).unwrap();
+    assert_eq!(report.players_counted, 16);
+    assert!(report.moves > 0);
+}
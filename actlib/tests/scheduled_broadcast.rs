@@ -0,0 +1,54 @@
+//! Delayed and scheduled broadcasts are sent by the timer of the Environment once their time has come.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static HEARD: Mutex<Option<Sender<(u32, Instant)>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Listener;
+
+impl Actor for Listener {}
+
+fn listen(_: &mut Listener, event: &u32) {
+    let heard = HEARD.lock().unwrap();
+    heard
+        .as_ref()
+        .unwrap()
+        .send((*event, Instant::now()))
+        .unwrap();
+}
+
+impl_message_handler!(Listener: u32 => listen);
+
+#[test]
+fn broadcasts_are_sent_once_their_time_has_come() {
+    let (tx, rx) = channel();
+    *HEARD.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Listener" => Listener));
+    env.spawn("Listener").unwrap();
+    env.spawn("Listener").unwrap();
+
+    let scheduled = Instant::now();
+    env.broadcast_after(2u32, Duration::from_millis(200))
+        .unwrap();
+    env.broadcast_at(scheduled + Duration::from_millis(100), 1u32)
+        .unwrap();
+    // the caller is not blocked
+    assert!(scheduled.elapsed() < Duration::from_millis(100));
+    // an instant in the past sends right away
+    env.broadcast_at(scheduled - Duration::from_millis(10), 0u32)
+        .unwrap();
+
+    let heard: Vec<(u32, Instant)> = (0..6)
+        .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    let events: Vec<u32> = heard.iter().map(|(event, _)| *event).collect();
+    assert_eq!(events, vec![0, 0, 1, 1, 2, 2]);
+    for (event, at) in heard {
+        assert!(at >= scheduled + Duration::from_millis(100) * event);
+    }
+}
@@ -0,0 +1,47 @@
+//! The SelfSendGuard applies its YieldPolicy to an Actor that keeps sending Messages to itself, without breaking its loop.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static DONE: Mutex<Option<Sender<()>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Looper;
+
+impl Actor for Looper {}
+
+fn count_down(_: &mut Looper, remaining: &u32) {
+    if *remaining == 0 {
+        DONE.lock().unwrap().as_ref().unwrap().send(()).unwrap();
+    } else {
+        Context::self_ref().send_message(remaining - 1).unwrap();
+    }
+}
+
+impl_message_handler!(Looper: u32 => count_down);
+
+#[test]
+fn self_sending_actors_are_slowed_down() {
+    let (tx, rx) = channel();
+    *DONE.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Looper" => Looper),
+        EnvironmentOptions::new().self_send_guard(SelfSendGuard {
+            max_depth: 5,
+            max_ratio: 0.5,
+            window: 10,
+            yield_policy: YieldPolicy::Sleep(Duration::from_millis(20)),
+        }),
+    );
+    let looper = env.spawn("Looper").unwrap();
+
+    let start = Instant::now();
+    looper.send_message(10u32).unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    // every self-sending handler after the first one sleeps
+    assert!(start.elapsed() >= Duration::from_millis(9 * 20));
+}
@@ -0,0 +1,78 @@
+//! Handlers learn who sent the current message through the Context, and answer it without an id in the message.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static PONGS: Mutex<Option<Sender<(u32, Option<ActorId>)>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Ping(u32);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Pong(u32);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Start(ActorId);
+
+#[derive(Debug)]
+struct Pinger;
+
+impl Actor for Pinger {}
+
+fn start(_: &mut Pinger, Start(ponger): &Start) {
+    let ponger = Context::env().to_actor_ref(ponger.clone()).unwrap();
+    ponger.send_message(Ping(7)).unwrap();
+}
+
+fn pong(_: &mut Pinger, Pong(n): &Pong) {
+    let pongs = PONGS.lock().unwrap();
+    pongs
+        .as_ref()
+        .unwrap()
+        .send((*n, Context::sender_id()))
+        .unwrap();
+}
+
+impl_message_handler!(Pinger: Start => start, Pong => pong);
+
+#[derive(Debug)]
+struct Ponger;
+
+impl Actor for Ponger {}
+
+fn ping(_: &mut Ponger, Ping(n): &Ping) {
+    match Context::sender() {
+        Some(sender) => sender.send_message(Pong(*n)).unwrap(),
+        None => {
+            let pongs = PONGS.lock().unwrap();
+            pongs.as_ref().unwrap().send((*n, None)).unwrap();
+        }
+    }
+}
+
+impl_message_handler!(Ponger: Ping => ping);
+
+#[test]
+fn handlers_answer_the_sender() {
+    let (tx, rx) = channel();
+    *PONGS.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Pinger" => Pinger,
+        "Ponger" => Ponger
+    ));
+    let pinger = env.spawn("Pinger").unwrap();
+    let ponger = env.spawn("Ponger").unwrap();
+
+    pinger.send_message(Start(ponger.clone_id())).unwrap();
+    let (n, sender) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(n, 7);
+    assert_eq!(sender, Some(ponger.clone_id()));
+
+    // a message from outside of an Actor has no sender
+    ponger.send_message(Ping(8)).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), (8, None));
+    assert!(Context::sender_id().is_none());
+}
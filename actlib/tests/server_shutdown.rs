@@ -0,0 +1,59 @@
+//! The server accepting remote machines reports the address it listens on, and releases its port once the Environment expired.
+//!
+//! Both machines run in this process, on 127.0.0.1 and 127.0.0.2; the second one serves the first.
+
+mod common;
+
+use actlib::api::*;
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Idle;
+
+impl Actor for Idle {}
+
+impl_message_handler!(Idle: u32 => |_: &mut Idle, _: &u32| {});
+
+/// Start a machine on *ip* and *port*, connected to the machine at *peer*.
+fn machine(
+    ip: [u8; 4],
+    port: u16,
+    peer: SocketAddr,
+) -> (Environment, EnvironmentExpirationChecker) {
+    common::start_machine(
+        ip,
+        port,
+        peer,
+        actor_builder!("Idle" => Idle),
+        EnvironmentOptions::new(),
+    )
+}
+
+/// Connect both machines, returning the address the second one listens on.
+fn connect_and_shut_down() -> Option<SocketAddr> {
+    let first_addr = SocketAddr::from(([127, 0, 0, 1], 42761));
+    let second_addr = SocketAddr::from(([127, 0, 0, 2], 42762));
+    let second = thread::spawn(move || machine([127, 0, 0, 2], 42762, first_addr));
+    let (first, first_checker) = machine([127, 0, 0, 1], 42761, second_addr);
+    let (second, second_checker) = second.join().unwrap();
+    let listen_addr = second.info().listen_addr;
+
+    first.shutdown(Duration::from_millis(200)).unwrap();
+    second.shutdown(Duration::from_millis(200)).unwrap();
+    first_checker.wait_until_expiration().unwrap();
+    second_checker.wait_until_expiration().unwrap();
+    assert_eq!(second.info().listen_addr, None);
+    listen_addr
+}
+
+#[test]
+fn the_port_is_released_on_expiration() {
+    let listen_addr = connect_and_shut_down().unwrap();
+    assert_eq!(listen_addr, SocketAddr::from(([127, 0, 0, 2], 42762)));
+    drop(TcpListener::bind(listen_addr).unwrap());
+
+    // the next Environment serves on the same port
+    assert_eq!(connect_and_shut_down(), Some(listen_addr));
+}
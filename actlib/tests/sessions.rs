@@ -0,0 +1,102 @@
+//! Every client of a SessionServer is bound to a session Actor of its own, which lives as long as the connection.
+
+use actlib::api::*;
+use actlib::session::{SessionInput, SessionOpened, SessionServer};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static STOPPED: Mutex<Option<Sender<StopReason>>> = Mutex::new(None);
+
+/// Answers every frame with the number of frames its client sent so far, closes the session on "bye".
+#[derive(Debug, Default)]
+struct Counting {
+    frames: u32,
+}
+
+impl Actor for Counting {
+    fn on_stop_with_reason(&mut self, reason: StopReason) {
+        let stopped = STOPPED.lock().unwrap();
+        stopped.as_ref().unwrap().send(reason).unwrap();
+    }
+}
+
+fn opened(_: &mut Counting, opened: &SessionOpened) {
+    assert!(opened.session.send(b"hello"));
+}
+
+fn input(counting: &mut Counting, input: &SessionInput) {
+    if input.payload == b"bye" {
+        input.session.close();
+        return;
+    }
+    counting.frames += 1;
+    assert!(input.session.send(counting.frames.to_string().as_bytes()));
+}
+
+impl_message_handler!(Counting:
+    SessionOpened => opened,
+    SessionInput => input
+);
+
+fn send(stream: &mut TcpStream, payload: &[u8]) {
+    stream
+        .write_all(&(payload.len() as u32).to_be_bytes())
+        .unwrap();
+    stream.write_all(payload).unwrap();
+}
+
+fn receive(stream: &mut TcpStream) -> Vec<u8> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len).unwrap();
+    let mut payload = vec![0; u32::from_be_bytes(len) as usize];
+    stream.read_exact(&mut payload).unwrap();
+    payload
+}
+
+fn connect(addr: SocketAddr) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(receive(&mut stream), b"hello");
+    stream
+}
+
+#[test]
+fn every_client_has_a_session_of_its_own() {
+    let (tx, rx) = channel();
+    *STOPPED.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Counting" => Counting::default()));
+    let addr = SessionServer::new(env.clone(), "Counting")
+        .serve(SocketAddr::from(([127, 0, 0, 1], 0)))
+        .unwrap();
+
+    let mut first = connect(addr);
+    let mut second = connect(addr);
+    send(&mut first, b"a");
+    send(&mut first, b"b");
+    assert_eq!(receive(&mut first), b"1");
+    assert_eq!(receive(&mut first), b"2");
+    send(&mut second, b"c");
+    assert_eq!(receive(&mut second), b"1");
+
+    // the session Actor closes the connection
+    send(&mut first, b"bye");
+    let mut rest = Vec::new();
+    assert_eq!(first.read_to_end(&mut rest).unwrap(), 0);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        StopReason::Removed
+    );
+
+    // the client disconnects
+    drop(second);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        StopReason::Removed
+    );
+}
@@ -0,0 +1,76 @@
+//! The shutdown report tells for every Actor whether its on_stop method completed, panicked or did not run in time.
+
+use actlib::api::*;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Calm;
+
+impl Actor for Calm {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(Calm: u32 => |_: &mut Calm, _: &u32| {});
+
+#[derive(Debug)]
+struct Clumsy;
+
+impl Actor for Clumsy {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
+        panic!("dropped the cleanup");
+    }
+}
+
+impl_message_handler!(Clumsy: u32 => |_: &mut Clumsy, _: &u32| {});
+
+#[derive(Debug)]
+struct Busy;
+
+impl Actor for Busy {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(Busy: u32 => |_: &mut Busy, _: &u32| thread::sleep(Duration::from_secs(2)));
+
+#[test]
+fn report_lists_every_on_stop_outcome() {
+    // the sleeping handler occupies its worker, the other Actors stop on the remaining ones
+    let (env, expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!(
+            "Calm" => Calm,
+            "Clumsy" => Clumsy,
+            "Busy" => Busy
+        ),
+        EnvironmentOptions::new().worker_threads(3),
+    );
+    let calm = env.spawn("Calm").unwrap();
+    let clumsy = env.spawn("Clumsy").unwrap();
+    let busy = env.spawn("Busy").unwrap();
+    busy.send_message(1u32).unwrap();
+    thread::sleep(Duration::from_millis(100));
+
+    env.shutdown(Duration::from_millis(500)).unwrap();
+    let result = expiration_checker.wait_until_expiration().unwrap();
+
+    let outcome_of = |actor_ref: &ActorRef| {
+        result
+            .stop_records()
+            .into_iter()
+            .find(|record| record.actor == actor_ref.clone_id())
+            .map(|record| record.outcome)
+    };
+    assert_eq!(outcome_of(&calm), Some(StopOutcome::Completed));
+    assert!(matches!(
+        outcome_of(&clumsy),
+        Some(StopOutcome::Panicked(_))
+    ));
+    assert_eq!(outcome_of(&busy), Some(StopOutcome::TimedOut));
+    assert_eq!(result.actors_panicked(), vec![clumsy.clone_id()]);
+    assert_eq!(result.actors_not_stopped(), vec![busy.clone_id()]);
+    assert!(!result.is_clean());
+}
@@ -0,0 +1,59 @@
+//! Once the Environment is shutting down, spawns and broadcasts fail with a typed error while mailboxes are still drained.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static HANDLED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Slow;
+
+impl Actor for Slow {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+fn handle(_slow: &mut Slow, msg: &u32) {
+    thread::sleep(Duration::from_millis(200));
+    HANDLED
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .send(*msg)
+        .unwrap();
+}
+
+impl_message_handler!(Slow: u32 => handle);
+
+#[test]
+fn spawns_and_broadcasts_fail_but_messages_are_drained() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+    let (env, expiration_checker) = Environment::new_local_only(actor_builder!("Slow" => Slow));
+    let slow = env.spawn("Slow").unwrap();
+    slow.send_message(1u32).unwrap();
+    slow.send_message(2u32).unwrap();
+    env.broadcast(3u32).unwrap();
+    assert!(!env.is_shutting_down());
+
+    env.shutdown(Duration::from_secs(5)).unwrap();
+    assert!(env.is_shutting_down());
+    assert!(matches!(
+        env.spawn("Slow"),
+        Err(ActlibError::ShuttingDown(_))
+    ));
+    assert!(matches!(
+        env.broadcast(4u32),
+        Err(ActlibError::ShuttingDown(_))
+    ));
+
+    let result = expiration_checker.wait_until_expiration().unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 1);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 2);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(1)).unwrap(), 3);
+    assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+    assert!(result.is_clean());
+}
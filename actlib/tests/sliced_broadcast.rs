@@ -0,0 +1,45 @@
+//! A broadcast in slices reaches every local Actor over several timer ticks and reports its completion.
+
+use actlib::api::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct Resident;
+
+impl Actor for Resident {}
+
+fn receive(_: &mut Resident, _: &String) {
+    RECEIVED.fetch_add(1, Ordering::SeqCst);
+}
+
+impl_message_handler!(Resident: String => receive);
+
+#[test]
+fn every_actor_gets_its_slice() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Resident" => Resident));
+    for _ in 0..250 {
+        env.spawn("Resident").unwrap();
+    }
+
+    let completed = env.broadcast_in_slices(
+        "earthquake".to_string(),
+        BroadcastSlices::new()
+            .slice_size(100)
+            .interval(Duration::from_millis(20)),
+    );
+    let completed = completed.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(completed.actors, 250);
+    assert_eq!(completed.slices, 3);
+    assert!(completed.elapsed >= Duration::from_millis(40));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while RECEIVED.load(Ordering::SeqCst) < 250 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(RECEIVED.load(Ordering::SeqCst), 250);
+}
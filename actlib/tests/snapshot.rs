@@ -0,0 +1,94 @@
+//! A snapshot describes the local Actors with their exported state, restoring it respawns them with their state.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct Counter {
+    count: u32,
+}
+
+impl Actor for Counter {
+    fn export_state(&self) -> Option<ExportedState> {
+        ExportedState::new(&CounterState(self.count)).ok()
+    }
+
+    fn import_state(&mut self, state: ExportedState) {
+        self.count = state.deserialize::<CounterState>().unwrap().0;
+    }
+}
+
+impl QueryableActor for Counter {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CounterState(u32);
+
+fn add(counter: &mut Counter, n: &u32) {
+    counter.count += n;
+}
+
+impl_message_handler!(Counter: u32 => add);
+
+#[derive(Debug)]
+struct Stateless;
+
+impl Actor for Stateless {}
+
+impl_message_handler!(Stateless: u32 => |_: &mut Stateless, _: &u32| {});
+
+fn builder() -> ActorBuilder {
+    actor_builder!("Counter" => Counter::default(), "Stateless" => Stateless)
+}
+
+fn count(counter: &ActorRef) -> u32 {
+    counter
+        .query(|counter: &Counter| counter.count, Duration::from_secs(1))
+        .unwrap()
+}
+
+/// Wait until the Counter counted up to *expected*.
+fn wait_for_count(counter: &ActorRef, expected: u32) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while count(counter) != expected && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn snapshots_are_restored_with_their_state() {
+    let (env, _expiration_checker) = Environment::new_local_only(builder());
+    let keyed = env.spawn_with_id("Counter", b"keyed".to_vec()).unwrap();
+    let automatic = env.spawn("Counter").unwrap();
+    env.spawn("Stateless").unwrap();
+    keyed.send_message(5u32).unwrap();
+    automatic.send_message(7u32).unwrap();
+    wait_for_count(&keyed, 5);
+    wait_for_count(&automatic, 7);
+
+    let snapshot = env.snapshot(Duration::from_secs(1));
+    assert_eq!(snapshot.actors.len(), 3);
+    let stateless = snapshot
+        .actors
+        .iter()
+        .find(|actor| actor.type_id == "Stateless")
+        .unwrap();
+    assert_eq!(stateless.state, None);
+    // e.g. written to disk by the previous run
+    let snapshot: Snapshot = bincode::deserialize(&bincode::serialize(&snapshot).unwrap()).unwrap();
+
+    let (restored_env, _restored_checker) = Environment::new_local_only(builder());
+    let restored = restored_env.restore(&snapshot);
+    assert_eq!(restored.len(), 3);
+    let restored: Vec<ActorRef> = restored.into_iter().map(Result::unwrap).collect();
+    let counts: Vec<(Option<Vec<u8>>, u32)> = snapshot
+        .actors
+        .iter()
+        .zip(restored.iter())
+        .filter(|(actor, _)| actor.type_id == "Counter")
+        .map(|(_, actor_ref)| (actor_ref.clone_id().when_specified(), count(actor_ref)))
+        .collect();
+    assert!(counts.contains(&(Some(b"keyed".to_vec()), 5)));
+    assert!(counts.contains(&(None, 7)));
+}
@@ -0,0 +1,68 @@
+//! A child spawned with Context::spawn_after_handle starts only once its parent's handler returned.
+
+use actlib::api::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static EVENTS: Mutex<Option<Sender<&'static str>>> = Mutex::new(None);
+static PARENT_DONE: AtomicBool = AtomicBool::new(false);
+
+fn record(event: &'static str) {
+    let events = EVENTS.lock().unwrap();
+    events.as_ref().unwrap().send(event).unwrap();
+}
+
+#[derive(Debug)]
+struct Child;
+
+impl Actor for Child {
+    fn on_start(&mut self, _: Environment, _: ActorRef) {
+        if PARENT_DONE.load(Ordering::SeqCst) {
+            record("child started after the parent's handler");
+        } else {
+            record("child started during the parent's handler");
+        }
+    }
+}
+
+impl_message_handler!(Child: u32 => |_: &mut Child, _: &u32| record("child handled"));
+
+#[derive(Debug)]
+struct Parent;
+
+impl Actor for Parent {}
+
+fn spawn_child(_: &mut Parent, _: &u32) {
+    let child = Context::spawn_after_handle("Child", SpawnOptions::new()).unwrap();
+    child.send_message(1u32).unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+    PARENT_DONE.store(true, Ordering::SeqCst);
+}
+
+impl_message_handler!(Parent: u32 => spawn_child);
+
+#[test]
+fn children_start_after_the_handler_returned() {
+    let (tx, rx) = channel();
+    *EVENTS.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Parent" => Parent, "Child" => Child));
+    let parent = env.spawn("Parent").unwrap();
+
+    parent.send_message(0u32).unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        "child started after the parent's handler"
+    );
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        "child handled"
+    );
+
+    match Context::spawn_after_handle("Child", SpawnOptions::new()) {
+        Err(ActlibError::InvalidState(_)) => {}
+        other => panic!("spawned outside of an Actor: {:?}", other),
+    }
+}
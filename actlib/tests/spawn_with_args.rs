@@ -0,0 +1,64 @@
+//! Actors spawned with arguments receive them in on_init before they start.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Position {
+    x: i64,
+    y: i64,
+}
+
+#[derive(Debug, Default)]
+struct Field {
+    position: Option<(i64, i64)>,
+    position_at_start: Option<(i64, i64)>,
+}
+
+impl Actor for Field {
+    fn on_init(&mut self, args: &[u8]) {
+        let Position { x, y } = bincode::deserialize(args).unwrap();
+        self.position = Some((x, y));
+    }
+
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {
+        self.position_at_start = self.position;
+    }
+}
+
+impl QueryableActor for Field {}
+
+impl_message_handler!(Field: () => |_: &mut Field, _: &()| {});
+
+#[test]
+fn arguments_are_passed_before_on_start() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Field" => Field::default()));
+
+    let field = env
+        .spawn_with_args("Field", Position { x: 3, y: -2 })
+        .unwrap();
+    let position = field
+        .query(|f: &Field| f.position_at_start, Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(position, Some((3, -2)));
+
+    let plain = env.spawn("Field").unwrap();
+    let position = plain
+        .query(|f: &Field| f.position, Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(position, None);
+}
+
+#[test]
+fn arguments_that_do_not_fit_fail_the_spawn() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Field" => Field::default()));
+
+    match env.spawn_with_args("Field", 7u8) {
+        Err(ActlibError::SpawnFailed(reason)) => assert!(reason.contains("on_init")),
+        other => panic!("expected SpawnFailed, got {:?}", other),
+    }
+    assert!(env.list_local_actors(Some("Field")).is_empty());
+}
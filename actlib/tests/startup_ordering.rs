@@ -0,0 +1,95 @@
+//! Messages sent right after spawning an Actor must never be handled before its on_start method completed,
+//! they are handled afterwards or dropped, following the PreStartMessages policy.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How long every on_start blocks, so messages surely arrive while it is running.
+const START_DELAY: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record(u32);
+
+static DELIVER_EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static DISCARD_EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct DeliverActor {
+    own_ref: Option<ActorRef>,
+}
+
+impl Actor for DeliverActor {
+    fn on_start(&mut self, _local_env: Environment, own_ref: ActorRef) {
+        thread::sleep(START_DELAY);
+        self.own_ref = Some(own_ref);
+        DELIVER_EVENTS.lock().unwrap().push("start".to_string());
+    }
+}
+
+fn deliver_record(actor: &mut DeliverActor, Record(i): &Record) {
+    // panics if a handler ever runs before on_start
+    let _ = actor.own_ref.as_ref().unwrap();
+    DELIVER_EVENTS.lock().unwrap().push(format!("record {}", i));
+}
+
+impl_message_handler!(DeliverActor: Record => deliver_record);
+
+#[derive(Debug)]
+struct DiscardActor;
+
+impl Actor for DiscardActor {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {
+        thread::sleep(START_DELAY);
+        DISCARD_EVENTS.lock().unwrap().push("start".to_string());
+    }
+}
+
+fn discard_record(_actor: &mut DiscardActor, Record(i): &Record) {
+    DISCARD_EVENTS.lock().unwrap().push(format!("record {}", i));
+}
+
+impl_message_handler!(DiscardActor: Record => discard_record);
+
+#[test]
+fn messages_are_handled_after_on_start_in_order() {
+    let (env, _expiration_checker) = Environment::new_local_only(
+        actor_builder!("DeliverActor" => DeliverActor { own_ref: None }),
+    );
+    let actor_ref = env.spawn("DeliverActor").unwrap();
+    for i in 0..5 {
+        actor_ref.send_message(Record(i)).unwrap();
+    }
+    thread::sleep(START_DELAY * 3);
+
+    let events = DELIVER_EVENTS.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        vec!["start", "record 0", "record 1", "record 2", "record 3", "record 4"]
+    );
+}
+
+#[test]
+fn messages_before_on_start_are_discarded_on_request() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("DiscardActor" => DiscardActor),
+        EnvironmentOptions::new().pre_start_messages(PreStartMessages::Discard),
+    );
+    let dropped = env.watch_dropped_messages();
+    let actor_ref = env.spawn("DiscardActor").unwrap();
+    actor_ref.send_message(Record(0)).unwrap();
+    thread::sleep(START_DELAY * 2);
+    actor_ref.send_message(Record(1)).unwrap();
+    thread::sleep(START_DELAY);
+
+    let events = DISCARD_EVENTS.lock().unwrap().clone();
+    assert_eq!(events, vec!["start", "record 1"]);
+    let early = dropped.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(early.cause, DropCause::BeforeStart);
+    assert_eq!(early.target, Some(actor_ref.clone_id()));
+    assert_eq!(env.metrics().messages_dropped[&DropCause::BeforeStart], 1);
+}
@@ -0,0 +1,57 @@
+//! An Actor stashes the messages arriving before its initial state, and handles them in order once it is ready.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static HANDLED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Init(u32);
+
+#[derive(Debug, Default)]
+struct Lazy {
+    offset: Option<u32>,
+}
+
+impl Actor for Lazy {}
+
+fn init(lazy: &mut Lazy, Init(offset): &Init) {
+    lazy.offset = Some(*offset);
+    Context::unstash_all();
+}
+
+fn number(lazy: &mut Lazy, n: &u32) {
+    match lazy.offset {
+        Some(offset) => {
+            let handled = HANDLED.lock().unwrap();
+            handled.as_ref().unwrap().send(offset + n).unwrap();
+        }
+        None => Context::stash(),
+    }
+}
+
+impl_message_handler!(Lazy: Init => init, u32 => number);
+
+#[test]
+fn stashed_messages_are_handled_in_order_after_unstash_all() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Lazy" => Lazy::default()));
+    let lazy = env.spawn("Lazy").unwrap();
+
+    for n in 0..10u32 {
+        lazy.send_message(n).unwrap();
+    }
+    // nothing is handled before the Actor is ready
+    assert!(rx.recv_timeout(Duration::from_millis(300)).is_err());
+
+    lazy.send_message(Init(100)).unwrap();
+    lazy.send_message(10u32).unwrap();
+    for expected in 100..111 {
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), expected);
+    }
+}
@@ -0,0 +1,76 @@
+//! A configured StateDump periodically writes the introspection data to a rolling set of compressed files.
+
+use actlib::api::*;
+use flate2::read::GzDecoder;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Idler;
+
+impl Actor for Idler {}
+
+impl_message_handler!(Idler: u32 => |_: &mut Idler, _: &u32| {});
+
+fn dump_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
+
+fn dump_files(directory: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Run an Environment with one Actor for a while, dumping its state every 50 ms.
+fn dump_for_a_while(directory: &Path, state_dump: StateDump) -> String {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Idler" => Idler),
+        EnvironmentOptions::new().state_dump(state_dump.interval(Duration::from_millis(50))),
+    );
+    env.spawn("Idler").unwrap();
+    thread::sleep(Duration::from_millis(600));
+    let machine_id = env.info().machine_id.to_string();
+    drop(env);
+    thread::sleep(Duration::from_millis(100));
+    assert!(directory.exists());
+    machine_id
+}
+
+#[test]
+fn only_the_newest_dumps_are_kept() {
+    let directory = dump_directory("state_dump_keep");
+    let machine_id = dump_for_a_while(&directory, StateDump::new(&directory).keep(3));
+
+    let files = dump_files(&directory);
+    assert_eq!(files.len(), 3);
+    let newest = files.last().unwrap();
+    assert!(newest
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with(&machine_id));
+    let introspection: serde_json::Value =
+        serde_json::from_reader(GzDecoder::new(std::fs::File::open(newest).unwrap())).unwrap();
+    assert!(introspection.to_string().contains("Idler"));
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn the_newest_dump_is_kept_at_least() {
+    let directory = dump_directory("state_dump_keep_none");
+    dump_for_a_while(&directory, StateDump::new(&directory).keep(0));
+
+    assert_eq!(dump_files(&directory).len(), 1);
+
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,143 @@
+//! The StopPolicy of an Actor type decides whether the Messages left in its mailbox at its removal are dropped, handled or handed over to on_stop_with_pending.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static EVENTS: Mutex<Vec<(&str, String)>> = Mutex::new(Vec::new());
+
+fn record(tag: &'static str, event: String) {
+    EVENTS.lock().unwrap().push((tag, event));
+}
+
+/// Wait until the Actor tagged *tag* stopped, returning what it recorded.
+fn events_of(tag: &str) -> Vec<String> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let events: Vec<String> = EVENTS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(t, _)| *t == tag)
+            .map(|(_, event)| event.clone())
+            .collect();
+        if events.iter().any(|event| event.starts_with("stopped")) || Instant::now() > deadline {
+            return events;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Stashes updates until it is ready, and unstashes them on the next Tick.
+#[derive(Debug)]
+struct Flusher {
+    tag: &'static str,
+    ready: bool,
+}
+
+impl Flusher {
+    fn new(tag: &'static str) -> Self {
+        Flusher { tag, ready: false }
+    }
+}
+
+impl Actor for Flusher {
+    fn on_stop_with_reason(&mut self, reason: StopReason) {
+        record(self.tag, format!("stopped {:?}", reason));
+    }
+
+    fn on_stop_with_pending(&mut self, reason: StopReason, pending: Vec<Box<dyn Any + Send>>) {
+        let pending: Vec<u32> = pending
+            .into_iter()
+            .filter_map(|msg| msg.downcast::<u32>().ok())
+            .map(|msg| *msg)
+            .collect();
+        record(self.tag, format!("flushed {:?}", pending));
+        self.on_stop_with_reason(reason);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Ready;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Tick;
+
+fn update(flusher: &mut Flusher, update: &u32) {
+    if flusher.ready {
+        record(flusher.tag, format!("handled {}", update));
+    } else {
+        Context::stash();
+    }
+}
+
+fn ready(flusher: &mut Flusher, _: &Ready) {
+    flusher.ready = true;
+}
+
+fn tick(_: &mut Flusher, _: &Tick) {
+    Context::unstash_all();
+}
+
+impl_message_handler!(Flusher: u32 => update, Ready => ready, Tick => tick);
+
+/// Remove an Actor of the given type whose updates are still stashed.
+fn remove_with_stashed_updates(type_id: &str, options: EnvironmentOptions) {
+    let (mut env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!(
+            "Dropping" => Flusher::new("Dropping"),
+            "Draining" => Flusher::new("Draining"),
+            "HandingOver" => Flusher::new("HandingOver")
+        ),
+        options,
+    );
+    let flusher = env.spawn(type_id).unwrap();
+    for update in 1..=3u32 {
+        flusher.send_message(update).unwrap();
+    }
+    flusher.send_message(Ready).unwrap();
+    env.remove(flusher);
+}
+
+#[test]
+fn remaining_messages_are_dropped_by_default() {
+    remove_with_stashed_updates("Dropping", EnvironmentOptions::new());
+    assert_eq!(events_of("Dropping"), vec!["stopped Removed".to_string()]);
+}
+
+#[test]
+fn remaining_messages_are_handled_before_stopping() {
+    remove_with_stashed_updates(
+        "Draining",
+        EnvironmentOptions::new().stop_policy("Draining", StopPolicy::DrainThenStop),
+    );
+    assert_eq!(
+        events_of("Draining"),
+        vec![
+            "handled 1".to_string(),
+            "handled 2".to_string(),
+            "handled 3".to_string(),
+            "stopped Removed".to_string()
+        ]
+    );
+}
+
+#[test]
+fn remaining_messages_are_handed_over_to_on_stop() {
+    remove_with_stashed_updates(
+        "HandingOver",
+        EnvironmentOptions::new().stop_policy("HandingOver", StopPolicy::HandOver),
+    );
+    assert_eq!(
+        events_of("HandingOver"),
+        vec![
+            "flushed [1, 2, 3]".to_string(),
+            "stopped Removed".to_string()
+        ]
+    );
+}
@@ -0,0 +1,70 @@
+//! on_stop_with_reason tells an Actor why it stops, Actors implementing the deprecated on_stop keep working.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static STOPPED: Mutex<Option<Sender<String>>> = Mutex::new(None);
+
+fn report(event: String) {
+    STOPPED
+        .lock()
+        .unwrap()
+        .as_ref()
+        .unwrap()
+        .send(event)
+        .unwrap();
+}
+
+#[derive(Debug)]
+struct Reasoned;
+
+impl Actor for Reasoned {
+    fn on_stop_with_reason(&mut self, reason: StopReason) {
+        report(format!("Reasoned {:?}", reason));
+    }
+}
+
+impl_message_handler!(Reasoned: u32 => |_: &mut Reasoned, _: &u32| {});
+
+/// Written against the hook without a StopReason.
+#[derive(Debug)]
+struct Legacy;
+
+impl Actor for Legacy {
+    fn on_stop(&mut self) {
+        report("Legacy".to_string());
+    }
+}
+
+impl_message_handler!(Legacy: u32 => |_: &mut Legacy, _: &u32| {});
+
+#[test]
+fn on_stop_is_told_the_reason() {
+    let (tx, rx) = channel();
+    *STOPPED.lock().unwrap() = Some(tx);
+    let (mut env, expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Reasoned" => Reasoned,
+        "Legacy" => Legacy
+    ));
+
+    let removed = env.spawn("Reasoned").unwrap();
+    env.remove(removed);
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        "Reasoned Removed"
+    );
+
+    let legacy = env.spawn("Legacy").unwrap();
+    env.remove(legacy);
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "Legacy");
+
+    env.spawn("Reasoned").unwrap();
+    env.set_expired().unwrap();
+    expiration_checker.wait_until_expiration().unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        "Reasoned Expired"
+    );
+}
@@ -0,0 +1,27 @@
+//! A synthetic workload on a local only Environment.
+
+use actlib::api::*;
+use actlib::stress::{self, StressWorkload};
+use std::time::Duration;
+
+#[test]
+fn every_message_is_echoed() {
+    let (env, _expiration_checker) = Environment::new_local_only(stress::actor_builder());
+    let workload = StressWorkload::new(8, 500)
+        .payload_size(256)
+        .duration(Duration::from_secs(1));
+    let report = stress::run(&env, &workload).unwrap();
+    assert_eq!(report.local_actors, 8);
+    assert_eq!(report.remote_actors, 0);
+    assert!(report.messages_sent >= 400);
+    assert_eq!(report.messages_echoed, report.messages_sent);
+    assert!(report.latency_percentile(50.0) <= report.latency_percentile(99.0));
+    assert!(report.latency_percentile(99.0).is_some());
+}
+
+#[test]
+fn remote_sinks_need_a_remote_machine() {
+    let (env, _expiration_checker) = Environment::new_local_only(stress::actor_builder());
+    let workload = StressWorkload::new(4, 100).remote_share(0.5);
+    assert!(stress::run(&env, &workload).is_err());
+}
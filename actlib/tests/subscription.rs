@@ -0,0 +1,77 @@
+//! The updates an Actor publishes reach its subscribers as a Stream, which ends with the publisher.
+
+use actlib::api::*;
+use actlib::subscription::*;
+use futures::executor::block_on;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Price(u32);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Tick(u32);
+
+#[derive(Debug, Default)]
+struct Ticker {
+    subscribers: Subscribers<Price>,
+}
+
+impl Actor for Ticker {}
+
+impl QueryableActor for Ticker {}
+
+impl_message_handler!(Ticker:
+    Subscribe<Price> => |ticker: &mut Ticker, request: &Subscribe<Price>| ticker.subscribers.subscribe(request),
+    Unsubscribe<Price> => |ticker: &mut Ticker, request: &Unsubscribe<Price>| ticker.subscribers.unsubscribe(request),
+    Tick => |ticker: &mut Ticker, Tick(n): &Tick| ticker.subscribers.publish(&Price(*n)),
+);
+
+fn wait_for_subscribers(ticker: &ActorRef, expected: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let current = || {
+        ticker
+            .query(|t: &Ticker| t.subscribers.len(), Duration::from_secs(1))
+            .unwrap()
+    };
+    while current() != expected && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(current(), expected);
+}
+
+#[test]
+fn published_updates_arrive_until_the_stream_is_dropped() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Ticker" => Ticker::default()));
+    let ticker = env.spawn("Ticker").unwrap();
+
+    let prices = ticker.subscribe::<Price>(&env).unwrap();
+    wait_for_subscribers(&ticker, 1);
+    for n in 1..=3 {
+        ticker.send_message(Tick(n)).unwrap();
+    }
+    let received: Vec<Price> = block_on(prices.take(3).collect());
+    assert_eq!(received, vec![Price(1), Price(2), Price(3)]);
+
+    // take dropped the stream, which unsubscribed and removed the proxy
+    wait_for_subscribers(&ticker, 0);
+    assert!(env.list_local_actors(Some(SUBSCRIPTION_TYPE_ID)).is_empty());
+}
+
+#[test]
+fn the_stream_ends_once_the_publisher_stopped() {
+    let (mut env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Ticker" => Ticker::default()));
+    let ticker = env.spawn("Ticker").unwrap();
+
+    let mut prices = ticker.subscribe::<Price>(&env).unwrap();
+    wait_for_subscribers(&ticker, 1);
+    ticker.send_message(Tick(7)).unwrap();
+    env.remove(ticker);
+
+    assert_eq!(block_on(prices.next()), Some(Price(7)));
+    assert_eq!(block_on(prices.next()), None);
+}
@@ -0,0 +1,86 @@
+//! Serialized Messages carry the tag of their type, so structurally similar types are not mistaken for one another.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Meters(u64);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Seconds(u64);
+
+#[derive(Debug, Default)]
+struct Odometer {
+    meters: u64,
+    seconds: u64,
+}
+
+impl Actor for Odometer {}
+
+impl QueryableActor for Odometer {}
+
+impl_message_handler!(Odometer:
+    Meters => |odometer: &mut Odometer, Meters(m): &Meters| odometer.meters += m,
+    Seconds => |odometer: &mut Odometer, Seconds(s): &Seconds| odometer.seconds += s,
+);
+
+#[derive(Debug, Default)]
+struct Stopwatch {
+    meters: u64,
+    seconds: u64,
+}
+
+impl Actor for Stopwatch {}
+
+impl QueryableActor for Stopwatch {}
+
+impl RegisteredHandlers for Stopwatch {
+    fn handlers() -> HandlerRegistry<Self> {
+        HandlerRegistry::<Self>::new()
+            .on::<Meters>(|stopwatch, Meters(m), _ctx| stopwatch.meters += m)
+            .on::<Seconds>(|stopwatch, Seconds(s), _ctx| stopwatch.seconds += s)
+    }
+}
+
+/// Wait until the Actor of type *A* read (meters, seconds) as *expected*.
+fn wait_for<A: QueryableActor + 'static>(
+    actor: &ActorRef,
+    read: fn(&A) -> (u64, u64),
+    expected: (u64, u64),
+) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let current = || actor.query(read, Duration::from_secs(1)).unwrap();
+    while current() != expected && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(current(), expected);
+}
+
+#[test]
+fn tags_tell_apart_types_of_the_same_shape() {
+    assert_ne!(message_tag::<Meters>(), message_tag::<Seconds>());
+    assert_eq!(
+        bincode::serialize(&Meters(7)).unwrap(),
+        bincode::serialize(&Seconds(7)).unwrap()
+    );
+
+    let (env, _expiration_checker) = Environment::new_local_only(
+        actor_builder!("Odometer" => Odometer::default(), "Stopwatch" => Stopwatch::default()),
+    );
+    let odometer = env.spawn("Odometer").unwrap();
+    let stopwatch = env.spawn("Stopwatch").unwrap();
+
+    // broadcasts are passed in serialized form, like Messages from a remote machine
+    env.broadcast_in_total_order(Seconds(7)).unwrap();
+    env.broadcast_in_total_order(Meters(2)).unwrap();
+    wait_for(&odometer, |o: &Odometer| (o.meters, o.seconds), (2, 7));
+    wait_for(&stopwatch, |s: &Stopwatch| (s.meters, s.seconds), (2, 7));
+
+    // untagged bytes are deserialized to the first type that fits
+    odometer
+        .send_serialized(bincode::serialize(&Seconds(1)).unwrap())
+        .unwrap();
+    wait_for(&odometer, |o: &Odometer| (o.meters, o.seconds), (3, 7));
+}
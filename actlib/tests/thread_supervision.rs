@@ -0,0 +1,82 @@
+//! Internal threads that panic are restarted and show up in the health report, until they panicked too often.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+
+/// Panics when it is cloned, which the timer thread does for every periodic message.
+#[derive(Debug, Serialize, Deserialize)]
+struct Bomb;
+
+impl Clone for Bomb {
+    fn clone(&self) -> Self {
+        panic!("a Bomb cannot be cloned");
+    }
+}
+
+#[derive(Debug)]
+struct Target;
+
+impl Actor for Target {}
+
+fn receive(_: &mut Target, _: &String) {
+    RECEIVED.fetch_add(1, Ordering::SeqCst);
+}
+
+impl_message_handler!(Target: String => receive, Bomb => |_: &mut Target, _: &Bomb| {});
+
+/// Wait until the timer panicked *panics* times.
+fn wait_for_timer_panics(env: &Environment, panics: usize) -> ThreadHealth {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let threads = env.health().threads;
+        let timer = threads.iter().find(|thread| thread.name == "actlib-timer");
+        match timer {
+            Some(timer) if timer.panics >= panics => return timer.clone(),
+            _ if Instant::now() > deadline => panic!("The timer did not panic: {:?}", threads),
+            _ => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
+#[test]
+fn a_panicking_timer_is_restarted_then_given_up_on() {
+    let (env, expiration_checker) = Environment::new_local_only(actor_builder!("Target" => Target));
+    let target = env.spawn("Target").unwrap();
+    assert!(env.health().threads.is_empty());
+
+    let bomb = target.send_periodic_message(Bomb, Duration::from_millis(10));
+    let timer = wait_for_timer_panics(&env, 1);
+    assert!(timer.running);
+    assert_eq!(timer.last_panic, "a Bomb cannot be cloned");
+    assert!(!bomb.is_active());
+    assert_eq!(env.health().status, HealthStatus::Degraded);
+
+    // the restarted timer still sends delayed messages
+    target.send_delayed_message("later".to_string(), Duration::from_millis(10));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while RECEIVED.load(Ordering::SeqCst) == 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(RECEIVED.load(Ordering::SeqCst), 1);
+    assert!(expiration_checker.try_check().is_none());
+
+    for _ in 0..5 {
+        target.send_periodic_message(Bomb, Duration::from_millis(10));
+    }
+    let timer = wait_for_timer_panics(&env, 6);
+    assert!(!timer.running);
+    assert_eq!(env.health().status, HealthStatus::Failed);
+    // the thread is recorded as given up on just before the expiration
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut result = expiration_checker.try_check();
+    while result.is_none() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+        result = expiration_checker.try_check();
+    }
+    assert!(matches!(result.unwrap().reason, ExpirationReason::Fatal(_)));
+}
@@ -0,0 +1,82 @@
+//! The TickDriver ticks its group at the target rate while every member keeps up, and slows down for members that don't.
+
+use actlib::api::*;
+use actlib::builtin::{JoinTicks, LeaveTicks, StartTicks, Tick, TickDriver, TickStatus};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Worker {
+    env: Option<Environment>,
+}
+
+impl Actor for Worker {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn handle_tick(worker: &mut Worker, tick: &Tick) {
+    tick.ack(worker.env.as_ref().unwrap()).unwrap();
+}
+
+impl_message_handler!(Worker: Tick => handle_tick);
+
+/// Never acknowledges a tick.
+#[derive(Debug)]
+struct Laggard;
+
+impl Actor for Laggard {}
+
+impl_message_handler!(Laggard: Tick => |_: &mut Laggard, _: &Tick| {});
+
+fn status(driver: &ActorRef) -> TickStatus {
+    driver
+        .query(
+            |driver: &TickDriver| driver.status(),
+            Duration::from_secs(1),
+        )
+        .unwrap()
+}
+
+#[test]
+fn the_driver_slows_down_for_members_that_do_not_keep_up() {
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "TickDriver" => TickDriver::default(),
+        "Worker" => Worker { env: None },
+        "Laggard" => Laggard
+    ));
+    let driver = env.spawn("TickDriver").unwrap();
+    let worker = env.spawn("Worker").unwrap();
+    let laggard = env.spawn("Laggard").unwrap();
+
+    driver.send_message(JoinTicks(worker.clone_id())).unwrap();
+    driver
+        .send_message(StartTicks {
+            interval: Duration::from_millis(20),
+            max_interval: Duration::from_millis(80),
+        })
+        .unwrap();
+    thread::sleep(Duration::from_millis(300));
+    let keeping_up = status(&driver);
+    assert_eq!(keeping_up.members, 1);
+    assert!(keeping_up.completed_ticks >= 3);
+    assert_eq!(keeping_up.slowdowns, 0);
+    assert_eq!(keeping_up.interval, Duration::from_millis(20));
+
+    driver.send_message(JoinTicks(laggard.clone_id())).unwrap();
+    thread::sleep(Duration::from_millis(400));
+    let lagging = status(&driver);
+    assert!(lagging.slowdowns >= 2);
+    assert_eq!(lagging.interval, Duration::from_millis(80));
+    // the tick the laggard holds up is never completed
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(status(&driver).completed_ticks, lagging.completed_ticks);
+
+    // once the laggard left, the ticks complete again and the driver speeds up
+    driver.send_message(LeaveTicks(laggard.clone_id())).unwrap();
+    thread::sleep(Duration::from_millis(600));
+    let recovered = status(&driver);
+    assert!(recovered.completed_ticks > lagging.completed_ticks + 2);
+    assert!(recovered.interval < Duration::from_millis(80));
+}
@@ -0,0 +1,58 @@
+//! With a TimeBudget a long-running handler gives way at its checkpoints once its time slice is used up.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static YIELDS: Mutex<Option<Sender<usize>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Simulation;
+
+impl Actor for Simulation {}
+
+/// Busy for the given number of milliseconds, passing a checkpoint every millisecond.
+fn simulate(_: &mut Simulation, millis: &u64) {
+    let started = Instant::now();
+    let mut yields = 0;
+    while started.elapsed() < Duration::from_millis(*millis) {
+        let step = Instant::now();
+        while step.elapsed() < Duration::from_millis(1) {}
+        if checkpoint() {
+            yields += 1;
+        }
+    }
+    let yields_sender = YIELDS.lock().unwrap();
+    yields_sender.as_ref().unwrap().send(yields).unwrap();
+}
+
+impl_message_handler!(Simulation: u64 => simulate);
+
+/// The number of times a handler running for 100 milliseconds had to give way.
+fn yields_with(options: EnvironmentOptions) -> usize {
+    let (tx, rx) = channel();
+    *YIELDS.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) =
+        Environment::new_with_options(0, &[], actor_builder!("Simulation" => Simulation), options);
+    env.spawn("Simulation")
+        .unwrap()
+        .send_message(100u64)
+        .unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap()
+}
+
+#[test]
+fn handlers_give_way_once_their_slice_is_used_up() {
+    // outside of a handler a checkpoint never gives way
+    assert!(!checkpoint());
+
+    assert_eq!(yields_with(EnvironmentOptions::new()), 0);
+
+    let yields = yields_with(EnvironmentOptions::new().time_budget(TimeBudget {
+        slice: Duration::from_millis(20),
+        penalty: YieldPolicy::Yield,
+    }));
+    // a new slice starts after every time the handler gave way
+    assert!((2..=5).contains(&yields), "gave way {} times", yields);
+}
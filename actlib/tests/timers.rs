@@ -0,0 +1,57 @@
+//! Delayed and periodic messages share the timer thread of the Environment, and stop once their handle is cancelled.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static TICKS: Mutex<Option<Sender<Tick>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum Tick {
+    Once,
+    Periodic,
+}
+
+#[derive(Debug)]
+struct Clock;
+
+impl Actor for Clock {}
+
+fn tick(_: &mut Clock, tick: &Tick) {
+    let ticks = TICKS.lock().unwrap();
+    ticks.as_ref().unwrap().send(tick.clone()).unwrap();
+}
+
+impl_message_handler!(Clock: Tick => tick);
+
+#[test]
+fn timers_fire_until_cancelled() {
+    let (tx, rx) = channel();
+    *TICKS.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_local_only(actor_builder!("Clock" => Clock));
+    let clock = env.spawn("Clock").unwrap();
+
+    let once = env.schedule_once(&clock, Tick::Once, Duration::from_millis(50));
+    let cancelled = env.schedule_once(&clock, Tick::Once, Duration::from_millis(100));
+    assert!(cancelled.cancel());
+    assert!(!cancelled.is_active());
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), Tick::Once);
+    assert!(!once.is_active());
+    assert!(!once.cancel());
+
+    let periodic = env.schedule_periodic(&clock, Tick::Periodic, Duration::from_millis(20));
+    for _ in 0..3 {
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            Tick::Periodic
+        );
+    }
+    assert!(periodic.is_active());
+    assert!(periodic.cancel());
+    // a tick may have been sent while cancelling, nothing follows it
+    std::thread::sleep(Duration::from_millis(100));
+    rx.try_iter().for_each(drop);
+    assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+}
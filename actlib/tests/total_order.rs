@@ -0,0 +1,67 @@
+//! Broadcasts in total order reach every Actor in the same order, also when they are sent from several threads at once.
+
+use actlib::api::*;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+static SEEN: Mutex<Vec<(ActorId, u32)>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Default)]
+struct Listener {
+    id: Option<ActorId>,
+}
+
+impl Actor for Listener {
+    fn on_start(&mut self, _local_env: Environment, own_ref: ActorRef) {
+        self.id = Some(own_ref.clone_id());
+    }
+}
+
+fn listen(listener: &mut Listener, event: &u32) {
+    let id = listener.id.clone().unwrap();
+    SEEN.lock().unwrap().push((id, *event));
+}
+
+impl_message_handler!(Listener: u32 => listen);
+
+#[test]
+fn every_actor_sees_the_same_order() {
+    let (env, _expiration_checker) =
+        Environment::new_local_only(actor_builder!("Listener" => Listener::default()));
+    assert_eq!(env.leader(), env.info().machine_id);
+    let listeners: Vec<ActorId> = (0..3)
+        .map(|_| env.spawn("Listener").unwrap().clone_id())
+        .collect();
+
+    let senders: Vec<_> = (0..4)
+        .map(|sender| {
+            let env = env.clone();
+            thread::spawn(move || {
+                for event in 0..50u32 {
+                    env.broadcast_in_total_order(sender * 100 + event).unwrap();
+                }
+            })
+        })
+        .collect();
+    for sender in senders {
+        sender.join().unwrap();
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while SEEN.lock().unwrap().len() < 3 * 200 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+    }
+    let seen = SEEN.lock().unwrap();
+    let order_of = |listener: &ActorId| -> Vec<u32> {
+        seen.iter()
+            .filter(|(id, _)| id == listener)
+            .map(|(_, event)| *event)
+            .collect()
+    };
+    let first = order_of(&listeners[0]);
+    assert_eq!(first.len(), 200);
+    for listener in &listeners[1..] {
+        assert_eq!(order_of(listener), first);
+    }
+}
@@ -0,0 +1,69 @@
+//! A TypedActorRef only sends the Messages its Actor handles, and is only spawned for type ids building that Actor.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static HANDLED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+#[derive(Debug, Default)]
+struct Counter {
+    env: Option<Environment>,
+    count: u32,
+}
+
+impl Actor for Counter {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn count(counter: &mut Counter, by: &u32) {
+    counter.count += by;
+    let handled = HANDLED.lock().unwrap();
+    handled.as_ref().unwrap().send(counter.count).unwrap();
+}
+
+fn get(counter: &mut Counter, request: &Request<()>) {
+    let env = counter.env.as_ref().unwrap();
+    env.reply(request, counter.count).unwrap();
+}
+
+impl_message_handler!(Counter: u32 => count, Request<()> => get);
+
+#[derive(Debug)]
+struct Other;
+
+impl Actor for Other {}
+
+impl_message_handler!(Other: u8 => |_: &mut Other, _: &u8| {});
+
+#[test]
+fn typed_refs_send_the_handled_messages() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+    let (env, _expiration_checker) = Environment::new_local_only(
+        actor_builder!("Counter" => Counter::default(), "Other" => Other),
+    );
+
+    let counter: TypedActorRef<Counter> = env.spawn_typed("Counter").unwrap();
+    counter.send_message(2u32).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 2);
+    counter.clone().send_message(3u32).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 5);
+    let count: u32 = counter
+        .ask(())
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(count, 5);
+    assert_eq!(counter.untyped().clone_id(), counter.clone_id());
+
+    // the type id builds Actors handling other message types
+    assert!(matches!(
+        env.spawn_typed::<Counter>("Other"),
+        Err(ActlibError::InvalidState(_))
+    ));
+    assert!(env.list_local_actors(Some("Other")).is_empty());
+}
@@ -0,0 +1,58 @@
+//! Watchers must learn about every stopped Actor, also about Actors that stopped before they were watched.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static TERMINATED: Mutex<Option<Sender<ActorId>>> = Mutex::new(None);
+
+#[derive(Debug)]
+struct Watcher;
+
+impl Actor for Watcher {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+fn handle_terminated(_: &mut Watcher, Terminated(actor_id): &Terminated) {
+    let terminated = TERMINATED.lock().unwrap();
+    terminated.as_ref().unwrap().send(actor_id.clone()).unwrap();
+}
+
+impl_message_handler!(Watcher: Terminated => handle_terminated);
+
+#[derive(Debug)]
+struct Idler;
+
+impl Actor for Idler {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(Idler: u32 => |_: &mut Idler, _: &u32| {});
+
+#[test]
+fn watchers_receive_terminated() {
+    let (tx, rx) = channel();
+    *TERMINATED.lock().unwrap() = Some(tx);
+    let (mut env, _expiration_checker) = Environment::new_local_only(actor_builder!(
+        "Watcher" => Watcher,
+        "Idler" => Idler
+    ));
+    let watcher = env.spawn("Watcher").unwrap();
+    let idler = env.spawn("Idler").unwrap();
+
+    idler.watch(&watcher).unwrap();
+    assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+    env.remove(idler.clone());
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        idler.clone_id()
+    );
+
+    // the Idler is gone already
+    idler.watch(&watcher).unwrap();
+    assert_eq!(
+        rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+        idler.clone_id()
+    );
+}
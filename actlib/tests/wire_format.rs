@@ -0,0 +1,102 @@
+//! Messages and replies pass through the wire format the Environment is configured with.
+
+use actlib::api::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Reading {
+    sensor: String,
+    values: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Default)]
+struct Collector {
+    readings: Vec<String>,
+}
+
+impl Actor for Collector {}
+
+impl QueryableActor for Collector {}
+
+fn collect(collector: &mut Collector, reading: &Reading) {
+    collector.readings.push(reading.sensor.clone());
+}
+
+fn count(collector: &mut Collector, request: &Request<String>) {
+    let matching = collector
+        .readings
+        .iter()
+        .filter(|sensor| **sensor == request.message)
+        .count();
+    Context::env().reply(request, matching).unwrap();
+}
+
+impl_message_handler!(Collector: Reading => collect, Request<String> => count);
+
+#[test]
+fn formats_round_trip_and_parse_by_name() {
+    let reading = Reading {
+        sensor: "north".to_string(),
+        values: vec![("celsius".to_string(), -3), ("humidity".to_string(), 81)]
+            .into_iter()
+            .collect(),
+    };
+    for format in [
+        WireFormat::Bincode,
+        WireFormat::Json,
+        WireFormat::MessagePack,
+    ] {
+        let bytes = format.serialize(&reading).unwrap();
+        assert_eq!(format.deserialize::<Reading>(&bytes).unwrap(), reading);
+        assert_eq!(format.to_string().parse::<WireFormat>().unwrap(), format);
+    }
+    assert_eq!(WireFormat::default(), WireFormat::Bincode);
+    assert_eq!(
+        "MessagePack".parse::<WireFormat>().unwrap(),
+        WireFormat::MessagePack
+    );
+    assert!("yaml".parse::<WireFormat>().is_err());
+    assert!(WireFormat::Json
+        .deserialize::<Reading>(&WireFormat::Bincode.serialize(&reading).unwrap())
+        .is_err());
+}
+
+#[test]
+fn json_environment_delivers_broadcasts_and_replies() {
+    let (env, _expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!("Collector" => Collector::default()),
+        EnvironmentOptions::new().wire_format(WireFormat::Json),
+    );
+    let collector = env.spawn("Collector").unwrap();
+
+    // broadcasts are passed in serialized form, like Messages from a remote machine
+    for sensor in &["north", "south", "north"] {
+        env.broadcast_in_total_order(Reading {
+            sensor: sensor.to_string(),
+            values: BTreeMap::new(),
+        })
+        .unwrap();
+    }
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let received = || {
+        collector
+            .query(|c: &Collector| c.readings.len(), Duration::from_secs(1))
+            .unwrap()
+    };
+    while received() < 3 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(received(), 3);
+
+    let north: usize = collector
+        .ask("north".to_string())
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(north, 2);
+}
@@ -0,0 +1,113 @@
+//! On the worker pool many Actors share a fixed number of threads, and handlers may wait for replies without starving the others.
+
+use actlib::api::*;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+static HANDLED: Mutex<Option<Sender<u32>>> = Mutex::new(None);
+
+fn report(value: u32) {
+    let handled = HANDLED.lock().unwrap();
+    handled.as_ref().unwrap().send(value).unwrap();
+}
+
+#[derive(Debug)]
+struct Cell;
+
+impl Actor for Cell {
+    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {}
+}
+
+impl_message_handler!(Cell: u32 => |_: &mut Cell, value: &u32| report(*value));
+
+#[derive(Debug)]
+struct Doubler {
+    env: Option<Environment>,
+}
+
+impl Actor for Doubler {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn double(doubler: &mut Doubler, request: &Request<u32>) {
+    let env = doubler.env.as_ref().unwrap();
+    env.reply(request, request.message * 2).unwrap();
+}
+
+impl_message_handler!(Doubler: Request<u32> => double);
+
+#[derive(Debug)]
+struct Asker {
+    env: Option<Environment>,
+}
+
+impl Actor for Asker {
+    fn on_start(&mut self, local_env: Environment, _own_ref: ActorRef) {
+        self.env = Some(local_env);
+    }
+}
+
+fn ask_doubler(asker: &mut Asker, doubler: &ActorId) {
+    let env = asker.env.as_ref().unwrap();
+    let doubler = env.to_actor_ref(doubler.clone()).unwrap();
+    let reply: u32 = doubler
+        .ask(21u32)
+        .unwrap()
+        .wait(Duration::from_secs(5))
+        .unwrap();
+    report(reply);
+}
+
+impl_message_handler!(Asker: ActorId => ask_doubler);
+
+#[test]
+fn actors_share_a_single_worker() {
+    let (tx, rx) = channel();
+    *HANDLED.lock().unwrap() = Some(tx);
+    let (env, expiration_checker) = Environment::new_with_options(
+        0,
+        &[],
+        actor_builder!(
+            "Cell" => Cell,
+            "Doubler" => Doubler { env: None },
+            "Asker" => Asker { env: None }
+        ),
+        EnvironmentOptions::new()
+            .runtime(Runtime::Pool)
+            .worker_threads(1),
+    );
+
+    let cells: Vec<ActorRef> = (0..2000).map(|_| env.spawn("Cell").unwrap()).collect();
+    for (i, cell) in cells.iter().enumerate() {
+        cell.send_message(i as u32).unwrap();
+    }
+    let mut handled: Vec<u32> = (0..2000)
+        .map(|_| rx.recv_timeout(Duration::from_secs(10)).unwrap())
+        .collect();
+    handled.sort();
+    assert_eq!(handled, (0..2000).collect::<Vec<u32>>());
+
+    // a single Cell handles its messages in order, across several turns
+    for i in 0..100u32 {
+        cells[0].send_message(i).unwrap();
+    }
+    let in_order: Vec<u32> = (0..100)
+        .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect();
+    assert_eq!(in_order, (0..100).collect::<Vec<u32>>());
+
+    // the only worker waits for the reply, a spare one runs the Doubler meanwhile
+    let doubler = env.spawn("Doubler").unwrap();
+    let asker = env.spawn("Asker").unwrap();
+    asker.send_message(doubler.clone_id()).unwrap();
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), 42);
+
+    env.shutdown(Duration::from_secs(5)).unwrap();
+    assert!(expiration_checker
+        .wait_until_expiration()
+        .unwrap()
+        .is_clean());
+}
@@ -4,12 +4,28 @@ use hostname;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Write;
-use std::net::{IpAddr, Ipv4Addr, Shutdown, SocketAddr, TcpListener};
-use std::sync::{Arc, Mutex};
+use std::net::Shutdown;
+
+/// The name the collector is registered under, so every field can find it.
+pub(crate) const COLLECTOR_NAME: &str = "collector";
+
+/// The port clients fetch the collected state from.
+pub(crate) const COLLECTOR_PORT: u16 = 4028;
 
 #[derive(Debug, Clone)]
 pub struct CollectingActor {
-    pub state: Arc<Mutex<HashMap<ActorId, ActorInfo>>>,
+    pub state: HashMap<ActorId, ActorInfo>,
+    /// latest version reported by every field with players, dropped once the field empties and is removed
+    pub versions: HashMap<ActorId, u64>,
+}
+
+impl CollectingActor {
+    pub fn new() -> CollectingActor {
+        CollectingActor {
+            state: HashMap::new(),
+            versions: HashMap::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,60 +39,45 @@ pub struct InjectCollector {
     pub collector_id: ActorId,
 }
 
-fn collecting_actor_handler(
-    actor_state: Arc<Mutex<HashMap<ActorId, ActorInfo>>>,
-    listener: TcpListener,
-) {
-    loop {
-        match listener.accept() {
-            Ok((mut stream, _socket)) => match actor_state.lock() {
-                Ok(locked_state) => match bincode::serialize(&locked_state.clone()) {
-                    Ok(ser_state) => {
-                        drop(locked_state);
-                        // println!("Hello???");
-                        // println!(
-                        //     "[Collecting Actor] Write current state of len {:?} to client",
-                        //     ser_state.len()
-                        // );
-                        //println!("Writing to client {:?}", ser_state);
-                        let _ = stream.write(&ser_state[..]);
-                        let _ = stream.flush();
-                        //let _ = stream.shutdown(Shutdown::Both);
-                    }
-                    Err(_) => {
-                        println!("could not serialize state");
-                        let _ = stream.shutdown(Shutdown::Both);
-                    }
-                },
-                Err(e) => {
-                    println!("Cannot get lock of collecting actor");
-                }
-            },
-            Err(e) => {
-                println!("Listener accept failed");
-            }
+/// Broadcast by a (re)started collector, every field answers with its current state.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResyncRequest {
+    pub collector_id: ActorId,
+}
+
+/// Answer a client of the collector with the collected state.
+fn serve_client(actor: &mut CollectingActor, connection: &ExternalConnection) {
+    let mut stream = match connection.take_stream() {
+        Some(stream) => stream,
+        None => return,
+    };
+    match bincode::serialize(&actor.state) {
+        Ok(ser_state) => {
+            let _ = stream.write(&ser_state[..]);
+            let _ = stream.flush();
+        }
+        Err(_) => {
+            println!("could not serialize state");
+            let _ = stream.shutdown(Shutdown::Both);
         }
     }
 }
 
 impl Actor for CollectingActor {
-    fn on_start(&mut self, _local_env: Environment, _own_ref: ActorRef) {
+    fn on_start(&mut self, local_env: Environment, own_ref: ActorRef) {
         // println!("{:?}", "ON_START called");
-        match TcpListener::bind(SocketAddr::new(
-            IpAddr::V4(Ipv4Addr::new(141, 84, 94, 111)),
-            4028,
-        )) {
-            Ok(listener) => {
-                let state_clone = self.state.clone();
-                std::thread::spawn(move || {
-                    collecting_actor_handler(state_clone, listener);
-                });
-            }
-            //server already existing?? // TODO
-            Err(_) => {}
+        // a restarted collector takes over the socket of its predecessor
+        if let Err(e) = local_env.register_external_listener(COLLECTOR_PORT, &own_ref) {
+            println!("Collector cannot serve clients: {:?}", e);
+        }
+        // rebuild the state after a restart, every field reports back
+        if let Err(e) = local_env.broadcast(ResyncRequest {
+            collector_id: own_ref.clone_id(),
+        }) {
+            println!("Collector cannot resync the fields: {:?}", e);
         }
     }
-    fn on_stop(&mut self) {
+    fn on_stop_with_reason(&mut self, _reason: StopReason) {
         println!("{:?}", "Collector went offline.");
     }
 }
@@ -86,29 +87,124 @@ pub struct UpdateState {
     pub actor_id: ActorId,
     pub position: Position,
     pub num_figures: usize,
+    /// increases with every change of the field, resent unchanged on a ResyncRequest
+    pub version: u64,
 }
 
 impl UpdateState {}
 
+/// Only the latest state of a field matters, a queued update is replaced by a newer one.
+impl Keyed for UpdateState {
+    type Key = ActorId;
+
+    fn key(&self) -> ActorId {
+        self.actor_id.clone()
+    }
+}
+
 fn update_state(actor: &mut CollectingActor, new_state: &UpdateState) {
-    match actor.state.lock() {
-        Ok(mut locked_state) => {
-            if new_state.num_figures == 0 {
-                locked_state.remove(&new_state.actor_id.clone());
-            } else {
-                locked_state.insert(
-                    new_state.actor_id.clone(),
-                    ActorInfo {
-                        position: new_state.position.clone(),
-                        num_figures: new_state.num_figures.clone(),
-                    },
-                );
-            }
-        }
-        Err(_) => {
-            println!("Could not get lock of actor state");
+    // resync answers and regular updates may overtake each other
+    if let Some(version) = actor.versions.get(&new_state.actor_id) {
+        if new_state.version < *version {
+            return;
         }
     }
+    if new_state.num_figures == 0 {
+        // the field is removed, a new field with the same id starts over at version 0
+        actor.versions.remove(&new_state.actor_id);
+        actor.state.remove(&new_state.actor_id);
+    } else {
+        actor
+            .versions
+            .insert(new_state.actor_id.clone(), new_state.version);
+        actor.state.insert(
+            new_state.actor_id.clone(),
+            ActorInfo {
+                position: new_state.position.clone(),
+                num_figures: new_state.num_figures,
+            },
+        );
+    }
 }
 
-impl_message_handler!(CollectingActor: UpdateState => update_state);
+impl_message_handler!(CollectingActor: UpdateState => update_state, ExternalConnection => serve_client);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Idle;
+
+    impl Actor for Idle {}
+
+    impl_message_handler!(Idle: () => |_: &mut Idle, _: &()| {});
+
+    /// The ids of *n* Actors, standing in for the fields.
+    fn field_ids(env: &Environment, n: usize) -> Vec<ActorId> {
+        (0..n)
+            .map(|_| env.spawn("Idle").unwrap().clone_id())
+            .collect()
+    }
+
+    fn report(actor_id: &ActorId, num_figures: usize, version: u64) -> UpdateState {
+        UpdateState {
+            actor_id: actor_id.clone(),
+            position: Position { x: 0, y: 0 },
+            num_figures,
+            version,
+        }
+    }
+
+    fn figures(collector: &CollectingActor, actor_id: &ActorId) -> Option<usize> {
+        collector.state.get(actor_id).map(|info| info.num_figures)
+    }
+
+    #[test]
+    fn a_restarted_collector_is_rebuilt_by_the_resync_answers() {
+        let (env, _expiration_checker) =
+            Environment::new_local_only(actor_builder!("Idle" => Idle));
+        let fields = field_ids(&env, 2);
+
+        // every field resends its state unchanged, whatever version it reached
+        let mut collector = CollectingActor::new();
+        update_state(&mut collector, &report(&fields[0], 3, 7));
+        update_state(&mut collector, &report(&fields[1], 5, 2));
+        assert_eq!(figures(&collector, &fields[0]), Some(3));
+        assert_eq!(figures(&collector, &fields[1]), Some(5));
+
+        // the answer to a second resync carries the same version and is taken as well
+        update_state(&mut collector, &report(&fields[0], 3, 7));
+        assert_eq!(figures(&collector, &fields[0]), Some(3));
+    }
+
+    #[test]
+    fn outdated_updates_are_ignored() {
+        let (env, _expiration_checker) =
+            Environment::new_local_only(actor_builder!("Idle" => Idle));
+        let field = field_ids(&env, 1).remove(0);
+
+        let mut collector = CollectingActor::new();
+        update_state(&mut collector, &report(&field, 4, 2));
+        update_state(&mut collector, &report(&field, 9, 1));
+        assert_eq!(figures(&collector, &field), Some(4));
+        assert_eq!(collector.versions.get(&field), Some(&2));
+    }
+
+    #[test]
+    fn removed_fields_are_forgotten() {
+        let (env, _expiration_checker) =
+            Environment::new_local_only(actor_builder!("Idle" => Idle));
+        let field = field_ids(&env, 1).remove(0);
+
+        let mut collector = CollectingActor::new();
+        update_state(&mut collector, &report(&field, 1, 4));
+        update_state(&mut collector, &report(&field, 0, 5));
+        assert!(collector.state.is_empty());
+        assert!(collector.versions.is_empty());
+
+        // a new field with the same id starts over
+        update_state(&mut collector, &report(&field, 2, 1));
+        assert_eq!(figures(&collector, &field), Some(2));
+    }
+}
@@ -65,6 +65,8 @@ pub struct FieldInstance {
     pub position: Option<Position>,
     ///Collector
     pub collector: Option<ActorRef>,
+    /// Version of the last state sent to the collector.
+    pub state_version: u64,
 }
 
 impl FieldInstance {
@@ -76,8 +78,16 @@ impl FieldInstance {
             environment: None,
             position: None,
             collector: None,
+            state_version: 0,
         }
     }
+    /// Check that a user specified id is the Position of the field, rejecting the spawn otherwise.
+    pub(crate) fn validate_id(id: &[u8]) -> Result<(), String> {
+        bincode::deserialize::<Position>(id)
+            .map(|_| ())
+            .map_err(|e| format!("not a Position: {:?}", e))
+    }
+
     /// unwrap-wrapper for self.own_ref
     pub(crate) fn unwrap_own_ref(&self) -> &ActorRef {
         self.own_ref.as_ref().unwrap()
@@ -126,13 +136,22 @@ impl FieldInstance {
     }
 
     fn send_state_update(&mut self) {
+        self.state_version += 1;
+        self.report_state();
+    }
+
+    /// Send the current state to the collector without changing its version.
+    fn report_state(&self) {
         if let Some(collector) = &self.collector {
             if let Some(position) = &self.position {
-                collector.send_message(UpdateState {
+                if let Err(e) = collector.send_keyed(UpdateState {
                     actor_id: self.unwrap_own_ref().clone_id(),
                     position: position.clone(),
                     num_figures: self.players.len(),
-                });
+                    version: self.state_version,
+                }) {
+                    error!("Could not report the state to the collector: {:?}", e);
+                }
             } else {
                 println!("Failed position {:?}", self.players.len());
             }
@@ -178,16 +197,49 @@ impl FieldInstance {
             .find_actor_ref(&local_id, own_actor_id.clone(), true)
         {
             Ok(Some(neighbour)) => {
-                // ignore non-existent actor: make sure only moves occur
-                let _ = self.players.remove(&outgoing_player_message.player);
-
-                neighbour.send_message(PlayerEnters {
+                let entered = neighbour.send_message(PlayerEnters {
                     player: outgoing_player_message.player.clone(),
                     from: outgoing_player_message.to.reverse(),
                 });
-                self.send_state_update();
                 self.unwrap_environment()
                     .drop_protector(own_actor_id, neighbour.clone_id());
+                match entered {
+                    Ok(_) => {
+                        // ignore non-existent actor: make sure only moves occur
+                        let _ = self.players.remove(&outgoing_player_message.player);
+                        self.send_state_update();
+                    }
+                    Err(ActlibError::MailboxFull(_)) => {
+                        // the neighbour is busy, the player waits for another round
+                        self.unwrap_own_ref().send_delayed_message(
+                            outgoing_player_message.clone(),
+                            std::time::Duration::from_millis(500),
+                        );
+                        return;
+                    }
+                    Err(ActlibError::ActorStopping(_)) => {
+                        // the neighbour is about to vanish, look it up again once it is gone
+                        self.unwrap_own_ref().send_delayed_message(
+                            outgoing_player_message.clone(),
+                            std::time::Duration::from_millis(50),
+                        );
+                        return;
+                    }
+                    Err(ActlibError::InvalidActorRef(_)) => {
+                        // the neighbour vanished, look it up again or spawn a new one
+                        let _ = self
+                            .unwrap_own_ref()
+                            .send_message(outgoing_player_message.clone());
+                        return;
+                    }
+                    Err(e) => {
+                        error!(
+                            "Player {:?} failed to move: {:?}",
+                            outgoing_player_message.player, e
+                        );
+                        return;
+                    }
+                }
                 if self.players.is_empty() {
                     // println!("[E] Removing Field: {:?}", self.unwrap_position());
                     let own_ref = self.unwrap_own_ref().clone();
@@ -196,48 +248,48 @@ impl FieldInstance {
                 }
             }
             Ok(None) => {
-                // spawn new field actor in desired direction
-                match self
-                    .unwrap_environment()
-                    .spawn_with_id(FIELD_INSTANCE_TYPE_ID, local_id)
-                {
+                // spawn new field actor in desired direction, close to the fields its players come from
+                match self.unwrap_environment().find_or_spawn_near(
+                    FIELD_INSTANCE_TYPE_ID,
+                    local_id,
+                    Some(own_actor_id.location()),
+                ) {
                     Ok(new_ref) => {
                         match &self.collector {
                             Some(c) => {
-                                new_ref.send_message(InjectCollector {
+                                if let Err(e) = new_ref.send_message(InjectCollector {
                                     collector_id: c.clone_id(),
-                                });
+                                }) {
+                                    error!("Could not pass the collector on: {:?}", e);
+                                }
                             }
-                            None => {
-                                match self.unwrap_environment().find_actor_ref(
-                                    &Vec::new(),
-                                    self.unwrap_own_ref().clone_id(),
-                                    false,
-                                ) {
-                                    Ok(opt_collector) => {
-                                        if let Some(collector) = opt_collector {
-                                            new_ref.send_message(InjectCollector {
-                                                collector_id: collector.clone_id(),
-                                            });
-                                            self.collector = Some(collector);
-                                        } else {
-                                            error!(
-                                                "Could not find Collector via it's fixed address!"
-                                            );
-                                        }
-                                    }
-                                    Err(actlib_err) => {
-                                        error!("{:?}", actlib_err);
+                            None => match self.unwrap_environment().lookup_name(COLLECTOR_NAME) {
+                                Some(collector) => {
+                                    if let Err(e) = new_ref.send_message(InjectCollector {
+                                        collector_id: collector.clone_id(),
+                                    }) {
+                                        error!("Could not pass the collector on: {:?}", e);
                                     }
+                                    self.collector = Some(collector);
                                 }
-                            }
+                                None => {
+                                    error!("Could not find the Collector by its name!");
+                                }
+                            },
                         }
 
                         // unwraps used (own_ref, environment) are save here
                         // we can only get messages after on_start has been called.
                         // send message to self to move player there (no infinite loop, since actor now exists)
-                        self.unwrap_own_ref()
-                            .send_message(outgoing_player_message.clone());
+                        if let Err(e) = self
+                            .unwrap_own_ref()
+                            .send_message(outgoing_player_message.clone())
+                        {
+                            error!(
+                                "Player {:?} failed to move: {:?}",
+                                outgoing_player_message.player, e
+                            );
+                        }
                     }
                     Err(e) => {
                         // Failed to spawn actor
@@ -252,6 +304,24 @@ impl FieldInstance {
         }
     }
 
+    fn handle_resync_request(&mut self, request: &ResyncRequest) {
+        match self
+            .unwrap_environment()
+            .to_actor_ref(request.collector_id.clone())
+        {
+            Ok(collector_ref) => {
+                self.collector = Some(collector_ref);
+                self.report_state();
+            }
+            Err(e) => {
+                error!(
+                    "Cannot resync with collector {:?}: {:?}",
+                    request.collector_id, e
+                );
+            }
+        }
+    }
+
     fn debug_query(&self, _debug_query: &DebugQuery) {
         println_green(&format!(
             "Field at {:?} holds {} players.",
@@ -271,6 +341,7 @@ impl Actor for FieldInstance {
                     self.position = Some(position);
                 }
                 Err(e) => {
+                    // cannot happen with the id validator registered in main
                     warn!("Spawned Field with invalid user specified Id: {:?}", e);
                     let _ = local_env.remove(own_ref);
                 }
@@ -281,6 +352,23 @@ impl Actor for FieldInstance {
         }
     }
 
+    fn on_stop_with_reason(&mut self, reason: StopReason) {
+        match reason {
+            // the collector goes down as well, nobody is interested in a final update
+            StopReason::Expired => {}
+            // the replacement reports the state from now on
+            StopReason::Migration => {}
+            StopReason::Removed
+            | StopReason::SupervisorDecision
+            | StopReason::IdConflict
+            | StopReason::Failed => {
+                // the players are gone with this field, make sure the collector forgets about it
+                self.players.clear();
+                self.send_state_update();
+            }
+        }
+    }
+
     fn on_reset(&mut self) {
         info!("Reset {} players.", self.players.len());
         self.players.clear();
@@ -299,5 +387,6 @@ impl_message_handler!(FieldInstance:
     PlayerEnters => FieldInstance::handle_incoming_actor,
     ForcePlayerLeave => FieldInstance::handle_force_player_leave,
     DebugQuery => FieldInstance::debug_query,
-    InjectCollector => FieldInstance::inject_collector
+    InjectCollector => FieldInstance::inject_collector,
+    ResyncRequest => FieldInstance::handle_resync_request
 );
@@ -3,13 +3,12 @@ use crate::field::*;
 use crate::position::*;
 use actlib::api::*;
 use hostname;
-use log::{warn, info};
+use log::{info, warn};
 use simple_logger;
-use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::net::SocketAddr;
-use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 pub mod collector;
 pub mod field;
@@ -48,68 +47,83 @@ fn main() {
 
     // Use port 4020 to establish a TCP-connection
     // let (env, expiration_checker) = Environment::new_local_only(
-    let (env, expiration_checker) = Environment::new(
+    let peers: Vec<Peer> = remotes.iter().cloned().map(Peer::from).collect();
+    let started = Instant::now();
+    let round_end = started + std::time::Duration::from_secs(64);
+    // the collector and the root field exist on every machine before the constructor returns
+    let (env, expiration_checker) = Environment::new_with_bootstrap(
         4020,
-        &remotes,
+        &peers,
         actor_builder!(
             FIELD_INSTANCE_TYPE_ID => FieldInstance::new(),
-            "CollectingActor" => CollectingActor{
-                state: Arc::new(Mutex::new(HashMap::new()))
+            "CollectingActor" => CollectingActor::new()
+        )
+        .with_id_validator(FIELD_INSTANCE_TYPE_ID, FieldInstance::validate_id),
+        // the collector only needs the latest state of every field
+        EnvironmentOptions::new().coalescing_mailbox("CollectingActor"),
+        |env| {
+            let collecting_actor = match env.spawn_local("CollectingActor") {
+                Ok(actor_ref) => actor_ref,
+                Err(e) => panic!("Failed to spawn the collector: {:?}", e),
+            };
+            if let Err(e) = env.register_name(COLLECTOR_NAME, &collecting_actor) {
+                panic!("Failed to register the collector: {:?}", e);
             }
-        ),
-    );
 
-    if &hostname == "agakauitai" {
-        let collecting_actor;
-        match env.spawn_local_with_id("CollectingActor", Vec::new()) {
-            Ok(actor_ref) => {
-                collecting_actor = actor_ref;
-            }
-            Err(e) => {
-                println!("Error: {:?}", e);
-                return;
+            let start_id: Vec<u8>;
+            match bincode::serialize(&Position { x: 0, y: 0 }) {
+                Ok(position) => start_id = position,
+                Err(e) => panic!("Failed to serialize start Position: {:?}", e),
             }
-        }
-
-        let start_id: Vec<u8>;
-        match bincode::serialize(&Position { x: 0, y: 0 }) {
-            Ok(position) => start_id = position,
-            Err(e) => panic!("Failed to serialize start Position: {:?}", e),
-        }
-        match env.spawn_with_id(FIELD_INSTANCE_TYPE_ID, start_id) {
-            Ok(actor_ref) => {
-                actor_ref.send_message(InjectCollector {
-                    collector_id: collecting_actor.clone_id(),
-                });
-                for i in 0..128 {
-                    actor_ref.send_message(PlayerEnters {
-                        player: Player(i),
-                        from: Direction::South,
-                    });
+            match env.spawn_with_id(FIELD_INSTANCE_TYPE_ID, start_id) {
+                Ok(actor_ref) => {
+                    if let Err(e) = actor_ref.send_message(InjectCollector {
+                        collector_id: collecting_actor.clone_id(),
+                    }) {
+                        panic!("Failed to pass the collector to the start Field: {:?}", e);
+                    }
+                    for i in 0..128 {
+                        if let Err(e) = actor_ref.send_message(PlayerEnters {
+                            player: Player(i),
+                            from: Direction::South,
+                        }) {
+                            panic!("Failed to place player {}: {:?}", i, e);
+                        }
+                    }
                 }
-            }
-            Err(e) => {
                 // Sth. went wrong when spawning the actor.
-                // Inform the user and exit
-                println!("Encountered a problem while spawning an actor: {:?}", e);
-                return;
+                Err(e) => panic!("Encountered a problem while spawning an actor: {:?}", e),
             }
-        }
+            if let Err(e) = env.broadcast_at(round_end, DebugQuery) {
+                warn!("No debug output at the end of the round: {:?}", e);
+            }
+        },
+    );
 
+    if env.is_bootstrap_machine() {
         info!("RUNNING FOR SOME TIME...");
-        std::thread::sleep(std::time::Duration::from_secs(64));
-        env.broadcast(DebugQuery);
-
-        std::thread::sleep(std::time::Duration::from_secs(2));
+        // wait for the collector to print its debug output
+        let expiration = round_end + std::time::Duration::from_secs(2);
+        std::thread::sleep(expiration.saturating_duration_since(Instant::now()));
         info!("ENDING THE PROGRAM AFTER THE SET TIMER - NOW.");
         let _ignored = env.set_expired();
     }
 
-    // Each Actor has its own thread to handle messages.
+    // The Actors handle their messages on the worker threads of the Environment.
     // Since all of them terminate once the main function finishes
-    // we have to wait block the current thread until the ```Environment::set_expired()```-method is called.
-    // Note: This doesn't happen here, so we block indefinitely (until the user hits 'Ctrl+C').
-    if let Err(e) = expiration_checker.wait_until_expiration() {
-        panic!("Something went wrong: {:?}", e);
+    // we have to block the current thread until the ```Environment::set_expired()```-method is called.
+    // Note: Only the bootstrap machine calls it, the others block until it expires the whole Environment.
+    match expiration_checker.wait_until_expiration() {
+        Ok(result) => {
+            if result.is_clean() {
+                info!(
+                    "Run ended cleanly, {} actors stopped.",
+                    result.actors_stopped()
+                );
+            } else {
+                warn!("Run did not end cleanly: {:?}", result);
+            }
+        }
+        Err(e) => panic!("Something went wrong: {:?}", e),
     }
 }
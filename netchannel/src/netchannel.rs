@@ -10,16 +10,54 @@
 //!     to spawn more then one server thread. The API is already there and
 //!     won't change when this is implemented.
 //!
+//! IPv4 and IPv6 are both supported. A server on an IPv6 address listens on
+//! every interface, accepting IPv4 connections as IPv4-mapped addresses.
+//!
+//! The server keeps listening until shutdown_server() is called, which releases
+//! its port for the next server.
+//!
+//! A dropped connection can be re-established with NetChannel::reconnect(),
+//! retrying with the delays of a Backoff.
+//!
+//! Every frame is prefixed with its length as a 4-byte big-endian integer.
+//! Frames may arrive split across several reads from the TCP stream, the
+//! NetReceiver buffers the bytes until the frame is complete.
+//!
+//! With the `tls` feature, a NetChannel can be encrypted with TLS, see
+//! NetChannel::with_tls().
+//!
 
 use log::*;
+use std::fmt;
 use std::io::prelude::*;
 use std::io::{Error, ErrorKind};
-use std::net::{IpAddr, Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::net::{IpAddr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
-use std::thread;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+#[cfg(feature = "tls")]
+mod tls;
 
-type ExpectedConnection = (IpAddr, Sender<TcpStream>);
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
+#[cfg(feature = "tls")]
+use tls::TlsSession;
+
+type ExpectedConnection = (Peer, Sender<TcpStream>);
+
+/// Largest payload a single frame can carry.
+///
+/// The 4-byte length prefix could describe more, but a corrupted prefix must not make the receiver buffer gigabytes.
+pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// Bytes of the length prefix in front of every frame.
+const PREFIX_SIZE: usize = 4;
+
+/// Buffer-size for a single read from the TCP stream.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
 
 // I'm a Singleton, the only pattern I know :P
 //
@@ -28,11 +66,192 @@ type ExpectedConnection = (IpAddr, Sender<TcpStream>);
 //
 // https://docs.rust-embedded.org/book/peripherals/singletons.html
 //
-// We really want a Mutex here. Unfortunately that's not possible for statics.
-// Instead we (ab)use the fact that only one thread can listen on a SockAddr at
-// any time as a mutex -- see run_server() method.
-#[allow(non_upper_case_globals)]
-static mut server_communicator: Option<Mutex<Sender<ExpectedConnection>>> = None;
+// The lock is held while binding the port, so a NetChannel that fails to bind
+// because another thread of this process listens there finds its server.
+
+/// The running server.
+struct Server {
+    /// The address it listens on.
+    addr: SocketAddr,
+    /// Set to stop the server.
+    stopped: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+    /// Passes the connections NetChannels expect on to the server.
+    communicator: Sender<ExpectedConnection>,
+}
+
+static SERVER: Mutex<Option<Server>> = Mutex::new(None);
+
+/// The address the server listens on, ```None``` if no server is running.
+pub fn server_addr() -> Option<SocketAddr> {
+    match SERVER.lock() {
+        Ok(server) => server.as_ref().map(|server| server.addr),
+        Err(_) => None,
+    }
+}
+
+///
+/// Stop the server listener and release its port
+///
+/// Connections that were already established stay open, expected connections
+/// that did not arrive yet never will. Returns the address the server
+/// listened on, ```None``` if no server was running.
+///
+pub fn shutdown_server() -> Option<SocketAddr> {
+    // dropping the communicator fails the NetChannels still waiting for a connection
+    let Server {
+        addr,
+        stopped,
+        thread,
+        ..
+    } = match SERVER.lock() {
+        Ok(mut server) => server.take()?,
+        Err(_) => return None,
+    };
+    stopped.store(true, Ordering::SeqCst);
+    // wake up the blocking accept, the server notices the flag and drops the listener
+    let wake_ip = match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+        IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ip => ip,
+    };
+    match TcpStream::connect_timeout(
+        &SocketAddr::new(wake_ip, addr.port()),
+        Duration::from_secs(1),
+    ) {
+        Ok(_) => {
+            let _ = thread.join();
+        }
+        Err(e) => warn!("Failed to wake up the server on {}: {:?}", addr, e),
+    }
+    Some(addr)
+}
+
+/// Delays between attempts to re-establish a connection, doubling from *initial* up to *max*.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Backoff {
+        Backoff {
+            initial,
+            max,
+            next: initial,
+        }
+    }
+
+    /// The delay before the next attempt, every call doubles the following one.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = std::cmp::min(self.next * 2, self.max);
+        delay
+    }
+
+    /// Start over with the initial delay, e.g. after a successful attempt.
+    pub fn reset(&mut self) {
+        self.next = self.initial;
+    }
+}
+
+/// Number of frames and bytes that passed one half of a NetChannel.
+///
+/// Shared by all clones of the half.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    frames: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Traffic {
+    /// Number of frames passed so far.
+    pub fn frames(&self) -> u64 {
+        self.frames.load(Ordering::Relaxed)
+    }
+
+    /// Number of payload bytes passed so far, without the length prefixes.
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, bytes: usize) {
+        self.frames.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// A remote host, either by address or by hostname.
+///
+/// Hostnames are resolved again on every connection attempt, so a peer whose
+/// address changed (e.g. a new DHCP lease) is still found.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Peer {
+    /// A fixed address.
+    Addr(SocketAddr),
+    /// A hostname and port, resolved when connecting.
+    Host(String, u16),
+}
+
+impl Peer {
+    /// Resolve the current address of the peer.
+    ///
+    /// Hostnames resolving to several addresses prefer an address of the same
+    /// family as *local*.
+    pub fn resolve(&self, local: &SocketAddr) -> std::io::Result<SocketAddr> {
+        match self {
+            Peer::Addr(addr) => Ok(*addr),
+            Peer::Host(host, port) => {
+                let addrs: Vec<SocketAddr> = (host.as_str(), *port).to_socket_addrs()?.collect();
+                addrs
+                    .iter()
+                    .find(|addr| addr.is_ipv6() == local.is_ipv6())
+                    .or_else(|| addrs.first())
+                    .cloned()
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::NotFound,
+                            format!("{} does not resolve to any address", host),
+                        )
+                    })
+            }
+        }
+    }
+
+    /// Returns ```true``` if *ip* is a current address of the peer.
+    ///
+    /// IPv4-mapped IPv6 addresses match their IPv4 address.
+    pub fn matches(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        match self {
+            Peer::Addr(addr) => addr.ip().to_canonical() == ip,
+            Peer::Host(host, port) => match (host.as_str(), *port).to_socket_addrs() {
+                Ok(mut addrs) => addrs.any(|addr| addr.ip().to_canonical() == ip),
+                Err(e) => {
+                    warn!("Failed to resolve {}: {:?}", host, e);
+                    false
+                }
+            },
+        }
+    }
+}
+
+impl From<SocketAddr> for Peer {
+    fn from(addr: SocketAddr) -> Self {
+        Peer::Addr(addr)
+    }
+}
+
+impl fmt::Display for Peer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Peer::Addr(addr) => write!(f, "{}", addr),
+            Peer::Host(host, port) => write!(f, "{}:{}", host, port),
+        }
+    }
+}
 
 enum Mode {
     Client,
@@ -42,6 +261,11 @@ enum Mode {
 #[derive(Debug)]
 pub struct NetChannel {
     stream: Arc<Mutex<Option<TcpStream>>>,
+    // the remote this machine connected to, None if it accepted the connection
+    #[cfg(feature = "tls")]
+    client_of: Option<Peer>,
+    #[cfg(feature = "tls")]
+    tls: Option<TlsConfig>,
 }
 
 impl NetChannel {
@@ -57,7 +281,7 @@ impl NetChannel {
     ///       but the type system gets in the way.
 
     fn machine_type(local: &SocketAddr, remote: &SocketAddr) -> Mode {
-        if local.ip() < remote.ip() {
+        if local.ip().to_canonical() < remote.ip().to_canonical() {
             return Mode::Client;
         } else {
             return Mode::Server;
@@ -68,11 +292,18 @@ impl NetChannel {
     /// Initialize Client Mode
     ///
     /// Once a connection is initialized, the stream is stored in self.stream
-    /// behind a Mutex.
+    /// behind a Mutex. Hostnames are resolved again before every attempt.
     ///
-    fn run_client(&self, remote: SocketAddr) {
+    fn run_client(&self, local: SocketAddr, remote: &Peer) {
         match self.stream.lock() {
             Ok(mut stream) => loop {
+                let remote = match remote.resolve(&local) {
+                    Ok(remote) => remote,
+                    Err(_) => {
+                        thread::yield_now();
+                        continue;
+                    }
+                };
                 match TcpStream::connect(remote) {
                     Ok(incoming_stream) => {
                         *stream = Some(incoming_stream);
@@ -92,77 +323,97 @@ impl NetChannel {
     /// Initialize Server Mode
     ///
     /// Try to start a server listener. If it fails one has to connect
-    /// to it using the global server_communicator sender.
+    /// to it using the communicator of the running server.
     ///
-    fn run_server(&self, local: SocketAddr, remote: SocketAddr) {
-        // Try to create a new server thread.
-        match TcpListener::bind(local) {
-            // Winner winner chicken dinner
-            // We're first so let's start the server thread.
-            Ok(listener) => {
-                let (sender, receiver) = channel();
-                thread::spawn(move || server(listener, sender, receiver));
-            }
-            // another thread has already started the server
-            Err(_) => {
-                error!("Port {} already taken.", local.port())
-                // port is taken by another process
-                // TODO: what to do if  _every_ NetChannels hits Err() here?
-            }
+    fn run_server(&self, local: SocketAddr, remote: Peer) {
+        // An IPv6 listener on every interface accepts IPv4 connections as well.
+        let listen = match local {
+            SocketAddr::V4(_) => local,
+            SocketAddr::V6(_) => SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), local.port()),
+        };
+        match SERVER.lock() {
+            // Try to create a new server thread.
+            Ok(mut running) => match TcpListener::bind(listen) {
+                // Winner winner chicken dinner
+                // We're first so let's start the server thread.
+                Ok(listener) => {
+                    let (sender, receiver) = channel();
+                    let addr = listener.local_addr().unwrap_or(listen);
+                    let stopped = Arc::new(AtomicBool::new(false));
+                    let server_stopped = stopped.clone();
+                    let thread = thread::spawn(move || server(listener, receiver, server_stopped));
+                    *running = Some(Server {
+                        addr,
+                        stopped,
+                        thread,
+                        communicator: sender,
+                    });
+                }
+                // another thread has already started the server
+                Err(_) => {
+                    error!("Port {} already taken.", local.port())
+                    // port is taken by another process
+                    // TODO: what to do if  _every_ NetChannels hits Err() here?
+                }
+            },
+            Err(_) => error!("Couldn't acquire Mutex lock for the server."),
+        }
+
+        if let Err(e) = self.await_peer(remote, None) {
+            warn!("{}", e);
         }
+    }
 
+    ///
+    /// Wait for the server to pass on the connection of the remote
+    ///
+    /// Without a *timeout* this waits until the remote connected or the
+    /// server was shut down. Fails right away if no server is running.
+    ///
+    fn await_peer(&self, remote: Peer, timeout: Option<Duration>) -> std::io::Result<()> {
         // Inform the server about the expected connection
         // The receiver is a 'callback' where the server can inform us about an
         // incoming expected connection.
         let (sender, receiver) = channel();
-        unsafe {
-            // Rust's limitations regarding global static variables require us
-            // to spin here to make it safe.
-            // We might be able to do this by using AtomicPtr. But now it's too
-            // late to change..
-            loop {
-                match &server_communicator {
-                    Some(mutex) => {
-                        match mutex.lock() {
-                            Ok(sc) => {
-                                let _ = sc.send((remote.ip(), sender));
-                                // TODO: Error handling
-                            }
-                            Err(error) => {
-                                panic!("Error acquiring lock: {:?}", error);
-                            }
-                        }
-                        break;
-                    }
-                    None => {
-                        thread::yield_now();
-                        continue;
-                    }
-                }
-            }
+        let expected = match SERVER.lock() {
+            Ok(server) => match &*server {
+                Some(server) => server.communicator.send((remote.clone(), sender)).is_ok(),
+                None => false,
+            },
+            Err(_) => false,
+        };
+        if !expected {
+            return Err(Error::new(
+                ErrorKind::NotConnected,
+                format!("No server is listening for {}", remote),
+            ));
         }
 
-        // At this point we're safe. All unsafe {} blocks are misnomers.
-
         // Start listening for the server to inform us about a connecting
         // remote we expect.
         let stream = self.stream.clone();
         let stream = stream.lock();
-        loop {
-            match receiver.recv() {
-                Ok(remote) => {
-                    match stream {
-                        Ok(mut stream) => {
-                            *stream = Some(remote);
-                        }
-                        Err(_) => {}
-                    }
-                    break;
-                }
-                Err(_) => {
-                    continue;
+        let received = match timeout {
+            Some(timeout) => receiver.recv_timeout(timeout).ok(),
+            None => receiver.recv().ok(),
+        };
+        match received {
+            Some(remote) => {
+                if let Ok(mut stream) = stream {
+                    *stream = Some(remote);
                 }
-            };
+                Ok(())
+            }
+            // the remote may still connect for the next attempt
+            None if timeout.is_some() => Err(Error::new(
+                ErrorKind::TimedOut,
+                format!("{} did not connect in time", remote),
+            )),
+            // the server was shut down, the connection never arrives
+            None => Err(Error::new(
+                ErrorKind::NotConnected,
+                format!("Server shut down before {} connected", remote),
+            )),
         }
     }
 
@@ -173,44 +424,137 @@ impl NetChannel {
     /// pair. The remote with the flipped pair will automaticalle use the other
     /// mode.
     pub fn new(local: SocketAddr, remote: SocketAddr) -> NetChannel {
-        match Self::machine_type(&local, &remote) {
-            Mode::Client => {
-                return Self::as_client(remote);
-            }
-            Mode::Server => {
-                return Self::as_server(local, remote);
+        Self::with_peer(local, &Peer::Addr(remote))
+    }
+
+    ///
+    /// Create NetChannel to a Peer
+    ///
+    /// Like [new](#method.new), hostnames are resolved until they resolve
+    /// to an address, which decides the mode.
+    pub fn with_peer(local: SocketAddr, remote: &Peer) -> NetChannel {
+        let remote_addr = loop {
+            match remote.resolve(&local) {
+                Ok(remote_addr) => break remote_addr,
+                Err(e) => {
+                    warn!("Failed to resolve {}, retrying: {:?}", remote, e);
+                    thread::sleep(std::time::Duration::from_millis(100));
+                }
             }
         };
+        match Self::machine_type(&local, &remote_addr) {
+            Mode::Client => Self::as_client_to_peer(local, remote),
+            Mode::Server => Self::as_server_for_peer(local, remote.clone()),
+        }
+    }
+
+    ///
+    /// Re-establish a dropped connection to a Peer
+    ///
+    /// The mode is the same as for the first connection. A client tries to
+    /// connect once, a server waits until the remote connected again. Both
+    /// give up after *timeout*. Retry with the delays of a [Backoff](struct.Backoff.html)
+    /// until the returned NetChannel can be [split](#method.split).
+    pub fn reconnect(
+        local: SocketAddr,
+        remote: &Peer,
+        timeout: Duration,
+    ) -> std::io::Result<NetChannel> {
+        let remote_addr = remote.resolve(&local)?;
+        let mode = Self::machine_type(&local, &remote_addr);
+        let netchannel = NetChannel::unconnected(match mode {
+            Mode::Client => Some(remote),
+            Mode::Server => None,
+        });
+        match mode {
+            Mode::Client => {
+                let stream = TcpStream::connect_timeout(&remote_addr, timeout)?;
+                if let Ok(mut netchannel_stream) = netchannel.stream.lock() {
+                    *netchannel_stream = Some(stream);
+                }
+            }
+            Mode::Server => netchannel.await_peer(remote.clone(), Some(timeout))?,
+        }
+        Ok(netchannel)
     }
 
     /// Create NetChannel in Client Mode
     pub fn as_client(remote: SocketAddr) -> NetChannel {
-        let netchannel = NetChannel {
-            stream: Arc::new(Mutex::new(None)),
-        };
+        Self::as_client_to_peer(remote, &Peer::Addr(remote))
+    }
+
+    /// Create NetChannel in Client Mode, connecting to a Peer
+    fn as_client_to_peer(local: SocketAddr, remote: &Peer) -> NetChannel {
+        let netchannel = NetChannel::unconnected(Some(remote));
 
-        netchannel.run_client(remote);
+        netchannel.run_client(local, remote);
 
         netchannel
     }
 
     /// Create NetChannel in Server Mode
     pub fn as_server(local: SocketAddr, remote: SocketAddr) -> NetChannel {
-        let netchannel = NetChannel {
-            stream: Arc::new(Mutex::new(None)),
-        };
+        Self::as_server_for_peer(local, Peer::Addr(remote))
+    }
+
+    /// Create NetChannel in Server Mode, waiting for a Peer
+    fn as_server_for_peer(local: SocketAddr, remote: Peer) -> NetChannel {
+        let netchannel = NetChannel::unconnected(None);
 
         netchannel.run_server(local, remote);
 
         netchannel
     }
 
+    /// A NetChannel without a connection, which connects to *client_of* or waits for the remote.
+    #[allow(unused_variables)]
+    fn unconnected(client_of: Option<&Peer>) -> NetChannel {
+        NetChannel {
+            stream: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "tls")]
+            client_of: client_of.cloned(),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    ///
+    /// Encrypt the NetChannel with TLS
+    ///
+    /// The handshake runs when the NetChannel is [split](#method.split), the
+    /// remote has to use TLS as well. Requires the `tls` feature.
+    ///
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
     /// Split NetChannel in Sender in Receiver halves, similar to
     /// std::sync::mpsc::channel().
+    ///
+    /// An encrypted NetChannel runs the TLS handshake first. A certificate
+    /// that is not accepted fails with ErrorKind::InvalidData.
     pub fn split(&mut self) -> std::io::Result<(NetSender, NetReceiver)> {
         match self.stream.lock() {
-            Ok(stream) => match &*stream {
+            Ok(mut stream) => match &mut *stream {
                 Some(stream) => {
+                    #[cfg(feature = "tls")]
+                    let session = match &self.tls {
+                        Some(tls) => {
+                            Some(Arc::new(tls.handshake(stream, self.client_of.as_ref())?))
+                        }
+                        None => None,
+                    };
+                    #[allow(unused_mut)]
+                    let mut pending = Vec::new();
+                    #[cfg(feature = "tls")]
+                    {
+                        if let Some(session) = &session {
+                            // the first frames may have arrived along with the handshake
+                            session.decrypt(&[], &mut pending)?;
+                        }
+                    }
                     let reader: TcpStream;
                     let writer: TcpStream;
 
@@ -231,11 +575,28 @@ impl NetChannel {
                         }
                     }
 
-                    Ok((NetSender { stream: writer }, NetReceiver { stream: reader }))
-                }
-                None => {
-                    panic!("No TCP stream established despite Mutex. This code should never get executed!");
+                    Ok((
+                        NetSender {
+                            stream: writer,
+                            traffic: Arc::new(Traffic::default()),
+                            #[cfg(feature = "tls")]
+                            session: session.clone(),
+                        },
+                        NetReceiver {
+                            stream: reader,
+                            pending,
+                            traffic: Arc::new(Traffic::default()),
+                            max_frame_size: MAX_FRAME_SIZE,
+                            #[cfg(feature = "tls")]
+                            session,
+                        },
+                    ))
                 }
+                // the server was shut down before a peer connected
+                None => Err(Error::new(
+                    ErrorKind::NotConnected,
+                    "No connection was established",
+                )),
             },
             Err(_) => Err(Error::new(ErrorKind::Other, "Mutex Poisoned")),
         }
@@ -248,48 +609,48 @@ impl NetChannel {
 /// It is here that we wait for incoming connections and pass them on to the
 /// requesting NetChannel instance.
 ///
-fn server(
-    listener: TcpListener,
-    sender: Sender<ExpectedConnection>,
-    receiver: Receiver<ExpectedConnection>,
-) {
-    use std::collections::HashMap;
-
-    let incoming: Arc<Mutex<HashMap<IpAddr, Sender<TcpStream>>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+fn server(listener: TcpListener, receiver: Receiver<ExpectedConnection>, stopped: Arc<AtomicBool>) {
+    let incoming: Arc<Mutex<Vec<ExpectedConnection>>> = Arc::new(Mutex::new(Vec::new()));
 
     let incoming2 = Arc::clone(&incoming);
     let incoming3 = Arc::clone(&incoming);
 
     thread::spawn(move || {
-        // It is important the only here the server_communicator is populated.
-        // Else the NetChannels will start doing stuff before the server has
-        // started and is listening for messages on its receiver.
-        unsafe {
-            server_communicator = Some(Mutex::new(sender));
-        }
-
         for (client, thread) in receiver.iter() {
             let mut incoming = incoming2.lock().unwrap();
-            incoming.insert(client, thread);
+            incoming.retain(|(expected, _)| *expected != client);
+            incoming.push((client, thread));
         }
     });
 
     loop {
-        match listener.accept() {
-            Ok((stream, socket)) => {
-                match incoming3.lock().unwrap().get_mut(&socket.ip()) {
-                    // If a NetChannel has requested this connection, pass it on
-                    Some(receiver) => {
-                        let _ = receiver.send(stream);
-                    }
-                    // ... else just close it.
-                    None => {
-                        let _ = stream.shutdown(Shutdown::Both);
+        let accepted = listener.accept();
+        if stopped.load(Ordering::SeqCst) {
+            // dropping the listener releases the port
+            info!("Server on {:?} shut down.", listener.local_addr());
+            return;
+        }
+        if let Ok((stream, socket)) = accepted {
+            // hostnames are resolved again, the peer may have a new address by now
+            let mut incoming = incoming3.lock().unwrap();
+            match incoming
+                .iter()
+                .position(|(expected, _)| expected.matches(socket.ip()))
+            {
+                // If a NetChannel has requested this connection, pass it on
+                Some(position) => {
+                    if let Err(rejected) = incoming[position].1.send(stream) {
+                        // the NetChannel was connected before, the remote has to
+                        // connect again once it is expected by a reconnect
+                        incoming.remove(position);
+                        let _ = rejected.0.shutdown(Shutdown::Both);
                     }
-                };
-            }
-            Err(_) => (),
+                }
+                // ... else just close it.
+                None => {
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+            };
         }
     }
 }
@@ -298,42 +659,200 @@ fn server(
 #[derive(Debug)]
 pub struct NetReceiver {
     stream: TcpStream,
+    // bytes of frames that were only partially read so far
+    pending: Vec<u8>,
+    traffic: Arc<Traffic>,
+    // larger frames are refused before they are buffered
+    max_frame_size: usize,
+    #[cfg(feature = "tls")]
+    session: Option<Arc<TlsSession>>,
 }
 
 // TODO: properly implement Read Trait.
 impl NetReceiver {
+    /// Block until at least one complete frame arrived and copy every complete frame that fits into *buffer*.
+    ///
+    /// Frames that do not fit are kept for the next read. Fails if the first frame alone is larger than *buffer*,
+    /// use [read_frames](#method.read_frames) to receive frames of any size.
     pub fn read<'a>(&mut self, buffer: &'a mut [u8]) -> std::io::Result<Vec<&'a [u8]>> {
-        let mut results: Vec<&[u8]> = Vec::with_capacity(5);
-        let size = self.stream.read(buffer)?;
+        let mut chunk = [0; READ_BUFFER_SIZE];
+        loop {
+            match self.next_frame_len()? {
+                Some(len) if len > buffer.len() => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Frame of {} bytes does not fit into a buffer of {} bytes",
+                            len,
+                            buffer.len()
+                        ),
+                    ));
+                }
+                Some(_) => break,
+                None => self.fill(&mut chunk)?,
+            }
+        }
+
+        let mut lens = Vec::new();
         let mut pointer = 0_usize;
+        while let Some(len) = self.next_frame_len()? {
+            if pointer + len > buffer.len() {
+                break;
+            }
+            buffer[pointer..pointer + len]
+                .copy_from_slice(&self.pending[PREFIX_SIZE..PREFIX_SIZE + len]);
+            self.traffic.record(len);
+            self.pending.drain(..PREFIX_SIZE + len);
+            lens.push(len);
+            pointer += len;
+        }
+
+        // the frames lie back to back at the start of the buffer
+        let mut rest: &'a [u8] = buffer;
+        let mut results: Vec<&[u8]> = Vec::with_capacity(lens.len());
+        for len in lens {
+            let (frame, tail) = rest.split_at(len);
+            results.push(frame);
+            rest = tail;
+        }
+        Ok(results)
+    }
+
+    /// Block until at least one complete frame arrived and return every complete frame.
+    ///
+    /// Unlike [read](#method.read), frames split across several reads from the TCP stream are reassembled.
+    pub fn read_frames(&mut self) -> std::io::Result<Vec<Vec<u8>>> {
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        loop {
+            let frames = self.take_frames()?;
+            if !frames.is_empty() {
+                return Ok(frames);
+            }
+            self.fill(&mut buffer)?;
+        }
+    }
+
+    /// Block until a single complete frame arrived and return it.
+    ///
+    /// Frames received beyond it are kept for the next read.
+    pub fn read_frame(&mut self) -> std::io::Result<Vec<u8>> {
+        let mut buffer = [0; READ_BUFFER_SIZE];
+        loop {
+            if let Some(frame) = self.take_frame()? {
+                return Ok(frame);
+            }
+            self.fill(&mut buffer)?;
+        }
+    }
 
+    /// Append the bytes of a single read from the TCP stream to the pending bytes.
+    fn fill(&mut self, buffer: &mut [u8]) -> std::io::Result<()> {
+        let size = self.stream.read(buffer)?;
         if size == 0 {
             return Err(Error::new(
                 std::io::ErrorKind::BrokenPipe,
                 "Read 0 bytes form TCP stream",
             ));
         }
+        #[cfg(feature = "tls")]
+        {
+            if let Some(session) = &self.session {
+                return session.decrypt(&buffer[..size], &mut self.pending);
+            }
+        }
+        self.pending.extend_from_slice(&buffer[..size]);
+        Ok(())
+    }
 
-        let mut size_bits: &[u8] = &buffer[pointer..pointer + 2];
-        let mut len = ((size_bits[0] as u16) * 256) | size_bits[1] as u16;
+    /// The length of the first pending frame, if it is complete.
+    fn next_frame_len(&self) -> std::io::Result<Option<usize>> {
+        frame_len(&self.pending, 0, self.max_frame_size)
+    }
+
+    /// Remove the first complete frame from the pending bytes.
+    fn take_frame(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+        let len = match self.next_frame_len()? {
+            Some(len) => len,
+            None => return Ok(None),
+        };
+        let frame = self.pending[PREFIX_SIZE..PREFIX_SIZE + len].to_vec();
+        self.traffic.record(len);
+        self.pending.drain(..PREFIX_SIZE + len);
+        Ok(Some(frame))
+    }
 
-        while len > 0 {
-            pointer = pointer + 2;
-            let obj = &buffer[pointer..pointer + len as usize];
-            results.push(obj);
-            pointer += len as usize;
-            size_bits = &buffer[pointer..pointer + 2];
-            len = ((size_bits[0] as u16) * 256) | size_bits[1] as u16;
+    /// Remove every complete frame from the pending bytes.
+    fn take_frames(&mut self) -> std::io::Result<Vec<Vec<u8>>> {
+        let mut frames = Vec::new();
+        let mut pointer = 0_usize;
+        while let Some(len) = frame_len(&self.pending, pointer, self.max_frame_size)? {
+            frames.push(self.pending[pointer + PREFIX_SIZE..pointer + PREFIX_SIZE + len].to_vec());
+            self.traffic.record(len);
+            pointer += PREFIX_SIZE + len;
         }
-        Ok(results)
+        self.pending.drain(..pointer);
+        Ok(frames)
+    }
+
+    /// Frames received by [read_frames](#method.read_frames) so far.
+    pub fn traffic(&self) -> Arc<Traffic> {
+        self.traffic.clone()
+    }
+
+    /// Count the frames received on *traffic*, e.g. to continue the counters of a dropped connection.
+    pub fn with_traffic(mut self, traffic: Arc<Traffic>) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// Refuse frames larger than *bytes* with an error before buffering them, at most [MAX_FRAME_SIZE](constant.MAX_FRAME_SIZE.html).
+    pub fn with_max_frame_size(mut self, bytes: usize) -> Self {
+        self.max_frame_size = bytes.min(MAX_FRAME_SIZE);
+        self
+    }
+}
+
+/// The length of the frame starting at *pointer* in *pending*, if all of its bytes arrived.
+///
+/// A length beyond *max_frame_size* means the stream is corrupted or the sender misbehaves.
+fn frame_len(
+    pending: &[u8],
+    pointer: usize,
+    max_frame_size: usize,
+) -> std::io::Result<Option<usize>> {
+    if pending.len() < pointer + PREFIX_SIZE {
+        return Ok(None);
+    }
+    let mut prefix = [0; PREFIX_SIZE];
+    prefix.copy_from_slice(&pending[pointer..pointer + PREFIX_SIZE]);
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > max_frame_size {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Frame of {} bytes exceeds the maximum of {} bytes",
+                len, max_frame_size
+            ),
+        ));
+    }
+    if pending.len() < pointer + PREFIX_SIZE + len {
+        return Ok(None);
     }
+    Ok(Some(len))
 }
 
 impl Clone for NetReceiver {
     fn clone(&self) -> Self {
         match self.stream.try_clone() {
             Ok(clone) => {
-                return NetReceiver { stream: clone };
+                return NetReceiver {
+                    stream: clone,
+                    pending: Vec::new(),
+                    traffic: self.traffic.clone(),
+                    max_frame_size: self.max_frame_size,
+                    #[cfg(feature = "tls")]
+                    session: self.session.clone(),
+                };
             }
             Err(error) => {
                 panic!("Cloning NetReceiver failed: {:?}", error);
@@ -346,20 +865,58 @@ impl Clone for NetReceiver {
 #[derive(Debug)]
 pub struct NetSender {
     stream: TcpStream,
+    traffic: Arc<Traffic>,
+    #[cfg(feature = "tls")]
+    session: Option<Arc<TlsSession>>,
 }
 
 // TODO: properly implement Write Trait.
 impl NetSender {
+    /// Write *bin_obj* as a single frame, it must not be larger than [MAX_FRAME_SIZE](constant.MAX_FRAME_SIZE.html).
     pub fn write(&mut self, bin_obj: &[u8]) -> std::io::Result<usize> {
-        let mut array = Vec::with_capacity(bin_obj.len() + 2);
-        let len = u16::to_be_bytes(bin_obj.len() as u16);
-        for val in &len {
-            array.push(*val);
+        if bin_obj.len() > MAX_FRAME_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Frame of {} bytes exceeds the maximum of {} bytes",
+                    bin_obj.len(),
+                    MAX_FRAME_SIZE
+                ),
+            ));
         }
-        for val in bin_obj {
-            array.push(*val);
+        let mut array = Vec::with_capacity(bin_obj.len() + PREFIX_SIZE);
+        array.extend_from_slice(&(bin_obj.len() as u32).to_be_bytes());
+        array.extend_from_slice(bin_obj);
+        // a partially written frame would corrupt the stream
+        #[cfg(feature = "tls")]
+        {
+            if let Some(session) = &self.session {
+                session.encrypt(&mut self.stream, &array[..])?;
+                self.traffic.record(bin_obj.len());
+                return Ok(array.len());
+            }
         }
-        self.stream.write(&array[..])
+        self.stream.write_all(&array[..])?;
+        self.traffic.record(bin_obj.len());
+        Ok(array.len())
+    }
+
+    /// Frames written so far.
+    pub fn traffic(&self) -> Arc<Traffic> {
+        self.traffic.clone()
+    }
+
+    /// Count the frames written on *traffic*, e.g. to continue the counters of a dropped connection.
+    pub fn with_traffic(mut self, traffic: Arc<Traffic>) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// Close the connection in both directions, for every clone of both halves.
+    ///
+    /// A [NetReceiver](struct.NetReceiver.html) blocking on the connection returns with an error.
+    pub fn shutdown(&self) -> std::io::Result<()> {
+        self.stream.shutdown(Shutdown::Both)
     }
 }
 
@@ -367,7 +924,12 @@ impl Clone for NetSender {
     fn clone(&self) -> Self {
         match self.stream.try_clone() {
             Ok(clone) => {
-                return NetSender { stream: clone };
+                return NetSender {
+                    stream: clone,
+                    traffic: self.traffic.clone(),
+                    #[cfg(feature = "tls")]
+                    session: self.session.clone(),
+                };
             }
             Err(error) => {
                 panic!("Cloning NetSender failed: {:?}", error);
@@ -375,3 +937,59 @@ impl Clone for NetSender {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame of *len* bytes with its length prefix.
+    fn frame(len: usize) -> Vec<u8> {
+        let mut frame = (len as u32).to_be_bytes().to_vec();
+        frame.resize(PREFIX_SIZE + len, 7);
+        frame
+    }
+
+    #[test]
+    fn waiting_for_a_peer_without_a_server_fails() {
+        // the higher address waits as server, but no server runs in this process
+        let local = SocketAddr::from(([127, 0, 0, 2], 42701));
+        let remote = Peer::Addr(SocketAddr::from(([127, 0, 0, 1], 42701)));
+        let e = NetChannel::reconnect(local, &remote, Duration::from_secs(60)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NotConnected);
+    }
+
+    #[test]
+    fn oversized_length_prefixes_are_refused() {
+        let prefix = ((MAX_FRAME_SIZE + 1) as u32).to_be_bytes();
+        let e = frame_len(&prefix, 0, MAX_FRAME_SIZE).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        // before the frame arrived, and below MAX_FRAME_SIZE for a receiver with a smaller limit
+        let e = frame_len(&frame(100)[..PREFIX_SIZE], 0, 99).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn zero_length_frames_are_complete() {
+        let mut pending = frame(0);
+        pending.extend(frame(3));
+        assert_eq!(frame_len(&pending, 0, MAX_FRAME_SIZE).unwrap(), Some(0));
+        assert_eq!(
+            frame_len(&pending, PREFIX_SIZE, MAX_FRAME_SIZE).unwrap(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn frames_at_the_limit_are_accepted_once_complete() {
+        let pending = frame(MAX_FRAME_SIZE);
+        assert_eq!(
+            frame_len(&pending, 0, MAX_FRAME_SIZE).unwrap(),
+            Some(MAX_FRAME_SIZE)
+        );
+        assert_eq!(
+            frame_len(&pending[..pending.len() - 1], 0, MAX_FRAME_SIZE).unwrap(),
+            None
+        );
+        assert_eq!(frame_len(&pending[..2], 0, MAX_FRAME_SIZE).unwrap(), None);
+    }
+}
@@ -0,0 +1,298 @@
+//!
+//! TLS for the NetChannel traffic, requires the `tls` feature
+//!
+//! Every machine presents a certificate signed by a shared certificate
+//! authority and verifies the certificate of the remote against it, no matter
+//! whether it connected as client or accepted the connection as server.
+//!
+//! Both halves of a NetChannel share the session. The receiver decrypts the
+//! records it read, the sender encrypts and writes its frames, so neither
+//! holds the session while blocking on the TCP stream.
+//!
+
+use crate::Peer;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{
+    ClientConfig, ClientConnection, Connection, RootCertStore, ServerConfig, ServerConnection,
+};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{BufReader, Error, ErrorKind};
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long the remote may take to complete the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Certificates and key of the local machine, and the authority the remotes are verified against.
+#[derive(Clone)]
+pub struct TlsConfig {
+    client: Arc<ClientConfig>,
+    server: Arc<ServerConfig>,
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TlsConfig")
+    }
+}
+
+impl TlsConfig {
+    ///
+    /// Load the PEM encoded certificate chain and private key of the local
+    /// machine, and the certificate of the authority that signed the
+    /// certificates of all machines
+    ///
+    /// A remote is verified by its configured address: a hostname needs a
+    /// matching DNS name, an address a matching IP address in the
+    /// certificate.
+    ///
+    pub fn from_pem_files<P: AsRef<Path>>(cert: P, key: P, ca: P) -> std::io::Result<TlsConfig> {
+        let certs = read_certs(cert.as_ref())?;
+        let key = read_key(key.as_ref())?;
+        let mut roots = RootCertStore::empty();
+        for ca_cert in read_certs(ca.as_ref())? {
+            roots.add(ca_cert).map_err(invalid_data)?;
+        }
+        let roots = Arc::new(roots);
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let client_verifier =
+            WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone())
+                .build()
+                .map_err(invalid_data)?;
+        let server = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(invalid_data)?
+            .with_client_cert_verifier(client_verifier)
+            .with_single_cert(certs.clone(), key.clone_key())
+            .map_err(invalid_data)?;
+        let client = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(invalid_data)?
+            .with_root_certificates(roots)
+            .with_client_auth_cert(certs, key)
+            .map_err(invalid_data)?;
+        Ok(TlsConfig {
+            client: Arc::new(client),
+            server: Arc::new(server),
+        })
+    }
+
+    ///
+    /// Run the handshake on a freshly established stream
+    ///
+    /// The machine that connected to *remote* is the client, the other one
+    /// the server. Certificates that are not accepted fail with
+    /// ErrorKind::InvalidData, dropped connections with other kinds.
+    ///
+    pub(crate) fn handshake(
+        &self,
+        stream: &mut TcpStream,
+        client_of: Option<&Peer>,
+    ) -> std::io::Result<TlsSession> {
+        let mut connection: Connection = match client_of {
+            Some(remote) => ClientConnection::new(self.client.clone(), server_name(remote)?)
+                .map_err(invalid_data)?
+                .into(),
+            None => ServerConnection::new(self.server.clone())
+                .map_err(invalid_data)?
+                .into(),
+        };
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        while connection.is_handshaking() {
+            connection.complete_io(stream)?;
+        }
+        stream.set_read_timeout(None)?;
+        Ok(TlsSession {
+            connection: Mutex::new(connection),
+            writing: Mutex::new(()),
+        })
+    }
+}
+
+/// The established session, shared by both halves of a NetChannel and their clones.
+pub(crate) struct TlsSession {
+    connection: Mutex<Connection>,
+    // keeps the records of concurrent senders in the order they were encrypted
+    writing: Mutex<()>,
+}
+
+impl fmt::Debug for TlsSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TlsSession")
+    }
+}
+
+impl TlsSession {
+    /// Decrypt the *records* read from the TCP stream, appending the plaintext to *plaintext*.
+    ///
+    /// Plaintext the session decrypted before, e.g. along with the handshake, is appended as well.
+    pub(crate) fn decrypt(
+        &self,
+        mut records: &[u8],
+        plaintext: &mut Vec<u8>,
+    ) -> std::io::Result<()> {
+        let mut connection = self.connection.lock().map_err(|_| poisoned())?;
+        let mut buffer = [0; 16 * 1024];
+        loop {
+            // drained after every read, the session buffers only a limited amount of plaintext
+            loop {
+                match connection.reader().read(&mut buffer) {
+                    Ok(0) => {
+                        return Err(Error::new(
+                            ErrorKind::BrokenPipe,
+                            "The remote closed the TLS session",
+                        ))
+                    }
+                    Ok(size) => plaintext.extend_from_slice(&buffer[..size]),
+                    // every complete record was consumed
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(e),
+                }
+            }
+            if records.is_empty() {
+                return Ok(());
+            }
+            connection.read_tls(&mut records)?;
+            connection.process_new_packets().map_err(invalid_data)?;
+        }
+    }
+
+    /// Encrypt *plaintext* and write the records to the TCP stream.
+    ///
+    /// Records the session queued on its own, e.g. key updates, are written along.
+    pub(crate) fn encrypt(&self, stream: &mut TcpStream, plaintext: &[u8]) -> std::io::Result<()> {
+        let _writing = self.writing.lock().map_err(|_| poisoned())?;
+        let mut records = Vec::with_capacity(plaintext.len() + 64);
+        {
+            let mut connection = self.connection.lock().map_err(|_| poisoned())?;
+            let mut plaintext = plaintext;
+            loop {
+                // the session accepts only a limited amount of plaintext before it was encrypted
+                let size = connection.writer().write(plaintext)?;
+                plaintext = &plaintext[size..];
+                while connection.wants_write() {
+                    connection.write_tls(&mut records)?;
+                }
+                if plaintext.is_empty() {
+                    break;
+                }
+            }
+        }
+        stream.write_all(&records)
+    }
+}
+
+/// The name the certificate of *remote* has to be issued for.
+fn server_name(remote: &Peer) -> std::io::Result<ServerName<'static>> {
+    match remote {
+        Peer::Addr(addr) => Ok(ServerName::IpAddress(addr.ip().into())),
+        Peer::Host(host, _) => ServerName::try_from(host.clone()).map_err(invalid_data),
+    }
+}
+
+fn read_certs(path: &Path) -> std::io::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<std::io::Result<Vec<_>>>()?;
+    if certs.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("No certificate found in {:?}", path),
+        ));
+    }
+    Ok(certs)
+}
+
+fn read_key(path: &Path) -> std::io::Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    match rustls_pemfile::private_key(&mut reader)? {
+        Some(key) => Ok(key),
+        None => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("No private key found in {:?}", path),
+        )),
+    }
+}
+
+fn invalid_data<E: fmt::Debug>(e: E) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("{:?}", e))
+}
+
+fn poisoned() -> Error {
+    Error::other("Mutex Poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NetChannel, NetReceiver, NetSender};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::thread;
+
+    type Halves = std::io::Result<(NetSender, NetReceiver)>;
+
+    /// The test certificates, signed by ca.pem unless named untrusted and valid for 100 years.
+    fn certs() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/certs")
+    }
+
+    /// The TLS configuration of a machine presenting the certificate *name*, trusting the test authority.
+    fn config(name: &str) -> TlsConfig {
+        TlsConfig::from_pem_files(
+            certs().join(format!("{}.pem", name)),
+            certs().join(format!("{}-key.pem", name)),
+            certs().join("ca.pem"),
+        )
+        .unwrap()
+    }
+
+    /// Connect a client presenting *client* to a server presenting *server* on 127.0.0.1 and split both NetChannels.
+    fn connect(server: TlsConfig, client: TlsConfig) -> (Halves, Halves) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut channel = NetChannel::unconnected(None).with_tls(server);
+            *channel.stream.lock().unwrap() = Some(stream);
+            channel.split()
+        });
+        let mut channel = NetChannel::unconnected(Some(&Peer::Addr(addr))).with_tls(client);
+        *channel.stream.lock().unwrap() = Some(TcpStream::connect(addr).unwrap());
+        let connected = channel.split();
+        (accepted.join().unwrap(), connected)
+    }
+
+    #[test]
+    fn frames_round_trip_through_the_session() {
+        let (server, client) = connect(config("machine"), config("machine"));
+        let (mut server_sender, mut server_receiver) = server.unwrap();
+        let (mut client_sender, mut client_receiver) = client.unwrap();
+
+        client_sender.write(b"hello").unwrap();
+        assert_eq!(server_receiver.read_frame().unwrap(), b"hello");
+        // spans many TLS records
+        let large: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+        server_sender.write(&large).unwrap();
+        server_sender.write(b"after").unwrap();
+        assert_eq!(client_receiver.read_frame().unwrap(), large);
+        assert_eq!(client_receiver.read_frame().unwrap(), b"after");
+    }
+
+    #[test]
+    fn foreign_certificates_are_rejected() {
+        // issued for 127.0.0.2, the client connected to 127.0.0.1
+        let (_server, client) = connect(config("misnamed"), config("machine"));
+        assert_eq!(client.unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // signed by another authority
+        let (server, _client) = connect(config("machine"), config("untrusted"));
+        assert_eq!(server.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -24,16 +24,14 @@ enum ParentDirection {
 #[derive(Debug)]
 pub(crate) struct WorkerActor {
     env: Option<Environment>,
-    self_ref: Option<ActorRef>,
     parent_info: (ParentDirection, Option<ActorRef>),
     partial_result: Vec<i32>,
     children: Children,
 }
 
 impl Actor for WorkerActor {
-    fn on_start(&mut self, env: Environment, self_ref: ActorRef) {
+    fn on_start(&mut self, env: Environment, _self_ref: ActorRef) {
         self.env = Some(env);
-        self.self_ref = Some(self_ref);
     }
 }
 
@@ -41,7 +39,6 @@ impl WorkerActor {
     pub fn new() -> Self {
         WorkerActor {
             env: None,
-            self_ref: None,
             parent_info: (ParentDirection::None, Option::None),
             partial_result: Vec::with_capacity(1),
             children: Children::None,
@@ -73,10 +70,7 @@ impl WorkerActor {
                 // left worker
                 match env.spawn("WorkerActor") {
                     Ok(actor_ref) => {
-                        actor_ref.send_message(IAmYourFather(
-                            ParentDirection::Left,
-                            self.self_ref.clone().unwrap().clone_id(),
-                        ));
+                        actor_ref.send_message(IAmYourFather(ParentDirection::Left));
                         actor_ref.send_message(DoWorkMessage {
                             workload: left_work,
                         });
@@ -99,10 +93,7 @@ impl WorkerActor {
                 // right worker
                 match env.spawn("WorkerActor") {
                     Ok(actor_ref) => {
-                        actor_ref.send_message(IAmYourFather(
-                            ParentDirection::Right,
-                            self.self_ref.clone().unwrap().clone_id(),
-                        ));
+                        actor_ref.send_message(IAmYourFather(ParentDirection::Right));
                         actor_ref.send_message(DoWorkMessage {
                             workload: right_work,
                         });
@@ -146,21 +137,16 @@ pub(crate) struct ResultMessage(ParentDirection, i32);
 
 /// tells an actor that the sender is the parent actor
 #[derive(Serialize, Deserialize, Debug)]
-pub(crate) struct IAmYourFather(ParentDirection, ActorId);
+pub(crate) struct IAmYourFather(ParentDirection);
 
 fn handle_father_message(actor: &mut WorkerActor, msg: &IAmYourFather) {
-    let IAmYourFather(dir, actor_ref) = msg;
-    match &actor.env {
-        Some(env) => match env.to_actor_ref(actor_ref.clone()) {
-            Ok(actor_ref) => {
-                actor.parent_info = (dir.clone(), Some(actor_ref));
-            }
-            Err(e) => {
-                panic!(e);
-            }
-        },
+    let IAmYourFather(dir) = msg;
+    match Context::sender() {
+        Some(parent_ref) => {
+            actor.parent_info = (dir.clone(), Some(parent_ref));
+        }
         None => {
-            panic!("Empty: actor's environment field");
+            panic!("IAmYourFather was not sent by an Actor");
         }
     }
 }